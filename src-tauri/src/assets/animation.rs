@@ -0,0 +1,77 @@
+use super::error::{AssetError, AssetResult};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// File extension of engine-native animation clips.
+pub const ANIMATION_EXTENSION: &str = "anim";
+
+/// Binds the joints of skinned meshes to nodes of a model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Skin {
+    pub name: String,
+    /// Node indices, addressed by the `joints` of skinned vertices.
+    pub joints: Vec<usize>,
+    /// Column-major matrices from mesh space to each joint's bind pose.
+    pub inverse_bind_matrices: Vec<[[f32; 4]; 4]>,
+    /// Common root of the joint hierarchy, if the source names one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skeleton: Option<usize>,
+}
+
+/// Keyframed animation of the nodes of one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationClip {
+    pub name: String,
+    /// Length in seconds, the time of the last key of any channel.
+    pub duration: f32,
+    pub channels: Vec<AnimationChannel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationChannel {
+    /// Index of the animated node.
+    pub node: usize,
+    pub property: AnimatedProperty,
+    pub interpolation: Interpolation,
+    /// Key times in seconds, increasing.
+    pub times: Vec<f32>,
+    /// Key values, flattened: three floats per key for translation and
+    /// scale, an `[x, y, z, w]` quaternion for rotation and one float per
+    /// morph target for weights. Cubic splines store an in-tangent, the
+    /// value and an out-tangent for every key.
+    pub values: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnimatedProperty {
+    Translation,
+    Rotation,
+    Scale,
+    /// Morph target weights, one per target.
+    Weights,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Interpolation {
+    Step,
+    Linear,
+    CubicSpline,
+}
+
+impl AnimationClip {
+    pub fn save(&self, path: &Path) -> AssetResult<()> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        fs::write(path, text).map_err(AssetError::io(path))
+    }
+
+    pub fn load(path: &Path) -> AssetResult<Self> {
+        let text = fs::read_to_string(path).map_err(AssetError::io(path))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
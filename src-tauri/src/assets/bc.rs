@@ -0,0 +1,404 @@
+//! Block compression encoders for BC1, BC3, BC5 and BC7.
+//!
+//! Endpoints are fit along the principal axis of each 4x4 block and every
+//! pixel takes the nearest palette entry. That is far from the best quality
+//! offline compressors reach, but it is fast and fully deterministic, so the
+//! same image always compresses to the same bytes. BC7 only uses mode 6, a
+//! single RGBA endpoint pair with 16 levels.
+
+use super::texture::TextureFormat;
+
+type Block = [[u8; 4]; 16];
+
+/// Compresses an RGBA8 image of `width` by `height` pixels. Blocks that
+/// extend past the edges repeat the last row and column.
+///
+/// # Panics
+///
+/// If `format` is not a block compressed format.
+pub fn compress(format: TextureFormat, width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let mut output = Vec::with_capacity(format.level_size(width as u32, height as u32));
+
+    for block_y in (0..height).step_by(4) {
+        for block_x in (0..width).step_by(4) {
+            let mut block = [[0; 4]; 16];
+            for (i, pixel) in block.iter_mut().enumerate() {
+                let x = (block_x + i % 4).min(width - 1);
+                let y = (block_y + i / 4).min(height - 1);
+                let offset = (y * width + x) * 4;
+                pixel.copy_from_slice(&rgba[offset..offset + 4]);
+            }
+
+            match format {
+                TextureFormat::Bc1 => output.extend_from_slice(&encode_bc1(&block, true)),
+                TextureFormat::Bc3 => {
+                    output.extend_from_slice(&encode_bc4(&block.map(|pixel| pixel[3])));
+                    output.extend_from_slice(&encode_bc1(&block, false));
+                }
+                TextureFormat::Bc5 => {
+                    output.extend_from_slice(&encode_bc4(&block.map(|pixel| pixel[0])));
+                    output.extend_from_slice(&encode_bc4(&block.map(|pixel| pixel[1])));
+                }
+                TextureFormat::Bc7 => output.extend_from_slice(&encode_bc7_mode6(&block)),
+                TextureFormat::Rgba8 | TextureFormat::Rgba16Float => {
+                    panic!("{:?} is not a block compressed format", format)
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Unit direction of largest variance of `points` around `mean`, by power
+/// iteration on the covariance matrix.
+fn principal_axis<const N: usize>(points: &[[f32; N]], mean: [f32; N]) -> [f32; N] {
+    let mut covariance = [[0.0f32; N]; N];
+    for point in points {
+        for i in 0..N {
+            for j in 0..N {
+                covariance[i][j] += (point[i] - mean[i]) * (point[j] - mean[j]);
+            }
+        }
+    }
+
+    let mut axis = [1.0f32; N];
+    for _ in 0..8 {
+        let mut next = [0.0f32; N];
+        for i in 0..N {
+            for j in 0..N {
+                next[i] += covariance[i][j] * axis[j];
+            }
+        }
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length <= f32::EPSILON {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+    axis
+}
+
+/// The extremes of `points` projected on their principal axis.
+fn fit_endpoints<const N: usize>(points: &[[f32; N]]) -> ([f32; N], [f32; N]) {
+    let mut mean = [0.0f32; N];
+    for point in points {
+        for i in 0..N {
+            mean[i] += point[i] / points.len() as f32;
+        }
+    }
+
+    let axis = principal_axis(points, mean);
+    let project = |point: &[f32; N]| (0..N).map(|i| (point[i] - mean[i]) * axis[i]).sum::<f32>();
+    let (min, max) = points
+        .iter()
+        .map(project)
+        .fold((f32::MAX, f32::MIN), |(min, max), t| {
+            (min.min(t), max.max(t))
+        });
+
+    let endpoint = |t: f32| {
+        let mut endpoint = [0.0f32; N];
+        for i in 0..N {
+            endpoint[i] = (mean[i] + axis[i] * t).clamp(0.0, 255.0);
+        }
+        endpoint
+    };
+    (endpoint(max), endpoint(min))
+}
+
+fn distance<const N: usize>(a: [i32; N], b: [i32; N]) -> i32 {
+    (0..N).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+/// Index of the palette entry nearest to `value`; the first one on ties.
+fn nearest<const N: usize>(palette: &[[i32; N]], value: [i32; N]) -> usize {
+    let mut best = 0;
+    for (i, &entry) in palette.iter().enumerate().skip(1) {
+        if distance(entry, value) < distance(palette[best], value) {
+            best = i;
+        }
+    }
+    best
+}
+
+fn to_565(color: [f32; 3]) -> u16 {
+    let r = (color[0] * 31.0 / 255.0 + 0.5) as u16;
+    let g = (color[1] * 63.0 / 255.0 + 0.5) as u16;
+    let b = (color[2] * 31.0 / 255.0 + 0.5) as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn from_565(color: u16) -> [i32; 3] {
+    let (r, g, b) = ((color >> 11) & 31, (color >> 5) & 63, color & 31);
+    [
+        ((r << 3) | (r >> 2)) as i32,
+        ((g << 2) | (g >> 4)) as i32,
+        ((b << 3) | (b >> 2)) as i32,
+    ]
+}
+
+/// Encodes the color of a block. With `punch_through` pixels with alpha
+/// below 128 become transparent, which BC1 supports in its three color mode;
+/// BC3 color blocks are always decoded in four color mode.
+fn encode_bc1(block: &Block, punch_through: bool) -> [u8; 8] {
+    let transparent = |pixel: &[u8; 4]| punch_through && pixel[3] < 128;
+    let opaque: Vec<[f32; 3]> = block
+        .iter()
+        .filter(|pixel| !transparent(pixel))
+        .map(|pixel| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32])
+        .collect();
+    let three_color = opaque.len() < 16;
+
+    let (mut color0, mut color1) = if opaque.is_empty() {
+        (0, 0)
+    } else {
+        let (max, min) = fit_endpoints(&opaque);
+        (to_565(max), to_565(min))
+    };
+    // The order of the endpoints selects the mode
+    if (three_color && color0 > color1) || (!three_color && color0 < color1) {
+        std::mem::swap(&mut color0, &mut color1);
+    }
+
+    let (c0, c1) = (from_565(color0), from_565(color1));
+    let palette: Vec<[i32; 3]> = if three_color {
+        vec![c0, c1, [0, 1, 2].map(|i| (c0[i] + c1[i]) / 2)]
+    } else {
+        vec![
+            c0,
+            c1,
+            [0, 1, 2].map(|i| (2 * c0[i] + c1[i]) / 3),
+            [0, 1, 2].map(|i| (c0[i] + 2 * c1[i]) / 3),
+        ]
+    };
+
+    let mut indices = 0u32;
+    for (i, pixel) in block.iter().enumerate() {
+        let index = if transparent(pixel) {
+            3
+        } else {
+            nearest(
+                &palette,
+                [pixel[0] as i32, pixel[1] as i32, pixel[2] as i32],
+            )
+        };
+        indices |= (index as u32) << (2 * i);
+    }
+
+    let mut output = [0; 8];
+    output[0..2].copy_from_slice(&color0.to_le_bytes());
+    output[2..4].copy_from_slice(&color1.to_le_bytes());
+    output[4..8].copy_from_slice(&indices.to_le_bytes());
+    output
+}
+
+/// Encodes one channel of a block with eight interpolated levels.
+fn encode_bc4(values: &[u8; 16]) -> [u8; 8] {
+    let max = *values.iter().max().unwrap_or(&0);
+    let min = *values.iter().min().unwrap_or(&0);
+
+    let (a0, a1) = (max as i32, min as i32);
+    let mut palette = [[0i32; 1]; 8];
+    palette[0] = [a0];
+    palette[1] = [a1];
+    for (i, entry) in palette.iter_mut().enumerate().skip(2) {
+        *entry = [((8 - i as i32) * a0 + (i as i32 - 1) * a1) / 7];
+    }
+
+    let mut indices = 0u64;
+    if max > min {
+        for (i, &value) in values.iter().enumerate() {
+            indices |= (nearest(&palette, [value as i32]) as u64) << (3 * i);
+        }
+    }
+
+    let mut output = [0; 8];
+    output[0] = max;
+    output[1] = min;
+    output[2..8].copy_from_slice(&indices.to_le_bytes()[..6]);
+    output
+}
+
+const BC7_WEIGHTS: [i32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Quantizes an endpoint to 7 bits per channel plus a shared p-bit,
+/// picking the p-bit that reconstructs it best.
+fn quantize_bc7(endpoint: [f32; 4]) -> ([u8; 4], u8) {
+    let mut best = ([0; 4], 0, i32::MAX);
+    for p in 0..2u8 {
+        let quantized = endpoint.map(|v| ((v - p as f32) / 2.0).round().clamp(0.0, 127.0) as u8);
+        let restored = quantized.map(|q| ((q << 1) | p) as i32);
+        let error = distance(restored, endpoint.map(|v| v.round() as i32));
+        if error < best.2 {
+            best = (quantized, p, error);
+        }
+    }
+    (best.0, best.1)
+}
+
+/// Appends bits to a 128-bit block, least significant first.
+struct BitWriter {
+    bits: u128,
+    position: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.bits |= ((value as u128) & ((1 << count) - 1)) << self.position;
+        self.position += count;
+    }
+}
+
+fn encode_bc7_mode6(block: &Block) -> [u8; 16] {
+    let points: Vec<[f32; 4]> = block.iter().map(|pixel| pixel.map(|v| v as f32)).collect();
+    let (max, min) = fit_endpoints(&points);
+    let (mut e0, mut p0) = quantize_bc7(min);
+    let (mut e1, mut p1) = quantize_bc7(max);
+
+    let restore = |e: [u8; 4], p: u8| e.map(|q| ((q << 1) | p) as i32);
+    let (c0, c1) = (restore(e0, p0), restore(e1, p1));
+    let palette: Vec<[i32; 4]> = BC7_WEIGHTS
+        .iter()
+        .map(|&w| [0, 1, 2, 3].map(|i| ((64 - w) * c0[i] + w * c1[i] + 32) >> 6))
+        .collect();
+    let mut indices: Vec<u32> = block
+        .iter()
+        .map(|pixel| nearest(&palette, pixel.map(|v| v as i32)) as u32)
+        .collect();
+
+    // The first index is stored with one bit less, so its top bit must be 0
+    if indices[0] >= 8 {
+        std::mem::swap(&mut e0, &mut e1);
+        std::mem::swap(&mut p0, &mut p1);
+        for index in &mut indices {
+            *index = 15 - *index;
+        }
+    }
+
+    let mut writer = BitWriter {
+        bits: 0,
+        position: 0,
+    };
+    writer.write(1 << 6, 7);
+    for channel in 0..4 {
+        writer.write(e0[channel] as u32, 7);
+        writer.write(e1[channel] as u32, 7);
+    }
+    writer.write(p0 as u32, 1);
+    writer.write(p1 as u32, 1);
+    for (i, &index) in indices.iter().enumerate() {
+        writer.write(index, if i == 0 { 3 } else { 4 });
+    }
+    debug_assert_eq!(writer.position, 128);
+    writer.bits.to_le_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::texture::content_hash;
+
+    const FORMATS: [TextureFormat; 4] = [
+        TextureFormat::Bc1,
+        TextureFormat::Bc3,
+        TextureFormat::Bc5,
+        TextureFormat::Bc7,
+    ];
+
+    /// Gradients with a checker in blue and soft alpha, so every block has
+    /// its own endpoints.
+    fn pattern(width: u32, height: u32) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| {
+                (0..width).flat_map(move |x| {
+                    [
+                        (x * 255 / (width - 1)) as u8,
+                        (y * 255 / (height - 1)) as u8,
+                        if (x / 2 + y / 2) % 2 == 0 { 32 } else { 224 },
+                        255 - (x * y * 255 / ((width - 1) * (height - 1))) as u8,
+                    ]
+                })
+            })
+            .collect()
+    }
+
+    fn solid(width: u32, height: u32, pixel: [u8; 4]) -> Vec<u8> {
+        pixel.repeat((width * height) as usize)
+    }
+
+    #[test]
+    fn compresses_to_the_recorded_bytes() {
+        let rgba = pattern(16, 16);
+        let hashes: Vec<_> = FORMATS
+            .iter()
+            .map(|&format| content_hash(&compress(format, 16, 16, &rgba)))
+            .collect();
+        assert_eq!(
+            hashes,
+            [
+                "d20de27889ec710e4d220cc69ea212c121f900a29c8af82b3ff172f52348ebf0",
+                "e7147e64a875c6497610ef99fa99115d7e21ccc7f6c84085a4daf50557ea4fbc",
+                "2f19cce4f74eb4bf80c30a88ac1bfa66a0047ff0f709e80d82a94a5c5019979e",
+                "9dbf8e4e3ba38f7fc02a3b9b6e398ec4e5d4886daafa996bd097a5bd7e95b259",
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_one_block_per_4x4_pixels() {
+        let rgba = pattern(12, 8);
+        for format in FORMATS {
+            assert_eq!(
+                compress(format, 12, 8, &rgba).len(),
+                format.level_size(12, 8),
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn repeats_the_edges_of_partial_blocks() {
+        let small = pattern(2, 2);
+        let padded: Vec<u8> = (0..4)
+            .flat_map(|y: usize| {
+                let small = &small;
+                (0..4).flat_map(move |x: usize| {
+                    let offset = (y.min(1) * 2 + x.min(1)) * 4;
+                    small[offset..offset + 4].to_vec()
+                })
+            })
+            .collect();
+        for format in FORMATS {
+            assert_eq!(
+                compress(format, 2, 2, &small),
+                compress(format, 4, 4, &padded),
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn encodes_solid_blocks_exactly() {
+        let red = compress(TextureFormat::Bc1, 4, 4, &solid(4, 4, [255, 0, 0, 255]));
+        assert_eq!(red, [0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0]);
+
+        // Transparent pixels use the fourth index of the three color mode
+        let clear = compress(TextureFormat::Bc1, 4, 4, &solid(4, 4, [255, 0, 0, 0]));
+        assert_eq!(clear, [0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+
+        let normal = compress(TextureFormat::Bc5, 4, 4, &solid(4, 4, [100, 200, 0, 255]));
+        assert_eq!(
+            normal,
+            [100, 100, 0, 0, 0, 0, 0, 0, 200, 200, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not a block compressed format")]
+    fn refuses_uncompressed_formats() {
+        compress(TextureFormat::Rgba8, 4, 4, &solid(4, 4, [0; 4]));
+    }
+}
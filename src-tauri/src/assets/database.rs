@@ -0,0 +1,848 @@
+//! Project asset database.
+//!
+//! Every image, model source and engine asset file under the project root
+//! is an asset; scripts, documents and editor data are not. An asset's GUID
+//! lives in a JSON sidecar named after it, `<file>.meta`, together with its
+//! import settings, so the GUID follows the file through renames and
+//! version control as long as the sidecar moves with it. References between assets are read from
+//! the asset files themselves; they are stored as project relative paths so
+//! a reference to a file that does not exist yet resolves once it appears.
+
+use super::{
+    animation::ANIMATION_EXTENSION,
+    error::{AssetError, AssetResult},
+    import::{
+        texture::{TextureImportSettings, TEXTURE_EXTENSIONS},
+        MODEL_EXTENSIONS,
+    },
+    ktx2::KTX2_EXTENSION,
+    material::{MaterialAsset, MATERIAL_EXTENSION},
+    mesh::MESH_EXTENSION,
+    model::{ModelAsset, MODEL_EXTENSION},
+    texture::TEXTURE_EXTENSION,
+};
+use crate::scene::{file::SCENE_EXTENSION, Component, SceneFile};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fs,
+    path::{Component as PathComponent, Path, PathBuf},
+};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+/// Extension of the sidecar files holding asset GUIDs.
+pub const META_EXTENSION: &str = "meta";
+
+const META_VERSION: u32 = 1;
+
+/// Directories never scanned, besides hidden ones.
+const IGNORED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// Extensions of temporary files, e.g. from atomic scene saves.
+const IGNORED_EXTENSIONS: &[&str] = &["tmp"];
+
+/// Contents of a `.meta` sidecar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetMeta {
+    pub version: u32,
+    pub guid: Uuid,
+    /// How the asset is imported, for source files such as images.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import: Option<ImportSettings>,
+    /// Where the asset was last imported to, relative to the project root.
+    /// Changes to the asset reimport it there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+}
+
+impl AssetMeta {
+    pub fn new(import: Option<ImportSettings>) -> Self {
+        Self {
+            version: META_VERSION,
+            guid: Uuid::new_v4(),
+            import,
+            output: None,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> AssetResult<()> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        fs::write(path, text).map_err(AssetError::io(path))
+    }
+
+    pub fn load(path: &Path) -> AssetResult<Self> {
+        let text = fs::read_to_string(path).map_err(AssetError::io(path))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+/// Import settings kept in the sidecar of a source file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "importer", rename_all = "camelCase")]
+pub enum ImportSettings {
+    Texture(TextureImportSettings),
+    Model,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetKind {
+    /// A source image, imported as a `Texture`.
+    Image,
+    /// A glTF or GLB file, imported as a `Model`.
+    ModelSource,
+    Model,
+    Mesh,
+    Material,
+    Animation,
+    Texture,
+    Scene,
+}
+
+impl AssetKind {
+    /// Kind of the asset at `path`, or `None` if files like it are not
+    /// assets.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let extension = extension.as_str();
+
+        let kind = if TEXTURE_EXTENSIONS.contains(&extension) {
+            AssetKind::Image
+        } else if MODEL_EXTENSIONS.contains(&extension) {
+            AssetKind::ModelSource
+        } else {
+            match extension {
+                MODEL_EXTENSION => AssetKind::Model,
+                MESH_EXTENSION => AssetKind::Mesh,
+                MATERIAL_EXTENSION => AssetKind::Material,
+                ANIMATION_EXTENSION => AssetKind::Animation,
+                TEXTURE_EXTENSION | KTX2_EXTENSION => AssetKind::Texture,
+                SCENE_EXTENSION => AssetKind::Scene,
+                _ => return None,
+            }
+        };
+        Some(kind)
+    }
+
+    /// Settings a new sidecar starts with.
+    fn default_import(self) -> Option<ImportSettings> {
+        match self {
+            AssetKind::Image => Some(ImportSettings::Texture(Default::default())),
+            AssetKind::ModelSource => Some(ImportSettings::Model),
+            _ => None,
+        }
+    }
+}
+
+/// An asset known to the database.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetRecord {
+    pub guid: Uuid,
+    /// Path relative to the project root.
+    pub path: PathBuf,
+    pub kind: AssetKind,
+    /// Project relative paths of the assets this one uses, whether they
+    /// exist or not.
+    pub references: Vec<PathBuf>,
+}
+
+/// Assets that would break if an asset was deleted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteImpact {
+    /// Assets referencing the deleted one.
+    pub direct: Vec<AssetRecord>,
+    /// Assets referencing those, transitively.
+    pub indirect: Vec<AssetRecord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetChange {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// An asset that changed on disk.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetChanged {
+    pub guid: Uuid,
+    /// Path relative to the project root.
+    pub path: PathBuf,
+    pub kind: AssetKind,
+    pub change: AssetChange,
+}
+
+impl AssetChanged {
+    fn new(record: &AssetRecord, change: AssetChange) -> Self {
+        Self {
+            guid: record.guid,
+            path: record.path.clone(),
+            kind: record.kind,
+            change,
+        }
+    }
+}
+
+/// The assets of a project, indexed by GUID and by path.
+#[derive(Debug)]
+pub struct AssetDatabase {
+    root: PathBuf,
+    assets: HashMap<Uuid, AssetRecord>,
+    paths: HashMap<PathBuf, Uuid>,
+}
+
+impl AssetDatabase {
+    /// Scans the project at `root`, writing a sidecar for every asset that
+    /// has none yet.
+    pub fn open(root: &Path) -> AssetResult<Self> {
+        let root = root.canonicalize().map_err(AssetError::io(root))?;
+        if !root.is_dir() {
+            return Err(AssetError::Invalid(format!(
+                "{} is not a directory",
+                root.display()
+            )));
+        }
+
+        let mut database = Self {
+            root,
+            assets: HashMap::new(),
+            paths: HashMap::new(),
+        };
+        database.scan()?;
+        Ok(database)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Every asset, sorted by path.
+    pub fn assets(&self) -> Vec<&AssetRecord> {
+        let mut assets: Vec<_> = self.assets.values().collect();
+        assets.sort_by(|a, b| a.path.cmp(&b.path));
+        assets
+    }
+
+    pub fn get(&self, guid: Uuid) -> Option<&AssetRecord> {
+        self.assets.get(&guid)
+    }
+
+    /// Looks up an asset by absolute or project relative path.
+    pub fn find_by_path(&self, path: &Path) -> Option<&AssetRecord> {
+        let path = self.relative(path)?;
+        self.paths.get(&path).and_then(|guid| self.assets.get(guid))
+    }
+
+    /// Absolute path of a project relative one.
+    pub fn absolute(&self, path: &Path) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// Project relative form of `path`, or `None` if it is outside the
+    /// project.
+    pub fn relative(&self, path: &Path) -> Option<PathBuf> {
+        if path.is_relative() {
+            return Some(normalize(path));
+        }
+        let path = normalize(path);
+        if let Ok(relative) = path.strip_prefix(&self.root) {
+            return Some(relative.to_path_buf());
+        }
+        // The root is canonical, the path may not be, e.g. on macOS where
+        // temporary directories live behind a symlink
+        let (parent, name) = (path.parent()?, path.file_name()?);
+        let parent = parent.canonicalize().ok()?;
+        parent
+            .strip_prefix(&self.root)
+            .ok()
+            .map(|relative| relative.join(name))
+    }
+
+    /// How asset files store `path`: relative to the project root if it is
+    /// inside the project, otherwise as given.
+    pub fn stored_path(&self, path: &Path) -> PathBuf {
+        self.relative(path)
+            .filter(|relative| !relative.starts_with(".."))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Contents of the sidecar of the asset at `path`.
+    pub fn meta(&self, path: &Path) -> Option<AssetMeta> {
+        let path = self.absolute(&self.relative(path)?);
+        AssetMeta::load(&meta_path(&path)).ok()
+    }
+
+    /// Stores how the asset at `source` was imported and where to in its
+    /// sidecar, then registers `output`. The sidecar is only rewritten when
+    /// this changes it.
+    pub fn record_import(
+        &mut self,
+        source: &Path,
+        import: ImportSettings,
+        output: &Path,
+    ) -> AssetResult<()> {
+        let path = self
+            .find_by_path(source)
+            .map(|record| self.absolute(&record.path))
+            .ok_or_else(|| AssetError::UnknownAsset(source.display().to_string()))?;
+        let meta_path = meta_path(&path);
+
+        let mut meta = AssetMeta::load(&meta_path)?;
+        let output_path = self.relative(output);
+        if meta.import.as_ref() != Some(&import) || meta.output != output_path {
+            meta.import = Some(import);
+            meta.output = output_path;
+            meta.save(&meta_path)?;
+        }
+        self.refresh(output)?;
+        Ok(())
+    }
+
+    /// Brings the assets at the absolute `paths` up to date after changes on
+    /// disk, returning what changed. A directory stands for every file in it.
+    /// Sidecars are ignored; their asset changes along with them.
+    pub fn apply(&mut self, paths: &[PathBuf]) -> Vec<AssetChanged> {
+        let mut changes = Vec::new();
+        let (present, gone): (Vec<_>, Vec<_>) = paths.iter().partition(|path| path.exists());
+
+        // Removals first, so a renamed file and its sidecar keep their GUID
+        for path in gone {
+            let Some(relative) = self.relative(path) else {
+                continue;
+            };
+            let mut removed: Vec<_> = self
+                .assets
+                .values()
+                .filter(|record| record.path.starts_with(&relative))
+                .cloned()
+                .collect();
+            removed.sort_by(|a, b| a.path.cmp(&b.path));
+            for record in removed {
+                self.paths.remove(&record.path);
+                self.assets.remove(&record.guid);
+                changes.push(AssetChanged::new(&record, AssetChange::Removed));
+            }
+        }
+
+        // A new directory is reported along with the files in it
+        let mut files = BTreeSet::new();
+        for path in present {
+            if path.is_dir() {
+                files.extend(
+                    WalkDir::new(path)
+                        .sort_by_file_name()
+                        .into_iter()
+                        .filter_entry(|entry| !is_hidden_or_ignored_dir(entry))
+                        .filter_map(Result::ok)
+                        .filter(|entry| entry.file_type().is_file())
+                        .map(|entry| entry.into_path()),
+                );
+            } else {
+                files.insert(path.clone());
+            }
+        }
+
+        for path in files {
+            let Some(relative) = self.relative(&path) else {
+                continue;
+            };
+            if !is_asset(&relative) {
+                continue;
+            }
+            let change = if self.paths.contains_key(&relative) {
+                AssetChange::Modified
+            } else {
+                AssetChange::Created
+            };
+            match self.refresh(&path) {
+                Ok(Some(guid)) => changes.push(AssetChanged::new(&self.assets[&guid], change)),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to update asset {}: {}", relative.display(), e),
+            }
+        }
+        changes
+    }
+
+    /// Brings the asset at `path` up to date after it was created, changed
+    /// or deleted. Returns its GUID, or `None` if it is gone or not an
+    /// asset.
+    pub fn refresh(&mut self, path: &Path) -> AssetResult<Option<Uuid>> {
+        let Some(relative) = self.relative(path) else {
+            return Ok(None);
+        };
+        let absolute = self.absolute(&relative);
+
+        if !absolute.is_file() || !is_asset(&relative) {
+            if let Some(guid) = self.paths.remove(&relative) {
+                debug!("Asset {} removed", relative.display());
+                self.assets.remove(&guid);
+            }
+            return Ok(None);
+        }
+
+        let guid = match self.paths.get(&relative) {
+            Some(&guid) => guid,
+            None => self.register(&relative)?,
+        };
+        let references = read_references(&absolute, self.assets[&guid].kind)
+            .into_iter()
+            .filter_map(|reference| self.resolve(&relative, &reference))
+            .collect();
+        if let Some(record) = self.assets.get_mut(&guid) {
+            record.references = references;
+        }
+        Ok(Some(guid))
+    }
+
+    /// Assets `guid` references, and the references that match no asset.
+    pub fn dependencies(&self, guid: Uuid) -> (Vec<&AssetRecord>, Vec<&Path>) {
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for reference in self
+            .assets
+            .get(&guid)
+            .iter()
+            .flat_map(|record| &record.references)
+        {
+            match self.paths.get(reference) {
+                Some(dependency) => found.push(&self.assets[dependency]),
+                None => missing.push(reference.as_path()),
+            }
+        }
+        (found, missing)
+    }
+
+    /// Assets directly referencing `guid`, sorted by path.
+    pub fn references_to(&self, guid: Uuid) -> Vec<&AssetRecord> {
+        let Some(target) = self.assets.get(&guid) else {
+            return Vec::new();
+        };
+        let mut referencing: Vec<_> = self
+            .assets
+            .values()
+            .filter(|record| record.guid != guid && record.references.contains(&target.path))
+            .collect();
+        referencing.sort_by(|a, b| a.path.cmp(&b.path));
+        referencing
+    }
+
+    /// Assets that would be left with broken references if `guid` was
+    /// deleted.
+    pub fn delete_impact(&self, guid: Uuid) -> DeleteImpact {
+        let direct = self.references_to(guid);
+        let mut seen: HashSet<Uuid> = direct.iter().map(|record| record.guid).collect();
+        seen.insert(guid);
+
+        let mut indirect = Vec::new();
+        let mut queue: VecDeque<Uuid> = direct.iter().map(|record| record.guid).collect();
+        while let Some(next) = queue.pop_front() {
+            for record in self.references_to(next) {
+                if seen.insert(record.guid) {
+                    queue.push_back(record.guid);
+                    indirect.push(record);
+                }
+            }
+        }
+        indirect.sort_by(|a, b| a.path.cmp(&b.path));
+
+        DeleteImpact {
+            direct: direct.into_iter().cloned().collect(),
+            indirect: indirect.into_iter().cloned().collect(),
+        }
+    }
+
+    fn scan(&mut self) -> AssetResult<()> {
+        let mut files = Vec::new();
+        let mut metas = BTreeMap::new();
+        let walker = WalkDir::new(&self.root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !is_hidden_or_ignored_dir(entry));
+
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Skipping unreadable project entry: {}", e);
+                    continue;
+                }
+            };
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(&self.root) else {
+                continue;
+            };
+            if has_extension(relative, META_EXTENSION) {
+                metas.insert(relative.with_extension(""), relative.to_path_buf());
+            } else if is_asset(relative) {
+                files.push(relative.to_path_buf());
+            }
+        }
+
+        // Register everything before reading references so they resolve
+        for file in &files {
+            metas.remove(file);
+            if let Err(e) = self.register(file) {
+                warn!("Skipping asset {}: {}", file.display(), e);
+            }
+        }
+        for meta in metas.values() {
+            warn!("{} belongs to no asset", meta.display());
+        }
+        for file in &files {
+            if self.paths.contains_key(file) {
+                self.refresh(file)?;
+            }
+        }
+
+        info!(
+            "Scanned {} assets in {}",
+            self.assets.len(),
+            self.root.display()
+        );
+        Ok(())
+    }
+
+    /// Adds the asset at the project relative `path`, reading its sidecar
+    /// or writing a new one.
+    fn register(&mut self, path: &Path) -> AssetResult<Uuid> {
+        let kind = AssetKind::from_path(path)
+            .ok_or_else(|| AssetError::UnsupportedFormat(path.to_path_buf()))?;
+        let meta_path = meta_path(&self.absolute(path));
+
+        let meta = if meta_path.is_file() {
+            let mut meta = AssetMeta::load(&meta_path)?;
+            // A copied file brings the sidecar of the original along
+            if self.assets.contains_key(&meta.guid) {
+                warn!(
+                    "{} has the GUID of {}, assigning a new one",
+                    path.display(),
+                    self.assets[&meta.guid].path.display()
+                );
+                meta.guid = Uuid::new_v4();
+                meta.save(&meta_path)?;
+            }
+            meta
+        } else {
+            let meta = AssetMeta::new(kind.default_import());
+            meta.save(&meta_path)?;
+            meta
+        };
+
+        self.paths.insert(path.to_path_buf(), meta.guid);
+        self.assets.insert(
+            meta.guid,
+            AssetRecord {
+                guid: meta.guid,
+                path: path.to_path_buf(),
+                kind,
+                references: Vec::new(),
+            },
+        );
+        Ok(meta.guid)
+    }
+
+    /// Project relative path of `reference`, made in the asset at `from`.
+    fn resolve(&self, from: &Path, reference: &Reference) -> Option<PathBuf> {
+        let resolved = match reference {
+            Reference::Sibling(path) | Reference::Project(path) if path.is_absolute() => {
+                self.relative(path)
+            }
+            Reference::Sibling(path) => {
+                let dir = from.parent().unwrap_or_else(|| Path::new(""));
+                Some(normalize(&dir.join(path)))
+            }
+            Reference::Project(path) => Some(normalize(path)),
+        };
+        // References leaving the project are not tracked
+        resolved.filter(|path| !path.starts_with(".."))
+    }
+}
+
+/// Sidecar path of the asset at `path`: its file name plus `.meta`.
+pub fn meta_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(META_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// A path as written in an asset file.
+enum Reference {
+    /// Relative to the referencing file, like the parts of a model.
+    Sibling(PathBuf),
+    /// Relative to the project root, like model sources and mesh components.
+    Project(PathBuf),
+}
+
+/// Paths the asset at `path` references, as written in the file. Unreadable
+/// assets reference nothing.
+fn read_references(path: &Path, kind: AssetKind) -> Vec<Reference> {
+    let references = match kind {
+        AssetKind::Material => MaterialAsset::load(path).map(|material| {
+            material
+                .textures()
+                .map(|texture| Reference::Sibling(PathBuf::from(&texture.image)))
+                .collect()
+        }),
+        AssetKind::Model => ModelAsset::load(path).map(|model| {
+            let parts = model
+                .meshes
+                .iter()
+                .chain(&model.materials)
+                .chain(&model.images)
+                .chain(&model.animations)
+                .map(|part| Reference::Sibling(PathBuf::from(part)));
+            std::iter::once(Reference::Project(model.source))
+                .chain(parts)
+                .collect()
+        }),
+        AssetKind::Scene => fs::read_to_string(path)
+            .map_err(AssetError::io(path))
+            .and_then(|text| Ok(SceneFile::from_json(&text)?))
+            .map(|scene| {
+                scene
+                    .entities
+                    .iter()
+                    .flat_map(|entity| &entity.components)
+                    .filter_map(|component| match component {
+                        // Built-in primitives have no extension
+                        Component::Mesh { mesh } if Path::new(mesh).extension().is_some() => {
+                            Some(Reference::Project(PathBuf::from(mesh)))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            }),
+        _ => Ok(Vec::new()),
+    };
+
+    references.unwrap_or_else(|e| {
+        warn!("Failed to read references of {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            PathComponent::CurDir => {}
+            PathComponent::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// Whether the project relative `path` is not an asset.
+pub(crate) fn is_ignored(path: &Path) -> bool {
+    has_extension(path, META_EXTENSION)
+        || IGNORED_EXTENSIONS
+            .iter()
+            .any(|ext| has_extension(path, ext))
+        || path.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy();
+            name.starts_with('.') || IGNORED_DIRS.contains(&name.as_ref())
+        })
+}
+
+/// Whether the project relative `path` is an asset, which gets a sidecar.
+fn is_asset(path: &Path) -> bool {
+    !is_ignored(path) && AssetKind::from_path(path).is_some()
+}
+
+fn is_hidden_or_ignored_dir(entry: &walkdir::DirEntry) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    name.starts_with('.') || (entry.file_type().is_dir() && IGNORED_DIRS.contains(&name.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "pulsar-database-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn write(&self, path: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn paths(database: &AssetDatabase) -> Vec<(String, AssetKind)> {
+        database
+            .assets()
+            .into_iter()
+            .map(|record| {
+                (
+                    record.path.to_string_lossy().replace('\\', "/"),
+                    record.kind,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn writes_sidecars_only_for_assets() {
+        let project = TempDir::new("sidecars");
+        project.write("Textures/wood.png", "");
+        project.write("Models/crate.gltf", "{}");
+        project.write("Models/crate/crate.model", "{}");
+        project.write("level.scene", "{}");
+        project.write("Scripts/player.rs", "fn main() {}");
+        project.write("README.md", "# Game");
+        project.write("plugins/nodes/plugin.yaml", "name: nodes");
+        project.write("AI/guard.behavior.layout", "{}");
+        project.write("level.scene.tmp", "");
+
+        let database = AssetDatabase::open(&project.0).unwrap();
+
+        assert_eq!(
+            paths(&database),
+            [
+                ("Models/crate/crate.model".to_string(), AssetKind::Model),
+                ("Models/crate.gltf".to_string(), AssetKind::ModelSource),
+                ("Textures/wood.png".to_string(), AssetKind::Image),
+                ("level.scene".to_string(), AssetKind::Scene),
+            ]
+        );
+        let mut sidecars: Vec<_> = WalkDir::new(&project.0)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| has_extension(entry.path(), META_EXTENSION))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        sidecars.sort();
+        assert_eq!(
+            sidecars,
+            [
+                "crate.gltf.meta",
+                "crate.model.meta",
+                "level.scene.meta",
+                "wood.png.meta"
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_changes_to_files_that_are_not_assets() {
+        let project = TempDir::new("changes");
+        let mut database = AssetDatabase::open(&project.0).unwrap();
+
+        let script = project.write("Scripts/enemy.rs", "fn main() {}");
+        let texture = project.write("Textures/stone.png", "");
+        let changes = database.apply(&[project.0.join("Scripts"), texture]);
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (change.kind, change.change))
+                .collect::<Vec<_>>(),
+            [(AssetKind::Image, AssetChange::Created)]
+        );
+        assert_eq!(database.refresh(&script).unwrap(), None);
+        assert!(!meta_path(&script).exists());
+    }
+
+    #[test]
+    fn resolves_model_sources_and_scene_meshes_from_the_project_root() {
+        let project = TempDir::new("references");
+        project.write("Sources/crate.gltf", "{}");
+        project.write(
+            "Models/crate/crate.model",
+            r#"{
+  "name": "crate",
+  "source": "Sources/crate.gltf",
+  "nodes": [],
+  "roots": [],
+  "meshes": ["crate.mesh"],
+  "materials": [],
+  "images": ["wood.png"],
+  "skins": [],
+  "animations": []
+}"#,
+        );
+        let mut world = crate::scene::World::new();
+        let entity = world.spawn("Crate", None).unwrap();
+        world
+            .set_component(
+                entity,
+                Component::Mesh {
+                    mesh: "Models/crate/crate.mesh".to_string(),
+                },
+            )
+            .unwrap();
+        project.write(
+            "Levels/level.scene",
+            &SceneFile::from_world(&world).to_json().unwrap(),
+        );
+
+        let database = AssetDatabase::open(&project.0).unwrap();
+
+        let scene = database
+            .find_by_path(Path::new("Levels/level.scene"))
+            .unwrap();
+        assert_eq!(scene.references, [PathBuf::from("Models/crate/crate.mesh")]);
+        let model = database
+            .find_by_path(Path::new("Models/crate/crate.model"))
+            .unwrap();
+        assert_eq!(
+            model.references,
+            [
+                PathBuf::from("Sources/crate.gltf"),
+                PathBuf::from("Models/crate/crate.mesh"),
+                PathBuf::from("Models/crate/wood.png"),
+            ]
+        );
+    }
+}
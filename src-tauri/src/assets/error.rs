@@ -0,0 +1,78 @@
+use crate::error::serialize_as_code_and_message;
+use crate::scene::SceneError;
+use std::path::PathBuf;
+
+/// Errors raised when importing or writing assets. Scene errors keep their
+/// own code.
+#[derive(Debug, thiserror::Error)]
+pub enum AssetError {
+    #[error("Failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Unsupported asset type: {}", .0.display())]
+    UnsupportedFormat(PathBuf),
+
+    #[error("Invalid glTF file: {0}")]
+    Gltf(#[from] gltf::Error),
+
+    #[error("Failed to decode image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("Failed to load glTF buffer {index}: {reason}")]
+    MissingBuffer { index: usize, reason: String },
+
+    #[error("Failed to write asset metadata: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Invalid asset: {0}")]
+    Invalid(String),
+
+    #[error("Failed to watch the project: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[error("No project is open")]
+    NoProject,
+
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+
+    #[error(transparent)]
+    Scene(#[from] SceneError),
+
+    #[error("Background task failed: {0}")]
+    Task(String),
+}
+
+impl AssetError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AssetError::Io { .. } => "io",
+            AssetError::UnsupportedFormat(_) => "unsupportedFormat",
+            AssetError::Gltf(_) => "gltf",
+            AssetError::Image(_) => "image",
+            AssetError::MissingBuffer { .. } => "missingBuffer",
+            AssetError::Serialization(_) => "serialization",
+            AssetError::Invalid(_) => "invalid",
+            AssetError::Watch(_) => "watch",
+            AssetError::NoProject => "noProject",
+            AssetError::UnknownAsset(_) => "unknownAsset",
+            AssetError::Scene(error) => error.code(),
+            AssetError::Task(_) => "task",
+        }
+    }
+
+    /// Wraps an I/O error with the path it happened on.
+    pub fn io(path: impl Into<PathBuf>) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |source| AssetError::Io { path, source }
+    }
+}
+
+serialize_as_code_and_message!(AssetError);
+
+pub type AssetResult<T> = Result<T, AssetError>;
@@ -0,0 +1,750 @@
+//! glTF 2.0 importer, for both `.gltf` with external or embedded buffers and
+//! binary `.glb`.
+
+use crate::assets::{
+    animation::{AnimatedProperty, AnimationChannel, AnimationClip, Interpolation, Skin},
+    error::{AssetError, AssetResult},
+    material::{AlphaMode, MaterialAsset, TextureRef},
+    mesh::{compute_normals, compute_tangents, MeshAsset, MeshVertex, Submesh},
+    model::{FileNames, ImageSource, ImportedModel, ModelNode},
+};
+use crate::scene::Transform;
+use ::gltf::{animation::util::ReadOutputs, buffer, image, mesh::Mode, Document, Gltf};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use glam::{Mat4, Quat, Vec3};
+use log::{debug, warn};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+pub fn import(path: &Path) -> AssetResult<ImportedModel> {
+    let Gltf { document, blob } = Gltf::open(path)?;
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    let buffers = load_buffers(&document, blob, base)?;
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "model".to_string());
+
+    let mut importer = Importer {
+        buffers: &buffers,
+        base,
+        name: &name,
+        image_files: Vec::new(),
+    };
+
+    let images = importer.images(&document)?;
+    let model = ImportedModel {
+        nodes: nodes(&document),
+        roots: roots(&document),
+        meshes: document
+            .meshes()
+            .map(|mesh| importer.mesh(mesh))
+            .collect::<AssetResult<_>>()?,
+        materials: document
+            .materials()
+            .map(|material| importer.material(material))
+            .collect(),
+        images,
+        skins: document.skins().map(|skin| importer.skin(skin)).collect(),
+        animations: document
+            .animations()
+            .map(|animation| importer.animation(animation))
+            .collect(),
+        name,
+    };
+
+    debug!(
+        "Imported {}: {} nodes, {} meshes, {} materials, {} skins, {} animations",
+        path.display(),
+        model.nodes.len(),
+        model.meshes.len(),
+        model.materials.len(),
+        model.skins.len(),
+        model.animations.len()
+    );
+    Ok(model)
+}
+
+fn load_buffers(
+    document: &Document,
+    blob: Option<Vec<u8>>,
+    base: &Path,
+) -> AssetResult<Vec<Vec<u8>>> {
+    let mut blob = blob;
+    document
+        .buffers()
+        .map(|buffer| {
+            let missing = |reason: String| AssetError::MissingBuffer {
+                index: buffer.index(),
+                reason,
+            };
+            let data = match buffer.source() {
+                buffer::Source::Bin => blob
+                    .take()
+                    .ok_or_else(|| missing("the file has no binary chunk".into()))?,
+                buffer::Source::Uri(uri) => {
+                    load_uri(uri, base).map_err(|e| missing(e.to_string()))?
+                }
+            };
+            if data.len() < buffer.length() {
+                return Err(missing(format!(
+                    "expected {} bytes, found {}",
+                    buffer.length(),
+                    data.len()
+                )));
+            }
+            Ok(data)
+        })
+        .collect()
+}
+
+/// Reads a `data:` URI or a file relative to the source.
+fn load_uri(uri: &str, base: &Path) -> AssetResult<Vec<u8>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, payload) = data
+            .split_once(";base64,")
+            .ok_or_else(|| AssetError::Invalid("only base64 data URIs are supported".into()))?;
+        return STANDARD
+            .decode(payload)
+            .map_err(|e| AssetError::Invalid(format!("malformed data URI: {}", e)));
+    }
+
+    let path = uri_path(uri, base)?;
+    fs::read(&path).map_err(AssetError::io(path))
+}
+
+/// Path of the file a relative URI refers to. Only files in the source's
+/// directory or below it can be referred to, so a crafted file cannot make
+/// the import read or copy any file the editor can.
+fn uri_path(uri: &str, base: &Path) -> AssetResult<PathBuf> {
+    let relative = PathBuf::from(percent_decode(uri));
+    let escapes = relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes || relative.as_os_str().is_empty() {
+        return Err(AssetError::Invalid(format!(
+            "URI '{}' does not refer to a file next to the source",
+            uri
+        )));
+    }
+    Ok(base.join(relative))
+}
+
+/// Decodes `%XX` escapes, which relative glTF URIs use for e.g. spaces.
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| uri.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn nodes(document: &Document) -> Vec<ModelNode> {
+    let mut nodes: Vec<ModelNode> = document
+        .nodes()
+        .map(|node| {
+            let (translation, rotation, scale) = node.transform().decomposed();
+            ModelNode {
+                name: node
+                    .name()
+                    .map_or_else(|| format!("Node {}", node.index()), str::to_string),
+                parent: None,
+                children: node.children().map(|child| child.index()).collect(),
+                transform: Transform::from_scale_rotation_translation(
+                    Vec3::from(scale),
+                    Quat::from_array(rotation).normalize(),
+                    Vec3::from(translation),
+                ),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                skin: node.skin().map(|skin| skin.index()),
+            }
+        })
+        .collect();
+
+    for parent in 0..nodes.len() {
+        for child in nodes[parent].children.clone() {
+            nodes[child].parent = Some(parent);
+        }
+    }
+    nodes
+}
+
+/// Nodes of the default scene, or of the first one. Files without scenes
+/// get every top-level node.
+fn roots(document: &Document) -> Vec<usize> {
+    match document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    {
+        Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+        None => {
+            let children: Vec<usize> = document
+                .nodes()
+                .flat_map(|node| node.children().map(|child| child.index()))
+                .collect();
+            (0..document.nodes().len())
+                .filter(|node| !children.contains(node))
+                .collect()
+        }
+    }
+}
+
+struct Importer<'a> {
+    buffers: &'a [Vec<u8>],
+    base: &'a Path,
+    name: &'a str,
+    /// Output file name of every image, by glTF image index.
+    image_files: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn buffer(&self, buffer: buffer::Buffer<'_>) -> Option<&'a [u8]> {
+        self.buffers.get(buffer.index()).map(Vec::as_slice)
+    }
+
+    fn images(&mut self, document: &Document) -> AssetResult<Vec<(String, ImageSource)>> {
+        let mut names = FileNames::default();
+        let mut images = Vec::new();
+
+        for image in document.images() {
+            let (source, extension) = match image.source() {
+                image::Source::View { view, mime_type } => {
+                    let start = view.offset();
+                    let bytes = self
+                        .buffer(view.buffer())
+                        .and_then(|buffer| buffer.get(start..start + view.length()))
+                        .ok_or_else(|| {
+                            AssetError::Invalid(format!("image {} is out of bounds", image.index()))
+                        })?;
+                    (
+                        ImageSource::Embedded(bytes.to_vec()),
+                        mime_extension(mime_type),
+                    )
+                }
+                image::Source::Uri { uri, mime_type } if uri.starts_with("data:") => {
+                    let mime_type = mime_type
+                        .or_else(|| uri.strip_prefix("data:")?.split(';').next())
+                        .unwrap_or_default();
+                    (
+                        ImageSource::Embedded(load_uri(uri, self.base)?),
+                        mime_extension(mime_type),
+                    )
+                }
+                image::Source::Uri { uri, .. } => {
+                    let path = uri_path(uri, self.base)?;
+                    let extension = path
+                        .extension()
+                        .map(|extension| extension.to_string_lossy().to_lowercase())
+                        .unwrap_or_else(|| "bin".to_string());
+                    (ImageSource::File(path), extension)
+                }
+            };
+
+            let stem = match (image.name(), &source) {
+                (Some(name), _) => name.to_string(),
+                (None, ImageSource::File(path)) => path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                (None, ImageSource::Embedded(_)) => format!("{}-image{}", self.name, image.index()),
+            };
+            let file = names.reserve(&stem, &extension);
+            self.image_files.push(file.clone());
+            images.push((file, source));
+        }
+        Ok(images)
+    }
+
+    fn texture(&self, texture: ::gltf::Texture<'_>, tex_coord: u32) -> Option<TextureRef> {
+        let image = self.image_files.get(texture.source().index())?.clone();
+        Some(TextureRef { image, tex_coord })
+    }
+
+    fn material(&self, material: ::gltf::Material<'_>) -> MaterialAsset {
+        let pbr = material.pbr_metallic_roughness();
+        let info = |info: Option<::gltf::texture::Info<'_>>| {
+            info.and_then(|info| self.texture(info.texture(), info.tex_coord()))
+        };
+        let normal = material.normal_texture();
+        let occlusion = material.occlusion_texture();
+
+        MaterialAsset {
+            name: material.name().map_or_else(
+                || format!("{}-material{}", self.name, material.index().unwrap_or(0)),
+                str::to_string,
+            ),
+            base_color: pbr.base_color_factor(),
+            base_color_texture: info(pbr.base_color_texture()),
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            metallic_roughness_texture: info(pbr.metallic_roughness_texture()),
+            normal_texture: normal
+                .as_ref()
+                .and_then(|normal| self.texture(normal.texture(), normal.tex_coord())),
+            normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+            occlusion_texture: occlusion
+                .as_ref()
+                .and_then(|occlusion| self.texture(occlusion.texture(), occlusion.tex_coord())),
+            occlusion_strength: occlusion
+                .as_ref()
+                .map_or(1.0, |occlusion| occlusion.strength()),
+            emissive: material.emissive_factor(),
+            emissive_texture: info(material.emissive_texture()),
+            alpha_mode: match material.alpha_mode() {
+                ::gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                ::gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+                ::gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+            },
+            alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+            double_sided: material.double_sided(),
+        }
+    }
+
+    /// Merges the primitives of a mesh into one vertex and index buffer,
+    /// with a submesh per primitive.
+    fn mesh(&self, mesh: ::gltf::Mesh<'_>) -> AssetResult<MeshAsset> {
+        let mut asset = MeshAsset {
+            name: mesh.name().map_or_else(
+                || format!("{}-mesh{}", self.name, mesh.index()),
+                str::to_string,
+            ),
+            ..MeshAsset::default()
+        };
+
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| self.buffer(buffer));
+            let Some(positions) = reader.read_positions() else {
+                warn!(
+                    "Skipping primitive {} of mesh '{}' without positions",
+                    primitive.index(),
+                    asset.name
+                );
+                continue;
+            };
+
+            let mut vertices: Vec<MeshVertex> = positions
+                .map(|position| MeshVertex {
+                    position,
+                    ..MeshVertex::default()
+                })
+                .collect();
+            let sequential = || (0..vertices.len() as u32).collect::<Vec<_>>();
+            let indices = reader
+                .read_indices()
+                .map_or_else(sequential, |indices| indices.into_u32().collect());
+            let indices = match primitive.mode() {
+                Mode::Triangles => indices,
+                Mode::TriangleStrip => strip_to_list(&indices),
+                Mode::TriangleFan => fan_to_list(&indices),
+                mode => {
+                    warn!("Skipping {:?} primitive of mesh '{}'", mode, asset.name);
+                    continue;
+                }
+            };
+            if let Some(&index) = indices
+                .iter()
+                .find(|&&index| index as usize >= vertices.len())
+            {
+                return Err(AssetError::Invalid(format!(
+                    "mesh '{}' refers to vertex {} of {}",
+                    asset.name,
+                    index,
+                    vertices.len()
+                )));
+            }
+
+            if let Some(uvs) = reader.read_tex_coords(0) {
+                for (vertex, uv) in vertices.iter_mut().zip(uvs.into_f32()) {
+                    vertex.uv0 = uv;
+                }
+            }
+            if let Some(uvs) = reader.read_tex_coords(1) {
+                for (vertex, uv) in vertices.iter_mut().zip(uvs.into_f32()) {
+                    vertex.uv1 = uv;
+                }
+            }
+            if let Some(joints) = reader.read_joints(0) {
+                for (vertex, joints) in vertices.iter_mut().zip(joints.into_u16()) {
+                    vertex.joints = joints;
+                }
+            }
+            if let Some(weights) = reader.read_weights(0) {
+                for (vertex, weights) in vertices.iter_mut().zip(weights.into_f32()) {
+                    // Exporters do not always normalize, skinning expects it
+                    let sum: f32 = weights.iter().sum();
+                    vertex.weights = if sum > 0.0 {
+                        weights.map(|weight| weight / sum)
+                    } else {
+                        weights
+                    };
+                }
+            }
+
+            match reader.read_normals() {
+                Some(normals) => {
+                    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+                        vertex.normal = normal;
+                    }
+                }
+                None => compute_normals(&mut vertices, &indices),
+            }
+            match reader.read_tangents() {
+                Some(tangents) => {
+                    for (vertex, tangent) in vertices.iter_mut().zip(tangents) {
+                        vertex.tangent = tangent;
+                    }
+                }
+                None => compute_tangents(&mut vertices, &indices),
+            }
+
+            let base = asset.vertices.len() as u32;
+            asset.submeshes.push(Submesh {
+                first_index: asset.indices.len() as u32,
+                index_count: indices.len() as u32,
+                material: primitive.material().index().map(|index| index as u32),
+            });
+            asset.vertices.extend(vertices);
+            asset
+                .indices
+                .extend(indices.into_iter().map(|index| base + index));
+        }
+        Ok(asset)
+    }
+
+    fn skin(&self, skin: ::gltf::Skin<'_>) -> Skin {
+        let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+        let inverse_bind_matrices = skin
+            .reader(|buffer| self.buffer(buffer))
+            .read_inverse_bind_matrices()
+            .map(|matrices| matrices.collect())
+            .unwrap_or_else(|| vec![Mat4::IDENTITY.to_cols_array_2d(); joints.len()]);
+
+        Skin {
+            name: skin.name().map_or_else(
+                || format!("{}-skin{}", self.name, skin.index()),
+                str::to_string,
+            ),
+            joints,
+            inverse_bind_matrices,
+            skeleton: skin.skeleton().map(|node| node.index()),
+        }
+    }
+
+    fn animation(&self, animation: ::gltf::Animation<'_>) -> AnimationClip {
+        let name = animation.name().map_or_else(
+            || format!("{}-animation{}", self.name, animation.index()),
+            str::to_string,
+        );
+
+        let mut channels = Vec::new();
+        for channel in animation.channels() {
+            let reader = channel.reader(|buffer| self.buffer(buffer));
+            let (Some(times), Some(outputs)) = (reader.read_inputs(), reader.read_outputs()) else {
+                warn!(
+                    "Skipping animation channel {} of '{}' without keys",
+                    channel.index(),
+                    name
+                );
+                continue;
+            };
+
+            let (property, values): (_, Vec<f32>) = match outputs {
+                ReadOutputs::Translations(values) => {
+                    (AnimatedProperty::Translation, values.flatten().collect())
+                }
+                ReadOutputs::Rotations(values) => (
+                    AnimatedProperty::Rotation,
+                    values.into_f32().flatten().collect(),
+                ),
+                ReadOutputs::Scales(values) => {
+                    (AnimatedProperty::Scale, values.flatten().collect())
+                }
+                ReadOutputs::MorphTargetWeights(values) => {
+                    (AnimatedProperty::Weights, values.into_f32().collect())
+                }
+            };
+
+            channels.push(AnimationChannel {
+                node: channel.target().node().index(),
+                property,
+                interpolation: match channel.sampler().interpolation() {
+                    ::gltf::animation::Interpolation::Step => Interpolation::Step,
+                    ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                    ::gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+                },
+                times: times.collect(),
+                values,
+            });
+        }
+
+        let duration = channels
+            .iter()
+            .filter_map(|channel| channel.times.last().copied())
+            .fold(0.0, f32::max);
+        AnimationClip {
+            name,
+            duration,
+            channels,
+        }
+    }
+}
+
+fn mime_extension(mime_type: &str) -> String {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/ktx2" => "ktx2",
+        "image/webp" => "webp",
+        _ => "bin",
+    }
+    .to_string()
+}
+
+/// Triangle list from a strip, keeping the winding of every triangle.
+fn strip_to_list(indices: &[u32]) -> Vec<u32> {
+    indices
+        .windows(3)
+        .enumerate()
+        .flat_map(|(i, triangle)| {
+            if i % 2 == 0 {
+                [triangle[0], triangle[1], triangle[2]]
+            } else {
+                [triangle[1], triangle[0], triangle[2]]
+            }
+        })
+        .collect()
+}
+
+fn fan_to_list(indices: &[u32]) -> Vec<u32> {
+    let Some((&center, rest)) = indices.split_first() else {
+        return Vec::new();
+    };
+    rest.windows(2)
+        .flat_map(|edge| [center, edge[0], edge[1]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::ModelAsset;
+    use crate::scene::{Component, ComponentKind, World};
+
+    /// Reduced copies of the Khronos glTF sample models.
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/gltf")
+            .join(name)
+    }
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("pulsar-gltf-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn imports_the_triangle_from_its_embedded_buffer() {
+        let model = import(&fixture("Triangle.gltf")).unwrap();
+
+        assert_eq!(model.name, "Triangle");
+        assert_eq!(model.roots, vec![0]);
+        assert_eq!(model.meshes.len(), 1);
+        let mesh = &model.meshes[0];
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        let positions: Vec<_> = mesh.vertices.iter().map(|vertex| vertex.position).collect();
+        assert_eq!(
+            positions,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        // The sample has no normals, they face the counter-clockwise side
+        for vertex in &mesh.vertices {
+            assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn imports_the_box_with_its_external_buffer() {
+        let model = import(&fixture("Box.gltf")).unwrap();
+
+        assert_eq!(model.roots, vec![0]);
+        assert_eq!(model.nodes.len(), 2);
+        assert_eq!(model.nodes[0].children, vec![1]);
+        assert_eq!(model.nodes[1].parent, Some(0));
+        assert_eq!(model.nodes[1].mesh, Some(0));
+        let rotation = model.nodes[0].transform.rotation;
+        assert!((rotation.x + 90.0).abs() < 1e-3, "{:?}", rotation);
+
+        let mesh = &model.meshes[0];
+        assert_eq!(mesh.name, "Mesh");
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.indices.len(), 36);
+        assert_eq!(mesh.submeshes.len(), 1);
+        assert_eq!(mesh.submeshes[0].material, Some(0));
+
+        assert_eq!(model.materials.len(), 1);
+        assert_eq!(model.materials[0].name, "Red");
+        assert_eq!(model.materials[0].base_color, [0.8, 0.0, 0.0, 1.0]);
+        assert!(model.images.is_empty());
+    }
+
+    #[test]
+    fn imports_the_binary_box_like_the_text_one() {
+        let text = import(&fixture("Box.gltf")).unwrap();
+        let binary = import(&fixture("Box.glb")).unwrap();
+
+        assert_eq!(binary.nodes, text.nodes);
+        assert_eq!(binary.meshes, text.meshes);
+        assert_eq!(binary.materials, text.materials);
+    }
+
+    #[test]
+    fn imports_the_textured_box_with_its_image_file() {
+        let model = import(&fixture("BoxTextured.gltf")).unwrap();
+
+        assert_eq!(
+            model.images,
+            vec![(
+                "CesiumLogoFlat.png".to_string(),
+                ImageSource::File(fixture("CesiumLogoFlat.png"))
+            )]
+        );
+        let texture = model.materials[0].base_color_texture.as_ref().unwrap();
+        assert_eq!(texture.image, "CesiumLogoFlat.png");
+        assert_eq!(texture.tex_coord, 0);
+        let uvs: Vec<_> = model.meshes[0]
+            .vertices
+            .iter()
+            .map(|vertex| vertex.uv0)
+            .collect();
+        assert!(uvs.contains(&[0.0, 0.0]) && uvs.contains(&[1.0, 1.0]));
+    }
+
+    #[test]
+    fn writes_parts_and_instantiates_with_the_given_directory() {
+        let temp = TempDir::new("write");
+        let dir = temp.0.join("BoxTextured");
+        let model = import(&fixture("BoxTextured.gltf"))
+            .unwrap()
+            .write(Path::new("Sources/BoxTextured.gltf"), &dir)
+            .unwrap();
+
+        assert_eq!(model.source, Path::new("Sources/BoxTextured.gltf"));
+        assert_eq!(model.meshes, vec!["Mesh.mesh"]);
+        assert_eq!(model.images, vec!["CesiumLogoFlat.png"]);
+        assert_eq!(
+            fs::read(dir.join("CesiumLogoFlat.png")).unwrap(),
+            fs::read(fixture("CesiumLogoFlat.png")).unwrap()
+        );
+        assert_eq!(
+            ModelAsset::load(&dir.join(model.file_name())).unwrap(),
+            model
+        );
+
+        let mut world = World::new();
+        let root = model
+            .instantiate(&mut world, Path::new("Models/BoxTextured"), None)
+            .unwrap();
+        let meshes: Vec<_> = world
+            .depth_first(Some(root))
+            .unwrap()
+            .into_iter()
+            .filter_map(|id| {
+                world
+                    .get(id)
+                    .unwrap()
+                    .component(ComponentKind::Mesh)
+                    .cloned()
+            })
+            .collect();
+        assert_eq!(
+            meshes,
+            vec![Component::Mesh {
+                mesh: Path::new("Models/BoxTextured")
+                    .join("Mesh.mesh")
+                    .to_string_lossy()
+                    .into_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_buffers_outside_the_source_directory() {
+        let error = import(&fixture("escape/EscapingBuffer.gltf")).unwrap_err();
+        assert!(
+            matches!(&error, AssetError::MissingBuffer { index: 0, reason } if reason.contains("../Box0.bin")),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn rejects_images_outside_the_source_directory() {
+        let error = import(&fixture("escape/EscapingImage.gltf")).unwrap_err();
+        assert!(matches!(error, AssetError::Invalid(_)), "{:?}", error);
+    }
+
+    #[test]
+    fn rejects_absolute_uris() {
+        let temp = TempDir::new("absolute");
+        let text = fs::read_to_string(fixture("Box.gltf")).unwrap();
+        let absolute = fixture("Box0.bin").to_string_lossy().replace('\\', "/");
+        let path = temp.0.join("Box.gltf");
+        fs::write(
+            &path,
+            text.replace("\"Box0.bin\"", &format!("{:?}", absolute)),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            import(&path),
+            Err(AssetError::MissingBuffer { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn resolves_percent_encoded_uris_below_the_source() {
+        let base = Path::new("models");
+        assert_eq!(
+            uri_path("textures/Cesium%20Logo.png", base).unwrap(),
+            base.join("textures").join("Cesium Logo.png")
+        );
+        assert_eq!(uri_path("./Box0.bin", base).unwrap(), base.join("Box0.bin"));
+        for uri in [
+            "%2E%2E/secret.bin",
+            "textures/../../secret.bin",
+            "/etc/passwd",
+            "",
+        ] {
+            assert!(uri_path(uri, base).is_err(), "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+pub mod gltf;
+pub mod texture;
+
+use self::texture::TextureImportSettings;
+use super::{
+    database::ImportSettings,
+    error::{AssetError, AssetResult},
+    model::ImportedModel,
+    queue::{ImportEvent, ImportQueue, ImportReporter, ImportSink, JobId},
+    texture::content_hash,
+    SharedAssetDatabase,
+};
+use log::{debug, error, info};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Source extensions `import_model` understands.
+pub const MODEL_EXTENSIONS: &[&str] = &["gltf", "glb"];
+
+/// Reads a model source file, picking the importer by extension.
+pub fn import_model(path: &Path) -> AssetResult<ImportedModel> {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+
+    match extension.as_deref() {
+        Some("gltf" | "glb") => gltf::import(path),
+        _ => Err(AssetError::UnsupportedFormat(path.to_path_buf())),
+    }
+}
+
+/// Queues an import of the image at `source` into `output`. On success the
+/// settings and output are recorded in the source's sidecar if it belongs
+/// to the open project.
+pub fn submit_texture(
+    queue: &ImportQueue,
+    database: &SharedAssetDatabase,
+    sink: ImportSink,
+    source: PathBuf,
+    output: PathBuf,
+    settings: TextureImportSettings,
+) -> JobId {
+    let database = database.clone();
+    queue.submit(source.clone(), sink, move |reporter| {
+        let result = texture::import_texture(&source, &settings, &mut |progress| {
+            reporter.progress(progress)
+        })
+        .and_then(|texture| texture::write_texture(&texture, settings.container, &output));
+
+        finish(
+            reporter,
+            &database,
+            &source,
+            ImportSettings::Texture(settings),
+            result.map(|hash| (output, hash)),
+        );
+    })
+}
+
+/// Queues an import of the model at `source` into `directory`, without
+/// adding it to the scene. Finishes with the path of the `.model` file.
+pub fn submit_model(
+    queue: &ImportQueue,
+    database: &SharedAssetDatabase,
+    sink: ImportSink,
+    source: PathBuf,
+    directory: PathBuf,
+) -> JobId {
+    let stored_source = match database.read().as_ref() {
+        Some(database) => database.stored_path(&source),
+        None => source.clone(),
+    };
+    let database = database.clone();
+    queue.submit(source.clone(), sink, move |reporter| {
+        reporter.progress(0.0);
+        let result = import_model(&source)
+            .and_then(|model| model.write(&stored_source, &directory))
+            .and_then(|model| {
+                let output = directory.join(model.file_name());
+                let bytes = fs::read(&output).map_err(AssetError::io(&output))?;
+                Ok((output, content_hash(&bytes)))
+            });
+
+        finish(reporter, &database, &source, ImportSettings::Model, result);
+    })
+}
+
+/// Queues a reimport of the asset at `source` with the settings and output
+/// recorded in its sidecar. Returns `None` if it was never imported.
+pub fn reimport(
+    queue: &ImportQueue,
+    database: &SharedAssetDatabase,
+    sink: ImportSink,
+    source: &Path,
+) -> Option<JobId> {
+    let (import, output) = {
+        let database = database.read();
+        let database = database.as_ref()?;
+        let meta = database.meta(source)?;
+        (meta.import?, database.absolute(&meta.output?))
+    };
+
+    info!("Reimporting {}", source.display());
+    let source = source.to_path_buf();
+    Some(match import {
+        ImportSettings::Texture(settings) => {
+            submit_texture(queue, database, sink, source, output, settings)
+        }
+        ImportSettings::Model => {
+            let directory = output.parent().unwrap_or(&output).to_path_buf();
+            submit_model(queue, database, sink, source, directory)
+        }
+    })
+}
+
+/// Records a successful import and reports how the job ended.
+fn finish(
+    reporter: &ImportReporter,
+    database: &SharedAssetDatabase,
+    source: &Path,
+    import: ImportSettings,
+    result: AssetResult<(PathBuf, String)>,
+) {
+    reporter.report(match result {
+        Ok((output, hash)) => {
+            if let Some(database) = database.write().as_mut() {
+                if let Err(e) = database.record_import(source, import, &output) {
+                    debug!("Not recording import of {}: {}", source.display(), e);
+                }
+            }
+            ImportEvent::Finished { output, hash }
+        }
+        Err(e) => {
+            error!("Failed to import {}: {}", source.display(), e);
+            ImportEvent::failed(&e)
+        }
+    });
+}
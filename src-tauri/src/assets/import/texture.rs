@@ -0,0 +1,537 @@
+//! Texture importer for PNG, JPEG, TGA and Radiance HDR images.
+//!
+//! The pipeline is deterministic: the same source and settings always give
+//! byte-identical output, so results can be compared by `content_hash`.
+
+use crate::assets::{
+    bc,
+    error::{AssetError, AssetResult},
+    ktx2::{self, KTX2_EXTENSION},
+    texture::{content_hash, TextureAsset, TextureFormat, TEXTURE_EXTENSION},
+};
+use image::DynamicImage;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Source extensions `import_texture` understands.
+pub const TEXTURE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tga", "hdr"];
+
+/// What the texture holds, which decides how it is filtered and tagged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextureKind {
+    /// sRGB encoded color, e.g. albedo. Filtered in linear space.
+    #[default]
+    Color,
+    /// Linear data such as roughness or masks.
+    Linear,
+    /// Tangent space normals. Mips are renormalized.
+    NormalMap,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextureCompression {
+    #[default]
+    None,
+    Bc1,
+    Bc3,
+    Bc5,
+    Bc7,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextureContainer {
+    /// The engine's `.texture` format.
+    #[default]
+    Native,
+    Ktx2,
+}
+
+impl TextureContainer {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TextureContainer::Native => TEXTURE_EXTENSION,
+            TextureContainer::Ktx2 => KTX2_EXTENSION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TextureImportSettings {
+    pub kind: TextureKind,
+    /// Generate the full mip chain down to 1x1.
+    pub mipmaps: bool,
+    /// Block compression for LDR images. HDR images stay half-float.
+    pub compression: TextureCompression,
+    pub container: TextureContainer,
+    /// Invert the green channel of normal maps authored for Direct3D.
+    pub flip_green: bool,
+}
+
+impl Default for TextureImportSettings {
+    fn default() -> Self {
+        Self {
+            kind: TextureKind::Color,
+            mipmaps: true,
+            compression: TextureCompression::None,
+            container: TextureContainer::Native,
+            flip_green: false,
+        }
+    }
+}
+
+/// Decodes `path` and builds the texture described by `settings`, calling
+/// `progress` with the completed fraction as it goes. It stops at 0.9,
+/// leaving the rest for writing the result.
+pub fn import_texture(
+    path: &Path,
+    settings: &TextureImportSettings,
+    progress: &mut dyn FnMut(f32),
+) -> AssetResult<TextureAsset> {
+    // BC5 holds two linear channels and has no sRGB variant to decode color
+    if settings.kind == TextureKind::Color && settings.compression == TextureCompression::Bc5 {
+        return Err(AssetError::Invalid(format!(
+            "{} is a color texture, BC5 only holds linear data and normal maps",
+            path.display()
+        )));
+    }
+
+    progress(0.0);
+    let image = image::open(path)?;
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 {
+        return Err(AssetError::Invalid(format!("{} is empty", path.display())));
+    }
+    progress(0.1);
+
+    let levels = if settings.mipmaps {
+        32 - width.max(height).leading_zeros() as usize
+    } else {
+        1
+    };
+    let hdr = matches!(
+        image,
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+    );
+
+    let texture = if hdr {
+        if settings.compression != TextureCompression::None {
+            warn!(
+                "{} is HDR, storing it uncompressed instead of {:?}",
+                path.display(),
+                settings.compression
+            );
+        }
+        let pixels: Vec<[f32; 4]> = image.into_rgba32f().pixels().map(|pixel| pixel.0).collect();
+        let mips = float_mips(pixels, width, height, levels);
+        progress(0.9);
+        TextureAsset {
+            format: TextureFormat::Rgba16Float,
+            srgb: false,
+            width,
+            height,
+            mips: mips.iter().map(|mip| encode_half(mip)).collect(),
+        }
+    } else {
+        let mut pixels = image.into_rgba8().into_raw();
+        if settings.kind == TextureKind::NormalMap && settings.flip_green {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel[1] = 255 - pixel[1];
+            }
+        }
+
+        let mips = byte_mips(pixels, width, height, levels, settings.kind);
+        progress(0.3);
+        let format = match settings.compression {
+            TextureCompression::None => TextureFormat::Rgba8,
+            TextureCompression::Bc1 => TextureFormat::Bc1,
+            TextureCompression::Bc3 => TextureFormat::Bc3,
+            TextureCompression::Bc5 => TextureFormat::Bc5,
+            TextureCompression::Bc7 => TextureFormat::Bc7,
+        };
+        if format.is_compressed() && (width % 4 != 0 || height % 4 != 0) {
+            return Err(AssetError::Invalid(format!(
+                "{} is {}x{}, block compression needs a multiple of 4",
+                path.display(),
+                width,
+                height
+            )));
+        }
+
+        let mips = if format.is_compressed() {
+            // Level sizes shrink by 4x, weigh progress by pixel count
+            let total: u64 = (0..levels)
+                .map(|level| 1u64 << (2 * (levels - level)))
+                .sum();
+            let mut done = 0u64;
+            let mut compressed = Vec::with_capacity(levels);
+            for (level, mip) in mips.iter().enumerate() {
+                let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+                compressed.push(bc::compress(format, w, h, mip));
+                done += 1 << (2 * (levels - level));
+                progress(0.3 + 0.6 * done as f32 / total as f32);
+            }
+            compressed
+        } else {
+            progress(0.9);
+            mips
+        };
+
+        TextureAsset {
+            format,
+            srgb: settings.kind == TextureKind::Color,
+            width,
+            height,
+            mips,
+        }
+    };
+
+    debug!(
+        "Imported {}: {}x{} {:?}, {} levels",
+        path.display(),
+        width,
+        height,
+        texture.format,
+        texture.mips.len()
+    );
+    Ok(texture)
+}
+
+/// Writes the texture in `container` format. Returns the content hash of
+/// the written file.
+pub fn write_texture(
+    texture: &TextureAsset,
+    container: TextureContainer,
+    path: &Path,
+) -> AssetResult<String> {
+    let bytes = match container {
+        TextureContainer::Native => texture.to_bytes(),
+        TextureContainer::Ktx2 => ktx2::encode(texture),
+    };
+    fs::write(path, &bytes).map_err(AssetError::io(path))?;
+    Ok(content_hash(&bytes))
+}
+
+/// Mip chain of an RGBA8 image, filtered as `kind` requires.
+fn byte_mips(
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    levels: usize,
+    kind: TextureKind,
+) -> Vec<Vec<u8>> {
+    // Filter in a space where averaging is meaningful
+    let decode = |pixel: &[u8]| -> [f32; 4] {
+        match kind {
+            TextureKind::Color => [
+                srgb_to_linear(pixel[0]),
+                srgb_to_linear(pixel[1]),
+                srgb_to_linear(pixel[2]),
+                pixel[3] as f32 / 255.0,
+            ],
+            TextureKind::Linear => [0, 1, 2, 3].map(|i| pixel[i] as f32 / 255.0),
+            TextureKind::NormalMap => [0, 1, 2, 3].map(|i| pixel[i] as f32 / 255.0 * 2.0 - 1.0),
+        }
+    };
+    let encode = |value: [f32; 4]| -> [u8; 4] {
+        let unorm = |v: f32| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+        match kind {
+            TextureKind::Color => [
+                linear_to_srgb(value[0]),
+                linear_to_srgb(value[1]),
+                linear_to_srgb(value[2]),
+                unorm(value[3]),
+            ],
+            TextureKind::Linear => value.map(unorm),
+            TextureKind::NormalMap => {
+                let length =
+                    (value[0] * value[0] + value[1] * value[1] + value[2] * value[2]).sqrt();
+                let normal = if length > f32::EPSILON {
+                    [value[0] / length, value[1] / length, value[2] / length]
+                } else {
+                    [0.0, 0.0, 1.0]
+                };
+                [
+                    unorm(normal[0] * 0.5 + 0.5),
+                    unorm(normal[1] * 0.5 + 0.5),
+                    unorm(normal[2] * 0.5 + 0.5),
+                    unorm(value[3] * 0.5 + 0.5),
+                ]
+            }
+        }
+    };
+
+    let mut mips = Vec::with_capacity(levels);
+    let mut current: Vec<[f32; 4]> = pixels.chunks_exact(4).map(decode).collect();
+    mips.push(pixels);
+    for level in 1..levels {
+        current = downsample(
+            &current,
+            (width >> (level - 1)).max(1),
+            (height >> (level - 1)).max(1),
+        );
+        mips.push(current.iter().flat_map(|&pixel| encode(pixel)).collect());
+    }
+    mips
+}
+
+fn float_mips(pixels: Vec<[f32; 4]>, width: u32, height: u32, levels: usize) -> Vec<Vec<[f32; 4]>> {
+    let mut mips = Vec::with_capacity(levels);
+    mips.push(pixels);
+    for level in 1..levels {
+        let previous = &mips[level - 1];
+        let next = downsample(
+            previous,
+            (width >> (level - 1)).max(1),
+            (height >> (level - 1)).max(1),
+        );
+        mips.push(next);
+    }
+    mips
+}
+
+/// Halves an image with a box filter. Odd edges fold into the last pixel.
+fn downsample(pixels: &[[f32; 4]], width: u32, height: u32) -> Vec<[f32; 4]> {
+    let (width, height) = (width as usize, height as usize);
+    let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+    let mut next = Vec::with_capacity(next_width * next_height);
+
+    for y in 0..next_height {
+        for x in 0..next_width {
+            let mut sum = [0.0f32; 4];
+            let xs = [(2 * x).min(width - 1), (2 * x + 1).min(width - 1)];
+            let ys = [(2 * y).min(height - 1), (2 * y + 1).min(height - 1)];
+            for sy in ys {
+                for sx in xs {
+                    let pixel = pixels[sy * width + sx];
+                    for i in 0..4 {
+                        sum[i] += pixel[i];
+                    }
+                }
+            }
+            next.push(sum.map(|v| v / 4.0));
+        }
+    }
+    next
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let v = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (v * 255.0 + 0.5) as u8
+}
+
+fn encode_half(pixels: &[[f32; 4]]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|pixel| pixel.iter().flat_map(|&v| f32_to_f16(v).to_le_bytes()))
+        .collect()
+}
+
+/// IEEE half-precision bits of `value`, rounded to nearest even.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    let round = |half: u32, rest: u32, halfway: u32| {
+        if rest > halfway || (rest == halfway && half & 1 == 1) {
+            half + 1
+        } else {
+            half
+        }
+    };
+
+    if exponent <= 0 {
+        // Subnormal, or too small for half precision
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let half = round(
+            mantissa >> shift,
+            mantissa & ((1 << shift) - 1),
+            1 << (shift - 1),
+        );
+        return sign | half as u16;
+    }
+
+    // A carry out of the mantissa correctly bumps the exponent
+    let half = round(
+        ((exponent as u32) << 10) | (mantissa >> 13),
+        mantissa & 0x1fff,
+        0x1000,
+    );
+    sign | half as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+    use std::path::PathBuf;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "pulsar-texture-{}-{}",
+                name,
+                std::process::id()
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Writes an 8x8 PNG with gradients in red and green.
+    fn source(dir: &TempDir) -> PathBuf {
+        let path = dir.0.join("source.png");
+        RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 32, y as u8 * 32, 128, 255]))
+            .save(&path)
+            .unwrap();
+        path
+    }
+
+    fn settings(
+        kind: TextureKind,
+        compression: TextureCompression,
+        container: TextureContainer,
+    ) -> TextureImportSettings {
+        TextureImportSettings {
+            kind,
+            compression,
+            container,
+            ..TextureImportSettings::default()
+        }
+    }
+
+    fn import(path: &Path, settings: &TextureImportSettings) -> AssetResult<TextureAsset> {
+        import_texture(path, settings, &mut |_| {})
+    }
+
+    #[test]
+    fn rejects_color_textures_compressed_as_bc5() {
+        let dir = TempDir::new("bc5-color");
+        let settings = settings(
+            TextureKind::Color,
+            TextureCompression::Bc5,
+            TextureContainer::Ktx2,
+        );
+        assert!(matches!(
+            import(&source(&dir), &settings),
+            Err(AssetError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn stores_bc5_normal_maps_as_linear() {
+        let dir = TempDir::new("bc5-normal");
+        let settings = settings(
+            TextureKind::NormalMap,
+            TextureCompression::Bc5,
+            TextureContainer::Ktx2,
+        );
+        let texture = import(&source(&dir), &settings).unwrap();
+        assert_eq!(texture.format, TextureFormat::Bc5);
+        assert!(!texture.srgb);
+    }
+
+    #[test]
+    fn tags_only_color_textures_as_srgb() {
+        let dir = TempDir::new("srgb");
+        let path = source(&dir);
+        for (kind, srgb) in [
+            (TextureKind::Color, true),
+            (TextureKind::Linear, false),
+            (TextureKind::NormalMap, false),
+        ] {
+            let settings = settings(kind, TextureCompression::Bc7, TextureContainer::Native);
+            assert_eq!(import(&path, &settings).unwrap().srgb, srgb, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn writes_the_recorded_bytes() {
+        let dir = TempDir::new("hashes");
+        let path = source(&dir);
+        let hashes: Vec<_> = [
+            settings(
+                TextureKind::Color,
+                TextureCompression::None,
+                TextureContainer::Native,
+            ),
+            settings(
+                TextureKind::Color,
+                TextureCompression::Bc7,
+                TextureContainer::Ktx2,
+            ),
+            settings(
+                TextureKind::Linear,
+                TextureCompression::Bc1,
+                TextureContainer::Ktx2,
+            ),
+            settings(
+                TextureKind::NormalMap,
+                TextureCompression::Bc5,
+                TextureContainer::Ktx2,
+            ),
+        ]
+        .iter()
+        .map(|settings| {
+            let texture = import(&path, settings).unwrap();
+            let output = dir
+                .0
+                .join(format!("output.{}", settings.container.extension()));
+            let hash = write_texture(&texture, settings.container, &output).unwrap();
+            assert_eq!(hash, content_hash(&fs::read(&output).unwrap()));
+            hash
+        })
+        .collect();
+        assert_eq!(
+            hashes,
+            [
+                "06201632c9d6b4f16b6b2b257dc3af98aef4b940b8c59b1e81ece4b136a6815c",
+                "d5633135a78624417f163c919dc6c97c8b5b45b612458994bef551f5ece36f07",
+                "c94ac9d4a1239af66f6bcd7d263d1240a6ff03462b9c0317e906c9aca05b3f54",
+                "6c775943566b00db46cbb31fce8729ea494f6a0fbb41c91f5239a31ee18d5452",
+            ]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Minimal KTX2 writer for `TextureAsset`s: a single 2D image with its mip
+//! chain, no supercompression and no key/value data.
+
+use super::texture::{TextureAsset, TextureFormat};
+
+/// File extension of KTX2 containers.
+pub const KTX2_EXTENSION: &str = "ktx2";
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const HEADER_SIZE: usize = 80;
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+// Data format descriptor values from the Khronos Data Format Specification
+const MODEL_RGBSDA: u32 = 1;
+const MODEL_BC1A: u32 = 128;
+const MODEL_BC3: u32 = 130;
+const MODEL_BC5: u32 = 132;
+const MODEL_BC7: u32 = 134;
+const PRIMARIES_BT709: u32 = 1;
+const TRANSFER_LINEAR: u32 = 1;
+const TRANSFER_SRGB: u32 = 2;
+const CHANNEL_ALPHA: u8 = 15;
+const QUALIFIER_LINEAR: u8 = 0x10;
+const QUALIFIER_SIGNED: u8 = 0x40;
+const QUALIFIER_FLOAT: u8 = 0x80;
+
+fn vk_format(format: TextureFormat, srgb: bool) -> u32 {
+    let srgb = srgb && format.supports_srgb();
+    match (format, srgb) {
+        (TextureFormat::Rgba8, false) => 37,
+        (TextureFormat::Rgba8, true) => 43,
+        (TextureFormat::Rgba16Float, _) => 97,
+        (TextureFormat::Bc1, false) => 133,
+        (TextureFormat::Bc1, true) => 134,
+        (TextureFormat::Bc3, false) => 137,
+        (TextureFormat::Bc3, true) => 138,
+        (TextureFormat::Bc5, _) => 141,
+        (TextureFormat::Bc7, false) => 145,
+        (TextureFormat::Bc7, true) => 146,
+    }
+}
+
+/// One sample of the basic descriptor block.
+struct Sample {
+    bit_offset: u16,
+    bit_length: u8,
+    channel: u8,
+    lower: u32,
+    upper: u32,
+}
+
+/// The data format descriptor, including its leading total size.
+fn data_format_descriptor(format: TextureFormat, srgb: bool) -> Vec<u8> {
+    let srgb = srgb && format.supports_srgb();
+    // Alpha is never sRGB encoded
+    let alpha = CHANNEL_ALPHA | if srgb { QUALIFIER_LINEAR } else { 0 };
+    let compressed = |channel: u8, bit_offset: u16, bit_length: u8| Sample {
+        bit_offset,
+        bit_length,
+        channel,
+        lower: 0,
+        upper: u32::MAX,
+    };
+
+    let (model, samples) = match format {
+        TextureFormat::Rgba8 => (
+            MODEL_RGBSDA,
+            [0, 1, 2, alpha]
+                .into_iter()
+                .enumerate()
+                .map(|(i, channel)| Sample {
+                    bit_offset: i as u16 * 8,
+                    bit_length: 8,
+                    channel,
+                    lower: 0,
+                    upper: 255,
+                })
+                .collect(),
+        ),
+        TextureFormat::Rgba16Float => (
+            MODEL_RGBSDA,
+            [0, 1, 2, CHANNEL_ALPHA]
+                .into_iter()
+                .enumerate()
+                .map(|(i, channel)| Sample {
+                    bit_offset: i as u16 * 16,
+                    bit_length: 16,
+                    channel: channel | QUALIFIER_FLOAT | QUALIFIER_SIGNED,
+                    lower: (-1.0f32).to_bits(),
+                    upper: 1.0f32.to_bits(),
+                })
+                .collect(),
+        ),
+        TextureFormat::Bc1 => (MODEL_BC1A, vec![compressed(1, 0, 64)]),
+        TextureFormat::Bc3 => (
+            MODEL_BC3,
+            vec![compressed(alpha, 0, 64), compressed(0, 64, 64)],
+        ),
+        TextureFormat::Bc5 => (MODEL_BC5, vec![compressed(0, 0, 64), compressed(1, 64, 64)]),
+        TextureFormat::Bc7 => (MODEL_BC7, vec![compressed(0, 0, 128)]),
+    };
+
+    let block_dimensions: u32 = if format.is_compressed() { 0x0303 } else { 0 };
+    let transfer = if srgb { TRANSFER_SRGB } else { TRANSFER_LINEAR };
+    let block_size = 24 + 16 * samples.len() as u32;
+
+    let mut words = vec![
+        4 + block_size,
+        0,
+        2 | (block_size << 16),
+        model | (PRIMARIES_BT709 << 8) | (transfer << 16),
+        block_dimensions,
+        format.block_size() as u32,
+        0,
+    ];
+    for sample in samples {
+        words.extend([
+            sample.bit_offset as u32
+                | ((sample.bit_length as u32 - 1) << 16)
+                | ((sample.channel as u32) << 24),
+            0,
+            sample.lower,
+            sample.upper,
+        ]);
+    }
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+fn align(offset: usize, alignment: usize) -> usize {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// Encodes the texture as a KTX2 container.
+pub fn encode(texture: &TextureAsset) -> Vec<u8> {
+    let format = texture.format;
+    let levels = texture.mips.len();
+    let dfd = data_format_descriptor(format, texture.srgb);
+    let dfd_offset = HEADER_SIZE + levels * LEVEL_INDEX_ENTRY_SIZE;
+
+    // Levels are stored smallest first, each aligned to the least common
+    // multiple of the texel block size and 4, which is the block size for
+    // every format we write
+    let alignment = format.block_size();
+    let mut offsets = vec![0; levels];
+    let mut end = dfd_offset + dfd.len();
+    for level in (0..levels).rev() {
+        offsets[level] = align(end, alignment);
+        end = offsets[level] + texture.mips[level].len();
+    }
+
+    let mut bytes = Vec::with_capacity(end);
+    bytes.extend_from_slice(&IDENTIFIER);
+    for value in [
+        vk_format(format, texture.srgb),
+        if format == TextureFormat::Rgba16Float {
+            2
+        } else {
+            1
+        },
+        texture.width,
+        texture.height,
+        0,
+        0,
+        1,
+        levels as u32,
+        0,
+        dfd_offset as u32,
+        dfd.len() as u32,
+        0,
+        0,
+    ] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    // No supercompression global data
+    bytes.extend_from_slice(&[0; 16]);
+    for (offset, mip) in offsets.iter().zip(&texture.mips) {
+        for value in [*offset, mip.len(), mip.len()] {
+            bytes.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+    }
+    bytes.extend_from_slice(&dfd);
+
+    for level in (0..levels).rev() {
+        bytes.resize(offsets[level], 0);
+        bytes.extend_from_slice(&texture.mips[level]);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::texture::content_hash;
+
+    fn word(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn qword(bytes: &[u8], offset: usize) -> usize {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap()) as usize
+    }
+
+    /// A texture whose level `i` is filled with the byte `i + 1`.
+    fn texture(format: TextureFormat, srgb: bool, width: u32, height: u32) -> TextureAsset {
+        let levels = 32 - width.max(height).leading_zeros() as usize;
+        TextureAsset {
+            format,
+            srgb,
+            width,
+            height,
+            mips: (0..levels)
+                .map(|level| {
+                    let size = format.level_size((width >> level).max(1), (height >> level).max(1));
+                    vec![level as u8 + 1; size]
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn encodes_to_the_recorded_bytes() {
+        let hashes: Vec<_> = [
+            texture(TextureFormat::Rgba8, true, 8, 4),
+            texture(TextureFormat::Rgba16Float, false, 4, 4),
+            texture(TextureFormat::Bc1, true, 8, 8),
+            texture(TextureFormat::Bc5, false, 8, 8),
+            texture(TextureFormat::Bc7, false, 16, 8),
+        ]
+        .iter()
+        .map(|texture| content_hash(&encode(texture)))
+        .collect();
+        assert_eq!(
+            hashes,
+            [
+                "424edfade47d7e20bbd1376683967332d981ac7ca9819569d1ae26cc050d0bbd",
+                "40a02facdd2d1f2fec5df998f4d927345d189f934ffb57112c1a5f29bd60e934",
+                "6bf7634e8a52d8416cdd305d6872562c0fb82c695546482cb315726fe5cf5b38",
+                "7117c41f69c4e4966c6aea769ed42288c0bee2f967e93b659feb1c761d4abd7e",
+                "fbf0a5ca53de82dabd7cf7677c5cc3eb69a64d15ae3f3ed43f79cad4c4196350",
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_the_header_and_level_index() {
+        let texture = texture(TextureFormat::Bc7, true, 16, 8);
+        let bytes = encode(&texture);
+
+        assert_eq!(bytes[..12], IDENTIFIER);
+        assert_eq!(word(&bytes, 12), 146);
+        assert_eq!(word(&bytes, 16), 1);
+        assert_eq!((word(&bytes, 20), word(&bytes, 24)), (16, 8));
+        assert_eq!(word(&bytes, 40), 5);
+
+        // Levels are stored smallest first, aligned to the block size
+        let mut previous_offset = bytes.len();
+        for (level, mip) in texture.mips.iter().enumerate() {
+            let entry = HEADER_SIZE + level * LEVEL_INDEX_ENTRY_SIZE;
+            let (offset, length) = (qword(&bytes, entry), qword(&bytes, entry + 8));
+            assert_eq!(length, mip.len());
+            assert_eq!(qword(&bytes, entry + 16), mip.len());
+            assert_eq!(offset % 16, 0);
+            assert!(offset < previous_offset);
+            assert_eq!(&bytes[offset..offset + length], mip.as_slice());
+            previous_offset = offset;
+        }
+        let dfd_offset = word(&bytes, 48) as usize;
+        let dfd_length = word(&bytes, 52) as usize;
+        assert_eq!(dfd_offset, HEADER_SIZE + 5 * LEVEL_INDEX_ENTRY_SIZE);
+        assert!(dfd_offset + dfd_length <= previous_offset);
+        assert_eq!(word(&bytes, dfd_offset), dfd_length as u32);
+    }
+
+    #[test]
+    fn tags_only_formats_with_srgb_variants_as_srgb() {
+        let transfer = |format, srgb| {
+            let bytes = encode(&texture(format, srgb, 4, 4));
+            let dfd_offset = word(&bytes, 48) as usize;
+            (word(&bytes, 12), word(&bytes, dfd_offset + 12) >> 16 & 0xff)
+        };
+
+        assert_eq!(transfer(TextureFormat::Rgba8, true), (43, TRANSFER_SRGB));
+        assert_eq!(transfer(TextureFormat::Rgba8, false), (37, TRANSFER_LINEAR));
+        assert_eq!(transfer(TextureFormat::Bc3, true), (138, TRANSFER_SRGB));
+        assert_eq!(transfer(TextureFormat::Bc5, true), (141, TRANSFER_LINEAR));
+        assert_eq!(
+            transfer(TextureFormat::Rgba16Float, true),
+            (97, TRANSFER_LINEAR)
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use super::error::{AssetError, AssetResult};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// File extension of engine-native materials.
+pub const MATERIAL_EXTENSION: &str = "material";
+
+/// A metallic-roughness PBR material, the model glTF uses.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaterialAsset {
+    pub name: String,
+    /// Linear RGBA multiplier of the base color texture.
+    pub base_color: [f32; 4],
+    pub base_color_texture: Option<TextureRef>,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Metalness in the blue channel and roughness in the green channel.
+    pub metallic_roughness_texture: Option<TextureRef>,
+    pub normal_texture: Option<TextureRef>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<TextureRef>,
+    pub occlusion_strength: f32,
+    /// Linear RGB emission.
+    pub emissive: [f32; 3],
+    pub emissive_texture: Option<TextureRef>,
+    pub alpha_mode: AlphaMode,
+    /// Alpha below which fragments are discarded with `AlphaMode::Mask`.
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+impl Default for MaterialAsset {
+    /// The glTF default material.
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: [1.0; 4],
+            base_color_texture: None,
+            metallic: 1.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            occlusion_strength: 1.0,
+            emissive: [0.0; 3],
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl MaterialAsset {
+    pub fn save(&self, path: &Path) -> AssetResult<()> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        fs::write(path, text).map_err(AssetError::io(path))
+    }
+
+    pub fn load(path: &Path) -> AssetResult<Self> {
+        let text = fs::read_to_string(path).map_err(AssetError::io(path))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Every texture the material samples.
+    pub fn textures(&self) -> impl Iterator<Item = &TextureRef> {
+        [
+            &self.base_color_texture,
+            &self.metallic_roughness_texture,
+            &self.normal_texture,
+            &self.occlusion_texture,
+            &self.emissive_texture,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureRef {
+    /// Path of the source image, relative to the material file.
+    pub image: String,
+    /// Which UV set of the mesh to sample with.
+    #[serde(default)]
+    pub tex_coord: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    Mask,
+    Blend,
+}
@@ -0,0 +1,235 @@
+use super::error::{AssetError, AssetResult};
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// File extension of engine-native meshes.
+pub const MESH_EXTENSION: &str = "mesh";
+
+const MESH_MAGIC: [u8; 4] = *b"PMSH";
+const MESH_VERSION: u32 = 1;
+
+/// Vertex layout shared by every engine mesh. Unskinned meshes leave the
+/// joints and weights zeroed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    /// Tangent with the bitangent sign in `w`, as in glTF.
+    pub tangent: [f32; 4],
+    pub uv0: [f32; 2],
+    pub uv1: [f32; 2],
+    pub joints: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+/// A run of indices drawn with one material.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Submesh {
+    pub first_index: u32,
+    pub index_count: u32,
+    /// Index into the materials of the model the mesh was imported with.
+    pub material: Option<u32>,
+}
+
+/// An indexed triangle mesh.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshAsset {
+    pub name: String,
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<Submesh>,
+}
+
+impl MeshAsset {
+    pub fn is_skinned(&self) -> bool {
+        self.vertices
+            .iter()
+            .any(|vertex| vertex.weights.iter().any(|&weight| weight > 0.0))
+    }
+
+    /// Axis-aligned bounds as `(min, max)`, or `None` for an empty mesh.
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let mut positions = self
+            .vertices
+            .iter()
+            .map(|vertex| Vec3::from(vertex.position));
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), position| {
+            (min.min(position), max.max(position))
+        }))
+    }
+
+    /// Serializes the mesh to the engine's binary format: a small
+    /// little-endian header followed by the submesh table, the vertices and
+    /// the indices.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let vertices: &[u8] = bytemuck::cast_slice(&self.vertices);
+        let indices: &[u8] = bytemuck::cast_slice(&self.indices);
+        let mut bytes =
+            Vec::with_capacity(20 + self.submeshes.len() * 12 + vertices.len() + indices.len());
+
+        bytes.extend_from_slice(&MESH_MAGIC);
+        for value in [
+            MESH_VERSION,
+            self.vertices.len() as u32,
+            self.indices.len() as u32,
+            self.submeshes.len() as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for submesh in &self.submeshes {
+            bytes.extend_from_slice(&submesh.first_index.to_le_bytes());
+            bytes.extend_from_slice(&submesh.index_count.to_le_bytes());
+            bytes.extend_from_slice(&submesh.material.unwrap_or(u32::MAX).to_le_bytes());
+        }
+        bytes.extend_from_slice(vertices);
+        bytes.extend_from_slice(indices);
+        bytes
+    }
+
+    pub fn from_bytes(name: impl Into<String>, bytes: &[u8]) -> AssetResult<Self> {
+        let invalid = |reason: &str| AssetError::Invalid(format!("mesh {}", reason));
+        let mut reader = ByteReader(bytes);
+
+        if reader.take(4)? != MESH_MAGIC {
+            return Err(invalid("has an unknown header"));
+        }
+        let version = reader.u32()?;
+        if version != MESH_VERSION {
+            return Err(invalid(&format!("has unsupported version {}", version)));
+        }
+        let vertex_count = reader.u32()? as usize;
+        let index_count = reader.u32()? as usize;
+        let submesh_count = reader.u32()? as usize;
+
+        let mut submeshes = Vec::with_capacity(submesh_count);
+        for _ in 0..submesh_count {
+            let first_index = reader.u32()?;
+            let index_count = reader.u32()?;
+            let material = Some(reader.u32()?).filter(|&m| m != u32::MAX);
+            submeshes.push(Submesh {
+                first_index,
+                index_count,
+                material,
+            });
+        }
+
+        // The payload is not necessarily aligned, so copy it out
+        let vertices = reader
+            .take(vertex_count * std::mem::size_of::<MeshVertex>())?
+            .chunks_exact(std::mem::size_of::<MeshVertex>())
+            .map(bytemuck::pod_read_unaligned)
+            .collect();
+        let indices = reader
+            .take(index_count * 4)?
+            .chunks_exact(4)
+            .map(bytemuck::pod_read_unaligned)
+            .collect();
+
+        Ok(Self {
+            name: name.into(),
+            vertices,
+            indices,
+            submeshes,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> AssetResult<()> {
+        fs::write(path, self.to_bytes()).map_err(AssetError::io(path))
+    }
+
+    pub fn load(path: &Path) -> AssetResult<Self> {
+        let bytes = fs::read(path).map_err(AssetError::io(path))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::from_bytes(name, &bytes)
+    }
+}
+
+/// Cursor over a serialized mesh.
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> AssetResult<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(AssetError::Invalid("mesh is truncated".into()));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> AssetResult<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// Area-weighted smooth normals for the triangles in `indices`.
+pub fn compute_normals(vertices: &mut [MeshVertex], indices: &[u32]) {
+    let mut normals = vec![Vec3::ZERO; vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+        let [pa, pb, pc] = [a, b, c].map(|i| Vec3::from(vertices[i].position));
+        // Unnormalized, so larger triangles weigh more
+        let normal = (pb - pa).cross(pc - pa);
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        vertex.normal = normal.normalize_or_zero().into();
+    }
+}
+
+/// Per-vertex tangents derived from the `uv0` gradients of the triangles in
+/// `indices`, orthogonalized against the normals.
+pub fn compute_tangents(vertices: &mut [MeshVertex], indices: &[u32]) {
+    let mut tangents = vec![Vec3::ZERO; vertices.len()];
+    let mut bitangents = vec![Vec3::ZERO; vertices.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+        let [pa, pb, pc] = [a, b, c].map(|i| Vec3::from(vertices[i].position));
+        let [ta, tb, tc] = [a, b, c].map(|i| Vec2::from(vertices[i].uv0));
+
+        let (edge1, edge2) = (pb - pa, pc - pa);
+        let (duv1, duv2) = (tb - ta, tc - ta);
+        let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+        if determinant.abs() <= f32::EPSILON {
+            continue;
+        }
+
+        let r = 1.0 / determinant;
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+        for i in [a, b, c] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    for ((vertex, tangent), bitangent) in vertices.iter_mut().zip(tangents).zip(bitangents) {
+        let normal = Vec3::from(vertex.normal);
+        let tangent = (tangent - normal * normal.dot(tangent)).normalize_or_zero();
+        // Fall back to any direction perpendicular to the normal
+        let tangent = if tangent == Vec3::ZERO {
+            normal.any_orthonormal_vector()
+        } else {
+            tangent
+        };
+        let handedness = if normal.cross(tangent).dot(bitangent) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        vertex.tangent = tangent.extend(handedness).into();
+    }
+}
@@ -0,0 +1,38 @@
+pub mod animation;
+pub mod bc;
+pub mod database;
+pub mod error;
+pub mod import;
+pub mod ktx2;
+pub mod material;
+pub mod mesh;
+pub mod model;
+pub mod queue;
+pub mod texture;
+pub mod watcher;
+
+pub use database::{AssetDatabase, AssetRecord};
+pub use error::AssetError;
+pub use material::MaterialAsset;
+pub use mesh::{MeshAsset, MeshVertex};
+pub use model::{ImportedModel, ModelAsset};
+pub use queue::{ImportQueue, ASSET_IMPORT_EVENT};
+pub use texture::TextureAsset;
+pub use watcher::{AssetWatcher, ASSET_CHANGED_EVENT};
+
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
+
+/// Database shared between the asset commands and import jobs, `None`
+/// until a project is opened.
+pub type SharedAssetDatabase = Arc<RwLock<Option<AssetDatabase>>>;
+
+/// Tauri state shared by the asset commands.
+#[derive(Default)]
+pub struct AssetState {
+    /// Runs texture imports off the command threads.
+    pub queue: ImportQueue,
+    pub database: SharedAssetDatabase,
+    /// Watches the open project for changes made outside the editor.
+    pub watcher: Mutex<Option<AssetWatcher>>,
+}
@@ -0,0 +1,244 @@
+use super::{
+    animation::{AnimationClip, Skin, ANIMATION_EXTENSION},
+    error::{AssetError, AssetResult},
+    material::{MaterialAsset, MATERIAL_EXTENSION},
+    mesh::{MeshAsset, MESH_EXTENSION},
+};
+use crate::scene::{error::SceneResult, Component, EntityId, Transform, World};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File extension of engine-native models.
+pub const MODEL_EXTENSION: &str = "model";
+
+/// A node of a model's hierarchy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelNode {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<usize>,
+    #[serde(default)]
+    pub transform: Transform,
+    /// Index into the model's meshes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<usize>,
+    /// Index into the model's skins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skin: Option<usize>,
+}
+
+/// An image a material refers to, as found in the source file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageSource {
+    /// An image file next to the source.
+    File(PathBuf),
+    /// Encoded image data stored inside the source.
+    Embedded(Vec<u8>),
+}
+
+/// Everything read from a model source file, before it is written out as
+/// engine assets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedModel {
+    pub name: String,
+    pub nodes: Vec<ModelNode>,
+    /// Top-level nodes in order.
+    pub roots: Vec<usize>,
+    pub meshes: Vec<MeshAsset>,
+    /// Texture references of the materials hold the file names `images`
+    /// are written under.
+    pub materials: Vec<MaterialAsset>,
+    pub images: Vec<(String, ImageSource)>,
+    pub skins: Vec<Skin>,
+    pub animations: Vec<AnimationClip>,
+}
+
+/// The engine-native description of an imported model. Meshes, materials,
+/// images and animations are separate files in the same directory, listed
+/// by file name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelAsset {
+    pub name: String,
+    /// File the model was imported from, relative to the project root if
+    /// it is inside the project.
+    pub source: PathBuf,
+    pub nodes: Vec<ModelNode>,
+    pub roots: Vec<usize>,
+    pub meshes: Vec<String>,
+    pub materials: Vec<String>,
+    pub images: Vec<String>,
+    pub skins: Vec<Skin>,
+    pub animations: Vec<String>,
+}
+
+impl ImportedModel {
+    /// Writes the model and its parts into `dir`, creating it if needed.
+    /// Returns the model description, also written as
+    /// `<name>.model` in `dir`. `source` is recorded as given, so callers
+    /// pass it project relative.
+    pub fn write(&self, source: &Path, dir: &Path) -> AssetResult<ModelAsset> {
+        fs::create_dir_all(dir).map_err(AssetError::io(dir))?;
+        let mut names = FileNames::default();
+        let model_file = names.reserve(&self.name, MODEL_EXTENSION);
+
+        let mut meshes = Vec::with_capacity(self.meshes.len());
+        for mesh in &self.meshes {
+            let file = names.reserve(&mesh.name, MESH_EXTENSION);
+            mesh.save(&dir.join(&file))?;
+            meshes.push(file);
+        }
+
+        let mut images = Vec::with_capacity(self.images.len());
+        for (file, image) in &self.images {
+            let path = dir.join(file);
+            match image {
+                ImageSource::File(from) => {
+                    fs::copy(from, &path).map_err(AssetError::io(from))?;
+                }
+                ImageSource::Embedded(bytes) => {
+                    fs::write(&path, bytes).map_err(AssetError::io(&path))?;
+                }
+            }
+            images.push(file.clone());
+        }
+
+        let mut materials = Vec::with_capacity(self.materials.len());
+        for material in &self.materials {
+            let file = names.reserve(&material.name, MATERIAL_EXTENSION);
+            material.save(&dir.join(&file))?;
+            materials.push(file);
+        }
+
+        let mut animations = Vec::with_capacity(self.animations.len());
+        for animation in &self.animations {
+            let file = names.reserve(&animation.name, ANIMATION_EXTENSION);
+            animation.save(&dir.join(&file))?;
+            animations.push(file);
+        }
+
+        let model = ModelAsset {
+            name: self.name.clone(),
+            source: source.to_path_buf(),
+            nodes: self.nodes.clone(),
+            roots: self.roots.clone(),
+            meshes,
+            materials,
+            images,
+            skins: self.skins.clone(),
+            animations,
+        };
+        model.save(&dir.join(model_file))?;
+
+        info!(
+            "Wrote model '{}' to {}: {} meshes, {} materials, {} images, {} animations",
+            model.name,
+            dir.display(),
+            model.meshes.len(),
+            model.materials.len(),
+            model.images.len(),
+            model.animations.len()
+        );
+        Ok(model)
+    }
+}
+
+impl ModelAsset {
+    /// Name of the file `ImportedModel::write` stores the model in.
+    pub fn file_name(&self) -> String {
+        // The model file is the first name reserved in its directory
+        FileNames::default().reserve(&self.name, MODEL_EXTENSION)
+    }
+
+    pub fn save(&self, path: &Path) -> AssetResult<()> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        fs::write(path, text).map_err(AssetError::io(path))
+    }
+
+    pub fn load(path: &Path) -> AssetResult<Self> {
+        let text = fs::read_to_string(path).map_err(AssetError::io(path))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Adds the node hierarchy to `world` under a new root entity named
+    /// after the model. Nodes with a mesh get a mesh component referring to
+    /// the mesh file in `dir`, which is the model's directory relative to
+    /// the project root so scenes stay valid when the project moves.
+    pub fn instantiate(
+        &self,
+        world: &mut World,
+        dir: &Path,
+        parent: Option<EntityId>,
+    ) -> SceneResult<EntityId> {
+        let root = world.spawn(self.name.clone(), parent)?;
+
+        let mut stack: Vec<(usize, EntityId)> =
+            self.roots.iter().rev().map(|&node| (node, root)).collect();
+        // Malformed files can list a node twice or form cycles
+        let mut visited = HashSet::new();
+        while let Some((index, parent)) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                continue;
+            };
+            if !visited.insert(index) {
+                continue;
+            }
+
+            let id = world.spawn(node.name.clone(), Some(parent))?;
+            world.set_transform(id, node.transform)?;
+            if let Some(mesh) = node.mesh.and_then(|mesh| self.meshes.get(mesh)) {
+                let mesh = dir.join(mesh).to_string_lossy().into_owned();
+                world.set_component(id, Component::Mesh { mesh })?;
+            }
+            stack.extend(node.children.iter().rev().map(|&child| (child, id)));
+        }
+        Ok(root)
+    }
+}
+
+/// Hands out file names that are unique within one output directory.
+#[derive(Default)]
+pub(crate) struct FileNames {
+    taken: HashSet<String>,
+}
+
+impl FileNames {
+    pub(crate) fn reserve(&mut self, name: &str, extension: &str) -> String {
+        let stem = sanitize(name);
+        let mut file = format!("{}.{}", stem, extension);
+        let mut suffix = 1;
+        while self.taken.contains(&file.to_lowercase()) {
+            suffix += 1;
+            file = format!("{}-{}.{}", stem, suffix, extension);
+        }
+        self.taken.insert(file.to_lowercase());
+        file
+    }
+}
+
+/// Keeps characters that are safe in file names on every platform.
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | ' ' => c,
+            _ => '_',
+        })
+        .collect();
+    let name = name.trim_matches(|c| c == '.' || c == ' ');
+    if name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,174 @@
+use super::error::AssetError;
+use crossbeam_channel::{unbounded, Sender};
+use log::{debug, error};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Name of the Tauri event import jobs report `ImportEvent`s on.
+pub const ASSET_IMPORT_EVENT: &str = "asset-import";
+
+/// Most workers the queue starts, however many cores there are.
+const MAX_WORKERS: usize = 4;
+
+pub type JobId = u64;
+
+/// Progress of an import job, sent to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ImportEvent {
+    /// `progress` is the completed fraction, from 0 to 1.
+    Progress {
+        progress: f32,
+    },
+    /// The job wrote `output`, whose content hashes to `hash`.
+    Finished {
+        output: PathBuf,
+        hash: String,
+    },
+    Failed {
+        code: &'static str,
+        message: String,
+    },
+}
+
+impl ImportEvent {
+    pub fn failed(error: &AssetError) -> Self {
+        ImportEvent::Failed {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Payload of `ASSET_IMPORT_EVENT`: the event and the job it concerns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEventPayload {
+    pub job: JobId,
+    pub source: PathBuf,
+    #[serde(flatten)]
+    pub event: ImportEvent,
+}
+
+/// Callback import jobs report `ImportEvent`s through.
+pub type ImportSink = Arc<dyn Fn(ImportEventPayload) + Send + Sync>;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A pool of worker threads running imports in submission order, so long
+/// imports never block the Tauri command threads.
+pub struct ImportQueue {
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    next_id: AtomicU64,
+}
+
+impl ImportQueue {
+    /// Starts `workers` threads, or one per core up to `MAX_WORKERS` if
+    /// `None`.
+    pub fn new(workers: Option<usize>) -> Self {
+        let count = workers
+            .unwrap_or_else(|| {
+                thread::available_parallelism()
+                    .map_or(1, |cores| cores.get())
+                    .min(MAX_WORKERS)
+            })
+            .max(1);
+        let (sender, receiver) = unbounded::<Job>();
+
+        let workers = (0..count)
+            .filter_map(|index| {
+                let receiver = receiver.clone();
+                thread::Builder::new()
+                    .name(format!("asset-import-{}", index))
+                    .spawn(move || {
+                        // Ends once the queue is dropped and drained
+                        for job in receiver {
+                            job();
+                        }
+                    })
+                    .map_err(|e| error!("Failed to start import worker: {}", e))
+                    .ok()
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Queues `job`, which receives a reporter for its events. Returns the
+    /// id the events are tagged with.
+    pub fn submit(
+        &self,
+        source: PathBuf,
+        sink: ImportSink,
+        job: impl FnOnce(&ImportReporter) + Send + 'static,
+    ) -> JobId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let reporter = ImportReporter { id, source, sink };
+        debug!(
+            "Queueing import job {} for {}",
+            id,
+            reporter.source.display()
+        );
+
+        if let Some(sender) = &self.sender {
+            if sender.send(Box::new(move || job(&reporter))).is_ok() {
+                return id;
+            }
+        }
+        // Only reachable while shutting down
+        error!("Import queue is closed, dropping job {}", id);
+        id
+    }
+}
+
+impl Default for ImportQueue {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Drop for ImportQueue {
+    fn drop(&mut self) {
+        self.sender = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Reports the events of one import job.
+pub struct ImportReporter {
+    id: JobId,
+    source: PathBuf,
+    sink: ImportSink,
+}
+
+impl ImportReporter {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn report(&self, event: ImportEvent) {
+        (self.sink)(ImportEventPayload {
+            job: self.id,
+            source: self.source.clone(),
+            event,
+        });
+    }
+
+    pub fn progress(&self, progress: f32) {
+        self.report(ImportEvent::Progress { progress });
+    }
+}
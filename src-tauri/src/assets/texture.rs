@@ -0,0 +1,202 @@
+use super::error::{AssetError, AssetResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{fs, path::Path};
+
+/// File extension of engine-native textures.
+pub const TEXTURE_EXTENSION: &str = "texture";
+
+const TEXTURE_MAGIC: [u8; 4] = *b"PTEX";
+const TEXTURE_VERSION: u32 = 1;
+
+/// Pixel storage of a texture. Whether color formats hold sRGB or linear
+/// values is tracked separately by `TextureAsset::srgb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextureFormat {
+    Rgba8,
+    /// Half-float RGBA, for HDR images.
+    Rgba16Float,
+    /// RGB with 1-bit alpha, 8 bytes per 4x4 block.
+    Bc1,
+    /// RGBA, 16 bytes per block.
+    Bc3,
+    /// Two channels, for normal maps. 16 bytes per block.
+    Bc5,
+    /// High quality RGBA, 16 bytes per block.
+    Bc7,
+}
+
+impl TextureFormat {
+    pub fn is_compressed(self) -> bool {
+        !matches!(self, TextureFormat::Rgba8 | TextureFormat::Rgba16Float)
+    }
+
+    /// Bytes per pixel, or per 4x4 block for compressed formats.
+    pub fn block_size(self) -> usize {
+        match self {
+            TextureFormat::Rgba8 => 4,
+            TextureFormat::Rgba16Float | TextureFormat::Bc1 => 8,
+            TextureFormat::Bc3 | TextureFormat::Bc5 | TextureFormat::Bc7 => 16,
+        }
+    }
+
+    /// Size in bytes of one mip level.
+    pub fn level_size(self, width: u32, height: u32) -> usize {
+        let (width, height) = (width as usize, height as usize);
+        if self.is_compressed() {
+            ((width + 3) / 4) * ((height + 3) / 4) * self.block_size()
+        } else {
+            width * height * self.block_size()
+        }
+    }
+
+    /// Whether the format can hold sRGB encoded values.
+    pub fn supports_srgb(self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Rgba8 | TextureFormat::Bc1 | TextureFormat::Bc3 | TextureFormat::Bc7
+        )
+    }
+
+    pub fn wgpu_format(self, srgb: bool) -> wgpu::TextureFormat {
+        use wgpu::TextureFormat as F;
+        let srgb = srgb && self.supports_srgb();
+        match (self, srgb) {
+            (TextureFormat::Rgba8, false) => F::Rgba8Unorm,
+            (TextureFormat::Rgba8, true) => F::Rgba8UnormSrgb,
+            (TextureFormat::Rgba16Float, _) => F::Rgba16Float,
+            (TextureFormat::Bc1, false) => F::Bc1RgbaUnorm,
+            (TextureFormat::Bc1, true) => F::Bc1RgbaUnormSrgb,
+            (TextureFormat::Bc3, false) => F::Bc3RgbaUnorm,
+            (TextureFormat::Bc3, true) => F::Bc3RgbaUnormSrgb,
+            (TextureFormat::Bc5, _) => F::Bc5RgUnorm,
+            (TextureFormat::Bc7, false) => F::Bc7RgbaUnorm,
+            (TextureFormat::Bc7, true) => F::Bc7RgbaUnormSrgb,
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            TextureFormat::Rgba8 => 0,
+            TextureFormat::Rgba16Float => 1,
+            TextureFormat::Bc1 => 2,
+            TextureFormat::Bc3 => 3,
+            TextureFormat::Bc5 => 4,
+            TextureFormat::Bc7 => 5,
+        }
+    }
+
+    fn from_u32(value: u32) -> Option<Self> {
+        Some(match value {
+            0 => TextureFormat::Rgba8,
+            1 => TextureFormat::Rgba16Float,
+            2 => TextureFormat::Bc1,
+            3 => TextureFormat::Bc3,
+            4 => TextureFormat::Bc5,
+            5 => TextureFormat::Bc7,
+            _ => return None,
+        })
+    }
+}
+
+/// A 2D texture with its full mip chain, ready for upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureAsset {
+    pub format: TextureFormat,
+    /// Color values are sRGB encoded and are decoded when sampled.
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    /// Level data from the full size image down to 1x1.
+    pub mips: Vec<Vec<u8>>,
+}
+
+impl TextureAsset {
+    /// Size of mip `level`.
+    pub fn level_extent(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// Serializes to the engine's binary format: a little-endian header,
+    /// then each level's byte length and data.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let data: usize = self.mips.iter().map(|mip| mip.len() + 4).sum();
+        let mut bytes = Vec::with_capacity(28 + data);
+        bytes.extend_from_slice(&TEXTURE_MAGIC);
+        for value in [
+            TEXTURE_VERSION,
+            self.format.to_u32(),
+            self.srgb as u32,
+            self.width,
+            self.height,
+            self.mips.len() as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for mip in &self.mips {
+            bytes.extend_from_slice(&(mip.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(mip);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> AssetResult<Self> {
+        let invalid = |reason: &str| AssetError::Invalid(format!("texture {}", reason));
+        let mut words = bytes
+            .get(4..28)
+            .filter(|_| bytes[..4] == TEXTURE_MAGIC)
+            .ok_or_else(|| invalid("has an unknown header"))?
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]));
+        let mut next = || words.next().unwrap_or_default();
+
+        let version = next();
+        if version != TEXTURE_VERSION {
+            return Err(invalid(&format!("has unsupported version {}", version)));
+        }
+        let format =
+            TextureFormat::from_u32(next()).ok_or_else(|| invalid("has an unknown format"))?;
+        let srgb = next() != 0;
+        let (width, height, levels) = (next(), next(), next());
+
+        let mut mips = Vec::with_capacity(levels.min(32) as usize);
+        let mut rest = &bytes[28..];
+        for _ in 0..levels {
+            let len = rest
+                .get(..4)
+                .map(|len| u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+                .ok_or_else(|| invalid("is truncated"))?;
+            let mip = rest
+                .get(4..4 + len)
+                .ok_or_else(|| invalid("is truncated"))?;
+            mips.push(mip.to_vec());
+            rest = &rest[4 + len..];
+        }
+
+        Ok(Self {
+            format,
+            srgb,
+            width,
+            height,
+            mips,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> AssetResult<()> {
+        fs::write(path, self.to_bytes()).map_err(AssetError::io(path))
+    }
+
+    pub fn load(path: &Path) -> AssetResult<Self> {
+        let bytes = fs::read(path).map_err(AssetError::io(path))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`, used to identify import results.
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
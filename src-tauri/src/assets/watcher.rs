@@ -0,0 +1,180 @@
+use super::{
+    database::{is_ignored, AssetChanged},
+    error::{AssetError, AssetResult},
+    queue::JobId,
+};
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError};
+use log::{debug, info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Name of the Tauri event emitted for every asset changed on disk, with
+/// an `AssetChangedPayload`.
+pub const ASSET_CHANGED_EVENT: &str = "asset-changed";
+
+/// How long the project must be quiet before changes are handled, so an
+/// editor saving a file in several writes triggers a single reimport.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Longest changes wait for the project to go quiet. A file written
+/// continuously, like a log or a render in progress, is still handled this
+/// often.
+const MAX_LATENCY: Duration = Duration::from_secs(2);
+
+/// Payload of `ASSET_CHANGED_EVENT`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetChangedPayload {
+    #[serde(flatten)]
+    pub asset: AssetChanged,
+    /// Import job reimporting the asset, reported on `ASSET_IMPORT_EVENT`.
+    pub reimport: Option<JobId>,
+}
+
+/// Watches a directory tree and hands changed paths to a callback in
+/// batches, once changes have settled for `DEBOUNCE` or at most
+/// `MAX_LATENCY` after the first one.
+pub struct AssetWatcher {
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AssetWatcher {
+    /// Starts watching `root`. `on_change` runs on the watcher's thread with
+    /// the absolute paths that were created, modified, removed or renamed.
+    pub fn start(
+        root: &Path,
+        mut on_change: impl FnMut(Vec<PathBuf>) + Send + 'static,
+    ) -> AssetResult<Self> {
+        let (sender, receiver) = unbounded::<Vec<PathBuf>>();
+        let prefix = root.to_path_buf();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(mut event) => {
+                    // Skip files assets never live in, such as the editor's own log
+                    event.paths.retain(|path| {
+                        path.strip_prefix(&prefix)
+                            .map_or(true, |relative| !is_ignored(relative))
+                    });
+                    if !event.paths.is_empty() && !matches!(event.kind, EventKind::Access(_)) {
+                        let _ = sender.send(event.paths);
+                    }
+                }
+                Err(e) => warn!("File watcher error: {}", e),
+            })?;
+        watcher.watch(root, RecursiveMode::Recursive)?;
+
+        let thread = thread::Builder::new()
+            .name("asset-watcher".into())
+            .spawn(move || {
+                // Ends once the watcher is dropped and its sender with it
+                while let Some(batch) = next_batch(&receiver, DEBOUNCE, MAX_LATENCY) {
+                    debug!("{} paths changed", batch.len());
+                    on_change(batch.into_iter().collect());
+                }
+            })
+            .map_err(AssetError::io(root))?;
+
+        info!("Watching {} for changes", root.display());
+        Ok(Self {
+            watcher: Some(watcher),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Waits for changes and collects them until none arrive for `debounce`, or
+/// until `max_latency` passed since the first. `None` once the sender is
+/// gone.
+fn next_batch(
+    receiver: &Receiver<Vec<PathBuf>>,
+    debounce: Duration,
+    max_latency: Duration,
+) -> Option<BTreeSet<PathBuf>> {
+    let mut batch: BTreeSet<PathBuf> = receiver.recv().ok()?.into_iter().collect();
+    let deadline = Instant::now() + max_latency;
+    loop {
+        let timeout = debounce.min(deadline.saturating_duration_since(Instant::now()));
+        match receiver.recv_timeout(timeout) {
+            Ok(paths) => batch.extend(paths),
+            Err(RecvTimeoutError::Timeout) => return Some(batch),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
+
+impl Drop for AssetWatcher {
+    fn drop(&mut self) {
+        self.watcher = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEBOUNCE: Duration = Duration::from_millis(50);
+    const MAX_LATENCY: Duration = Duration::from_millis(300);
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn batches_changes_until_they_settle() {
+        let (sender, receiver) = unbounded();
+        sender.send(paths(&["b.png", "a.png"])).unwrap();
+        sender.send(paths(&["a.png", "c.png"])).unwrap();
+
+        let batch = next_batch(&receiver, DEBOUNCE, MAX_LATENCY).unwrap();
+        assert_eq!(
+            batch.into_iter().collect::<Vec<_>>(),
+            paths(&["a.png", "b.png", "c.png"])
+        );
+    }
+
+    #[test]
+    fn flushes_continuous_changes_after_the_maximum_latency() {
+        let (sender, receiver) = unbounded();
+        let writer = thread::spawn(move || {
+            // Keeps writing well past the maximum latency
+            for _ in 0..200 {
+                if sender.send(paths(&["render.png"])).is_err() {
+                    return;
+                }
+                thread::sleep(DEBOUNCE / 5);
+            }
+        });
+
+        let start = Instant::now();
+        let batch = next_batch(&receiver, DEBOUNCE, MAX_LATENCY).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(batch.len(), 1);
+        assert!(elapsed >= MAX_LATENCY, "{:?}", elapsed);
+        assert!(elapsed < MAX_LATENCY * 3, "{:?}", elapsed);
+
+        drop(receiver);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn stops_once_the_sender_is_gone() {
+        let (sender, receiver) = unbounded();
+        sender.send(paths(&["a.png"])).unwrap();
+        drop(sender);
+        assert_eq!(next_batch(&receiver, DEBOUNCE, MAX_LATENCY), None);
+
+        let (sender, receiver) = unbounded::<Vec<PathBuf>>();
+        drop(sender);
+        assert_eq!(next_batch(&receiver, DEBOUNCE, MAX_LATENCY), None);
+    }
+}
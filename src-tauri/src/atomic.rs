@@ -0,0 +1,143 @@
+//! Atomic file replacement for everything the editor saves.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// Writes `contents` to `path`, replacing the file atomically: readers see
+/// either the old file or the whole new one, and a failed write leaves the
+/// old file untouched.
+///
+/// The data is written to a temporary file beside `path`, created
+/// exclusively under an unpredictable name so neither a concurrent save nor
+/// a file planted at a guessable name can interfere, and then renamed over
+/// `path`. A replaced file keeps its permissions.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    // Hidden and ending in `.tmp`, which the asset watcher ignores
+    let prefix = format!(
+        ".{}.",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(".tmp");
+    #[cfg(unix)]
+    {
+        // Like `fs::write`, before the umask; temporary files default to 0600
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+
+    let mut temp = builder.tempfile_in(dir)?;
+    temp.write_all(contents.as_ref())?;
+    temp.as_file().sync_all()?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp.path(), metadata.permissions())?;
+    }
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A scratch directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("pulsar-atomic-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn entries(&self) -> Vec<String> {
+            let mut names: Vec<_> = fs::read_dir(&self.0)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn creates_and_replaces_files_without_leaving_temporaries() {
+        let dir = TempDir::new("replace");
+        let path = dir.0.join("level.scene");
+
+        write(&path, "first").unwrap();
+        write(&path, "second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(dir.entries(), ["level.scene"]);
+    }
+
+    #[test]
+    fn ignores_a_file_at_the_old_temporary_name() {
+        let dir = TempDir::new("planted");
+        let path = dir.0.join("tree.bt.json");
+        // Saves used to go through `<name>.tmp`, which anyone could create first
+        fs::create_dir(dir.0.join("tree.bt.json.tmp")).unwrap();
+
+        write(&path, "{}").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+        assert!(dir.0.join("tree.bt.json.tmp").is_dir());
+    }
+
+    #[test]
+    fn leaves_the_file_alone_when_the_write_fails() {
+        let dir = TempDir::new("failed");
+        let path = dir.0.join("scripts");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("main.rs"), "fn main() {}").unwrap();
+
+        // A directory cannot be replaced by a file
+        assert!(write(&path, "data").is_err());
+
+        assert_eq!(
+            fs::read_to_string(path.join("main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert_eq!(dir.entries(), ["scripts"]);
+    }
+
+    #[test]
+    fn fails_without_a_parent_directory() {
+        let dir = TempDir::new("missing");
+        let path = dir.0.join("missing").join("file.txt");
+        let error = write(&path, "data").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_the_permissions_of_a_replaced_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("permissions");
+        let path = dir.0.join("run.sh");
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        write(&path, "#!/bin/sh\necho hi\n").unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+}
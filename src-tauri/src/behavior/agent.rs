@@ -0,0 +1,345 @@
+//! Agents ticking behavior trees in the editor, and the stream of what
+//! their trees do for the one being debugged.
+
+use super::{
+    asset,
+    blackboard::{Blackboard, Value},
+    error::{BehaviorError, BehaviorResult},
+    registry::NodeRegistry,
+    tree::BehaviorTree,
+    Status,
+};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use indexmap::IndexMap;
+use log::{debug, info};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Name of the Tauri event the debugged agent reports a `TickTrace` on
+/// after each tick.
+pub const BEHAVIOR_DEBUG_EVENT: &str = "behavior-debug";
+
+/// Time between the ticks of agents, which is also the delta they tick
+/// with, so runs do not depend on how busy the machine is.
+pub const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+pub type AgentId = u64;
+
+/// How a blackboard changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackboardDiff {
+    /// Values added or changed.
+    pub set: IndexMap<String, Value>,
+    pub removed: Vec<String>,
+}
+
+impl BlackboardDiff {
+    pub fn between(before: &Blackboard, after: &Blackboard) -> Self {
+        let set = after
+            .iter()
+            .filter(|(key, value)| before.value(key) != Some(*value))
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        let removed = before
+            .iter()
+            .filter(|(key, _)| !after.contains_key(key))
+            .map(|(key, _)| key.to_string())
+            .collect();
+        Self { set, removed }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// What one tick of the debugged agent did. The first trace after an agent
+/// is debugged holds its whole blackboard.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickTrace {
+    pub agent: AgentId,
+    /// Number of the tick, from 1.
+    pub tick: u64,
+    /// Time of the tree in seconds.
+    pub time: f64,
+    /// What the root returned.
+    pub status: Status,
+    /// What the nodes ticked returned, by asset id. Running nodes form the
+    /// active branch.
+    pub nodes: IndexMap<String, Status>,
+    pub blackboard: BlackboardDiff,
+}
+
+/// Callback the debugged agent reports its `TickTrace`s through.
+pub type TraceSink = Arc<dyn Fn(TickTrace) + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentInfo {
+    pub id: AgentId,
+    pub name: String,
+    /// Tree file the agent was spawned from.
+    pub path: PathBuf,
+}
+
+enum Control {
+    Debug(bool),
+    Stop,
+}
+
+/// A behavior tree ticking on a thread of its own. Dropping the agent stops
+/// it.
+pub struct BehaviorAgent {
+    info: AgentInfo,
+    control: Sender<Control>,
+}
+
+impl BehaviorAgent {
+    /// Starts ticking `tree` every `TICK_INTERVAL`, reporting to `sink`
+    /// while debugged.
+    pub fn spawn(info: AgentInfo, tree: BehaviorTree, sink: TraceSink) -> BehaviorResult<Self> {
+        let (control, receiver) = crossbeam_channel::unbounded();
+        let id = info.id;
+        thread::Builder::new()
+            .name(format!("behavior-agent-{}", id))
+            .spawn(move || {
+                run(id, tree, receiver, sink);
+                debug!("Behavior agent {} stopped", id);
+            })
+            .map_err(BehaviorError::Spawn)?;
+        Ok(Self { info, control })
+    }
+
+    pub fn info(&self) -> &AgentInfo {
+        &self.info
+    }
+
+    /// Starts or stops reporting the agent's ticks.
+    pub fn set_debugged(&self, debugged: bool) {
+        let _ = self.control.send(Control::Debug(debugged));
+    }
+
+    pub fn stop(&self) {
+        let _ = self.control.send(Control::Stop);
+    }
+}
+
+impl Drop for BehaviorAgent {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run(id: AgentId, mut tree: BehaviorTree, receiver: Receiver<Control>, sink: TraceSink) {
+    // Blackboard as the last trace left it, `None` while not debugged
+    let mut shown: Option<Blackboard> = None;
+    let mut next = Instant::now();
+    loop {
+        // After a stall, like the machine sleeping, tick once and go on
+        // from now rather than catching up on every missed tick
+        next = (next + TICK_INTERVAL).max(Instant::now());
+        loop {
+            let timeout = next.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(Control::Debug(debugged)) => shown = debugged.then(Blackboard::new),
+                Ok(Control::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+
+        let status = tree.tick(TICK_INTERVAL);
+        if let Some(shown) = &mut shown {
+            let nodes = tree
+                .statuses()
+                .iter()
+                .enumerate()
+                .filter_map(|(index, status)| Some((tree.node_id(index)?.to_string(), (*status)?)))
+                .collect();
+            let blackboard = BlackboardDiff::between(shown, tree.blackboard());
+            *shown = tree.blackboard().clone();
+            sink(TickTrace {
+                agent: id,
+                tick: tree.ticks(),
+                time: tree.now().as_secs_f64(),
+                status,
+                nodes,
+                blackboard,
+            });
+        }
+    }
+}
+
+/// Tauri state holding the agents ticking in the editor.
+pub struct AgentState {
+    agents: Mutex<IndexMap<AgentId, BehaviorAgent>>,
+    debugged: Mutex<Option<AgentId>>,
+    next_id: AtomicU64,
+    registry: RwLock<NodeRegistry>,
+}
+
+impl Default for AgentState {
+    fn default() -> Self {
+        Self {
+            agents: Mutex::default(),
+            debugged: Mutex::default(),
+            next_id: AtomicU64::default(),
+            registry: RwLock::new(NodeRegistry::with_builtins()),
+        }
+    }
+}
+
+impl AgentState {
+    /// Adds actions and conditions for the trees of agents spawned from now
+    /// on.
+    pub fn register(&self, f: impl FnOnce(&mut NodeRegistry)) {
+        f(&mut self.registry.write());
+    }
+
+    /// The actions and conditions trees of agents can use.
+    pub fn registry(&self) -> RwLockReadGuard<'_, NodeRegistry> {
+        self.registry.read()
+    }
+
+    /// Loads the tree at `path` and spawns an agent ticking it, which
+    /// reports to `sink` while debugged.
+    pub fn spawn(&self, path: &Path, sink: TraceSink) -> BehaviorResult<AgentInfo> {
+        let asset = asset::load(path)?;
+        let tree = BehaviorTree::from_asset(&asset, &self.registry.read())?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let info = AgentInfo {
+            id,
+            name: format!("{} #{}", stem, id),
+            path: path.to_path_buf(),
+        };
+        let agent = BehaviorAgent::spawn(info.clone(), tree, sink)?;
+        self.agents.lock().insert(id, agent);
+
+        info!("Spawned behavior agent {}", info.name);
+        Ok(info)
+    }
+
+    /// Stops an agent and removes it.
+    pub fn despawn(&self, id: AgentId) -> BehaviorResult<()> {
+        let agent = self
+            .agents
+            .lock()
+            .shift_remove(&id)
+            .ok_or(BehaviorError::UnknownAgent(id))?;
+        let mut debugged = self.debugged.lock();
+        if *debugged == Some(id) {
+            *debugged = None;
+        }
+        info!("Despawned behavior agent {}", agent.info().name);
+        Ok(())
+    }
+
+    /// The agents, in the order they were spawned.
+    pub fn agents(&self) -> Vec<AgentInfo> {
+        let agents = self.agents.lock();
+        agents.values().map(|agent| agent.info().clone()).collect()
+    }
+
+    /// Debugs an agent, the one debugged before no longer reporting its
+    /// ticks, or stops debugging if `id` is `None`.
+    pub fn debug(&self, id: Option<AgentId>) -> BehaviorResult<()> {
+        let agents = self.agents.lock();
+        if let Some(id) = id {
+            if !agents.contains_key(&id) {
+                return Err(BehaviorError::UnknownAgent(id));
+            }
+        }
+
+        let mut debugged = self.debugged.lock();
+        if let Some(agent) = debugged.and_then(|previous| agents.get(&previous)) {
+            agent.set_debugged(false);
+        }
+        if let Some(agent) = id.and_then(|id| agents.get(&id)) {
+            agent.set_debugged(true);
+        }
+        *debugged = id;
+        Ok(())
+    }
+
+    /// Stops every agent, when the editor closes.
+    pub fn shutdown(&self) {
+        let agents: Vec<_> = self.agents.lock().drain(..).collect();
+        *self.debugged.lock() = None;
+        if !agents.is_empty() {
+            info!("Stopping {} behavior agents", agents.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::Node;
+
+    #[test]
+    fn diffs_report_values_set_and_removed() {
+        let mut before = Blackboard::new();
+        before.set("health", 100).unwrap();
+        before.set("target", "door").unwrap();
+        before.set("alert", false).unwrap();
+        let mut after = before.clone();
+        after.set("health", 80).unwrap();
+        after.remove("target");
+        after.set("seen", true).unwrap();
+
+        let diff = BlackboardDiff::between(&before, &after);
+        assert_eq!(
+            diff.set.into_iter().collect::<Vec<_>>(),
+            [
+                ("health".to_string(), Value::Int(80)),
+                ("seen".to_string(), Value::Bool(true)),
+            ]
+        );
+        assert_eq!(diff.removed, ["target"]);
+        assert!(BlackboardDiff::between(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn first_trace_after_debugging_holds_the_whole_blackboard() {
+        let root = Node::action("set")
+            .with_param("key", "ticked")
+            .with_param("value", true);
+        let mut blackboard = Blackboard::new();
+        blackboard.set("health", 100).unwrap();
+        let tree = BehaviorTree::new(&root, &NodeRegistry::with_builtins())
+            .unwrap()
+            .with_blackboard(blackboard);
+        let info = AgentInfo {
+            id: 1,
+            name: "guard #1".into(),
+            path: PathBuf::from("guard.behavior"),
+        };
+        let (traces, received) = crossbeam_channel::unbounded();
+        let sink: TraceSink = Arc::new(move |trace| {
+            let _ = traces.send(trace);
+        });
+        let agent = BehaviorAgent::spawn(info, tree, sink).unwrap();
+
+        let next = || received.recv_timeout(Duration::from_secs(5)).unwrap();
+        agent.set_debugged(true);
+        let first = next();
+        assert_eq!(first.agent, 1);
+        assert_eq!(first.status, Status::Success);
+        let keys: Vec<_> = first.blackboard.set.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["health", "ticked"]);
+        // Later traces only hold what changed
+        assert!(next().blackboard.is_empty());
+    }
+}
@@ -0,0 +1,531 @@
+//! On-disk behavior tree format.
+//!
+//! A tree is saved as pretty-printed JSON listing its nodes by the ids the
+//! editor gave them, each with its type, parameters and children in order.
+//! Where the editor draws the nodes is saved in a layout file next to it,
+//! so the runtime never reads it and moving nodes around does not change
+//! the tree's file.
+
+use super::{
+    blackboard::{Blackboard, BlackboardType},
+    error::{BehaviorError, BehaviorResult},
+    node::{Node, ParallelPolicy, Params},
+};
+use crate::atomic;
+use indexmap::IndexMap;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Extension the editor saves behavior trees with.
+pub const BEHAVIOR_EXTENSION: &str = "behavior";
+
+/// Extension appended to the path of a tree for its layout file.
+pub const LAYOUT_EXTENSION: &str = "layout";
+
+/// Version written by `save`.
+pub const BEHAVIOR_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeType {
+    Sequence,
+    Selector,
+    Parallel,
+    Inverter,
+    Repeat,
+    Timeout,
+    Cooldown,
+    Condition,
+    Action,
+}
+
+/// A node of a saved tree.
+///
+/// Parameters of the decorators and parallel nodes are `count` for repeats,
+/// forever if unset, `seconds` for timeouts and cooldowns, and `policy`,
+/// `"requireAll"` or `"requireOne"`, for parallel nodes. Those of
+/// conditions and actions are passed to their handler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetNode {
+    #[serde(rename = "type")]
+    pub kind: NodeType,
+    pub name: String,
+    /// Name the action or condition a leaf runs is registered under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub params: Params,
+    /// Ids of the children, in the order they are ticked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehaviorAsset {
+    pub version: u32,
+    /// Id of the root node. If unset, the root is the only node without a
+    /// parent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub nodes: IndexMap<String, AssetNode>,
+    /// Values the blackboard of an agent starts with.
+    #[serde(default, skip_serializing_if = "Blackboard::is_empty")]
+    pub blackboard: Blackboard,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Where the editor draws the nodes of a tree, by node id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorLayout {
+    pub version: u32,
+    #[serde(default)]
+    pub nodes: IndexMap<String, Position>,
+}
+
+impl Default for BehaviorLayout {
+    fn default() -> Self {
+        Self {
+            version: BEHAVIOR_FORMAT_VERSION,
+            nodes: IndexMap::new(),
+        }
+    }
+}
+
+impl BehaviorAsset {
+    /// Id of the root node.
+    pub fn root(&self) -> BehaviorResult<&str> {
+        if let Some(root) = &self.root {
+            return if self.nodes.contains_key(root) {
+                Ok(root)
+            } else {
+                Err(invalid(format!("the root {} is not a node", root)))
+            };
+        }
+
+        let children: HashSet<&str> = self
+            .nodes
+            .values()
+            .flat_map(|node| node.children.iter().map(String::as_str))
+            .collect();
+        let mut roots = self
+            .nodes
+            .keys()
+            .filter(|id| !children.contains(id.as_str()));
+        match (roots.next(), roots.next()) {
+            (Some(root), None) => Ok(root),
+            (None, _) if self.nodes.is_empty() => Err(invalid("it has no nodes")),
+            (None, _) => Err(invalid("every node has a parent")),
+            (Some(_), Some(_)) => Err(invalid(
+                "more than one node has no parent; connect them under one root",
+            )),
+        }
+    }
+
+    /// Definition of the tree from its root, with the ids of its nodes in
+    /// the order `BehaviorTree` numbers them. Nodes not under the root are
+    /// left out.
+    pub fn to_node(&self) -> BehaviorResult<(Node, Vec<String>)> {
+        let mut ids = Vec::new();
+        let mut seen = HashSet::new();
+        let node = self.build(self.root()?, &mut ids, &mut seen)?;
+        Ok((node, ids))
+    }
+
+    fn build<'a>(
+        &'a self,
+        id: &'a str,
+        ids: &mut Vec<String>,
+        seen: &mut HashSet<&'a str>,
+    ) -> BehaviorResult<Node> {
+        let node = self
+            .nodes
+            .get(id)
+            .ok_or_else(|| invalid(format!("the child {} is not a node", id)))?;
+        if !seen.insert(id) {
+            return Err(invalid(format!(
+                "{} has more than one parent, or is its own descendant",
+                node.name
+            )));
+        }
+        ids.push(id.to_string());
+
+        let mut children = node
+            .children
+            .iter()
+            .map(|child| self.build(child, ids, seen))
+            .collect::<BehaviorResult<Vec<_>>>()?;
+        let mut child = || match children.len() {
+            1 => Ok(Box::new(children.remove(0))),
+            count => Err(invalid(format!(
+                "{} needs one child, it has {}",
+                node.name, count
+            ))),
+        };
+
+        Ok(match node.kind {
+            NodeType::Sequence => Node::Sequence(children),
+            NodeType::Selector => Node::Selector(children),
+            NodeType::Parallel => {
+                let policy = match node.params.get("policy") {
+                    None => ParallelPolicy::default(),
+                    Some(policy) => {
+                        let policy = serde_json::to_value(policy)?;
+                        serde_json::from_value(policy.clone()).map_err(|_| {
+                            invalid(format!("{} has an unknown policy {}", node.name, policy))
+                        })?
+                    }
+                };
+                Node::Parallel { policy, children }
+            }
+            NodeType::Inverter => Node::Inverter(child()?),
+            NodeType::Repeat => {
+                let count = match node.params.get("count") {
+                    None => None,
+                    Some(count) => Some(
+                        i64::from_value(count)
+                            .and_then(|count| u32::try_from(count).ok())
+                            .ok_or_else(|| param(node, "count", "a positive integer"))?,
+                    ),
+                };
+                Node::Repeat {
+                    count,
+                    child: child()?,
+                }
+            }
+            NodeType::Timeout => Node::Timeout {
+                limit: seconds(node)?,
+                child: child()?,
+            },
+            NodeType::Cooldown => Node::Cooldown {
+                duration: seconds(node)?,
+                child: child()?,
+            },
+            NodeType::Condition | NodeType::Action => {
+                if !node.children.is_empty() {
+                    return Err(invalid(format!("{} cannot have children", node.name)));
+                }
+                let name = node
+                    .handler
+                    .clone()
+                    .filter(|handler| !handler.is_empty())
+                    .ok_or_else(|| invalid(format!("{} has no handler", node.name)))?;
+                let params = node.params.clone();
+                match node.kind {
+                    NodeType::Condition => Node::Condition { name, params },
+                    _ => Node::Action { name, params },
+                }
+            }
+        })
+    }
+
+    /// Parses a tree file.
+    pub fn from_json(text: &str) -> BehaviorResult<Self> {
+        // Read the version alone first, so a newer file is reported as such
+        // rather than as malformed
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: Option<u32>,
+        }
+
+        let found = serde_json::from_str::<Versioned>(text)?
+            .version
+            .ok_or_else(|| invalid("missing or invalid version"))?;
+        if found > BEHAVIOR_FORMAT_VERSION {
+            return Err(BehaviorError::UnsupportedVersion {
+                found,
+                supported: BEHAVIOR_FORMAT_VERSION,
+            });
+        }
+        Ok(serde_json::from_str(text)?)
+    }
+}
+
+/// The `seconds` of a timeout or cooldown.
+fn seconds(node: &AssetNode) -> BehaviorResult<Duration> {
+    node.params
+        .get("seconds")
+        .and_then(f64::from_value)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| param(node, "seconds", "a duration in seconds"))
+}
+
+fn param(node: &AssetNode, name: &str, expected: &str) -> BehaviorError {
+    if node.params.contains_key(name) {
+        invalid(format!(
+            "the {} of {} must be {}",
+            name, node.name, expected
+        ))
+    } else {
+        invalid(format!(
+            "{} needs its {} parameter, {}",
+            node.name, name, expected
+        ))
+    }
+}
+
+fn invalid(message: impl Into<String>) -> BehaviorError {
+    BehaviorError::InvalidTree(message.into())
+}
+
+/// Path of the layout file of the tree at `path`.
+pub fn layout_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(LAYOUT_EXTENSION);
+    path.with_file_name(name)
+}
+
+pub fn load(path: &Path) -> BehaviorResult<BehaviorAsset> {
+    let text = fs::read_to_string(path).map_err(|source| BehaviorError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let asset = BehaviorAsset::from_json(&text)?;
+    info!(
+        "Loaded behavior tree of {} nodes from {}",
+        asset.nodes.len(),
+        path.display()
+    );
+    Ok(asset)
+}
+
+/// Loads the layout of the tree at `path`, empty if it has none.
+pub fn load_layout(path: &Path) -> BehaviorResult<BehaviorLayout> {
+    let path = layout_path(path);
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(BehaviorLayout::default()),
+        Err(source) => Err(BehaviorError::Io { path, source }),
+    }
+}
+
+/// Writes a tree to `path` and its layout next to it. Files are replaced
+/// atomically, so a failed save never leaves a truncated tree behind.
+pub fn save(asset: &BehaviorAsset, layout: &BehaviorLayout, path: &Path) -> BehaviorResult<()> {
+    let asset = BehaviorAsset {
+        version: BEHAVIOR_FORMAT_VERSION,
+        ..asset.clone()
+    };
+    let layout = BehaviorLayout {
+        version: BEHAVIOR_FORMAT_VERSION,
+        ..layout.clone()
+    };
+    write_json(&asset, path)?;
+    write_json(&layout, &layout_path(path))?;
+    info!(
+        "Saved behavior tree of {} nodes to {}",
+        asset.nodes.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn write_json(value: &impl Serialize, path: &Path) -> BehaviorResult<()> {
+    let mut text = serde_json::to_string_pretty(value)?;
+    text.push('\n');
+    atomic::write(path, text).map_err(|source| BehaviorError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A scratch directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "pulsar-behavior-{}-{}",
+                name,
+                std::process::id()
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// An asset of the current version with `nodes`.
+    fn asset(nodes: serde_json::Value) -> BehaviorAsset {
+        BehaviorAsset::from_json(&json!({ "version": 1, "nodes": nodes }).to_string()).unwrap()
+    }
+
+    fn leaf(name: &str) -> serde_json::Value {
+        json!({ "type": "action", "name": name, "handler": "succeed" })
+    }
+
+    /// The message of the `InvalidTree` error building `asset` fails with.
+    fn invalid_tree(asset: &BehaviorAsset) -> String {
+        match asset.to_node() {
+            Err(BehaviorError::InvalidTree(message)) => message,
+            other => panic!(
+                "expected an invalid tree, got {:?}",
+                other.map(|(node, _)| node)
+            ),
+        }
+    }
+
+    #[test]
+    fn saves_and_loads_a_tree_and_its_layout() {
+        let dir = TempDir::new("round-trip");
+        let path = dir.0.join("guard.behavior");
+        let mut asset = asset(json!({
+            "root": {
+                "type": "sequence",
+                "name": "Patrol",
+                "children": ["wait", "log"]
+            },
+            "wait": {
+                "type": "action",
+                "name": "Wait",
+                "handler": "wait",
+                "params": { "seconds": 1.5 }
+            },
+            "log": {
+                "type": "action",
+                "name": "Log",
+                "handler": "log",
+                "params": { "message": "done" }
+            }
+        }));
+        asset.blackboard.set("alert", false).unwrap();
+        let mut layout = BehaviorLayout::default();
+        layout
+            .nodes
+            .insert("root".into(), Position { x: 0.0, y: 0.0 });
+        layout
+            .nodes
+            .insert("wait".into(), Position { x: -80.0, y: 120.5 });
+
+        save(&asset, &layout, &path).unwrap();
+
+        assert_eq!(load(&path).unwrap(), asset);
+        assert_eq!(load_layout(&path).unwrap(), layout);
+        let mut files: Vec<_> = fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["guard.behavior", "guard.behavior.layout"]);
+    }
+
+    #[test]
+    fn loads_an_empty_layout_for_a_tree_without_one() {
+        let dir = TempDir::new("no-layout");
+        let layout = load_layout(&dir.0.join("guard.behavior")).unwrap();
+        assert_eq!(layout, BehaviorLayout::default());
+    }
+
+    #[test]
+    fn rejects_files_of_a_newer_version() {
+        let text = json!({ "version": 2, "nodes": { "root": { "kind": "future" } } });
+        assert!(matches!(
+            BehaviorAsset::from_json(&text.to_string()),
+            Err(BehaviorError::UnsupportedVersion {
+                found: 2,
+                supported: BEHAVIOR_FORMAT_VERSION
+            })
+        ));
+    }
+
+    #[test]
+    fn numbers_nodes_depth_first_from_the_root() {
+        let asset = asset(json!({
+            "b": leaf("B"),
+            "root": { "type": "selector", "name": "Root", "children": ["not", "b"] },
+            "not": { "type": "inverter", "name": "Not", "children": ["a"] },
+            "a": leaf("A")
+        }));
+        let (_, ids) = asset.to_node().unwrap();
+        assert_eq!(ids, ["root", "not", "a", "b"]);
+    }
+
+    #[test]
+    fn rejects_a_missing_child() {
+        let asset = asset(json!({
+            "root": { "type": "sequence", "name": "Root", "children": ["gone"] }
+        }));
+        assert!(invalid_tree(&asset).contains("gone is not a node"));
+    }
+
+    #[test]
+    fn rejects_more_than_one_root() {
+        let asset = asset(json!({ "a": leaf("A"), "b": leaf("B") }));
+        assert!(invalid_tree(&asset).contains("more than one node has no parent"));
+    }
+
+    #[test]
+    fn rejects_a_node_with_two_parents() {
+        let asset = asset(json!({
+            "root": { "type": "sequence", "name": "Root", "children": ["x", "y"] },
+            "x": { "type": "inverter", "name": "X", "children": ["shared"] },
+            "y": { "type": "inverter", "name": "Y", "children": ["shared"] },
+            "shared": leaf("Shared")
+        }));
+        assert!(invalid_tree(&asset).contains("Shared has more than one parent"));
+    }
+
+    #[test]
+    fn rejects_a_cycle() {
+        let mut asset = asset(json!({
+            "a": { "type": "sequence", "name": "A", "children": ["b"] },
+            "b": { "type": "sequence", "name": "B", "children": ["a"] }
+        }));
+        assert!(invalid_tree(&asset).contains("every node has a parent"));
+
+        asset.root = Some("a".into());
+        assert!(invalid_tree(&asset).contains("A has more than one parent, or is its own"));
+    }
+
+    #[test]
+    fn rejects_a_leaf_with_children() {
+        let asset = asset(json!({
+            "root": {
+                "type": "action",
+                "name": "Jump",
+                "handler": "succeed",
+                "children": ["child"]
+            },
+            "child": leaf("Child")
+        }));
+        assert!(invalid_tree(&asset).contains("Jump cannot have children"));
+    }
+
+    #[test]
+    fn rejects_a_negative_repeat_count() {
+        let asset = asset(json!({
+            "root": {
+                "type": "repeat",
+                "name": "Again",
+                "params": { "count": -1 },
+                "children": ["child"]
+            },
+            "child": leaf("Child")
+        }));
+        assert!(invalid_tree(&asset).contains("the count of Again must be a positive integer"));
+    }
+}
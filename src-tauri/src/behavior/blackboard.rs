@@ -0,0 +1,304 @@
+use super::error::{BehaviorError, BehaviorResult};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A value on a blackboard, or a parameter of a node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValueType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueType::Bool => "bool",
+            ValueType::Int => "int",
+            ValueType::Float => "float",
+            ValueType::String => "string",
+        })
+    }
+}
+
+impl Value {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::Bool(_) => ValueType::Bool,
+            Value::Int(_) => ValueType::Int,
+            Value::Float(_) => ValueType::Float,
+            Value::String(_) => ValueType::String,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+/// A Rust type blackboard values are read as.
+pub trait BlackboardType: Sized {
+    const TYPE: ValueType;
+
+    /// The value as this type, `None` if it is of another type.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl BlackboardType for bool {
+    const TYPE: ValueType = ValueType::Bool;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl BlackboardType for i64 {
+    const TYPE: ValueType = ValueType::Int;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl BlackboardType for f64 {
+    const TYPE: ValueType = ValueType::Float;
+
+    // Integers read as floats, as JSON does not tell `1.0` from `1`
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(value) => Some(*value),
+            Value::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+impl BlackboardType for String {
+    const TYPE: ValueType = ValueType::String;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Values the nodes of a behavior tree share, by key.
+///
+/// A key keeps the type of the first value set to it: setting a value of
+/// another type fails, except for numbers, as JSON does not tell `1.0` from
+/// `1`. Integers are stored in float keys as floats, and setting a float to
+/// an integer key makes it a float key. Keys iterate in the order they were
+/// first set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Blackboard {
+    values: IndexMap<String, Value>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value of a key, `None` if it is not set or of another type.
+    pub fn get<T: BlackboardType>(&self, key: &str) -> Option<T> {
+        self.values.get(key).and_then(T::from_value)
+    }
+
+    /// The value of a key, failing if it is not set or of another type.
+    pub fn try_get<T: BlackboardType>(&self, key: &str) -> BehaviorResult<T> {
+        let value = self
+            .values
+            .get(key)
+            .ok_or_else(|| BehaviorError::MissingKey(key.to_string()))?;
+        T::from_value(value).ok_or_else(|| BehaviorError::WrongType {
+            key: key.to_string(),
+            expected: T::TYPE,
+            found: value.value_type(),
+        })
+    }
+
+    pub fn value(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// Sets the value of a key, failing if the key holds a value of another
+    /// type. Numbers set to a key holding a number make it a float key if
+    /// either is a float.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<Value>) -> BehaviorResult<()> {
+        let key = key.into();
+        let mut value = value.into();
+        if let Some(current) = self.values.get(&key) {
+            if let (Value::Float(_), Value::Int(int)) = (current, &value) {
+                value = Value::Float(*int as f64);
+            }
+            let upgrade = matches!((current, &value), (Value::Int(_), Value::Float(_)));
+            if current.value_type() != value.value_type() && !upgrade {
+                return Err(BehaviorError::WrongType {
+                    expected: current.value_type(),
+                    found: value.value_type(),
+                    key,
+                });
+            }
+        }
+        self.values.insert(key, value);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.values.shift_remove(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_type_of_the_first_value_of_a_key() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("ready", true).unwrap();
+        blackboard.set("ready", false).unwrap();
+        assert_eq!(blackboard.get::<bool>("ready"), Some(false));
+
+        let error = blackboard.set("ready", "yes").unwrap_err();
+        assert!(matches!(
+            error,
+            BehaviorError::WrongType {
+                expected: ValueType::Bool,
+                found: ValueType::String,
+                ..
+            }
+        ));
+        assert!(blackboard.set("ready", 1).is_err());
+        assert_eq!(blackboard.value("ready"), Some(&Value::Bool(false)));
+    }
+
+    #[test]
+    fn stores_integers_set_to_float_keys_as_floats() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("speed", 1.5).unwrap();
+        blackboard.set("speed", 2).unwrap();
+        assert_eq!(blackboard.value("speed"), Some(&Value::Float(2.0)));
+        assert_eq!(blackboard.get::<f64>("speed"), Some(2.0));
+    }
+
+    #[test]
+    fn makes_integer_keys_float_keys_when_set_to_floats() {
+        // The editor sends `1.0` as `1`, so a float key may start as an
+        // integer one
+        let mut blackboard: Blackboard = serde_json::from_str(r#"{"speed": 1}"#).unwrap();
+        assert_eq!(blackboard.value("speed"), Some(&Value::Int(1)));
+
+        blackboard.set("speed", 2.5).unwrap();
+        assert_eq!(blackboard.value("speed"), Some(&Value::Float(2.5)));
+        assert_eq!(blackboard.get::<f64>("speed"), Some(2.5));
+        assert_eq!(blackboard.get::<i64>("speed"), None);
+
+        // From then on it stays a float key
+        blackboard.set("speed", 3).unwrap();
+        assert_eq!(blackboard.value("speed"), Some(&Value::Float(3.0)));
+        assert!(blackboard.set("speed", "fast").is_err());
+    }
+
+    #[test]
+    fn reads_integers_as_floats_but_not_floats_as_integers() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("count", 3).unwrap();
+        blackboard.set("ratio", 0.5).unwrap();
+        assert_eq!(blackboard.get::<f64>("count"), Some(3.0));
+        assert_eq!(blackboard.get::<i64>("ratio"), None);
+        assert!(matches!(
+            blackboard.try_get::<i64>("ratio"),
+            Err(BehaviorError::WrongType {
+                expected: ValueType::Int,
+                found: ValueType::Float,
+                ..
+            })
+        ));
+        assert!(matches!(
+            blackboard.try_get::<i64>("missing"),
+            Err(BehaviorError::MissingKey(_))
+        ));
+    }
+
+    #[test]
+    fn iterates_keys_in_the_order_they_were_first_set() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("b", 1).unwrap();
+        blackboard.set("a", 1).unwrap();
+        blackboard.set("b", 2).unwrap();
+        let keys: Vec<&str> = blackboard.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["b", "a"]);
+        assert_eq!(blackboard.remove("b"), Some(Value::Int(2)));
+        assert_eq!(blackboard.len(), 1);
+    }
+}
@@ -0,0 +1,71 @@
+//! Actions and conditions built into the editor, so trees can be tried out
+//! before the game registers its own.
+
+use super::{
+    registry::{Context, NodeRegistry},
+    Status,
+};
+use log::info;
+
+impl NodeRegistry {
+    /// Registry holding the built-in actions and conditions:
+    ///
+    /// - `succeed` and `fail` complete right away.
+    /// - `wait` runs for its `seconds`.
+    /// - `set` sets the blackboard `key` to `value`.
+    /// - `log` logs its `message`.
+    /// - `isSet` checks the blackboard has a `key`.
+    /// - `isTrue` checks the blackboard `key` is `true`.
+    /// - `equals` checks the blackboard `key` holds `value`.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register_action("succeed", |_: &mut Context| Status::Success);
+        registry.register_action("fail", |_: &mut Context| Status::Failure);
+        registry.register_action("wait", |context: &mut Context| {
+            match context.param::<f64>("seconds") {
+                Some(seconds) if context.elapsed.as_secs_f64() < seconds => Status::Running,
+                Some(_) => Status::Success,
+                None => Status::Failure,
+            }
+        });
+        registry.register_action("set", |context: &mut Context| {
+            let (Some(key), Some(value)) =
+                (context.param::<String>("key"), context.params.get("value"))
+            else {
+                return Status::Failure;
+            };
+            match context.blackboard.set(key, value.clone()) {
+                Ok(()) => Status::Success,
+                Err(_) => Status::Failure,
+            }
+        });
+        registry.register_action("log", |context: &mut Context| {
+            let message = context.param::<String>("message").unwrap_or_default();
+            info!("Behavior tree: {}", message);
+            Status::Success
+        });
+
+        registry.register_condition("isSet", |context: &Context| {
+            context
+                .param::<String>("key")
+                .map_or(false, |key| context.blackboard.contains_key(&key))
+        });
+        registry.register_condition("isTrue", |context: &Context| {
+            context
+                .param::<String>("key")
+                .and_then(|key| context.blackboard.get::<bool>(&key))
+                .unwrap_or(false)
+        });
+        registry.register_condition("equals", |context: &Context| {
+            let key = context.param::<String>("key");
+            match (
+                key.and_then(|key| context.blackboard.value(&key)),
+                context.params.get("value"),
+            ) {
+                (Some(value), Some(expected)) => value == expected,
+                _ => false,
+            }
+        });
+        registry
+    }
+}
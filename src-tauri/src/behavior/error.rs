@@ -0,0 +1,69 @@
+use super::blackboard::ValueType;
+use super::AgentId;
+use crate::error::serialize_as_code_and_message;
+use std::path::PathBuf;
+
+/// Errors raised loading or building a behavior tree, using its blackboard
+/// or debugging its agents.
+#[derive(Debug, thiserror::Error)]
+pub enum BehaviorError {
+    #[error("No action named {0} is registered")]
+    UnknownAction(String),
+
+    #[error("No condition named {0} is registered")]
+    UnknownCondition(String),
+
+    #[error("The blackboard has no value {0}")]
+    MissingKey(String),
+
+    #[error("Blackboard value {key} is a {found}, not a {expected}")]
+    WrongType {
+        key: String,
+        expected: ValueType,
+        found: ValueType,
+    },
+
+    #[error("Failed to access behavior tree file {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Malformed behavior tree file: {0}")]
+    Format(#[from] serde_json::Error),
+
+    #[error("Behavior tree file version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Invalid behavior tree: {0}")]
+    InvalidTree(String),
+
+    #[error("No behavior agent with id {0}")]
+    UnknownAgent(AgentId),
+
+    #[error("Failed to start the behavior agent: {0}")]
+    Spawn(#[source] std::io::Error),
+}
+
+impl BehaviorError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BehaviorError::UnknownAction(_) => "unknownAction",
+            BehaviorError::UnknownCondition(_) => "unknownCondition",
+            BehaviorError::MissingKey(_) => "missingKey",
+            BehaviorError::WrongType { .. } => "wrongType",
+            BehaviorError::Io { .. } => "io",
+            BehaviorError::Format(_) => "format",
+            BehaviorError::UnsupportedVersion { .. } => "unsupportedVersion",
+            BehaviorError::InvalidTree(_) => "invalidTree",
+            BehaviorError::UnknownAgent(_) => "unknownAgent",
+            BehaviorError::Spawn(_) => "spawn",
+        }
+    }
+}
+
+serialize_as_code_and_message!(BehaviorError);
+
+pub type BehaviorResult<T> = Result<T, BehaviorError>;
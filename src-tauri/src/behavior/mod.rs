@@ -0,0 +1,51 @@
+//! Behavior trees driving the agents of a game.
+//!
+//! A tree is defined as a `Node` from its root, built into a
+//! `BehaviorTree` against a `NodeRegistry` of the Rust actions and
+//! conditions its leaves name, and ticked with the time since the previous
+//! tick. Each node ticked returns whether it is still running, succeeded or
+//! failed, and a running node continues from where it was on the next tick.
+//!
+//! Composites are sequences, selectors and parallel nodes, and decorators
+//! invert, repeat, time out or cool down their child. Nodes share values
+//! through the tree's typed `Blackboard`.
+//!
+//! Trees drawn in the Behavior Tree editor are saved as assets, apart from
+//! their layout, and can be tried out on agents ticking in the editor. The
+//! agent being debugged streams what its nodes returned and how its
+//! blackboard changed on every tick.
+
+pub mod agent;
+pub mod asset;
+pub mod blackboard;
+pub mod builtin;
+pub mod error;
+pub mod node;
+pub mod registry;
+pub mod tree;
+
+pub use agent::{
+    AgentId, AgentInfo, AgentState, BehaviorAgent, BlackboardDiff, TickTrace, TraceSink,
+    BEHAVIOR_DEBUG_EVENT, TICK_INTERVAL,
+};
+pub use asset::{
+    AssetNode, BehaviorAsset, BehaviorLayout, NodeType, Position, BEHAVIOR_EXTENSION,
+    BEHAVIOR_FORMAT_VERSION,
+};
+pub use blackboard::{Blackboard, BlackboardType, Value, ValueType};
+pub use error::{BehaviorError, BehaviorResult};
+pub use node::{Node, ParallelPolicy, Params};
+pub use registry::{Action, Condition, Context, NodeRegistry};
+pub use tree::{BehaviorTree, NodeIndex};
+
+use serde::{Deserialize, Serialize};
+
+/// What ticking a node returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    /// The node has not completed, and continues on the next tick.
+    Running,
+    Success,
+    Failure,
+}
@@ -0,0 +1,134 @@
+use super::blackboard::Value;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Parameters of a condition or action node, like the key it reads or the
+/// speed it moves at.
+pub type Params = IndexMap<String, Value>;
+
+/// When a parallel node completes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParallelPolicy {
+    /// Succeeds once every child succeeded, fails once one failed.
+    #[default]
+    RequireAll,
+    /// Succeeds once one child succeeded, fails once every child failed.
+    RequireOne,
+}
+
+/// Definition of a behavior tree, from its root. `BehaviorTree` runs it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// Ticks its children in order until one does not succeed. Succeeds if
+    /// they all do.
+    Sequence(Vec<Node>),
+    /// Ticks its children in order until one does not fail. Fails if they
+    /// all do.
+    Selector(Vec<Node>),
+    /// Ticks all its children on every tick, until its policy is met.
+    Parallel {
+        policy: ParallelPolicy,
+        children: Vec<Node>,
+    },
+    /// Swaps the success and failure of its child.
+    Inverter(Box<Node>),
+    /// Runs its child again each time it succeeds, `count` times or forever,
+    /// and fails as soon as it fails. The child runs at most once a tick.
+    Repeat {
+        count: Option<u32>,
+        child: Box<Node>,
+    },
+    /// Fails, halting its child, if the child is still running `limit`
+    /// after it started.
+    Timeout { limit: Duration, child: Box<Node> },
+    /// Fails without ticking its child for `duration` after the child
+    /// completed or was halted.
+    Cooldown {
+        duration: Duration,
+        child: Box<Node>,
+    },
+    /// Checks a condition registered in the `NodeRegistry`.
+    Condition { name: String, params: Params },
+    /// Runs an action registered in the `NodeRegistry`.
+    Action { name: String, params: Params },
+}
+
+impl Node {
+    pub fn sequence(children: impl IntoIterator<Item = Node>) -> Self {
+        Node::Sequence(children.into_iter().collect())
+    }
+
+    pub fn selector(children: impl IntoIterator<Item = Node>) -> Self {
+        Node::Selector(children.into_iter().collect())
+    }
+
+    pub fn parallel(policy: ParallelPolicy, children: impl IntoIterator<Item = Node>) -> Self {
+        Node::Parallel {
+            policy,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    pub fn inverter(child: Node) -> Self {
+        Node::Inverter(Box::new(child))
+    }
+
+    pub fn repeat(count: Option<u32>, child: Node) -> Self {
+        Node::Repeat {
+            count,
+            child: Box::new(child),
+        }
+    }
+
+    pub fn timeout(limit: Duration, child: Node) -> Self {
+        Node::Timeout {
+            limit,
+            child: Box::new(child),
+        }
+    }
+
+    pub fn cooldown(duration: Duration, child: Node) -> Self {
+        Node::Cooldown {
+            duration,
+            child: Box::new(child),
+        }
+    }
+
+    pub fn condition(name: impl Into<String>) -> Self {
+        Node::Condition {
+            name: name.into(),
+            params: Params::new(),
+        }
+    }
+
+    pub fn action(name: impl Into<String>) -> Self {
+        Node::Action {
+            name: name.into(),
+            params: Params::new(),
+        }
+    }
+
+    /// Sets a parameter of a condition or action. Other nodes have none, and
+    /// are returned unchanged.
+    pub fn with_param(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        if let Node::Condition { params, .. } | Node::Action { params, .. } = &mut self {
+            params.insert(name.into(), value.into());
+        }
+        self
+    }
+
+    pub fn children(&self) -> Vec<&Node> {
+        match self {
+            Node::Sequence(children)
+            | Node::Selector(children)
+            | Node::Parallel { children, .. } => children.iter().collect(),
+            Node::Inverter(child)
+            | Node::Repeat { child, .. }
+            | Node::Timeout { child, .. }
+            | Node::Cooldown { child, .. } => vec![child],
+            Node::Condition { .. } | Node::Action { .. } => Vec::new(),
+        }
+    }
+}
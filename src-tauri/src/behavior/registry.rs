@@ -0,0 +1,128 @@
+use super::{
+    blackboard::{Blackboard, BlackboardType},
+    node::Params,
+    Status,
+};
+use std::{any::Any, collections::HashMap, sync::Arc, time::Duration};
+
+/// What a condition or action sees when it is ticked.
+pub struct Context<'a> {
+    pub blackboard: &'a mut Blackboard,
+    pub params: &'a Params,
+    /// Time of the tree, the sum of the deltas of its ticks.
+    pub now: Duration,
+    /// Time since the previous tick.
+    pub delta: Duration,
+    /// Time since the node started running. Zero on the tick it starts.
+    pub elapsed: Duration,
+    /// Whether the node starts running on this tick, rather than still
+    /// running from the previous one.
+    pub starting: bool,
+    pub(super) state: &'a mut Option<Box<dyn Any + Send>>,
+}
+
+impl Context<'_> {
+    /// A parameter of the node, `None` if it is not set or of another type.
+    pub fn param<T: BlackboardType>(&self, name: &str) -> Option<T> {
+        self.params.get(name).and_then(T::from_value)
+    }
+
+    /// What the node remembers between ticks, `T::default()` until it is
+    /// first set. Every node of every tree has its own, even where they
+    /// run the same action.
+    pub fn state<T: Default + Send + 'static>(&mut self) -> &mut T {
+        let state = self.state.get_or_insert_with(|| Box::new(T::default()));
+        if !state.is::<T>() {
+            *state = Box::new(T::default());
+        }
+        state.downcast_mut().expect("the state was just made a T")
+    }
+}
+
+/// A leaf of behavior trees doing something, possibly over several ticks.
+///
+/// One action is shared by all the nodes naming it, so what it remembers
+/// between ticks belongs in `Context::state` rather than in the action.
+/// Closures taking a `&mut Context` and returning a `Status` are actions.
+pub trait Action: Send + Sync {
+    fn tick(&self, context: &mut Context) -> Status;
+
+    /// Called when the action is interrupted while running, like by a
+    /// timeout, so it can undo what it started.
+    fn halt(&self, _context: &mut Context) {}
+}
+
+impl<F> Action for F
+where
+    F: Fn(&mut Context) -> Status + Send + Sync,
+{
+    fn tick(&self, context: &mut Context) -> Status {
+        self(context)
+    }
+}
+
+/// A leaf of behavior trees succeeding when something holds, and failing
+/// otherwise. It never runs over several ticks.
+///
+/// Closures taking a `&Context` and returning a `bool` are conditions.
+pub trait Condition: Send + Sync {
+    fn check(&self, context: &Context) -> bool;
+}
+
+impl<F> Condition for F
+where
+    F: Fn(&Context) -> bool + Send + Sync,
+{
+    fn check(&self, context: &Context) -> bool {
+        self(context)
+    }
+}
+
+/// The Rust actions and conditions trees refer to by name.
+#[derive(Clone, Default)]
+pub struct NodeRegistry {
+    actions: HashMap<String, Arc<dyn Action>>,
+    conditions: HashMap<String, Arc<dyn Condition>>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an action, replacing the one of the same name.
+    pub fn register_action(&mut self, name: impl Into<String>, action: impl Action + 'static) {
+        self.actions.insert(name.into(), Arc::new(action));
+    }
+
+    /// Registers a condition, replacing the one of the same name.
+    pub fn register_condition(
+        &mut self,
+        name: impl Into<String>,
+        condition: impl Condition + 'static,
+    ) {
+        self.conditions.insert(name.into(), Arc::new(condition));
+    }
+
+    pub fn action(&self, name: &str) -> Option<Arc<dyn Action>> {
+        self.actions.get(name).cloned()
+    }
+
+    pub fn condition(&self, name: &str) -> Option<Arc<dyn Condition>> {
+        self.conditions.get(name).cloned()
+    }
+
+    /// Names of the registered actions, sorted.
+    pub fn action_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.actions.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Names of the registered conditions, sorted.
+    pub fn condition_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.conditions.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
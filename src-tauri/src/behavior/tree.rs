@@ -0,0 +1,855 @@
+use super::{
+    asset::BehaviorAsset,
+    blackboard::Blackboard,
+    error::{BehaviorError, BehaviorResult},
+    node::{Node, ParallelPolicy, Params},
+    registry::{Action, Condition, Context, NodeRegistry},
+    Status,
+};
+use std::{any::Any, sync::Arc, time::Duration};
+
+/// Index of a node of a `BehaviorTree`. Nodes are numbered depth first, the
+/// root being 0 and children following their parent in order.
+pub type NodeIndex = usize;
+
+#[derive(Clone)]
+enum Kind {
+    Sequence,
+    Selector,
+    Parallel(ParallelPolicy),
+    Inverter,
+    Repeat(Option<u32>),
+    Timeout(Duration),
+    Cooldown(Duration),
+    Condition(Arc<dyn Condition>),
+    Action(Arc<dyn Action>),
+}
+
+/// A node of the tree and what it remembers between ticks.
+struct Slot {
+    kind: Kind,
+    params: Params,
+    children: Vec<NodeIndex>,
+    /// Whether the node was running after the last tick, so the next one
+    /// continues it instead of starting it over.
+    running: bool,
+    /// Time the node last started running.
+    started_at: Duration,
+    /// Child a sequence or selector is at, or the runs a repeat completed.
+    counter: usize,
+    /// Results of the children of a parallel node that completed.
+    results: Vec<Option<Status>>,
+    /// Time until which a cooldown fails.
+    cooldown_until: Duration,
+    /// What an action or condition keeps in `Context::state`.
+    state: Option<Box<dyn Any + Send>>,
+}
+
+/// A behavior tree ready to tick, with its blackboard.
+///
+/// Ticks are deterministic: the tree's time only advances by the deltas
+/// given to `tick`, and nodes are ticked in the order of the definition.
+pub struct BehaviorTree {
+    nodes: Vec<Slot>,
+    /// Ids of the nodes in the asset the tree was loaded from, by index.
+    ids: Vec<String>,
+    /// What each node returned on the last tick, `None` if it was not
+    /// ticked.
+    statuses: Vec<Option<Status>>,
+    blackboard: Blackboard,
+    ticks: u64,
+    now: Duration,
+    delta: Duration,
+}
+
+impl BehaviorTree {
+    /// Builds a tree, looking its actions and conditions up in `registry`.
+    pub fn new(root: &Node, registry: &NodeRegistry) -> BehaviorResult<Self> {
+        let mut nodes = Vec::new();
+        add_node(&mut nodes, root, registry)?;
+        Ok(Self {
+            statuses: vec![None; nodes.len()],
+            nodes,
+            ids: Vec::new(),
+            blackboard: Blackboard::new(),
+            ticks: 0,
+            now: Duration::ZERO,
+            delta: Duration::ZERO,
+        })
+    }
+
+    /// Builds the tree saved in an asset, with the blackboard it starts
+    /// with.
+    pub fn from_asset(asset: &BehaviorAsset, registry: &NodeRegistry) -> BehaviorResult<Self> {
+        let (root, ids) = asset.to_node()?;
+        let mut tree = Self::new(&root, registry)?.with_blackboard(asset.blackboard.clone());
+        tree.ids = ids;
+        Ok(tree)
+    }
+
+    pub fn with_blackboard(mut self, blackboard: Blackboard) -> Self {
+        self.blackboard = blackboard;
+        self
+    }
+
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.blackboard
+    }
+
+    /// Time of the tree, the sum of the deltas of its ticks.
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Asset id of a node, `None` if the tree was not loaded from an asset.
+    pub fn node_id(&self, index: NodeIndex) -> Option<&str> {
+        self.ids.get(index).map(String::as_str)
+    }
+
+    /// What each node returned on the last tick, by index, `None` for the
+    /// nodes that were not ticked.
+    pub fn statuses(&self) -> &[Option<Status>] {
+        &self.statuses
+    }
+
+    /// Number of ticks so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Whether the root was still running after the last tick.
+    pub fn is_running(&self) -> bool {
+        self.nodes[0].running
+    }
+
+    /// Advances the tree's time by `delta` and ticks it from the root. Once
+    /// the root completed, the next tick starts it over.
+    pub fn tick(&mut self, delta: Duration) -> Status {
+        self.now += delta;
+        self.delta = delta;
+        self.ticks += 1;
+        self.statuses.fill(None);
+        self.tick_node(0)
+    }
+
+    /// Halts the running nodes, so the next tick starts the tree over.
+    /// Cooldowns keep going.
+    pub fn reset(&mut self) {
+        self.halt(0);
+    }
+
+    fn tick_node(&mut self, index: NodeIndex) -> Status {
+        let now = self.now;
+        let slot = &mut self.nodes[index];
+        let starting = !slot.running;
+        if starting {
+            slot.started_at = now;
+            slot.counter = 0;
+            slot.results = vec![None; slot.children.len()];
+        }
+
+        let status = match slot.kind.clone() {
+            Kind::Sequence => self.tick_composite(index, Status::Success),
+            Kind::Selector => self.tick_composite(index, Status::Failure),
+            Kind::Parallel(policy) => self.tick_parallel(index, policy),
+            Kind::Inverter => match self.tick_node(self.nodes[index].children[0]) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Kind::Repeat(count) => self.tick_repeat(index, count),
+            Kind::Timeout(limit) => {
+                let child = self.nodes[index].children[0];
+                if !starting && now - self.nodes[index].started_at >= limit {
+                    self.halt(child);
+                    Status::Failure
+                } else {
+                    self.tick_node(child)
+                }
+            }
+            Kind::Cooldown(duration) => {
+                if starting && now < self.nodes[index].cooldown_until {
+                    Status::Failure
+                } else {
+                    let status = self.tick_node(self.nodes[index].children[0]);
+                    if status != Status::Running {
+                        self.nodes[index].cooldown_until = now + duration;
+                    }
+                    status
+                }
+            }
+            Kind::Condition(condition) => {
+                if condition.check(&self.context(index, starting)) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
+            Kind::Action(action) => action.tick(&mut self.context(index, starting)),
+        };
+        self.nodes[index].running = status == Status::Running;
+        self.statuses[index] = Some(status);
+        status
+    }
+
+    /// Ticks the children of a sequence or selector from the one it is at,
+    /// moving past the ones returning `keep_going`.
+    fn tick_composite(&mut self, index: NodeIndex, keep_going: Status) -> Status {
+        loop {
+            let slot = &self.nodes[index];
+            let Some(&child) = slot.children.get(slot.counter) else {
+                return keep_going;
+            };
+            let status = self.tick_node(child);
+            if status != keep_going {
+                return status;
+            }
+            self.nodes[index].counter += 1;
+        }
+    }
+
+    fn tick_parallel(&mut self, index: NodeIndex, policy: ParallelPolicy) -> Status {
+        let children = self.nodes[index].children.clone();
+        let mut status = parallel_status(policy, &self.nodes[index].results);
+        for (i, &child) in children.iter().enumerate() {
+            if status != Status::Running {
+                break;
+            }
+            if self.nodes[index].results[i].is_some() {
+                continue;
+            }
+            let result = self.tick_node(child);
+            if result != Status::Running {
+                self.nodes[index].results[i] = Some(result);
+                status = parallel_status(policy, &self.nodes[index].results);
+            }
+        }
+        if status != Status::Running {
+            for child in children {
+                self.halt(child);
+            }
+        }
+        status
+    }
+
+    fn tick_repeat(&mut self, index: NodeIndex, count: Option<u32>) -> Status {
+        let done = |runs: usize| count.map_or(false, |count| runs >= count as usize);
+        if done(self.nodes[index].counter) {
+            return Status::Success;
+        }
+        match self.tick_node(self.nodes[index].children[0]) {
+            Status::Success => {
+                let slot = &mut self.nodes[index];
+                slot.counter += 1;
+                if done(slot.counter) {
+                    Status::Success
+                } else {
+                    Status::Running
+                }
+            }
+            status => status,
+        }
+    }
+
+    /// Interrupts a running node and its running descendants.
+    fn halt(&mut self, index: NodeIndex) {
+        if !self.nodes[index].running {
+            return;
+        }
+        self.nodes[index].running = false;
+        for child in self.nodes[index].children.clone() {
+            self.halt(child);
+        }
+        match self.nodes[index].kind.clone() {
+            Kind::Action(action) => action.halt(&mut self.context(index, false)),
+            Kind::Cooldown(duration) => self.nodes[index].cooldown_until = self.now + duration,
+            _ => {}
+        }
+    }
+
+    fn context(&mut self, index: NodeIndex, starting: bool) -> Context<'_> {
+        let slot = &mut self.nodes[index];
+        Context {
+            blackboard: &mut self.blackboard,
+            params: &slot.params,
+            now: self.now,
+            delta: self.delta,
+            elapsed: self.now - slot.started_at,
+            starting,
+            state: &mut slot.state,
+        }
+    }
+}
+
+/// Status of a parallel node given the results of its children.
+fn parallel_status(policy: ParallelPolicy, results: &[Option<Status>]) -> Status {
+    let count = |status| {
+        results
+            .iter()
+            .filter(|result| **result == Some(status))
+            .count()
+    };
+    let (successes, failures) = (count(Status::Success), count(Status::Failure));
+    match policy {
+        ParallelPolicy::RequireAll if failures > 0 => Status::Failure,
+        ParallelPolicy::RequireAll if successes == results.len() => Status::Success,
+        ParallelPolicy::RequireOne if successes > 0 => Status::Success,
+        ParallelPolicy::RequireOne if failures == results.len() => Status::Failure,
+        _ => Status::Running,
+    }
+}
+
+/// Adds a node and its descendants to `nodes`, depth first.
+fn add_node(
+    nodes: &mut Vec<Slot>,
+    node: &Node,
+    registry: &NodeRegistry,
+) -> BehaviorResult<NodeIndex> {
+    let (kind, params) = match node {
+        Node::Sequence(_) => (Kind::Sequence, Params::new()),
+        Node::Selector(_) => (Kind::Selector, Params::new()),
+        Node::Parallel { policy, .. } => (Kind::Parallel(*policy), Params::new()),
+        Node::Inverter(_) => (Kind::Inverter, Params::new()),
+        Node::Repeat { count, .. } => (Kind::Repeat(*count), Params::new()),
+        Node::Timeout { limit, .. } => (Kind::Timeout(*limit), Params::new()),
+        Node::Cooldown { duration, .. } => (Kind::Cooldown(*duration), Params::new()),
+        Node::Condition { name, params } => {
+            let condition = registry
+                .condition(name)
+                .ok_or_else(|| BehaviorError::UnknownCondition(name.clone()))?;
+            (Kind::Condition(condition), params.clone())
+        }
+        Node::Action { name, params } => {
+            let action = registry
+                .action(name)
+                .ok_or_else(|| BehaviorError::UnknownAction(name.clone()))?;
+            (Kind::Action(action), params.clone())
+        }
+    };
+
+    let index = nodes.len();
+    nodes.push(Slot {
+        kind,
+        params,
+        children: Vec::new(),
+        running: false,
+        started_at: Duration::ZERO,
+        counter: 0,
+        results: Vec::new(),
+        cooldown_until: Duration::ZERO,
+        state: None,
+    });
+    let children = node
+        .children()
+        .into_iter()
+        .map(|child| add_node(nodes, child, registry))
+        .collect::<BehaviorResult<_>>()?;
+    nodes[index].children = children;
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    const DELTA: Duration = Duration::from_millis(100);
+
+    /// What the scripted actions of a test did, in order.
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    /// An action running for `ticks` ticks before it returns `result`,
+    /// logging its ticks and halts.
+    struct Scripted {
+        name: &'static str,
+        ticks: usize,
+        result: Status,
+        log: Log,
+    }
+
+    impl Action for Scripted {
+        fn tick(&self, context: &mut Context) -> Status {
+            let starting = context.starting;
+            // Ticks of the current run
+            let run = context.state::<usize>();
+            if starting {
+                *run = 0;
+            }
+            *run += 1;
+            let what = if starting { "start" } else { "tick" };
+            self.log.lock().push(format!("{} {}", self.name, what));
+            if *run > self.ticks {
+                self.result
+            } else {
+                Status::Running
+            }
+        }
+
+        fn halt(&self, _context: &mut Context) {
+            self.log.lock().push(format!("{} halt", self.name));
+        }
+    }
+
+    #[derive(Default)]
+    struct Actions {
+        registry: NodeRegistry,
+        log: Log,
+    }
+
+    impl Actions {
+        /// An action node running for `ticks` ticks before it returns
+        /// `result`.
+        fn add(&mut self, name: &'static str, ticks: usize, result: Status) -> Node {
+            let action = Scripted {
+                name,
+                ticks,
+                result,
+                log: self.log.clone(),
+            };
+            self.registry.register_action(name, action);
+            Node::action(name)
+        }
+
+        fn build(&self, root: &Node) -> BehaviorTree {
+            BehaviorTree::new(root, &self.registry).unwrap()
+        }
+
+        /// Ticks the tree with a fixed delta, returning its status and what
+        /// the actions did.
+        fn tick(&self, tree: &mut BehaviorTree) -> (Status, Vec<String>) {
+            let status = tree.tick(DELTA);
+            (status, self.log.lock().drain(..).collect())
+        }
+    }
+
+    use Status::{Failure, Running, Success};
+
+    #[test]
+    fn sequence_continues_from_its_running_child() {
+        let mut actions = Actions::default();
+        let root = Node::sequence([
+            actions.add("a", 0, Success),
+            actions.add("b", 1, Success),
+            actions.add("c", 0, Success),
+        ]);
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Running, vec!["a start".into(), "b start".into()])
+        );
+        assert_eq!(
+            tree.statuses(),
+            [Some(Running), Some(Success), Some(Running), None]
+        );
+        // `a` is not ticked again
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Success, vec!["b tick".into(), "c start".into()])
+        );
+        assert_eq!(
+            tree.statuses(),
+            [Some(Success), None, Some(Success), Some(Success)]
+        );
+        // Once complete, it starts over
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Running, vec!["a start".into(), "b start".into()])
+        );
+    }
+
+    #[test]
+    fn sequence_fails_at_the_first_child_failing() {
+        let mut actions = Actions::default();
+        let root = Node::sequence([actions.add("a", 1, Failure), actions.add("b", 0, Success)]);
+        let mut tree = actions.build(&root);
+
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["a tick".into()]));
+        assert_eq!(tree.statuses(), [Some(Failure), Some(Failure), None]);
+        assert!(!tree.is_running());
+    }
+
+    #[test]
+    fn selector_continues_from_its_running_child() {
+        let mut actions = Actions::default();
+        let root = Node::selector([
+            actions.add("a", 0, Failure),
+            actions.add("b", 1, Failure),
+            actions.add("c", 0, Success),
+            actions.add("d", 0, Success),
+        ]);
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Running, vec!["a start".into(), "b start".into()])
+        );
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Success, vec!["b tick".into(), "c start".into()])
+        );
+        assert_eq!(
+            tree.statuses(),
+            [Some(Success), None, Some(Failure), Some(Success), None]
+        );
+    }
+
+    #[test]
+    fn selector_fails_once_every_child_failed() {
+        let mut actions = Actions::default();
+        let root = Node::selector([actions.add("a", 0, Failure), actions.add("b", 0, Failure)]);
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Failure, vec!["a start".into(), "b start".into()])
+        );
+    }
+
+    #[test]
+    fn parallel_requiring_all_succeeds_once_every_child_succeeded() {
+        let mut actions = Actions::default();
+        let root = Node::parallel(
+            ParallelPolicy::RequireAll,
+            [actions.add("a", 0, Success), actions.add("b", 2, Success)],
+        );
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Running, vec!["a start".into(), "b start".into()])
+        );
+        // Children that completed are not ticked again
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["b tick".into()]));
+        assert_eq!(actions.tick(&mut tree), (Success, vec!["b tick".into()]));
+        // Once complete, every child starts over
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Running, vec!["a start".into(), "b start".into()])
+        );
+    }
+
+    #[test]
+    fn parallel_requiring_all_fails_once_a_child_failed_and_halts_the_others() {
+        let mut actions = Actions::default();
+        let root = Node::parallel(
+            ParallelPolicy::RequireAll,
+            [
+                actions.add("a", usize::MAX, Success),
+                actions.add("b", 1, Failure),
+                actions.add("c", usize::MAX, Success),
+            ],
+        );
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (
+                Running,
+                vec!["a start".into(), "b start".into(), "c start".into()]
+            )
+        );
+        // `c` is not ticked once the policy is met
+        assert_eq!(
+            actions.tick(&mut tree),
+            (
+                Failure,
+                vec![
+                    "a tick".into(),
+                    "b tick".into(),
+                    "a halt".into(),
+                    "c halt".into()
+                ]
+            )
+        );
+        assert!(!tree.is_running());
+    }
+
+    #[test]
+    fn parallel_requiring_one_succeeds_once_a_child_succeeded_and_halts_the_others() {
+        let mut actions = Actions::default();
+        let root = Node::parallel(
+            ParallelPolicy::RequireOne,
+            [
+                actions.add("a", 0, Failure),
+                actions.add("b", 1, Success),
+                actions.add("c", usize::MAX, Success),
+            ],
+        );
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (
+                Running,
+                vec!["a start".into(), "b start".into(), "c start".into()]
+            )
+        );
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Success, vec!["b tick".into(), "c halt".into()])
+        );
+    }
+
+    #[test]
+    fn parallel_requiring_one_fails_once_every_child_failed() {
+        let mut actions = Actions::default();
+        let root = Node::parallel(
+            ParallelPolicy::RequireOne,
+            [actions.add("a", 0, Failure), actions.add("b", 1, Failure)],
+        );
+        let mut tree = actions.build(&root);
+
+        assert_eq!(
+            actions.tick(&mut tree),
+            (Running, vec!["a start".into(), "b start".into()])
+        );
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["b tick".into()]));
+    }
+
+    #[test]
+    fn repeat_runs_its_child_count_times_once_a_tick() {
+        let mut actions = Actions::default();
+        let root = Node::repeat(Some(3), actions.add("a", 0, Success));
+        let mut tree = actions.build(&root);
+
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+        assert_eq!(actions.tick(&mut tree), (Success, vec!["a start".into()]));
+        // Once complete, it counts again
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+    }
+
+    #[test]
+    fn repeat_counts_runs_of_a_child_running_over_several_ticks() {
+        let mut actions = Actions::default();
+        let root = Node::repeat(Some(2), actions.add("a", 1, Success));
+        let mut tree = actions.build(&root);
+
+        let statuses: Vec<Status> = (0..4).map(|_| actions.tick(&mut tree).0).collect();
+        assert_eq!(statuses, [Running, Running, Running, Success]);
+    }
+
+    #[test]
+    fn repeat_fails_as_soon_as_its_child_fails() {
+        let mut actions = Actions::default();
+        let root = Node::repeat(Some(3), actions.add("a", 0, Failure));
+        let mut tree = actions.build(&root);
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["a start".into()]));
+        // The next tick counts from zero again
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["a start".into()]));
+    }
+
+    #[test]
+    fn repeat_without_count_runs_forever() {
+        let mut actions = Actions::default();
+        let root = Node::repeat(None, actions.add("a", 0, Success));
+        let mut tree = actions.build(&root);
+
+        for _ in 0..100 {
+            assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+        }
+        assert_eq!(tree.now(), DELTA * 100);
+        assert_eq!(tree.ticks(), 100);
+    }
+
+    #[test]
+    fn repeat_of_zero_succeeds_without_ticking_its_child() {
+        let mut actions = Actions::default();
+        let root = Node::repeat(Some(0), actions.add("a", 0, Success));
+        let mut tree = actions.build(&root);
+
+        assert_eq!(actions.tick(&mut tree), (Success, Vec::<String>::new()));
+    }
+
+    #[test]
+    fn timeout_halts_its_child_once_the_limit_passed() {
+        let mut actions = Actions::default();
+        let root = Node::timeout(
+            Duration::from_millis(250),
+            actions.add("a", usize::MAX, Success),
+        );
+        let mut tree = actions.build(&root);
+
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a tick".into()]));
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a tick".into()]));
+        // 300ms after it started, without ticking the child again
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["a halt".into()]));
+        assert_eq!(tree.statuses(), [Some(Failure), None]);
+        assert!(!tree.is_running());
+        // The next tick starts over
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+    }
+
+    #[test]
+    fn timeout_returns_what_its_child_returned_within_the_limit() {
+        let mut actions = Actions::default();
+        let root = Node::timeout(Duration::from_millis(300), actions.add("a", 2, Success));
+        let mut tree = actions.build(&root);
+
+        let statuses: Vec<Status> = (0..3).map(|_| actions.tick(&mut tree).0).collect();
+        assert_eq!(statuses, [Running, Running, Success]);
+        assert!(!actions
+            .log
+            .lock()
+            .iter()
+            .any(|entry| entry.ends_with("halt")));
+    }
+
+    #[test]
+    fn timeout_halts_the_running_descendants_of_its_child() {
+        let mut actions = Actions::default();
+        let root = Node::timeout(
+            Duration::from_millis(100),
+            Node::sequence([
+                actions.add("a", 0, Success),
+                Node::parallel(
+                    ParallelPolicy::RequireAll,
+                    [
+                        actions.add("b", usize::MAX, Success),
+                        actions.add("c", 0, Success),
+                    ],
+                ),
+            ]),
+        );
+        let mut tree = actions.build(&root);
+
+        actions.tick(&mut tree);
+        // `c` completed, so only `b` is halted
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["b halt".into()]));
+    }
+
+    #[test]
+    fn cooldown_fails_for_its_duration_after_its_child_completed() {
+        let mut actions = Actions::default();
+        let root = Node::cooldown(Duration::from_millis(200), actions.add("a", 0, Success));
+        let mut tree = actions.build(&root);
+
+        assert_eq!(actions.tick(&mut tree), (Success, vec!["a start".into()]));
+        assert_eq!(actions.tick(&mut tree), (Failure, Vec::<String>::new()));
+        assert_eq!(actions.tick(&mut tree), (Success, vec!["a start".into()]));
+    }
+
+    #[test]
+    fn cooldown_starts_when_its_child_is_halted() {
+        let mut actions = Actions::default();
+        let root = Node::timeout(
+            Duration::from_millis(200),
+            Node::cooldown(
+                Duration::from_millis(300),
+                actions.add("a", usize::MAX, Success),
+            ),
+        );
+        let mut tree = actions.build(&root);
+
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a tick".into()]));
+        // Timed out at 300ms, cooling down until 600ms
+        assert_eq!(actions.tick(&mut tree), (Failure, vec!["a halt".into()]));
+        assert_eq!(actions.tick(&mut tree), (Failure, Vec::<String>::new()));
+        assert_eq!(tree.statuses(), [Some(Failure), Some(Failure), None]);
+        assert_eq!(actions.tick(&mut tree), (Failure, Vec::<String>::new()));
+        assert_eq!(tree.now(), Duration::from_millis(500));
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+    }
+
+    #[test]
+    fn reset_halts_running_nodes_and_keeps_cooldowns() {
+        let mut actions = Actions::default();
+        let root = Node::sequence([
+            actions.add("a", usize::MAX, Success),
+            Node::cooldown(Duration::from_millis(100), actions.add("b", 0, Success)),
+        ]);
+        let mut tree = actions.build(&root);
+
+        actions.tick(&mut tree);
+        tree.reset();
+        assert_eq!(actions.log.lock().drain(..).collect::<Vec<_>>(), ["a halt"]);
+        assert!(!tree.is_running());
+        assert_eq!(actions.tick(&mut tree), (Running, vec!["a start".into()]));
+    }
+
+    #[test]
+    fn inverter_swaps_success_and_failure() {
+        let registry = NodeRegistry::with_builtins();
+        let status = |node: Node| BehaviorTree::new(&node, &registry).unwrap().tick(DELTA);
+        assert_eq!(status(Node::inverter(Node::action("succeed"))), Failure);
+        assert_eq!(status(Node::inverter(Node::action("fail"))), Success);
+        let wait = Node::action("wait").with_param("seconds", 1.0);
+        assert_eq!(status(Node::inverter(wait)), Running);
+    }
+
+    #[test]
+    fn gives_actions_the_time_since_they_started() {
+        let registry = NodeRegistry::with_builtins();
+        let root = Node::sequence([
+            Node::action("wait").with_param("seconds", 0.25),
+            Node::action("set")
+                .with_param("key", "done")
+                .with_param("value", true),
+        ]);
+        let mut tree = BehaviorTree::new(&root, &registry).unwrap();
+
+        // Elapsed is zero on the tick it starts, then 100ms, 200ms and 300ms
+        let statuses: Vec<Status> = (0..4).map(|_| tree.tick(DELTA)).collect();
+        assert_eq!(statuses, [Running, Running, Running, Success]);
+        assert_eq!(tree.blackboard().get::<bool>("done"), Some(true));
+    }
+
+    #[test]
+    fn keeps_the_state_of_each_node_running_an_action() {
+        let mut registry = NodeRegistry::new();
+        // Succeeds on its second tick
+        registry.register_action("twice", |context: &mut Context| {
+            let ticks = context.state::<u32>();
+            *ticks += 1;
+            if *ticks < 2 {
+                Running
+            } else {
+                Success
+            }
+        });
+        let root = Node::parallel(
+            ParallelPolicy::RequireAll,
+            [Node::action("twice"), Node::action("twice")],
+        );
+        let mut tree = BehaviorTree::new(&root, &registry).unwrap();
+        let mut other = BehaviorTree::new(&root, &registry).unwrap();
+
+        assert_eq!(tree.tick(DELTA), Running);
+        assert_eq!(
+            tree.statuses(),
+            [Some(Running), Some(Running), Some(Running)]
+        );
+        // Another tree of the same registry starts from its own state
+        assert_eq!(other.tick(DELTA), Running);
+        assert_eq!(tree.tick(DELTA), Success);
+        assert_eq!(
+            tree.statuses(),
+            [Some(Success), Some(Success), Some(Success)]
+        );
+    }
+
+    #[test]
+    fn fails_to_build_trees_naming_unregistered_nodes() {
+        let registry = NodeRegistry::new();
+        assert!(matches!(
+            BehaviorTree::new(&Node::action("jump"), &registry),
+            Err(BehaviorError::UnknownAction(name)) if name == "jump"
+        ));
+        assert!(matches!(
+            BehaviorTree::new(&Node::inverter(Node::condition("seen")), &registry),
+            Err(BehaviorError::UnknownCondition(name)) if name == "seen"
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+use super::{Compilation, NodeId, Severity};
+use crate::error::serialize_as_code_and_message;
+use log::{debug, info};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Arc,
+};
+
+/// Manifest of the crate generated code is checked in. It has no
+/// dependencies, so checking it works offline.
+const MANIFEST: &str = "[package]
+name = \"blueprint\"
+version = \"0.1.0\"
+edition = \"2021\"
+
+[dependencies]
+
+[workspace]
+";
+
+/// Name of the directory of the scratch crate in the editor's cache.
+pub const CHECK_DIR: &str = "blueprint-check";
+
+/// File of the scratch crate the code is checked in, as rustc names it.
+const SOURCE: &str = "src/main.rs";
+
+/// Errors raised checking generated code. Problems of the code itself are
+/// `CargoDiagnostic`s instead.
+#[derive(Debug, thiserror::Error)]
+pub enum CheckError {
+    #[error("The graph has errors, so there is no code to check")]
+    Compile,
+
+    #[error("Cargo was not found; install Rust from https://rustup.rs")]
+    CargoNotFound,
+
+    #[error("cargo check failed: {0}")]
+    Cargo(String),
+
+    #[error("Failed to write the scratch crate: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The check was interrupted: {0}")]
+    Task(String),
+}
+
+impl CheckError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CheckError::Compile => "compile",
+            CheckError::CargoNotFound => "cargoNotFound",
+            CheckError::Cargo(_) => "cargo",
+            CheckError::Io(_) => "io",
+            CheckError::Task(_) => "task",
+        }
+    }
+}
+
+serialize_as_code_and_message!(CheckError);
+
+pub type CheckResult<T> = Result<T, CheckError>;
+
+/// A problem rustc found in generated code.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CargoDiagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// rustc's code of the problem, like `E0308`.
+    pub code: Option<String>,
+    /// Node whose code the problem is in, `None` if it is in the code
+    /// around the nodes.
+    pub node: Option<NodeId>,
+    /// Line of the generated source, from 1.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// What rustc says at the problem, like "expected `f64`, found integer".
+    pub label: Option<String>,
+    /// The problem as rustc prints it.
+    pub rendered: String,
+}
+
+/// What checking the code of a graph found.
+#[derive(Debug, Clone, Serialize)]
+pub struct CargoCheck {
+    /// Whether the code compiles.
+    pub success: bool,
+    /// The code that was checked.
+    pub code: String,
+    pub diagnostics: Vec<CargoDiagnostic>,
+}
+
+/// A line `cargo --message-format=json` prints.
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<RustcMessage>,
+}
+
+#[derive(Deserialize)]
+struct RustcMessage {
+    message: String,
+    code: Option<RustcCode>,
+    level: String,
+    spans: Vec<RustcSpan>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+    label: Option<String>,
+}
+
+/// Checks the code of a graph with `cargo check` in the crate at `dir`,
+/// which is created if needed, and ties rustc's problems to the nodes whose
+/// code they are in.
+pub fn check(compilation: &Compilation, dir: &Path) -> CheckResult<CargoCheck> {
+    let code = compilation.code.clone().ok_or(CheckError::Compile)?;
+    write_if_changed(&dir.join("Cargo.toml"), MANIFEST)?;
+    write_if_changed(&dir.join(SOURCE), &code)?;
+
+    // A build of the editor may set `CARGO` to the cargo that built it
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args(["check", "--message-format=json", "--offline", "--quiet"])
+        .current_dir(dir)
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => CheckError::CargoNotFound,
+            _ => CheckError::Io(e),
+        })?;
+
+    let diagnostics = diagnostics(compilation, &String::from_utf8_lossy(&output.stdout));
+    let success = output.status.success();
+    let errors = diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error);
+    if !success && !errors {
+        // Cargo itself failed, before rustc could report anything
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CheckError::Cargo(stderr.trim().to_string()));
+    }
+
+    debug!(
+        "Checked Blueprint code with {} diagnostics",
+        diagnostics.len()
+    );
+    Ok(CargoCheck {
+        success,
+        code,
+        diagnostics,
+    })
+}
+
+/// The diagnostics of the rustc messages in the JSON lines cargo printed.
+fn diagnostics(compilation: &Compilation, output: &str) -> Vec<CargoDiagnostic> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|message| message.reason == "compiler-message")
+        .filter_map(|message| diagnostic(compilation, message.message?))
+        .collect()
+}
+
+/// The diagnostic of a rustc message, or `None` for notes on their own and
+/// the summaries rustc ends with, which have neither a code nor a span.
+fn diagnostic(compilation: &Compilation, message: RustcMessage) -> Option<CargoDiagnostic> {
+    let severity = match message.level.as_str() {
+        "warning" => Severity::Warning,
+        "error" | "error: internal compiler error" => Severity::Error,
+        _ => return None,
+    };
+    if message.code.is_none() && message.spans.is_empty() {
+        return None;
+    }
+    let span = message
+        .spans
+        .iter()
+        .filter(|span| span.file_name == SOURCE)
+        .find(|span| span.is_primary);
+
+    Some(CargoDiagnostic {
+        severity,
+        code: message.code.map(|code| code.code),
+        node: span.and_then(|span| compilation.node_at(span.line_start).cloned()),
+        line: span.map(|span| span.line_start),
+        column: span.map(|span| span.column_start),
+        label: span.and_then(|span| span.label.clone()),
+        rendered: message.rendered.unwrap_or_else(|| message.message.clone()),
+        message: message.message,
+    })
+}
+
+/// Writes a file unless it already holds `contents`, so cargo does not
+/// check an unchanged crate again.
+fn write_if_changed(path: &Path, contents: &str) -> std::io::Result<()> {
+    if fs::read_to_string(path).map_or(false, |current| current == contents) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+#[derive(Default)]
+struct Shared {
+    dir: Mutex<Option<PathBuf>>,
+    /// Held while cargo runs, so checks do not write the crate under each
+    /// other.
+    running: Mutex<()>,
+}
+
+/// Checks the code of graphs in a scratch crate of the editor's cache.
+#[derive(Clone, Default)]
+pub struct CheckState {
+    shared: Arc<Shared>,
+}
+
+impl CheckState {
+    /// Keeps the scratch crate in `dir`, by default in the temporary
+    /// directory.
+    pub fn set_dir(&self, dir: PathBuf) {
+        info!("Checking Blueprint code in {}", dir.display());
+        *self.shared.dir.lock() = Some(dir);
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        self.shared
+            .dir
+            .lock()
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join(format!("pulsar-{}", CHECK_DIR)))
+    }
+
+    /// Checks the code of a graph, waiting for the check before it to end.
+    pub fn check(&self, compilation: &Compilation) -> CheckResult<CargoCheck> {
+        let dir = self.dir();
+        let _running = self.shared.running.lock();
+        check(compilation, &dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blueprint::NodeSpan;
+
+    /// Reads `tests/fixtures/blueprint/<name>.jsonl`, what cargo printed
+    /// checking `<name>.rs`.
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/blueprint")
+            .join(format!("{}.jsonl", name));
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn span(node: &str, first_line: usize, last_line: usize) -> NodeSpan {
+        NodeSpan {
+            node: node.into(),
+            first_line,
+            last_line,
+            outputs: Default::default(),
+        }
+    }
+
+    /// A compilation whose nodes are at `spans` of the checked code.
+    fn compilation(spans: Vec<NodeSpan>) -> Compilation {
+        Compilation {
+            code: Some(String::new()),
+            diagnostics: Vec::new(),
+            spans,
+        }
+    }
+
+    #[test]
+    fn ties_errors_and_warnings_to_their_nodes() {
+        let compilation = compilation(vec![
+            span("count", 5, 5),
+            span("half", 6, 6),
+            span("loop", 7, 9),
+        ]);
+        let diagnostics = diagnostics(&compilation, &fixture("check-errors"));
+
+        // The failure notes after the errors are left out
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code.as_deref(), d.node.as_deref(), d.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Severity::Error, Some("E0425"), None, Some(10)),
+                (Severity::Warning, Some("while_true"), Some("loop"), Some(7)),
+                (Severity::Error, Some("E0277"), Some("half"), Some(6)),
+            ]
+        );
+        assert_eq!(diagnostics[2].column, Some(35));
+    }
+
+    #[test]
+    fn keeps_warnings_of_code_that_compiles() {
+        let compilation = compilation(vec![span("count", 5, 5), span("loop", 6, 8)]);
+        let diagnostics = diagnostics(&compilation, &fixture("check-warnings"));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("while_true"));
+        assert_eq!(diagnostics[0].node.as_deref(), Some("loop"));
+        assert!(diagnostics[0].rendered.contains("while true"));
+    }
+
+    #[test]
+    fn skips_summaries_without_a_code_or_span() {
+        // What older toolchains print after the errors
+        let summary = r#"{"reason":"compiler-message","message":{"message":"aborting due to 2 previous errors","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting due to 2 previous errors\n\n"}}"#;
+        let compilation = compilation(Vec::new());
+        assert!(diagnostics(&compilation, summary).is_empty());
+    }
+}
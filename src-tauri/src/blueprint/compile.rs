@@ -0,0 +1,1006 @@
+use super::{
+    error::{CompileError, PinDirection, Severity},
+    graph::{FieldDefinition, Graph, GraphNode, NodeDefinition, NodeId, PinDefinition, TYPE_VALUE},
+    template::{to_text, Template, TemplateContext},
+    types::{Ty, TypeExpr, TypeTable},
+};
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+/// What a graph compiled to.
+#[derive(Debug, Clone, Serialize)]
+pub struct Compilation {
+    /// The Rust source, or `None` if the graph has errors.
+    pub code: Option<String>,
+    /// Errors and warnings, in the order they were found.
+    pub diagnostics: Vec<CompileError>,
+    /// Code of every node that compiled to any, in the order it runs.
+    pub spans: Vec<NodeSpan>,
+}
+
+/// Where the code of a node is in the generated source.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeSpan {
+    pub node: NodeId,
+    /// First line of the node's code, from 1.
+    pub first_line: usize,
+    /// Last line of the node's code, included.
+    pub last_line: usize,
+    /// Variables holding the values of the node's outputs, by pin.
+    pub outputs: IndexMap<String, String>,
+}
+
+impl Compilation {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity() == Severity::Error)
+    }
+
+    /// Node whose code is on a line of the generated source, from 1.
+    pub fn node_at(&self, line: usize) -> Option<&NodeId> {
+        self.spans
+            .iter()
+            .find(|span| (span.first_line..=span.last_line).contains(&line))
+            .map(|span| &span.node)
+    }
+}
+
+const HEADER: &str = "// Generated by Pulsar Blueprint Editor\n#![allow(unused)]\n";
+
+const INDENT: &str = "    ";
+
+/// Words a template starts with when it compiles to an item rather than a
+/// statement or expression.
+const ITEM_KEYWORDS: &[&str] = &[
+    "fn", "struct", "enum", "impl", "trait", "mod", "use", "const", "static", "type", "pub",
+];
+
+/// Compiles a graph to a Rust `main` running its nodes in dependency order.
+///
+/// Each node's template is rendered with its fields and the variables of the
+/// nodes connected to its inputs, and bound to a variable of the inferred
+/// type of its output. Problems are reported against the nodes they are on.
+pub fn compile(graph: &Graph) -> Compilation {
+    let mut compiler = Compiler::new(graph);
+    compiler.check_nodes();
+    compiler.resolve_edges();
+    compiler.check_fields();
+    let order = compiler.sort();
+    compiler.infer();
+    compiler.check_inputs();
+
+    let (code, spans) = if compiler.has_errors() {
+        (None, Vec::new())
+    } else {
+        let (code, spans) = compiler.emit(&order);
+        if compiler.has_errors() {
+            (None, Vec::new())
+        } else {
+            (Some(code), spans)
+        }
+    };
+    Compilation {
+        code,
+        diagnostics: compiler.diagnostics,
+        spans,
+    }
+}
+
+/// A connection from an output to an input, by node and pin index.
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    from: usize,
+    output: usize,
+    to: usize,
+    input: usize,
+}
+
+struct NodeInfo<'a> {
+    node: &'a GraphNode,
+    template: Option<Template>,
+    inputs: Vec<Ty>,
+    outputs: Vec<Ty>,
+}
+
+impl NodeInfo<'_> {
+    fn definition(&self) -> &NodeDefinition {
+        &self.node.data.definition
+    }
+
+    /// Value of a field, or its default if the node does not set it.
+    fn field(&self, name: &str) -> Option<&Value> {
+        self.node.data.fields.get(name).or_else(|| {
+            self.definition()
+                .fields
+                .get(name)
+                .and_then(|field| field.default.as_ref())
+        })
+    }
+}
+
+/// How a rendered template is placed in `main`.
+enum Statement {
+    /// An item, like a `fn` or `struct`; its outputs have no value.
+    Item,
+    /// A `let` statement; its one output is the variable it declares.
+    Let(Option<String>),
+    /// An expression bound to the outputs' variables.
+    Expr,
+}
+
+struct Compiler<'a> {
+    nodes: Vec<NodeInfo<'a>>,
+    index: HashMap<&'a str, usize>,
+    graph: &'a Graph,
+    links: Vec<Link>,
+    /// Types values are converted to with `as` on the links, by link.
+    casts: HashMap<usize, Ty>,
+    types: TypeTable,
+    diagnostics: Vec<CompileError>,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(graph: &'a Graph) -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            graph,
+            links: Vec::new(),
+            casts: HashMap::new(),
+            types: TypeTable::default(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity() == Severity::Error)
+    }
+
+    /// Parses the pin types and template of every node.
+    fn check_nodes(&mut self) {
+        for node in &self.graph.nodes {
+            let definition = &node.data.definition;
+            if self.index.insert(&node.id, self.nodes.len()).is_some() {
+                self.diagnostics
+                    .push(CompileError::DuplicateNode(node.id.clone()));
+            }
+
+            // Type parameters are shared by the pins of a node
+            let mut scope = HashMap::new();
+            let mut pin_types = |pins: &[PinDefinition], diagnostics: &mut Vec<_>| {
+                pins.iter()
+                    .map(|pin| match TypeExpr::parse(&pin.ty) {
+                        Ok(expr) => self.types.instantiate(&expr, &mut scope),
+                        Err(reason) => {
+                            diagnostics.push(CompileError::InvalidPinType {
+                                node: node.id.clone(),
+                                definition: definition.name.clone(),
+                                pin: pin.name.clone(),
+                                ty: pin.ty.clone(),
+                                reason,
+                            });
+                            self.types.fresh(false)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let inputs = pin_types(&definition.pins.inputs, &mut self.diagnostics);
+            let outputs = pin_types(&definition.pins.outputs, &mut self.diagnostics);
+
+            let template = match Template::parse(&definition.template) {
+                Ok(template) => {
+                    self.check_template_values(node, &template);
+                    Some(template)
+                }
+                Err(reason) => {
+                    self.diagnostics.push(CompileError::InvalidTemplate {
+                        node: node.id.clone(),
+                        definition: definition.name.clone(),
+                        reason,
+                    });
+                    None
+                }
+            };
+
+            self.nodes.push(NodeInfo {
+                node,
+                template,
+                inputs,
+                outputs,
+            });
+        }
+    }
+
+    fn check_template_values(&mut self, node: &GraphNode, template: &Template) {
+        let definition = &node.data.definition;
+        let mut reported = HashSet::new();
+        for name in template.names() {
+            if !definition.provides(name) && reported.insert(name) {
+                self.diagnostics.push(CompileError::UnknownTemplateValue {
+                    node: node.id.clone(),
+                    definition: definition.name.clone(),
+                    name: name.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Resolves the edges to the pins they connect.
+    fn resolve_edges(&mut self) {
+        for edge in &self.graph.edges {
+            let mut node = |id: &String| match self.index.get(id.as_str()) {
+                Some(&node) => Some(node),
+                None => {
+                    self.diagnostics.push(CompileError::MissingNode {
+                        edge: edge.id.clone(),
+                        node: id.clone(),
+                    });
+                    None
+                }
+            };
+            let (Some(source), Some(target)) = (node(&edge.source), node(&edge.target)) else {
+                continue;
+            };
+
+            let source_handle = edge.source_handle.as_deref();
+            let target_handle = edge.target_handle.as_deref();
+            let link = self.link(source, source_handle, target, target_handle);
+            // Loose connections can be drawn from an input to an output
+            let link = link.or_else(|| self.link(target, target_handle, source, source_handle));
+            match link {
+                Some(link) => self.links.push(link),
+                None => {
+                    let from = &self.nodes[source];
+                    if pin_index(from.definition(), source_handle, PinDirection::Output).is_none() {
+                        self.diagnostics.push(unknown_pin(
+                            from,
+                            source_handle,
+                            PinDirection::Output,
+                        ));
+                    }
+                    let to = &self.nodes[target];
+                    if pin_index(to.definition(), target_handle, PinDirection::Input).is_none() {
+                        self.diagnostics
+                            .push(unknown_pin(to, target_handle, PinDirection::Input));
+                    }
+                }
+            }
+        }
+
+        // Execution inputs may be entered from several places, values not
+        let mut connected = HashSet::new();
+        for link in &self.links {
+            let to = &self.nodes[link.to];
+            if to.inputs[link.input] != Ty::Exec && !connected.insert((link.to, link.input)) {
+                self.diagnostics.push(CompileError::DuplicateInput {
+                    node: to.node.id.clone(),
+                    definition: to.definition().name.clone(),
+                    pin: to.definition().pins.inputs[link.input].name.clone(),
+                });
+            }
+        }
+    }
+
+    fn link(
+        &self,
+        from: usize,
+        output: Option<&str>,
+        to: usize,
+        input: Option<&str>,
+    ) -> Option<Link> {
+        Some(Link {
+            from,
+            output: pin_index(self.nodes[from].definition(), output, PinDirection::Output)?,
+            to,
+            input: pin_index(self.nodes[to].definition(), input, PinDirection::Input)?,
+        })
+    }
+
+    /// Orders the nodes so every node comes after the nodes it depends on,
+    /// keeping the graph's order otherwise. Reports the cycles that keep
+    /// nodes out of the order.
+    fn sort(&mut self) -> Vec<usize> {
+        let mut dependents = vec![Vec::new(); self.nodes.len()];
+        let mut dependencies = vec![0; self.nodes.len()];
+        for link in &self.links {
+            dependents[link.from].push(link.to);
+            dependencies[link.to] += 1;
+        }
+
+        let mut ready: BinaryHeap<_> = (0..self.nodes.len())
+            .filter(|&node| dependencies[node] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(Reverse(node)) = ready.pop() {
+            order.push(node);
+            for &dependent in &dependents[node] {
+                dependencies[dependent] -= 1;
+                if dependencies[dependent] == 0 {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+
+        if order.len() < self.nodes.len() {
+            let blocked: Vec<bool> = dependencies.iter().map(|&count| count > 0).collect();
+            for cycle in cycles(&dependents, &blocked) {
+                self.diagnostics.push(CompileError::Cycle {
+                    nodes: cycle
+                        .into_iter()
+                        .map(|node| self.nodes[node].node.id.clone())
+                        .collect(),
+                });
+            }
+        }
+        order
+    }
+
+    /// Unifies the types of connected pins, converting numbers where a
+    /// wider type is expected.
+    fn infer(&mut self) {
+        for (index, link) in self.links.iter().enumerate() {
+            let (from, to) = (&self.nodes[link.from], &self.nodes[link.to]);
+            let (output, input) = (&from.outputs[link.output], &to.inputs[link.input]);
+            if self.types.unify(output, input) {
+                continue;
+            }
+
+            let (found, expected) = (self.types.resolve(output), self.types.resolve(input));
+            if found.widens_to(&expected) {
+                self.casts.insert(index, expected);
+            } else {
+                self.diagnostics.push(CompileError::TypeMismatch {
+                    node: to.node.id.clone(),
+                    from: from.node.id.clone(),
+                    definition: to.definition().name.clone(),
+                    pin: to.definition().pins.inputs[link.input].name.clone(),
+                    expected: describe(&expected, &self.types),
+                    found: describe(&found, &self.types),
+                });
+            }
+        }
+        self.types.default_numbers();
+    }
+
+    /// Validates the field values. A required field is not needed when an
+    /// input of the same name, which it stands in for, is connected.
+    fn check_fields(&mut self) {
+        let connected: HashSet<_> = self
+            .links
+            .iter()
+            .map(|link| (link.to, link.input))
+            .collect();
+        for (index, info) in self.nodes.iter().enumerate() {
+            for (name, field) in &info.definition().fields {
+                let replaced = info
+                    .definition()
+                    .input(name)
+                    .map_or(false, |input| connected.contains(&(index, input)));
+                if replaced {
+                    continue;
+                }
+                if let Some(error) = check_field(info, name, field) {
+                    self.diagnostics.push(error);
+                }
+            }
+        }
+    }
+
+    /// Warns about value inputs left unconnected, which get a default.
+    fn check_inputs(&mut self) {
+        let connected: HashSet<_> = self
+            .links
+            .iter()
+            .map(|link| (link.to, link.input))
+            .collect();
+        for (index, info) in self.nodes.iter().enumerate() {
+            for (input, pin) in info.definition().pins.inputs.iter().enumerate() {
+                let ty = self.types.resolve(&info.inputs[input]);
+                if pin.optional
+                    || ty == Ty::Exec
+                    || connected.contains(&(index, input))
+                    || info
+                        .field(&pin.name)
+                        .map_or(false, |value| !to_text(value).trim().is_empty())
+                {
+                    continue;
+                }
+                self.diagnostics.push(CompileError::UnconnectedInput {
+                    node: info.node.id.clone(),
+                    definition: info.definition().name.clone(),
+                    pin: pin.name.clone(),
+                    default: ty.default_value(),
+                });
+            }
+        }
+    }
+
+    fn emit(&mut self, order: &[usize]) -> (String, Vec<NodeSpan>) {
+        let mut incoming = HashMap::new();
+        for (index, link) in self.links.iter().enumerate() {
+            incoming.insert((link.to, link.input), index);
+        }
+
+        let mut names = HashSet::new();
+        let mut values: HashMap<(usize, usize), Option<String>> = HashMap::new();
+        let mut blocks = Vec::new();
+        let mut unbound = HashSet::new();
+        let mut errors = Vec::new();
+        for &node in order {
+            let info = &self.nodes[node];
+            let definition = info.definition();
+            let Some(template) = &info.template else {
+                continue;
+            };
+
+            let mut context = TemplateContext::new();
+            for name in definition.fields.keys() {
+                if let Some(value) = info.field(name) {
+                    context.insert(name, value.clone());
+                }
+            }
+            for (name, value) in &info.node.data.fields {
+                context.entry(name).or_insert_with(|| value.clone());
+            }
+            let first_output = info.outputs.iter().find(|&ty| *ty != Ty::Exec);
+            if let Some(ty) = first_output {
+                let ty = self.types.resolve(ty);
+                context.insert(TYPE_VALUE, Value::String(ty.to_string()));
+            }
+
+            for (input, pin) in definition.pins.inputs.iter().enumerate() {
+                let ty = self.types.resolve(&info.inputs[input]);
+                if ty == Ty::Exec {
+                    context.insert(&pin.name, Value::String(String::new()));
+                    continue;
+                }
+                let value = match incoming.get(&(node, input)) {
+                    Some(&index) => {
+                        let link = self.links[index];
+                        match values.get(&(link.from, link.output)).cloned().flatten() {
+                            Some(value) => match self.casts.get(&index) {
+                                Some(ty) => format!("({} as {})", value, ty),
+                                None => value,
+                            },
+                            None => {
+                                let from = &self.nodes[link.from];
+                                if unbound.insert((link.from, link.output)) {
+                                    errors.push(CompileError::NoValue {
+                                        node: from.node.id.clone(),
+                                        definition: from.definition().name.clone(),
+                                        pin: from.definition().pins.outputs[link.output]
+                                            .name
+                                            .clone(),
+                                    });
+                                }
+                                String::new()
+                            }
+                        }
+                    }
+                    // A field of the same name stands in for the input
+                    None => match info.field(&pin.name).map(to_text) {
+                        Some(text) if !text.trim().is_empty() => text,
+                        _ => ty.default_value(),
+                    },
+                };
+                context.insert(&pin.name, Value::String(value));
+            }
+
+            let (comments, body) = split_comments(&template.render(&context));
+            let outputs: Vec<usize> = (0..info.outputs.len())
+                .filter(|&output| info.outputs[output] != Ty::Exec)
+                .collect();
+            let statement = classify(&body);
+            let mut lines = comments;
+            match &statement {
+                Statement::Item => lines.extend(body),
+                Statement::Let(_) => lines.extend(terminate(body)),
+                Statement::Expr if body.is_empty() => {}
+                Statement::Expr => {
+                    let variables: Vec<String> = outputs
+                        .iter()
+                        .map(|&output| {
+                            let pin = &definition.pins.outputs[output].name;
+                            let base = if outputs.len() == 1 {
+                                variable_name(&info.node.id)
+                            } else {
+                                variable_name(&format!("{}_{}", info.node.id, pin))
+                            };
+                            unique_name(base, &mut names)
+                        })
+                        .collect();
+                    let types: Vec<Ty> = outputs
+                        .iter()
+                        .map(|&output| self.types.resolve(&info.outputs[output]))
+                        .collect();
+                    let binding = match variables.len() {
+                        0 => String::new(),
+                        1 if types[0].is_unknown() => format!("let {} = ", variables[0]),
+                        1 => format!("let {}: {} = ", variables[0], types[0]),
+                        _ if types.iter().all(Ty::is_unknown) => {
+                            format!("let ({}) = ", variables.join(", "))
+                        }
+                        _ => {
+                            let types: Vec<String> = types.iter().map(Ty::to_string).collect();
+                            format!("let ({}): ({}) = ", variables.join(", "), types.join(", "))
+                        }
+                    };
+
+                    let mut body = terminate(body);
+                    // Integer literals of float fields would not type check
+                    if body.len() == 1 && types.len() == 1 && types[0].is_float() {
+                        let literal = body[0].trim_end_matches(';');
+                        if literal.parse::<i64>().is_ok() {
+                            body[0] = format!("{}.0;", literal);
+                        }
+                    }
+                    body[0].insert_str(0, &binding);
+                    lines.extend(body);
+                    for (&output, variable) in outputs.iter().zip(variables) {
+                        values.insert((node, output), Some(variable));
+                    }
+                }
+            }
+
+            // Only an expression binds its outputs; a `let` binds one
+            for (position, &output) in outputs.iter().enumerate() {
+                values
+                    .entry((node, output))
+                    .or_insert_with(|| match &statement {
+                        Statement::Let(name) if position == 0 && outputs.len() == 1 => name.clone(),
+                        _ => None,
+                    });
+            }
+            if !lines.is_empty() {
+                let outputs: IndexMap<String, String> = outputs
+                    .iter()
+                    .filter_map(|&output| {
+                        let variable = values.get(&(node, output)).cloned().flatten()?;
+                        Some((definition.pins.outputs[output].name.clone(), variable))
+                    })
+                    .collect();
+                blocks.push((node, lines, outputs));
+            }
+        }
+
+        self.diagnostics.extend(errors);
+
+        let mut code = format!("{}\nfn main() {{", HEADER);
+        let mut spans = Vec::new();
+        if blocks.is_empty() {
+            code.push_str("}\n");
+            return (code, spans);
+        }
+        code.push('\n');
+        let mut line = code.matches('\n').count() + 1;
+        for (index, (node, block, outputs)) in blocks.iter().enumerate() {
+            let spaced = |block: &Vec<String>| block.len() > 1;
+            if index > 0 && (spaced(block) || spaced(&blocks[index - 1].1)) {
+                code.push('\n');
+                line += 1;
+            }
+            for text in block {
+                if !text.is_empty() {
+                    code.push_str(INDENT);
+                    code.push_str(text);
+                }
+                code.push('\n');
+            }
+            spans.push(NodeSpan {
+                node: self.nodes[*node].node.id.clone(),
+                first_line: line,
+                last_line: line + block.len() - 1,
+                outputs: outputs.clone(),
+            });
+            line += block.len();
+        }
+        code.push_str("}\n");
+        (code, spans)
+    }
+}
+
+/// Index of the pin `handle` names, or of the only pin if it names none.
+fn pin_index(
+    definition: &NodeDefinition,
+    handle: Option<&str>,
+    direction: PinDirection,
+) -> Option<usize> {
+    let pins = match direction {
+        PinDirection::Input => &definition.pins.inputs,
+        PinDirection::Output => &definition.pins.outputs,
+    };
+    match handle {
+        Some(name) => pins.iter().position(|pin| pin.name == name),
+        None if pins.len() == 1 => Some(0),
+        None => None,
+    }
+}
+
+fn unknown_pin(info: &NodeInfo, handle: Option<&str>, direction: PinDirection) -> CompileError {
+    CompileError::UnknownPin {
+        node: info.node.id.clone(),
+        definition: info.definition().name.clone(),
+        pin: handle.unwrap_or_default().to_string(),
+        direction,
+    }
+}
+
+/// Returns what is wrong with the value of a field, if anything.
+fn check_field(info: &NodeInfo, name: &str, field: &FieldDefinition) -> Option<CompileError> {
+    let invalid = |reason: String| CompileError::InvalidField {
+        node: info.node.id.clone(),
+        definition: info.definition().name.clone(),
+        field: name.to_string(),
+        reason,
+    };
+
+    let text = info.field(name).map(to_text).unwrap_or_default();
+    if text.trim().is_empty() {
+        if field.required {
+            return Some(CompileError::MissingField {
+                node: info.node.id.clone(),
+                definition: info.definition().name.clone(),
+                field: name.to_string(),
+            });
+        }
+        return None;
+    }
+
+    if !field.options.is_empty() && !field.options.contains(&text) {
+        return Some(invalid(format!(
+            "must be one of {}",
+            field.options.join(", ")
+        )));
+    }
+    let number = text.trim().parse::<f64>();
+    if field.ty == "number" && number.is_err() {
+        return Some(invalid("must be a number".to_string()));
+    }
+
+    let Some(validation) = &field.validation else {
+        return None;
+    };
+    if let Ok(number) = number {
+        if validation.min.map_or(false, |min| number < min) {
+            return Some(invalid(format!(
+                "must be at least {}",
+                validation.min.unwrap()
+            )));
+        }
+        if validation.max.map_or(false, |max| number > max) {
+            return Some(invalid(format!(
+                "must be at most {}",
+                validation.max.unwrap()
+            )));
+        }
+    }
+    let length = text.chars().count();
+    if validation.min_length.map_or(false, |min| length < min) {
+        return Some(invalid(format!(
+            "must be at least {} characters long",
+            validation.min_length.unwrap()
+        )));
+    }
+    if validation.max_length.map_or(false, |max| length > max) {
+        return Some(invalid(format!(
+            "must be at most {} characters long",
+            validation.max_length.unwrap()
+        )));
+    }
+    if let Some(pattern) = &validation.pattern {
+        match Regex::new(pattern) {
+            Ok(regex) if !regex.is_match(&text) => {
+                return Some(invalid(format!("must match {}", pattern)));
+            }
+            Ok(_) => {}
+            Err(e) => return Some(invalid(format!("its pattern is invalid: {}", e))),
+        }
+    }
+    None
+}
+
+/// Finds the cycles among `blocked` nodes, each in graph order, using
+/// Tarjan's strongly connected components.
+fn cycles(dependents: &[Vec<usize>], blocked: &[bool]) -> Vec<Vec<usize>> {
+    struct Search<'a> {
+        dependents: &'a [Vec<usize>],
+        blocked: &'a [bool],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        cycles: Vec<Vec<usize>>,
+    }
+
+    impl Search<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next);
+            self.low[node] = self.next;
+            self.next += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+
+            for &dependent in &self.dependents[node] {
+                if !self.blocked[dependent] {
+                    continue;
+                }
+                match self.index[dependent] {
+                    None => {
+                        self.visit(dependent);
+                        self.low[node] = self.low[node].min(self.low[dependent]);
+                    }
+                    Some(index) if self.on_stack[dependent] => {
+                        self.low[node] = self.low[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.low[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                let looped = self.dependents[node].contains(&node);
+                if component.len() > 1 || looped {
+                    component.sort_unstable();
+                    self.cycles.push(component);
+                }
+            }
+        }
+    }
+
+    let count = dependents.len();
+    let mut search = Search {
+        dependents,
+        blocked,
+        index: vec![None; count],
+        low: vec![0; count],
+        stack: Vec::new(),
+        on_stack: vec![false; count],
+        next: 0,
+        cycles: Vec::new(),
+    };
+    for (node, &blocked) in blocked.iter().enumerate() {
+        if blocked && search.index[node].is_none() {
+            search.visit(node);
+        }
+    }
+    search.cycles.sort();
+    search.cycles
+}
+
+/// Describes a type for a message.
+fn describe(ty: &Ty, types: &TypeTable) -> String {
+    match ty {
+        Ty::Var(_) if types.is_numeric(ty) => "a number".to_string(),
+        Ty::Var(_) => "any value".to_string(),
+        ty => ty.to_string(),
+    }
+}
+
+/// Splits rendered code into the comment lines it starts with and the code
+/// after them, both dedented and without trailing whitespace. Comments left
+/// empty by an unset field are dropped.
+fn split_comments(code: &str) -> (Vec<String>, Vec<String>) {
+    let lines: Vec<&str> = code.lines().map(str::trim_end).collect();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut lines = lines
+        .into_iter()
+        .map(|line| line.get(indent..).unwrap_or_default().to_string())
+        .peekable();
+
+    let mut comments = Vec::new();
+    while let Some(line) = lines.peek() {
+        if !line.is_empty() && !line.starts_with("//") {
+            break;
+        }
+        if !line.trim_start_matches('/').trim().is_empty() {
+            comments.push(line.clone());
+        }
+        lines.next();
+    }
+
+    let mut body: Vec<String> = lines.collect();
+    while body.last().map_or(false, |line| line.is_empty()) {
+        body.pop();
+    }
+    (comments, body)
+}
+
+fn classify(body: &[String]) -> Statement {
+    let Some(first) = body.first() else {
+        return Statement::Expr;
+    };
+    let word = first
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or_default();
+    if ITEM_KEYWORDS.contains(&word) || first.starts_with("#[") {
+        return Statement::Item;
+    }
+    match first.strip_prefix("let ") {
+        Some(rest) => {
+            let rest = rest.trim_start();
+            let rest = rest.strip_prefix("mut ").unwrap_or(rest).trim_start();
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            Statement::Let((!name.is_empty()).then_some(name))
+        }
+        None => Statement::Expr,
+    }
+}
+
+/// Ends the last line of a statement with a semicolon.
+fn terminate(mut body: Vec<String>) -> Vec<String> {
+    if let Some(last) = body.last_mut() {
+        if !last.ends_with(';') {
+            last.push(';');
+        }
+    }
+    body
+}
+
+fn variable_name(id: &str) -> String {
+    let id: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("var_{}", id)
+}
+
+fn unique_name(base: String, names: &mut HashSet<String>) -> String {
+    let mut name = base.clone();
+    let mut suffix = 1;
+    while !names.insert(name.clone()) {
+        suffix += 1;
+        name = format!("{}_{}", base, suffix);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    /// Compiles `tests/fixtures/blueprint/<name>.json` and compares the code
+    /// to `<name>.rs` and the diagnostics to `<name>.diagnostics.txt`; a
+    /// missing snapshot means no code or no diagnostics. Run with
+    /// `UPDATE_GOLDEN=1` to write the snapshots instead.
+    fn assert_matches_snapshots(name: &str) -> Compilation {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/blueprint");
+        let text = fs::read_to_string(dir.join(name).with_extension("json")).unwrap();
+        let graph: Graph = serde_json::from_str(&text).unwrap();
+        let compilation = compile(&graph);
+
+        let diagnostics: String = compilation
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let nodes: Vec<&str> = diagnostic.nodes().into_iter().map(String::as_str).collect();
+                format!(
+                    "{:?} {} [{}]: {}\n",
+                    diagnostic.severity(),
+                    diagnostic.code(),
+                    nodes.join(", "),
+                    diagnostic
+                )
+            })
+            .collect();
+        let snapshots = [
+            (
+                format!("{}.rs", name),
+                compilation.code.clone().unwrap_or_default(),
+            ),
+            (format!("{}.diagnostics.txt", name), diagnostics),
+        ];
+
+        for (file, actual) in snapshots {
+            let path = dir.join(&file);
+            if std::env::var_os("UPDATE_GOLDEN").is_some() {
+                if actual.is_empty() {
+                    let _ = fs::remove_file(&path);
+                } else {
+                    fs::write(&path, &actual).unwrap();
+                }
+                continue;
+            }
+            let expected = fs::read_to_string(&path).unwrap_or_default();
+            assert_eq!(actual, expected, "{} differs from the snapshot", file);
+        }
+        compilation
+    }
+
+    #[test]
+    fn widens_numbers_with_casts() {
+        let compilation = assert_matches_snapshots("widening");
+        assert!(!compilation.has_errors());
+        let code = compilation.code.unwrap();
+        assert!(code.contains("(var_count as f64)"));
+        assert!(code.contains("(var_scale as f64)"));
+        assert!(code.contains("(var_count as i64)"));
+    }
+
+    #[test]
+    fn infers_generic_pin_types() {
+        let compilation = assert_matches_snapshots("generics");
+        assert!(!compilation.has_errors());
+        let code = compilation.code.unwrap();
+        assert!(code.contains("let var_list: Vec<u8> = "));
+        assert!(code.contains("let var_first: u8 = "));
+        assert!(code.contains("let (var_pair_left, var_pair_right): (u8, f64) = "));
+        assert!(code.contains("let var_sum: f64 = "));
+    }
+
+    #[test]
+    fn reports_cycles_without_code() {
+        let compilation = assert_matches_snapshots("cycle");
+        assert_eq!(compilation.code, None);
+        let cycles: Vec<_> = compilation
+            .diagnostics
+            .iter()
+            .filter_map(|diagnostic| match diagnostic {
+                CompileError::Cycle { nodes } => Some(nodes.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(cycles, [vec!["b", "c", "d"], vec!["e"]]);
+    }
+
+    #[test]
+    fn reports_pin_type_mismatches() {
+        let compilation = assert_matches_snapshots("mismatch");
+        assert_eq!(compilation.code, None);
+        let mismatches: Vec<_> = compilation
+            .diagnostics
+            .iter()
+            .filter_map(|diagnostic| match diagnostic {
+                CompileError::TypeMismatch {
+                    node,
+                    pin,
+                    expected,
+                    found,
+                    ..
+                } => Some((
+                    node.as_str(),
+                    pin.as_str(),
+                    expected.as_str(),
+                    found.as_str(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            mismatches,
+            [
+                ("narrow", "value", "i32", "f64"),
+                ("sum", "a", "a number", "String"),
+                ("list", "second", "f64", "bool"),
+            ]
+        );
+    }
+}
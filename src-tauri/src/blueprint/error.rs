@@ -0,0 +1,204 @@
+use super::graph::NodeId;
+use crate::error::serialize_as_code_and_message;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Error,
+    /// The graph still compiles.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinDirection {
+    Input,
+    Output,
+}
+
+impl fmt::Display for PinDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinDirection::Input => write!(f, "input"),
+            PinDirection::Output => write!(f, "output"),
+        }
+    }
+}
+
+/// Problems found compiling a Blueprint graph, each tied to the nodes it is
+/// about so the editor can show it on them.
+///
+/// Serialized to the frontend as `{ code, message, severity, nodeIds, pin,
+/// field }`.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CompileError {
+    #[error("More than one node has the id {0:?}")]
+    DuplicateNode(NodeId),
+
+    #[error("Edge {edge:?} connects node {node:?}, which does not exist")]
+    MissingNode { edge: String, node: NodeId },
+
+    #[error("{definition} has no {direction} {pin:?}")]
+    UnknownPin {
+        node: NodeId,
+        definition: String,
+        pin: String,
+        direction: PinDirection,
+    },
+
+    #[error("Input {pin:?} of {definition} is connected more than once")]
+    DuplicateInput {
+        node: NodeId,
+        definition: String,
+        pin: String,
+    },
+
+    #[error("Pin {pin:?} of {definition} has an invalid type {ty:?}: {reason}")]
+    InvalidPinType {
+        node: NodeId,
+        definition: String,
+        pin: String,
+        ty: String,
+        reason: String,
+    },
+
+    #[error("Template of {definition} is invalid: {reason}")]
+    InvalidTemplate {
+        node: NodeId,
+        definition: String,
+        reason: String,
+    },
+
+    #[error("Template of {definition} uses {name:?}, which is not a field or input")]
+    UnknownTemplateValue {
+        node: NodeId,
+        definition: String,
+        name: String,
+    },
+
+    #[error("Field {field:?} of {definition} is required")]
+    MissingField {
+        node: NodeId,
+        definition: String,
+        field: String,
+    },
+
+    #[error("Field {field:?} of {definition} is invalid: {reason}")]
+    InvalidField {
+        node: NodeId,
+        definition: String,
+        field: String,
+        reason: String,
+    },
+
+    #[error("The graph has a cycle through {}", .nodes.join(", "))]
+    Cycle { nodes: Vec<NodeId> },
+
+    #[error("Cannot connect {found} to input {pin:?} of {definition}, which takes {expected}")]
+    TypeMismatch {
+        node: NodeId,
+        /// Node the connection comes from.
+        from: NodeId,
+        definition: String,
+        pin: String,
+        expected: String,
+        found: String,
+    },
+
+    /// The node compiles to a statement or item, so its outputs have no
+    /// value to pass on.
+    #[error("Output {pin:?} of {definition} has no value to connect")]
+    NoValue {
+        node: NodeId,
+        definition: String,
+        pin: String,
+    },
+
+    #[error("Input {pin:?} of {definition} is not connected, so it is {default}")]
+    UnconnectedInput {
+        node: NodeId,
+        definition: String,
+        pin: String,
+        default: String,
+    },
+}
+
+impl CompileError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CompileError::DuplicateNode(_) => "duplicateNode",
+            CompileError::MissingNode { .. } => "missingNode",
+            CompileError::UnknownPin { .. } => "unknownPin",
+            CompileError::DuplicateInput { .. } => "duplicateInput",
+            CompileError::InvalidPinType { .. } => "invalidPinType",
+            CompileError::InvalidTemplate { .. } => "invalidTemplate",
+            CompileError::UnknownTemplateValue { .. } => "unknownTemplateValue",
+            CompileError::MissingField { .. } => "missingField",
+            CompileError::InvalidField { .. } => "invalidField",
+            CompileError::Cycle { .. } => "cycle",
+            CompileError::TypeMismatch { .. } => "typeMismatch",
+            CompileError::NoValue { .. } => "noValue",
+            CompileError::UnconnectedInput { .. } => "unconnectedInput",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            CompileError::UnconnectedInput { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Nodes the problem is on, the one to show it on first.
+    pub fn nodes(&self) -> Vec<&NodeId> {
+        match self {
+            CompileError::DuplicateNode(node) | CompileError::MissingNode { node, .. } => {
+                vec![node]
+            }
+            CompileError::Cycle { nodes } => nodes.iter().collect(),
+            CompileError::TypeMismatch { node, from, .. } => vec![node, from],
+            CompileError::UnknownPin { node, .. }
+            | CompileError::DuplicateInput { node, .. }
+            | CompileError::InvalidPinType { node, .. }
+            | CompileError::InvalidTemplate { node, .. }
+            | CompileError::UnknownTemplateValue { node, .. }
+            | CompileError::MissingField { node, .. }
+            | CompileError::InvalidField { node, .. }
+            | CompileError::NoValue { node, .. }
+            | CompileError::UnconnectedInput { node, .. } => vec![node],
+        }
+    }
+
+    /// Pin the problem is on, if any.
+    pub fn pin(&self) -> Option<&str> {
+        match self {
+            CompileError::UnknownPin { pin, .. }
+            | CompileError::DuplicateInput { pin, .. }
+            | CompileError::InvalidPinType { pin, .. }
+            | CompileError::TypeMismatch { pin, .. }
+            | CompileError::NoValue { pin, .. }
+            | CompileError::UnconnectedInput { pin, .. } => Some(pin),
+            _ => None,
+        }
+    }
+
+    /// Field the problem is on, if any.
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            CompileError::MissingField { field, .. } | CompileError::InvalidField { field, .. } => {
+                Some(field)
+            }
+            _ => None,
+        }
+    }
+}
+
+serialize_as_code_and_message!(
+    CompileError,
+    "severity" => severity,
+    "nodeIds" => nodes,
+    "pin" => pin,
+    "field" => field,
+);
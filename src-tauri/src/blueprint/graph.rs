@@ -0,0 +1,156 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Name templates read the type of the node's first output from.
+pub const TYPE_VALUE: &str = "type";
+
+/// Types a field can have. `enum` is the original node library's name for
+/// `select`, and `multiline` is a string edited in a text area.
+pub const FIELD_TYPES: &[&str] = &[
+    "string",
+    "text",
+    "multiline",
+    "number",
+    "boolean",
+    "select",
+    "enum",
+];
+
+/// Id of a node, as React Flow assigns it in the editor.
+pub type NodeId = String;
+
+/// A Blueprint graph as the editor holds it: React Flow nodes carrying their
+/// definition and field values, and the edges between their pins.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Graph {
+    #[serde(default)]
+    pub nodes: Vec<GraphNode>,
+    #[serde(default)]
+    pub edges: Vec<GraphEdge>,
+}
+
+/// A node of the graph. Other React Flow properties, like its position, are
+/// ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: NodeId,
+    pub data: NodeData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeData {
+    #[serde(rename = "nodeDefinition")]
+    pub definition: NodeDefinition,
+    /// Field values by field name, as edited in the properties panel.
+    #[serde(default)]
+    pub fields: Map<String, Value>,
+}
+
+/// A connection from an output pin to an input pin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    #[serde(default)]
+    pub id: String,
+    pub source: NodeId,
+    /// Pin on the source node; React Flow leaves it out for nodes with a
+    /// single handle.
+    #[serde(default)]
+    pub source_handle: Option<String>,
+    pub target: NodeId,
+    #[serde(default)]
+    pub target_handle: Option<String>,
+}
+
+/// A kind of node, as defined in the YAML node library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub fields: IndexMap<String, FieldDefinition>,
+    #[serde(default)]
+    pub pins: Pins,
+    /// Rust code the node compiles to, with `{{field}}` and `{{input}}`
+    /// placeholders and `{{#if ..}}` blocks.
+    #[serde(default)]
+    pub template: String,
+}
+
+impl NodeDefinition {
+    pub fn input(&self, name: &str) -> Option<usize> {
+        self.pins.inputs.iter().position(|pin| pin.name == name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<usize> {
+        self.pins.outputs.iter().position(|pin| pin.name == name)
+    }
+
+    /// Whether the template can use `name`: a field, an input or the
+    /// output type.
+    pub fn provides(&self, name: &str) -> bool {
+        name == TYPE_VALUE || self.fields.contains_key(name) || self.input(name).is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pins {
+    #[serde(default)]
+    pub inputs: Vec<PinDefinition>,
+    #[serde(default)]
+    pub outputs: Vec<PinDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinDefinition {
+    pub name: String,
+    /// Type of the values on the pin, parsed by `TypeExpr::parse`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether an input may be left unconnected without a warning.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDefinition {
+    /// One of `FIELD_TYPES`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Values a `select` field can take.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<FieldValidation>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldValidation {
+    /// Regular expression the value must match somewhere, like JavaScript's
+    /// `RegExp.test`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+}
@@ -0,0 +1,953 @@
+use super::{
+    error::Severity,
+    graph::{FieldDefinition, NodeDefinition, PinDefinition, FIELD_TYPES},
+    template::Template,
+    types::TypeExpr,
+};
+use crate::assets::AssetWatcher;
+use indexmap::IndexMap;
+use log::{debug, info, warn};
+use parking_lot::{Mutex, RwLock};
+use regex::Regex;
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserializer, Serialize,
+};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+};
+use walkdir::WalkDir;
+
+/// Name of the Tauri event emitted with the `NodeLibrary` whenever it is
+/// reloaded after its files change.
+pub const NODE_LIBRARY_EVENT: &str = "node-library";
+
+/// Directory of a project its plugins live in. A plugin's node definitions
+/// are in the `nodes` directory inside it.
+pub const PLUGIN_DIR: &str = "plugins";
+
+const PLUGIN_NODE_DIR: &str = "nodes";
+
+/// Receives the library every time it is reloaded.
+pub type NodeLibrarySink = Arc<dyn Fn(&NodeLibrary) + Send + Sync>;
+
+/// Where a node definition was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum NodeSource {
+    /// Shipped with the editor.
+    Builtin,
+    /// The user's own, in the editor's config directory.
+    User,
+    /// A plugin of the open project.
+    Plugin { name: String },
+}
+
+/// Directories node definitions are loaded from. Definitions from later
+/// sources replace those of the same name from earlier ones, so users and
+/// plugins can override the builtin nodes.
+#[derive(Debug, Clone, Default)]
+pub struct LibraryDirs {
+    pub builtin: Option<PathBuf>,
+    pub user: Option<PathBuf>,
+    /// `PLUGIN_DIR` of the open project.
+    pub plugins: Option<PathBuf>,
+}
+
+impl LibraryDirs {
+    /// Directories to load, in order.
+    fn sources(&self) -> Vec<(NodeSource, PathBuf)> {
+        let mut sources = Vec::new();
+        if let Some(dir) = &self.builtin {
+            sources.push((NodeSource::Builtin, dir.clone()));
+        }
+        if let Some(dir) = &self.user {
+            sources.push((NodeSource::User, dir.clone()));
+        }
+        if let Some(dir) = &self.plugins {
+            let mut plugins: Vec<_> = fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.join(PLUGIN_NODE_DIR).is_dir())
+                .collect();
+            plugins.sort();
+            for plugin in plugins {
+                let name = plugin
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                sources.push((NodeSource::Plugin { name }, plugin.join(PLUGIN_NODE_DIR)));
+            }
+        }
+        sources
+    }
+
+    /// Directories to watch for changes: the plugin directory as a whole,
+    /// so plugins added later are picked up.
+    fn watched(&self) -> Vec<&Path> {
+        [&self.builtin, &self.user, &self.plugins]
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+}
+
+/// A node definition of the library and where it came from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryNode {
+    #[serde(flatten)]
+    pub definition: NodeDefinition,
+    pub source: NodeSource,
+    pub path: PathBuf,
+    /// Line of the file the definition starts on, from 1.
+    pub line: usize,
+}
+
+/// A definition that could not be loaded, or a file that could not be read.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryProblem {
+    pub severity: Severity,
+    pub path: PathBuf,
+    /// From 1, if the problem is at a known place in the file.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// The node definitions the editor offers, checked so that a broken file
+/// only loses its own definitions.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeLibrary {
+    /// Definitions by name.
+    pub nodes: IndexMap<String, LibraryNode>,
+    /// Names of the definitions in each category.
+    pub categories: IndexMap<String, Vec<String>>,
+    pub problems: Vec<LibraryProblem>,
+}
+
+impl NodeLibrary {
+    /// Loads every `.yaml` and `.yml` file below the directories of `dirs`.
+    pub fn load(dirs: &LibraryDirs) -> Self {
+        let mut library = NodeLibrary::default();
+        for (source, dir) in dirs.sources() {
+            if dir.is_dir() {
+                library.load_dir(&source, &dir);
+            }
+        }
+        for node in library.nodes.values() {
+            library
+                .categories
+                .entry(node.definition.category.clone())
+                .or_default()
+                .push(node.definition.name.clone());
+        }
+        library
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NodeDefinition> {
+        self.nodes.get(name).map(|node| &node.definition)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.problems
+            .iter()
+            .any(|problem| problem.severity == Severity::Error)
+    }
+
+    fn load_dir(&mut self, source: &NodeSource, dir: &Path) {
+        let mut files: Vec<PathBuf> = WalkDir::new(dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| is_definition_file(path))
+            .collect();
+        files.sort();
+
+        // Names defined by this source, which may not repeat
+        let mut defined = HashSet::new();
+        for path in files {
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    self.problem(
+                        Severity::Error,
+                        &path,
+                        None,
+                        format!("Failed to read: {}", e),
+                    );
+                    continue;
+                }
+            };
+            for document in documents(&text) {
+                if let Some(node) = self.load_document(source, &path, &document) {
+                    self.add(node, &mut defined);
+                }
+            }
+        }
+    }
+
+    fn load_document(
+        &mut self,
+        source: &NodeSource,
+        path: &Path,
+        document: &Document,
+    ) -> Option<LibraryNode> {
+        let definition = match serde_yaml::from_str::<NodeDefinition>(&document.text) {
+            Ok(definition) => definition,
+            Err(e) => {
+                let (line, column) = match e.location() {
+                    Some(location) => (
+                        Some(document.line + location.line() - 1),
+                        Some(location.column()),
+                    ),
+                    None => (Some(document.line), None),
+                };
+                let message = yaml_message(&e);
+                self.problems.push(LibraryProblem {
+                    severity: Severity::Error,
+                    path: path.to_path_buf(),
+                    line,
+                    column,
+                    message,
+                });
+                return None;
+            }
+        };
+
+        let invalid = check_definition(&definition);
+        if !invalid.is_empty() {
+            for (key, message) in invalid {
+                let line = document.line_of(&key);
+                self.problem(Severity::Error, path, Some(line), message);
+            }
+            return None;
+        }
+        Some(LibraryNode {
+            definition,
+            source: source.clone(),
+            path: path.to_path_buf(),
+            line: document.line_of(&Key::Name),
+        })
+    }
+
+    fn add(&mut self, node: LibraryNode, defined: &mut HashSet<String>) {
+        let name = node.definition.name.clone();
+        if !defined.insert(name.clone()) {
+            let first = &self.nodes[&name];
+            let message = format!(
+                "{} is already defined at {}:{}",
+                name,
+                first.path.display(),
+                first.line
+            );
+            self.problem(Severity::Error, &node.path, Some(node.line), message);
+            return;
+        }
+        if let Some(previous) = self.nodes.get(&name) {
+            debug!(
+                "{} from {} overrides the one from {}",
+                name,
+                node.path.display(),
+                previous.path.display()
+            );
+        }
+        self.nodes.insert(name, node);
+    }
+
+    fn problem(&mut self, severity: Severity, path: &Path, line: Option<usize>, message: String) {
+        self.problems.push(LibraryProblem {
+            severity,
+            path: path.to_path_buf(),
+            line,
+            column: None,
+            message,
+        });
+    }
+}
+
+fn is_definition_file(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .map_or(false, |name| name.to_string_lossy().starts_with('.'));
+    let yaml = path
+        .extension()
+        .map_or(false, |extension| extension == "yaml" || extension == "yml");
+    yaml && !hidden
+}
+
+/// A YAML document of a file, which holds one definition.
+struct Document {
+    text: String,
+    /// Line of the file the document starts on, from 1.
+    line: usize,
+}
+
+/// Part of a definition a problem is about, to point at its line.
+enum Key<'a> {
+    Name,
+    Category,
+    Template,
+    /// A pin by its index in `inputs` or `outputs`.
+    Pin(&'static str, usize),
+    Field(&'a str),
+}
+
+impl Key<'_> {
+    /// Where the key is in a definition document.
+    fn path(&self) -> Vec<Step<'_>> {
+        match self {
+            Key::Name => vec![Step::Key("name")],
+            Key::Category => vec![Step::Key("category")],
+            Key::Template => vec![Step::Key("template")],
+            Key::Pin(list, index) => vec![Step::Key("pins"), Step::Key(list), Step::Index(*index)],
+            Key::Field(name) => vec![Step::Key("fields"), Step::Key(name)],
+        }
+    }
+}
+
+impl Document {
+    /// Line of the file `key` is written on, or where the document starts.
+    fn line_of(&self, key: &Key) -> usize {
+        let path = key.path();
+        // The locator fails where it finds the key, and YAML errors carry
+        // the position of the event they happened on
+        let found = Locator(&path).deserialize(serde_yaml::Deserializer::from_str(&self.text));
+        match found {
+            Err(e) => e
+                .location()
+                .map_or(self.line, |location| self.line + location.line() - 1),
+            Ok(()) => self.line,
+        }
+    }
+}
+
+/// A step from a YAML node to one inside it.
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Walks a YAML document along its path, failing on the event the path
+/// ends at, so the error is positioned there. Succeeds if the path does not
+/// exist.
+struct Locator<'a>(&'a [Step<'a>]);
+
+impl<'de> DeserializeSeed<'de> for Locator<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        match self.0.first() {
+            Some(Step::Key(_)) => deserializer.deserialize_map(self),
+            Some(Step::Index(_)) => deserializer.deserialize_seq(self),
+            // Any value is unexpected
+            None => deserializer.deserialize_any(Found),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Locator<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a mapping or sequence")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Some((Step::Key(key), rest)) = self.0.split_first() else {
+            return Ok(());
+        };
+        let seed = KeySeed {
+            key,
+            found: rest.is_empty(),
+        };
+        while let Some(matches) = map.next_key_seed(seed.clone())? {
+            if matches {
+                map.next_value_seed(Locator(rest))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let Some((Step::Index(index), rest)) = self.0.split_first() else {
+            return Ok(());
+        };
+        for _ in 0..*index {
+            if seq.next_element::<IgnoredAny>()?.is_none() {
+                return Ok(());
+            }
+        }
+        seq.next_element_seed(Locator(rest))?;
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(())
+    }
+}
+
+/// Rejects every value, at its position.
+struct Found;
+
+impl Visitor<'_> for Found {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("nothing")
+    }
+}
+
+/// Whether a mapping key is `key`, failing on it if it is the one `found`.
+#[derive(Clone)]
+struct KeySeed<'a> {
+    key: &'a str,
+    found: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for KeySeed<'_> {
+    type Value = bool;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a mapping key")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<bool, E> {
+        if value != self.key {
+            return Ok(false);
+        }
+        if self.found {
+            return Err(E::custom(format_args!("found {:?}", value)));
+        }
+        Ok(true)
+    }
+
+    // Keys that are not strings are never the key
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<bool, A::Error> {
+        IgnoredAny.visit_seq(seq).map(|_| false)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<bool, A::Error> {
+        IgnoredAny.visit_map(map).map(|_| false)
+    }
+}
+
+/// Splits a file into the documents between its `---` lines. YAML would
+/// parse them all at once, but then a broken document would lose the rest.
+fn documents(text: &str) -> Vec<Document> {
+    let mut documents = Vec::new();
+    let mut current = Document {
+        text: String::new(),
+        line: 1,
+    };
+    for (i, line) in text.lines().enumerate() {
+        let marker = line.trim_end();
+        if marker == "---" || marker == "..." {
+            let next = Document {
+                text: String::new(),
+                line: i + 2,
+            };
+            documents.push(std::mem::replace(&mut current, next));
+        } else {
+            current.text.push_str(line);
+            current.text.push('\n');
+        }
+    }
+    documents.push(current);
+
+    documents.retain(|document| {
+        document.text.lines().any(|line| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+    });
+    documents
+}
+
+/// The message of a YAML error, without the location it ends with.
+fn yaml_message(error: &serde_yaml::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(end) if error.location().is_some() => message[..end].to_string(),
+        _ => message,
+    }
+}
+
+/// Problems of a definition the compiler would otherwise trip over, with
+/// what they are about.
+fn check_definition(definition: &NodeDefinition) -> Vec<(Key<'_>, String)> {
+    let mut invalid = Vec::new();
+    let name = &definition.name;
+    if name.trim().is_empty() {
+        invalid.push((Key::Name, "Definition has no name".to_string()));
+        return invalid;
+    }
+    if definition.category.trim().is_empty() {
+        invalid.push((Key::Category, format!("{} has no category", name)));
+    }
+
+    for (direction, list, pins) in [
+        ("input", "inputs", &definition.pins.inputs),
+        ("output", "outputs", &definition.pins.outputs),
+    ] {
+        let mut seen = HashSet::new();
+        for (index, pin) in pins.iter().enumerate() {
+            if let Some(message) = check_pin(name, direction, pin, &mut seen) {
+                invalid.push((Key::Pin(list, index), message));
+            }
+        }
+    }
+
+    for (field_name, field) in &definition.fields {
+        if let Some(reason) = check_field(field) {
+            let message = format!("Field {:?} of {} {}", field_name, name, reason);
+            invalid.push((Key::Field(field_name), message));
+        }
+    }
+
+    match Template::parse(&definition.template) {
+        Ok(template) => {
+            let mut reported = HashSet::new();
+            for value in template.names() {
+                if !definition.provides(value) && reported.insert(value) {
+                    let message = format!(
+                        "Template of {} uses {:?}, which is not a field or input",
+                        name, value
+                    );
+                    invalid.push((Key::Template, message));
+                }
+            }
+        }
+        Err(reason) => {
+            let message = format!("Template of {} is invalid: {}", name, reason);
+            invalid.push((Key::Template, message));
+        }
+    }
+    invalid
+}
+
+fn check_pin<'a>(
+    definition: &str,
+    direction: &str,
+    pin: &'a PinDefinition,
+    seen: &mut HashSet<&'a str>,
+) -> Option<String> {
+    if pin.name.trim().is_empty() {
+        return Some(format!("An {} of {} has no name", direction, definition));
+    }
+    if !seen.insert(&pin.name) {
+        return Some(format!(
+            "{} has more than one {} {:?}",
+            definition, direction, pin.name
+        ));
+    }
+    TypeExpr::parse(&pin.ty).err().map(|reason| {
+        format!(
+            "Pin {:?} of {} has an invalid type {:?}: {}",
+            pin.name, definition, pin.ty, reason
+        )
+    })
+}
+
+/// Why a field definition is invalid, as the end of a sentence.
+fn check_field(field: &FieldDefinition) -> Option<String> {
+    if !FIELD_TYPES.contains(&field.ty.as_str()) {
+        return Some(format!(
+            "has the unknown type {:?}, expected one of {}",
+            field.ty,
+            FIELD_TYPES.join(", ")
+        ));
+    }
+    let select = field.ty == "select" || field.ty == "enum";
+    if select && field.options.is_empty() {
+        return Some("has no options".to_string());
+    }
+
+    if let Some(default) = &field.default {
+        let valid = match field.ty.as_str() {
+            "number" => default.is_number(),
+            "boolean" => default.is_boolean(),
+            _ if select => default
+                .as_str()
+                .map_or(false, |default| field.options.iter().any(|o| o == default)),
+            _ => !default.is_array() && !default.is_object(),
+        };
+        if !valid && !default.is_null() {
+            return Some(format!("has the invalid default {}", default_text(default)));
+        }
+    }
+
+    let validation = field.validation.as_ref()?;
+    if let Some(pattern) = &validation.pattern {
+        if let Err(e) = Regex::new(pattern) {
+            return Some(format!("has an invalid pattern: {}", e));
+        }
+    }
+    if let (Some(min), Some(max)) = (validation.min, validation.max) {
+        if min > max {
+            return Some(format!("has a min of {} above its max of {}", min, max));
+        }
+    }
+    if let (Some(min), Some(max)) = (validation.min_length, validation.max_length) {
+        if min > max {
+            return Some(format!(
+                "has a minLength of {} above its maxLength of {}",
+                min, max
+            ));
+        }
+    }
+    None
+}
+
+fn default_text(default: &Value) -> String {
+    serde_json::to_string(default).unwrap_or_default()
+}
+
+#[derive(Default)]
+struct Shared {
+    dirs: Mutex<LibraryDirs>,
+    library: RwLock<Arc<NodeLibrary>>,
+    watchers: Mutex<Vec<AssetWatcher>>,
+    sink: RwLock<Option<NodeLibrarySink>>,
+}
+
+/// The node library of the editor, reloaded whenever its files change.
+#[derive(Clone, Default)]
+pub struct NodeLibraryState {
+    shared: Arc<Shared>,
+}
+
+impl NodeLibraryState {
+    /// Hands the library to `sink` after every reload from now on, or stops.
+    pub fn set_sink(&self, sink: Option<NodeLibrarySink>) {
+        *self.shared.sink.write() = sink;
+    }
+
+    /// Loads the library from `dirs` and watches them for changes.
+    pub fn set_dirs(&self, dirs: LibraryDirs) -> Arc<NodeLibrary> {
+        *self.shared.dirs.lock() = dirs.clone();
+        let library = self.reload();
+        self.watch(&dirs);
+        library
+    }
+
+    /// Loads the plugins of the project at `root` instead of those of the
+    /// previous project.
+    pub fn set_project(&self, root: Option<&Path>) -> Arc<NodeLibrary> {
+        let mut dirs = self.shared.dirs.lock().clone();
+        dirs.plugins = root.map(|root| root.join(PLUGIN_DIR));
+        self.set_dirs(dirs)
+    }
+
+    pub fn library(&self) -> Arc<NodeLibrary> {
+        self.shared.library.read().clone()
+    }
+
+    /// Loads the library again, and hands it to the sink.
+    pub fn reload(&self) -> Arc<NodeLibrary> {
+        reload(&self.shared)
+    }
+
+    fn watch(&self, dirs: &LibraryDirs) {
+        // Stop the previous watchers before starting new ones on the same dirs
+        let mut watchers = self.shared.watchers.lock();
+        watchers.clear();
+        for dir in dirs.watched() {
+            let shared = Arc::downgrade(&self.shared);
+            let watcher = AssetWatcher::start(dir, move |paths| {
+                on_change(&shared, &paths);
+            });
+            match watcher {
+                Ok(watcher) => watchers.push(watcher),
+                Err(e) => warn!(
+                    "Failed to watch node definitions in {}: {}",
+                    dir.display(),
+                    e
+                ),
+            }
+        }
+    }
+}
+
+fn on_change(shared: &Weak<Shared>, paths: &[PathBuf]) {
+    // Directories changing may add or remove definition files below them
+    let relevant = paths
+        .iter()
+        .any(|path| is_definition_file(path) || path.extension().is_none());
+    if let (true, Some(shared)) = (relevant, shared.upgrade()) {
+        reload(&shared);
+    }
+}
+
+fn reload(shared: &Shared) -> Arc<NodeLibrary> {
+    let dirs = shared.dirs.lock().clone();
+    let library = Arc::new(NodeLibrary::load(&dirs));
+    info!(
+        "Loaded {} node definitions with {} problems",
+        library.nodes.len(),
+        library.problems.len()
+    );
+    for problem in &library.problems {
+        let line = problem
+            .line
+            .map(|line| format!(":{}", line))
+            .unwrap_or_default();
+        warn!("{}{}: {}", problem.path.display(), line, problem.message);
+    }
+
+    *shared.library.write() = library.clone();
+    let sink = shared.sink.read().clone();
+    if let Some(sink) = sink {
+        sink(&library);
+    }
+    library
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "pulsar-library-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn write(&self, name: &str, text: &str) -> PathBuf {
+            let path = self.0.join(name);
+            fs::write(&path, text).unwrap();
+            path
+        }
+
+        fn load(&self) -> NodeLibrary {
+            NodeLibrary::load(&LibraryDirs {
+                user: Some(self.0.clone()),
+                ..LibraryDirs::default()
+            })
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Lines and messages of the problems, in order.
+    fn problems(library: &NodeLibrary) -> Vec<(Option<usize>, &str)> {
+        library
+            .problems
+            .iter()
+            .map(|problem| (problem.line, problem.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn loads_every_definition_with_its_line() {
+        let dir = TempDir::new("valid");
+        let path = dir.write(
+            "math.yaml",
+            r#"---
+# A field called `name` comes before the definition's name
+fields:
+  name:
+    type: string
+name: Label
+category: Text
+template: '{{name}}'
+---
+name: Negate
+category: Math
+pins:
+  inputs:
+    - name: value
+      type: number
+  outputs:
+    - name: negated
+      type: number
+template: "-{{value}}"
+"#,
+        );
+
+        let library = dir.load();
+        assert!(library.problems.is_empty(), "{:?}", library.problems);
+        let label = &library.nodes["Label"];
+        assert_eq!((label.line, &label.path), (6, &path));
+        assert_eq!(label.source, NodeSource::User);
+        assert_eq!(library.nodes["Negate"].line, 10);
+        assert_eq!(library.get("Negate").unwrap().pins.inputs[0].name, "value");
+        assert_eq!(library.categories["Text"], ["Label"]);
+        assert_eq!(library.categories["Math"], ["Negate"]);
+    }
+
+    #[test]
+    fn reports_a_duplicate_name_on_the_second_definition() {
+        let dir = TempDir::new("duplicate");
+        let first = dir.write("a.yaml", "name: Add\ncategory: Math\ntemplate: '1 + 1'\n");
+        dir.write(
+            "b.yaml",
+            "name: Zero\ncategory: Math\ntemplate: '0'\n---\n\nname: Add\ncategory: Math\ntemplate: '2'\n",
+        );
+
+        let library = dir.load();
+        let message = format!("Add is already defined at {}:1", first.display());
+        assert_eq!(problems(&library), [(Some(6), message.as_str())]);
+        assert!(library.problems[0].path.ends_with("b.yaml"));
+        assert_eq!(library.nodes["Add"].definition.template, "1 + 1");
+        assert!(library.has_errors());
+    }
+
+    #[test]
+    fn reports_a_missing_field_where_its_mapping_starts() {
+        let dir = TempDir::new("missing");
+        dir.write(
+            "nodes.yaml",
+            r#"name: Valid
+category: Math
+template: '1'
+---
+category: Math
+template: '2'
+---
+name: Print
+category: Debug
+pins:
+  inputs:
+    - name: value
+      description: No type
+template: 'println!("{:?}", {{value}});'
+"#,
+        );
+
+        let library = dir.load();
+        assert_eq!(
+            problems(&library),
+            [
+                (Some(5), "missing field `name`"),
+                (Some(12), "pins.inputs[0]: missing field `type`"),
+            ]
+        );
+        // The broken definitions do not take the valid one with them
+        assert_eq!(library.nodes.keys().collect::<Vec<_>>(), ["Valid"]);
+    }
+
+    #[test]
+    fn reports_type_errors_on_their_line() {
+        let dir = TempDir::new("types");
+        dir.write(
+            "nodes.yaml",
+            r#"name: Optional
+category: Math
+pins:
+  inputs:
+    - name: value
+      type: number
+      optional: maybe
+template: '{{value}}'
+---
+name: Convert
+category: Math
+pins:
+  inputs:
+    - name: value
+      type: number
+  outputs:
+    - name: value
+      type: Vec<number
+template: '{{value}}'
+"#,
+        );
+
+        let library = dir.load();
+        let problems = problems(&library);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert_eq!(problems[0].0, Some(7));
+        assert!(
+            problems[0]
+                .1
+                .starts_with("pins.inputs[0].optional: invalid type"),
+            "{}",
+            problems[0].1
+        );
+        // The output, not the input of the same name before it
+        assert_eq!(problems[1].0, Some(17));
+        assert!(
+            problems[1]
+                .1
+                .starts_with(r#"Pin "value" of Convert has an invalid type "Vec<number""#),
+            "{}",
+            problems[1].1
+        );
+    }
+
+    #[test]
+    fn points_at_the_field_a_problem_is_about() {
+        let dir = TempDir::new("field");
+        dir.write(
+            "nodes.yaml",
+            r#"name: Pick
+category: Logic
+fields:
+  label:
+    type: string
+    default: options
+  options:
+    type: select
+template: '{{options}}'
+"#,
+        );
+
+        let library = dir.load();
+        assert_eq!(
+            problems(&library),
+            [(Some(7), r#"Field "options" of Pick has no options"#)]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Blueprint visual scripts.
+//!
+//! Graphs come from the Blueprint editor as React Flow nodes, each carrying
+//! its definition from the node library, and compile to Rust source. Pin
+//! types are checked and inferred across connections, and every problem is
+//! reported against the nodes it is on.
+//!
+//! The node library itself is loaded from YAML files of the editor, the
+//! user and the open project's plugins, and reloaded when they change.
+//!
+//! Graphs also run in the editor through an interpreter, `vm`, which can
+//! pause on breakpoints and step from node to node, and their code can be
+//! checked with `cargo check` to find what the compiler's own checks miss.
+
+pub mod check;
+pub mod compile;
+pub mod error;
+pub mod graph;
+pub mod library;
+pub mod template;
+pub mod types;
+pub mod vm;
+
+pub use check::{CargoCheck, CargoDiagnostic, CheckError, CheckResult, CheckState, CHECK_DIR};
+pub use compile::{compile, Compilation, NodeSpan};
+pub use error::{CompileError, PinDirection, Severity};
+pub use graph::{Graph, GraphEdge, GraphNode, NodeDefinition, NodeId};
+pub use library::{
+    LibraryDirs, LibraryNode, LibraryProblem, NodeLibrary, NodeLibrarySink, NodeLibraryState,
+    NodeSource, NODE_LIBRARY_EVENT,
+};
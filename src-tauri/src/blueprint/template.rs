@@ -0,0 +1,273 @@
+use serde_json::Value;
+use std::{cmp::Ordering, collections::HashMap};
+
+/// A parsed node template.
+///
+/// Templates are Rust code with `{{name}}` placeholders for fields and
+/// inputs, and `{{#if condition}} .. {{else}} .. {{/if}}` blocks. A condition
+/// is a name, true unless its value is empty, `false` or `0`, or a
+/// comparison like `count > 1` with `<`, `>`, `<=`, `>=`, `==` or `!=`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    Value(String),
+    If {
+        condition: Condition,
+        then: Vec<Segment>,
+        otherwise: Vec<Segment>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    Truthy(String),
+    Compare(String, Compare, String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compare {
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Eq,
+    Ne,
+}
+
+/// Values placeholders are replaced with, by name.
+pub type TemplateContext<'a> = HashMap<&'a str, Value>;
+
+enum Tag<'a> {
+    If(&'a str),
+    Else,
+    EndIf,
+    Value(&'a str),
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut rest = source;
+        match parse_block(&mut rest)? {
+            (segments, BlockEnd::Source) => Ok(Self { segments }),
+            (_, BlockEnd::Else) => Err("{{else}} outside of {{#if}}".to_string()),
+            (_, BlockEnd::EndIf) => Err("{{/if}} without {{#if}}".to_string()),
+        }
+    }
+
+    /// Names the template reads from its context. Comparison operands that
+    /// are not in the context are taken literally, so they are left out.
+    pub fn names(&self) -> Vec<&str> {
+        fn collect<'a>(segments: &'a [Segment], names: &mut Vec<&'a str>) {
+            for segment in segments {
+                match segment {
+                    Segment::Text(_) => {}
+                    Segment::Value(name) => names.push(name),
+                    Segment::If {
+                        condition,
+                        then,
+                        otherwise,
+                    } => {
+                        if let Condition::Truthy(name) = condition {
+                            names.push(name);
+                        }
+                        collect(then, names);
+                        collect(otherwise, names);
+                    }
+                }
+            }
+        }
+
+        let mut names = Vec::new();
+        collect(&self.segments, &mut names);
+        names
+    }
+
+    /// Renders the template. Values spanning several lines keep the
+    /// indentation of the line they are placed on.
+    pub fn render(&self, context: &TemplateContext) -> String {
+        let mut out = String::new();
+        render_block(&self.segments, context, &mut out);
+        out
+    }
+}
+
+/// What ended a block of segments.
+enum BlockEnd {
+    Source,
+    Else,
+    EndIf,
+}
+
+fn parse_block<'a>(rest: &mut &'a str) -> Result<(Vec<Segment>, BlockEnd), String> {
+    let mut segments = Vec::new();
+    loop {
+        let source: &'a str = rest;
+        let Some(start) = source.find("{{") else {
+            if !source.is_empty() {
+                segments.push(Segment::Text(source.to_string()));
+            }
+            *rest = "";
+            return Ok((segments, BlockEnd::Source));
+        };
+        if start > 0 {
+            segments.push(Segment::Text(source[..start].to_string()));
+        }
+        let end = source[start..]
+            .find("}}")
+            .ok_or_else(|| "A {{ is missing its }}".to_string())?
+            + start;
+        let tag = parse_tag(source[start + 2..end].trim())?;
+        *rest = &source[end + 2..];
+
+        match tag {
+            Tag::Value(name) => segments.push(Segment::Value(name.to_string())),
+            Tag::If(condition) => {
+                let condition = parse_condition(condition)?;
+                let (then, end) = parse_block(rest)?;
+                let otherwise = match end {
+                    BlockEnd::EndIf => Vec::new(),
+                    BlockEnd::Else => match parse_block(rest)? {
+                        (otherwise, BlockEnd::EndIf) => otherwise,
+                        (_, BlockEnd::Else) => {
+                            return Err("{{#if}} has more than one {{else}}".to_string())
+                        }
+                        (_, BlockEnd::Source) => {
+                            return Err("{{#if}} is missing its {{/if}}".to_string())
+                        }
+                    },
+                    BlockEnd::Source => return Err("{{#if}} is missing its {{/if}}".to_string()),
+                };
+                segments.push(Segment::If {
+                    condition,
+                    then,
+                    otherwise,
+                });
+            }
+            Tag::Else => return Ok((segments, BlockEnd::Else)),
+            Tag::EndIf => return Ok((segments, BlockEnd::EndIf)),
+        }
+    }
+}
+
+fn parse_tag(tag: &str) -> Result<Tag<'_>, String> {
+    if let Some(condition) = tag.strip_prefix("#if") {
+        let condition = condition.trim();
+        if condition.is_empty() {
+            return Err("{{#if}} needs a condition".to_string());
+        }
+        return Ok(Tag::If(condition));
+    }
+    match tag {
+        "else" => Ok(Tag::Else),
+        "/if" => Ok(Tag::EndIf),
+        "" => Err("Empty {{}}".to_string()),
+        _ if tag.starts_with('#') || tag.starts_with('/') => {
+            Err(format!("Unknown block {{{{{}}}}}", tag))
+        }
+        _ => Ok(Tag::Value(tag)),
+    }
+}
+
+fn parse_condition(condition: &str) -> Result<Condition, String> {
+    let parts: Vec<&str> = condition.split_whitespace().collect();
+    match parts[..] {
+        [name] => Ok(Condition::Truthy(name.to_string())),
+        [left, op, right] => {
+            let op = match op {
+                "<" => Compare::Lt,
+                ">" => Compare::Gt,
+                "<=" => Compare::Le,
+                ">=" => Compare::Ge,
+                "==" => Compare::Eq,
+                "!=" => Compare::Ne,
+                _ => return Err(format!("Unknown comparison {:?}", op)),
+            };
+            Ok(Condition::Compare(left.to_string(), op, right.to_string()))
+        }
+        _ => Err(format!("Invalid condition {:?}", condition)),
+    }
+}
+
+fn render_block(segments: &[Segment], context: &TemplateContext, out: &mut String) {
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => out.push_str(text),
+            Segment::Value(name) => {
+                let value = context.get(name.as_str()).map(to_text).unwrap_or_default();
+                let indent: String = out
+                    .rsplit('\n')
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .take_while(|c| c.is_whitespace())
+                    .collect();
+                out.push_str(&value.replace('\n', &format!("\n{}", indent)));
+            }
+            Segment::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if evaluate(condition, context) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_block(branch, context, out);
+            }
+        }
+    }
+}
+
+/// Text a value is placed in the code as.
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Whether a value counts as set: not empty, `false` or `0`. Field values
+/// edited as text are strings, so `"false"` and `"0"` count as unset too.
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !matches!(text.trim(), "" | "false" | "0"),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+fn evaluate(condition: &Condition, context: &TemplateContext) -> bool {
+    match condition {
+        Condition::Truthy(name) => context.get(name.as_str()).map_or(false, is_truthy),
+        Condition::Compare(left, op, right) => {
+            let operand = |name: &String| {
+                context
+                    .get(name.as_str())
+                    .map_or_else(|| name.clone(), to_text)
+            };
+            let (left, right) = (operand(left), operand(right));
+            let ordering = match (left.trim().parse::<f64>(), right.trim().parse::<f64>()) {
+                (Ok(left), Ok(right)) => left.partial_cmp(&right),
+                _ => Some(left.cmp(&right)),
+            };
+            match (op, ordering) {
+                (Compare::Ne, ordering) => ordering != Some(Ordering::Equal),
+                (_, None) => false,
+                (Compare::Lt, Some(ordering)) => ordering == Ordering::Less,
+                (Compare::Gt, Some(ordering)) => ordering == Ordering::Greater,
+                (Compare::Le, Some(ordering)) => ordering != Ordering::Greater,
+                (Compare::Ge, Some(ordering)) => ordering != Ordering::Less,
+                (Compare::Eq, Some(ordering)) => ordering == Ordering::Equal,
+            }
+        }
+    }
+}
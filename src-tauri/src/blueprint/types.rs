@@ -0,0 +1,372 @@
+use std::{collections::HashMap, fmt};
+
+/// A pin type as written in a node definition.
+///
+/// Besides Rust types like `f64` or `Vec<T>`, definitions use the names of
+/// the original node library: `boolean`, `string`, `array` (a `Vec` of
+/// anything), `T[]`, `any` and `number`. Single capital letters, optionally
+/// followed by digits, are type parameters shared by the pins of a node, so
+/// `Vec<T>` in and `T` out relate the two. `number` is a numeric type
+/// parameter shared the same way, while every `any` stands on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeExpr {
+    /// Execution flow; orders nodes but carries no value.
+    Exec,
+    /// A type constructor applied to arguments: `f64`, `Vec<T>`.
+    Con(String, Vec<TypeExpr>),
+    /// A type parameter of the node.
+    Param(String),
+    /// A type parameter of the pin.
+    Any,
+}
+
+/// The type parameter name `number` maps to.
+const NUMBER: &str = "number";
+
+const NUMERIC_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64",
+];
+
+/// Numeric types a value converts to with `as` when connected, by type.
+const WIDENINGS: &[(&str, &[&str])] = &[("i32", &["i64", "f32", "f64"]), ("f32", &["f64"])];
+
+impl TypeExpr {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let ty = parser.ty()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(ty),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_alphanumeric() || c == '_' || c == ':' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == ':') {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(word);
+        } else if "<>,[]".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else {
+            return Err(format!("unexpected {:?}", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self
+            .tokens
+            .get(self.pos)
+            .map_or(false, |next| next == token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}", token))
+        }
+    }
+
+    fn ty(&mut self) -> Result<TypeExpr, String> {
+        let mut ty = self.atom()?;
+        // `T[]`, as the original node library writes arrays
+        while self.eat("[") {
+            self.expect("]")?;
+            ty = TypeExpr::Con("Vec".to_string(), vec![ty]);
+        }
+        Ok(ty)
+    }
+
+    fn atom(&mut self) -> Result<TypeExpr, String> {
+        let name = match self.next() {
+            Some("[") => {
+                let element = self.ty()?;
+                self.expect("]")?;
+                return Ok(TypeExpr::Con("Vec".to_string(), vec![element]));
+            }
+            Some(name) if !"<>,]".contains(name) => name.to_string(),
+            Some(token) => return Err(format!("unexpected {:?}", token)),
+            None => return Err("expected a type".to_string()),
+        };
+
+        let mut args = Vec::new();
+        if self.eat("<") {
+            loop {
+                args.push(self.ty()?);
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(">")?;
+        }
+
+        let no_args = |ty: TypeExpr| {
+            if args.is_empty() {
+                Ok(ty)
+            } else {
+                Err(format!("{} takes no type arguments", name))
+            }
+        };
+        match name.as_str() {
+            "exec" | "execution" => no_args(TypeExpr::Exec),
+            "any" => no_args(TypeExpr::Any),
+            NUMBER => no_args(TypeExpr::Param(NUMBER.to_string())),
+            "bool" | "boolean" => no_args(TypeExpr::Con("bool".to_string(), Vec::new())),
+            "string" => no_args(TypeExpr::Con("String".to_string(), Vec::new())),
+            "array" | "Vec" => match args.len() {
+                0 => Ok(TypeExpr::Con("Vec".to_string(), vec![TypeExpr::Any])),
+                1 => Ok(TypeExpr::Con("Vec".to_string(), args)),
+                _ => Err(format!("{} takes one type argument", name)),
+            },
+            _ if is_param(&name) => no_args(TypeExpr::Param(name.clone())),
+            _ => Ok(TypeExpr::Con(name, args)),
+        }
+    }
+}
+
+fn is_param(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().map_or(false, |c| c.is_ascii_uppercase()) && chars.all(|c| c.is_ascii_digit())
+}
+
+/// A type during inference, where type variables stand for what is not
+/// known yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ty {
+    Exec,
+    Con(String, Vec<Ty>),
+    Var(usize),
+}
+
+impl Ty {
+    fn is_numeric(&self) -> bool {
+        matches!(self, Ty::Con(name, args) if args.is_empty() && NUMERIC_TYPES.contains(&name.as_str()))
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Ty::Con(name, args) if args.is_empty() && (name == "f32" || name == "f64"))
+    }
+
+    /// Whether a value of this type converts to `to` with `as`.
+    pub fn widens_to(&self, to: &Ty) -> bool {
+        match (self, to) {
+            (Ty::Con(from, _), Ty::Con(to, _)) if self.is_numeric() => WIDENINGS
+                .iter()
+                .any(|(name, targets)| name == from && targets.contains(&to.as_str())),
+            _ => false,
+        }
+    }
+
+    /// Whether nothing about the type is known.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Ty::Var(_))
+    }
+
+    /// Expression of the type's default value.
+    pub fn default_value(&self) -> String {
+        match self {
+            Ty::Exec => "()".to_string(),
+            Ty::Con(name, _) => match name.as_str() {
+                "bool" => "false".to_string(),
+                "f32" | "f64" => "0.0".to_string(),
+                "String" => "String::new()".to_string(),
+                "Vec" => "vec![]".to_string(),
+                _ if self.is_numeric() => "0".to_string(),
+                _ => "Default::default()".to_string(),
+            },
+            Ty::Var(_) => "Default::default()".to_string(),
+        }
+    }
+}
+
+/// Formats the type as Rust, with `_` for what is not known.
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Exec => write!(f, "exec"),
+            Ty::Var(_) => write!(f, "_"),
+            Ty::Con(name, args) if args.is_empty() => write!(f, "{}", name),
+            Ty::Con(name, args) => {
+                write!(f, "{}<", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ">")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    bound: Option<Ty>,
+    /// Only numeric types may be bound.
+    numeric: bool,
+}
+
+/// Type variables and what unification has bound them to.
+#[derive(Debug, Clone, Default)]
+pub struct TypeTable {
+    slots: Vec<Slot>,
+}
+
+impl TypeTable {
+    pub fn fresh(&mut self, numeric: bool) -> Ty {
+        self.slots.push(Slot {
+            bound: None,
+            numeric,
+        });
+        Ty::Var(self.slots.len() - 1)
+    }
+
+    /// Turns a pin type into a `Ty`, with a variable for every parameter.
+    /// Parameters already in `scope` share their variable.
+    pub fn instantiate(&mut self, expr: &TypeExpr, scope: &mut HashMap<String, Ty>) -> Ty {
+        match expr {
+            TypeExpr::Exec => Ty::Exec,
+            TypeExpr::Con(name, args) => Ty::Con(
+                name.clone(),
+                args.iter()
+                    .map(|arg| self.instantiate(arg, scope))
+                    .collect(),
+            ),
+            TypeExpr::Param(name) => {
+                if let Some(ty) = scope.get(name) {
+                    return ty.clone();
+                }
+                let ty = self.fresh(name == NUMBER);
+                scope.insert(name.clone(), ty.clone());
+                ty
+            }
+            TypeExpr::Any => self.fresh(false),
+        }
+    }
+
+    /// Whether `ty` is a variable only numeric types may be bound to.
+    pub fn is_numeric(&self, ty: &Ty) -> bool {
+        matches!(self.shallow(ty), Ty::Var(var) if self.slots[var].numeric)
+    }
+
+    /// Substitutes everything known into `ty`.
+    pub fn resolve(&self, ty: &Ty) -> Ty {
+        match ty {
+            Ty::Var(var) => match &self.slots[*var].bound {
+                Some(bound) => self.resolve(bound),
+                None => ty.clone(),
+            },
+            Ty::Con(name, args) => Ty::Con(
+                name.clone(),
+                args.iter().map(|arg| self.resolve(arg)).collect(),
+            ),
+            Ty::Exec => Ty::Exec,
+        }
+    }
+
+    /// Makes `a` and `b` the same type, binding variables as needed. Leaves
+    /// the table as it was if they cannot be.
+    pub fn unify(&mut self, a: &Ty, b: &Ty) -> bool {
+        let saved = self.slots.clone();
+        let unified = self.unify_inner(a, b);
+        if !unified {
+            self.slots = saved;
+        }
+        unified
+    }
+
+    fn unify_inner(&mut self, a: &Ty, b: &Ty) -> bool {
+        let (a, b) = (self.shallow(a), self.shallow(b));
+        match (&a, &b) {
+            (Ty::Var(x), Ty::Var(y)) if x == y => true,
+            (Ty::Var(x), Ty::Var(y)) => {
+                let numeric = self.slots[*x].numeric || self.slots[*y].numeric;
+                self.slots[*y].numeric = numeric;
+                self.slots[*x].bound = Some(b.clone());
+                true
+            }
+            (Ty::Var(var), ty) | (ty, Ty::Var(var)) => self.bind(*var, ty),
+            (Ty::Exec, Ty::Exec) => true,
+            (Ty::Con(a_name, a_args), Ty::Con(b_name, b_args)) => {
+                a_name == b_name
+                    && a_args.len() == b_args.len()
+                    && a_args
+                        .iter()
+                        .zip(b_args)
+                        .all(|(a, b)| self.unify_inner(a, b))
+            }
+            _ => false,
+        }
+    }
+
+    fn bind(&mut self, var: usize, ty: &Ty) -> bool {
+        if self.occurs(var, ty) || (self.slots[var].numeric && !ty.is_numeric()) {
+            return false;
+        }
+        self.slots[var].bound = Some(ty.clone());
+        true
+    }
+
+    fn occurs(&self, var: usize, ty: &Ty) -> bool {
+        match self.shallow(ty) {
+            Ty::Var(other) => other == var,
+            Ty::Con(_, args) => args.iter().any(|arg| self.occurs(var, arg)),
+            Ty::Exec => false,
+        }
+    }
+
+    /// Follows bound variables until a type or an unbound variable.
+    fn shallow(&self, ty: &Ty) -> Ty {
+        match ty {
+            Ty::Var(var) => match &self.slots[*var].bound {
+                Some(bound) => self.shallow(bound),
+                None => ty.clone(),
+            },
+            _ => ty.clone(),
+        }
+    }
+
+    /// Binds numeric variables nothing else decided to `f64`, the type of
+    /// an unsuffixed float literal.
+    pub fn default_numbers(&mut self) {
+        for var in 0..self.slots.len() {
+            if self.slots[var].numeric && self.slots[var].bound.is_none() {
+                self.slots[var].bound = Some(Ty::Con("f64".to_string(), Vec::new()));
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use super::value::Value;
+use std::sync::Arc;
+
+/// A statement of the Rust the interpreter runs.
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    Let {
+        pattern: Pattern,
+        /// Type written after the pattern, kept to give float variables
+        /// float values.
+        ty: Option<String>,
+        value: Option<Expr>,
+    },
+    Expr(Expr),
+    Fn(Arc<Function>),
+    /// An item the interpreter has no use for, like a `struct`.
+    Item,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Ident(String),
+    Wild,
+    Tuple(Vec<Pattern>),
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<Pattern>,
+    pub body: Block,
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub params: Vec<Pattern>,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    /// Expression the block evaluates to, if it does not end with `;`.
+    pub tail: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnOp {
+    Neg,
+    Not,
+    /// `&` and `*`, which make no difference to values.
+    Ref,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Lit(Value),
+    Path(String),
+    Unary(UnOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    /// `place = value`, or `place op= value`.
+    Assign(Box<Expr>, Option<BinOp>, Box<Expr>),
+    Cast(Box<Expr>, String),
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+    },
+    Call(Box<Expr>, Vec<Expr>),
+    MethodCall {
+        receiver: Box<Expr>,
+        method: String,
+        /// Type of a turbofish, like `f64` in `parse::<f64>()`.
+        turbofish: Option<String>,
+        args: Vec<Expr>,
+    },
+    /// `value.0` of a tuple.
+    Field(Box<Expr>, usize),
+    Index(Box<Expr>, Box<Expr>),
+    Tuple(Vec<Expr>),
+    Array(Vec<Expr>),
+    /// `[value; count]`.
+    Repeat(Box<Expr>, Box<Expr>),
+    Block(Block),
+    If(Box<Expr>, Block, Option<Box<Expr>>),
+    While(Box<Expr>, Block),
+    Loop(Block),
+    For(Pattern, Box<Expr>, Block),
+    Break(Option<Box<Expr>>),
+    Continue,
+    Return(Option<Box<Expr>>),
+    Closure(Arc<Closure>),
+    /// `name!(..)`, with the format string of formatting macros as the
+    /// first argument.
+    Macro(String, Vec<Expr>),
+}
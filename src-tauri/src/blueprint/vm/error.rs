@@ -0,0 +1,46 @@
+use super::session::RunId;
+use crate::blueprint::NodeId;
+use crate::error::serialize_as_code_and_message;
+
+/// Errors raised starting or controlling a Blueprint run. Problems of the
+/// running code itself are `RunEvent::Failed` events instead.
+///
+/// Serialized to the frontend as `{ code, message, node }`, `node` being
+/// the node the error is on, if any.
+#[derive(Debug, thiserror::Error)]
+pub enum RunError {
+    #[error("The graph has errors, so it cannot run")]
+    Compile,
+
+    #[error("Cannot run the code of node {node:?}: {message}")]
+    Unsupported { node: NodeId, message: String },
+
+    #[error("Unknown Blueprint run {0}")]
+    UnknownRun(RunId),
+
+    #[error("Failed to start the run: {0}")]
+    Spawn(#[from] std::io::Error),
+}
+
+impl RunError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RunError::Compile => "compile",
+            RunError::Unsupported { .. } => "unsupported",
+            RunError::UnknownRun(_) => "unknownRun",
+            RunError::Spawn(_) => "spawn",
+        }
+    }
+
+    pub fn node(&self) -> Option<&NodeId> {
+        match self {
+            RunError::Unsupported { node, .. } => Some(node),
+            _ => None,
+        }
+    }
+}
+
+serialize_as_code_and_message!(RunError, "node" => node);
+
+pub type RunResult<T> = Result<T, RunError>;
@@ -0,0 +1,2512 @@
+use super::{
+    ast::{BinOp, Block, Closure, Expr, Function, Pattern, Stmt, UnOp},
+    value::Value,
+};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc,
+    },
+};
+
+/// Expressions a node may evaluate before it is taken to loop forever.
+const MAX_STEPS: u64 = 10_000_000;
+
+/// How deep functions may call each other.
+const MAX_DEPTH: usize = 256;
+
+/// Stack the thread running the interpreter needs to reach `MAX_DEPTH`,
+/// which the default stack of a thread is too small for.
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Items a collection may have, so `(0..u64::MAX).collect()` fails instead
+/// of taking all memory.
+const MAX_ITEMS: i64 = 10_000_000;
+
+/// Why a node stopped before its end.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Halt {
+    /// The code failed, like Rust code panicking.
+    Error(String),
+    /// The run was stopped from the outside.
+    Stopped,
+}
+
+/// Leaves the evaluation of an expression early.
+enum Flow {
+    Break(Value),
+    Continue,
+    Return(Value),
+    Halt(Halt),
+}
+
+impl From<String> for Flow {
+    fn from(message: String) -> Self {
+        Flow::Halt(Halt::Error(message))
+    }
+}
+
+type Eval<T = Value> = Result<T, Flow>;
+
+fn fail<T>(message: impl Into<String>) -> Eval<T> {
+    Err(Flow::Halt(Halt::Error(message.into())))
+}
+
+/// A path into a variable, to assign to or mutate part of it.
+enum Step {
+    Index(usize),
+    Field(usize),
+}
+
+/// Runs the statements of nodes one after the other, keeping the variables
+/// of `main` between them.
+pub struct Interpreter {
+    scopes: Vec<HashMap<String, Value>>,
+    functions: HashMap<String, Arc<Function>>,
+    depth: usize,
+    steps: u64,
+    stop: Arc<AtomicBool>,
+    print: Box<dyn FnMut(String) + Send>,
+}
+
+impl Interpreter {
+    /// `print` receives what the code prints; setting `stop` interrupts it.
+    pub fn new(stop: Arc<AtomicBool>, print: impl FnMut(String) + Send + 'static) -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            depth: 0,
+            steps: 0,
+            stop,
+            print: Box::new(print),
+        }
+    }
+
+    /// Makes a function callable from everywhere, as items are in Rust.
+    pub fn define(&mut self, function: Arc<Function>) {
+        self.functions.insert(function.name.clone(), function);
+    }
+
+    /// Value of a variable of `main`.
+    pub fn variable(&self, name: &str) -> Option<&Value> {
+        self.scopes.first()?.get(name)
+    }
+
+    /// Runs statements in `main`. Returns whether they returned from it,
+    /// which ends the program.
+    pub fn run(&mut self, stmts: &[Stmt]) -> Result<bool, Halt> {
+        self.steps = 0;
+        for stmt in stmts {
+            match self.stmt(stmt) {
+                Ok(()) => {}
+                Err(Flow::Return(_)) => return Ok(true),
+                Err(Flow::Break(_) | Flow::Continue) => {
+                    return Err(Halt::Error("`break` outside of a loop".to_string()))
+                }
+                Err(Flow::Halt(halt)) => return Err(halt),
+            }
+        }
+        Ok(false)
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Eval<()> {
+        match stmt {
+            Stmt::Let { pattern, ty, value } => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                let value = match (ty.as_deref(), value) {
+                    (Some("f32" | "f64"), Value::Int(number)) => Value::Float(number as f64),
+                    (_, value) => value,
+                };
+                self.bind(pattern, value)
+            }
+            Stmt::Expr(expr) => self.eval(expr).map(drop),
+            Stmt::Fn(function) => {
+                self.define(function.clone());
+                Ok(())
+            }
+            Stmt::Item => Ok(()),
+        }
+    }
+
+    fn bind(&mut self, pattern: &Pattern, value: Value) -> Eval<()> {
+        match (pattern, value) {
+            (Pattern::Ident(name), value) => {
+                let scope = self.scopes.last_mut().expect("there is always a scope");
+                scope.insert(name.clone(), value);
+                Ok(())
+            }
+            (Pattern::Wild, _) => Ok(()),
+            (Pattern::Tuple(patterns), Value::Tuple(values) | Value::List(values))
+                if patterns.len() == values.len() =>
+            {
+                for (pattern, value) in patterns.iter().zip(values) {
+                    self.bind(pattern, value)?;
+                }
+                Ok(())
+            }
+            (Pattern::Tuple(patterns), value) => fail(format!(
+                "Cannot destructure {} into {} values",
+                value.debug(),
+                patterns.len()
+            )),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn lookup_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
+    /// Runs `f` in a new scope, which is left again however `f` ends.
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> Eval<T>) -> Eval<T> {
+        self.scopes.push(HashMap::new());
+        let result = f(self);
+        self.scopes.pop();
+        result
+    }
+
+    fn block(&mut self, block: &Block) -> Eval {
+        // Counts as a step, so even `loop {}` runs out of them
+        self.tick()?;
+        self.scoped(|this| {
+            for stmt in &block.stmts {
+                if let Stmt::Fn(function) = stmt {
+                    this.define(function.clone());
+                }
+            }
+            for stmt in &block.stmts {
+                this.stmt(stmt)?;
+            }
+            match &block.tail {
+                Some(tail) => this.eval(tail),
+                None => Ok(Value::Unit),
+            }
+        })
+    }
+
+    fn tick(&mut self) -> Eval<()> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return fail(format!(
+                "Ran for more than {} steps, so it may never end",
+                MAX_STEPS
+            ));
+        }
+        if self.steps % 1024 == 0 && self.stop.load(AtomicOrdering::Relaxed) {
+            return Err(Flow::Halt(Halt::Stopped));
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Eval {
+        self.tick()?;
+        match expr {
+            Expr::Lit(value) => Ok(value.clone()),
+            Expr::Path(path) => match self.lookup(path) {
+                Some(value) => Ok(value.clone()),
+                None => constant(path)
+                    .ok_or_else(|| Flow::from(format!("Cannot find value `{}`", path))),
+            },
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand)?;
+                unary(*op, value).map_err(Flow::from)
+            }
+            Expr::Binary(BinOp::And, left, right) => match self.eval(left)? {
+                Value::Bool(false) => Ok(Value::Bool(false)),
+                Value::Bool(true) => self.condition(right).map(Value::Bool),
+                other => fail(format!("Expected a bool, found {}", other.type_name())),
+            },
+            Expr::Binary(BinOp::Or, left, right) => match self.eval(left)? {
+                Value::Bool(true) => Ok(Value::Bool(true)),
+                Value::Bool(false) => self.condition(right).map(Value::Bool),
+                other => fail(format!("Expected a bool, found {}", other.type_name())),
+            },
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary(*op, left, right).map_err(Flow::from)
+            }
+            Expr::Assign(place, op, value) => {
+                let mut value = self.eval(value)?;
+                if let Some(op) = op {
+                    let current = self.eval(place)?;
+                    value = binary(*op, current, value)?;
+                }
+                let (name, steps) = self.place(place)?;
+                let target = self.target(&name, &steps)?;
+                *target = match (&*target, value) {
+                    // A float variable stays one
+                    (Value::Float(_), Value::Int(number)) => Value::Float(number as f64),
+                    (_, value) => value,
+                };
+                Ok(Value::Unit)
+            }
+            Expr::Cast(value, ty) => {
+                let value = self.eval(value)?;
+                cast(value, ty).map_err(Flow::from)
+            }
+            Expr::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let bound = |this: &mut Self, bound: &Option<Box<Expr>>, default: i64| match bound {
+                    Some(bound) => match this.eval(bound)? {
+                        Value::Int(number) => Ok(number),
+                        other => fail(format!(
+                            "Range bounds must be integers, not {}",
+                            other.type_name()
+                        )),
+                    },
+                    None => Ok(default),
+                };
+                let start = bound(self, start, 0)?;
+                let end = bound(self, end, i64::MAX)?;
+                let end = match inclusive {
+                    true => end.saturating_add(1),
+                    false => end,
+                };
+                Ok(Value::Range(start, end))
+            }
+            Expr::Call(callee, args) => self.call(callee, args),
+            Expr::MethodCall {
+                receiver,
+                method,
+                turbofish,
+                args,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Eval<Vec<_>>>()?;
+                if MUTATING.contains(&method.as_str()) {
+                    if let Ok((name, steps)) = self.place(receiver) {
+                        let mut value = self.target(&name, &steps)?.clone();
+                        let result = self.mutate(&mut value, method, args)?;
+                        *self.target(&name, &steps)? = value;
+                        return Ok(result);
+                    }
+                    // A temporary is changed and thrown away
+                    let mut value = self.eval(receiver)?;
+                    return self.mutate(&mut value, method, args);
+                }
+                let receiver = self.eval(receiver)?;
+                self.method(receiver, method, turbofish.as_deref(), args)
+            }
+            Expr::Field(value, index) => match self.eval(value)? {
+                Value::Tuple(mut items) if *index < items.len() => Ok(items.swap_remove(*index)),
+                other => fail(format!("{} has no field {}", other.debug(), index)),
+            },
+            Expr::Index(value, index) => {
+                let value = self.eval(value)?;
+                let index = self.eval(index)?;
+                self.index(value, index)
+            }
+            Expr::Tuple(items) => Ok(match items.is_empty() {
+                true => Value::Unit,
+                false => Value::Tuple(self.eval_all(items)?),
+            }),
+            Expr::Array(items) => Ok(Value::List(self.eval_all(items)?)),
+            Expr::Repeat(value, count) => {
+                let value = self.eval(value)?;
+                match self.eval(count)? {
+                    Value::Int(count) if (0..=MAX_ITEMS).contains(&count) => {
+                        Ok(Value::List(vec![value; count as usize]))
+                    }
+                    other => fail(format!("Invalid number of items {}", other.debug())),
+                }
+            }
+            Expr::Block(block) => self.block(block),
+            Expr::If(condition, then, otherwise) => {
+                if self.condition(condition)? {
+                    self.block(then)
+                } else if let Some(otherwise) = otherwise {
+                    self.eval(otherwise)
+                } else {
+                    Ok(Value::Unit)
+                }
+            }
+            Expr::While(condition, body) => {
+                while self.condition(condition)? {
+                    match self.block(body) {
+                        Ok(_) | Err(Flow::Continue) => {}
+                        Err(Flow::Break(_)) => break,
+                        Err(flow) => return Err(flow),
+                    }
+                }
+                Ok(Value::Unit)
+            }
+            Expr::Loop(body) => loop {
+                match self.block(body) {
+                    Ok(_) | Err(Flow::Continue) => {}
+                    Err(Flow::Break(value)) => return Ok(value),
+                    Err(flow) => return Err(flow),
+                }
+            },
+            Expr::For(pattern, iterable, body) => {
+                let iterable = self.eval(iterable)?;
+                let items: Box<dyn Iterator<Item = Value>> = match iterable {
+                    Value::Range(start, end) => Box::new((start..end).map(Value::Int)),
+                    other => Box::new(items(other)?.into_iter()),
+                };
+                for item in items {
+                    let result = self.scoped(|this| {
+                        this.bind(pattern, item)?;
+                        this.block(body)
+                    });
+                    match result {
+                        Ok(_) | Err(Flow::Continue) => {}
+                        Err(Flow::Break(_)) => break,
+                        Err(flow) => return Err(flow),
+                    }
+                }
+                Ok(Value::Unit)
+            }
+            Expr::Break(value) => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                Err(Flow::Break(value))
+            }
+            Expr::Continue => Err(Flow::Continue),
+            Expr::Return(value) => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                Err(Flow::Return(value))
+            }
+            Expr::Closure(closure) => {
+                // Closures capture by value what is visible where they are made
+                let mut captured = HashMap::new();
+                for scope in &self.scopes {
+                    for (name, value) in scope {
+                        captured.insert(name.clone(), value.clone());
+                    }
+                }
+                let captured = captured.into_iter().collect();
+                Ok(Value::Closure(closure.clone(), Arc::new(captured)))
+            }
+            Expr::Macro(name, args) => self.macro_call(name, args),
+        }
+    }
+
+    fn eval_all(&mut self, exprs: &[Expr]) -> Eval<Vec<Value>> {
+        exprs.iter().map(|expr| self.eval(expr)).collect()
+    }
+
+    fn condition(&mut self, expr: &Expr) -> Eval<bool> {
+        match self.eval(expr)? {
+            Value::Bool(value) => Ok(value),
+            other => fail(format!(
+                "Expected a bool condition, found {}",
+                other.type_name()
+            )),
+        }
+    }
+
+    /// Resolves an assignable expression to a variable and the path into it.
+    fn place(&mut self, expr: &Expr) -> Eval<(String, Vec<Step>)> {
+        match expr {
+            Expr::Path(name) if self.lookup(name).is_some() => Ok((name.clone(), Vec::new())),
+            Expr::Path(name) => fail(format!("Cannot find value `{}`", name)),
+            Expr::Unary(UnOp::Ref, inner) => self.place(inner),
+            Expr::Field(inner, index) => {
+                let (name, mut steps) = self.place(inner)?;
+                steps.push(Step::Field(*index));
+                Ok((name, steps))
+            }
+            Expr::Index(inner, index) => {
+                let (name, mut steps) = self.place(inner)?;
+                match self.eval(index)? {
+                    Value::Int(index) if index >= 0 => steps.push(Step::Index(index as usize)),
+                    other => return fail(format!("Cannot index with {}", other.debug())),
+                }
+                Ok((name, steps))
+            }
+            _ => fail("Cannot assign to this expression"),
+        }
+    }
+
+    fn target(&mut self, name: &str, steps: &[Step]) -> Eval<&mut Value> {
+        let mut target = self
+            .lookup_mut(name)
+            .ok_or_else(|| Flow::from(format!("Cannot find value `{}`", name)))?;
+        for step in steps {
+            let ty = target.type_name();
+            target = match (target, step) {
+                (Value::List(items), Step::Index(index)) => {
+                    let len = items.len();
+                    items.get_mut(*index).ok_or_else(|| {
+                        Flow::from(format!(
+                            "Index out of bounds: the len is {} but the index is {}",
+                            len, index
+                        ))
+                    })?
+                }
+                (Value::Tuple(items), Step::Field(index)) => items
+                    .get_mut(*index)
+                    .ok_or_else(|| Flow::from(format!("The tuple has no field {}", index)))?,
+                _ => return fail(format!("Cannot assign into {}", ty)),
+            };
+        }
+        Ok(target)
+    }
+
+    fn index(&mut self, value: Value, index: Value) -> Eval {
+        match (value, index) {
+            (Value::List(mut items), Value::Int(index)) => {
+                let len = items.len();
+                match usize::try_from(index) {
+                    Ok(index) if index < len => Ok(items.swap_remove(index)),
+                    _ => fail(format!(
+                        "Index out of bounds: the len is {} but the index is {}",
+                        len, index
+                    )),
+                }
+            }
+            (Value::List(items), Value::Range(start, end)) => {
+                let end = end.min(items.len() as i64);
+                match (usize::try_from(start), usize::try_from(end)) {
+                    (Ok(start), Ok(end)) if start <= end => {
+                        Ok(Value::List(items[start..end].to_vec()))
+                    }
+                    _ => fail(format!("Range {}..{} is out of bounds", start, end)),
+                }
+            }
+            (Value::Str(text), Value::Range(start, end)) => {
+                let end = end.min(text.len() as i64);
+                match text.get(start.max(0) as usize..end.max(0) as usize) {
+                    Some(slice) if start >= 0 => Ok(Value::Str(slice.to_string())),
+                    _ => fail(format!("Range {}..{} is out of bounds", start, end)),
+                }
+            }
+            (value, index) => fail(format!(
+                "Cannot index {} with {}",
+                value.type_name(),
+                index.type_name()
+            )),
+        }
+    }
+
+    fn call(&mut self, callee: &Expr, args: &[Expr]) -> Eval {
+        let args = self.eval_all(args)?;
+        let Expr::Path(path) = callee else {
+            let callee = self.eval(callee)?;
+            return self.call_value(callee, args);
+        };
+        if let Some(value) = self.lookup(path) {
+            let value = value.clone();
+            return self.call_value(value, args);
+        }
+        if let Some(function) = self.functions.get(path) {
+            let function = function.clone();
+            return self.call_function(&function, args);
+        }
+
+        let mut args = args;
+        let one = |args: &mut Vec<Value>| match args.len() {
+            1 => Ok(args.pop().expect("one argument")),
+            count => fail(format!("`{}` takes 1 argument, not {}", path, count)),
+        };
+        let name = path.rsplit("::").next().unwrap_or(path);
+        let ty = path.strip_suffix(name).map(|ty| ty.trim_end_matches("::"));
+        match (ty, name) {
+            (Some(""), "Some") => Ok(Value::Option(Some(Box::new(one(&mut args)?)))),
+            (Some(""), "Ok") => Ok(Value::Result(Ok(Box::new(one(&mut args)?)))),
+            (Some(""), "Err") => Ok(Value::Result(Err(Box::new(one(&mut args)?)))),
+            (Some(""), "drop") => one(&mut args).map(|_| Value::Unit),
+            (_, "max" | "min") if args.len() == 2 && !is_primitive(ty) => {
+                let (b, a) = (args.pop().expect("two"), args.pop().expect("two"));
+                let order = compare(&a, &b).ok_or_else(|| {
+                    Flow::from(format!("Cannot compare {} and {}", a.debug(), b.debug()))
+                })?;
+                // Of equal values, `max` returns the second and `min` the first
+                Ok(match (name, order) {
+                    ("max", Ordering::Greater) | ("min", Ordering::Less | Ordering::Equal) => a,
+                    _ => b,
+                })
+            }
+            (Some("String"), "new") => Ok(Value::Str(String::new())),
+            (Some("Vec"), "new" | "with_capacity") => Ok(Value::List(Vec::new())),
+            (Some("String"), "from") => Ok(Value::Str(one(&mut args)?.to_string())),
+            (Some(ty), "from") if is_primitive(Some(ty)) => {
+                cast(one(&mut args)?, ty).map_err(Flow::from)
+            }
+            (Some(_), "from" | "into") => one(&mut args),
+            (Some("Default"), "default") => fail("`Default::default()` needs a known type"),
+            (Some(ty), "default") => {
+                default(ty).ok_or_else(|| Flow::from(format!("No default for {}", ty)))
+            }
+            // `f64::sqrt(x)` is `x.sqrt()`
+            (Some(ty), method) if is_primitive(Some(ty)) && !args.is_empty() => {
+                let receiver = args.remove(0);
+                self.method(receiver, method, None, args)
+            }
+            _ => fail(format!("Cannot find function `{}`", path)),
+        }
+    }
+
+    fn call_value(&mut self, callee: Value, args: Vec<Value>) -> Eval {
+        match callee {
+            Value::Closure(closure, captured) => self.call_closure(&closure, &captured, args),
+            other => fail(format!("{} is not a function", other.type_name())),
+        }
+    }
+
+    fn call_function(&mut self, function: &Function, args: Vec<Value>) -> Eval {
+        if args.len() != function.params.len() {
+            return fail(format!(
+                "`{}` takes {} arguments, not {}",
+                function.name,
+                function.params.len(),
+                args.len()
+            ));
+        }
+        if self.depth >= MAX_DEPTH {
+            return fail(format!("`{}` recursed too deeply", function.name));
+        }
+
+        // Functions do not see the variables of their caller
+        let caller = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+        self.depth += 1;
+        let mut result = Ok(());
+        for (pattern, arg) in function.params.iter().zip(args) {
+            result = result.and_then(|_| self.bind(pattern, arg));
+        }
+        let result = result.and_then(|_| self.block(&function.body));
+        self.depth -= 1;
+        self.scopes = caller;
+        match result {
+            Err(Flow::Return(value)) => Ok(value),
+            Err(Flow::Break(_) | Flow::Continue) => fail("`break` outside of a loop"),
+            result => result,
+        }
+    }
+
+    fn call_closure(
+        &mut self,
+        closure: &Closure,
+        captured: &[(String, Value)],
+        args: Vec<Value>,
+    ) -> Eval {
+        if args.len() != closure.params.len() {
+            return fail(format!(
+                "Closure takes {} arguments, not {}",
+                closure.params.len(),
+                args.len()
+            ));
+        }
+        if self.depth >= MAX_DEPTH {
+            return fail("Closure recursed too deeply");
+        }
+        self.depth += 1;
+        let result = self.scoped(|this| {
+            this.scopes
+                .last_mut()
+                .expect("a scope was pushed")
+                .extend(captured.iter().cloned());
+            for (pattern, arg) in closure.params.iter().zip(args) {
+                this.bind(pattern, arg)?;
+            }
+            this.eval(&closure.body)
+        });
+        self.depth -= 1;
+        match result {
+            Err(Flow::Return(value)) => Ok(value),
+            result => result,
+        }
+    }
+
+    /// Calls a closure taking a single value.
+    fn apply(&mut self, f: &Value, arg: Value) -> Eval {
+        self.call_value(f.clone(), vec![arg])
+    }
+
+    fn predicate(&mut self, f: &Value, arg: Value) -> Eval<bool> {
+        match self.apply(f, arg)? {
+            Value::Bool(value) => Ok(value),
+            other => fail(format!("Expected a bool, found {}", other.type_name())),
+        }
+    }
+
+    /// Methods changing their receiver, which must then be assignable.
+    fn mutate(&mut self, target: &mut Value, method: &str, mut args: Vec<Value>) -> Eval {
+        match (target, method) {
+            (Value::List(items), "push") if args.len() == 1 => {
+                items.push(args.remove(0));
+                Ok(Value::Unit)
+            }
+            (Value::List(items), "pop") => Ok(Value::Option(items.pop().map(Box::new))),
+            (Value::List(items), "clear") => {
+                items.clear();
+                Ok(Value::Unit)
+            }
+            (Value::List(items), "insert") if args.len() == 2 => match &args[0] {
+                Value::Int(index) if (0..=items.len() as i64).contains(index) => {
+                    items.insert(*index as usize, args.remove(1));
+                    Ok(Value::Unit)
+                }
+                other => fail(format!("Cannot insert at {}", other.debug())),
+            },
+            (Value::List(items), "remove") if args.len() == 1 => match &args[0] {
+                Value::Int(index) if (0..items.len() as i64).contains(index) => {
+                    Ok(items.remove(*index as usize))
+                }
+                other => fail(format!("Cannot remove at {}", other.debug())),
+            },
+            (Value::List(items), "truncate") => match args.first() {
+                Some(Value::Int(len)) => {
+                    items.truncate((*len).max(0) as usize);
+                    Ok(Value::Unit)
+                }
+                _ => fail("`truncate` takes a length"),
+            },
+            (Value::List(items), "extend") if args.len() == 1 => {
+                items.extend(self::items(args.remove(0))?);
+                Ok(Value::Unit)
+            }
+            (Value::List(items), "reverse") => {
+                items.reverse();
+                Ok(Value::Unit)
+            }
+            (Value::List(items), "sort" | "sort_unstable") => {
+                let mut error = None;
+                items.sort_by(|a, b| {
+                    compare(a, b).unwrap_or_else(|| {
+                        error = Some(format!("Cannot compare {} and {}", a.debug(), b.debug()));
+                        Ordering::Equal
+                    })
+                });
+                match error {
+                    Some(error) => fail(error),
+                    None => Ok(Value::Unit),
+                }
+            }
+            (Value::List(items), "dedup") => {
+                items.dedup();
+                Ok(Value::Unit)
+            }
+            (Value::List(items), "retain") if args.len() == 1 => {
+                let mut kept = Vec::new();
+                for item in items.drain(..) {
+                    if self.predicate(&args[0], item.clone())? {
+                        kept.push(item);
+                    }
+                }
+                *items = kept;
+                Ok(Value::Unit)
+            }
+            (Value::Str(text), "push_str") => match args.first() {
+                Some(Value::Str(more)) => {
+                    text.push_str(more);
+                    Ok(Value::Unit)
+                }
+                _ => fail("`push_str` takes a string"),
+            },
+            (Value::Str(text), "push") => match args.first() {
+                Some(Value::Str(more)) => {
+                    text.push_str(more);
+                    Ok(Value::Unit)
+                }
+                _ => fail("`push` takes a char"),
+            },
+            (Value::Str(text), "clear") => {
+                text.clear();
+                Ok(Value::Unit)
+            }
+            (Value::Str(text), "pop") => Ok(Value::Option(
+                text.pop().map(|c| Box::new(Value::Str(c.to_string()))),
+            )),
+            (target, method) => fail(format!("No method `{}` on {}", method, target.type_name())),
+        }
+    }
+
+    fn method(
+        &mut self,
+        receiver: Value,
+        method: &str,
+        turbofish: Option<&str>,
+        mut args: Vec<Value>,
+    ) -> Eval {
+        let arg = |args: &mut Vec<Value>, index: usize| match args.get_mut(index) {
+            Some(arg) => Ok(std::mem::replace(arg, Value::Unit)),
+            None => fail(format!("`{}` takes more arguments", method)),
+        };
+
+        // Conversions that keep the value
+        match method {
+            "clone" | "to_owned" | "into" | "iter" | "into_iter" | "iter_mut" | "copied"
+            | "cloned" | "as_str" | "as_slice" | "to_vec" | "borrow" | "as_ref" => {
+                return Ok(receiver)
+            }
+            "to_string" => return Ok(Value::Str(receiver.to_string())),
+            _ => {}
+        }
+
+        match receiver {
+            Value::Int(_) | Value::Float(_) => number_method(receiver, method, args),
+            Value::Str(text) => match method {
+                "len" => Ok(Value::Int(text.len() as i64)),
+                "is_empty" => Ok(Value::Bool(text.is_empty())),
+                "to_uppercase" => Ok(Value::Str(text.to_uppercase())),
+                "to_lowercase" => Ok(Value::Str(text.to_lowercase())),
+                "trim" => Ok(Value::Str(text.trim().to_string())),
+                "trim_start" => Ok(Value::Str(text.trim_start().to_string())),
+                "trim_end" => Ok(Value::Str(text.trim_end().to_string())),
+                "chars" => Ok(Value::List(
+                    text.chars().map(|c| Value::Str(c.to_string())).collect(),
+                )),
+                "lines" => Ok(Value::List(
+                    text.lines()
+                        .map(|line| Value::Str(line.to_string()))
+                        .collect(),
+                )),
+                "contains" | "starts_with" | "ends_with" | "split" | "replace" | "repeat" => {
+                    let pattern = arg(&mut args, 0)?;
+                    match (method, pattern) {
+                        ("contains", Value::Str(p)) => Ok(Value::Bool(text.contains(&p))),
+                        ("starts_with", Value::Str(p)) => Ok(Value::Bool(text.starts_with(&p))),
+                        ("ends_with", Value::Str(p)) => Ok(Value::Bool(text.ends_with(&p))),
+                        ("split", Value::Str(p)) => Ok(Value::List(
+                            text.split(&p).map(|s| Value::Str(s.to_string())).collect(),
+                        )),
+                        ("replace", Value::Str(p)) => match arg(&mut args, 1)? {
+                            Value::Str(with) => Ok(Value::Str(text.replace(&p, &with))),
+                            other => fail(format!("Cannot replace with {}", other.type_name())),
+                        },
+                        ("repeat", Value::Int(count)) if (0..=MAX_ITEMS).contains(&count) => {
+                            Ok(Value::Str(text.repeat(count as usize)))
+                        }
+                        (method, other) => {
+                            fail(format!("`{}` does not take {}", method, other.type_name()))
+                        }
+                    }
+                }
+                "parse" => Ok(Value::Result(parse_number(&text, turbofish))),
+                "collect" => Ok(Value::Str(text)),
+                _ => fail(format!("No method `{}` on String", method)),
+            },
+            Value::Option(option) => match (method, option) {
+                ("is_some", option) => Ok(Value::Bool(option.is_some())),
+                ("is_none", option) => Ok(Value::Bool(option.is_none())),
+                ("unwrap" | "expect", Some(value)) => Ok(*value),
+                ("unwrap", None) => fail("Called `Option::unwrap()` on a `None` value"),
+                ("expect", None) => fail(arg(&mut args, 0)?.to_string()),
+                ("unwrap_or", option) => option.map_or_else(|| arg(&mut args, 0), |v| Ok(*v)),
+                ("unwrap_or_else", None) => {
+                    let f = arg(&mut args, 0)?;
+                    self.call_value(f, Vec::new())
+                }
+                ("unwrap_or_else", Some(value)) => Ok(*value),
+                ("map", Some(value)) => {
+                    let f = arg(&mut args, 0)?;
+                    Ok(Value::Option(Some(Box::new(self.apply(&f, *value)?))))
+                }
+                ("map", None) => Ok(Value::Option(None)),
+                (method, _) => fail(format!("No method `{}` on Option", method)),
+            },
+            Value::Result(result) => match (method, result) {
+                ("is_ok", result) => Ok(Value::Bool(result.is_ok())),
+                ("is_err", result) => Ok(Value::Bool(result.is_err())),
+                ("ok", result) => Ok(Value::Option(result.ok())),
+                ("unwrap" | "expect", Ok(value)) => Ok(*value),
+                ("unwrap", Err(error)) => fail(format!(
+                    "Called `Result::unwrap()` on an `Err` value: {}",
+                    error.debug()
+                )),
+                ("expect", Err(error)) => {
+                    fail(format!("{}: {}", arg(&mut args, 0)?, error.debug()))
+                }
+                ("unwrap_or", result) => result.map_or_else(|_| arg(&mut args, 0), |v| Ok(*v)),
+                (method, _) => fail(format!("No method `{}` on Result", method)),
+            },
+            Value::List(_) | Value::Range(..) => {
+                self.list_method(receiver, method, turbofish, args)
+            }
+            receiver => fail(format!(
+                "No method `{}` on {}",
+                method,
+                receiver.type_name()
+            )),
+        }
+    }
+
+    fn list_method(
+        &mut self,
+        receiver: Value,
+        method: &str,
+        turbofish: Option<&str>,
+        args: Vec<Value>,
+    ) -> Eval {
+        // Ranges answer these without counting
+        if let Value::Range(start, end) = receiver {
+            let len = (end - start).max(0);
+            match (method, args.first()) {
+                ("len" | "count", _) => return Ok(Value::Int(len)),
+                ("is_empty", _) => return Ok(Value::Bool(len == 0)),
+                ("contains", Some(Value::Int(n))) => {
+                    return Ok(Value::Bool((start..end).contains(n)))
+                }
+                _ => {}
+            }
+        }
+
+        let f = args.first().cloned();
+        let f = || {
+            f.clone()
+                .ok_or_else(|| Flow::from(format!("`{}` takes an argument", method)))
+        };
+        let items = items(receiver)?;
+        match method {
+            "len" | "count" => Ok(Value::Int(items.len() as i64)),
+            "is_empty" => Ok(Value::Bool(items.is_empty())),
+            "collect" if turbofish == Some("String") => {
+                Ok(Value::Str(items.iter().map(Value::to_string).collect()))
+            }
+            "collect" | "rev" if method == "collect" => Ok(Value::List(items)),
+            "rev" => Ok(Value::List(items.into_iter().rev().collect())),
+            "first" => Ok(Value::Option(items.into_iter().next().map(Box::new))),
+            "last" => Ok(Value::Option(items.into_iter().last().map(Box::new))),
+            "get" => match f()? {
+                Value::Int(index) => Ok(Value::Option(
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|index| items.into_iter().nth(index))
+                        .map(Box::new),
+                )),
+                other => fail(format!("Cannot index with {}", other.debug())),
+            },
+            "contains" => {
+                let value = f()?;
+                Ok(Value::Bool(items.contains(&value)))
+            }
+            "enumerate" => Ok(Value::List(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| Value::Tuple(vec![Value::Int(index as i64), item]))
+                    .collect(),
+            )),
+            "zip" | "chain" => {
+                let other = self::items(f()?)?;
+                Ok(Value::List(match method {
+                    "zip" => items
+                        .into_iter()
+                        .zip(other)
+                        .map(|(a, b)| Value::Tuple(vec![a, b]))
+                        .collect(),
+                    _ => items.into_iter().chain(other).collect(),
+                }))
+            }
+            "take" | "skip" | "step_by" => match f()? {
+                Value::Int(n) if n >= 0 && !(method == "step_by" && n == 0) => {
+                    let n = n as usize;
+                    Ok(Value::List(match method {
+                        "take" => items.into_iter().take(n).collect(),
+                        "skip" => items.into_iter().skip(n).collect(),
+                        _ => items.into_iter().step_by(n).collect(),
+                    }))
+                }
+                other => fail(format!("`{}` does not take {}", method, other.debug())),
+            },
+            "sum" | "product" => {
+                let mut total = match (method, items.first()) {
+                    ("sum", Some(Value::Float(_))) => Value::Float(0.0),
+                    ("sum", _) => Value::Int(0),
+                    (_, Some(Value::Float(_))) => Value::Float(1.0),
+                    _ => Value::Int(1),
+                };
+                let op = if method == "sum" {
+                    BinOp::Add
+                } else {
+                    BinOp::Mul
+                };
+                for item in items {
+                    total = binary(op, total, item)?;
+                }
+                Ok(total)
+            }
+            "min" | "max" => {
+                let mut best: Option<Value> = None;
+                for item in items {
+                    let better = match &best {
+                        None => true,
+                        Some(current) => {
+                            let order = compare(&item, current).ok_or_else(|| {
+                                Flow::from(format!("Cannot compare {}", item.debug()))
+                            })?;
+                            match method {
+                                "min" => order == Ordering::Less,
+                                _ => order != Ordering::Less,
+                            }
+                        }
+                    };
+                    if better {
+                        best = Some(item);
+                    }
+                }
+                Ok(Value::Option(best.map(Box::new)))
+            }
+            "join" | "concat" => {
+                let separator = match args.first() {
+                    Some(Value::Str(separator)) => separator.clone(),
+                    _ => String::new(),
+                };
+                let parts: Vec<String> = items.iter().map(Value::to_string).collect();
+                Ok(Value::Str(parts.join(&separator)))
+            }
+            "map" => {
+                let f = f()?;
+                let mut mapped = Vec::with_capacity(items.len());
+                for item in items {
+                    mapped.push(self.apply(&f, item)?);
+                }
+                Ok(Value::List(mapped))
+            }
+            "for_each" => {
+                let f = f()?;
+                for item in items {
+                    self.apply(&f, item)?;
+                }
+                Ok(Value::Unit)
+            }
+            "filter" => {
+                let f = f()?;
+                let mut kept = Vec::new();
+                for item in items {
+                    if self.predicate(&f, item.clone())? {
+                        kept.push(item);
+                    }
+                }
+                Ok(Value::List(kept))
+            }
+            "filter_map" | "flat_map" => {
+                let f = f()?;
+                let mut mapped = Vec::new();
+                for item in items {
+                    match self.apply(&f, item)? {
+                        Value::Option(value) => mapped.extend(value.map(|value| *value)),
+                        other => mapped.extend(self::items(other)?),
+                    }
+                }
+                Ok(Value::List(mapped))
+            }
+            "any" | "all" | "find" | "position" => {
+                let f = f()?;
+                for (index, item) in items.into_iter().enumerate() {
+                    let matched = self.predicate(&f, item.clone())?;
+                    match method {
+                        "any" if matched => return Ok(Value::Bool(true)),
+                        "all" if !matched => return Ok(Value::Bool(false)),
+                        "find" if matched => return Ok(Value::Option(Some(Box::new(item)))),
+                        "position" if matched => {
+                            return Ok(Value::Option(Some(Box::new(Value::Int(index as i64)))))
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(match method {
+                    "any" => Value::Bool(false),
+                    "all" => Value::Bool(true),
+                    _ => Value::Option(None),
+                })
+            }
+            "fold" => {
+                let mut args = args.into_iter();
+                let (Some(mut acc), Some(f)) = (args.next(), args.next()) else {
+                    return fail("`fold` takes an initial value and a closure");
+                };
+                for item in items {
+                    acc = self.call_value(f.clone(), vec![acc, item])?;
+                }
+                Ok(acc)
+            }
+            _ => fail(format!("No method `{}` on Vec", method)),
+        }
+    }
+
+    fn macro_call(&mut self, name: &str, args: &[Expr]) -> Eval {
+        match name {
+            "println" | "print" | "eprintln" | "eprint" => {
+                let mut text = match args.is_empty() {
+                    true => String::new(),
+                    false => self.format(args)?,
+                };
+                if name.ends_with("ln") {
+                    text.push('\n');
+                }
+                (self.print)(text);
+                Ok(Value::Unit)
+            }
+            "format" => self.format(args).map(Value::Str),
+            "panic" | "unreachable" | "todo" | "unimplemented" => {
+                let message = match args.is_empty() {
+                    true => format!("`{}!` was reached", name),
+                    false => self.format(args)?,
+                };
+                fail(message)
+            }
+            "assert" => match args.split_first() {
+                Some((condition, message)) => {
+                    if self.condition(condition)? {
+                        return Ok(Value::Unit);
+                    }
+                    let message = match message.is_empty() {
+                        true => "Assertion failed".to_string(),
+                        false => self.format(message)?,
+                    };
+                    fail(message)
+                }
+                None => fail("`assert!` takes a condition"),
+            },
+            "assert_eq" | "assert_ne" if args.len() >= 2 => {
+                let left = self.eval(&args[0])?;
+                let right = self.eval(&args[1])?;
+                if (left == right) == (name == "assert_eq") {
+                    return Ok(Value::Unit);
+                }
+                let message = match args.len() {
+                    2 => String::new(),
+                    _ => format!(": {}", self.format(&args[2..])?),
+                };
+                fail(format!(
+                    "Assertion `left {} right` failed{}: left is {}, right is {}",
+                    if name == "assert_eq" { "==" } else { "!=" },
+                    message,
+                    left.debug(),
+                    right.debug()
+                ))
+            }
+            "dbg" => {
+                let value = match args.first() {
+                    Some(arg) => self.eval(arg)?,
+                    None => Value::Unit,
+                };
+                (self.print)(format!("{}\n", value.debug()));
+                Ok(value)
+            }
+            _ => fail(format!("Macro `{}!` is not supported", name)),
+        }
+    }
+
+    /// Formats the arguments of a formatting macro, the first being the
+    /// format string.
+    fn format(&mut self, args: &[Expr]) -> Eval<String> {
+        let Some((Expr::Lit(Value::Str(format)), args)) = args.split_first() else {
+            return fail("Expected a format string");
+        };
+        let args = self.eval_all(args)?;
+        let mut chars = format.chars().peekable();
+        let mut text = String::new();
+        let mut next = 0;
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                        spec.push(c);
+                    }
+                    let (argument, options) = spec.split_once(':').unwrap_or((&spec, ""));
+                    let value = if argument.is_empty() {
+                        next += 1;
+                        args.get(next - 1).cloned()
+                    } else if let Ok(index) = argument.parse::<usize>() {
+                        args.get(index).cloned()
+                    } else {
+                        self.lookup(argument).cloned()
+                    };
+                    let value = value.ok_or_else(|| {
+                        Flow::from(format!("No value for {{{}}} in {:?}", spec, format))
+                    })?;
+                    text.push_str(&format_value(&value, options));
+                }
+                c => text.push(c),
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// Methods `Expr::MethodCall` runs on the variable itself.
+const MUTATING: &[&str] = &[
+    "push",
+    "pop",
+    "clear",
+    "insert",
+    "remove",
+    "truncate",
+    "extend",
+    "reverse",
+    "sort",
+    "sort_unstable",
+    "dedup",
+    "retain",
+    "push_str",
+];
+
+/// Formats a value with the options of a format spec, like `.2` or `>8?`.
+fn format_value(value: &Value, options: &str) -> String {
+    let mut options = options;
+    let debug = options.ends_with('?');
+    if debug {
+        options = &options[..options.len() - 1];
+    }
+    let (options, precision) = match options.split_once('.') {
+        Some((options, precision)) => (options, precision.parse::<usize>().ok()),
+        None => (options, None),
+    };
+    let mut text = match (value, precision) {
+        (Value::Float(number), Some(precision)) => format!("{:.*}", precision, number),
+        (Value::Int(number), Some(precision)) => format!("{:.*}", precision, *number as f64),
+        _ if debug => value.debug(),
+        _ => value.to_string(),
+    };
+
+    let align = options.chars().find(|c| "<^>".contains(*c));
+    let fill = match align {
+        Some(align) if options.starts_with(align) => ' ',
+        Some(_) => options.chars().next().unwrap_or(' '),
+        None => ' ',
+    };
+    let zero = align.is_none() && options.starts_with('0');
+    let width: usize = options
+        .trim_start_matches(|c: char| !c.is_ascii_digit() || (zero && c == '0'))
+        .parse()
+        .unwrap_or(0);
+    let len = text.chars().count();
+    if width > len {
+        let pad = width - len;
+        let numeric = matches!(value, Value::Int(_) | Value::Float(_));
+        text = match align.unwrap_or(if numeric { '>' } else { '<' }) {
+            _ if zero => {
+                let sign = text.starts_with('-');
+                let digits = text.trim_start_matches('-');
+                format!(
+                    "{}{}{}",
+                    if sign { "-" } else { "" },
+                    "0".repeat(pad),
+                    digits
+                )
+            }
+            '<' => format!("{}{}", text, fill.to_string().repeat(pad)),
+            '^' => format!(
+                "{}{}{}",
+                fill.to_string().repeat(pad / 2),
+                text,
+                fill.to_string().repeat(pad - pad / 2)
+            ),
+            _ => format!("{}{}", fill.to_string().repeat(pad), text),
+        };
+    }
+    text
+}
+
+/// The items of a collection, to iterate over.
+fn items(value: Value) -> Eval<Vec<Value>> {
+    match value {
+        Value::List(items) => Ok(items),
+        Value::Range(start, end) if end.saturating_sub(start) <= MAX_ITEMS => {
+            Ok((start..end).map(Value::Int).collect())
+        }
+        Value::Range(start, end) => fail(format!("Range {}..{} is too long", start, end)),
+        Value::Option(value) => Ok(value.map(|value| *value).into_iter().collect()),
+        Value::Str(text) => Ok(text.chars().map(|c| Value::Str(c.to_string())).collect()),
+        other => fail(format!("Cannot iterate over {}", other.type_name())),
+    }
+}
+
+/// Names of paths that are values, like `f64::consts::PI`.
+fn constant(path: &str) -> Option<Value> {
+    let name = path.rsplit("::").next()?;
+    let ty = path.split("::").find(|part| is_primitive(Some(part)));
+    let value = match (ty, name) {
+        (_, "None") => Value::Option(None),
+        (_, "PI") => Value::Float(std::f64::consts::PI),
+        (_, "TAU") => Value::Float(std::f64::consts::TAU),
+        (_, "E") => Value::Float(std::f64::consts::E),
+        (_, "SQRT_2") => Value::Float(std::f64::consts::SQRT_2),
+        (_, "LN_2") => Value::Float(std::f64::consts::LN_2),
+        (_, "LN_10") => Value::Float(std::f64::consts::LN_10),
+        (Some("f32" | "f64"), "INFINITY") => Value::Float(f64::INFINITY),
+        (Some("f32" | "f64"), "NEG_INFINITY") => Value::Float(f64::NEG_INFINITY),
+        (Some("f32" | "f64"), "NAN") => Value::Float(f64::NAN),
+        (Some("f32"), "EPSILON") => Value::Float(f32::EPSILON as f64),
+        (Some("f64"), "EPSILON") => Value::Float(f64::EPSILON),
+        (Some("f32"), "MAX") => Value::Float(f32::MAX as f64),
+        (Some("f32"), "MIN") => Value::Float(f32::MIN as f64),
+        (Some("f64"), "MAX") => Value::Float(f64::MAX),
+        (Some("f64"), "MIN") => Value::Float(f64::MIN),
+        (Some(ty), "MAX" | "MIN") => {
+            let (min, max) = int_bounds(ty)?;
+            Value::Int(if name == "MAX" { max } else { min })
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn is_primitive(ty: Option<&str>) -> bool {
+    matches!(ty, Some(ty) if ty == "f32" || ty == "f64" || ty == "bool" || int_bounds(ty).is_some())
+}
+
+/// The bounds of an integer type, clamped to what `Value::Int` holds.
+fn int_bounds(ty: &str) -> Option<(i64, i64)> {
+    Some(match ty {
+        "i8" => (i8::MIN as i64, i8::MAX as i64),
+        "i16" => (i16::MIN as i64, i16::MAX as i64),
+        "i32" => (i32::MIN as i64, i32::MAX as i64),
+        "i64" | "i128" | "isize" => (i64::MIN, i64::MAX),
+        "u8" => (0, u8::MAX as i64),
+        "u16" => (0, u16::MAX as i64),
+        "u32" => (0, u32::MAX as i64),
+        "u64" | "u128" | "usize" => (0, i64::MAX),
+        _ => return None,
+    })
+}
+
+fn default(ty: &str) -> Option<Value> {
+    match ty {
+        "f32" | "f64" => Some(Value::Float(0.0)),
+        "bool" => Some(Value::Bool(false)),
+        "String" => Some(Value::Str(String::new())),
+        "Vec" => Some(Value::List(Vec::new())),
+        _ => int_bounds(ty).map(|_| Value::Int(0)),
+    }
+}
+
+fn parse_number(text: &str, ty: Option<&str>) -> Result<Box<Value>, Box<Value>> {
+    let error = |message: &str| Box::new(Value::Str(message.to_string()));
+    match ty {
+        Some("f32" | "f64") => text
+            .parse::<f64>()
+            .map(|number| Box::new(Value::Float(number)))
+            .map_err(|_| error("invalid float literal")),
+        Some("bool") => text
+            .parse::<bool>()
+            .map(|value| Box::new(Value::Bool(value)))
+            .map_err(|_| error("provided string was not `true` or `false`")),
+        Some("String") => Ok(Box::new(Value::Str(text.to_string()))),
+        Some(ty) if int_bounds(ty).is_some() => {
+            let (min, max) = int_bounds(ty).expect("an integer type");
+            match text.parse::<i64>() {
+                Ok(number) if (min..=max).contains(&number) => Ok(Box::new(Value::Int(number))),
+                Ok(_) => Err(error("number too large to fit in target type")),
+                Err(_) => Err(error("invalid digit found in string")),
+            }
+        }
+        _ => Err(error("`parse` needs a type, like `parse::<f64>()`")),
+    }
+}
+
+fn unary(op: UnOp, value: Value) -> Result<Value, String> {
+    match (op, value) {
+        (UnOp::Ref, value) => Ok(value),
+        (UnOp::Neg, Value::Int(number)) => number
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| "Attempt to negate with overflow".to_string()),
+        (UnOp::Neg, Value::Float(number)) => Ok(Value::Float(-number)),
+        (UnOp::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+        (UnOp::Not, Value::Int(number)) => Ok(Value::Int(!number)),
+        (op, value) => Err(format!(
+            "Cannot apply `{}` to {}",
+            if op == UnOp::Neg { "-" } else { "!" },
+            value.type_name()
+        )),
+    }
+}
+
+fn binary(op: BinOp, left: Value, right: Value) -> Result<Value, String> {
+    use BinOp::*;
+    match op {
+        Eq => return Ok(Value::Bool(left == right)),
+        Ne => return Ok(Value::Bool(left != right)),
+        Lt | Le | Gt | Ge => {
+            let order = compare(&left, &right).ok_or_else(|| {
+                format!(
+                    "Cannot compare {} with {}",
+                    left.type_name(),
+                    right.type_name()
+                )
+            });
+            // Comparisons with NaN are false
+            return Ok(Value::Bool(match (order, op) {
+                (Err(_), _) if is_nan(&left) || is_nan(&right) => false,
+                (Err(e), _) => return Err(e),
+                (Ok(order), Lt) => order == Ordering::Less,
+                (Ok(order), Le) => order != Ordering::Greater,
+                (Ok(order), Gt) => order == Ordering::Greater,
+                (Ok(order), _) => order != Ordering::Less,
+            }));
+        }
+        _ => {}
+    }
+
+    let overflow = || {
+        let verb = match op {
+            Add => "add",
+            Sub => "subtract",
+            Mul => "multiply",
+            Div => "divide",
+            _ => "calculate the remainder",
+        };
+        format!("Attempt to {} with overflow", verb)
+    };
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => {
+            let result = match op {
+                Add => a.checked_add(b),
+                Sub => a.checked_sub(b),
+                Mul => a.checked_mul(b),
+                Div | Rem if b == 0 => {
+                    return Err(match op {
+                        Div => "Attempt to divide by zero".to_string(),
+                        _ => {
+                            "Attempt to calculate the remainder with a divisor of zero".to_string()
+                        }
+                    })
+                }
+                Div => a.checked_div(b),
+                Rem => a.checked_rem(b),
+                BitAnd => Some(a & b),
+                BitOr => Some(a | b),
+                BitXor => Some(a ^ b),
+                _ => unreachable!("handled above"),
+            };
+            result.map(Value::Int).ok_or_else(overflow)
+        }
+        (a @ (Value::Int(_) | Value::Float(_)), b @ (Value::Int(_) | Value::Float(_))) => {
+            let (a, b) = (as_float(&a), as_float(&b));
+            Ok(Value::Float(match op {
+                Add => a + b,
+                Sub => a - b,
+                Mul => a * b,
+                Div => a / b,
+                Rem => a % b,
+                _ => return Err("Bitwise operators need integers".to_string()),
+            }))
+        }
+        (Value::Bool(a), Value::Bool(b)) => match op {
+            BitAnd => Ok(Value::Bool(a & b)),
+            BitOr => Ok(Value::Bool(a | b)),
+            BitXor => Ok(Value::Bool(a ^ b)),
+            _ => Err("Cannot do arithmetic with bools".to_string()),
+        },
+        (Value::Str(a), Value::Str(b)) if op == Add => Ok(Value::Str(a + &b)),
+        (a, b) => Err(format!(
+            "Cannot apply `{}` to {} and {}",
+            symbol(op),
+            a.type_name(),
+            b.type_name()
+        )),
+    }
+}
+
+fn symbol(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Add => "+",
+        BinOp::Sub => "-",
+        BinOp::Mul => "*",
+        BinOp::Div => "/",
+        BinOp::Rem => "%",
+        BinOp::And => "&&",
+        BinOp::Or => "||",
+        BinOp::BitAnd => "&",
+        BinOp::BitOr => "|",
+        BinOp::BitXor => "^",
+        BinOp::Eq => "==",
+        BinOp::Ne => "!=",
+        BinOp::Lt => "<",
+        BinOp::Le => "<=",
+        BinOp::Gt => ">",
+        BinOp::Ge => ">=",
+    }
+}
+
+fn is_nan(value: &Value) -> bool {
+    matches!(value, Value::Float(number) if number.is_nan())
+}
+
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Int(number) => *number as f64,
+        Value::Float(number) => *number,
+        _ => f64::NAN,
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+            as_float(a).partial_cmp(&as_float(b))
+        }
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Unit, Value::Unit) => Some(Ordering::Equal),
+        (Value::List(a), Value::List(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+            for (a, b) in a.iter().zip(b) {
+                match compare(a, b)? {
+                    Ordering::Equal => {}
+                    order => return Some(order),
+                }
+            }
+            Some(a.len().cmp(&b.len()))
+        }
+        _ => None,
+    }
+}
+
+fn cast(value: Value, ty: &str) -> Result<Value, String> {
+    let number = match &value {
+        Value::Int(number) => *number as f64,
+        Value::Float(number) => *number,
+        Value::Bool(value) => *value as i64 as f64,
+        Value::Str(text) if ty == "u32" || ty == "u8" => match text.chars().next() {
+            Some(c) if text.chars().count() == 1 => c as u32 as f64,
+            _ => f64::NAN,
+        },
+        _ => f64::NAN,
+    };
+    if number.is_nan() && !matches!(value, Value::Float(_)) {
+        return Err(format!("Cannot cast {} as {}", value.type_name(), ty));
+    }
+    match ty {
+        "f64" => Ok(Value::Float(number)),
+        "f32" => Ok(Value::Float(number as f32 as f64)),
+        "char" => match value {
+            Value::Int(code) => char::from_u32(code as u32)
+                .map(|c| Value::Str(c.to_string()))
+                .ok_or_else(|| format!("{} is not a char", code)),
+            _ => Err(format!("Cannot cast {} as char", value.type_name())),
+        },
+        _ => {
+            let Some((min, max)) = int_bounds(ty) else {
+                return Err(format!("Cannot cast to {}", ty));
+            };
+            // Wrap like `as` does between integer types, and saturate floats
+            // at the bounds of the type
+            let wide = match value {
+                Value::Int(number) => number,
+                Value::Float(_) => (number as i64).clamp(min, max),
+                _ => number as i64,
+            };
+            Ok(Value::Int(match ty {
+                "i8" => wide as i8 as i64,
+                "i16" => wide as i16 as i64,
+                "i32" => wide as i32 as i64,
+                "u8" => wide as u8 as i64,
+                "u16" => wide as u16 as i64,
+                "u32" => wide as u32 as i64,
+                _ => wide,
+            }))
+        }
+    }
+}
+
+fn number_method(receiver: Value, method: &str, args: Vec<Value>) -> Eval {
+    let arg = |index: usize| {
+        args.get(index)
+            .cloned()
+            .ok_or_else(|| Flow::from(format!("`{}` takes more arguments", method)))
+    };
+    if let Value::Int(n) = receiver {
+        let int_arg = |index: usize| match arg(index)? {
+            Value::Int(value) => Ok(value),
+            other => fail(format!(
+                "`{}` takes an integer, not {}",
+                method,
+                other.type_name()
+            )),
+        };
+        let overflow = || Flow::from(format!("Attempt to `{}` with overflow", method));
+        match method {
+            "abs" => return n.checked_abs().map(Value::Int).ok_or_else(overflow),
+            "pow" => {
+                let exponent = u32::try_from(int_arg(0)?).map_err(|_| overflow())?;
+                return n.checked_pow(exponent).map(Value::Int).ok_or_else(overflow);
+            }
+            "min" => return Ok(Value::Int(n.min(int_arg(0)?))),
+            "max" => return Ok(Value::Int(n.max(int_arg(0)?))),
+            "clamp" => {
+                return Ok(Value::Int(
+                    n.clamp(int_arg(0)?, int_arg(1)?.max(int_arg(0)?)),
+                ))
+            }
+            "signum" => return Ok(Value::Int(n.signum())),
+            "rem_euclid" => {
+                let divisor = int_arg(0)?;
+                if divisor == 0 {
+                    return fail("Attempt to calculate the remainder with a divisor of zero");
+                }
+                return Ok(Value::Int(n.rem_euclid(divisor)));
+            }
+            "is_positive" => return Ok(Value::Bool(n > 0)),
+            "is_negative" => return Ok(Value::Bool(n < 0)),
+            _ => {}
+        }
+    }
+
+    let x = as_float(&receiver);
+    let float_arg = |index: usize| match arg(index)? {
+        value @ (Value::Int(_) | Value::Float(_)) => Ok(as_float(&value)),
+        other => fail(format!(
+            "`{}` takes a number, not {}",
+            method,
+            other.type_name()
+        )),
+    };
+    let result = match method {
+        "abs" => x.abs(),
+        "sqrt" => x.sqrt(),
+        "cbrt" => x.cbrt(),
+        "exp" => x.exp(),
+        "ln" => x.ln(),
+        "log10" => x.log10(),
+        "log2" => x.log2(),
+        "log" => x.log(float_arg(0)?),
+        "powi" | "powf" => x.powf(float_arg(0)?),
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "tan" => x.tan(),
+        "asin" => x.asin(),
+        "acos" => x.acos(),
+        "atan" => x.atan(),
+        "atan2" => x.atan2(float_arg(0)?),
+        "sinh" => x.sinh(),
+        "cosh" => x.cosh(),
+        "tanh" => x.tanh(),
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        "round" => x.round(),
+        "trunc" => x.trunc(),
+        "fract" => x.fract(),
+        "signum" => x.signum(),
+        "min" => x.min(float_arg(0)?),
+        "max" => x.max(float_arg(0)?),
+        "clamp" => x.max(float_arg(0)?).min(float_arg(1)?),
+        "hypot" => x.hypot(float_arg(0)?),
+        "rem_euclid" => x.rem_euclid(float_arg(0)?),
+        "to_radians" => x.to_radians(),
+        "to_degrees" => x.to_degrees(),
+        "mul_add" => x.mul_add(float_arg(0)?, float_arg(1)?),
+        "is_nan" => return Ok(Value::Bool(x.is_nan())),
+        "is_finite" => return Ok(Value::Bool(x.is_finite())),
+        "is_infinite" => return Ok(Value::Bool(x.is_infinite())),
+        "is_sign_positive" => return Ok(Value::Bool(x.is_sign_positive())),
+        "is_sign_negative" => return Ok(Value::Bool(x.is_sign_negative())),
+        _ => {
+            return fail(format!(
+                "No method `{}` on {}",
+                method,
+                receiver.type_name()
+            ))
+        }
+    };
+    Ok(Value::Float(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blueprint::vm::parse::parse;
+    use parking_lot::Mutex;
+
+    /// An interpreter and the text it printed.
+    fn interpreter() -> (Interpreter, Arc<Mutex<String>>) {
+        let output = Arc::new(Mutex::new(String::new()));
+        let printed = output.clone();
+        let interpreter = Interpreter::new(Arc::new(AtomicBool::new(false)), move |text| {
+            printed.lock().push_str(&text)
+        });
+        (interpreter, output)
+    }
+
+    /// Runs code as statements of `main`, returning what it printed.
+    fn run(source: &str) -> Result<String, Halt> {
+        let (mut interpreter, output) = interpreter();
+        interpreter.run(&parse(source, 1).unwrap())?;
+        let printed = output.lock().clone();
+        Ok(printed)
+    }
+
+    /// The value of a block's code, formatted like `{:?}` does so numbers
+    /// show whether they are integers or floats.
+    fn eval(source: &str) -> String {
+        let (mut interpreter, _) = interpreter();
+        let stmts = parse(&format!("let result = {{ {} }};", source), 1).unwrap();
+        match interpreter.run(&stmts) {
+            Ok(_) => interpreter.variable("result").unwrap().debug(),
+            Err(halt) => panic!("{} failed: {:?}", source, halt),
+        }
+    }
+
+    /// The error a block's code fails with.
+    fn error(source: &str) -> String {
+        let (mut interpreter, _) = interpreter();
+        let stmts = parse(&format!("let result = {{ {} }};", source), 1).unwrap();
+        match interpreter.run(&stmts) {
+            Err(Halt::Error(message)) => message,
+            other => panic!("{} did not fail: {:?}", source, other),
+        }
+    }
+
+    fn assert_evals(cases: &[(&str, &str)]) {
+        for (source, expected) in cases {
+            assert_eq!(eval(source), *expected, "{}", source);
+        }
+    }
+
+    fn assert_fails(cases: &[(&str, &str)]) {
+        for (source, expected) in cases {
+            assert_eq!(error(source), *expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn does_arithmetic_like_rust() {
+        assert_evals(&[
+            ("1 + 2 * 3 - 4", "3"),
+            ("7 / 2", "3"),
+            ("-7 / 2", "-3"),
+            ("-7 % 3", "-1"),
+            ("7.0 / 2.0", "3.5"),
+            ("7 / 2.0", "3.5"),
+            ("1.5 + 1", "2.5"),
+            ("5.5 % 2.0", "1.5"),
+            ("-(2 - 5)", "3"),
+            ("6 & 3", "2"),
+            ("6 | 3", "7"),
+            ("6 ^ 3", "5"),
+            ("!5", "-6"),
+            ("true ^ true", "false"),
+            ("true & false | true", "true"),
+            ("\"ab\".to_string() + \"cd\"", "\"abcd\""),
+        ]);
+    }
+
+    #[test]
+    fn fails_on_overflow_and_division_by_zero() {
+        assert_fails(&[
+            ("i64::MAX + 1", "Attempt to add with overflow"),
+            ("i64::MIN - 1", "Attempt to subtract with overflow"),
+            ("i64::MAX * 2", "Attempt to multiply with overflow"),
+            ("-i64::MIN", "Attempt to negate with overflow"),
+            ("1 / 0", "Attempt to divide by zero"),
+            (
+                "1 % 0",
+                "Attempt to calculate the remainder with a divisor of zero",
+            ),
+            ("1.5 & 1.0", "Bitwise operators need integers"),
+            ("true + false", "Cannot do arithmetic with bools"),
+            ("\"a\" - 1", "Cannot apply `-` to String and integer"),
+            ("-true", "Cannot apply `-` to bool"),
+        ]);
+        // Division of floats by zero is not an error
+        assert_evals(&[("1.0 / 0.0", "inf"), ("(0.0 / 0.0).is_nan()", "true")]);
+    }
+
+    #[test]
+    fn compares_values() {
+        assert_evals(&[
+            ("1 < 2", "true"),
+            ("2 <= 2", "true"),
+            ("3 > 4", "false"),
+            ("3 >= 4", "false"),
+            ("1 < 1.5", "true"),
+            ("2 == 2.0", "true"),
+            ("1 != 1", "false"),
+            ("\"abc\" < \"abd\"", "true"),
+            ("false < true", "true"),
+            ("vec![1, 2] < vec![1, 3]", "true"),
+            ("(1, \"b\") > (1, \"a\")", "true"),
+            ("vec![1] < vec![1, 0]", "true"),
+            ("f64::NAN < 1.0", "false"),
+            ("f64::NAN >= 1.0", "false"),
+            ("f64::NAN == f64::NAN", "false"),
+            ("Some(1) == Some(1)", "true"),
+            ("None == Some(1)", "false"),
+        ]);
+        assert_fails(&[("1 < \"a\"", "Cannot compare integer with String")]);
+    }
+
+    #[test]
+    fn short_circuits_boolean_operators() {
+        assert_evals(&[
+            ("false && panic!()", "false"),
+            ("true || panic!()", "true"),
+            ("true && 1 < 2", "true"),
+            ("false || 1 > 2", "false"),
+        ]);
+        assert_fails(&[
+            ("1 && true", "Expected a bool, found integer"),
+            ("true && 1", "Expected a bool condition, found integer"),
+        ]);
+    }
+
+    #[test]
+    fn casts_like_as_does() {
+        assert_evals(&[
+            ("300 as u8", "44"),
+            ("(-1) as u8", "255"),
+            ("(-1) as u32", "4294967295"),
+            ("70000 as i16", "4464"),
+            ("2.9 as i32", "2"),
+            ("(-2.9) as i32", "-2"),
+            ("1e20 as i32", "2147483647"),
+            ("-1e20 as i64", "-9223372036854775808"),
+            ("-2.9 as i32", "-2"),
+            ("(-1e20) as i64", "-9223372036854775808"),
+            ("(-5.0) as u32", "0"),
+            ("f64::NAN as u8", "0"),
+            ("3 as f64", "3.0"),
+            ("0.1 as f32", "0.10000000149011612"),
+            ("true as i32", "1"),
+            ("'a' as u32", "97"),
+            ("'a' as u8", "97"),
+            ("97 as char", "\"a\""),
+            ("1 as usize as f64 / 2 as f64", "0.5"),
+        ]);
+        assert_fails(&[
+            ("\"ab\" as u32", "Cannot cast String as u32"),
+            ("1.5 as char", "Cannot cast float as char"),
+            ("1 as Point", "Cannot cast to Point"),
+        ]);
+    }
+
+    #[test]
+    fn resolves_constants_and_associated_functions() {
+        assert_evals(&[
+            ("std::f64::consts::PI", "3.141592653589793"),
+            ("f32::consts::TAU", "6.283185307179586"),
+            ("i8::MAX", "127"),
+            ("u8::MIN", "0"),
+            ("u32::MAX", "4294967295"),
+            ("i32::MIN", "-2147483648"),
+            ("f64::INFINITY", "inf"),
+            ("f64::EPSILON", "2.220446049250313e-16"),
+            ("String::new()", "\"\""),
+            ("String::from(\"a\")", "\"a\""),
+            ("Vec::new()", "[]"),
+            ("Vec::<u8>::with_capacity(4)", "[]"),
+            ("f64::from(3)", "3.0"),
+            ("i32::default()", "0"),
+            ("bool::default()", "false"),
+            ("f64::sqrt(16.0)", "4.0"),
+            ("f64::max(1.0, 2.5)", "2.5"),
+            ("std::cmp::max(3, 4)", "4"),
+            ("std::cmp::min(\"b\", \"a\")", "\"a\""),
+            ("i32::pow(2, 10)", "1024"),
+            ("Some(1)", "Some(1)"),
+            ("None", "None"),
+            ("Ok(1)", "Ok(1)"),
+            ("Err(\"no\")", "Err(\"no\")"),
+            ("drop(1)", "()"),
+        ]);
+        assert_fails(&[
+            ("missing", "Cannot find value `missing`"),
+            ("missing(1)", "Cannot find function `missing`"),
+            ("Some(1, 2)", "`Some` takes 1 argument, not 2"),
+            (
+                "Default::default()",
+                "`Default::default()` needs a known type",
+            ),
+            ("Point::default()", "No default for Point"),
+        ]);
+    }
+
+    #[test]
+    fn scopes_variables_to_their_blocks() {
+        assert_evals(&[
+            ("let a = 1; { let a = 2; } a", "1"),
+            ("let mut a = 1; { a = 2; } a", "2"),
+            ("let a = 1; let a = a + 1; a", "2"),
+            ("let a; a", "()"),
+            ("let _ = 5; 1", "1"),
+        ]);
+        assert_fails(&[
+            ("{ let b = 1; } b", "Cannot find value `b`"),
+            ("c = 1;", "Cannot find value `c`"),
+            ("1 = 2;", "Cannot assign to this expression"),
+        ]);
+    }
+
+    #[test]
+    fn keeps_float_variables_floats() {
+        assert_evals(&[
+            ("let x: f64 = 2; x", "2.0"),
+            ("let x: f32 = 2; x", "2.0"),
+            ("let x: i32 = 2; x", "2"),
+            ("let mut x = 1.5; x = 2; x", "2.0"),
+            ("let mut x = 1.5; x += 1; x", "2.5"),
+            ("let mut x = vec![0.5]; x[0] = 1; x", "[1.0]"),
+        ]);
+    }
+
+    #[test]
+    fn destructures_tuples_and_lists() {
+        assert_evals(&[
+            ("let (a, (b, _)) = (1, (2, 3)); a + b", "3"),
+            ("let (a, b) = vec![1, 2]; b", "2"),
+            ("let &mut c = 4; c", "4"),
+        ]);
+        assert_fails(&[
+            ("let (a, b) = 5;", "Cannot destructure 5 into 2 values"),
+            (
+                "let (a, b) = (1, 2, 3);",
+                "Cannot destructure (1, 2, 3) into 2 values",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn assigns_into_elements_and_fields() {
+        assert_evals(&[
+            ("let mut v = vec![1, 2]; v[1] += 5; v", "[1, 7]"),
+            ("let mut t = (1, (2, 3)); t.1 = (4, 5); t", "(1, (4, 5))"),
+            (
+                "let mut g = vec![vec![0; 2]; 2]; g[1][0] = 9; g",
+                "[[0, 0], [9, 0]]",
+            ),
+            ("let mut v = vec![(1, 2)]; v[0].1 *= 3; v", "[(1, 6)]"),
+            ("let mut a = 6; a -= 1; a *= 2; a /= 3; a %= 2; a", "1"),
+            ("let mut a = 6; a &= 3; a |= 8; a ^= 1; a", "11"),
+        ]);
+        assert_fails(&[
+            (
+                "let mut v = vec![1]; v[3] = 1;",
+                "Index out of bounds: the len is 1 but the index is 3",
+            ),
+            ("let mut v = vec![1]; v[-1] = 1;", "Cannot index with -1"),
+            ("let mut t = (1,); t.2 = 1;", "The tuple has no field 2"),
+            ("let mut n = 1; n[0] = 1;", "Cannot assign into integer"),
+        ]);
+    }
+
+    #[test]
+    fn indexes_and_slices_lists_and_strings() {
+        assert_evals(&[
+            ("vec![1, 2, 3][2]", "3"),
+            ("vec![1, 2, 3][1..]", "[2, 3]"),
+            ("vec![1, 2, 3][..=1]", "[1, 2]"),
+            ("vec![1, 2, 3][..]", "[1, 2, 3]"),
+            ("\"hello\"[1..3]", "\"el\""),
+            ("(1, 2.5).1", "2.5"),
+        ]);
+        assert_fails(&[
+            (
+                "vec![1, 2][2]",
+                "Index out of bounds: the len is 2 but the index is 2",
+            ),
+            ("vec![1, 2][2..1]", "Range 2..1 is out of bounds"),
+            ("\"é\"[0..1]", "Range 0..1 is out of bounds"),
+            ("5[0]", "Cannot index integer with integer"),
+            ("(1, 2).2", "(1, 2) has no field 2"),
+            (
+                "vec![1][1.5..2]",
+                "Range bounds must be integers, not float",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn builds_arrays_tuples_and_repeats() {
+        assert_evals(&[
+            ("[1, 2]", "[1, 2]"),
+            ("vec![]", "[]"),
+            ("[\"a\"; 3]", "[\"a\", \"a\", \"a\"]"),
+            ("(1, \"a\", 2.0)", "(1, \"a\", 2.0)"),
+            ("(1,)", "(1,)"),
+            ("()", "()"),
+            ("0..3", "0..3"),
+            ("1..=3", "1..4"),
+        ]);
+        assert_fails(&[("[0; -1]", "Invalid number of items -1")]);
+    }
+
+    #[test]
+    fn evaluates_if_else_chains() {
+        assert_evals(&[
+            ("if 1 < 2 { \"a\" } else { \"b\" }", "\"a\""),
+            ("if false { 1 } else if true { 2 } else { 3 }", "2"),
+            ("if false { 1 }", "()"),
+        ]);
+        assert_fails(&[("if 1 { }", "Expected a bool condition, found integer")]);
+    }
+
+    #[test]
+    fn runs_loops_with_break_and_continue() {
+        assert_evals(&[
+            (
+                "let mut i = 0; let mut sum = 0; \
+                 while i < 10 { i += 1; if i % 2 == 0 { continue; } sum += i; } sum",
+                "25",
+            ),
+            (
+                "let mut i = 0; loop { i += 1; if i == 4 { break i * 10; } }",
+                "40",
+            ),
+            (
+                "let mut i = 0; while true { i += 1; if i > 2 { break; } } i",
+                "3",
+            ),
+            (
+                "let mut seen = vec![]; for i in 0..10 { if i == 3 { break; } seen.push(i); } seen",
+                "[0, 1, 2]",
+            ),
+            (
+                "let mut n = 0; for _ in 0..3 { for _ in 0..4 { n += 1; } } n",
+                "12",
+            ),
+            (
+                "let mut s = String::new(); for c in \"abc\".chars().rev() { s.push(c); } s",
+                "\"cba\"",
+            ),
+            (
+                "let mut t = 0; for x in Some(4) { t += x; } for x in None { t += x; } t",
+                "4",
+            ),
+            (
+                "let mut t = 0; for (i, x) in vec![5, 6].iter().enumerate() { t += i * x; } t",
+                "6",
+            ),
+            (
+                "let mut n = 0; for i in (0..10).step_by(3) { n += i; } n",
+                "18",
+            ),
+            ("let mut n = 0; for i in 5..2 { n += i; } n", "0"),
+            ("loop { break; }", "()"),
+        ]);
+        assert_fails(&[("for x in 5 { }", "Cannot iterate over integer")]);
+        assert_eq!(
+            run("break;"),
+            Err(Halt::Error("`break` outside of a loop".to_string()))
+        );
+    }
+
+    #[test]
+    fn calls_functions_defined_anywhere_in_a_block() {
+        assert_evals(&[
+            ("let a = double(4); fn double(x: i32) -> i32 { x * 2 } a", "8"),
+            (
+                "fn fib(n: u64) -> u64 { if n < 2 { return n; } fib(n - 1) + fib(n - 2) } fib(15)",
+                "610",
+            ),
+            (
+                "fn swap((a, b): (i32, i32)) -> (i32, i32) { (b, a) } swap((1, 2))",
+                "(2, 1)",
+            ),
+            (
+                "fn first_even(v: Vec<i32>) -> Option<i32> { for x in v { if x % 2 == 0 { return Some(x); } } None } \
+                 first_even(vec![1, 3, 4, 6])",
+                "Some(4)",
+            ),
+            ("fn nothing() { } nothing()", "()"),
+        ]);
+        assert_fails(&[
+            (
+                "let a = 1; fn get() -> i32 { a } get()",
+                "Cannot find value `a`",
+            ),
+            (
+                "fn one(x: i32) { } one(1, 2)",
+                "`one` takes 1 arguments, not 2",
+            ),
+            (
+                "fn escape() { break; } loop { escape(); }",
+                "`break` outside of a loop",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn fails_on_too_deep_recursion_on_a_thread_of_stack_size() {
+        let thread = std::thread::Builder::new().stack_size(STACK_SIZE);
+        let errors = thread
+            .spawn(|| {
+                [
+                    error("fn forever(n: i32) -> i32 { forever(n + 1) } forever(0)"),
+                    error("fn f(n: i32) -> i32 { let g = |x| f(x); g(n) + 1 } f(0)"),
+                ]
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(
+            errors,
+            ["`forever` recursed too deeply", "`f` recursed too deeply"]
+        );
+        // Up to the limit, functions recurse
+        let thread = std::thread::Builder::new().stack_size(STACK_SIZE);
+        let deepest = thread
+            .spawn(|| {
+                eval(
+                    "fn down(n: i32) -> i32 { if n == 0 { 0 } else { down(n - 1) + 1 } } down(255)",
+                )
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(deepest, "255");
+    }
+
+    #[test]
+    fn calls_closures_with_captured_values() {
+        assert_evals(&[
+            ("let add = |a, b| a + b; add(2, 3)", "5"),
+            ("let k = 10; let f = move |x: i32| x * k; f(4)", "40"),
+            // Captured by value, as `move` closures of `Copy` values are
+            ("let mut k = 1; let f = || k; k = 2; f()", "1"),
+            ("let f = |(a, b)| a - b; f((5, 2))", "3"),
+            (
+                "let f = |x| -> i32 { if x > 0 { return 1; } -1 }; f(-3)",
+                "-1",
+            ),
+            (
+                "let make = |n| move |x| x + n; let add2 = make(2); add2(1)",
+                "3",
+            ),
+            ("(|| 7)()", "7"),
+        ]);
+        assert_fails(&[
+            ("let f = |x| x; f()", "Closure takes 1 arguments, not 0"),
+            ("let n = 3; n(1)", "integer is not a function"),
+        ]);
+    }
+
+    #[test]
+    fn runs_number_methods() {
+        assert_evals(&[
+            ("(-3).abs()", "3"),
+            ("2.pow(10)", "1024"),
+            ("5.min(3)", "3"),
+            ("5.max(8)", "8"),
+            ("15.clamp(0, 10)", "10"),
+            ("(-4).signum()", "-1"),
+            ("(-7).rem_euclid(3)", "2"),
+            ("3.is_positive()", "true"),
+            ("(-1.5).abs()", "1.5"),
+            ("16.0.sqrt()", "4.0"),
+            ("27.0.cbrt()", "3.0"),
+            ("2.0.powi(3)", "8.0"),
+            ("4.0.powf(0.5)", "2.0"),
+            ("100.0.log10()", "2.0"),
+            ("8.0.log2()", "3.0"),
+            ("8.0.log(2.0)", "3.0"),
+            ("1.0.exp().ln()", "1.0"),
+            ("0.0.sin()", "0.0"),
+            ("0.0.cos()", "1.0"),
+            ("1.0.atan2(1.0).to_degrees()", "45.0"),
+            ("180.0.to_radians()", "3.141592653589793"),
+            ("2.7.floor()", "2.0"),
+            ("2.1.ceil()", "3.0"),
+            ("2.5.round()", "3.0"),
+            ("(-2.7).trunc()", "-2.0"),
+            ("2.75.fract()", "0.75"),
+            ("1.5.min(0.5)", "0.5"),
+            ("1.5.max(2)", "2.0"),
+            ("5.5.clamp(0.0, 1.0)", "1.0"),
+            ("3.0.hypot(4.0)", "5.0"),
+            ("(-1.5).rem_euclid(1.0)", "0.5"),
+            ("2.0.mul_add(3.0, 1.0)", "7.0"),
+            ("4.sqrt()", "2.0"),
+            ("f64::INFINITY.is_finite()", "false"),
+            ("f64::NEG_INFINITY.is_infinite()", "true"),
+            ("(-0.0).is_sign_negative()", "true"),
+            ("1.0.is_sign_positive()", "true"),
+            ("7.to_string()", "\"7\""),
+            ("1.5.to_string()", "\"1.5\""),
+        ]);
+        assert_fails(&[
+            ("i64::MIN.abs()", "Attempt to `abs` with overflow"),
+            ("2.pow(-1)", "Attempt to `pow` with overflow"),
+            ("2.pow(100)", "Attempt to `pow` with overflow"),
+            ("5.min(1.5)", "`min` takes an integer, not float"),
+            ("1.0.max(\"a\")", "`max` takes a number, not String"),
+            ("1.0.powf()", "`powf` takes more arguments"),
+            (
+                "5.rem_euclid(0)",
+                "Attempt to calculate the remainder with a divisor of zero",
+            ),
+            ("1.5.frobnicate()", "No method `frobnicate` on float"),
+        ]);
+    }
+
+    #[test]
+    fn runs_string_methods() {
+        assert_evals(&[
+            ("\"héllo\".len()", "6"),
+            ("\"\".is_empty()", "true"),
+            ("\"Ab\".to_uppercase()", "\"AB\""),
+            ("\"Ab\".to_lowercase()", "\"ab\""),
+            ("\"  a  \".trim()", "\"a\""),
+            ("\"  a  \".trim_start()", "\"a  \""),
+            ("\"  a  \".trim_end()", "\"  a\""),
+            ("\"ab\".chars()", "[\"a\", \"b\"]"),
+            ("\"a\\nb\".lines()", "[\"a\", \"b\"]"),
+            ("\"abc\".contains(\"bc\")", "true"),
+            ("\"abc\".starts_with(\"b\")", "false"),
+            ("\"abc\".ends_with(\"bc\")", "true"),
+            ("\"a,b,,c\".split(\",\")", "[\"a\", \"b\", \"\", \"c\"]"),
+            ("\"aXbX\".replace(\"X\", \"-\")", "\"a-b-\""),
+            ("\"ab\".repeat(3)", "\"ababab\""),
+            ("\"abc\".chars().rev().collect::<String>()", "\"cba\""),
+            ("\"abc\".to_owned().as_str().len()", "3"),
+            (
+                "let mut s = String::from(\"a\"); s.push_str(\"bc\"); s.push('d'); s",
+                "\"abcd\"",
+            ),
+            (
+                "let mut s = \"ab\".to_string(); let c = s.pop(); (s, c)",
+                "(\"a\", Some(\"b\"))",
+            ),
+            ("let mut s = \"ab\".to_string(); s.clear(); s", "\"\""),
+            ("\"42\".parse::<i32>()", "Ok(42)"),
+            ("\" 4\".trim().parse::<u8>().unwrap()", "4"),
+            ("\"2.5\".parse::<f64>()", "Ok(2.5)"),
+            ("\"true\".parse::<bool>()", "Ok(true)"),
+            ("\"x\".parse::<String>()", "Ok(\"x\")"),
+            (
+                "\"300\".parse::<u8>()",
+                "Err(\"number too large to fit in target type\")",
+            ),
+            (
+                "\"4x\".parse::<i32>()",
+                "Err(\"invalid digit found in string\")",
+            ),
+            ("\"x\".parse::<f32>()", "Err(\"invalid float literal\")"),
+            (
+                "\"yes\".parse::<bool>()",
+                "Err(\"provided string was not `true` or `false`\")",
+            ),
+            (
+                "\"1\".parse()",
+                "Err(\"`parse` needs a type, like `parse::<f64>()`\")",
+            ),
+        ]);
+        assert_fails(&[
+            ("\"a\".contains(1)", "`contains` does not take integer"),
+            ("\"a\".replace(\"a\", 1)", "Cannot replace with integer"),
+            ("\"a\".shout()", "No method `shout` on String"),
+            (
+                "let mut s = String::new(); s.push_str(1);",
+                "`push_str` takes a string",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn runs_option_and_result_methods() {
+        assert_evals(&[
+            ("Some(1).is_some()", "true"),
+            ("None.is_none()", "true"),
+            ("Some(1).unwrap()", "1"),
+            ("Some(1).expect(\"one\")", "1"),
+            ("None.unwrap_or(2)", "2"),
+            ("Some(1).unwrap_or(2)", "1"),
+            ("None.unwrap_or_else(|| 3)", "3"),
+            ("Some(1).unwrap_or_else(|| 3)", "1"),
+            ("Some(2).map(|x| x * 2)", "Some(4)"),
+            ("None.map(|x| x * 2)", "None"),
+            ("Ok(1).is_ok()", "true"),
+            ("Err(1).is_err()", "true"),
+            ("Ok(1).ok()", "Some(1)"),
+            ("Err(1).ok()", "None"),
+            ("Ok(1).unwrap()", "1"),
+            ("Err(1).unwrap_or(5)", "5"),
+            ("vec![1, 2].first().copied().unwrap()", "1"),
+        ]);
+        assert_fails(&[
+            (
+                "None.unwrap()",
+                "Called `Option::unwrap()` on a `None` value",
+            ),
+            ("None.expect(\"needs a value\")", "needs a value"),
+            (
+                "Err(\"bad\").unwrap()",
+                "Called `Result::unwrap()` on an `Err` value: \"bad\"",
+            ),
+            (
+                "\"x\".parse::<i32>().expect(\"a number\")",
+                "a number: \"invalid digit found in string\"",
+            ),
+            ("Some(1).flatten()", "No method `flatten` on Option"),
+            ("Ok(1).flatten()", "No method `flatten` on Result"),
+            ("(1, 2).len()", "No method `len` on tuple"),
+        ]);
+    }
+
+    #[test]
+    fn runs_vec_methods_on_variables() {
+        assert_evals(&[
+            ("let mut v = vec![1]; v.push(2); v", "[1, 2]"),
+            (
+                "let mut v = vec![1, 2]; let last = v.pop(); (v, last)",
+                "([1], Some(2))",
+            ),
+            ("let mut v: Vec<i32> = vec![]; v.pop()", "None"),
+            (
+                "let mut v = vec![1, 3]; v.insert(1, 2); v.insert(3, 4); v",
+                "[1, 2, 3, 4]",
+            ),
+            (
+                "let mut v = vec![1, 2, 3]; let x = v.remove(1); (v, x)",
+                "([1, 3], 2)",
+            ),
+            ("let mut v = vec![1, 2, 3]; v.truncate(1); v", "[1]"),
+            (
+                "let mut v = vec![1]; v.extend(vec![2, 3]); v.extend(4..6); v",
+                "[1, 2, 3, 4, 5]",
+            ),
+            ("let mut v = vec![1, 2]; v.reverse(); v", "[2, 1]"),
+            ("let mut v = vec![3, 1, 2]; v.sort(); v", "[1, 2, 3]"),
+            (
+                "let mut v = vec![\"b\", \"a\"]; v.sort_unstable(); v",
+                "[\"a\", \"b\"]",
+            ),
+            ("let mut v = vec![1, 1, 2, 1]; v.dedup(); v", "[1, 2, 1]"),
+            (
+                "let mut v = vec![1, 2, 3, 4]; v.retain(|x| x % 2 == 0); v",
+                "[2, 4]",
+            ),
+            ("let mut v = vec![1]; v.clear(); v", "[]"),
+            ("let mut t = (vec![1], 2); t.0.push(3); t", "([1, 3], 2)"),
+            ("let mut g = vec![vec![1]]; g[0].push(2); g", "[[1, 2]]"),
+            // A temporary is changed and thrown away, as in Rust
+            ("let v = vec![1]; v.clone().push(2); v", "[1]"),
+        ]);
+        assert_fails(&[
+            ("let mut v = vec![1]; v.insert(3, 0);", "Cannot insert at 3"),
+            ("let mut v = vec![1]; v.remove(1);", "Cannot remove at 1"),
+            (
+                "let mut v = vec![1]; v.truncate();",
+                "`truncate` takes a length",
+            ),
+            (
+                "let mut v = vec![1, \"a\"]; v.sort();",
+                "Cannot compare \"a\" and 1",
+            ),
+            ("let mut n = 1; n.push(2);", "No method `push` on integer"),
+        ]);
+    }
+
+    #[test]
+    fn runs_iterator_methods() {
+        assert_evals(&[
+            ("vec![1, 2, 3].len()", "3"),
+            ("(0..10).len()", "10"),
+            ("(0..i64::MAX).count()", "9223372036854775807"),
+            ("(3..1).is_empty()", "true"),
+            ("(0..10).contains(&9)", "true"),
+            ("vec![1, 2].iter().map(|x| x * 10).collect::<Vec<_>>()", "[10, 20]"),
+            ("(1..=4).filter(|x| x % 2 == 0).collect::<Vec<i32>>()", "[2, 4]"),
+            ("(1..=4).sum::<i32>()", "10"),
+            ("vec![0.5, 1.0].iter().sum::<f64>()", "1.5"),
+            ("vec![].iter().sum::<i32>()", "0"),
+            ("(1..=5).product::<i32>()", "120"),
+            ("vec![1.5, 2.0].iter().product::<f64>()", "3.0"),
+            ("vec![3, 1, 2].iter().min()", "Some(1)"),
+            ("vec![3, 1, 2].iter().max()", "Some(3)"),
+            ("vec![(1, \"a\"), (1, \"b\")].iter().max()", "Some((1, \"b\"))"),
+            ("Vec::<i32>::new().iter().max()", "None"),
+            ("(1..4).fold(0, |acc, x| acc * 10 + x)", "123"),
+            ("vec![1, 2].iter().any(|x| *x > 1)", "true"),
+            ("vec![1, 2].iter().all(|x| *x > 1)", "false"),
+            ("vec![1, 2, 3].iter().find(|x| **x > 1)", "Some(2)"),
+            ("vec![1, 2, 3].iter().position(|x| *x == 3)", "Some(2)"),
+            ("vec![1, 2].iter().find(|x| **x > 5)", "None"),
+            ("vec![\"a\", \"b\"].iter().enumerate().collect::<Vec<_>>()", "[(0, \"a\"), (1, \"b\")]"),
+            ("(0..3).zip(vec![\"a\", \"b\"]).collect::<Vec<_>>()", "[(0, \"a\"), (1, \"b\")]"),
+            ("(0..2).chain(5..7).collect::<Vec<_>>()", "[0, 1, 5, 6]"),
+            ("(0..10).skip(2).take(3).collect::<Vec<_>>()", "[2, 3, 4]"),
+            ("(0..10).step_by(4).collect::<Vec<_>>()", "[0, 4, 8]"),
+            ("(0..3).rev().collect::<Vec<_>>()", "[2, 1, 0]"),
+            ("vec![\"1\", \"x\", \"3\"].iter().filter_map(|s| s.parse::<i32>().ok()).collect::<Vec<_>>()", "[1, 3]"),
+            ("(1..3).flat_map(|n| vec![n; n as usize]).collect::<Vec<_>>()", "[1, 2, 2]"),
+            ("vec![1, 2, 3].iter().map(|x| x.to_string()).collect::<Vec<String>>().join(\"-\")", "\"1-2-3\""),
+            ("vec![\"a\", \"b\"].concat()", "\"ab\""),
+            ("vec![1, 2].first()", "Some(1)"),
+            ("vec![1, 2].last()", "Some(2)"),
+            ("vec![1, 2].get(1)", "Some(2)"),
+            ("vec![1, 2].get(5)", "None"),
+            ("vec![1, 2].contains(&2)", "true"),
+            ("vec![1, 2].iter().cloned().collect::<Vec<_>>()", "[1, 2]"),
+            ("let mut n = 0; (1..4).for_each(|x| n += x); n", "0"),
+        ]);
+        assert_fails(&[
+            ("(0..3).step_by(0)", "`step_by` does not take 0"),
+            ("vec![1].iter().map()", "`map` takes an argument"),
+            (
+                "vec![1].iter().filter(|x| 1)",
+                "Expected a bool, found integer",
+            ),
+            (
+                "vec![1].iter().fold(0)",
+                "`fold` takes an initial value and a closure",
+            ),
+            ("vec![1].get(\"a\")", "Cannot index with \"a\""),
+            ("vec![1, \"a\"].iter().max()", "Cannot compare \"a\""),
+            (
+                "(0..i64::MAX).collect::<Vec<_>>()",
+                "Range 0..9223372036854775807 is too long",
+            ),
+            ("vec![1].iter().peekable()", "No method `peekable` on Vec"),
+        ]);
+    }
+
+    #[test]
+    fn formats_values_like_format_does() {
+        assert_evals(&[
+            ("format!(\"plain\")", "\"plain\""),
+            ("format!(\"{} and {}\", 1, \"two\")", "\"1 and two\""),
+            ("format!(\"{1} {0} {1}\", \"a\", \"b\")", "\"b a b\""),
+            ("let name = \"x\"; format!(\"{name}={}\", 3)", "\"x=3\""),
+            ("format!(\"{{}} {{{}}}\", 1)", "\"{} {1}\""),
+            ("format!(\"{:?}\", \"q\")", "\"\\\"q\\\"\""),
+            ("format!(\"{:?} {}\", 1.0, 1.0)", "\"1.0 1\""),
+            (
+                "format!(\"{:?}\", (vec![Some(1), None], Ok::<i32, String>(2), \"s\"))",
+                "\"([Some(1), None], Ok(2), \\\"s\\\")\"",
+            ),
+            ("format!(\"{}\", vec![\"a\"])", "\"[\\\"a\\\"]\""),
+            ("format!(\"{}\", 0..3)", "\"0..3\""),
+            ("format!(\"{:.2}\", 3.14159)", "\"3.14\""),
+            ("format!(\"{:.1}\", 2)", "\"2.0\""),
+            ("format!(\"[{:5}]\", 42)", "\"[   42]\""),
+            ("format!(\"[{:5}]\", \"ab\")", "\"[ab   ]\""),
+            ("format!(\"[{:<5}]\", 42)", "\"[42   ]\""),
+            ("format!(\"[{:>5}]\", \"ab\")", "\"[   ab]\""),
+            ("format!(\"[{:^6}]\", \"ab\")", "\"[  ab  ]\""),
+            ("format!(\"[{:*^7}]\", \"ab\")", "\"[**ab***]\""),
+            ("format!(\"[{:05}]\", -42)", "\"[-0042]\""),
+            ("format!(\"[{:08.3}]\", 3.14159)", "\"[0003.142]\""),
+            ("format!(\"[{:>8?}]\", \"a\")", "\"[     \\\"a\\\"]\""),
+            ("format!(\"[{:2}]\", \"long\")", "\"[long]\""),
+        ]);
+        assert_fails(&[
+            ("format!(\"{} {}\", 1)", "No value for {} in \"{} {}\""),
+            (
+                "format!(\"{missing}\")",
+                "No value for {missing} in \"{missing}\"",
+            ),
+            ("let f = \"{}\"; format!(f, 1)", "Expected a format string"),
+        ]);
+    }
+
+    #[test]
+    fn prints_through_the_callback() {
+        assert_eq!(
+            run("print!(\"a\"); println!(\"b{}\", 1); eprint!(\"c\"); eprintln!(); println!(\"{:?}\", 0.5);"),
+            Ok("ab1\nc\n0.5\n".to_string())
+        );
+        assert_eq!(
+            run("let x = dbg!(vec![\"a\"]); dbg!(); println!(\"{}\", x.len());"),
+            Ok("[\"a\"]\n()\n1\n".to_string())
+        );
+    }
+
+    #[test]
+    fn fails_on_panics_and_assertions() {
+        assert_fails(&[
+            ("panic!(\"bad {}\", 1)", "bad 1"),
+            ("panic!()", "`panic!` was reached"),
+            ("unreachable!()", "`unreachable!` was reached"),
+            ("todo!()", "`todo!` was reached"),
+            ("unimplemented!(\"later\")", "later"),
+            ("assert!(1 > 2)", "Assertion failed"),
+            ("assert!(1 > 2, \"{} is small\", 1)", "1 is small"),
+            ("assert!()", "`assert!` takes a condition"),
+            (
+                "assert_eq!(1, 2)",
+                "Assertion `left == right` failed: left is 1, right is 2",
+            ),
+            (
+                "assert_eq!(\"a\", \"b\", \"names {}\", 3)",
+                "Assertion `left == right` failed: names 3: left is \"a\", right is \"b\"",
+            ),
+            (
+                "assert_ne!(1, 1.0)",
+                "Assertion `left != right` failed: left is 1, right is 1.0",
+            ),
+            ("write!(f, \"x\")", "Macro `write!` is not supported"),
+        ]);
+        assert_evals(&[(
+            "assert!(true); assert_eq!(2, 2.0); assert_ne!(1, 2); 0",
+            "0",
+        )]);
+    }
+
+    #[test]
+    fn keeps_variables_of_main_between_runs() {
+        let (mut interpreter, output) = interpreter();
+        let first = parse("let mut total = 1; fn twice(x: i32) -> i32 { x * 2 }", 1).unwrap();
+        assert_eq!(interpreter.run(&first), Ok(false));
+        assert_eq!(interpreter.variable("total"), Some(&Value::Int(1)));
+
+        let second = parse("total = twice(total + 2); println!(\"{}\", total);", 2).unwrap();
+        assert_eq!(interpreter.run(&second), Ok(false));
+        assert_eq!(interpreter.variable("total"), Some(&Value::Int(6)));
+        assert_eq!(*output.lock(), "6\n");
+
+        // Variables of blocks are not those of main
+        let third = parse("{ let inner = 1; }", 3).unwrap();
+        assert_eq!(interpreter.run(&third), Ok(false));
+        assert_eq!(interpreter.variable("inner"), None);
+    }
+
+    #[test]
+    fn returns_from_main() {
+        let (mut interpreter, output) = interpreter();
+        let stmts = parse("println!(\"a\"); return; println!(\"b\");", 1).unwrap();
+        assert_eq!(interpreter.run(&stmts), Ok(true));
+        assert_eq!(*output.lock(), "a\n");
+    }
+
+    #[test]
+    fn stops_when_asked_to() {
+        let stop = Arc::new(AtomicBool::new(true));
+        let mut interpreter = Interpreter::new(stop, |_| {});
+        let stmts = parse("loop { }", 1).unwrap();
+        assert_eq!(interpreter.run(&stmts), Err(Halt::Stopped));
+    }
+
+    #[test]
+    fn gives_up_on_code_running_too_long() {
+        let (mut interpreter, _) = interpreter();
+        let stmts = parse("loop { }", 1).unwrap();
+        assert_eq!(
+            interpreter.run(&stmts),
+            Err(Halt::Error(format!(
+                "Ran for more than {} steps, so it may never end",
+                MAX_STEPS
+            )))
+        );
+        // Each run has steps of its own
+        let stmts = parse("let done = true;", 1).unwrap();
+        assert_eq!(interpreter.run(&stmts), Ok(false));
+    }
+}
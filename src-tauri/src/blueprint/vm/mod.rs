@@ -0,0 +1,112 @@
+//! Interpreter running Blueprint graphs in the editor, without building
+//! them.
+//!
+//! A graph is compiled as for a build, and the code of each node parsed
+//! back and interpreted in order, so a run behaves like the built program
+//! would. Between nodes a run may pause, on breakpoints or to step through
+//! the graph, and after each node it reports the values of its outputs.
+//!
+//! The interpreter covers the Rust node templates generate: variables,
+//! arithmetic, control flow, closures, functions and the common methods of
+//! numbers, strings, `Vec`s, `Option`s and iterators. Items other than
+//! functions, like `struct`s, are skipped.
+
+pub mod ast;
+pub mod error;
+pub mod eval;
+pub mod parse;
+pub mod session;
+pub mod value;
+
+pub use error::{RunError, RunResult};
+pub use session::{
+    BlueprintRun, PauseReason, Program, RunEvent, RunEventPayload, RunId, RunOptions, RunSink,
+    BLUEPRINT_RUN_EVENT,
+};
+pub use value::Value;
+
+use super::{compile, Graph, NodeId};
+use log::info;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Tauri state holding the running graphs.
+#[derive(Default)]
+pub struct RunState {
+    runs: Arc<Mutex<HashMap<RunId, BlueprintRun>>>,
+    next_id: AtomicU64,
+}
+
+impl RunState {
+    /// Compiles a graph and starts running it, reporting its events to
+    /// `sink`. The run is removed once it ends.
+    pub fn start(&self, graph: &Graph, options: RunOptions, sink: RunSink) -> RunResult<RunId> {
+        let compilation = compile(graph);
+        if compilation.has_errors() {
+            return Err(RunError::Compile);
+        }
+        let program = Program::new(&compilation)?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let runs = Arc::downgrade(&self.runs);
+
+        // Hold the lock until the run is inserted, so a run ending right away
+        // cannot be removed before it was added
+        let mut started = self.runs.lock();
+        let run = BlueprintRun::spawn(id, program, options, move |event| {
+            let last = event.is_last();
+            sink(RunEventPayload { run: id, event });
+            if last {
+                if let Some(runs) = runs.upgrade() {
+                    runs.lock().remove(&id);
+                }
+            }
+        })?;
+        started.insert(id, run);
+
+        info!("Started Blueprint run {}", id);
+        Ok(id)
+    }
+
+    pub fn resume(&self, id: RunId) -> RunResult<()> {
+        self.with_run(id, BlueprintRun::resume)
+    }
+
+    pub fn step(&self, id: RunId) -> RunResult<()> {
+        self.with_run(id, BlueprintRun::step)
+    }
+
+    pub fn pause(&self, id: RunId) -> RunResult<()> {
+        self.with_run(id, BlueprintRun::pause)
+    }
+
+    pub fn set_breakpoints(&self, id: RunId, breakpoints: HashSet<NodeId>) -> RunResult<()> {
+        self.with_run(id, |run| run.set_breakpoints(breakpoints))
+    }
+
+    /// Stops a run. It reports `RunEvent::Stopped` once the running node is
+    /// interrupted.
+    pub fn stop(&self, id: RunId) -> RunResult<()> {
+        self.with_run(id, BlueprintRun::stop)
+    }
+
+    /// Stops every run, when the editor closes.
+    pub fn shutdown(&self) {
+        let runs: Vec<_> = self.runs.lock().drain().collect();
+        if !runs.is_empty() {
+            info!("Stopping {} Blueprint runs", runs.len());
+        }
+    }
+
+    fn with_run(&self, id: RunId, f: impl FnOnce(&BlueprintRun)) -> RunResult<()> {
+        let runs = self.runs.lock();
+        f(runs.get(&id).ok_or(RunError::UnknownRun(id))?);
+        Ok(())
+    }
+}
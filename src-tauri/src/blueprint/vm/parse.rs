@@ -0,0 +1,1495 @@
+use super::{
+    ast::{BinOp, Block, Closure, Expr, Function, Pattern, Stmt, UnOp},
+    value::Value,
+};
+use std::sync::Arc;
+
+/// Parses the statements of a node's code: the subset of Rust node
+/// templates are written in. `first_line` is the line the code starts on in
+/// the generated source, which errors refer to.
+pub fn parse(source: &str, first_line: usize) -> Result<Vec<Stmt>, String> {
+    let tokens = tokenize(source, first_line)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut stmts = Vec::new();
+    while !parser.at_end() {
+        if let Some(stmt) = parser.stmt(false)? {
+            stmts.push(stmt);
+        }
+    }
+    Ok(stmts)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Punct(&'static str),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Int(value) => format!("`{}`", value),
+            Token::Float(value) => format!("`{:?}`", value),
+            Token::Str(text) => format!("{:?}", text),
+            Token::Punct(punct) => format!("`{}`", punct),
+        }
+    }
+}
+
+/// Punctuation, longest first so `..=` is not read as `..` and `=`.
+const PUNCTS: &[&str] = &[
+    "..=", "...", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=",
+    "%=", "^=", "&=", "|=", "..", "+", "-", "*", "/", "%", "=", "<", ">", "!", "&", "|", "^", "(",
+    ")", "[", "]", "{", "}", ",", ";", ":", ".", "#", "?", "@", "$", "~",
+];
+
+fn tokenize(source: &str, first_line: usize) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = first_line;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    line += (chars[i] == '\n') as usize;
+                    i += 1;
+                }
+            }
+        } else if c == 'r' && (next == Some('"') || (next == Some('#') && raw_start(&chars, i))) {
+            let start = line;
+            let (text, end) = raw_string(&chars, i + 1).ok_or_else(|| unclosed(start))?;
+            line += text.matches('\n').count();
+            tokens.push((Token::Str(text), start));
+            i = end;
+        } else if c == '"' {
+            let start = line;
+            let (text, end) = string(&chars, i + 1, '"').ok_or_else(|| unclosed(start))?;
+            line += text.matches('\n').count();
+            tokens.push((Token::Str(text), start));
+            i = end;
+        } else if c == '\'' {
+            // A char literal, or a lifetime, which only appears in types
+            match string(&chars, i + 1, '\'') {
+                Some((text, end)) if text.chars().count() == 1 => {
+                    tokens.push((Token::Str(text), line));
+                    i = end;
+                }
+                _ => {
+                    let end = (i + 1..chars.len())
+                        .find(|&j| !is_ident_char(chars[j]))
+                        .unwrap_or(chars.len());
+                    let name: String = chars[i..end].iter().collect();
+                    tokens.push((Token::Ident(name), line));
+                    i = end;
+                }
+            }
+        } else if c.is_ascii_digit() {
+            let (token, end) = number(&chars, i).map_err(|e| format!("{} on line {}", e, line))?;
+            tokens.push((token, line));
+            i = end;
+        } else if is_ident_char(c) {
+            let end = (i..chars.len())
+                .find(|&j| !is_ident_char(chars[j]))
+                .unwrap_or(chars.len());
+            tokens.push((Token::Ident(chars[i..end].iter().collect()), line));
+            i = end;
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|punct| {
+                    punct
+                        .chars()
+                        .enumerate()
+                        .all(|(offset, p)| chars.get(i + offset) == Some(&p))
+                })
+                .ok_or_else(|| format!("Unexpected {:?} on line {}", c, line))?;
+            tokens.push((Token::Punct(punct), line));
+            i += punct.len();
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn unclosed(line: usize) -> String {
+    format!("Unterminated string on line {}", line)
+}
+
+fn raw_start(chars: &[char], i: usize) -> bool {
+    let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+    chars.get(i + 1 + hashes) == Some(&'"')
+}
+
+/// Reads a raw string from its `#`s or opening quote.
+fn raw_string(chars: &[char], mut i: usize) -> Option<(String, usize)> {
+    let hashes = chars[i..].iter().take_while(|&&c| c == '#').count();
+    i += hashes + 1;
+    let start = i;
+    while i < chars.len() {
+        if chars[i] == '"'
+            && chars[i + 1..]
+                .iter()
+                .take(hashes)
+                .filter(|&&c| c == '#')
+                .count()
+                == hashes
+        {
+            return Some((chars[start..i].iter().collect(), i + 1 + hashes));
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Reads a string or char literal after its opening quote.
+fn string(chars: &[char], mut i: usize, quote: char) -> Option<(String, usize)> {
+    let mut text = String::new();
+    while i < chars.len() {
+        match chars[i] {
+            c if c == quote => return Some((text, i + 1)),
+            '\\' => {
+                let escaped = *chars.get(i + 1)?;
+                i += 2;
+                match escaped {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    'r' => text.push('\r'),
+                    '0' => text.push('\0'),
+                    'u' => {
+                        let end = i + chars[i..].iter().position(|&c| c == '}')?;
+                        let hex: String = chars[i + 1..end].iter().collect();
+                        text.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                        i = end + 1;
+                    }
+                    // A line continuation skips the indentation after it
+                    '\n' => {
+                        while i < chars.len() && chars[i].is_whitespace() {
+                            i += 1;
+                        }
+                    }
+                    c => text.push(c),
+                }
+            }
+            // A lifetime, not a char literal
+            '\n' if quote == '\'' => return None,
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+fn number(chars: &[char], start: usize) -> Result<(Token, usize), String> {
+    let mut i = start;
+    let radix = match (chars[i], chars.get(i + 1)) {
+        ('0', Some('x')) => 16,
+        ('0', Some('o')) => 8,
+        ('0', Some('b')) => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        i += 2;
+        let end = (i..chars.len())
+            .find(|&j| !(chars[j].is_ascii_hexdigit() || chars[j] == '_'))
+            .unwrap_or(chars.len());
+        let digits: String = chars[i..end].iter().filter(|&&c| c != '_').collect();
+        let value = i64::from_str_radix(&digits, radix).map_err(|e| e.to_string())?;
+        let end = skip_suffix(chars, end);
+        return Ok((Token::Int(value), end));
+    }
+
+    let digits_end = |i: usize| {
+        (i..chars.len())
+            .find(|&j| !(chars[j].is_ascii_digit() || chars[j] == '_'))
+            .unwrap_or(chars.len())
+    };
+    i = digits_end(i);
+    let mut float = false;
+    // `1.5`, but not the range `1..5` or the method call `1.max(2)`
+    if chars.get(i) == Some(&'.') && chars.get(i + 1).map_or(false, char::is_ascii_digit) {
+        float = true;
+        i = digits_end(i + 1);
+    }
+    if matches!(chars.get(i), Some('e' | 'E')) {
+        let sign = matches!(chars.get(i + 1), Some('+' | '-')) as usize;
+        if chars.get(i + 1 + sign).map_or(false, char::is_ascii_digit) {
+            float = true;
+            i = digits_end(i + 1 + sign);
+        }
+    }
+    let text: String = chars[start..i].iter().filter(|&&c| c != '_').collect();
+    let suffix_end = skip_suffix(chars, i);
+    let suffix: String = chars[i..suffix_end].iter().collect();
+    let token = if float || suffix.starts_with('f') {
+        Token::Float(
+            text.parse()
+                .map_err(|_| format!("Invalid number {}", text))?,
+        )
+    } else {
+        Token::Int(
+            text.parse()
+                .map_err(|_| format!("Invalid number {}", text))?,
+        )
+    };
+    Ok((token, suffix_end))
+}
+
+fn skip_suffix(chars: &[char], i: usize) -> usize {
+    (i..chars.len())
+        .find(|&j| !is_ident_char(chars[j]))
+        .unwrap_or(chars.len())
+}
+
+/// Words that start an item, which is skipped unless it is a `fn`.
+const ITEMS: &[&str] = &[
+    "struct", "enum", "impl", "trait", "mod", "use", "type", "union", "extern",
+];
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|(token, _)| token)
+    }
+
+    fn is(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == word)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = self.is_word(word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.tokens.get(self.pos) {
+            Some((token, line)) => format!(
+                "Expected {}, found {} on line {}",
+                expected,
+                token.describe(),
+                line
+            ),
+            None => format!("Expected {}, found the end of the code", expected),
+        }
+    }
+
+    /// Error for Rust the interpreter does not run, on the line of the last
+    /// token read.
+    fn unsupported(&self, what: &str) -> String {
+        let line = self.tokens[..self.pos.min(self.tokens.len())]
+            .last()
+            .map(|(_, line)| *line);
+        match line {
+            Some(line) => format!("{} is not supported, on line {}", what, line),
+            None => format!("{} is not supported", what),
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}`", punct)))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("a name")),
+        }
+    }
+
+    /// Skips tokens until one of `ends` outside any brackets, and returns
+    /// them as text, which is how types are read.
+    fn skip_until(&mut self, ends: &[&str]) -> Result<String, String> {
+        let mut depth = 0usize;
+        let mut text = String::new();
+        while let Some(token) = self.peek() {
+            if let Token::Punct(punct) = token {
+                if depth == 0 && ends.contains(punct) {
+                    return Ok(text);
+                }
+                match *punct {
+                    "(" | "[" | "{" | "<" => depth += 1,
+                    ")" | "]" | "}" | ">" if depth > 0 => depth -= 1,
+                    ")" | "]" | "}" => return Err(self.error("a type")),
+                    _ => {}
+                }
+            }
+            text.push_str(&match token {
+                Token::Ident(name) => name.clone(),
+                Token::Punct(punct) => punct.to_string(),
+                other => other.describe(),
+            });
+            self.pos += 1;
+        }
+        Err(self.error(&format!("`{}`", ends[0])))
+    }
+
+    /// Skips a bracketed group starting at the current token.
+    fn skip_group(&mut self) -> Result<(), String> {
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            if let Token::Punct(punct) = token {
+                match *punct {
+                    "(" | "[" | "{" => depth += 1,
+                    ")" | "]" | "}" => {
+                        depth = depth.saturating_sub(1);
+                        if depth == 0 {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            self.pos += 1;
+        }
+        Err(self.error("a closing bracket"))
+    }
+
+    /// Skips `<..>` after a path or method, returning what was in it.
+    fn generics(&mut self) -> Result<Option<String>, String> {
+        if !self.eat("<") {
+            return Ok(None);
+        }
+        let mut depth = 1usize;
+        let mut text = String::new();
+        while let Some(token) = self.peek().cloned() {
+            self.pos += 1;
+            match &token {
+                Token::Punct("<") => depth += 1,
+                Token::Punct(">") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(Some(text));
+                    }
+                }
+                _ => {}
+            }
+            text.push_str(&match token {
+                Token::Ident(name) => name,
+                Token::Punct(punct) => punct.to_string(),
+                other => other.describe(),
+            });
+        }
+        Err(self.error("`>`"))
+    }
+
+    /// Parses a statement, or returns `None` for an empty one. In a block,
+    /// an expression without `;` before the closing brace is returned as
+    /// `Stmt::Expr` and left for the caller to take as the block's value.
+    fn stmt(&mut self, in_block: bool) -> Result<Option<Stmt>, String> {
+        if self.eat(";") {
+            return Ok(None);
+        }
+        if self.is("#") {
+            self.pos += 1;
+            self.eat("!");
+            self.skip_group()?;
+            return Ok(None);
+        }
+        if self.eat_word("pub") && self.is("(") {
+            self.skip_group()?;
+        }
+
+        if self.eat_word("let") {
+            let pattern = self.pattern()?;
+            let ty = match self.eat(":") {
+                true => Some(self.skip_until(&["=", ";"])?),
+                false => None,
+            };
+            let value = match self.eat("=") {
+                true => Some(self.expr()?),
+                false => None,
+            };
+            self.expect(";")?;
+            return Ok(Some(Stmt::Let { pattern, ty, value }));
+        }
+        if self.is_word("const") || self.is_word("static") {
+            self.pos += 1;
+            self.eat_word("mut");
+            let name = self.ident()?;
+            self.expect(":")?;
+            let ty = self.skip_until(&["="])?;
+            self.expect("=")?;
+            let value = self.expr()?;
+            self.expect(";")?;
+            return Ok(Some(Stmt::Let {
+                pattern: Pattern::Ident(name),
+                ty: Some(ty),
+                value: Some(value),
+            }));
+        }
+        if self.is_word("fn") {
+            self.pos += 1;
+            return Ok(Some(Stmt::Fn(Arc::new(self.function()?))));
+        }
+        if let Some(Token::Ident(word)) = self.peek() {
+            if ITEMS.contains(&word.as_str()) {
+                // Up to the end of its body or its `;`
+                while !self.is(";") && !self.is("{") {
+                    if self.at_end() {
+                        return Err(self.error("`;` or `{`"));
+                    }
+                    self.pos += 1;
+                }
+                if self.is("{") {
+                    self.skip_group()?;
+                }
+                self.eat(";");
+                return Ok(Some(Stmt::Item));
+            }
+        }
+
+        // A statement starting with a block ends with it, so `if a {} -1` is
+        // two statements, unless a method is called on its value
+        if self.starts_block() {
+            let start = self.pos;
+            let expr = self.primary()?;
+            if !self.is(".") {
+                self.eat(";");
+                return Ok(Some(Stmt::Expr(expr)));
+            }
+            self.pos = start;
+        }
+
+        let expr = self.expr()?;
+        if self.eat(";") || (in_block && self.is("}")) || (!in_block && self.at_end()) {
+            return Ok(Some(Stmt::Expr(expr)));
+        }
+        if is_block_like(&expr) {
+            return Ok(Some(Stmt::Expr(expr)));
+        }
+        Err(self.error("`;`"))
+    }
+
+    /// Whether the current token starts an expression ending with a block.
+    fn starts_block(&self) -> bool {
+        match self.peek() {
+            Some(Token::Punct("{")) => true,
+            Some(Token::Ident(word)) => {
+                matches!(word.as_str(), "if" | "while" | "loop" | "for" | "unsafe")
+            }
+            _ => false,
+        }
+    }
+
+    fn function(&mut self) -> Result<Function, String> {
+        let name = self.ident()?;
+        self.generics()?;
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.eat(")") {
+            self.eat("&");
+            self.eat_word("mut");
+            if self.eat_word("self") {
+                return Err(self.unsupported("A `self` parameter"));
+            }
+            params.push(self.pattern()?);
+            self.expect(":")?;
+            self.skip_until(&[",", ")"])?;
+            if !self.eat(",") {
+                self.expect(")")?;
+                break;
+            }
+        }
+        if self.eat("->") {
+            self.skip_until(&["{"])?;
+        }
+        if self.eat_word("where") {
+            self.skip_until(&["{"])?;
+        }
+        let body = self.block()?;
+        Ok(Function { name, params, body })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, String> {
+        self.eat("&");
+        self.eat_word("mut");
+        if self.eat("(") {
+            let mut patterns = Vec::new();
+            while !self.eat(")") {
+                patterns.push(self.pattern()?);
+                if !self.eat(",") {
+                    self.expect(")")?;
+                    break;
+                }
+            }
+            return Ok(Pattern::Tuple(patterns));
+        }
+        match self.ident()?.as_str() {
+            "_" => Ok(Pattern::Wild),
+            name => Ok(Pattern::Ident(name.to_string())),
+        }
+    }
+
+    fn block(&mut self) -> Result<Block, String> {
+        self.expect("{")?;
+        let mut block = Block::default();
+        while !self.eat("}") {
+            if self.at_end() {
+                return Err(self.error("`}`"));
+            }
+            let Some(stmt) = self.stmt(true)? else {
+                continue;
+            };
+            // An expression without `;` at the end is the block's value
+            let ended_with_semicolon = matches!(
+                self.tokens.get(self.pos.wrapping_sub(1)),
+                Some((Token::Punct(";"), _))
+            );
+            match stmt {
+                Stmt::Expr(expr) if self.is("}") && !ended_with_semicolon => {
+                    block.tail = Some(Box::new(expr));
+                }
+                stmt => block.stmts.push(stmt),
+            }
+        }
+        Ok(block)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let target = self.range()?;
+        const ASSIGN: &[(&str, Option<BinOp>)] = &[
+            ("=", None),
+            ("+=", Some(BinOp::Add)),
+            ("-=", Some(BinOp::Sub)),
+            ("*=", Some(BinOp::Mul)),
+            ("/=", Some(BinOp::Div)),
+            ("%=", Some(BinOp::Rem)),
+            ("&=", Some(BinOp::BitAnd)),
+            ("|=", Some(BinOp::BitOr)),
+            ("^=", Some(BinOp::BitXor)),
+        ];
+        for (punct, op) in ASSIGN {
+            if self.eat(punct) {
+                let value = self.expr()?;
+                return Ok(Expr::Assign(Box::new(target), *op, Box::new(value)));
+            }
+        }
+        Ok(target)
+    }
+
+    fn range(&mut self) -> Result<Expr, String> {
+        let start = match self.is("..") || self.is("..=") {
+            true => None,
+            false => Some(Box::new(self.binary(0)?)),
+        };
+        let inclusive = self.is("..=");
+        if !(self.eat("..") || self.eat("..=")) {
+            return Ok(*start.expect("an expression was parsed"));
+        }
+        let ends = self.at_end() || [")", "]", "}", ",", ";", "{"].iter().any(|p| self.is(p));
+        let end = match ends && !inclusive {
+            true => None,
+            false => Some(Box::new(self.binary(0)?)),
+        };
+        Ok(Expr::Range {
+            start,
+            end,
+            inclusive,
+        })
+    }
+
+    /// Parses binary operators of at least `min` precedence.
+    fn binary(&mut self, min: u8) -> Result<Expr, String> {
+        let mut left = self.cast()?;
+        while let Some((op, precedence)) = self.binary_op() {
+            if precedence < min {
+                break;
+            }
+            self.pos += 1;
+            // All binary operators are left associative
+            let right = self.binary(precedence + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+            // Comparisons do not chain
+            if precedence == 3 && matches!(self.binary_op(), Some((_, 3))) {
+                return Err(self.error("parentheses around chained comparisons"));
+            }
+        }
+        Ok(left)
+    }
+
+    fn binary_op(&self) -> Option<(BinOp, u8)> {
+        let Some(Token::Punct(punct)) = self.peek() else {
+            return None;
+        };
+        Some(match *punct {
+            "||" => (BinOp::Or, 1),
+            "&&" => (BinOp::And, 2),
+            "==" => (BinOp::Eq, 3),
+            "!=" => (BinOp::Ne, 3),
+            "<" => (BinOp::Lt, 3),
+            "<=" => (BinOp::Le, 3),
+            ">" => (BinOp::Gt, 3),
+            ">=" => (BinOp::Ge, 3),
+            "|" => (BinOp::BitOr, 4),
+            "^" => (BinOp::BitXor, 5),
+            "&" => (BinOp::BitAnd, 6),
+            "+" => (BinOp::Add, 7),
+            "-" => (BinOp::Sub, 7),
+            "*" => (BinOp::Mul, 8),
+            "/" => (BinOp::Div, 8),
+            "%" => (BinOp::Rem, 8),
+            _ => return None,
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let op = if self.eat("-") {
+            UnOp::Neg
+        } else if self.eat("!") {
+            UnOp::Not
+        } else if self.eat("&") || self.eat("&&") || self.eat("*") {
+            // `&&x` is a reference to a reference, the same value still
+            self.eat_word("mut");
+            UnOp::Ref
+        } else {
+            return self.postfix();
+        };
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    /// Parses casts, which bind looser than unary operators: `-x as u8` is
+    /// `(-x) as u8`.
+    fn cast(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat_word("as") {
+            let ty = self.ident()?;
+            expr = Expr::Cast(Box::new(expr), ty);
+        }
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                match self.peek().cloned() {
+                    Some(Token::Int(index)) => {
+                        self.pos += 1;
+                        expr = Expr::Field(Box::new(expr), index as usize);
+                    }
+                    // `tuple.0.1` reads as a float
+                    Some(Token::Float(_)) => {
+                        return Err(self.unsupported("A nested tuple field"));
+                    }
+                    Some(Token::Ident(method)) => {
+                        self.pos += 1;
+                        if method == "await" {
+                            return Err(self.unsupported("`.await`"));
+                        }
+                        let turbofish = match self.eat("::") {
+                            true => self.generics()?,
+                            false => None,
+                        };
+                        if !self.is("(") {
+                            return Err(
+                                self.unsupported(&format!("Field {:?} of a struct", method))
+                            );
+                        }
+                        let args = self.args(")")?;
+                        expr = Expr::MethodCall {
+                            receiver: Box::new(expr),
+                            method,
+                            turbofish,
+                            args,
+                        };
+                    }
+                    _ => return Err(self.error("a method or field")),
+                }
+            } else if self.is("(") {
+                let args = self.args(")")?;
+                expr = Expr::Call(Box::new(expr), args);
+            } else if self.eat("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if self.is("?") {
+                return Err(self.unsupported("The `?` operator"));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Parses arguments from an opening bracket to `close`.
+    fn args(&mut self, close: &str) -> Result<Vec<Expr>, String> {
+        self.pos += 1;
+        let mut args = Vec::new();
+        while !self.eat(close) {
+            args.push(self.expr()?);
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(args)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("an expression"));
+        };
+        match token {
+            Token::Int(value) => {
+                self.pos += 1;
+                Ok(Expr::Lit(Value::Int(value)))
+            }
+            Token::Float(value) => {
+                self.pos += 1;
+                Ok(Expr::Lit(Value::Float(value)))
+            }
+            Token::Str(text) => {
+                self.pos += 1;
+                Ok(Expr::Lit(Value::Str(text)))
+            }
+            Token::Punct("(") => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                let mut tuple = false;
+                while !self.eat(")") {
+                    items.push(self.expr()?);
+                    if self.eat(",") {
+                        tuple = true;
+                    } else {
+                        self.expect(")")?;
+                        break;
+                    }
+                }
+                match (items.len(), tuple) {
+                    (1, false) => Ok(items.pop().expect("one item")),
+                    _ => Ok(Expr::Tuple(items)),
+                }
+            }
+            Token::Punct("[") => self.array("]"),
+            Token::Punct("{") => Ok(Expr::Block(self.block()?)),
+            Token::Punct("|") | Token::Punct("||") => self.closure(),
+            Token::Ident(word) => self.word(word),
+            _ => Err(self.error("an expression")),
+        }
+    }
+
+    fn closure(&mut self) -> Result<Expr, String> {
+        let mut params = Vec::new();
+        if !self.eat("||") {
+            self.expect("|")?;
+            while !self.eat("|") {
+                params.push(self.pattern()?);
+                if self.eat(":") {
+                    self.skip_until(&[",", "|"])?;
+                }
+                if !self.eat(",") {
+                    self.expect("|")?;
+                    break;
+                }
+            }
+        }
+        let body = match self.eat("->") {
+            true => {
+                self.skip_until(&["{"])?;
+                Expr::Block(self.block()?)
+            }
+            false => self.expr()?,
+        };
+        Ok(Expr::Closure(Arc::new(Closure { params, body })))
+    }
+
+    fn word(&mut self, word: String) -> Result<Expr, String> {
+        self.pos += 1;
+        match word.as_str() {
+            "true" => return Ok(Expr::Lit(Value::Bool(true))),
+            "false" => return Ok(Expr::Lit(Value::Bool(false))),
+            "move" => return self.closure(),
+            "unsafe" => return Ok(Expr::Block(self.block()?)),
+            "if" => return self.if_expr(),
+            "while" => {
+                if self.is_word("let") {
+                    return Err(self.unsupported("`while let`"));
+                }
+                let condition = self.expr()?;
+                return Ok(Expr::While(Box::new(condition), self.block()?));
+            }
+            "loop" => return Ok(Expr::Loop(self.block()?)),
+            "for" => {
+                let pattern = self.pattern()?;
+                if !self.eat_word("in") {
+                    return Err(self.error("`in`"));
+                }
+                let iterable = self.expr()?;
+                return Ok(Expr::For(pattern, Box::new(iterable), self.block()?));
+            }
+            "break" => {
+                let value = match self.ends_expr() {
+                    true => None,
+                    false => Some(Box::new(self.expr()?)),
+                };
+                return Ok(Expr::Break(value));
+            }
+            "continue" => return Ok(Expr::Continue),
+            "return" => {
+                let value = match self.ends_expr() {
+                    true => None,
+                    false => Some(Box::new(self.expr()?)),
+                };
+                return Ok(Expr::Return(value));
+            }
+            "match" => return Err(self.unsupported("`match`")),
+            _ => {}
+        }
+
+        // A path like `f64::consts::PI`, with turbofishes skipped
+        let mut path = word;
+        while self.is("::") {
+            self.pos += 1;
+            if self.is("<") {
+                self.generics()?;
+            } else {
+                path.push_str("::");
+                path.push_str(&self.ident()?);
+            }
+        }
+
+        if self.is("!") && matches!(self.peek_at(1), Some(Token::Punct("(" | "[" | "{"))) {
+            self.pos += 1;
+            return self.macro_call(path);
+        }
+        Ok(Expr::Path(path))
+    }
+
+    /// Whether the current token ends an expression, for `break` and
+    /// `return` without a value.
+    fn ends_expr(&self) -> bool {
+        self.at_end() || [";", "}", ")", ","].iter().any(|p| self.is(p))
+    }
+
+    fn if_expr(&mut self) -> Result<Expr, String> {
+        if self.is_word("let") {
+            return Err(self.unsupported("`if let`"));
+        }
+        let condition = self.expr()?;
+        let then = self.block()?;
+        let otherwise = match self.eat_word("else") {
+            true if self.eat_word("if") => Some(Box::new(self.if_expr()?)),
+            true => Some(Box::new(Expr::Block(self.block()?))),
+            false => None,
+        };
+        Ok(Expr::If(Box::new(condition), then, otherwise))
+    }
+
+    fn macro_call(&mut self, name: String) -> Result<Expr, String> {
+        let close = match self.peek() {
+            Some(Token::Punct("(")) => ")",
+            Some(Token::Punct("[")) => "]",
+            _ => "}",
+        };
+        if name == "vec" {
+            return self.array(close);
+        }
+        let args = self.args(close)?;
+        Ok(Expr::Macro(name, args))
+    }
+
+    /// Parses `[a, b]` or `[value; count]`, also between the other brackets
+    /// `vec!` may use.
+    fn array(&mut self, close: &str) -> Result<Expr, String> {
+        self.pos += 1;
+        if self.eat(close) {
+            return Ok(Expr::Array(Vec::new()));
+        }
+        let first = self.expr()?;
+        if self.eat(";") {
+            let count = self.expr()?;
+            self.expect(close)?;
+            return Ok(Expr::Repeat(Box::new(first), Box::new(count)));
+        }
+        let mut items = vec![first];
+        while self.eat(",") && !self.is(close) {
+            items.push(self.expr()?);
+        }
+        self.expect(close)?;
+        Ok(Expr::Array(items))
+    }
+}
+
+/// Whether an expression ends with a block, so it needs no `;` as a
+/// statement.
+fn is_block_like(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Block(_) | Expr::If(..) | Expr::While(..) | Expr::Loop(_) | Expr::For(..)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses code holding a single expression statement.
+    fn expr(source: &str) -> Expr {
+        match parse(source, 1).unwrap().as_slice() {
+            [Stmt::Expr(expr)] => expr.clone(),
+            other => panic!("expected one expression, found {:?}", other),
+        }
+    }
+
+    fn tree(source: &str) -> String {
+        format!("{:?}", expr(source))
+    }
+
+    fn literal(source: &str) -> Value {
+        match expr(source) {
+            Expr::Lit(value) => value,
+            other => panic!("expected a literal, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_integer_literals_in_every_radix() {
+        for (source, expected) in [
+            ("42", 42),
+            ("1_000", 1000),
+            ("0x1F", 31),
+            ("0o17", 15),
+            ("0b101", 5),
+            ("7u8", 7),
+            ("0xffu32", 255),
+            ("3usize", 3),
+        ] {
+            assert!(
+                matches!(literal(source), Value::Int(value) if value == expected),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn reads_float_literals_with_exponents_and_suffixes() {
+        for (source, expected) in [
+            ("2.5", 2.5),
+            ("1e3", 1000.0),
+            ("1.5e-2", 0.015),
+            ("2E+2", 200.0),
+            ("3f32", 3.0),
+            ("0.5_f64", 0.5),
+        ] {
+            assert!(
+                matches!(literal(source), Value::Float(value) if value == expected),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn reads_dots_after_integers_as_ranges_and_method_calls() {
+        assert_eq!(
+            tree("1..5"),
+            "Range { start: Some(Lit(Int(1))), end: Some(Lit(Int(5))), inclusive: false }"
+        );
+        assert_eq!(
+            tree("1.max(2)"),
+            "MethodCall { receiver: Lit(Int(1)), method: \"max\", turbofish: None, args: [Lit(Int(2))] }"
+        );
+    }
+
+    #[test]
+    fn reads_string_and_char_literals_with_escapes() {
+        for (source, expected) in [
+            (r#""plain""#, "plain"),
+            (r#""a\nb\t\"c\"\\""#, "a\nb\t\"c\"\\"),
+            (r#""\u{48}i""#, "Hi"),
+            ("\"one \\\n    two\"", "one two"),
+            (r##"r"C:\path""##, "C:\\path"),
+            (r###"r#"say "hi""#"###, "say \"hi\""),
+            ("'x'", "x"),
+            (r"'\n'", "\n"),
+            (r"'\''", "'"),
+        ] {
+            assert!(
+                matches!(literal(source), Value::Str(text) if text == expected),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn counts_lines_across_multiline_strings_and_comments() {
+        let error = parse("let a = \"one\ntwo\";\n/* a\ncomment */\nlet b = ;", 10).unwrap_err();
+        assert_eq!(error, "Expected an expression, found `;` on line 14");
+    }
+
+    #[test]
+    fn skips_comments() {
+        let stmts = parse("// line\n/* block /* nested */ */ 1 + /* inline */ 2", 1).unwrap();
+        assert_eq!(
+            format!("{:?}", stmts),
+            "[Expr(Binary(Add, Lit(Int(1)), Lit(Int(2))))]"
+        );
+    }
+
+    #[test]
+    fn binds_operators_by_precedence() {
+        assert_eq!(
+            tree("1 + 2 * 3 - 4"),
+            "Binary(Sub, Binary(Add, Lit(Int(1)), Binary(Mul, Lit(Int(2)), Lit(Int(3)))), Lit(Int(4)))"
+        );
+        assert_eq!(
+            tree("a || b && c == d"),
+            "Binary(Or, Path(\"a\"), Binary(And, Path(\"b\"), Binary(Eq, Path(\"c\"), Path(\"d\"))))"
+        );
+        assert_eq!(
+            tree("a | b ^ c & d"),
+            "Binary(BitOr, Path(\"a\"), Binary(BitXor, Path(\"b\"), Binary(BitAnd, Path(\"c\"), Path(\"d\"))))"
+        );
+        assert_eq!(
+            tree("a < b + 1"),
+            "Binary(Lt, Path(\"a\"), Binary(Add, Path(\"b\"), Lit(Int(1))))"
+        );
+        assert_eq!(
+            tree("8 / 4 % 3"),
+            "Binary(Rem, Binary(Div, Lit(Int(8)), Lit(Int(4))), Lit(Int(3)))"
+        );
+    }
+
+    #[test]
+    fn binds_unary_operators_and_casts_tighter_than_binary_ones() {
+        assert_eq!(
+            tree("-a * !b"),
+            "Binary(Mul, Unary(Neg, Path(\"a\")), Unary(Not, Path(\"b\")))"
+        );
+        assert_eq!(
+            tree("a as f64 / 2.0"),
+            "Binary(Div, Cast(Path(\"a\"), \"f64\"), Lit(Float(2.0)))"
+        );
+        assert_eq!(
+            tree("-x as u8 as i32"),
+            "Cast(Cast(Unary(Neg, Path(\"x\")), \"u8\"), \"i32\")"
+        );
+        assert_eq!(
+            tree("&mut *&&v"),
+            "Unary(Ref, Unary(Ref, Unary(Ref, Path(\"v\"))))"
+        );
+    }
+
+    #[test]
+    fn rejects_chained_comparisons() {
+        assert_eq!(
+            parse("a < b < c", 3).unwrap_err(),
+            "Expected parentheses around chained comparisons, found `<` on line 3"
+        );
+    }
+
+    #[test]
+    fn parses_assignments_with_and_without_operators() {
+        assert_eq!(tree("a = 1"), "Assign(Path(\"a\"), None, Lit(Int(1)))");
+        for (op, name) in [
+            ("+=", "Add"),
+            ("-=", "Sub"),
+            ("*=", "Mul"),
+            ("/=", "Div"),
+            ("%=", "Rem"),
+            ("&=", "BitAnd"),
+            ("|=", "BitOr"),
+            ("^=", "BitXor"),
+        ] {
+            assert_eq!(
+                tree(&format!("v[0].1 {} 2", op)),
+                format!(
+                    "Assign(Field(Index(Path(\"v\"), Lit(Int(0))), 1), Some({}), Lit(Int(2)))",
+                    name
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn parses_ranges_with_and_without_bounds() {
+        assert_eq!(
+            tree("..=n"),
+            "Range { start: None, end: Some(Path(\"n\")), inclusive: true }"
+        );
+        assert_eq!(
+            tree("a + 1.."),
+            "Range { start: Some(Binary(Add, Path(\"a\"), Lit(Int(1)))), end: None, inclusive: false }"
+        );
+        assert_eq!(
+            tree("v[1..]"),
+            "Index(Path(\"v\"), Range { start: Some(Lit(Int(1))), end: None, inclusive: false })"
+        );
+        assert_eq!(
+            tree("v[..]"),
+            "Index(Path(\"v\"), Range { start: None, end: None, inclusive: false })"
+        );
+    }
+
+    #[test]
+    fn parses_let_statements_with_types_and_patterns() {
+        let stmts = parse(
+            "let a: Vec<(u8, f64)> = x;\nlet (b, _, (mut c, &d)) = y;\nlet e;",
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            format!("{:?}", stmts),
+            "[Let { pattern: Ident(\"a\"), ty: Some(\"Vec<(u8,f64)>\"), value: Some(Path(\"x\")) }, \
+             Let { pattern: Tuple([Ident(\"b\"), Wild, Tuple([Ident(\"c\"), Ident(\"d\")])]), ty: None, value: Some(Path(\"y\")) }, \
+             Let { pattern: Ident(\"e\"), ty: None, value: None }]"
+        );
+    }
+
+    #[test]
+    fn reads_constants_and_statics_as_lets() {
+        let stmts = parse("const LIMIT: u32 = 3;\nstatic mut NAME: &str = \"n\";", 1).unwrap();
+        assert_eq!(
+            format!("{:?}", stmts),
+            "[Let { pattern: Ident(\"LIMIT\"), ty: Some(\"u32\"), value: Some(Lit(Int(3))) }, \
+             Let { pattern: Ident(\"NAME\"), ty: Some(\"&str\"), value: Some(Lit(Str(\"n\"))) }]"
+        );
+    }
+
+    #[test]
+    fn parses_functions_skipping_generics_and_types() {
+        let stmts = parse(
+            "pub(crate) fn add<T: Into<f64>>(a: T, (b, c): (f64, f64)) -> f64 where T: Copy { a.into() + b }",
+            1,
+        )
+        .unwrap();
+        let [Stmt::Fn(function)] = stmts.as_slice() else {
+            panic!("expected a function, found {:?}", stmts);
+        };
+        assert_eq!(function.name, "add");
+        assert_eq!(
+            format!("{:?}", function.params),
+            "[Ident(\"a\"), Tuple([Ident(\"b\"), Ident(\"c\")])]"
+        );
+        assert!(function.body.stmts.is_empty());
+        assert_eq!(
+            format!("{:?}", function.body.tail),
+            "Some(Binary(Add, MethodCall { receiver: Path(\"a\"), method: \"into\", turbofish: None, args: [] }, Path(\"b\")))"
+        );
+    }
+
+    #[test]
+    fn rejects_methods_taking_self() {
+        assert_eq!(
+            parse("fn len(&self) -> usize { 0 }", 2).unwrap_err(),
+            "A `self` parameter is not supported, on line 2"
+        );
+    }
+
+    #[test]
+    fn skips_items_other_than_functions_and_attributes() {
+        let stmts = parse(
+            "#![allow(unused)]\n\
+             use std::fmt;\n\
+             #[derive(Debug)]\n\
+             struct Point { x: f64, y: f64 }\n\
+             enum Side { Left, Right }\n\
+             impl Point { fn new() -> Self { Point { x: 0.0, y: 0.0 } } }\n\
+             type Pair = (u8, u8);\n\
+             1",
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            format!("{:?}", stmts),
+            "[Item, Item, Item, Item, Item, Expr(Lit(Int(1)))]"
+        );
+    }
+
+    #[test]
+    fn takes_a_trailing_expression_as_the_value_of_a_block() {
+        assert_eq!(
+            tree("{ let a = 1; a }"),
+            "Block(Block { stmts: [Let { pattern: Ident(\"a\"), ty: None, value: Some(Lit(Int(1))) }], tail: Some(Path(\"a\")) })"
+        );
+        assert_eq!(
+            tree("{ a; }"),
+            "Block(Block { stmts: [Expr(Path(\"a\"))], tail: None })"
+        );
+        assert_eq!(
+            tree("unsafe { 1 }"),
+            "Block(Block { stmts: [], tail: Some(Lit(Int(1))) })"
+        );
+    }
+
+    #[test]
+    fn needs_no_semicolon_after_block_like_expressions() {
+        let stmts = parse(
+            "if a { b(); } else if c { d(); } else { e(); }\n\
+             while x { }\n\
+             loop { break; }\n\
+             for i in 0..3 { }\n\
+             { }\n\
+             f()",
+            1,
+        )
+        .unwrap();
+        let kinds: Vec<_> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Expr(Expr::If(..)) => "if",
+                Stmt::Expr(Expr::While(..)) => "while",
+                Stmt::Expr(Expr::Loop(_)) => "loop",
+                Stmt::Expr(Expr::For(..)) => "for",
+                Stmt::Expr(Expr::Block(_)) => "block",
+                Stmt::Expr(Expr::Call(..)) => "call",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, ["if", "while", "loop", "for", "block", "call"]);
+        assert_eq!(
+            parse("a b", 5).unwrap_err(),
+            "Expected `;`, found `b` on line 5"
+        );
+    }
+
+    #[test]
+    fn ends_statements_at_a_leading_block() {
+        let stmts = parse("if a { } -1", 1).unwrap();
+        assert_eq!(
+            format!("{:?}", stmts),
+            "[Expr(If(Path(\"a\"), Block { stmts: [], tail: None }, None)), Expr(Unary(Neg, Lit(Int(1))))]"
+        );
+        assert_eq!(
+            tree("{ { 1 } (2) }"),
+            "Block(Block { stmts: [Expr(Block(Block { stmts: [], tail: Some(Lit(Int(1))) }))], tail: Some(Lit(Int(2))) })"
+        );
+        // Unless a method is called on its value
+        assert_eq!(
+            tree("if a { 1 } else { 2 }.max(3) * 2"),
+            "Binary(Mul, MethodCall { receiver: If(Path(\"a\"), Block { stmts: [], tail: Some(Lit(Int(1))) }, \
+             Some(Block(Block { stmts: [], tail: Some(Lit(Int(2))) }))), method: \"max\", turbofish: None, args: [Lit(Int(3))] }, Lit(Int(2)))"
+        );
+        // Not where it is a value
+        let stmts = parse("let a = if b { 1 } else { 2 } - 1;", 1).unwrap();
+        assert_eq!(
+            format!("{:?}", stmts),
+            "[Let { pattern: Ident(\"a\"), ty: None, value: Some(Binary(Sub, If(Path(\"b\"), \
+             Block { stmts: [], tail: Some(Lit(Int(1))) }, Some(Block(Block { stmts: [], tail: Some(Lit(Int(2))) }))), \
+             Lit(Int(1)))) }]"
+        );
+    }
+
+    #[test]
+    fn parses_if_else_chains() {
+        assert_eq!(
+            tree("if a { 1 } else if b { 2 } else { 3 }"),
+            "If(Path(\"a\"), Block { stmts: [], tail: Some(Lit(Int(1))) }, \
+             Some(If(Path(\"b\"), Block { stmts: [], tail: Some(Lit(Int(2))) }, \
+             Some(Block(Block { stmts: [], tail: Some(Lit(Int(3))) })))))"
+        );
+    }
+
+    #[test]
+    fn parses_loops_with_break_values_and_returns() {
+        assert_eq!(
+            tree("loop { break 1 }"),
+            "Loop(Block { stmts: [], tail: Some(Break(Some(Lit(Int(1))))) })"
+        );
+        assert_eq!(
+            tree("for (i, x) in v.iter().enumerate() { continue; }"),
+            "For(Tuple([Ident(\"i\"), Ident(\"x\")]), \
+             MethodCall { receiver: MethodCall { receiver: Path(\"v\"), method: \"iter\", turbofish: None, args: [] }, method: \"enumerate\", turbofish: None, args: [] }, \
+             Block { stmts: [Expr(Continue)], tail: None })"
+        );
+        assert_eq!(
+            tree("while i < 3 { return; }"),
+            "While(Binary(Lt, Path(\"i\"), Lit(Int(3))), Block { stmts: [Expr(Return(None))], tail: None })"
+        );
+        assert_eq!(
+            tree("return a + 1"),
+            "Return(Some(Binary(Add, Path(\"a\"), Lit(Int(1)))))"
+        );
+        assert_eq!(
+            tree("f(break, return)"),
+            "Call(Path(\"f\"), [Break(None), Return(None)])"
+        );
+    }
+
+    #[test]
+    fn parses_closures() {
+        assert_eq!(
+            tree("|| 1"),
+            "Closure(Closure { params: [], body: Lit(Int(1)) })"
+        );
+        assert_eq!(
+            tree("move |a: f64, (b, _)| a + b"),
+            "Closure(Closure { params: [Ident(\"a\"), Tuple([Ident(\"b\"), Wild])], body: Binary(Add, Path(\"a\"), Path(\"b\")) })"
+        );
+        assert_eq!(
+            tree("|&x| -> i32 { x }"),
+            "Closure(Closure { params: [Ident(\"x\")], body: Block(Block { stmts: [], tail: Some(Path(\"x\")) }) })"
+        );
+    }
+
+    #[test]
+    fn parses_paths_and_skips_turbofishes_in_them() {
+        assert_eq!(tree("f64::consts::PI"), "Path(\"f64::consts::PI\")");
+        assert_eq!(tree("Vec::<u8>::new()"), "Call(Path(\"Vec::new\"), [])");
+        assert_eq!(
+            tree("std::cmp::max(a, b)"),
+            "Call(Path(\"std::cmp::max\"), [Path(\"a\"), Path(\"b\")])"
+        );
+    }
+
+    #[test]
+    fn keeps_the_turbofish_of_method_calls() {
+        assert_eq!(
+            tree("s.parse::<f64>()"),
+            "MethodCall { receiver: Path(\"s\"), method: \"parse\", turbofish: Some(\"f64\"), args: [] }"
+        );
+        assert_eq!(
+            tree("v.iter().collect::<Vec<_>>()"),
+            "MethodCall { receiver: MethodCall { receiver: Path(\"v\"), method: \"iter\", turbofish: None, args: [] }, method: \"collect\", turbofish: Some(\"Vec<_>\"), args: [] }"
+        );
+    }
+
+    #[test]
+    fn parses_tuples_fields_and_parentheses() {
+        assert_eq!(tree("()"), "Tuple([])");
+        assert_eq!(tree("(1)"), "Lit(Int(1))");
+        assert_eq!(tree("(1,)"), "Tuple([Lit(Int(1))])");
+        assert_eq!(
+            tree("(a, b).1"),
+            "Field(Tuple([Path(\"a\"), Path(\"b\")]), 1)"
+        );
+        assert_eq!(
+            parse("t.0.1", 4).unwrap_err(),
+            "A nested tuple field is not supported, on line 4"
+        );
+    }
+
+    #[test]
+    fn parses_arrays_and_repeats_in_every_bracket_vec_takes() {
+        assert_eq!(tree("[]"), "Array([])");
+        assert_eq!(tree("[1, 2,]"), "Array([Lit(Int(1)), Lit(Int(2))])");
+        assert_eq!(tree("[0.0; n]"), "Repeat(Lit(Float(0.0)), Path(\"n\"))");
+        assert_eq!(tree("vec![1, 2]"), "Array([Lit(Int(1)), Lit(Int(2))])");
+        assert_eq!(tree("vec!(1; 3)"), "Repeat(Lit(Int(1)), Lit(Int(3)))");
+        assert_eq!(tree("vec!{}"), "Array([])");
+    }
+
+    #[test]
+    fn parses_macros_with_their_arguments() {
+        assert_eq!(
+            tree("println!(\"{} {x:>4}\", a)"),
+            "Macro(\"println\", [Lit(Str(\"{} {x:>4}\")), Path(\"a\")])"
+        );
+        assert_eq!(
+            tree("assert_eq![a, 1]"),
+            "Macro(\"assert_eq\", [Path(\"a\"), Lit(Int(1))])"
+        );
+        assert_eq!(tree("todo!{}"), "Macro(\"todo\", [])");
+    }
+
+    #[test]
+    fn reports_rust_it_does_not_run() {
+        for (source, expected) in [
+            ("match x { _ => 1 }", "`match` is not supported, on line 7"),
+            (
+                "if let Some(x) = y { }",
+                "`if let` is not supported, on line 7",
+            ),
+            (
+                "while let Some(x) = y { }",
+                "`while let` is not supported, on line 7",
+            ),
+            ("f()?", "The `?` operator is not supported, on line 7"),
+            ("f().await", "`.await` is not supported, on line 7"),
+            (
+                "point.x",
+                "Field \"x\" of a struct is not supported, on line 7",
+            ),
+        ] {
+            assert_eq!(parse(source, 7).unwrap_err(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn reports_malformed_code_with_its_line() {
+        for (source, expected) in [
+            ("let a = 1", "Expected `;`, found the end of the code"),
+            ("f(1, 2", "Expected `)`, found the end of the code"),
+            ("{ let a = 1;", "Expected `}`, found the end of the code"),
+            ("\n\nlet = 1;", "Expected a name, found `=` on line 3"),
+            (
+                "a.",
+                "Expected a method or field, found the end of the code",
+            ),
+            ("for x v { }", "Expected `in`, found `v` on line 1"),
+            ("\"open", "Unterminated string on line 1"),
+            ("a\n€", "Unexpected '€' on line 2"),
+            ("0xZZ", "cannot parse integer from empty string on line 1"),
+            ("struct S", "Expected `;` or `{`, found the end of the code"),
+        ] {
+            assert_eq!(parse(source, 1).unwrap_err(), expected, "{}", source);
+        }
+    }
+}
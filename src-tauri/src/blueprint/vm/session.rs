@@ -0,0 +1,406 @@
+use super::{
+    ast::Stmt,
+    error::{RunError, RunResult},
+    eval::{Halt, Interpreter, STACK_SIZE},
+    parse::parse,
+    value::Value,
+};
+use crate::blueprint::{Compilation, NodeId};
+use crossbeam_channel::{Receiver, Sender};
+use indexmap::IndexMap;
+use log::debug;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+/// Name of the Tauri event runs report `RunEvent`s on, with a
+/// `RunEventPayload`.
+pub const BLUEPRINT_RUN_EVENT: &str = "blueprint-run";
+
+pub type RunId = u64;
+
+/// How a run starts.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RunOptions {
+    /// Nodes to pause before.
+    pub breakpoints: HashSet<NodeId>,
+    /// Pause before the first node, to step through the graph from there.
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseReason {
+    Breakpoint,
+    /// The previous node was stepped over, or the run started paused.
+    Step,
+    /// `BlueprintRun::pause` was called.
+    Request,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RunEvent {
+    /// A node ran, and its outputs hold these values, by pin.
+    Values {
+        node: NodeId,
+        values: IndexMap<String, Value>,
+    },
+    /// The run paused before a node.
+    Paused {
+        node: NodeId,
+        reason: PauseReason,
+    },
+    Resumed,
+    /// A node printed text.
+    Output {
+        node: NodeId,
+        text: String,
+    },
+    /// Every node ran, or one returned from `main`.
+    Finished,
+    /// A node failed, like Rust code panicking, and the run ended.
+    Failed {
+        node: NodeId,
+        message: String,
+    },
+    /// The run was stopped before it finished.
+    Stopped,
+}
+
+impl RunEvent {
+    /// Whether the run ends with this event.
+    pub fn is_last(&self) -> bool {
+        matches!(
+            self,
+            RunEvent::Finished | RunEvent::Failed { .. } | RunEvent::Stopped
+        )
+    }
+}
+
+/// Payload of `BLUEPRINT_RUN_EVENT`: the event and the run it concerns.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEventPayload {
+    pub run: RunId,
+    #[serde(flatten)]
+    pub event: RunEvent,
+}
+
+/// Callback runs report `RunEventPayload`s through.
+pub type RunSink = Arc<dyn Fn(RunEventPayload) + Send + Sync>;
+
+/// The code of one node, ready to run.
+struct Step {
+    node: NodeId,
+    stmts: Vec<Stmt>,
+    /// Variables holding the values of the node's outputs, by pin.
+    outputs: IndexMap<String, String>,
+}
+
+/// A compiled graph split back into the code of its nodes, so a run can
+/// pause between them.
+pub struct Program {
+    steps: Vec<Step>,
+}
+
+impl Program {
+    /// Parses the code of every node of a graph that compiled without
+    /// errors. Fails on the first node using Rust the interpreter does not
+    /// support.
+    pub fn new(compilation: &Compilation) -> RunResult<Self> {
+        let Some(code) = compilation.code.as_deref() else {
+            return Err(RunError::Compile);
+        };
+        let lines: Vec<&str> = code.lines().collect();
+        let steps = compilation
+            .spans
+            .iter()
+            .map(|span| {
+                let source = lines[span.first_line - 1..span.last_line].join("\n");
+                let stmts =
+                    parse(&source, span.first_line).map_err(|message| RunError::Unsupported {
+                        node: span.node.clone(),
+                        message,
+                    })?;
+                Ok(Step {
+                    node: span.node.clone(),
+                    stmts,
+                    outputs: span.outputs.clone(),
+                })
+            })
+            .collect::<RunResult<_>>()?;
+        Ok(Self { steps })
+    }
+}
+
+enum Control {
+    Continue,
+    Step,
+    Pause,
+    Stop,
+    SetBreakpoints(HashSet<NodeId>),
+}
+
+/// A graph running node by node on a thread of its own. Dropping the run
+/// stops it.
+pub struct BlueprintRun {
+    control: Sender<Control>,
+    stop: Arc<AtomicBool>,
+}
+
+impl BlueprintRun {
+    /// Starts running `program`. `on_event` runs on the run's thread, one of
+    /// `Finished`, `Failed` and `Stopped` being the last event.
+    pub fn spawn(
+        id: RunId,
+        program: Program,
+        options: RunOptions,
+        on_event: impl Fn(RunEvent) + Send + Sync + 'static,
+    ) -> RunResult<Self> {
+        let (control, receiver) = crossbeam_channel::unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let runner = Runner {
+            on_event: Arc::new(on_event),
+            receiver,
+            breakpoints: options.breakpoints,
+            pause: options.paused.then_some(PauseReason::Step),
+            stop: stop.clone(),
+        };
+        thread::Builder::new()
+            .name(format!("blueprint-run-{}", id))
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                let mut runner = runner;
+                let event = runner.run(program);
+                debug!("Blueprint run {} ended with {:?}", id, event);
+                if let Some(event) = event {
+                    (runner.on_event)(event);
+                }
+            })?;
+        Ok(Self { control, stop })
+    }
+
+    /// Resumes a paused run until the next breakpoint.
+    pub fn resume(&self) {
+        let _ = self.control.send(Control::Continue);
+    }
+
+    /// Runs the node a run paused before, and pauses again before the next.
+    pub fn step(&self) {
+        let _ = self.control.send(Control::Step);
+    }
+
+    /// Pauses before the next node, once the running one is done.
+    pub fn pause(&self) {
+        let _ = self.control.send(Control::Pause);
+    }
+
+    pub fn set_breakpoints(&self, breakpoints: HashSet<NodeId>) {
+        let _ = self.control.send(Control::SetBreakpoints(breakpoints));
+    }
+
+    /// Stops the run, interrupting the running node.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.control.send(Control::Stop);
+    }
+}
+
+impl Drop for BlueprintRun {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+type EventFn = Arc<dyn Fn(RunEvent) + Send + Sync>;
+
+/// State of the run's thread.
+struct Runner {
+    on_event: EventFn,
+    receiver: Receiver<Control>,
+    breakpoints: HashSet<NodeId>,
+    /// Why to pause before the next node, if the run should.
+    pause: Option<PauseReason>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Runner {
+    /// Runs the program, returning the event it ended with, or `None` if
+    /// the run was dropped while paused.
+    fn run(&mut self, program: Program) -> Option<RunEvent> {
+        let current = Arc::new(Mutex::new(NodeId::new()));
+        let mut interpreter = {
+            let current = current.clone();
+            let on_event = self.on_event.clone();
+            Interpreter::new(self.stop.clone(), move |text| {
+                let node = current.lock().clone();
+                on_event(RunEvent::Output { node, text });
+            })
+        };
+        for step in &program.steps {
+            for stmt in &step.stmts {
+                if let Stmt::Fn(function) = stmt {
+                    interpreter.define(function.clone());
+                }
+            }
+        }
+
+        for step in &program.steps {
+            while let Ok(control) = self.receiver.try_recv() {
+                self.apply(control);
+            }
+            if self.stop.load(Ordering::Relaxed) {
+                return Some(RunEvent::Stopped);
+            }
+            let reason = match self.pause {
+                Some(reason) => Some(reason),
+                None if self.breakpoints.contains(&step.node) => Some(PauseReason::Breakpoint),
+                None => None,
+            };
+            if let Some(reason) = reason {
+                (self.on_event)(RunEvent::Paused {
+                    node: step.node.clone(),
+                    reason,
+                });
+                self.wait()?;
+                if self.stop.load(Ordering::Relaxed) {
+                    return Some(RunEvent::Stopped);
+                }
+                (self.on_event)(RunEvent::Resumed);
+            }
+
+            *current.lock() = step.node.clone();
+            let returned = match interpreter.run(&step.stmts) {
+                Ok(returned) => returned,
+                Err(Halt::Stopped) => return Some(RunEvent::Stopped),
+                Err(Halt::Error(message)) => {
+                    return Some(RunEvent::Failed {
+                        node: step.node.clone(),
+                        message,
+                    })
+                }
+            };
+            let values = step
+                .outputs
+                .iter()
+                .filter_map(|(pin, variable)| {
+                    let value = interpreter.variable(variable)?;
+                    Some((pin.clone(), value.clone()))
+                })
+                .collect();
+            (self.on_event)(RunEvent::Values {
+                node: step.node.clone(),
+                values,
+            });
+            if returned {
+                break;
+            }
+        }
+        Some(RunEvent::Finished)
+    }
+
+    fn apply(&mut self, control: Control) {
+        match control {
+            Control::Pause => self.pause = Some(PauseReason::Request),
+            Control::SetBreakpoints(breakpoints) => self.breakpoints = breakpoints,
+            // Only meaningful while paused
+            Control::Continue | Control::Step | Control::Stop => {}
+        }
+    }
+
+    /// Blocks until the run is resumed or stopped. Returns `None` if the
+    /// run was dropped.
+    fn wait(&mut self) -> Option<()> {
+        loop {
+            match self.receiver.recv().ok()? {
+                Control::Continue => self.pause = None,
+                Control::Step => self.pause = Some(PauseReason::Step),
+                Control::Stop => {}
+                control => {
+                    self.apply(control);
+                    continue;
+                }
+            }
+            return Some(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blueprint::{compile, Graph};
+    use std::{fs, path::Path, time::Duration};
+
+    /// Runs `tests/fixtures/blueprint/<name>.json` to its end, returning its
+    /// events.
+    fn run_fixture(name: &str) -> Vec<RunEvent> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/blueprint")
+            .join(name)
+            .with_extension("json");
+        let graph: Graph = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let program = Program::new(&compile(&graph)).unwrap();
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let _run = BlueprintRun::spawn(0, program, RunOptions::default(), move |event| {
+            let _ = sender.send(event);
+        })
+        .unwrap();
+        let mut events = Vec::new();
+        loop {
+            let event = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+            let last = event.is_last();
+            events.push(event);
+            if last {
+                return events;
+            }
+        }
+    }
+
+    fn output(events: &[RunEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                RunEvent::Output { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn values(events: &[RunEvent], node: &str) -> String {
+        events
+            .iter()
+            .find_map(|event| match event {
+                RunEvent::Values { node: id, values } if id == node => {
+                    Some(serde_json::to_string(values).unwrap())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn runs_compiled_graphs_like_the_built_program() {
+        // What the snapshots `widening.rs` and `generics.rs` print when built
+        let events = run_fixture("widening");
+        assert_eq!(output(&events), "7.5\n");
+        assert_eq!(values(&events, "total"), r#"{"out":7.5}"#);
+        assert!(matches!(events.last(), Some(RunEvent::Finished)));
+
+        let events = run_fixture("generics");
+        assert_eq!(output(&events), "7\n1.0\n");
+        assert_eq!(values(&events, "list"), r#"{"list":[7,9]}"#);
+        assert_eq!(values(&events, "pair"), r#"{"left":7,"right":0.5}"#);
+        assert!(matches!(events.last(), Some(RunEvent::Finished)));
+    }
+}
@@ -0,0 +1,118 @@
+use super::ast::Closure;
+use serde::{Serialize, Serializer};
+use std::{fmt, sync::Arc};
+
+/// A value of a running graph.
+///
+/// Integers of every width are `Int` and floats `Float`; the compiler has
+/// checked the types, so the interpreter only tells numbers apart where
+/// Rust would behave differently, like integer division.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    List(Vec<Value>),
+    Tuple(Vec<Value>),
+    /// `start..end`, the end excluded.
+    Range(i64, i64),
+    Option(Option<Box<Value>>),
+    Result(Result<Box<Value>, Box<Value>>),
+    /// A closure and the variables it captured.
+    Closure(Arc<Closure>, Arc<Vec<(String, Value)>>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "()",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Str(_) => "String",
+            Value::List(_) => "Vec",
+            Value::Tuple(_) => "tuple",
+            Value::Range(..) => "range",
+            Value::Option(_) => "Option",
+            Value::Result(_) => "Result",
+            Value::Closure(..) => "closure",
+        }
+    }
+
+    /// Formats the value like `{:?}` does.
+    pub fn debug(&self) -> String {
+        match self {
+            Value::Str(text) => format!("{:?}", text),
+            Value::Float(number) => format!("{:?}", number),
+            Value::List(items) => format!("[{}]", join(items, Value::debug)),
+            Value::Tuple(items) if items.len() == 1 => format!("({},)", items[0].debug()),
+            Value::Tuple(items) => format!("({})", join(items, Value::debug)),
+            Value::Option(Some(value)) => format!("Some({})", value.debug()),
+            Value::Option(None) => "None".to_string(),
+            Value::Result(Ok(value)) => format!("Ok({})", value.debug()),
+            Value::Result(Err(error)) => format!("Err({})", error.debug()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+fn join(items: &[Value], format: fn(&Value) -> String) -> String {
+    items.iter().map(format).collect::<Vec<_>>().join(", ")
+}
+
+/// Formats the value like `{}` does. Values Rust cannot display, like a
+/// `Vec`, are formatted like `{:?}` does instead.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Str(text) => write!(f, "{}", text),
+            Value::Range(start, end) => write!(f, "{}..{}", start, end),
+            Value::Closure(..) => write!(f, "<closure>"),
+            _ => write!(f, "{}", self.debug()),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Unit, Value::Unit) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => *a as f64 == *b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::List(a), Value::List(b)) | (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::Range(a, b), Value::Range(c, d)) => a == c && b == d,
+            (Value::Option(a), Value::Option(b)) => a == b,
+            (Value::Result(a), Value::Result(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Serialized as the JSON closest to the value, for the editor to show on
+/// the node: `()` and `None` as `null`, collections as arrays and what has
+/// no JSON counterpart as text.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Unit | Value::Option(None) => serializer.serialize_unit(),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Int(value) => serializer.serialize_i64(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::Str(text) => serializer.serialize_str(text),
+            Value::List(items) | Value::Tuple(items) => serializer.collect_seq(items),
+            Value::Option(Some(value)) => value.serialize(serializer),
+            Value::Range(..) | Value::Result(_) | Value::Closure(..) => {
+                serializer.collect_str(self)
+            }
+        }
+    }
+}
@@ -1,111 +1,211 @@
-use crate::viewport::state::{ViewportState, ViewportConfig, ViewportCommand, VulkanHandle};
-use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle};
-use crate::viewport::vulkan::VulkanState;
-use tao::window::Window as TaoWindow;
-use tauri::{State, Window, Manager};
-use parking_lot::RwLock;
-use log::{info, debug};
-use std::sync::{atomic::AtomicBool, Arc};
+pub mod assets;
+pub mod behavior;
+pub mod blueprint;
+pub mod logging;
+pub mod project;
+pub mod scene;
+pub mod terminal;
+
+use crate::scene::SceneState;
+use crate::viewport::state::{ViewportState, ViewportConfig, ViewportCommand, ViewportId};
+use crate::viewport::error::ViewportResult;
+use crate::viewport::{pacing::FramePacing, policy::DisplaySettings, stats::FrameStatsSnapshot};
+use crate::viewport::{handle::WindowHandle, wgpu_backend::WgpuBackend};
+use crate::viewport::events::{EventSink, ViewportEvent, ViewportEventPayload, VIEWPORT_EVENT};
+#[cfg(debug_assertions)]
+use crate::viewport::fault::Fault;
+use crate::viewport::adapter::{self, AdapterDescriptor, AdapterPreference};
+use crate::viewport::gpu::GpuContext;
+use serde::Serialize;
+use tauri::{State, Window};
+use log::{info, debug, error};
+#[cfg(debug_assertions)]
+use log::warn;
+use std::sync::Arc;
 
 #[tauri::command]
 pub async fn initialize_viewport(
     window: Window,
     state: State<'_, ViewportState>,
-) -> Result<(), String> {
-    info!("Initializing viewport");
-    
-    // Get the raw window handle
-    let raw_window_handle = window.raw_window_handle();
-    
-    // Create platform-agnostic window handle based on the raw handle
-    let vulkan_handle = match raw_window_handle {
-        #[cfg(target_os = "windows")]
-        RawWindowHandle::Win32(handle) => VulkanHandle {
-            hwnd: handle.hwnd as u64,
-            is_active: Arc::new(AtomicBool::new(true)),
-        },
-        #[cfg(target_os = "linux")]
-        RawWindowHandle::Xlib(handle) => VulkanHandle {
-            xlib_window: handle.window as u64,
-            is_active: Arc::new(AtomicBool::new(true)),
-        },
-        #[cfg(target_os = "macos")]
-        RawWindowHandle::AppKit(handle) => VulkanHandle {
-            ns_view: handle.ns_view as u64,
-            is_active: Arc::new(AtomicBool::new(true)),
-        },
-        _ => return Err("Unsupported window system".to_string()),
-    };
-    
-    // Store the handle
-    state.set_vulkan_handle(vulkan_handle);
+    scene: State<'_, SceneState>,
+    viewport_id: ViewportId,
+) -> ViewportResult<()> {
+    info!("Initializing viewport '{}'", viewport_id);
 
-    // Create a thread-safe, nullable Vulkan state
-    let vulkan_state = Arc::new(RwLock::new(None));
+    // Translate the Tauri window into handles wgpu can create a surface on
+    let window_handle = WindowHandle::from_window(&window)?;
 
-    // Create a custom wrapper that implements the required traits
-    struct WindowHandleWrapper {
-        raw_handle: RawWindowHandle,
-    }
+    // Size the swapchain to the window until the frontend reports the viewport rect
+    let size = window.inner_size()?;
 
-    unsafe impl HasRawWindowHandle for WindowHandleWrapper {
-        fn raw_window_handle(&self) -> RawWindowHandle {
-            self.raw_handle
-        }
-    }
-
-    unsafe impl HasRawDisplayHandle for WindowHandleWrapper {
-        fn raw_display_handle(&self) -> RawDisplayHandle {
-            #[cfg(target_os = "windows")]
-            return RawDisplayHandle::Windows(raw_window_handle::WindowsDisplayHandle::empty());
-            #[cfg(target_os = "linux")]
-            return RawDisplayHandle::Xlib(raw_window_handle::XlibDisplayHandle::empty());
-            #[cfg(target_os = "macos")]
-            return RawDisplayHandle::AppKit(raw_window_handle::AppKitDisplayHandle::empty());
-        }
-    }
+    // Tear down a previous viewport with this id before creating a new surface
+    let viewport = state.create(&viewport_id, window.label())?;
 
-    let handle_wrapper = WindowHandleWrapper {
-        raw_handle: raw_window_handle,
-    };
+    // Store the handle
+    viewport.set_window_handle(window_handle);
 
-    // Attempt to create Vulkan state
-    match VulkanState::new(&handle_wrapper) {
-        Ok(state_instance) => {
-            *vulkan_state.write() = Some(state_instance);
-        },
+    // Attempt to create the wgpu backend
+    let policy = (&*state.display_settings.read()).into();
+    let preference = state.adapter_preference.read().clone();
+    let backend = match WgpuBackend::for_window(&state.gpu, &window_handle, size.width, size.height, policy, preference) {
+        Ok(wgpu_backend) => wgpu_backend.with_fault_injector(viewport.fault_injector.clone()),
         Err(e) => {
-            return Err(format!("Failed to initialize Vulkan state: {}", e));
+            error!("Failed to initialize wgpu viewport '{}': {}", viewport_id, e);
+            state.remove(&viewport_id);
+            return Err(e);
         }
-    }
+    };
 
-    // Start the render loop
-    let running = state.running.clone();
-    let receiver = state.command_receiver.clone();
-    let render_thread = crate::viewport::renderer::RenderLoop::start(
-        running, 
-        receiver, 
-        vulkan_state.clone(),
-    );
-    
-    state.set_render_thread(render_thread);
+    // Forward swapchain and device recovery to the frontend
+    let events: EventSink = Arc::new(move |event: ViewportEvent| {
+        let payload = ViewportEventPayload { viewport_id: viewport_id.clone(), event };
+        if let Err(e) = window.emit(VIEWPORT_EVENT, payload) {
+            debug!("Failed to emit viewport event: {}", e);
+        }
+    });
 
-    Ok(())
+    // Start the render loop
+    state.start(&viewport, Box::new(backend), scene.world.clone(), events)
 }
 
 #[tauri::command]
 pub async fn update_native_viewport(
     state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
     config: ViewportConfig,
-) -> Result<(), String> {
-    debug!("Updating viewport: {:?}", config);
-    state.send_command(ViewportCommand::UpdateConfig(config))
+) -> ViewportResult<()> {
+    debug!("Updating viewport '{}': {:?}", viewport_id, config);
+    state.get(&viewport_id)?.resize(config)
 }
 
 #[tauri::command]
 pub async fn reset_viewport_camera(
     state: State<'_, ViewportState>,
-) -> Result<(), String> {
-    debug!("Resetting viewport camera");
-    state.send_command(ViewportCommand::ResetCamera)
-}
\ No newline at end of file
+    viewport_id: ViewportId,
+) -> ViewportResult<()> {
+    debug!("Resetting camera of viewport '{}'", viewport_id);
+    state.get(&viewport_id)?.send_command(ViewportCommand::ResetCamera)
+}
+
+#[tauri::command]
+pub async fn orbit_viewport_camera(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+    delta_yaw: f32,
+    delta_pitch: f32,
+) -> ViewportResult<()> {
+    state.get(&viewport_id)?.send_command(ViewportCommand::OrbitCamera { delta_yaw, delta_pitch })
+}
+
+#[tauri::command]
+pub async fn pan_viewport_camera(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+    dx: f32,
+    dy: f32,
+) -> ViewportResult<()> {
+    state.get(&viewport_id)?.send_command(ViewportCommand::PanCamera { dx, dy })
+}
+
+#[tauri::command]
+pub async fn zoom_viewport_camera(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+    factor: f32,
+) -> ViewportResult<()> {
+    state.get(&viewport_id)?.send_command(ViewportCommand::ZoomCamera { factor })
+}
+
+#[tauri::command]
+pub async fn shutdown_viewport(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+) -> ViewportResult<()> {
+    info!("Shutting down viewport '{}'", viewport_id);
+    state.remove(&viewport_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_viewport_frame_pacing(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+    pacing: FramePacing,
+) -> ViewportResult<()> {
+    debug!("Setting frame pacing of viewport '{}': {:?}", viewport_id, pacing);
+    pacing.validate()?;
+    state.get(&viewport_id)?.send_command(ViewportCommand::SetFramePacing(pacing))
+}
+
+#[tauri::command]
+pub async fn get_viewport_frame_stats(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+) -> ViewportResult<FrameStatsSnapshot> {
+    Ok(state.get(&viewport_id)?.frame_stats.read().snapshot())
+}
+
+#[tauri::command]
+pub async fn set_viewport_display_settings(
+    state: State<'_, ViewportState>,
+    settings: DisplaySettings,
+) -> ViewportResult<()> {
+    debug!("Setting viewport display settings: {:?}", settings);
+    *state.display_settings.write() = settings.clone();
+    state.broadcast(ViewportCommand::SetDisplaySettings(settings));
+    Ok(())
+}
+
+/// Makes the next frame of a viewport fail with `fault`, to exercise the
+/// recovery paths. Only registered in debug builds.
+#[cfg(debug_assertions)]
+#[tauri::command]
+pub async fn inject_viewport_fault(
+    state: State<'_, ViewportState>,
+    viewport_id: ViewportId,
+    fault: Fault,
+) -> ViewportResult<()> {
+    warn!("Injecting fault into viewport '{}': {:?}", viewport_id, fault);
+    let viewport = state.get(&viewport_id)?;
+    viewport.fault_injector.inject(fault);
+    viewport.send_command(ViewportCommand::RequestRedraw)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphicsAdapter {
+    #[serde(flatten)]
+    pub adapter: AdapterDescriptor,
+    /// Whether the viewports currently render on this adapter.
+    pub active: bool,
+}
+
+/// Lists the adapters the viewport can run on, for the Graphics settings page.
+#[tauri::command]
+pub async fn list_graphics_adapters(
+    state: State<'_, ViewportState>,
+) -> ViewportResult<Vec<GraphicsAdapter>> {
+    let active = state.gpu.current().map(|gpu| gpu.adapter_name());
+
+    let adapters = adapter::list_adapters(&GpuContext::create_instance())
+        .into_iter()
+        .map(|adapter| GraphicsAdapter {
+            active: active.as_deref() == Some(adapter.name.as_str()),
+            adapter,
+        })
+        .collect();
+
+    Ok(adapters)
+}
+
+/// Sets the adapter preference. The shared device moves to the new adapter
+/// once every viewport has been shut down and one is initialized again.
+#[tauri::command]
+pub async fn set_graphics_adapter(
+    state: State<'_, ViewportState>,
+    preference: AdapterPreference,
+) -> ViewportResult<()> {
+    info!("Setting graphics adapter preference: {:?}", preference);
+    *state.adapter_preference.write() = preference;
+    Ok(())
+}
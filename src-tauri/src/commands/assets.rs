@@ -0,0 +1,319 @@
+use super::scene::notify_changed;
+use crate::assets::{
+    database::{AssetChange, DeleteImpact, ImportSettings},
+    error::AssetResult,
+    import::{self, texture::TextureImportSettings},
+    queue::{ImportSink, JobId},
+    watcher::AssetChangedPayload,
+    AssetDatabase, AssetError, AssetRecord, AssetState, AssetWatcher, ModelAsset,
+    ASSET_CHANGED_EVENT, ASSET_IMPORT_EVENT,
+};
+use crate::blueprint::NodeLibraryState;
+use crate::logging::{LogState, PROJECT_LOG_DIR};
+use crate::scene::{EntityId, SceneState};
+use crate::viewport::{state::ViewportCommand, ViewportState};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelImport {
+    /// The written `.model` description.
+    pub model: ModelAsset,
+    /// Directory the model and its parts were written to.
+    pub directory: PathBuf,
+    /// Root entity of the instance added to the scene, if any.
+    pub entity: Option<EntityId>,
+}
+
+/// Imports a glTF or GLB file as engine assets.
+///
+/// The assets go to `output_dir`, by default a directory named after the
+/// source next to it. Unless `instantiate` is false the model's node
+/// hierarchy is also added to the scene under `parent`. If the source is
+/// part of the open project, its `.meta` sidecar records the output so the
+/// model is reimported when the source changes.
+#[tauri::command]
+pub async fn import_model(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    assets: State<'_, AssetState>,
+    source: PathBuf,
+    output_dir: Option<PathBuf>,
+    instantiate: Option<bool>,
+    parent: Option<EntityId>,
+) -> AssetResult<ModelImport> {
+    info!("Importing model {}", source.display());
+    let directory = output_dir.unwrap_or_else(|| default_output_dir(&source));
+    // Paths written into the model and the scene are project relative
+    let (stored_source, stored_directory) = match assets.database.read().as_ref() {
+        Some(database) => (
+            database.stored_path(&source),
+            database.stored_path(&directory),
+        ),
+        None => (source.clone(), directory.clone()),
+    };
+
+    // Parsing and writing large models takes a while, keep it off the async runtime
+    let model = {
+        let (source, directory) = (source.clone(), directory.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            import::import_model(&source)?.write(&stored_source, &directory)
+        })
+        .await
+        .map_err(|e| AssetError::Task(e.to_string()))??
+    };
+
+    if let Some(database) = assets.database.write().as_mut() {
+        let output = directory.join(model.file_name());
+        if let Err(e) = database.record_import(&source, ImportSettings::Model, &output) {
+            debug!("Not recording import of {}: {}", source.display(), e);
+        }
+    }
+
+    let entity = if instantiate.unwrap_or(true) {
+        let mut world = scene.world.write();
+        let entity = model.instantiate(&mut world, &stored_directory, parent)?;
+        notify_changed(&app, &world);
+        Some(entity)
+    } else {
+        None
+    };
+
+    Ok(ModelImport {
+        model,
+        directory,
+        entity,
+    })
+}
+
+/// Queues an import of a PNG, JPEG, TGA or HDR image and returns its job
+/// id right away.
+///
+/// The job reports on `ASSET_IMPORT_EVENT`: progress as it goes, then the
+/// written file and its content hash, or the error. The texture is written
+/// to `output`, by default next to the source with the container's
+/// extension. Without `settings` the ones stored in the source's `.meta`
+/// sidecar are used; given settings are stored there for the next import.
+#[tauri::command]
+pub fn import_texture(
+    app: AppHandle,
+    assets: State<'_, AssetState>,
+    source: PathBuf,
+    output: Option<PathBuf>,
+    settings: Option<TextureImportSettings>,
+) -> AssetResult<JobId> {
+    let settings = settings.unwrap_or_else(|| {
+        let meta = assets
+            .database
+            .read()
+            .as_ref()
+            .and_then(|database| database.meta(&source));
+        match meta.and_then(|meta| meta.import) {
+            Some(ImportSettings::Texture(settings)) => settings,
+            _ => TextureImportSettings::default(),
+        }
+    });
+    let output = output.unwrap_or_else(|| source.with_extension(settings.container.extension()));
+    info!(
+        "Queueing texture import {} -> {}",
+        source.display(),
+        output.display()
+    );
+
+    Ok(import::submit_texture(
+        &assets.queue,
+        &assets.database,
+        import_sink(&app),
+        source,
+        output,
+        settings,
+    ))
+}
+
+/// Opens the project at `root`, scanning it into the asset database, and
+/// watches it for changes. Assets without a `.meta` sidecar get one with a
+/// new GUID. The editor log is written to the project from then on.
+#[tauri::command]
+pub async fn open_project(
+    app: AppHandle,
+    assets: State<'_, AssetState>,
+    logs: State<'_, LogState>,
+    library: State<'_, NodeLibraryState>,
+    root: PathBuf,
+) -> AssetResult<Vec<AssetRecord>> {
+    info!("Opening project {}", root.display());
+    let database = tauri::async_runtime::spawn_blocking(move || AssetDatabase::open(&root))
+        .await
+        .map_err(|e| AssetError::Task(e.to_string()))??;
+
+    let watcher = {
+        let app = app.clone();
+        AssetWatcher::start(database.root(), move |paths| {
+            apply_file_changes(&app, &paths)
+        })?
+    };
+
+    let records = database.assets().into_iter().cloned().collect();
+    let log_dir = database.root().join(PROJECT_LOG_DIR);
+    let project_root = database.root().to_path_buf();
+    // Stop watching the previous project before its database goes away
+    let previous = assets.watcher.lock().take();
+    drop(previous);
+    *assets.database.write() = Some(database);
+    *assets.watcher.lock() = Some(watcher);
+
+    if let Err(e) = logs.set_directory(Some(&log_dir)) {
+        warn!("Failed to write logs to {}: {}", log_dir.display(), e);
+    }
+    // Offer the nodes of the project's plugins
+    library.set_project(Some(&project_root));
+    // Scene meshes are relative to the project
+    let viewports = app.state::<ViewportState>();
+    *viewports.project_root.write() = Some(project_root.clone());
+    viewports.broadcast(ViewportCommand::SetProjectRoot(Some(project_root)));
+    Ok(records)
+}
+
+#[tauri::command]
+pub fn list_assets(assets: State<'_, AssetState>) -> AssetResult<Vec<AssetRecord>> {
+    with_database(&assets, |database| {
+        Ok(database.assets().into_iter().cloned().collect())
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetDependencies {
+    pub dependencies: Vec<AssetRecord>,
+    /// Project relative paths referenced but not found.
+    pub missing: Vec<PathBuf>,
+}
+
+/// The assets `guid` uses.
+#[tauri::command]
+pub fn get_asset_dependencies(
+    assets: State<'_, AssetState>,
+    guid: Uuid,
+) -> AssetResult<AssetDependencies> {
+    with_asset(&assets, guid, |database| {
+        let (dependencies, missing) = database.dependencies(guid);
+        Ok(AssetDependencies {
+            dependencies: dependencies.into_iter().cloned().collect(),
+            missing: missing.into_iter().map(Path::to_path_buf).collect(),
+        })
+    })
+}
+
+/// The assets directly using `guid`.
+#[tauri::command]
+pub fn find_asset_references(
+    assets: State<'_, AssetState>,
+    guid: Uuid,
+) -> AssetResult<Vec<AssetRecord>> {
+    with_asset(&assets, guid, |database| {
+        Ok(database.references_to(guid).into_iter().cloned().collect())
+    })
+}
+
+/// The assets that would be left with broken references if `guid` was
+/// deleted. Nothing is deleted.
+#[tauri::command]
+pub fn get_asset_delete_impact(
+    assets: State<'_, AssetState>,
+    guid: Uuid,
+) -> AssetResult<DeleteImpact> {
+    with_asset(&assets, guid, |database| Ok(database.delete_impact(guid)))
+}
+
+/// Handles files changed on disk: updates the asset database, reimports
+/// changed sources, reloads the viewports and notifies the frontend.
+fn apply_file_changes(app: &AppHandle, paths: &[PathBuf]) {
+    let assets = app.state::<AssetState>();
+    let changes = match assets.database.write().as_mut() {
+        Some(database) => database.apply(paths),
+        None => return,
+    };
+    if changes.is_empty() {
+        return;
+    }
+
+    let root = assets
+        .database
+        .read()
+        .as_ref()
+        .map(|database| database.root().to_path_buf())
+        .unwrap_or_default();
+    app.state::<ViewportState>()
+        .broadcast(ViewportCommand::ReloadAssets(
+            changes
+                .iter()
+                .map(|change| root.join(&change.path))
+                .collect(),
+        ));
+
+    for change in changes {
+        debug!("Asset {} {:?}", change.path.display(), change.change);
+        let reimport = match change.change {
+            AssetChange::Removed => None,
+            _ => import::reimport(
+                &assets.queue,
+                &assets.database,
+                import_sink(app),
+                &root.join(&change.path),
+            ),
+        };
+        let payload = AssetChangedPayload {
+            asset: change,
+            reimport,
+        };
+        if let Err(e) = app.emit_all(ASSET_CHANGED_EVENT, payload) {
+            error!("Failed to emit asset change: {}", e);
+        }
+    }
+}
+
+/// Sink emitting import job events to the frontend.
+fn import_sink(app: &AppHandle) -> ImportSink {
+    let app = app.clone();
+    Arc::new(move |payload| {
+        if let Err(e) = app.emit_all(ASSET_IMPORT_EVENT, payload) {
+            error!("Failed to emit import event: {}", e);
+        }
+    })
+}
+
+fn with_database<T>(
+    assets: &AssetState,
+    f: impl FnOnce(&AssetDatabase) -> AssetResult<T>,
+) -> AssetResult<T> {
+    f(assets
+        .database
+        .read()
+        .as_ref()
+        .ok_or(AssetError::NoProject)?)
+}
+
+fn with_asset<T>(
+    assets: &AssetState,
+    guid: Uuid,
+    f: impl FnOnce(&AssetDatabase) -> AssetResult<T>,
+) -> AssetResult<T> {
+    with_database(assets, |database| {
+        database
+            .get(guid)
+            .ok_or_else(|| AssetError::UnknownAsset(guid.to_string()))?;
+        f(database)
+    })
+}
+
+fn default_output_dir(source: &Path) -> PathBuf {
+    let stem = source.file_stem().unwrap_or_default();
+    source.with_file_name(stem)
+}
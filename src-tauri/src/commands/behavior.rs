@@ -0,0 +1,92 @@
+use crate::behavior::{
+    asset, AgentId, AgentInfo, AgentState, BehaviorAsset, BehaviorLayout, BehaviorResult,
+    TickTrace, TraceSink, BEHAVIOR_DEBUG_EVENT,
+};
+use log::debug;
+use serde::Serialize;
+use std::{path::PathBuf, sync::Arc};
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehaviorDocument {
+    pub asset: BehaviorAsset,
+    /// Where the editor drew the nodes, empty if the tree has no layout file.
+    pub layout: BehaviorLayout,
+}
+
+/// Registered action and condition names leaves can use as handlers.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehaviorHandlers {
+    pub actions: Vec<String>,
+    pub conditions: Vec<String>,
+}
+
+/// Reads the behavior tree saved at `path` and its layout.
+#[tauri::command]
+pub async fn load_behavior_tree(path: PathBuf) -> BehaviorResult<BehaviorDocument> {
+    Ok(BehaviorDocument {
+        asset: asset::load(&path)?,
+        layout: asset::load_layout(&path)?,
+    })
+}
+
+/// Writes a behavior tree to `path` and its layout next to it.
+#[tauri::command]
+pub async fn save_behavior_tree(
+    path: PathBuf,
+    asset: BehaviorAsset,
+    layout: BehaviorLayout,
+) -> BehaviorResult<()> {
+    asset::save(&asset, &layout, &path)
+}
+
+#[tauri::command]
+pub fn list_behavior_handlers(agents: State<'_, AgentState>) -> BehaviorHandlers {
+    let registry = agents.registry();
+    let names = |names: Vec<&str>| names.into_iter().map(String::from).collect();
+    BehaviorHandlers {
+        actions: names(registry.action_names()),
+        conditions: names(registry.condition_names()),
+    }
+}
+
+/// Spawns an agent ticking the behavior tree saved at `path`. While it is
+/// debugged, its ticks are reported on `BEHAVIOR_DEBUG_EVENT`.
+#[tauri::command]
+pub async fn spawn_behavior_agent(
+    app: AppHandle,
+    agents: State<'_, AgentState>,
+    path: PathBuf,
+) -> BehaviorResult<AgentInfo> {
+    let sink: TraceSink = Arc::new(move |trace: TickTrace| {
+        if let Err(e) = app.emit_all(BEHAVIOR_DEBUG_EVENT, trace) {
+            debug!("Failed to emit behavior tree trace: {}", e);
+        }
+    });
+    agents.spawn(&path, sink)
+}
+
+#[tauri::command]
+pub async fn despawn_behavior_agent(
+    agents: State<'_, AgentState>,
+    agent: AgentId,
+) -> BehaviorResult<()> {
+    agents.despawn(agent)
+}
+
+#[tauri::command]
+pub fn list_behavior_agents(agents: State<'_, AgentState>) -> Vec<AgentInfo> {
+    agents.agents()
+}
+
+/// Streams the ticks of an agent on `BEHAVIOR_DEBUG_EVENT`, instead of those
+/// of the agent debugged before, or stops streaming if `agent` is `None`.
+#[tauri::command]
+pub async fn debug_behavior_agent(
+    agents: State<'_, AgentState>,
+    agent: Option<AgentId>,
+) -> BehaviorResult<()> {
+    agents.debug(agent)
+}
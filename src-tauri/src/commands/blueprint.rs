@@ -0,0 +1,96 @@
+use crate::blueprint::{
+    self,
+    vm::{RunEventPayload, RunId, RunOptions, RunResult, RunSink, RunState, BLUEPRINT_RUN_EVENT},
+    CargoCheck, CheckError, CheckResult, CheckState, Compilation, Graph, NodeId, NodeLibrary,
+    NodeLibraryState,
+};
+use log::debug;
+use std::{collections::HashSet, sync::Arc};
+use tauri::{AppHandle, Manager, State};
+
+/// Compiles a Blueprint graph to Rust, with the problems found on its nodes.
+#[tauri::command]
+pub fn compile_blueprint(graph: Graph) -> Compilation {
+    let compilation = blueprint::compile(&graph);
+    debug!(
+        "Compiled Blueprint graph of {} nodes with {} diagnostics",
+        graph.nodes.len(),
+        compilation.diagnostics.len()
+    );
+    compilation
+}
+
+/// Compiles a graph and checks the code with `cargo check`, with rustc's
+/// problems on the nodes whose code they are in.
+#[tauri::command]
+pub async fn check_blueprint(
+    checks: State<'_, CheckState>,
+    graph: Graph,
+) -> CheckResult<CargoCheck> {
+    let checks = checks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || checks.check(&blueprint::compile(&graph)))
+        .await
+        .map_err(|e| CheckError::Task(e.to_string()))?
+}
+
+/// The node definitions the editor offers, with the problems of those that
+/// could not be loaded.
+#[tauri::command]
+pub fn get_node_library(library: State<'_, NodeLibraryState>) -> NodeLibrary {
+    library.library().as_ref().clone()
+}
+
+/// Loads the node library from disk again, as it does when its files change.
+#[tauri::command]
+pub fn reload_node_library(library: State<'_, NodeLibraryState>) -> NodeLibrary {
+    library.reload().as_ref().clone()
+}
+
+/// Starts interpreting a graph in the editor. Its progress is reported on
+/// `BLUEPRINT_RUN_EVENT`; fails if the graph does not compile.
+#[tauri::command]
+pub async fn start_blueprint_run(
+    app: AppHandle,
+    runs: State<'_, RunState>,
+    graph: Graph,
+    options: Option<RunOptions>,
+) -> RunResult<RunId> {
+    let sink: RunSink = Arc::new(move |payload: RunEventPayload| {
+        if let Err(e) = app.emit_all(BLUEPRINT_RUN_EVENT, payload) {
+            debug!("Failed to emit Blueprint run event: {}", e);
+        }
+    });
+    runs.start(&graph, options.unwrap_or_default(), sink)
+}
+
+/// Resumes a paused run until the next breakpoint.
+#[tauri::command]
+pub async fn continue_blueprint_run(runs: State<'_, RunState>, run: RunId) -> RunResult<()> {
+    runs.resume(run)
+}
+
+/// Runs the node a run is paused before, and pauses before the next one.
+#[tauri::command]
+pub async fn step_blueprint_run(runs: State<'_, RunState>, run: RunId) -> RunResult<()> {
+    runs.step(run)
+}
+
+#[tauri::command]
+pub async fn pause_blueprint_run(runs: State<'_, RunState>, run: RunId) -> RunResult<()> {
+    runs.pause(run)
+}
+
+#[tauri::command]
+pub async fn stop_blueprint_run(runs: State<'_, RunState>, run: RunId) -> RunResult<()> {
+    runs.stop(run)
+}
+
+/// Replaces the nodes a run pauses before.
+#[tauri::command]
+pub async fn set_blueprint_breakpoints(
+    runs: State<'_, RunState>,
+    run: RunId,
+    breakpoints: HashSet<NodeId>,
+) -> RunResult<()> {
+    runs.set_breakpoints(run, breakpoints)
+}
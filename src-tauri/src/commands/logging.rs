@@ -0,0 +1,42 @@
+use crate::logging::{LogFilterLevel, LogFilters, LogQuery, LogRecord, LogState};
+use log::info;
+use tauri::State;
+
+/// Returns the logged records matching `query`, oldest first.
+#[tauri::command]
+pub fn get_logs(logs: State<'_, LogState>, query: Option<LogQuery>) -> Vec<LogRecord> {
+    logs.query(&query.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn clear_logs(logs: State<'_, LogState>) {
+    logs.clear();
+}
+
+#[tauri::command]
+pub fn get_log_filters(logs: State<'_, LogState>) -> LogFilters {
+    logs.filters()
+}
+
+/// Sets the level `module` logs at, or the default level without one.
+#[tauri::command]
+pub fn set_log_filter(
+    logs: State<'_, LogState>,
+    module: Option<String>,
+    level: LogFilterLevel,
+) -> LogFilters {
+    info!(
+        "Setting log level of {} to {:?}",
+        module.as_deref().unwrap_or("all modules"),
+        level
+    );
+    logs.set_filter(module.as_deref(), level);
+    logs.filters()
+}
+
+/// Removes the level set for `module`, which then logs like its parent.
+#[tauri::command]
+pub fn remove_log_filter(logs: State<'_, LogState>, module: String) -> LogFilters {
+    logs.remove_filter(&module);
+    logs.filters()
+}
@@ -0,0 +1,115 @@
+use crate::assets::AssetState;
+use crate::project::{FileEntry, ProjectError, ProjectFiles, ProjectResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::{debug, info};
+use tauri::State;
+
+/// Lists the entries of the directory at `path`, relative to the project
+/// root, without descending into subdirectories.
+#[tauri::command]
+pub async fn list_project_dir(
+    assets: State<'_, AssetState>,
+    path: Option<String>,
+) -> ProjectResult<Vec<FileEntry>> {
+    project_files(&assets)?.list(path.as_deref().unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn read_project_file(
+    assets: State<'_, AssetState>,
+    path: String,
+) -> ProjectResult<String> {
+    project_files(&assets)?.read_text(&path)
+}
+
+/// Reads a binary file, base64 encoded.
+#[tauri::command]
+pub async fn read_project_file_binary(
+    assets: State<'_, AssetState>,
+    path: String,
+) -> ProjectResult<String> {
+    let bytes = project_files(&assets)?.read(&path)?;
+    Ok(STANDARD.encode(bytes))
+}
+
+#[tauri::command]
+pub async fn write_project_file(
+    assets: State<'_, AssetState>,
+    path: String,
+    contents: String,
+) -> ProjectResult<FileEntry> {
+    debug!("Writing {}", path);
+    project_files(&assets)?.write(&path, contents.as_bytes())
+}
+
+/// Writes a binary file from base64 encoded `data`.
+#[tauri::command]
+pub async fn write_project_file_binary(
+    assets: State<'_, AssetState>,
+    path: String,
+    data: String,
+) -> ProjectResult<FileEntry> {
+    debug!("Writing {}", path);
+    let bytes = STANDARD.decode(data)?;
+    project_files(&assets)?.write(&path, &bytes)
+}
+
+#[tauri::command]
+pub async fn create_project_file(
+    assets: State<'_, AssetState>,
+    path: String,
+) -> ProjectResult<FileEntry> {
+    info!("Creating file {}", path);
+    project_files(&assets)?.create_file(&path)
+}
+
+#[tauri::command]
+pub async fn create_project_dir(
+    assets: State<'_, AssetState>,
+    path: String,
+) -> ProjectResult<FileEntry> {
+    info!("Creating directory {}", path);
+    project_files(&assets)?.create_dir(&path)
+}
+
+/// Renames an entry within its directory. Its `.meta` sidecar is renamed
+/// with it, so the asset keeps its GUID.
+#[tauri::command]
+pub async fn rename_project_entry(
+    assets: State<'_, AssetState>,
+    path: String,
+    new_name: String,
+) -> ProjectResult<FileEntry> {
+    info!("Renaming {} to {}", path, new_name);
+    project_files(&assets)?.rename(&path, &new_name)
+}
+
+/// Moves an entry into the directory at `destination`, with its sidecar.
+#[tauri::command]
+pub async fn move_project_entry(
+    assets: State<'_, AssetState>,
+    path: String,
+    destination: String,
+) -> ProjectResult<FileEntry> {
+    info!("Moving {} to {}", path, destination);
+    project_files(&assets)?.move_to(&path, &destination)
+}
+
+/// Moves an entry and its sidecar to the system trash, or deletes them
+/// outright if `permanent`.
+#[tauri::command]
+pub async fn delete_project_entry(
+    assets: State<'_, AssetState>,
+    path: String,
+    permanent: Option<bool>,
+) -> ProjectResult<()> {
+    info!("Deleting {}", path);
+    project_files(&assets)?.delete(&path, permanent.unwrap_or(false))
+}
+
+/// Files of the project open in the asset database.
+fn project_files(assets: &AssetState) -> ProjectResult<ProjectFiles> {
+    let database = assets.database.read();
+    let database = database.as_ref().ok_or(ProjectError::NoProject)?;
+    ProjectFiles::new(database.root())
+}
@@ -0,0 +1,185 @@
+use crate::scene::{
+    error::SceneResult,
+    file, starter_world,
+    world::{EntityId, SceneQuery, World},
+    Component, ComponentKind, Entity, SceneState, Transform, SCENE_CHANGED_EVENT,
+};
+use log::{debug, info};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, State};
+
+/// Tells every editor panel that the scene changed.
+pub(crate) fn notify_changed(app: &AppHandle, world: &World) {
+    if let Err(e) = app.emit_all(SCENE_CHANGED_EVENT, world.revision()) {
+        debug!("Failed to emit scene change: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn create_entity(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    name: String,
+    parent: Option<EntityId>,
+    transform: Option<Transform>,
+    components: Option<Vec<Component>>,
+) -> SceneResult<Entity> {
+    debug!("Creating entity '{}' under {:?}", name, parent);
+    let mut world = scene.world.write();
+
+    let id = world.spawn(name, parent)?;
+    if let Some(transform) = transform {
+        world.set_transform(id, transform)?;
+    }
+    for component in components.unwrap_or_default() {
+        world.set_component(id, component)?;
+    }
+
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+/// Deletes an entity and its descendants. Returns the deleted ids.
+#[tauri::command]
+pub async fn delete_entity(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+) -> SceneResult<Vec<EntityId>> {
+    info!("Deleting entity {}", id);
+    let mut world = scene.world.write();
+    let removed = world.despawn(id)?;
+    notify_changed(&app, &world);
+    Ok(removed)
+}
+
+/// Moves an entity under `parent`, or to the top level. Keeps the world
+/// transform unless `keep_world_transform` is false.
+#[tauri::command]
+pub async fn reparent_entity(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+    parent: Option<EntityId>,
+    keep_world_transform: Option<bool>,
+) -> SceneResult<Entity> {
+    debug!("Reparenting entity {} to {:?}", id, parent);
+    let mut world = scene.world.write();
+    world.reparent(id, parent, keep_world_transform.unwrap_or(true))?;
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+#[tauri::command]
+pub async fn set_entity_transform(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+    transform: Transform,
+) -> SceneResult<Entity> {
+    let mut world = scene.world.write();
+    world.set_transform(id, transform)?;
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+#[tauri::command]
+pub async fn rename_entity(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+    name: String,
+) -> SceneResult<Entity> {
+    let mut world = scene.world.write();
+    world.set_name(id, name)?;
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+#[tauri::command]
+pub async fn set_entity_visible(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+    visible: bool,
+) -> SceneResult<Entity> {
+    let mut world = scene.world.write();
+    world.set_visible(id, visible)?;
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+/// Adds a component to an entity, replacing one of the same kind.
+#[tauri::command]
+pub async fn set_entity_component(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+    component: Component,
+) -> SceneResult<Entity> {
+    let mut world = scene.world.write();
+    world.set_component(id, component)?;
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+#[tauri::command]
+pub async fn remove_entity_component(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    id: EntityId,
+    kind: ComponentKind,
+) -> SceneResult<Entity> {
+    let mut world = scene.world.write();
+    world.remove_component(id, kind)?;
+    notify_changed(&app, &world);
+    Ok(world.get(id)?.clone())
+}
+
+#[tauri::command]
+pub async fn get_entity(scene: State<'_, SceneState>, id: EntityId) -> SceneResult<Entity> {
+    Ok(scene.world.read().get(id)?.clone())
+}
+
+/// Entities matching `query` in hierarchy order, parents before children.
+/// Without a query the whole scene is returned.
+#[tauri::command]
+pub async fn query_scene(
+    scene: State<'_, SceneState>,
+    query: Option<SceneQuery>,
+) -> SceneResult<Vec<Entity>> {
+    let world = scene.world.read();
+    let entities = world.query(&query.unwrap_or_default())?;
+    Ok(entities.into_iter().cloned().collect())
+}
+
+/// Replaces the scene with the starter scene.
+#[tauri::command]
+pub async fn new_scene(app: AppHandle, scene: State<'_, SceneState>) -> SceneResult<()> {
+    info!("Starting a new scene");
+    let mut world = scene.world.write();
+    world.replace(starter_world());
+    notify_changed(&app, &world);
+    Ok(())
+}
+
+/// Writes the scene to `path` in the current scene file format.
+#[tauri::command]
+pub async fn save_scene(scene: State<'_, SceneState>, path: PathBuf) -> SceneResult<()> {
+    file::save(&scene.world.read(), &path)
+}
+
+/// Replaces the scene with the one saved at `path`, upgrading files written
+/// by older versions. The current scene is kept if loading fails.
+#[tauri::command]
+pub async fn load_scene(
+    app: AppHandle,
+    scene: State<'_, SceneState>,
+    path: PathBuf,
+) -> SceneResult<()> {
+    let loaded = file::load(&path)?;
+    let mut world = scene.world.write();
+    world.replace(loaded);
+    notify_changed(&app, &world);
+    Ok(())
+}
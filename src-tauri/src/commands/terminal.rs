@@ -0,0 +1,70 @@
+use crate::assets::AssetState;
+use crate::terminal::{
+    SessionId, TerminalEventPayload, TerminalOptions, TerminalResult, TerminalSink, TerminalState,
+    TERMINAL_EVENT,
+};
+use log::debug;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+/// Starts the user's shell in a pseudo-terminal. Its output and exit are
+/// reported on `TERMINAL_EVENT`; the shell starts in the open project unless
+/// `options` name another directory.
+#[tauri::command]
+pub async fn open_terminal(
+    app: AppHandle,
+    terminals: State<'_, TerminalState>,
+    assets: State<'_, AssetState>,
+    options: Option<TerminalOptions>,
+) -> TerminalResult<SessionId> {
+    let mut options = options.unwrap_or_default();
+    if options.cwd.is_none() {
+        options.cwd = assets
+            .database
+            .read()
+            .as_ref()
+            .map(|database| database.root().to_path_buf())
+            .or_else(tauri::api::path::home_dir);
+    }
+
+    let sink: TerminalSink = Arc::new(move |payload: TerminalEventPayload| {
+        if let Err(e) = app.emit_all(TERMINAL_EVENT, payload) {
+            debug!("Failed to emit terminal event: {}", e);
+        }
+    });
+    terminals.open(&options, sink)
+}
+
+/// Sends keys or pasted text to a session.
+#[tauri::command]
+pub async fn write_terminal(
+    terminals: State<'_, TerminalState>,
+    session: SessionId,
+    data: String,
+) -> TerminalResult<()> {
+    terminals.write(session, data.as_bytes())
+}
+
+#[tauri::command]
+pub async fn resize_terminal(
+    terminals: State<'_, TerminalState>,
+    session: SessionId,
+    cols: u16,
+    rows: u16,
+) -> TerminalResult<()> {
+    terminals.resize(session, cols, rows)
+}
+
+/// Kills the shell of a session.
+#[tauri::command]
+pub async fn close_terminal(
+    terminals: State<'_, TerminalState>,
+    session: SessionId,
+) -> TerminalResult<()> {
+    terminals.close(session)
+}
+
+#[tauri::command]
+pub async fn list_terminals(terminals: State<'_, TerminalState>) -> TerminalResult<Vec<SessionId>> {
+    Ok(terminals.sessions())
+}
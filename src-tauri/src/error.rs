@@ -0,0 +1,94 @@
+/// Implements `Serialize` for an error type with a `code()` method, so the
+/// frontend receives `{ code, message, ... }` from a failed command.
+///
+/// `code` is a stable camelCase identifier the editor can branch on to offer
+/// recovery actions, `message` is the `Display` text and meant for display.
+/// Further fields are listed as `"name" => method`, serializing what
+/// `self.method()` returns:
+///
+/// ```ignore
+/// serialize_as_code_and_message!(RunError, "node" => node);
+/// ```
+macro_rules! serialize_as_code_and_message {
+    ($error:ident $(, $field:literal => $method:ident)* $(,)?) => {
+        impl serde::Serialize for $error {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStruct;
+
+                let fields = <[&str]>::len(&[$($field),*]);
+                let mut error = serializer.serialize_struct(stringify!($error), 2 + fields)?;
+                error.serialize_field("code", self.code())?;
+                error.serialize_field("message", &self.to_string())?;
+                $(error.serialize_field($field, &self.$method())?;)*
+                error.end()
+            }
+        }
+    };
+}
+
+pub(crate) use serialize_as_code_and_message;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    #[derive(Debug, thiserror::Error)]
+    enum TestError {
+        #[error("Nothing to see")]
+        Plain,
+
+        #[error("Problem at {0}")]
+        At(u32),
+    }
+
+    impl TestError {
+        fn code(&self) -> &'static str {
+            match self {
+                TestError::Plain => "plain",
+                TestError::At(_) => "at",
+            }
+        }
+
+        fn position(&self) -> Option<u32> {
+            match self {
+                TestError::At(position) => Some(*position),
+                TestError::Plain => None,
+            }
+        }
+    }
+
+    serialize_as_code_and_message!(TestError, "position" => position);
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("Failed")]
+    struct Failed;
+
+    impl Failed {
+        fn code(&self) -> &'static str {
+            "failed"
+        }
+    }
+
+    serialize_as_code_and_message!(Failed);
+
+    #[test]
+    fn serializes_code_and_message() {
+        let value = serde_json::to_value(Failed).unwrap();
+        assert_eq!(value, json!({ "code": "failed", "message": "Failed" }));
+    }
+
+    #[test]
+    fn serializes_extra_fields_after_the_message() {
+        let value = serde_json::to_value(TestError::At(3)).unwrap();
+        assert_eq!(
+            value,
+            json!({ "code": "at", "message": "Problem at 3", "position": 3 })
+        );
+
+        let value = serde_json::to_value(TestError::Plain).unwrap();
+        assert_eq!(
+            value,
+            json!({ "code": "plain", "message": "Nothing to see", "position": null })
+        );
+    }
+}
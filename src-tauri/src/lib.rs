@@ -0,0 +1,11 @@
+pub mod assets;
+pub mod atomic;
+pub mod behavior;
+pub mod blueprint;
+pub mod commands;
+mod error;
+pub mod logging;
+pub mod project;
+pub mod scene;
+pub mod terminal;
+pub mod viewport;
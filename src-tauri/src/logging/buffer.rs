@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+/// A captured log record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    /// Increases with every record, to page through the history.
+    pub id: u64,
+    pub level: LogLevel,
+    /// Module the record comes from, e.g. `pulsar_engine::viewport::renderer`.
+    pub target: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Which records of the history to return.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LogQuery {
+    /// Only records with a greater id.
+    pub after: Option<u64>,
+    /// Least severe level to include.
+    pub level: Option<LogLevel>,
+    /// Only records whose target starts with this.
+    pub target: Option<String>,
+    /// Only the most recent records, at most this many.
+    pub limit: Option<usize>,
+}
+
+/// The most recent records, oldest first.
+#[derive(Debug)]
+pub struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    next_id: u64,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            next_id: 1,
+        }
+    }
+
+    /// Stores `record` under the next id, dropping the oldest record once
+    /// the buffer is full. Returns the stored record.
+    pub fn push(&mut self, mut record: LogRecord) -> &LogRecord {
+        record.id = self.next_id;
+        self.next_id += 1;
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+        &self.records[self.records.len() - 1]
+    }
+
+    pub fn query(&self, query: &LogQuery) -> Vec<LogRecord> {
+        let matching = self.records.iter().filter(|record| {
+            query.after.map_or(true, |after| record.id > after)
+                && query.level.map_or(true, |level| record.level <= level)
+                && query
+                    .target
+                    .as_deref()
+                    .map_or(true, |target| record.target.starts_with(target))
+        });
+
+        let mut records: Vec<_> = match query.limit {
+            Some(limit) => matching.rev().take(limit).cloned().collect(),
+            None => matching.rev().cloned().collect(),
+        };
+        records.reverse();
+        records
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: LogLevel, target: &str, message: &str) -> LogRecord {
+        LogRecord {
+            id: 0,
+            level,
+            target: target.into(),
+            message: message.into(),
+            file: None,
+            line: None,
+            timestamp: 0,
+        }
+    }
+
+    fn messages(records: &[LogRecord]) -> Vec<&str> {
+        records
+            .iter()
+            .map(|record| record.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn evicts_the_oldest_records_once_full() {
+        let mut buffer = LogBuffer::new(3);
+        for message in ["a", "b", "c", "d", "e"] {
+            buffer.push(record(LogLevel::Info, "app", message));
+        }
+
+        let records = buffer.query(&LogQuery::default());
+        assert_eq!(messages(&records), ["c", "d", "e"]);
+        // Ids keep counting across evictions
+        let ids: Vec<_> = records.iter().map(|record| record.id).collect();
+        assert_eq!(ids, [3, 4, 5]);
+    }
+
+    #[test]
+    fn keeps_at_least_one_record() {
+        let mut buffer = LogBuffer::new(0);
+        buffer.push(record(LogLevel::Info, "app", "a"));
+        let last = buffer.push(record(LogLevel::Info, "app", "b"));
+        assert_eq!(last.id, 2);
+        assert_eq!(messages(&buffer.query(&LogQuery::default())), ["b"]);
+    }
+
+    #[test]
+    fn queries_by_id_level_target_and_limit() {
+        let mut buffer = LogBuffer::new(10);
+        buffer.push(record(LogLevel::Error, "pulsar_engine::assets", "a"));
+        buffer.push(record(LogLevel::Debug, "pulsar_engine::assets", "b"));
+        buffer.push(record(LogLevel::Warn, "wgpu_core", "c"));
+        buffer.push(record(LogLevel::Info, "pulsar_engine::scene", "d"));
+
+        let query = |query: LogQuery| messages(&buffer.query(&query)).join("");
+        assert_eq!(
+            query(LogQuery {
+                after: Some(2),
+                ..LogQuery::default()
+            }),
+            "cd"
+        );
+        assert_eq!(
+            query(LogQuery {
+                level: Some(LogLevel::Warn),
+                ..LogQuery::default()
+            }),
+            "ac"
+        );
+        assert_eq!(
+            query(LogQuery {
+                target: Some("pulsar_engine".into()),
+                ..LogQuery::default()
+            }),
+            "abd"
+        );
+        // The most recent matches, still oldest first
+        assert_eq!(
+            query(LogQuery {
+                target: Some("pulsar_engine".into()),
+                limit: Some(2),
+                ..LogQuery::default()
+            }),
+            "bd"
+        );
+    }
+
+    #[test]
+    fn clears_records_but_keeps_counting() {
+        let mut buffer = LogBuffer::new(10);
+        buffer.push(record(LogLevel::Info, "app", "a"));
+        buffer.clear();
+        assert!(buffer.query(&LogQuery::default()).is_empty());
+        assert_eq!(buffer.push(record(LogLevel::Info, "app", "b")).id, 2);
+    }
+}
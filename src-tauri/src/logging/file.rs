@@ -0,0 +1,248 @@
+use super::buffer::LogRecord;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, LineWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Name of the current log file; rotated files are `editor.1.log` and so on,
+/// the highest number being the oldest.
+const FILE_STEM: &str = "editor";
+
+/// Size a log file grows to before it is rotated.
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept besides the current one.
+const KEEP_FILES: usize = 3;
+
+/// Writes records to `editor.log` in a directory, rotating it once it
+/// reaches `MAX_FILE_SIZE`.
+pub struct RotatingFile {
+    dir: PathBuf,
+    file: LineWriter<File>,
+    size: u64,
+    max_size: u64,
+}
+
+impl RotatingFile {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        Self::open_with_max_size(dir, MAX_FILE_SIZE)
+    }
+
+    fn open_with_max_size(dir: &Path, max_size: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = log_path(dir, 0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file: LineWriter::new(file),
+            size,
+            max_size,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+
+        let location = match (&record.file, record.line) {
+            (Some(file), Some(line)) => format!(" {}:{}", file, line),
+            (Some(file), None) => format!(" {}", file),
+            _ => String::new(),
+        };
+        let line = format!(
+            "{} {:<5} {}{}: {}\n",
+            format_timestamp(record.timestamp),
+            format!("{:?}", record.level).to_uppercase(),
+            record.target,
+            location,
+            record.message
+        );
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Shifts `editor.N.log` to `editor.N+1.log`, dropping the oldest, and
+    /// starts a new `editor.log`.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let _ = fs::remove_file(log_path(&self.dir, KEEP_FILES));
+        for index in (0..KEEP_FILES).rev() {
+            let from = log_path(&self.dir, index);
+            if from.exists() {
+                fs::rename(&from, log_path(&self.dir, index + 1))?;
+            }
+        }
+
+        let file = File::create(log_path(&self.dir, 0))?;
+        self.file = LineWriter::new(file);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn log_path(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(format!("{}.log", FILE_STEM)),
+        index => dir.join(format!("{}.{}.log", FILE_STEM, index)),
+    }
+}
+
+/// Formats milliseconds since the Unix epoch as an RFC 3339 UTC timestamp.
+fn format_timestamp(millis: u64) -> String {
+    let seconds = millis / 1000;
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since the epoch, after Howard Hinnant's algorithm
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::LogLevel;
+
+    /// A scratch directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "pulsar-log-file-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn read(&self, name: &str) -> String {
+            fs::read_to_string(self.0.join(name)).unwrap()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            id: 0,
+            level: LogLevel::Info,
+            target: "app".into(),
+            message: message.into(),
+            file: Some("src/main.rs".into()),
+            line: Some(12),
+            timestamp: 1_700_000_000_123,
+        }
+    }
+
+    #[test]
+    fn formats_one_line_per_record() {
+        let dir = TempDir::new("format");
+        let mut file = RotatingFile::open(&dir.0).unwrap();
+        file.write(&record("started")).unwrap();
+        file.write(&LogRecord {
+            file: None,
+            line: None,
+            level: LogLevel::Error,
+            ..record("failed")
+        })
+        .unwrap();
+
+        assert_eq!(
+            dir.read("editor.log"),
+            "2023-11-14T22:13:20.123Z INFO  app src/main.rs:12: started\n\
+             2023-11-14T22:13:20.123Z ERROR app: failed\n"
+        );
+    }
+
+    #[test]
+    fn rotates_full_files_and_keeps_the_newest() {
+        let dir = TempDir::new("rotate");
+        // Every record fills a file, so each write rotates the previous one
+        let mut file = RotatingFile::open_with_max_size(&dir.0, 10).unwrap();
+        for index in 0..6 {
+            file.write(&record(&index.to_string())).unwrap();
+        }
+
+        let mut names: Vec<_> = fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["editor.1.log", "editor.2.log", "editor.3.log", "editor.log"]
+        );
+        assert!(dir.read("editor.log").ends_with(": 5\n"));
+        assert!(dir.read("editor.1.log").ends_with(": 4\n"));
+        assert!(dir.read("editor.3.log").ends_with(": 2\n"));
+    }
+
+    #[test]
+    fn continues_the_existing_file_when_reopened() {
+        let dir = TempDir::new("reopen");
+        let mut file = RotatingFile::open_with_max_size(&dir.0, 100).unwrap();
+        file.write(&record("first")).unwrap();
+        drop(file);
+
+        // Lines are under 60 bytes and the existing one counts towards the size,
+        // so the third record starts a new file
+        let mut file = RotatingFile::open_with_max_size(&dir.0, 100).unwrap();
+        file.write(&record("second")).unwrap();
+        file.write(&record("third")).unwrap();
+        file.flush().unwrap();
+
+        let rotated = dir.read("editor.1.log");
+        assert_eq!(rotated.lines().count(), 2);
+        assert!(rotated.ends_with(": second\n"));
+        assert!(dir.read("editor.log").ends_with(": third\n"));
+    }
+
+    #[test]
+    fn formats_timestamps_as_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp(951_782_400_000),
+            "2000-02-29T00:00:00.000Z"
+        );
+        assert_eq!(
+            format_timestamp(1_735_689_599_999),
+            "2024-12-31T23:59:59.999Z"
+        );
+    }
+}
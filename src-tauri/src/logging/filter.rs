@@ -0,0 +1,189 @@
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Verbosity of a log filter, from `Off` to the most verbose `Trace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFilterLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogFilterLevel> for LevelFilter {
+    fn from(level: LogFilterLevel) -> Self {
+        match level {
+            LogFilterLevel::Off => LevelFilter::Off,
+            LogFilterLevel::Error => LevelFilter::Error,
+            LogFilterLevel::Warn => LevelFilter::Warn,
+            LogFilterLevel::Info => LevelFilter::Info,
+            LogFilterLevel::Debug => LevelFilter::Debug,
+            LogFilterLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+impl From<LevelFilter> for LogFilterLevel {
+    fn from(level: LevelFilter) -> Self {
+        match level {
+            LevelFilter::Off => LogFilterLevel::Off,
+            LevelFilter::Error => LogFilterLevel::Error,
+            LevelFilter::Warn => LogFilterLevel::Warn,
+            LevelFilter::Info => LogFilterLevel::Info,
+            LevelFilter::Debug => LogFilterLevel::Debug,
+            LevelFilter::Trace => LogFilterLevel::Trace,
+        }
+    }
+}
+
+/// Levels records must reach to be logged: a default and overrides for
+/// modules, where the most specific module prefix of a target wins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilters {
+    pub default: LogFilterLevel,
+    /// Levels by module path, e.g. `pulsar_engine::assets` or `wgpu_core`.
+    pub modules: BTreeMap<String, LogFilterLevel>,
+}
+
+impl Default for LogFilters {
+    fn default() -> Self {
+        Self {
+            default: LogFilterLevel::Info,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl LogFilters {
+    /// Parses the `env_logger` style `RUST_LOG` syntax, e.g.
+    /// `info,wgpu_core=warn,pulsar_engine::assets=debug`. Invalid entries
+    /// are skipped.
+    pub fn parse(spec: &str) -> Self {
+        let mut filters = Self::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = level.trim().parse::<LevelFilter>() {
+                        filters
+                            .modules
+                            .insert(module.trim().to_string(), level.into());
+                    }
+                }
+                // A bare level sets the default, a bare module enables all of it
+                None => match directive.parse::<LevelFilter>() {
+                    Ok(level) => filters.default = level.into(),
+                    Err(_) => {
+                        filters
+                            .modules
+                            .insert(directive.to_string(), LogFilterLevel::Trace);
+                    }
+                },
+            }
+        }
+        filters
+    }
+
+    /// Level records of `target` must reach.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target.starts_with(module.as_str())
+                    && (target.len() == module.len() || target[module.len()..].starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, &level)| level)
+            .into()
+    }
+
+    /// Most verbose level any module logs at, for `log::set_max_level`.
+    pub fn max_level(&self) -> LevelFilter {
+        self.modules
+            .values()
+            .copied()
+            .chain(Some(self.default))
+            .max()
+            .unwrap_or(LogFilterLevel::Off)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules(filters: &LogFilters) -> Vec<(&str, LogFilterLevel)> {
+        filters
+            .modules
+            .iter()
+            .map(|(module, &level)| (module.as_str(), level))
+            .collect()
+    }
+
+    #[test]
+    fn parses_a_default_level_and_module_levels() {
+        let filters = LogFilters::parse("warn, wgpu_core=error ,pulsar_engine::assets = debug");
+        assert_eq!(filters.default, LogFilterLevel::Warn);
+        assert_eq!(
+            modules(&filters),
+            [
+                ("pulsar_engine::assets", LogFilterLevel::Debug),
+                ("wgpu_core", LogFilterLevel::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn enables_everything_of_a_bare_module() {
+        let filters = LogFilters::parse("pulsar_engine::viewport");
+        assert_eq!(filters.default, LogFilterLevel::Info);
+        assert_eq!(
+            modules(&filters),
+            [("pulsar_engine::viewport", LogFilterLevel::Trace)]
+        );
+    }
+
+    #[test]
+    fn skips_invalid_entries() {
+        let filters = LogFilters::parse("debug,,wgpu=loud,naga=off,=");
+        assert_eq!(filters.default, LogFilterLevel::Debug);
+        assert_eq!(modules(&filters), [("naga", LogFilterLevel::Off)]);
+        assert_eq!(LogFilters::parse(""), LogFilters::default());
+    }
+
+    #[test]
+    fn uses_the_most_specific_module_of_a_target() {
+        let filters = LogFilters::parse("info,pulsar_engine=warn,pulsar_engine::assets=trace");
+        assert_eq!(filters.level_for("app"), LevelFilter::Info);
+        assert_eq!(filters.level_for("pulsar_engine"), LevelFilter::Warn);
+        assert_eq!(filters.level_for("pulsar_engine::scene"), LevelFilter::Warn);
+        assert_eq!(
+            filters.level_for("pulsar_engine::assets::watcher"),
+            LevelFilter::Trace
+        );
+        // Only whole path segments match
+        assert_eq!(filters.level_for("pulsar_engine_macros"), LevelFilter::Info);
+        assert_eq!(
+            filters.level_for("pulsar_engine::assets_old"),
+            LevelFilter::Warn
+        );
+    }
+
+    #[test]
+    fn reports_the_most_verbose_level() {
+        assert_eq!(LogFilters::parse("warn").max_level(), LevelFilter::Warn);
+        assert_eq!(
+            LogFilters::parse("off,naga=debug").max_level(),
+            LevelFilter::Debug
+        );
+        assert_eq!(
+            LogFilters::parse("info,wgpu=off").max_level(),
+            LevelFilter::Info
+        );
+    }
+}
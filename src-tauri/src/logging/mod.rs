@@ -0,0 +1,401 @@
+//! The editor's `log` backend.
+//!
+//! Records are kept in a ring buffer the Console panel queries for history,
+//! forwarded to a sink as they happen and, once a project is open, written
+//! to rotating files in it; debug builds also echo them to stderr. Levels
+//! can be changed per module at runtime; they start from `RUST_LOG` like
+//! `env_logger`.
+//!
+//! Logging threads only queue records. A writer thread of its own stores
+//! them, so logging never waits on the file, the history or the frontend;
+//! records logged while the queue is full are dropped and counted.
+
+pub mod buffer;
+pub mod file;
+pub mod filter;
+
+pub use buffer::{LogBuffer, LogLevel, LogQuery, LogRecord};
+pub use file::RotatingFile;
+pub use filter::{LogFilterLevel, LogFilters};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use log::{Log, Metadata, Record};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    cell::Cell,
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the Tauri event every record is emitted on, with a `LogRecord`.
+pub const LOG_EVENT: &str = "log";
+
+/// Directory log files are written to inside a project. It is hidden, so
+/// the asset database and watcher leave it alone.
+pub const PROJECT_LOG_DIR: &str = ".pulsar/logs";
+
+/// Records the history keeps.
+const HISTORY: usize = 10_000;
+
+/// Records waiting for the writer thread before further ones are dropped.
+const QUEUE: usize = 4096;
+
+/// Callback records are forwarded to as they are logged.
+pub type LogSink = Arc<dyn Fn(&LogRecord) + Send + Sync>;
+
+thread_local! {
+    /// Set on the writer thread, so records logged by the sink or the file
+    /// writer are dropped instead of feeding back into the queue.
+    static WRITER: Cell<bool> = const { Cell::new(false) };
+}
+
+struct Shared {
+    filters: RwLock<LogFilters>,
+    buffer: Mutex<LogBuffer>,
+    file: Mutex<Option<RotatingFile>>,
+    sink: RwLock<Option<LogSink>>,
+    /// Records dropped since the writer thread last caught up.
+    dropped: AtomicU64,
+}
+
+enum Message {
+    Record(LogRecord),
+    /// Answered once the records queued before are written.
+    Flush(Sender<()>),
+}
+
+/// Tauri state controlling the logger installed by `init`.
+#[derive(Clone)]
+pub struct LogState {
+    shared: Arc<Shared>,
+    queue: Sender<Message>,
+}
+
+/// Installs the editor logger, with the filters in `RUST_LOG` or `info`.
+pub fn init() -> LogState {
+    let filters = std::env::var("RUST_LOG")
+        .map(|spec| LogFilters::parse(&spec))
+        .unwrap_or_default();
+    log::set_max_level(filters.max_level());
+
+    let state = LogState::new(filters);
+    let logger = EditorLogger {
+        state: state.clone(),
+    };
+    if log::set_logger(Box::leak(Box::new(logger))).is_err() {
+        eprintln!("A logger is already installed");
+    }
+    state
+}
+
+impl LogState {
+    /// Starts the writer thread, which runs until every clone is dropped.
+    fn new(filters: LogFilters) -> Self {
+        let shared = Arc::new(Shared {
+            filters: RwLock::new(filters),
+            buffer: Mutex::new(LogBuffer::new(HISTORY)),
+            file: Mutex::new(None),
+            sink: RwLock::new(None),
+            dropped: AtomicU64::new(0),
+        });
+        let (queue, messages) = crossbeam_channel::bounded(QUEUE);
+
+        let writer = shared.clone();
+        thread::Builder::new()
+            .name("log-writer".into())
+            .spawn(move || write_records(&writer, &messages))
+            .expect("Failed to start the log writer thread");
+
+        Self { shared, queue }
+    }
+
+    /// Forwards records to `sink` from now on, or stops forwarding.
+    pub fn set_sink(&self, sink: Option<LogSink>) {
+        *self.shared.sink.write() = sink;
+    }
+
+    /// Writes records to rotating files in `dir` from now on, or stops
+    /// writing files.
+    pub fn set_directory(&self, dir: Option<&Path>) -> io::Result<()> {
+        let file = dir.map(RotatingFile::open).transpose()?;
+        let previous = std::mem::replace(&mut *self.shared.file.lock(), file);
+        if let Some(mut previous) = previous {
+            let _ = previous.flush();
+        }
+        if let Some(dir) = dir {
+            log::info!("Writing logs to {}", dir.display());
+        }
+        Ok(())
+    }
+
+    pub fn filters(&self) -> LogFilters {
+        self.shared.filters.read().clone()
+    }
+
+    /// Sets the level of `module` and everything below it, or the default
+    /// level if `None`.
+    pub fn set_filter(&self, module: Option<&str>, level: LogFilterLevel) {
+        self.update_filters(|filters| match module {
+            Some(module) => {
+                filters.modules.insert(module.to_string(), level);
+            }
+            None => filters.default = level,
+        });
+    }
+
+    /// Lets `module` log at the level of its parent again.
+    pub fn remove_filter(&self, module: &str) {
+        self.update_filters(|filters| {
+            filters.modules.remove(module);
+        });
+    }
+
+    /// Waits until the records logged so far are stored and written.
+    pub fn flush(&self) {
+        if WRITER.with(Cell::get) {
+            return;
+        }
+        let (done, written) = crossbeam_channel::bounded(1);
+        if self.queue.send(Message::Flush(done)).is_ok() {
+            let _ = written.recv();
+        }
+    }
+
+    pub fn query(&self, query: &LogQuery) -> Vec<LogRecord> {
+        self.shared.buffer.lock().query(query)
+    }
+
+    pub fn clear(&self) {
+        self.shared.buffer.lock().clear();
+    }
+
+    fn update_filters(&self, update: impl FnOnce(&mut LogFilters)) {
+        let mut filters = self.shared.filters.write();
+        update(&mut filters);
+        log::set_max_level(filters.max_level());
+    }
+
+    /// Hands `record` to the writer thread without waiting for it.
+    fn submit(&self, record: LogRecord) {
+        if let Err(TrySendError::Full(_)) = self.queue.try_send(Message::Record(record)) {
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Stores the queued records until every `LogState` is dropped.
+fn write_records(shared: &Shared, messages: &Receiver<Message>) {
+    WRITER.with(|writer| writer.set(true));
+    for message in messages {
+        let record = match message {
+            Message::Record(record) => record,
+            Message::Flush(done) => {
+                if let Some(file) = shared.file.lock().as_mut() {
+                    let _ = file.flush();
+                }
+                let _ = done.send(());
+                continue;
+            }
+        };
+
+        let dropped = shared.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            let warning = LogRecord {
+                id: 0,
+                level: LogLevel::Warn,
+                target: module_path!().to_string(),
+                message: format!("Dropped {} records, the log queue was full", dropped),
+                file: None,
+                line: None,
+                timestamp: record.timestamp,
+            };
+            write_record(shared, warning);
+        }
+        write_record(shared, record);
+    }
+}
+
+fn write_record(shared: &Shared, record: LogRecord) {
+    let record = shared.buffer.lock().push(record).clone();
+    if let Some(file) = shared.file.lock().as_mut() {
+        if let Err(e) = file.write(&record) {
+            eprintln!("Failed to write to {}: {}", file.dir().display(), e);
+        }
+    }
+    let sink = shared.sink.read().clone();
+    if let Some(sink) = sink {
+        sink(&record);
+    }
+}
+
+struct EditorLogger {
+    state: LogState,
+}
+
+impl Log for EditorLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level()
+            <= self
+                .state
+                .shared
+                .filters
+                .read()
+                .level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) || WRITER.with(Cell::get) {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        let record = LogRecord {
+            id: 0,
+            level: record.level().into(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            timestamp,
+        };
+        #[cfg(debug_assertions)]
+        eprintln!("[{:?} {}] {}", record.level, record.target, record.message);
+
+        self.state.submit(record);
+    }
+
+    fn flush(&self) {
+        self.state.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn log(logger: &EditorLogger, level: Level, target: &str, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    fn messages(records: &[LogRecord]) -> Vec<&str> {
+        records
+            .iter()
+            .map(|record| record.message.as_str())
+            .collect()
+    }
+
+    /// A logger and its state, with the records forwarded to the sink.
+    fn logger(filters: &str) -> (EditorLogger, Arc<Mutex<Vec<LogRecord>>>) {
+        let state = LogState::new(LogFilters::parse(filters));
+        let forwarded = Arc::new(Mutex::new(Vec::new()));
+        let sink = forwarded.clone();
+        state.set_sink(Some(Arc::new(move |record: &LogRecord| {
+            sink.lock().push(record.clone())
+        })));
+        (EditorLogger { state }, forwarded)
+    }
+
+    #[test]
+    fn stores_and_forwards_the_enabled_records() {
+        let (logger, forwarded) = logger("info,noisy=warn");
+
+        log(&logger, Level::Info, "app", "started");
+        log(&logger, Level::Debug, "app", "details");
+        log(&logger, Level::Info, "noisy::module", "chatter");
+        log(&logger, Level::Warn, "noisy::module", "problem");
+        logger.flush();
+
+        let records = logger.state.query(&LogQuery::default());
+        assert_eq!(messages(&records), ["started", "problem"]);
+        assert_eq!(records[0].id, 1);
+        assert_eq!(records[1].id, 2);
+        assert_eq!(records[1].level, LogLevel::Warn);
+        assert_eq!(*forwarded.lock(), records);
+    }
+
+    #[test]
+    fn drops_records_while_the_queue_is_full_and_reports_them() {
+        let state = LogState::new(LogFilters::default());
+        let (entered, inside) = crossbeam_channel::bounded(0);
+        let (release, released) = crossbeam_channel::bounded::<()>(0);
+        state.set_sink(Some(Arc::new(move |record: &LogRecord| {
+            if record.message == "block" {
+                let _ = entered.send(());
+                let _ = released.recv();
+            }
+        })));
+        let logger = EditorLogger { state };
+
+        // Hold the writer thread in the sink while the queue fills up
+        log(&logger, Level::Info, "app", "block");
+        inside.recv().unwrap();
+        for index in 0..QUEUE + 10 {
+            log(&logger, Level::Info, "app", &index.to_string());
+        }
+        drop(release);
+        logger.flush();
+
+        let records = logger.state.query(&LogQuery::default());
+        assert_eq!(records.len(), 1 + 1 + QUEUE);
+        let warning = &records[1];
+        assert_eq!(warning.level, LogLevel::Warn);
+        assert_eq!(
+            warning.message,
+            "Dropped 10 records, the log queue was full"
+        );
+        assert_eq!(records.last().unwrap().message, (QUEUE - 1).to_string());
+    }
+
+    #[test]
+    fn ignores_records_logged_while_writing() {
+        let state = LogState::new(LogFilters::default());
+        let logger = Arc::new(EditorLogger { state });
+        let inner = Arc::downgrade(&logger);
+        logger.state.set_sink(Some(Arc::new(move |_: &LogRecord| {
+            if let Some(logger) = inner.upgrade() {
+                log(&logger, Level::Info, "app", "from the sink");
+            }
+        })));
+
+        log(&logger, Level::Info, "app", "logged");
+        logger.flush();
+        logger.flush();
+
+        let records = logger.state.query(&LogQuery::default());
+        assert_eq!(messages(&records), ["logged"]);
+        logger.state.set_sink(None);
+    }
+
+    #[test]
+    fn writes_records_to_the_log_directory() {
+        let dir = std::env::temp_dir().join(format!("pulsar-logging-{}", std::process::id()));
+        let (logger, _) = logger("info");
+        logger.state.set_directory(Some(&dir)).unwrap();
+
+        log(&logger, Level::Warn, "app::assets", "missing texture");
+        logger.flush();
+
+        let text = std::fs::read_to_string(dir.join("editor.log")).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(
+            text.ends_with(" WARN  app::assets: missing texture\n"),
+            "{}",
+            text
+        );
+    }
+}
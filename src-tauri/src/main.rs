@@ -1,19 +1,145 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use log::info;
-use parking_lot::RwLock;
+use log::{info, warn};
+use pulsar_engine::{assets::AssetState, commands, scene::SceneState, terminal::TerminalState};
+use pulsar_engine::behavior::AgentState;
+use pulsar_engine::blueprint::{CheckState, LibraryDirs, NodeLibrary, NodeLibrarySink, NodeLibraryState, CHECK_DIR, NODE_LIBRARY_EVENT};
+use pulsar_engine::blueprint::vm::RunState;
+use pulsar_engine::logging::{self, LogRecord, LogState, LogSink, LOG_EVENT};
+use pulsar_engine::viewport::ViewportState;
 use std::sync::Arc;
-use tauri::Manager;
-use tokio::time::Duration;
+use tauri::{Invoke, Manager};
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
+    let logs = logging::init();
     info!("Starting application");
 
+    // Fault injection exercises the viewport recovery paths and is left out of
+    // release builds
+    let handler: fn(Invoke) = tauri::generate_handler![
+        commands::initialize_viewport,
+        commands::update_native_viewport,
+        commands::reset_viewport_camera,
+        commands::orbit_viewport_camera,
+        commands::pan_viewport_camera,
+        commands::zoom_viewport_camera,
+        commands::shutdown_viewport,
+        commands::set_viewport_frame_pacing,
+        commands::get_viewport_frame_stats,
+        commands::set_viewport_display_settings,
+        commands::list_graphics_adapters,
+        commands::set_graphics_adapter,
+        commands::scene::create_entity,
+        commands::scene::delete_entity,
+        commands::scene::reparent_entity,
+        commands::scene::set_entity_transform,
+        commands::scene::rename_entity,
+        commands::scene::set_entity_visible,
+        commands::scene::set_entity_component,
+        commands::scene::remove_entity_component,
+        commands::scene::get_entity,
+        commands::scene::query_scene,
+        commands::scene::new_scene,
+        commands::scene::save_scene,
+        commands::scene::load_scene,
+        commands::assets::import_model,
+        commands::assets::import_texture,
+        commands::assets::open_project,
+        commands::assets::list_assets,
+        commands::assets::get_asset_dependencies,
+        commands::assets::find_asset_references,
+        commands::assets::get_asset_delete_impact,
+        commands::blueprint::compile_blueprint,
+        commands::blueprint::check_blueprint,
+        commands::blueprint::get_node_library,
+        commands::blueprint::reload_node_library,
+        commands::blueprint::start_blueprint_run,
+        commands::blueprint::continue_blueprint_run,
+        commands::blueprint::step_blueprint_run,
+        commands::blueprint::pause_blueprint_run,
+        commands::blueprint::stop_blueprint_run,
+        commands::blueprint::set_blueprint_breakpoints,
+        commands::behavior::load_behavior_tree,
+        commands::behavior::save_behavior_tree,
+        commands::behavior::list_behavior_handlers,
+        commands::behavior::spawn_behavior_agent,
+        commands::behavior::despawn_behavior_agent,
+        commands::behavior::list_behavior_agents,
+        commands::behavior::debug_behavior_agent,
+        commands::project::list_project_dir,
+        commands::project::read_project_file,
+        commands::project::read_project_file_binary,
+        commands::project::write_project_file,
+        commands::project::write_project_file_binary,
+        commands::project::create_project_file,
+        commands::project::create_project_dir,
+        commands::project::rename_project_entry,
+        commands::project::move_project_entry,
+        commands::project::delete_project_entry,
+        commands::terminal::open_terminal,
+        commands::terminal::write_terminal,
+        commands::terminal::resize_terminal,
+        commands::terminal::close_terminal,
+        commands::terminal::list_terminals,
+        commands::logging::get_logs,
+        commands::logging::clear_logs,
+        commands::logging::get_log_filters,
+        commands::logging::set_log_filter,
+        commands::logging::remove_log_filter,
+    ];
+    #[cfg(debug_assertions)]
+    let handler = {
+        let debug: fn(Invoke) = tauri::generate_handler![commands::inject_viewport_fault];
+        move |invoke: Invoke| match invoke.message.command() {
+            "inject_viewport_fault" => debug(invoke),
+            _ => handler(invoke),
+        }
+    };
+
     tauri::Builder::default()
+        .manage(ViewportState::new())
+        .manage(SceneState::new())
+        .manage(AssetState::default())
+        .manage(TerminalState::default())
+        .manage(logs)
+        .manage(NodeLibraryState::default())
+        .manage(RunState::default())
+        .manage(CheckState::default())
+        .manage(AgentState::default())
         .setup(|app| {
             let _main_window = app.get_window("main").unwrap();
+
+            // Forward log records to the Console panel
+            let handle = app.handle();
+            let sink: LogSink = Arc::new(move |record: &LogRecord| {
+                let _ = handle.emit_all(LOG_EVENT, record.clone());
+            });
+            app.state::<LogState>().set_sink(Some(sink));
+
+            // Load the node library and tell the Blueprint editor when it changes
+            let handle = app.handle();
+            let sink: NodeLibrarySink = Arc::new(move |library: &NodeLibrary| {
+                let _ = handle.emit_all(NODE_LIBRARY_EVENT, library.clone());
+            });
+            let library = app.state::<NodeLibraryState>();
+            library.set_sink(Some(sink));
+            let user = app.path_resolver().app_config_dir().map(|dir| dir.join("nodes"));
+            if let Some(dir) = &user {
+                if let Err(e) = std::fs::create_dir_all(dir) {
+                    warn!("Failed to create {}: {}", dir.display(), e);
+                }
+            }
+            library.set_dirs(LibraryDirs {
+                builtin: app.path_resolver().resolve_resource("../public/nodes"),
+                user,
+                plugins: None,
+            });
+
+            // Check generated code in a scratch crate that keeps its build cache
+            if let Some(dir) = app.path_resolver().app_cache_dir() {
+                app.state::<CheckState>().set_dir(dir.join(CHECK_DIR));
+            }
             
             // let render_window = tauri::WindowBuilder::new(
             //     app,
@@ -43,7 +169,21 @@ async fn main() {
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![])
+        .on_window_event(|event| match event.event() {
+            // Stop rendering into the window before its surfaces go away
+            tauri::WindowEvent::CloseRequested { .. } | tauri::WindowEvent::Destroyed
+                if event.window().label() == "main" =>
+            {
+                event.window().state::<ViewportState>().shutdown();
+                event.window().state::<TerminalState>().shutdown();
+                event.window().state::<RunState>().shutdown();
+                event.window().state::<AgentState>().shutdown();
+                // Write out the records still queued
+                event.window().state::<LogState>().flush();
+            }
+            _ => {}
+        })
+        .invoke_handler(handler)
         .run(tauri::generate_context!())
         .expect("Error while running application");
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+use crate::error::serialize_as_code_and_message;
+use std::path::PathBuf;
+
+/// Errors raised by file operations in the project.
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectError {
+    #[error("No project is open")]
+    NoProject,
+
+    /// The path is absolute, climbs out with `..` or leads out of the
+    /// project through a symlink.
+    #[error("{0} is outside the project")]
+    OutsideProject(String),
+
+    #[error("{} does not exist", .0.display())]
+    NotFound(PathBuf),
+
+    #[error("{} already exists", .0.display())]
+    AlreadyExists(PathBuf),
+
+    #[error("Invalid file name: {0:?}")]
+    InvalidName(String),
+
+    #[error("Cannot move {} into itself", .0.display())]
+    MoveIntoSelf(PathBuf),
+
+    #[error("Failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to move {} to the trash: {reason}", path.display())]
+    Trash { path: PathBuf, reason: String },
+
+    #[error("Invalid base64 data: {0}")]
+    Encoding(#[from] base64::DecodeError),
+}
+
+impl ProjectError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProjectError::NoProject => "noProject",
+            ProjectError::OutsideProject(_) => "outsideProject",
+            ProjectError::NotFound(_) => "notFound",
+            ProjectError::AlreadyExists(_) => "alreadyExists",
+            ProjectError::InvalidName(_) => "invalidName",
+            ProjectError::MoveIntoSelf(_) => "moveIntoSelf",
+            ProjectError::Io { .. } => "io",
+            ProjectError::Trash { .. } => "trash",
+            ProjectError::Encoding(_) => "encoding",
+        }
+    }
+
+    /// Wraps an I/O error with the path it happened on.
+    pub fn io(path: impl Into<PathBuf>) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |source| ProjectError::Io { path, source }
+    }
+}
+
+serialize_as_code_and_message!(ProjectError);
+
+pub type ProjectResult<T> = Result<T, ProjectError>;
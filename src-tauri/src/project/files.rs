@@ -0,0 +1,520 @@
+//! File operations confined to the project directory.
+//!
+//! Paths come from the frontend relative to the project root with `/`
+//! separators, e.g. `scripts/player.rs`; the empty path is the root itself.
+//! Absolute paths and `..` components are rejected, and resolved paths are
+//! checked against the canonical root so symlinks cannot lead out of the
+//! project either. Asset sidecars are hidden from listings and follow their
+//! file when it is moved or deleted.
+
+use super::error::{ProjectError, ProjectResult};
+use crate::{
+    assets::database::{meta_path, META_EXTENSION},
+    atomic,
+};
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    File,
+    Directory,
+}
+
+/// A file or directory in the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    pub name: String,
+    /// Path relative to the project root, with `/` separators.
+    pub path: String,
+    pub kind: EntryKind,
+    /// Size in bytes, 0 for directories.
+    pub size: u64,
+    /// Last modification in milliseconds since the Unix epoch.
+    pub modified: Option<u64>,
+    /// Whether a directory has entries, so trees can show an expander
+    /// without listing it.
+    pub has_children: bool,
+}
+
+/// The files of the project rooted at a directory.
+#[derive(Debug, Clone)]
+pub struct ProjectFiles {
+    root: PathBuf,
+}
+
+impl ProjectFiles {
+    pub fn new(root: &Path) -> ProjectResult<Self> {
+        let root = root.canonicalize().map_err(ProjectError::io(root))?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Absolute path of the existing entry at `path`.
+    pub fn resolve(&self, path: &str) -> ProjectResult<PathBuf> {
+        let absolute = self.join(path)?;
+        if absolute == self.root {
+            return Ok(absolute);
+        }
+
+        // Check the entry itself too, it may be a symlink leading out
+        let canonical = absolute.canonicalize().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => ProjectError::NotFound(PathBuf::from(path)),
+            _ => ProjectError::Io {
+                path: absolute.clone(),
+                source: e,
+            },
+        })?;
+        if !canonical.starts_with(&self.root) {
+            return Err(ProjectError::OutsideProject(path.to_string()));
+        }
+        self.resolve_parent(path, &absolute)
+    }
+
+    /// Absolute path for a new entry at `path`, whose parent must exist.
+    fn resolve_new(&self, path: &str) -> ProjectResult<PathBuf> {
+        let absolute = self.join(path)?;
+        if absolute == self.root {
+            return Err(ProjectError::AlreadyExists(PathBuf::from(path)));
+        }
+        let absolute = self.resolve_parent(path, &absolute)?;
+        if fs::symlink_metadata(&absolute).is_ok() {
+            return Err(ProjectError::AlreadyExists(PathBuf::from(path)));
+        }
+        Ok(absolute)
+    }
+
+    /// `absolute` with its parent canonicalized and checked to be inside
+    /// the project.
+    fn resolve_parent(&self, path: &str, absolute: &Path) -> ProjectResult<PathBuf> {
+        let (Some(parent), Some(name)) = (absolute.parent(), absolute.file_name()) else {
+            return Err(ProjectError::OutsideProject(path.to_string()));
+        };
+        let parent = parent.canonicalize().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => ProjectError::NotFound(PathBuf::from(path)),
+            _ => ProjectError::Io {
+                path: parent.to_path_buf(),
+                source: e,
+            },
+        })?;
+        if !parent.starts_with(&self.root) {
+            return Err(ProjectError::OutsideProject(path.to_string()));
+        }
+        Ok(parent.join(name))
+    }
+
+    /// `path` joined to the root, rejecting anything that could leave it.
+    fn join(&self, path: &str) -> ProjectResult<PathBuf> {
+        let outside = || ProjectError::OutsideProject(path.to_string());
+        if path.starts_with(['/', '\\']) || (cfg!(windows) && path.contains(':')) {
+            return Err(outside());
+        }
+
+        let mut absolute = self.root.clone();
+        for part in path.split(['/', '\\']) {
+            match part {
+                "" | "." => {}
+                ".." => return Err(outside()),
+                part => absolute.push(part),
+            }
+        }
+        Ok(absolute)
+    }
+
+    /// Project relative form of an absolute path inside the project.
+    fn relative(&self, absolute: &Path) -> String {
+        let relative = absolute.strip_prefix(&self.root).unwrap_or(absolute);
+        let parts: Vec<_> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        parts.join("/")
+    }
+
+    fn entry(&self, absolute: &Path) -> ProjectResult<FileEntry> {
+        let metadata = fs::metadata(absolute).map_err(ProjectError::io(absolute))?;
+        let kind = if metadata.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        };
+        let has_children = kind == EntryKind::Directory
+            && fs::read_dir(absolute)
+                .map(|mut entries| entries.next().is_some())
+                .unwrap_or(false);
+
+        Ok(FileEntry {
+            name: absolute
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: self.relative(absolute),
+            kind,
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_millis() as u64),
+            has_children,
+        })
+    }
+
+    /// Entries of the directory at `path`, directories first, without
+    /// descending into them.
+    pub fn list(&self, path: &str) -> ProjectResult<Vec<FileEntry>> {
+        let dir = self.resolve(path)?;
+        let entries = WalkDir::new(&dir)
+            .min_depth(1)
+            .max_depth(1)
+            .sort_by(|a, b| {
+                let (a_dir, b_dir) = (a.path().is_dir(), b.path().is_dir());
+                match b_dir.cmp(&a_dir) {
+                    Ordering::Equal => a.file_name().cmp(b.file_name()),
+                    order => order,
+                }
+            })
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| !is_sidecar(entry.path()))
+            // Symlinks leading out of the project cannot be opened, hide them
+            .filter(|entry| {
+                entry
+                    .path()
+                    .canonicalize()
+                    .map_or(false, |path| path.starts_with(&self.root))
+            });
+
+        entries.map(|entry| self.entry(entry.path())).collect()
+    }
+
+    pub fn read(&self, path: &str) -> ProjectResult<Vec<u8>> {
+        let file = self.resolve(path)?;
+        fs::read(&file).map_err(ProjectError::io(file))
+    }
+
+    pub fn read_text(&self, path: &str) -> ProjectResult<String> {
+        let file = self.resolve(path)?;
+        fs::read_to_string(&file).map_err(ProjectError::io(file))
+    }
+
+    /// Creates or replaces the file at `path`. The file is replaced
+    /// atomically, so a failed write never leaves it truncated.
+    pub fn write(&self, path: &str, contents: &[u8]) -> ProjectResult<FileEntry> {
+        let file = match self.resolve(path) {
+            Ok(file) => file,
+            Err(ProjectError::NotFound(_)) => self.resolve_new(path)?,
+            Err(e) => return Err(e),
+        };
+        if file.is_dir() {
+            return Err(ProjectError::AlreadyExists(PathBuf::from(path)));
+        }
+        check_name(&file)?;
+
+        atomic::write(&file, contents).map_err(ProjectError::io(&file))?;
+        self.entry(&file)
+    }
+
+    /// Creates an empty file at `path`, failing if it exists.
+    pub fn create_file(&self, path: &str) -> ProjectResult<FileEntry> {
+        let file = self.resolve_new(path)?;
+        check_name(&file)?;
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file)
+            .map_err(ProjectError::io(&file))?;
+        self.entry(&file)
+    }
+
+    /// Creates a directory at `path`, failing if it exists.
+    pub fn create_dir(&self, path: &str) -> ProjectResult<FileEntry> {
+        let dir = self.resolve_new(path)?;
+        check_name(&dir)?;
+        fs::create_dir(&dir).map_err(ProjectError::io(&dir))?;
+        self.entry(&dir)
+    }
+
+    /// Renames the entry at `path` within its directory.
+    pub fn rename(&self, path: &str, new_name: &str) -> ProjectResult<FileEntry> {
+        let from = self.resolve(path)?;
+        let to = from.with_file_name(new_name);
+        if to.file_name().map_or(true, |name| name != new_name) {
+            return Err(ProjectError::InvalidName(new_name.to_string()));
+        }
+        check_name(&to)?;
+        self.relocate(&from, &to)
+    }
+
+    /// Moves the entry at `path` into the directory at `destination`.
+    pub fn move_to(&self, path: &str, destination: &str) -> ProjectResult<FileEntry> {
+        let from = self.resolve(path)?;
+        let dir = self.resolve(destination)?;
+        if !dir.is_dir() {
+            return Err(ProjectError::NotFound(PathBuf::from(destination)));
+        }
+        if dir.starts_with(&from) {
+            return Err(ProjectError::MoveIntoSelf(PathBuf::from(path)));
+        }
+        let name = from.file_name().unwrap_or_default();
+        self.relocate(&from, &dir.join(name))
+    }
+
+    fn relocate(&self, from: &Path, to: &Path) -> ProjectResult<FileEntry> {
+        if from == self.root {
+            return Err(ProjectError::OutsideProject(String::new()));
+        }
+        // Allow case-only renames on case-insensitive file systems
+        let same = to.canonicalize().ok() == from.canonicalize().ok();
+        if !same && fs::symlink_metadata(to).is_ok() {
+            return Err(ProjectError::AlreadyExists(PathBuf::from(
+                self.relative(to),
+            )));
+        }
+
+        fs::rename(from, to).map_err(ProjectError::io(from))?;
+        let sidecar = meta_path(from);
+        if sidecar.is_file() {
+            fs::rename(&sidecar, meta_path(to)).map_err(ProjectError::io(&sidecar))?;
+        }
+        self.entry(to)
+    }
+
+    /// Deletes the entry at `path` with its sidecar, moving them to the
+    /// system trash unless `permanent`.
+    pub fn delete(&self, path: &str, permanent: bool) -> ProjectResult<()> {
+        let target = self.resolve(path)?;
+        if target == self.root {
+            return Err(ProjectError::OutsideProject(path.to_string()));
+        }
+
+        let sidecar = meta_path(&target);
+        for path in [Some(target), Some(sidecar).filter(|path| path.is_file())]
+            .into_iter()
+            .flatten()
+        {
+            if permanent {
+                let removed = if path.is_dir() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+                removed.map_err(ProjectError::io(&path))?;
+            } else {
+                trash::delete(&path).map_err(|e| ProjectError::Trash {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_sidecar(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |extension| extension == META_EXTENSION)
+}
+
+/// Rejects names that are reserved for sidecars or cannot be a single path
+/// component.
+fn check_name(path: &Path) -> ProjectResult<()> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if name.is_empty() || name.contains(['/', '\\', '\0']) || is_sidecar(path) {
+        return Err(ProjectError::InvalidName(name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "pulsar-project-{}-{}",
+                name,
+                std::process::id()
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A project with a `scripts` directory, beside a directory outside it.
+    fn project(name: &str) -> (TempDir, ProjectFiles, PathBuf) {
+        let dir = TempDir::new(name);
+        let root = dir.0.join("project");
+        let outside = dir.0.join("outside");
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        let files = ProjectFiles::new(&root).unwrap();
+        (dir, files, outside)
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn writes_and_replaces_files() {
+        let (_dir, files, _) = project("write");
+
+        let entry = files.write("scripts/player.rs", b"fn a() {}").unwrap();
+        assert_eq!(entry.path, "scripts/player.rs");
+        assert_eq!(entry.kind, EntryKind::File);
+        files.write("scripts/player.rs", b"fn b() {}").unwrap();
+
+        assert_eq!(files.read_text("scripts/player.rs").unwrap(), "fn b() {}");
+        assert_eq!(names(&files.root().join("scripts")), ["player.rs"]);
+    }
+
+    #[test]
+    fn rejects_paths_climbing_out_of_the_project() {
+        let (_dir, files, outside) = project("traversal");
+
+        for path in [
+            "../outside/escape.txt",
+            "scripts/../../outside/escape.txt",
+            "..",
+        ] {
+            let error = files.write(path, b"data").unwrap_err();
+            assert!(
+                matches!(error, ProjectError::OutsideProject(_)),
+                "{}: {:?}",
+                path,
+                error
+            );
+        }
+        assert!(matches!(
+            files.read("scripts/../../outside"),
+            Err(ProjectError::OutsideProject(_))
+        ));
+        assert!(names(&outside).is_empty());
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        let (_dir, files, outside) = project("absolute");
+        let absolute = outside.join("escape.txt");
+
+        let error = files
+            .write(&absolute.to_string_lossy(), b"data")
+            .unwrap_err();
+        assert!(matches!(error, ProjectError::OutsideProject(_)));
+        assert!(matches!(
+            files.create_file("\\escape.txt"),
+            Err(ProjectError::OutsideProject(_))
+        ));
+        assert!(names(&outside).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leading_out_of_the_project() {
+        let (_dir, files, outside) = project("symlink");
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+        std::os::unix::fs::symlink(&outside, files.root().join("linked")).unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.txt"), files.root().join("secret.txt"))
+            .unwrap();
+
+        for path in ["linked/escape.txt", "linked/secret.txt", "secret.txt"] {
+            let error = files.write(path, b"overwritten").unwrap_err();
+            assert!(
+                matches!(error, ProjectError::OutsideProject(_)),
+                "{}: {:?}",
+                path,
+                error
+            );
+        }
+        assert!(matches!(
+            files.read("secret.txt"),
+            Err(ProjectError::OutsideProject(_))
+        ));
+        assert_eq!(names(&outside), ["secret.txt"]);
+        assert_eq!(
+            fs::read_to_string(outside.join("secret.txt")).unwrap(),
+            "secret"
+        );
+
+        // Listings hide them
+        let listed: Vec<_> = files
+            .list("")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(listed, ["scripts"]);
+    }
+
+    #[test]
+    fn rejects_reserved_names() {
+        let (_dir, files, _) = project("reserved");
+        files.write("scripts/player.rs", b"").unwrap();
+
+        let sidecar = format!("scripts/player.rs.{}", META_EXTENSION);
+        for result in [
+            files.write(&sidecar, b"uuid: 0"),
+            files.create_file(&sidecar),
+            files.create_dir(&sidecar),
+            files.rename(
+                "scripts/player.rs",
+                &format!("player.rs.{}", META_EXTENSION),
+            ),
+            files.rename("scripts/player.rs", "nested/player.rs"),
+            files.rename("scripts/player.rs", ""),
+        ] {
+            assert!(
+                matches!(result, Err(ProjectError::InvalidName(_))),
+                "{:?}",
+                result
+            );
+        }
+        assert_eq!(names(&files.root().join("scripts")), ["player.rs"]);
+    }
+
+    #[test]
+    fn refuses_to_write_over_a_directory() {
+        let (_dir, files, _) = project("directory");
+
+        assert!(matches!(
+            files.write("scripts", b"data"),
+            Err(ProjectError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            files.write("", b"data"),
+            Err(ProjectError::AlreadyExists(_))
+        ));
+        assert!(files.root().join("scripts").is_dir());
+    }
+}
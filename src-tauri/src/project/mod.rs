@@ -0,0 +1,5 @@
+pub mod error;
+pub mod files;
+
+pub use error::{ProjectError, ProjectResult};
+pub use files::{EntryKind, FileEntry, ProjectFiles};
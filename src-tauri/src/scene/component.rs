@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// Data attached to an entity. An entity holds at most one component of
+/// each `ComponentKind`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Component {
+    Camera {
+        /// Vertical field of view in degrees.
+        fov_y: f32,
+        near: f32,
+        far: f32,
+    },
+    Light {
+        kind: LightKind,
+        /// Linear RGB color.
+        color: [f32; 3],
+        intensity: f32,
+    },
+    /// Renders a mesh, either a built-in primitive such as `"cube"` or an
+    /// asset path.
+    Mesh { mesh: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LightKind {
+    Directional,
+    Point,
+    Spot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ComponentKind {
+    Camera,
+    Light,
+    Mesh,
+}
+
+impl Component {
+    pub fn kind(&self) -> ComponentKind {
+        match self {
+            Component::Camera { .. } => ComponentKind::Camera,
+            Component::Light { .. } => ComponentKind::Light,
+            Component::Mesh { .. } => ComponentKind::Mesh,
+        }
+    }
+}
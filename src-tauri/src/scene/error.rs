@@ -0,0 +1,52 @@
+use super::world::EntityId;
+use crate::error::serialize_as_code_and_message;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Errors raised when editing, saving or loading the scene.
+#[derive(Debug, thiserror::Error)]
+pub enum SceneError {
+    #[error("No entity with id {0}")]
+    UnknownEntity(EntityId),
+
+    #[error("Entity {entity} cannot be parented to its own descendant {parent}")]
+    ParentCycle { entity: EntityId, parent: EntityId },
+
+    #[error("Another entity already has GUID {0}")]
+    DuplicateGuid(Uuid),
+
+    #[error("Failed to access scene file {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Malformed scene file: {0}")]
+    Format(#[from] serde_json::Error),
+
+    #[error("Scene file version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Invalid scene file: {0}")]
+    InvalidScene(String),
+}
+
+impl SceneError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SceneError::UnknownEntity(_) => "unknownEntity",
+            SceneError::ParentCycle { .. } => "parentCycle",
+            SceneError::DuplicateGuid(_) => "duplicateGuid",
+            SceneError::Io { .. } => "io",
+            SceneError::Format(_) => "format",
+            SceneError::UnsupportedVersion { .. } => "unsupportedVersion",
+            SceneError::InvalidScene(_) => "invalidScene",
+        }
+    }
+}
+
+serialize_as_code_and_message!(SceneError);
+
+pub type SceneResult<T> = Result<T, SceneError>;
@@ -0,0 +1,451 @@
+//! On-disk scene format.
+//!
+//! Scenes are saved as pretty-printed JSON so they diff well under version
+//! control. Entities are listed parents first and refer to each other by
+//! GUID; runtime `EntityId`s are never written. Every file carries a
+//! `version`, and older files are upgraded on load by the functions in
+//! `MIGRATIONS` before being deserialized.
+
+use super::{
+    component::{Component, LightKind},
+    error::{SceneError, SceneResult},
+    transform::Transform,
+    world::World,
+};
+use crate::atomic;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, fs, path::Path};
+use uuid::Uuid;
+
+/// Extension the editor saves scenes with.
+pub const SCENE_EXTENSION: &str = "scene";
+
+/// Version written by `save`. Bump it together with a new migration.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// Upgrades a scene document from version `n` to `n + 1`, where `n` is the
+/// index of the function.
+type Migration = fn(&mut Value) -> SceneResult<()>;
+
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneFile {
+    pub version: u32,
+    /// Entities in hierarchy order, parents before their children.
+    pub entities: Vec<EntityRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityRecord {
+    pub guid: Uuid,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub transform: Transform,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<Component>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+impl SceneFile {
+    pub fn from_world(world: &World) -> Self {
+        let entities = world
+            .depth_first(None)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| world.get(id).ok())
+            .map(|entity| EntityRecord {
+                guid: entity.guid,
+                name: entity.name.clone(),
+                parent: entity
+                    .parent
+                    .and_then(|parent| world.get(parent).ok())
+                    .map(|parent| parent.guid),
+                visible: entity.visible,
+                transform: entity.transform,
+                components: entity.components.clone(),
+            })
+            .collect();
+
+        Self {
+            version: SCENE_FORMAT_VERSION,
+            entities,
+        }
+    }
+
+    /// Builds a world from the records. Parents must be listed before their
+    /// children and GUIDs must be unique.
+    pub fn into_world(self) -> SceneResult<World> {
+        let mut world = World::new();
+        let mut ids = HashMap::with_capacity(self.entities.len());
+
+        for record in self.entities {
+            let parent = match record.parent {
+                Some(guid) => Some(*ids.get(&guid).ok_or_else(|| {
+                    SceneError::InvalidScene(format!(
+                        "entity {} is listed before its parent {}",
+                        record.guid, guid
+                    ))
+                })?),
+                None => None,
+            };
+
+            let id = world.spawn_with_guid(record.guid, record.name, parent)?;
+            world.set_visible(id, record.visible)?;
+            world.set_transform(id, record.transform)?;
+            for component in record.components {
+                world.set_component(id, component)?;
+            }
+            ids.insert(record.guid, id);
+        }
+        Ok(world)
+    }
+
+    /// Parses a scene document of any supported version.
+    pub fn from_json(text: &str) -> SceneResult<Self> {
+        let mut document: Value = serde_json::from_str(text)?;
+        let found = document_version(&document)?;
+        if found > SCENE_FORMAT_VERSION {
+            return Err(SceneError::UnsupportedVersion {
+                found,
+                supported: SCENE_FORMAT_VERSION,
+            });
+        }
+
+        for (version, migrate) in MIGRATIONS.iter().enumerate().skip(found as usize) {
+            debug!("Migrating scene from version {}", version);
+            migrate(&mut document)?;
+        }
+        Ok(serde_json::from_value(document)?)
+    }
+
+    pub fn to_json(&self) -> SceneResult<String> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        Ok(text)
+    }
+}
+
+/// Writes the world to `path`. The file is replaced atomically, so a failed
+/// save never leaves a truncated scene behind.
+pub fn save(world: &World, path: &Path) -> SceneResult<()> {
+    let text = SceneFile::from_world(world).to_json()?;
+    atomic::write(path, text).map_err(|source| SceneError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    info!("Saved {} entities to {}", world.len(), path.display());
+    Ok(())
+}
+
+pub fn load(path: &Path) -> SceneResult<World> {
+    let text = fs::read_to_string(path).map_err(|source| SceneError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let world = SceneFile::from_json(&text)?.into_world()?;
+    info!("Loaded {} entities from {}", world.len(), path.display());
+    Ok(world)
+}
+
+/// Version 0 is the bare array the editor used to keep in `localStorage`.
+fn document_version(document: &Value) -> SceneResult<u32> {
+    match document {
+        Value::Array(_) => Ok(0),
+        Value::Object(object) => object
+            .get("version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| SceneError::InvalidScene("missing or invalid version".into())),
+        _ => Err(SceneError::InvalidScene(
+            "expected an object or an array".into(),
+        )),
+    }
+}
+
+/// Converts the editor's flat `SceneObject` list. The object `type` becomes
+/// a component and every object gets a new GUID.
+fn migrate_v0_to_v1(document: &mut Value) -> SceneResult<()> {
+    let Value::Array(objects) = document.take() else {
+        return Err(SceneError::InvalidScene(
+            "expected an array of objects".into(),
+        ));
+    };
+
+    let entities = objects
+        .into_iter()
+        .map(|object| {
+            let Value::Object(mut object) = object else {
+                return Err(SceneError::InvalidScene("expected an object".into()));
+            };
+
+            let mut entity = Map::new();
+            entity.insert("guid".into(), json!(Uuid::new_v4()));
+            for key in ["name", "visible"] {
+                if let Some(value) = object.remove(key) {
+                    entity.insert(key.into(), value);
+                }
+            }
+            let transform: Map<String, Value> = ["position", "rotation", "scale"]
+                .into_iter()
+                .filter_map(|key| Some((key.to_string(), object.remove(key)?)))
+                .collect();
+            entity.insert("transform".into(), Value::Object(transform));
+
+            if let Some(kind) = object.get("type").and_then(Value::as_str) {
+                let component = legacy_component(kind);
+                entity.insert("components".into(), json!([component]));
+            }
+            Ok(Value::Object(entity))
+        })
+        .collect::<SceneResult<Vec<_>>>()?;
+
+    *document = json!({ "version": 1, "entities": entities });
+    Ok(())
+}
+
+fn legacy_component(kind: &str) -> Component {
+    let light = |kind| Component::Light {
+        kind,
+        color: [1.0; 3],
+        intensity: 1.0,
+    };
+
+    match kind {
+        "Camera" => Component::Camera {
+            fov_y: 60.0,
+            near: 0.1,
+            far: 1000.0,
+        },
+        "Directional Light" => light(LightKind::Directional),
+        "Point Light" => light(LightKind::Point),
+        "Spot Light" => light(LightKind::Spot),
+        mesh => Component::Mesh {
+            mesh: mesh.to_lowercase(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::ComponentKind;
+    use glam::Vec3;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/scenes")
+            .join(name)
+    }
+
+    fn read(name: &str) -> String {
+        fs::read_to_string(fixture(name)).unwrap()
+    }
+
+    /// A scratch directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("pulsar-scene-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn resave(text: &str) -> String {
+        let world = SceneFile::from_json(text).unwrap().into_world().unwrap();
+        SceneFile::from_world(&world).to_json().unwrap()
+    }
+
+    fn entity<'a>(world: &'a World, name: &str) -> &'a crate::scene::Entity {
+        world
+            .depth_first(None)
+            .unwrap()
+            .into_iter()
+            .map(|id| world.get(id).unwrap())
+            .find(|entity| entity.name == name)
+            .unwrap_or_else(|| panic!("no entity {}", name))
+    }
+
+    #[test]
+    fn saves_a_loaded_scene_byte_for_byte() {
+        let dir = TempDir::new("round-trip");
+        let path = dir.0.join("level.scene");
+
+        let world = load(&fixture("level.scene")).unwrap();
+        save(&world, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), read("level.scene"));
+        let names: Vec<_> = fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["level.scene"]);
+    }
+
+    #[test]
+    fn loads_the_hierarchy_and_components() {
+        let world = load(&fixture("level.scene")).unwrap();
+        assert_eq!(world.len(), 6);
+        assert_eq!(world.roots().len(), 4);
+
+        let lamp = entity(&world, "Lamp");
+        assert!(!lamp.visible);
+        let parent = world.get(lamp.parent.unwrap()).unwrap();
+        assert_eq!(parent.name, "Crate");
+        assert_eq!(
+            parent.component(ComponentKind::Mesh),
+            Some(&Component::Mesh {
+                mesh: "meshes/crate.mesh".to_string()
+            })
+        );
+        assert_eq!(parent.transform.scale, Vec3::splat(0.75));
+        assert_eq!(
+            lamp.component(ComponentKind::Light),
+            Some(&Component::Light {
+                kind: LightKind::Point,
+                color: [1.0, 0.6, 0.2],
+                intensity: 0.8,
+            })
+        );
+    }
+
+    #[test]
+    fn fills_in_defaults_once() {
+        let world = SceneFile::from_json(&read("minimal.scene"))
+            .unwrap()
+            .into_world()
+            .unwrap();
+        let child = entity(&world, "Child");
+        assert!(child.visible);
+        assert_eq!(child.transform.position, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(child.transform.scale, Vec3::ONE);
+        assert!(child.components.is_empty());
+
+        // Defaults are written out, after which saving is stable
+        let saved = resave(&read("minimal.scene"));
+        assert_ne!(saved, read("minimal.scene"));
+        assert_eq!(resave(&saved), saved);
+    }
+
+    #[test]
+    fn migrates_the_editor_object_list() {
+        let text = read("legacy_v0.scene");
+        let scene = SceneFile::from_json(&text).unwrap();
+        assert_eq!(scene.version, SCENE_FORMAT_VERSION);
+
+        let world = scene.into_world().unwrap();
+        assert_eq!(world.len(), 3);
+        assert_eq!(world.roots().len(), 3);
+
+        let camera = entity(&world, "Main Camera");
+        assert_eq!(camera.transform.position, Vec3::new(0.0, 2.0, 10.0));
+        assert!(matches!(
+            camera.component(ComponentKind::Camera),
+            Some(Component::Camera { .. })
+        ));
+        assert!(matches!(
+            entity(&world, "Directional Light").component(ComponentKind::Light),
+            Some(Component::Light {
+                kind: LightKind::Directional,
+                ..
+            })
+        ));
+
+        let cube = entity(&world, "Cube");
+        assert!(!cube.visible);
+        assert_eq!(cube.transform.scale, Vec3::new(2.0, 1.0, 2.0));
+        assert_eq!(
+            cube.components,
+            [Component::Mesh {
+                mesh: "cube".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn saves_a_migrated_scene_byte_for_byte_after_the_first_save() {
+        let dir = TempDir::new("migrated");
+        let path = dir.0.join("legacy.scene");
+
+        // Migrated GUIDs are random, so only the saved file is stable
+        save(&load(&fixture("legacy_v0.scene")).unwrap(), &path).unwrap();
+        let first = fs::read_to_string(&path).unwrap();
+        assert!(first.starts_with("{\n  \"version\": 1,"));
+
+        save(&load(&path).unwrap(), &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn gives_migrated_entities_distinct_guids() {
+        let scene = SceneFile::from_json(&read("legacy_v0.scene")).unwrap();
+        let mut guids: Vec<_> = scene.entities.iter().map(|entity| entity.guid).collect();
+        guids.sort();
+        guids.dedup();
+        assert_eq!(guids.len(), 3);
+        assert!(!guids.contains(&Uuid::nil()));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let text = read("level.scene").replacen("\"version\": 1", "\"version\": 2", 1);
+        assert!(matches!(
+            SceneFile::from_json(&text),
+            Err(SceneError::UnsupportedVersion {
+                found: 2,
+                supported: SCENE_FORMAT_VERSION
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        for text in [r#""scene""#, r#"{ "entities": [] }"#, r#"[1, 2]"#] {
+            assert!(
+                matches!(SceneFile::from_json(text), Err(SceneError::InvalidScene(_))),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_children_listed_before_their_parents() {
+        let mut scene = SceneFile::from_json(&read("level.scene")).unwrap();
+        scene.entities.swap(2, 3);
+        assert!(matches!(
+            scene.into_world(),
+            Err(SceneError::InvalidScene(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_duplicate_guids() {
+        let mut scene = SceneFile::from_json(&read("level.scene")).unwrap();
+        scene.entities[1].guid = scene.entities[0].guid;
+        assert!(matches!(
+            scene.into_world(),
+            Err(SceneError::DuplicateGuid(_))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+pub mod component;
+pub mod error;
+pub mod file;
+pub mod transform;
+pub mod world;
+
+pub use component::{Component, ComponentKind, LightKind};
+pub use error::SceneError;
+pub use file::{SceneFile, SCENE_FORMAT_VERSION};
+pub use transform::Transform;
+pub use world::{Entity, EntityId, World};
+
+use glam::Vec3;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// World shared between the Tauri commands and the viewport render loops.
+pub type SharedWorld = Arc<RwLock<World>>;
+
+/// Name of the Tauri event emitted after the scene changed, with the new
+/// world revision as payload.
+pub const SCENE_CHANGED_EVENT: &str = "scene-changed";
+
+/// Tauri state holding the scene edited by the Level editor.
+#[derive(Default)]
+pub struct SceneState {
+    pub world: SharedWorld,
+}
+
+impl SceneState {
+    /// State holding the starter scene.
+    pub fn new() -> Self {
+        Self {
+            world: Arc::new(RwLock::new(starter_world())),
+        }
+    }
+}
+
+/// The scene a new level starts with: a camera, a directional light and a
+/// cube at the origin.
+pub fn starter_world() -> World {
+    let mut world = World::new();
+    let entities = [
+        (
+            "Camera_1",
+            Vec3::new(0.0, 0.0, -10.0),
+            Component::Camera {
+                fov_y: 60.0,
+                near: 0.1,
+                far: 1000.0,
+            },
+        ),
+        (
+            "Light_1",
+            Vec3::new(5.0, 5.0, 5.0),
+            Component::Light {
+                kind: LightKind::Directional,
+                color: [1.0; 3],
+                intensity: 1.0,
+            },
+        ),
+        (
+            "Cube_1",
+            Vec3::ZERO,
+            Component::Mesh {
+                mesh: "cube".into(),
+            },
+        ),
+    ];
+
+    for (name, position, component) in entities {
+        // Spawning roots into a fresh world cannot fail
+        if let Ok(id) = world.spawn(name, None) {
+            let _ = world.set_transform(id, Transform::from_position(position));
+            let _ = world.set_component(id, component);
+        }
+    }
+    world
+}
@@ -0,0 +1,91 @@
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Local transform of an entity relative to its parent.
+///
+/// Rotation is kept as XYZ Euler angles in degrees, the way the properties
+/// panel edits it. Vectors serialize as `{ x, y, z }` like the editor's
+/// `SceneObject`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transform {
+    #[serde(with = "vec3")]
+    pub position: Vec3,
+    #[serde(with = "vec3")]
+    pub rotation: Vec3,
+    #[serde(with = "vec3")]
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        position: Vec3::ZERO,
+        rotation: Vec3::ZERO,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_position(position: Vec3) -> Self {
+        Self {
+            position,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn quat(&self) -> Quat {
+        let radians = self.rotation * (std::f32::consts::PI / 180.0);
+        Quat::from_euler(EulerRot::XYZ, radians.x, radians.y, radians.z)
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.quat(), self.position)
+    }
+
+    pub fn from_scale_rotation_translation(scale: Vec3, rotation: Quat, position: Vec3) -> Self {
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        Self {
+            position,
+            rotation: Vec3::new(x, y, z) * (180.0 / std::f32::consts::PI),
+            scale,
+        }
+    }
+
+    /// Decomposes an affine matrix. Shear, which a hierarchy with non-uniform
+    /// scale can produce, is lost.
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, position) = matrix.to_scale_rotation_translation();
+        Self::from_scale_rotation_translation(scale, rotation, position)
+    }
+}
+
+/// Serializes a `Vec3` as `{ x, y, z }`.
+mod vec3 {
+    use glam::Vec3;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Xyz {
+        x: f32,
+        y: f32,
+        z: f32,
+    }
+
+    pub fn serialize<S: Serializer>(v: &Vec3, serializer: S) -> Result<S::Ok, S::Error> {
+        Xyz {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec3, D::Error> {
+        let Xyz { x, y, z } = Xyz::deserialize(deserializer)?;
+        Ok(Vec3::new(x, y, z))
+    }
+}
@@ -0,0 +1,411 @@
+use super::{
+    component::{Component, ComponentKind},
+    error::{SceneError, SceneResult},
+    transform::Transform,
+};
+use glam::Mat4;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+use uuid::Uuid;
+
+/// Runtime identifier of an entity, unique within a `World`. Not persisted;
+/// scene files refer to entities by their GUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityId(pub u64);
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A named node of the scene hierarchy.
+///
+/// Entities are only changed through `World`, which keeps the parent and
+/// child links consistent and bumps its revision.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entity {
+    pub id: EntityId,
+    /// Persistent identifier, stable across saving and loading.
+    pub guid: Uuid,
+    pub name: String,
+    pub parent: Option<EntityId>,
+    /// Children in hierarchy order.
+    pub children: Vec<EntityId>,
+    pub visible: bool,
+    pub transform: Transform,
+    pub components: Vec<Component>,
+}
+
+impl Entity {
+    pub fn component(&self, kind: ComponentKind) -> Option<&Component> {
+        self.components
+            .iter()
+            .find(|component| component.kind() == kind)
+    }
+}
+
+/// Filter for `World::query`. Empty fields match every entity.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SceneQuery {
+    /// Only this entity and its descendants.
+    pub root: Option<EntityId>,
+    /// Case-insensitive part of the entity name.
+    pub name: Option<String>,
+    /// Only entities with a component of this kind.
+    pub component: Option<ComponentKind>,
+}
+
+/// The authoritative scene: entities with hierarchical transforms.
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    entities: HashMap<EntityId, Entity>,
+    guids: HashMap<Uuid, EntityId>,
+    /// Top-level entities in hierarchy order.
+    roots: Vec<EntityId>,
+    next_id: u64,
+    revision: u64,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counter bumped by every change, so observers such as the render loop
+    /// can tell whether the world changed since they last looked.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn roots(&self) -> &[EntityId] {
+        &self.roots
+    }
+
+    pub fn get(&self, id: EntityId) -> SceneResult<&Entity> {
+        self.entities.get(&id).ok_or(SceneError::UnknownEntity(id))
+    }
+
+    pub fn find_by_guid(&self, guid: Uuid) -> Option<EntityId> {
+        self.guids.get(&guid).copied()
+    }
+
+    fn get_mut(&mut self, id: EntityId) -> SceneResult<&mut Entity> {
+        self.entities
+            .get_mut(&id)
+            .ok_or(SceneError::UnknownEntity(id))
+    }
+
+    fn touch(&mut self) {
+        self.revision += 1;
+    }
+
+    /// Removes every entity.
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.guids.clear();
+        self.roots.clear();
+        self.touch();
+    }
+
+    /// Replaces the contents with `other`, e.g. a loaded scene. The revision
+    /// keeps counting up so observers notice the change.
+    pub fn replace(&mut self, other: World) {
+        let revision = self.revision;
+        *self = other;
+        self.revision = revision;
+        self.touch();
+    }
+
+    /// Adds an entity with a new GUID and an identity transform as the last
+    /// child of `parent`, or as the last root.
+    pub fn spawn(
+        &mut self,
+        name: impl Into<String>,
+        parent: Option<EntityId>,
+    ) -> SceneResult<EntityId> {
+        self.spawn_with_guid(Uuid::new_v4(), name, parent)
+    }
+
+    /// Like `spawn`, for an entity whose GUID is already known.
+    pub fn spawn_with_guid(
+        &mut self,
+        guid: Uuid,
+        name: impl Into<String>,
+        parent: Option<EntityId>,
+    ) -> SceneResult<EntityId> {
+        if let Some(parent) = parent {
+            self.get(parent)?;
+        }
+        if self.guids.contains_key(&guid) {
+            return Err(SceneError::DuplicateGuid(guid));
+        }
+
+        self.next_id += 1;
+        let id = EntityId(self.next_id);
+        self.guids.insert(guid, id);
+        self.entities.insert(
+            id,
+            Entity {
+                id,
+                guid,
+                name: name.into(),
+                parent,
+                children: Vec::new(),
+                visible: true,
+                transform: Transform::IDENTITY,
+                components: Vec::new(),
+            },
+        );
+        self.attach(id, parent);
+        self.touch();
+        Ok(id)
+    }
+
+    /// Removes an entity together with its descendants. Returns the removed
+    /// ids, parents before children.
+    pub fn despawn(&mut self, id: EntityId) -> SceneResult<Vec<EntityId>> {
+        let removed = self.depth_first(Some(id))?;
+        self.detach(id);
+        for id in &removed {
+            if let Some(entity) = self.entities.remove(id) {
+                self.guids.remove(&entity.guid);
+            }
+        }
+        self.touch();
+        Ok(removed)
+    }
+
+    /// Moves an entity under `parent`, or to the roots, as its last child.
+    ///
+    /// With `keep_world_transform` the local transform is adjusted so the
+    /// entity stays where it is; otherwise it keeps its local transform and
+    /// moves with the new parent.
+    pub fn reparent(
+        &mut self,
+        id: EntityId,
+        parent: Option<EntityId>,
+        keep_world_transform: bool,
+    ) -> SceneResult<()> {
+        self.get(id)?;
+        if let Some(parent) = parent {
+            // Walking up from the new parent must not reach the entity
+            let mut ancestor = Some(parent);
+            while let Some(current) = ancestor {
+                if current == id {
+                    return Err(SceneError::ParentCycle { entity: id, parent });
+                }
+                ancestor = self.get(current)?.parent;
+            }
+        }
+
+        if keep_world_transform {
+            let world = self.world_matrix(id)?;
+            let parent_world = match parent {
+                Some(parent) => self.world_matrix(parent)?,
+                None => Mat4::IDENTITY,
+            };
+            self.get_mut(id)?.transform = Transform::from_matrix(parent_world.inverse() * world);
+        }
+
+        self.detach(id);
+        self.get_mut(id)?.parent = parent;
+        self.attach(id, parent);
+        self.touch();
+        Ok(())
+    }
+
+    fn attach(&mut self, id: EntityId, parent: Option<EntityId>) {
+        match parent.and_then(|parent| self.entities.get_mut(&parent)) {
+            Some(parent) => parent.children.push(id),
+            None => self.roots.push(id),
+        }
+    }
+
+    fn detach(&mut self, id: EntityId) {
+        let parent = self.entities.get(&id).and_then(|entity| entity.parent);
+        let siblings = match parent.and_then(|parent| self.entities.get_mut(&parent)) {
+            Some(parent) => &mut parent.children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|&sibling| sibling != id);
+    }
+
+    pub fn set_name(&mut self, id: EntityId, name: impl Into<String>) -> SceneResult<()> {
+        self.get_mut(id)?.name = name.into();
+        self.touch();
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, id: EntityId, visible: bool) -> SceneResult<()> {
+        self.get_mut(id)?.visible = visible;
+        self.touch();
+        Ok(())
+    }
+
+    pub fn set_transform(&mut self, id: EntityId, transform: Transform) -> SceneResult<()> {
+        self.get_mut(id)?.transform = transform;
+        self.touch();
+        Ok(())
+    }
+
+    /// Adds a component, replacing one of the same kind. Returns the
+    /// replaced component.
+    pub fn set_component(
+        &mut self,
+        id: EntityId,
+        component: Component,
+    ) -> SceneResult<Option<Component>> {
+        let components = &mut self.get_mut(id)?.components;
+        let replaced = match components
+            .iter_mut()
+            .find(|existing| existing.kind() == component.kind())
+        {
+            Some(existing) => Some(std::mem::replace(existing, component)),
+            None => {
+                components.push(component);
+                None
+            }
+        };
+        self.touch();
+        Ok(replaced)
+    }
+
+    pub fn remove_component(
+        &mut self,
+        id: EntityId,
+        kind: ComponentKind,
+    ) -> SceneResult<Option<Component>> {
+        let components = &mut self.get_mut(id)?.components;
+        let removed = components
+            .iter()
+            .position(|component| component.kind() == kind)
+            .map(|index| components.remove(index));
+        if removed.is_some() {
+            self.touch();
+        }
+        Ok(removed)
+    }
+
+    /// Entity ids in hierarchy order, parents before their children. Starts
+    /// at `root` if given, otherwise covers the whole world.
+    pub fn depth_first(&self, root: Option<EntityId>) -> SceneResult<Vec<EntityId>> {
+        let mut stack: Vec<EntityId> = match root {
+            Some(root) => {
+                self.get(root)?;
+                vec![root]
+            }
+            None => self.roots.iter().rev().copied().collect(),
+        };
+
+        let mut order = Vec::with_capacity(self.entities.len());
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            if let Some(entity) = self.entities.get(&id) {
+                stack.extend(entity.children.iter().rev());
+            }
+        }
+        Ok(order)
+    }
+
+    /// Entities matching `query`, in hierarchy order.
+    pub fn query(&self, query: &SceneQuery) -> SceneResult<Vec<&Entity>> {
+        let name = query.name.as_ref().map(|name| name.to_lowercase());
+
+        Ok(self
+            .depth_first(query.root)?
+            .into_iter()
+            .filter_map(|id| self.entities.get(&id))
+            .filter(|entity| {
+                name.as_ref()
+                    .map_or(true, |name| entity.name.to_lowercase().contains(name))
+            })
+            .filter(|entity| {
+                query
+                    .component
+                    .map_or(true, |kind| entity.component(kind).is_some())
+            })
+            .collect())
+    }
+
+    /// Transform from the entity's local space to world space.
+    pub fn world_matrix(&self, id: EntityId) -> SceneResult<Mat4> {
+        let entity = self.get(id)?;
+        let mut matrix = entity.transform.matrix();
+        let mut parent = entity.parent;
+        while let Some(id) = parent {
+            let entity = self.get(id)?;
+            matrix = entity.transform.matrix() * matrix;
+            parent = entity.parent;
+        }
+        Ok(matrix)
+    }
+
+    /// Visits the entities that are visible along with all their ancestors,
+    /// in hierarchy order, with their world matrices.
+    pub fn visit_visible(&self, mut visit: impl FnMut(&Entity, Mat4)) {
+        let mut stack: Vec<(EntityId, Mat4)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&id| (id, Mat4::IDENTITY))
+            .collect();
+
+        while let Some((id, parent_world)) = stack.pop() {
+            let Some(entity) = self.entities.get(&id) else {
+                continue;
+            };
+            if !entity.visible {
+                continue;
+            }
+            let world = parent_world * entity.transform.matrix();
+            visit(entity, world);
+            stack.extend(entity.children.iter().rev().map(|&child| (child, world)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube() -> Component {
+        Component::Mesh {
+            mesh: "cube".to_string(),
+        }
+    }
+
+    #[test]
+    fn bumps_the_revision_when_a_component_is_removed() {
+        let mut world = World::new();
+        let id = world.spawn("Cube", None).unwrap();
+        world.set_component(id, cube()).unwrap();
+
+        let revision = world.revision();
+        let removed = world.remove_component(id, ComponentKind::Mesh).unwrap();
+        assert_eq!(removed, Some(cube()));
+        assert_eq!(world.revision(), revision + 1);
+    }
+
+    #[test]
+    fn keeps_the_revision_when_nothing_is_removed() {
+        let mut world = World::new();
+        let id = world.spawn("Empty", None).unwrap();
+
+        let revision = world.revision();
+        let removed = world.remove_component(id, ComponentKind::Light).unwrap();
+        assert_eq!(removed, None);
+        assert_eq!(world.revision(), revision);
+    }
+}
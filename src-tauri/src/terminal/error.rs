@@ -0,0 +1,41 @@
+use super::session::SessionId;
+use crate::error::serialize_as_code_and_message;
+use std::path::PathBuf;
+
+/// Errors raised by terminal sessions.
+#[derive(Debug, thiserror::Error)]
+pub enum TerminalError {
+    #[error("Failed to start the terminal: {0}")]
+    Pty(String),
+
+    #[error("Unknown terminal session {0}")]
+    UnknownSession(SessionId),
+
+    #[error("{} is not a directory", .0.display())]
+    InvalidDirectory(PathBuf),
+
+    #[error("Failed to write to the terminal: {0}")]
+    Write(#[from] std::io::Error),
+}
+
+impl TerminalError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TerminalError::Pty(_) => "pty",
+            TerminalError::UnknownSession(_) => "unknownSession",
+            TerminalError::InvalidDirectory(_) => "invalidDirectory",
+            TerminalError::Write(_) => "write",
+        }
+    }
+
+    /// Wraps an error of the pseudo-terminal backend, which only reports
+    /// `anyhow` errors.
+    pub fn pty(error: impl std::fmt::Display) -> Self {
+        TerminalError::Pty(error.to_string())
+    }
+}
+
+serialize_as_code_and_message!(TerminalError);
+
+pub type TerminalResult<T> = Result<T, TerminalError>;
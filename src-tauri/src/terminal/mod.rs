@@ -0,0 +1,263 @@
+pub mod error;
+pub mod session;
+
+pub use error::{TerminalError, TerminalResult};
+pub use session::{
+    SessionId, TerminalEvent, TerminalEventPayload, TerminalOptions, TerminalSession, TerminalSink,
+    TERMINAL_EVENT,
+};
+
+use log::info;
+use parking_lot::Mutex;
+use portable_pty::CommandBuilder;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Tauri state holding the open terminal sessions.
+///
+/// The map is only locked to look sessions up; input is written with the
+/// session's own lock, so a shell that stops reading blocks nothing else.
+#[derive(Default)]
+pub struct TerminalState {
+    sessions: Arc<Mutex<HashMap<SessionId, Arc<TerminalSession>>>>,
+    next_id: AtomicU64,
+}
+
+impl TerminalState {
+    /// Starts a session reporting its events to `sink`. The session is
+    /// removed once its shell exits.
+    pub fn open(&self, options: &TerminalOptions, sink: TerminalSink) -> TerminalResult<SessionId> {
+        self.open_command(CommandBuilder::new_default_prog(), options, sink)
+    }
+
+    /// Starts a session running `command` instead of the default shell.
+    fn open_command(
+        &self,
+        command: CommandBuilder,
+        options: &TerminalOptions,
+        sink: TerminalSink,
+    ) -> TerminalResult<SessionId> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let sessions = Arc::downgrade(&self.sessions);
+
+        // Hold the lock until the session is inserted, so a shell exiting
+        // right away cannot be removed before it was added
+        let mut open = self.sessions.lock();
+        let session = TerminalSession::spawn_command(id, command, options, move |event| {
+            let exited = matches!(event, TerminalEvent::Exited { .. });
+            sink(TerminalEventPayload { session: id, event });
+            if exited {
+                if let Some(sessions) = sessions.upgrade() {
+                    sessions.lock().remove(&id);
+                }
+            }
+        })?;
+        open.insert(id, Arc::new(session));
+
+        info!("Opened terminal {}", id);
+        Ok(id)
+    }
+
+    pub fn write(&self, id: SessionId, data: &[u8]) -> TerminalResult<()> {
+        self.with_session(id, |session| session.write(data))
+    }
+
+    pub fn resize(&self, id: SessionId, cols: u16, rows: u16) -> TerminalResult<()> {
+        self.with_session(id, |session| session.resize(cols, rows))
+    }
+
+    /// Kills the shell of a session and forgets it.
+    pub fn close(&self, id: SessionId) -> TerminalResult<()> {
+        let session = self
+            .sessions
+            .lock()
+            .remove(&id)
+            .ok_or(TerminalError::UnknownSession(id))?;
+        // A blocked write may still hold the session, kill the shell now
+        session.kill();
+        info!("Closed terminal {}", id);
+        Ok(())
+    }
+
+    pub fn sessions(&self) -> Vec<SessionId> {
+        let mut ids: Vec<_> = self.sessions.lock().keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Kills every shell, when the editor closes.
+    pub fn shutdown(&self) {
+        let sessions: Vec<_> = self.sessions.lock().drain().collect();
+        if !sessions.is_empty() {
+            info!("Closing {} terminals", sessions.len());
+        }
+        for (_, session) in sessions {
+            session.kill();
+        }
+    }
+
+    /// Runs `f` on a session without holding the lock on the map.
+    fn with_session<T>(
+        &self,
+        id: SessionId,
+        f: impl FnOnce(&TerminalSession) -> TerminalResult<T>,
+    ) -> TerminalResult<T> {
+        let session = self
+            .sessions
+            .lock()
+            .get(&id)
+            .cloned()
+            .ok_or(TerminalError::UnknownSession(id))?;
+        f(&session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::{unbounded, Receiver};
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Opens a `sh` session whose events arrive on the returned receiver.
+    fn open_sh(state: &TerminalState) -> (SessionId, Receiver<TerminalEventPayload>) {
+        let (sender, events) = unbounded();
+        let sink: TerminalSink = Arc::new(move |payload| {
+            let _ = sender.send(payload);
+        });
+        let id = state
+            .open_command(CommandBuilder::new("sh"), &TerminalOptions::default(), sink)
+            .unwrap();
+        (id, events)
+    }
+
+    /// Waits for output containing `expected`, returning everything read.
+    fn wait_for_output(events: &Receiver<TerminalEventPayload>, expected: &str) -> String {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut output = String::new();
+        while !output.contains(expected) {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match events.recv_timeout(timeout).map(|payload| payload.event) {
+                Ok(TerminalEvent::Output { data }) => output += &data,
+                event => panic!(
+                    "{:?} while waiting for {:?} in {:?}",
+                    event, expected, output
+                ),
+            }
+        }
+        output
+    }
+
+    fn wait_for_exit(events: &Receiver<TerminalEventPayload>) -> Option<u32> {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match events.recv_timeout(timeout).unwrap().event {
+                TerminalEvent::Exited { code } => return code,
+                TerminalEvent::Output { .. } => {}
+            }
+        }
+    }
+
+    #[test]
+    fn runs_input_through_a_shell() {
+        let state = TerminalState::default();
+        let (id, events) = open_sh(&state);
+        assert_eq!(state.sessions(), [id]);
+
+        // The echoed command does not contain the result
+        state.write(id, b"echo $((6 * 7))\n").unwrap();
+        wait_for_output(&events, "42");
+
+        state.resize(id, 100, 30).unwrap();
+        state.write(id, b"stty size\n").unwrap();
+        wait_for_output(&events, "30 100");
+
+        state.close(id).unwrap();
+        assert!(state.sessions().is_empty());
+        wait_for_exit(&events);
+        assert!(matches!(
+            state.write(id, b"echo\n"),
+            Err(TerminalError::UnknownSession(_))
+        ));
+    }
+
+    #[test]
+    fn a_blocked_write_holds_up_no_other_call() {
+        let state = Arc::new(TerminalState::default());
+        let (id, events) = open_sh(&state);
+        let (other, _) = open_sh(&state);
+
+        // Nothing reads the input any more, so the pty fills up and the write
+        // blocks. Canonical mode would drop input beyond a line instead.
+        state
+            .write(id, b"stty raw -echo; echo sleeping; sleep 2\n")
+            .unwrap();
+        wait_for_output(&events, "sleeping\n");
+        let (done, written) = unbounded();
+        let writer = state.clone();
+        std::thread::spawn(move || done.send(writer.write(id, &vec![b'x'; 1 << 20])));
+        assert!(written.recv_timeout(Duration::from_millis(100)).is_err());
+
+        state.resize(id, 100, 30).unwrap();
+        state.write(other, b"echo\n").unwrap();
+        assert_eq!(state.sessions(), [id, other]);
+
+        // Nor does closing the session
+        state.close(id).unwrap();
+        assert_eq!(state.sessions(), [other]);
+    }
+
+    #[test]
+    fn forgets_a_session_once_its_shell_exits() {
+        let state = TerminalState::default();
+        let (id, events) = open_sh(&state);
+
+        state.write(id, b"exit 3\n").unwrap();
+        assert_eq!(wait_for_exit(&events), Some(3));
+
+        // The sink removes the session after reporting the exit
+        let deadline = Instant::now() + TIMEOUT;
+        while !state.sessions().is_empty() {
+            assert!(Instant::now() < deadline, "session {} was not removed", id);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(matches!(
+            state.close(id),
+            Err(TerminalError::UnknownSession(_))
+        ));
+    }
+
+    #[test]
+    fn opens_sessions_with_distinct_ids() {
+        let state = TerminalState::default();
+        let (first, _) = open_sh(&state);
+        let (second, _) = open_sh(&state);
+        assert_ne!(first, second);
+        assert_eq!(state.sessions(), [first, second]);
+
+        state.shutdown();
+        assert!(state.sessions().is_empty());
+    }
+
+    #[test]
+    fn rejects_a_missing_working_directory() {
+        let state = TerminalState::default();
+        let options = TerminalOptions {
+            cwd: Some(std::env::temp_dir().join("pulsar-terminal-missing")),
+            ..TerminalOptions::default()
+        };
+        let sink: TerminalSink = Arc::new(|_| {});
+        let error = state
+            .open_command(CommandBuilder::new("sh"), &options, sink)
+            .unwrap_err();
+        assert!(matches!(error, TerminalError::InvalidDirectory(_)));
+        assert!(state.sessions().is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use super::error::{TerminalError, TerminalResult};
+use log::{debug, warn};
+use parking_lot::Mutex;
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{ErrorKind, Read, Write},
+    path::PathBuf,
+    sync::Arc,
+    thread,
+};
+
+/// Name of the Tauri event sessions report `TerminalEvent`s on, with a
+/// `TerminalEventPayload`.
+pub const TERMINAL_EVENT: &str = "terminal";
+
+pub type SessionId = u64;
+
+/// How a session starts. The shell is always the user's default one.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TerminalOptions {
+    /// Working directory, by default the open project or the home directory.
+    pub cwd: Option<PathBuf>,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        Self {
+            cwd: None,
+            cols: 80,
+            rows: 24,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TerminalEvent {
+    /// Output of the shell, escape sequences included.
+    Output { data: String },
+    /// The shell exited, and the session closed with it.
+    Exited { code: Option<u32> },
+}
+
+/// Payload of `TERMINAL_EVENT`: the event and the session it concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalEventPayload {
+    pub session: SessionId,
+    #[serde(flatten)]
+    pub event: TerminalEvent,
+}
+
+/// Callback sessions report `TerminalEventPayload`s through.
+pub type TerminalSink = Arc<dyn Fn(TerminalEventPayload) + Send + Sync>;
+
+/// A shell running in a pseudo-terminal. Its output is read on a thread of
+/// its own until the shell exits; dropping the session kills the shell.
+///
+/// Input, resizes and kills lock separately, so a write blocked on a shell
+/// that stopped reading holds up neither of the others.
+pub struct TerminalSession {
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
+}
+
+impl TerminalSession {
+    /// Starts the default shell. `on_event` runs on the session's reader
+    /// thread, `TerminalEvent::Exited` being the last event.
+    pub fn spawn(
+        id: SessionId,
+        options: &TerminalOptions,
+        on_event: impl Fn(TerminalEvent) + Send + 'static,
+    ) -> TerminalResult<Self> {
+        Self::spawn_command(id, CommandBuilder::new_default_prog(), options, on_event)
+    }
+
+    /// Starts `command` instead of the default shell.
+    pub(super) fn spawn_command(
+        id: SessionId,
+        mut command: CommandBuilder,
+        options: &TerminalOptions,
+        on_event: impl Fn(TerminalEvent) + Send + 'static,
+    ) -> TerminalResult<Self> {
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: options.rows.max(1),
+                cols: options.cols.max(1),
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(TerminalError::pty)?;
+
+        if let Some(cwd) = &options.cwd {
+            if !cwd.is_dir() {
+                return Err(TerminalError::InvalidDirectory(cwd.clone()));
+            }
+            command.cwd(cwd);
+        }
+        command.env("TERM", "xterm-256color");
+
+        let mut child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(TerminalError::pty)?;
+        // Only the shell may hold the slave, so reads end once it exits
+        drop(pair.slave);
+
+        let killer = child.clone_killer();
+        let mut reader = pair.master.try_clone_reader().map_err(TerminalError::pty)?;
+        let writer = pair.master.take_writer().map_err(TerminalError::pty)?;
+
+        thread::Builder::new()
+            .name(format!("terminal-{}", id))
+            .spawn(move || {
+                let mut buffer = [0; 8192];
+                let mut pending = Vec::new();
+                loop {
+                    match reader.read(&mut buffer) {
+                        // The pty reports EIO rather than EOF on some platforms
+                        Ok(0) => break,
+                        Ok(read) => {
+                            let data = decode(&mut pending, &buffer[..read]);
+                            if !data.is_empty() {
+                                on_event(TerminalEvent::Output { data });
+                            }
+                        }
+                        Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                        Err(_) => break,
+                    }
+                }
+
+                let code = match child.wait() {
+                    Ok(status) => Some(status.exit_code()),
+                    Err(e) => {
+                        warn!("Failed to wait for terminal {}: {}", id, e);
+                        None
+                    }
+                };
+                debug!("Terminal {} exited with {:?}", id, code);
+                on_event(TerminalEvent::Exited { code });
+            })
+            .map_err(TerminalError::pty)?;
+
+        Ok(Self {
+            master: Mutex::new(pair.master),
+            writer: Mutex::new(writer),
+            killer: Mutex::new(killer),
+        })
+    }
+
+    /// Sends input to the shell, as typed keys or pasted text. Blocks while
+    /// the shell does not read its input.
+    pub fn write(&self, data: &[u8]) -> TerminalResult<()> {
+        let mut writer = self.writer.lock();
+        writer.write_all(data)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn resize(&self, cols: u16, rows: u16) -> TerminalResult<()> {
+        self.master
+            .lock()
+            .resize(PtySize {
+                rows: rows.max(1),
+                cols: cols.max(1),
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(TerminalError::pty)
+    }
+
+    /// Kills the shell, even while a write is blocked on it.
+    pub fn kill(&self) {
+        // Fails once the shell has exited on its own
+        let _ = self.killer.lock().kill();
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Appends `bytes` to `pending` and takes the longest prefix that is valid
+/// UTF-8, keeping a character split across reads for the next one.
+/// Invalid bytes are replaced.
+fn decode(pending: &mut Vec<u8>, bytes: &[u8]) -> String {
+    pending.extend_from_slice(bytes);
+    let mut complete = pending.len();
+    let mut start = 0;
+    while let Err(e) = std::str::from_utf8(&pending[start..]) {
+        match e.error_len() {
+            // Skip invalid bytes, lossy decoding replaces them
+            Some(invalid) => start += e.valid_up_to() + invalid,
+            // Incomplete sequence at the end
+            None => {
+                complete = start + e.valid_up_to();
+                break;
+            }
+        }
+    }
+    let rest = pending.split_off(complete);
+    let data = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_complete_text_at_once() {
+        let mut pending = Vec::new();
+        assert_eq!(decode(&mut pending, b"ls -la\r\n"), "ls -la\r\n");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn carries_a_character_split_across_reads() {
+        let text = "caf\u{e9} \u{1f680}";
+        let bytes = text.as_bytes();
+        let mut pending = Vec::new();
+
+        // 'é' is split after its first byte, the rocket after its second
+        assert_eq!(decode(&mut pending, &bytes[..4]), "caf");
+        assert_eq!(pending, [0xc3]);
+        assert_eq!(decode(&mut pending, &bytes[4..8]), "\u{e9} ");
+        assert_eq!(decode(&mut pending, &bytes[8..9]), "");
+        assert_eq!(decode(&mut pending, &bytes[9..]), "\u{1f680}");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn decodes_every_split_of_a_multibyte_text() {
+        let text = "\u{3b1}\u{2192}\u{1f600}x";
+        let bytes = text.as_bytes();
+        for split in 0..=bytes.len() {
+            let mut pending = Vec::new();
+            let mut decoded = decode(&mut pending, &bytes[..split]);
+            decoded += &decode(&mut pending, &bytes[split..]);
+            assert_eq!(decoded, text, "split at {}", split);
+            assert!(pending.is_empty());
+        }
+    }
+
+    #[test]
+    fn replaces_invalid_bytes_instead_of_holding_them() {
+        let mut pending = Vec::new();
+        assert_eq!(decode(&mut pending, b"a\xffb"), "a\u{fffd}b");
+        assert!(pending.is_empty());
+
+        // An invalid byte followed by the start of a character
+        assert_eq!(decode(&mut pending, b"\xff\xe2\x86"), "\u{fffd}");
+        assert_eq!(pending, [0xe2, 0x86]);
+        assert_eq!(decode(&mut pending, b"\x92"), "\u{2192}");
+        assert!(pending.is_empty());
+    }
+}
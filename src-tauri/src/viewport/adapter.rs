@@ -0,0 +1,431 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// Kind of graphics adapter, in the order they are preferred for
+/// high-performance rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdapterKind {
+    Discrete,
+    Integrated,
+    Virtual,
+    Cpu,
+    Other,
+}
+
+impl From<wgpu::DeviceType> for AdapterKind {
+    fn from(device_type: wgpu::DeviceType) -> Self {
+        match device_type {
+            wgpu::DeviceType::DiscreteGpu => AdapterKind::Discrete,
+            wgpu::DeviceType::IntegratedGpu => AdapterKind::Integrated,
+            wgpu::DeviceType::VirtualGpu => AdapterKind::Virtual,
+            wgpu::DeviceType::Cpu => AdapterKind::Cpu,
+            wgpu::DeviceType::Other => AdapterKind::Other,
+        }
+    }
+}
+
+/// Subset of `wgpu::Limits` shown on the settings page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterLimits {
+    pub max_texture_dimension_2d: u32,
+    pub max_texture_dimension_3d: u32,
+    pub max_texture_array_layers: u32,
+    pub max_bind_groups: u32,
+    pub max_vertex_buffers: u32,
+    pub max_buffer_size: u64,
+    pub max_storage_buffer_binding_size: u32,
+    pub max_compute_invocations_per_workgroup: u32,
+}
+
+impl From<&wgpu::Limits> for AdapterLimits {
+    fn from(limits: &wgpu::Limits) -> Self {
+        Self {
+            max_texture_dimension_2d: limits.max_texture_dimension_2d,
+            max_texture_dimension_3d: limits.max_texture_dimension_3d,
+            max_texture_array_layers: limits.max_texture_array_layers,
+            max_bind_groups: limits.max_bind_groups,
+            max_vertex_buffers: limits.max_vertex_buffers,
+            max_buffer_size: limits.max_buffer_size,
+            max_storage_buffer_binding_size: limits.max_storage_buffer_binding_size,
+            max_compute_invocations_per_workgroup: limits.max_compute_invocations_per_workgroup,
+        }
+    }
+}
+
+/// Plain description of an adapter, detached from the wgpu handle so
+/// selection can be scored on data alone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterDescriptor {
+    /// Stable identifier `<backend>-<vendor>-<device>`. wgpu does not expose
+    /// device UUIDs, so two identical cards on one backend share an id and
+    /// are told apart by their position in the list.
+    pub id: String,
+    pub name: String,
+    pub kind: AdapterKind,
+    pub backend: String,
+    pub vendor: u32,
+    pub device: u32,
+    pub driver: String,
+    pub driver_info: String,
+    /// Whether the adapter can present to the viewport surface, `None` when
+    /// listed without a surface.
+    pub supports_surface: Option<bool>,
+    #[serde(skip)]
+    pub features: wgpu::Features,
+    #[serde(rename = "features")]
+    pub feature_names: Vec<String>,
+    pub limits: AdapterLimits,
+}
+
+impl AdapterDescriptor {
+    pub fn new(adapter: &wgpu::Adapter, surface: Option<&wgpu::Surface<'_>>) -> Self {
+        let info = adapter.get_info();
+        let features = adapter.features();
+
+        Self {
+            id: format!(
+                "{}-{:04x}-{:04x}",
+                info.backend.to_str(),
+                info.vendor,
+                info.device
+            ),
+            name: info.name,
+            kind: info.device_type.into(),
+            backend: info.backend.to_str().to_string(),
+            vendor: info.vendor,
+            device: info.device,
+            driver: info.driver,
+            driver_info: info.driver_info,
+            supports_surface: surface.map(|surface| adapter.is_surface_supported(surface)),
+            features,
+            feature_names: features
+                .iter_names()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            limits: (&adapter.limits()).into(),
+        }
+    }
+
+    /// Whether `preference` names this adapter by id or by a case-insensitive
+    /// part of its name.
+    pub fn matches(&self, preference: &str) -> bool {
+        let preference = preference.trim();
+        !preference.is_empty()
+            && (self.id.eq_ignore_ascii_case(preference)
+                || self
+                    .name
+                    .to_lowercase()
+                    .contains(&preference.to_lowercase()))
+    }
+}
+
+/// Which adapter the user wants the viewport to run on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AdapterPreference {
+    /// Adapter id or part of its name. Ignored if no usable adapter matches.
+    pub adapter: Option<String>,
+    /// Prefer integrated over discrete GPUs, e.g. to save battery.
+    pub low_power: bool,
+}
+
+/// Hard requirements an adapter must meet to be selected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdapterRequirements {
+    pub features: wgpu::Features,
+    /// Reject adapters that cannot present to the viewport surface.
+    pub surface: bool,
+}
+
+/// Score of `adapter` under `preference`, or `None` if it does not meet
+/// `requirements`. Higher is better.
+pub fn score_adapter(
+    adapter: &AdapterDescriptor,
+    requirements: &AdapterRequirements,
+    preference: &AdapterPreference,
+) -> Option<u32> {
+    if !adapter.features.contains(requirements.features) {
+        return None;
+    }
+    if requirements.surface && adapter.supports_surface != Some(true) {
+        return None;
+    }
+
+    let kind = match (adapter.kind, preference.low_power) {
+        (AdapterKind::Discrete, false) | (AdapterKind::Integrated, true) => 400,
+        (AdapterKind::Integrated, false) | (AdapterKind::Discrete, true) => 300,
+        (AdapterKind::Virtual, _) => 200,
+        (AdapterKind::Other, _) => 100,
+        (AdapterKind::Cpu, _) => 0,
+    };
+    // Native APIs over the GL compatibility path
+    let backend = match adapter.backend.as_str() {
+        "vulkan" | "metal" | "dx12" => 20,
+        _ => 0,
+    };
+    // Optional features the viewport makes use of
+    let features = if adapter.features.contains(wgpu::Features::TIMESTAMP_QUERY) {
+        5
+    } else {
+        0
+    };
+
+    Some(kind + backend + features)
+}
+
+/// Index of the adapter to use: the one named by the preference if it is
+/// usable, the best scoring one otherwise.
+pub fn select_adapter(
+    adapters: &[AdapterDescriptor],
+    requirements: &AdapterRequirements,
+    preference: &AdapterPreference,
+) -> Option<usize> {
+    let scores: Vec<Option<u32>> = adapters
+        .iter()
+        .map(|adapter| score_adapter(adapter, requirements, preference))
+        .collect();
+
+    if let Some(wanted) = preference.adapter.as_deref() {
+        let matching = adapters
+            .iter()
+            .zip(&scores)
+            .enumerate()
+            .filter(|(_, (adapter, score))| score.is_some() && adapter.matches(wanted))
+            .max_by_key(|(_, (_, score))| *score)
+            .map(|(index, _)| index);
+        match matching {
+            Some(index) => return Some(index),
+            None => warn!(
+                "Preferred adapter '{}' is not available, selecting automatically",
+                wanted
+            ),
+        }
+    }
+
+    // Earlier adapters win ties, keeping the backend's own ordering
+    scores
+        .iter()
+        .enumerate()
+        .filter_map(|(index, score)| score.map(|score| (index, score)))
+        .fold(
+            None,
+            |best: Option<(usize, u32)>, (index, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((index, score)),
+            },
+        )
+        .map(|(index, _)| index)
+}
+
+/// Picks an adapter from `instance` for the viewport.
+pub fn choose_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface<'_>>,
+    requirements: &AdapterRequirements,
+    preference: &AdapterPreference,
+) -> Option<(wgpu::Adapter, AdapterDescriptor)> {
+    let mut adapters = instance.enumerate_adapters(wgpu::Backends::all());
+    let descriptors: Vec<AdapterDescriptor> = adapters
+        .iter()
+        .map(|adapter| AdapterDescriptor::new(adapter, surface))
+        .collect();
+
+    for (descriptor, score) in descriptors.iter().map(|descriptor| {
+        (
+            descriptor,
+            score_adapter(descriptor, requirements, preference),
+        )
+    }) {
+        info!(
+            "Found adapter {} ({}, {:?}), score {:?}",
+            descriptor.name, descriptor.id, descriptor.kind, score
+        );
+    }
+
+    let index = select_adapter(&descriptors, requirements, preference)?;
+    Some((adapters.swap_remove(index), descriptors[index].clone()))
+}
+
+/// Describes every adapter the instance can see.
+pub fn list_adapters(instance: &wgpu::Instance) -> Vec<AdapterDescriptor> {
+    instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .iter()
+        .map(|adapter| AdapterDescriptor::new(adapter, None))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(name: &str, kind: AdapterKind, backend: &str) -> AdapterDescriptor {
+        AdapterDescriptor {
+            id: format!("{}-{}", backend, name.to_lowercase().replace(' ', "-")),
+            name: name.to_string(),
+            kind,
+            backend: backend.to_string(),
+            vendor: 0,
+            device: 0,
+            driver: String::new(),
+            driver_info: String::new(),
+            supports_surface: Some(true),
+            features: wgpu::Features::empty(),
+            feature_names: Vec::new(),
+            limits: (&wgpu::Limits::default()).into(),
+        }
+    }
+
+    fn laptop() -> Vec<AdapterDescriptor> {
+        vec![
+            adapter("Intel UHD 620", AdapterKind::Integrated, "vulkan"),
+            adapter("NVIDIA RTX 3060", AdapterKind::Discrete, "vulkan"),
+            adapter("llvmpipe", AdapterKind::Cpu, "vulkan"),
+        ]
+    }
+
+    fn select(adapters: &[AdapterDescriptor], preference: &AdapterPreference) -> Option<usize> {
+        select_adapter(adapters, &AdapterRequirements::default(), preference)
+    }
+
+    #[test]
+    fn prefers_discrete_gpus() {
+        assert_eq!(select(&laptop(), &AdapterPreference::default()), Some(1));
+    }
+
+    #[test]
+    fn prefers_integrated_gpus_for_low_power() {
+        let preference = AdapterPreference {
+            low_power: true,
+            ..AdapterPreference::default()
+        };
+        assert_eq!(select(&laptop(), &preference), Some(0));
+    }
+
+    #[test]
+    fn ranks_kinds_before_backends_and_features() {
+        let requirements = AdapterRequirements::default();
+        let preference = AdapterPreference::default();
+        let score = |adapter: &AdapterDescriptor| {
+            score_adapter(adapter, &requirements, &preference).unwrap()
+        };
+
+        let mut integrated = adapter("Integrated", AdapterKind::Integrated, "vulkan");
+        integrated.features = wgpu::Features::TIMESTAMP_QUERY;
+        let discrete_gl = adapter("Discrete", AdapterKind::Discrete, "gl");
+        assert!(score(&discrete_gl) > score(&integrated));
+
+        let discrete_vulkan = adapter("Discrete", AdapterKind::Discrete, "vulkan");
+        assert!(score(&discrete_vulkan) > score(&discrete_gl));
+        let virtual_gpu = adapter("Virtual", AdapterKind::Virtual, "vulkan");
+        let other = adapter("Other", AdapterKind::Other, "vulkan");
+        let cpu = adapter("Cpu", AdapterKind::Cpu, "vulkan");
+        assert!(score(&virtual_gpu) > score(&other));
+        assert!(score(&other) > score(&cpu));
+    }
+
+    #[test]
+    fn follows_the_preferred_adapter() {
+        let by_name = AdapterPreference {
+            adapter: Some("intel".to_string()),
+            ..AdapterPreference::default()
+        };
+        assert_eq!(select(&laptop(), &by_name), Some(0));
+
+        let by_id = AdapterPreference {
+            adapter: Some("VULKAN-LLVMPIPE".to_string()),
+            ..AdapterPreference::default()
+        };
+        assert_eq!(select(&laptop(), &by_id), Some(2));
+    }
+
+    #[test]
+    fn ignores_a_preference_nothing_matches() {
+        for wanted in ["Radeon", "", "  "] {
+            let preference = AdapterPreference {
+                adapter: Some(wanted.to_string()),
+                ..AdapterPreference::default()
+            };
+            assert_eq!(select(&laptop(), &preference), Some(1));
+        }
+    }
+
+    #[test]
+    fn ignores_a_preferred_adapter_that_is_unusable() {
+        let mut adapters = laptop();
+        adapters[0].supports_surface = Some(false);
+        let preference = AdapterPreference {
+            adapter: Some("intel".to_string()),
+            ..AdapterPreference::default()
+        };
+        let requirements = AdapterRequirements {
+            surface: true,
+            ..AdapterRequirements::default()
+        };
+        assert_eq!(
+            select_adapter(&adapters, &requirements, &preference),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn rejects_adapters_without_the_surface() {
+        let requirements = AdapterRequirements {
+            surface: true,
+            ..AdapterRequirements::default()
+        };
+        let preference = AdapterPreference::default();
+
+        let mut adapters = laptop();
+        adapters[1].supports_surface = Some(false);
+        // Listed without a surface, so whether it could present is unknown
+        adapters[0].supports_surface = None;
+        assert_eq!(
+            score_adapter(&adapters[1], &requirements, &preference),
+            None
+        );
+        assert_eq!(
+            score_adapter(&adapters[0], &requirements, &preference),
+            None
+        );
+        assert_eq!(
+            select_adapter(&adapters, &requirements, &preference),
+            Some(2)
+        );
+
+        adapters[2].supports_surface = Some(false);
+        assert_eq!(select_adapter(&adapters, &requirements, &preference), None);
+
+        // Without the requirement the surface does not matter
+        assert_eq!(select(&adapters, &preference), Some(1));
+    }
+
+    #[test]
+    fn rejects_adapters_missing_required_features() {
+        let mut adapters = laptop();
+        adapters[0].features = wgpu::Features::TIMESTAMP_QUERY;
+        let requirements = AdapterRequirements {
+            features: wgpu::Features::TIMESTAMP_QUERY,
+            ..AdapterRequirements::default()
+        };
+        let selected = select_adapter(&adapters, &requirements, &AdapterPreference::default());
+        assert_eq!(selected, Some(0));
+    }
+
+    #[test]
+    fn keeps_the_first_of_equal_adapters() {
+        let adapters = [
+            adapter("GPU A", AdapterKind::Discrete, "vulkan"),
+            adapter("GPU B", AdapterKind::Discrete, "dx12"),
+        ];
+        assert_eq!(select(&adapters, &AdapterPreference::default()), Some(0));
+    }
+
+    #[test]
+    fn selects_nothing_from_an_empty_list() {
+        assert_eq!(select(&[], &AdapterPreference::default()), None);
+    }
+}
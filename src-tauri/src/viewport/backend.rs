@@ -0,0 +1,69 @@
+use super::{
+    camera::OrbitCamera,
+    error::{ViewportError, ViewportResult},
+    grid::LineVertex,
+    policy::DisplaySettings,
+};
+use parking_lot::RwLock;
+use std::{sync::Arc, time::Duration};
+
+/// A rendering backend driven by the viewport `RenderLoop`.
+///
+/// Implemented by the wgpu renderer and by the CPU-only
+/// `HeadlessBackend`, so the render loop does not care whether a real
+/// window surface exists.
+pub trait RenderBackend: Send + Sync {
+    /// Resizes the render target to the given extent in physical pixels.
+    fn resize(&mut self, width: u32, height: u32) -> ViewportResult<()>;
+
+    /// Renders and presents a single frame as seen from `camera`.
+    fn render_frame(&mut self, camera: &OrbitCamera) -> ViewportResult<()>;
+
+    /// Replaces the line list marking the scene's entities, drawn in world
+    /// space with the grid from the next frame on.
+    fn set_scene_lines(&mut self, lines: &[LineVertex]);
+
+    /// Attempts to recover in place from an error returned by
+    /// `render_frame`, e.g. by rebuilding an out of date swapchain.
+    ///
+    /// Returns `Ok(false)` if the error is not one the backend can recover
+    /// from this way, in which case the render loop only logs it.
+    fn recover(&mut self, _error: &ViewportError) -> ViewportResult<bool> {
+        Ok(false)
+    }
+
+    /// Recreates the backend from scratch after `render_frame` reported
+    /// `ViewportError::DeviceLost`.
+    fn reinitialize(self: Box<Self>) -> ViewportResult<Box<dyn RenderBackend>> {
+        Err(ViewportError::DeviceLost)
+    }
+
+    /// Whether the device was lost, possibly by another viewport sharing
+    /// it, and `reinitialize` is due.
+    fn is_device_lost(&self) -> bool {
+        false
+    }
+
+    /// Applies the display settings to the swapchain, including whether to
+    /// present with vertical sync. Returns whether presenting now blocks on
+    /// the display refresh.
+    fn set_display_settings(&mut self, _settings: &DisplaySettings) -> bool {
+        false
+    }
+
+    /// GPU time of a recently rendered frame, if the backend can measure it.
+    fn last_gpu_time(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Name of the graphics adapter rendering the viewport, if any.
+    fn adapter_name(&self) -> Option<String> {
+        None
+    }
+
+    /// Short human readable name used in logs.
+    fn name(&self) -> &'static str;
+}
+
+/// Backend slot shared between the Tauri commands and the render thread.
+pub type SharedBackend = Arc<RwLock<Option<Box<dyn RenderBackend>>>>;
@@ -0,0 +1,95 @@
+use glam::{Mat4, Vec3};
+
+/// Orbit camera used by the editor viewport.
+///
+/// The camera circles `target` at `distance`, with `yaw` measured around the
+/// world Y axis and `pitch` above the ground plane. Both are in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: Vec3::ZERO,
+            yaw: 45f32.to_radians(),
+            pitch: 30f32.to_radians(),
+            distance: 12.0,
+            fov_y: 60f32.to_radians(),
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+}
+
+impl OrbitCamera {
+    const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+    const MIN_DISTANCE: f32 = 0.1;
+    const MAX_DISTANCE: f32 = 10_000.0;
+
+    /// Restores the default editor camera transform.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Unit vector pointing from the target towards the eye.
+    pub fn direction(&self) -> Vec3 {
+        Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        )
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        self.target + self.direction() * self.distance
+    }
+
+    /// Circles the eye around the target. Non-finite deltas are ignored.
+    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        if !(delta_yaw.is_finite() && delta_pitch.is_finite()) {
+            return;
+        }
+        self.yaw = (self.yaw + delta_yaw).rem_euclid(std::f32::consts::TAU);
+        self.pitch = (self.pitch + delta_pitch).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Moves the eye towards (`factor < 1`) or away from the target.
+    /// Factors that are not positive and finite are ignored.
+    pub fn zoom(&mut self, factor: f32) {
+        if !(factor.is_finite() && factor > 0.0) {
+            return;
+        }
+        self.distance = (self.distance * factor).clamp(Self::MIN_DISTANCE, Self::MAX_DISTANCE);
+    }
+
+    /// Moves the target in the camera plane, scaled by the orbit distance.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        if !(dx.is_finite() && dy.is_finite()) {
+            return;
+        }
+        let forward = -self.direction();
+        let right = forward.cross(Vec3::Y).normalize_or_zero();
+        let up = right.cross(forward);
+        self.target += (right * dx + up * dy) * self.distance;
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye(), self.target, Vec3::Y)
+    }
+
+    pub fn projection(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective_rh(self.fov_y, aspect.max(f32::EPSILON), self.near, self.far)
+    }
+
+    pub fn view_projection(&self, aspect: f32) -> Mat4 {
+        self.projection(aspect) * self.view()
+    }
+}
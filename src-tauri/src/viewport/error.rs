@@ -0,0 +1,112 @@
+use crate::error::serialize_as_code_and_message;
+use crossbeam_channel::TrySendError;
+
+/// Errors raised by the native viewport.
+#[derive(Debug, thiserror::Error)]
+pub enum ViewportError {
+    #[error("No suitable graphics adapter found")]
+    NoAdapter,
+
+    #[error("Failed to create graphics device: {0}")]
+    DeviceCreation(#[from] wgpu::RequestDeviceError),
+
+    #[error("Unsupported window system: {0}")]
+    UnsupportedWindowSystem(&'static str),
+
+    #[error("Failed to access the window: {0}")]
+    Window(String),
+
+    #[error("Failed to create a surface for the window: {0}")]
+    SurfaceCreation(#[from] wgpu::CreateSurfaceError),
+
+    #[error("The graphics adapter cannot present to this window")]
+    IncompatibleSurface,
+
+    #[error("The window surface was lost")]
+    SurfaceLost,
+
+    #[error("The swapchain is out of date")]
+    SwapchainOutOfDate,
+
+    #[error("Timed out waiting for the next swapchain image")]
+    SurfaceTimeout,
+
+    #[error("Out of GPU memory")]
+    OutOfMemory,
+
+    #[error("The graphics device was lost")]
+    DeviceLost,
+
+    #[error("Failed to read back the rendered frame")]
+    Readback(#[from] wgpu::BufferAsyncError),
+
+    #[error("Viewport command queue is full")]
+    QueueFull,
+
+    #[error("Viewport command channel is closed")]
+    ChannelClosed,
+
+    #[error("No viewport with id '{0}'")]
+    UnknownViewport(String),
+
+    #[error("Window '{window}' already shows viewport '{viewport}'")]
+    WindowInUse { window: String, viewport: String },
+
+    #[error("Invalid frame pacing: {0}")]
+    InvalidFramePacing(&'static str),
+}
+
+impl ViewportError {
+    /// Stable machine readable identifier of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ViewportError::NoAdapter => "noAdapter",
+            ViewportError::DeviceCreation(_) => "deviceCreation",
+            ViewportError::UnsupportedWindowSystem(_) => "unsupportedWindowSystem",
+            ViewportError::Window(_) => "window",
+            ViewportError::SurfaceCreation(_) => "surfaceCreation",
+            ViewportError::IncompatibleSurface => "incompatibleSurface",
+            ViewportError::SurfaceLost => "surfaceLost",
+            ViewportError::SwapchainOutOfDate => "swapchainOutOfDate",
+            ViewportError::SurfaceTimeout => "surfaceTimeout",
+            ViewportError::OutOfMemory => "outOfMemory",
+            ViewportError::DeviceLost => "deviceLost",
+            ViewportError::Readback(_) => "readback",
+            ViewportError::QueueFull => "queueFull",
+            ViewportError::ChannelClosed => "channelClosed",
+            ViewportError::UnknownViewport(_) => "unknownViewport",
+            ViewportError::WindowInUse { .. } => "windowInUse",
+            ViewportError::InvalidFramePacing(_) => "invalidFramePacing",
+        }
+    }
+}
+
+impl From<wgpu::SurfaceError> for ViewportError {
+    fn from(error: wgpu::SurfaceError) -> Self {
+        match error {
+            wgpu::SurfaceError::Lost => ViewportError::SurfaceLost,
+            wgpu::SurfaceError::Outdated => ViewportError::SwapchainOutOfDate,
+            wgpu::SurfaceError::Timeout => ViewportError::SurfaceTimeout,
+            wgpu::SurfaceError::OutOfMemory => ViewportError::OutOfMemory,
+        }
+    }
+}
+
+impl<T> From<TrySendError<T>> for ViewportError {
+    fn from(error: TrySendError<T>) -> Self {
+        match error {
+            TrySendError::Full(_) => ViewportError::QueueFull,
+            TrySendError::Disconnected(_) => ViewportError::ChannelClosed,
+        }
+    }
+}
+
+impl From<tauri::Error> for ViewportError {
+    fn from(error: tauri::Error) -> Self {
+        ViewportError::Window(error.to_string())
+    }
+}
+
+serialize_as_code_and_message!(ViewportError);
+
+pub type ViewportResult<T> = Result<T, ViewportError>;
@@ -0,0 +1,35 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Name of the Tauri event the viewport emits `ViewportEvent`s on.
+pub const VIEWPORT_EVENT: &str = "viewport-event";
+
+/// Notifications about the viewport's render state, sent to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ViewportEvent {
+    /// The swapchain was out of date or lost and has been rebuilt. `reason`
+    /// is the code of the `ViewportError` that triggered it.
+    SwapchainRecreated { reason: &'static str },
+    /// The graphics device was lost; the viewport is reinitializing.
+    DeviceLost,
+    /// A new device was created after a device loss.
+    DeviceRecovered { adapter: String },
+    /// Recovery failed. Rendering is paused until the next viewport command.
+    RecoveryFailed { code: &'static str, message: String },
+    /// The device was lost and could not be recreated. The viewport no
+    /// longer renders until it is initialized again.
+    DeviceUnrecoverable { code: &'static str, message: String },
+}
+
+/// Payload of `VIEWPORT_EVENT`: the event and the viewport it concerns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewportEventPayload {
+    pub viewport_id: String,
+    #[serde(flatten)]
+    pub event: ViewportEvent,
+}
+
+/// Callback the render loop reports `ViewportEvent`s through.
+pub type EventSink = Arc<dyn Fn(ViewportEvent) + Send + Sync>;
@@ -0,0 +1,50 @@
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::{collections::VecDeque, sync::Arc};
+
+/// A failure that can be injected into a render backend to exercise the
+/// recovery paths without a misbehaving driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Fault {
+    /// The next frame fails as if the swapchain were out of date.
+    SwapchainOutOfDate,
+    /// The next frame fails as if the window surface were lost.
+    SurfaceLost,
+    /// The device is destroyed before the next frame.
+    DeviceLost,
+}
+
+/// Queue of faults consumed by the backend, one per rendered frame.
+///
+/// Cloning yields a handle to the same queue, so faults can be injected from
+/// a Tauri command while the render thread owns the backend.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    pending: Arc<Mutex<VecDeque<Fault>>>,
+}
+
+impl FaultInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn inject(&self, fault: Fault) {
+        self.pending.lock().push_back(fault);
+    }
+
+    /// Takes the next fault to trigger, if any.
+    pub fn take(&self) -> Option<Fault> {
+        self.pending.lock().pop_front()
+    }
+
+    /// Takes the next fault if it is `fault`.
+    pub fn take_if(&self, fault: Fault) -> bool {
+        let mut pending = self.pending.lock();
+        let next = pending.front() == Some(&fault);
+        if next {
+            pending.pop_front();
+        }
+        next
+    }
+}
@@ -0,0 +1,295 @@
+use super::{
+    adapter::{self, AdapterPreference, AdapterRequirements},
+    error::{ViewportError, ViewportResult},
+    handle::WindowHandle,
+};
+use crossbeam_channel::{Receiver, Sender};
+use log::{error, info, warn};
+use parking_lot::{Mutex, MutexGuard};
+use std::{
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+/// wgpu instance, adapter, device and queue used by the viewport.
+pub struct GpuContext {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    lost: Arc<AtomicBool>,
+    /// Never sent on; dropping it with the context disconnects the receiver
+    /// `SharedGpu` waits on.
+    _alive: Option<Sender<Infallible>>,
+}
+
+impl GpuContext {
+    /// Creates the wgpu instance used for both windowed and offscreen rendering.
+    ///
+    /// Backends can be restricted with the `WGPU_BACKEND` environment variable.
+    pub fn create_instance() -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all()),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a surface for a native window on the given instance.
+    pub fn create_surface(
+        instance: &wgpu::Instance,
+        handle: &WindowHandle,
+    ) -> ViewportResult<wgpu::Surface<'static>> {
+        // SAFETY: the handles come from a live Tauri window, and the surface is
+        // dropped together with the viewport before that window is destroyed.
+        let surface = unsafe {
+            instance.create_surface_unsafe(wgpu::SurfaceTargetUnsafe::RawHandle {
+                raw_display_handle: handle.display,
+                raw_window_handle: handle.window,
+            })
+        }
+        .map_err(|e| {
+            error!(
+                "Failed to create surface on {}: {}",
+                handle.window_system(),
+                e
+            );
+            e
+        })?;
+
+        Ok(surface)
+    }
+
+    /// Selects an adapter and creates a device on it.
+    ///
+    /// Adapters are scored by `adapter::select_adapter`, honouring the user's
+    /// `preference`. When `compatible_surface` is given the adapter must be
+    /// able to present to it. Without a surface a software fallback adapter is
+    /// accepted if no hardware adapter is available, so offscreen rendering
+    /// works on machines without a GPU.
+    pub fn new(
+        instance: wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+        preference: &AdapterPreference,
+    ) -> ViewportResult<Self> {
+        let requirements = AdapterRequirements {
+            features: wgpu::Features::empty(),
+            surface: compatible_surface.is_some(),
+        };
+
+        let adapter =
+            adapter::choose_adapter(&instance, compatible_surface, &requirements, preference)
+                .map(|(adapter, _)| adapter)
+                .or_else(|| {
+                    compatible_surface
+                        .is_none()
+                        .then(|| {
+                            pollster::block_on(instance.request_adapter(
+                                &wgpu::RequestAdapterOptions {
+                                    power_preference: wgpu::PowerPreference::HighPerformance,
+                                    force_fallback_adapter: true,
+                                    compatible_surface: None,
+                                },
+                            ))
+                        })
+                        .flatten()
+                })
+                .ok_or_else(|| {
+                    error!("No suitable graphics adapter found");
+                    ViewportError::NoAdapter
+                })?;
+
+        let adapter_info = adapter.get_info();
+        info!(
+            "Using adapter {} ({:?}, {:?})",
+            adapter_info.name, adapter_info.device_type, adapter_info.backend
+        );
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Viewport Device"),
+                // Timestamp queries feed the GPU time in the frame statistics
+                required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+                required_limits:
+                    wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+            },
+            None,
+        ))
+        .map_err(|e| {
+            error!("Failed to create logical device: {}", e);
+            e
+        })?;
+
+        let lost = Arc::new(AtomicBool::new(false));
+        device.set_device_lost_callback({
+            let lost = lost.clone();
+            move |reason, message| {
+                // The callback also runs when the context is dropped on purpose
+                if matches!(
+                    reason,
+                    wgpu::DeviceLostReason::Unknown | wgpu::DeviceLostReason::Destroyed
+                ) {
+                    error!("Graphics device lost ({:?}): {}", reason, message);
+                    lost.store(true, Ordering::Release);
+                }
+            }
+        });
+        // wgpu panics on uncaptured errors by default, which would take the
+        // render thread down with a lost device
+        device.on_uncaptured_error(Box::new(|e| error!("wgpu error: {}", e)));
+
+        Ok(Self {
+            instance,
+            adapter,
+            device,
+            queue,
+            lost,
+            _alive: None,
+        })
+    }
+
+    /// Whether the device has been lost and the context must be recreated.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Name of the adapter the device was created on.
+    pub fn adapter_name(&self) -> String {
+        self.adapter.get_info().name
+    }
+}
+
+impl Drop for GpuContext {
+    fn drop(&mut self) {
+        // wgpu reports dropping the device as an unknown loss, detach the
+        // callback so a regular shutdown is not logged as one
+        self.device.set_device_lost_callback(|_, _| {});
+    }
+}
+
+/// GPU context shared by all viewports.
+///
+/// Only a weak reference is kept, so the device lives as long as a viewport
+/// renders with it and the adapter is picked anew once all viewports are
+/// gone. After a device loss the first viewport to reinitialize creates a
+/// new context and the others move over to it. It waits for them to release
+/// the lost one first, since wgpu cannot reliably create a device while
+/// another one on the same backend is being torn down.
+#[derive(Clone, Default)]
+pub struct SharedGpu {
+    current: Arc<Mutex<Current>>,
+}
+
+struct Current {
+    gpu: Weak<GpuContext>,
+    /// Disconnected once `gpu` is dropped.
+    released: Receiver<Infallible>,
+}
+
+impl Default for Current {
+    fn default() -> Self {
+        Self {
+            gpu: Weak::new(),
+            released: crossbeam_channel::never(),
+        }
+    }
+}
+
+impl SharedGpu {
+    /// How long replacing a lost context waits for the other viewports to
+    /// release it before going ahead anyway.
+    pub const RELEASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared context, if one is alive and its device was not lost.
+    pub fn current(&self) -> Option<Arc<GpuContext>> {
+        Self::usable(&self.current.lock().gpu)
+    }
+
+    fn usable(current: &Weak<GpuContext>) -> Option<Arc<GpuContext>> {
+        current.upgrade().filter(|gpu| !gpu.is_lost())
+    }
+
+    /// Locks the shared context and returns it if it is usable. Returns
+    /// `None` once a new one may be created, which the caller does before
+    /// releasing the lock.
+    fn reuse(&self) -> (MutexGuard<'_, Current>, Option<Arc<GpuContext>>) {
+        let deadline = Instant::now() + Self::RELEASE_TIMEOUT;
+        loop {
+            let current = self.current.lock();
+            if let Some(gpu) = Self::usable(&current.gpu) {
+                return (current, Some(gpu));
+            }
+            if current.gpu.strong_count() == 0 {
+                return (current, None);
+            }
+            if Instant::now() >= deadline {
+                warn!("Lost graphics device is still in use, replacing it anyway");
+                return (current, None);
+            }
+
+            // Wait without the lock, so the viewports still holding the lost
+            // context can get here and release it
+            let released = current.released.clone();
+            drop(current);
+            let _ = released.recv_deadline(deadline);
+        }
+    }
+
+    /// Makes `gpu` the shared context.
+    fn share(current: &mut Current, mut gpu: GpuContext) -> Arc<GpuContext> {
+        let (alive, released) = crossbeam_channel::bounded(0);
+        gpu._alive = Some(alive);
+        let gpu = Arc::new(gpu);
+        *current = Current {
+            gpu: Arc::downgrade(&gpu),
+            released,
+        };
+        gpu
+    }
+
+    /// Creates a surface for a native window on the shared context, creating
+    /// the context on an adapter that can present to it if there is none.
+    pub fn for_window(
+        &self,
+        handle: &WindowHandle,
+        preference: &AdapterPreference,
+    ) -> ViewportResult<(Arc<GpuContext>, wgpu::Surface<'static>)> {
+        let (mut current, reused) = self.reuse();
+
+        if let Some(gpu) = reused {
+            let surface = GpuContext::create_surface(&gpu.instance, handle)?;
+            if !gpu.adapter.is_surface_supported(&surface) {
+                error!(
+                    "Adapter {} cannot present to the window",
+                    gpu.adapter_name()
+                );
+                return Err(ViewportError::IncompatibleSurface);
+            }
+            return Ok((gpu, surface));
+        }
+
+        let instance = GpuContext::create_instance();
+        let surface = GpuContext::create_surface(&instance, handle)?;
+        let gpu = GpuContext::new(instance, Some(&surface), preference)?;
+        Ok((Self::share(&mut current, gpu), surface))
+    }
+
+    /// The shared context for offscreen rendering, created if there is none.
+    pub fn offscreen(&self, preference: &AdapterPreference) -> ViewportResult<Arc<GpuContext>> {
+        let (mut current, reused) = self.reuse();
+
+        if let Some(gpu) = reused {
+            return Ok(gpu);
+        }
+
+        let gpu = GpuContext::new(GpuContext::create_instance(), None, preference)?;
+        Ok(Self::share(&mut current, gpu))
+    }
+}
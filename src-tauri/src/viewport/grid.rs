@@ -0,0 +1,163 @@
+use super::camera::OrbitCamera;
+use crate::scene::{Component, Entity, World};
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+
+/// Vertex of a line list, with a linear RGBA color.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl LineVertex {
+    pub const fn new(position: [f32; 3], color: [f32; 4]) -> Self {
+        Self { position, color }
+    }
+}
+
+pub const GRID_HALF_EXTENT: i32 = 20;
+pub const GRID_MAJOR_EVERY: i32 = 5;
+
+const MINOR_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 1.0];
+const MAJOR_COLOR: [f32; 4] = [0.12, 0.12, 0.12, 1.0];
+const X_AXIS_COLOR: [f32; 4] = [0.80, 0.08, 0.08, 1.0];
+const Y_AXIS_COLOR: [f32; 4] = [0.10, 0.70, 0.10, 1.0];
+const Z_AXIS_COLOR: [f32; 4] = [0.10, 0.20, 0.90, 1.0];
+const MESH_COLOR: [f32; 4] = [0.60, 0.60, 0.60, 1.0];
+
+/// Length of the axes drawn at every entity's origin.
+pub const ENTITY_AXIS_LENGTH: f32 = 0.5;
+
+/// Line list for the editor ground grid on the XZ plane.
+///
+/// The lines through the origin are tinted with the X (red) and Z (blue)
+/// axis colors.
+pub fn ground_grid() -> Vec<LineVertex> {
+    let extent = GRID_HALF_EXTENT as f32;
+    let mut lines = Vec::with_capacity((GRID_HALF_EXTENT as usize * 2 + 1) * 4);
+
+    for i in -GRID_HALF_EXTENT..=GRID_HALF_EXTENT {
+        let offset = i as f32;
+        let color = if i % GRID_MAJOR_EVERY == 0 {
+            MAJOR_COLOR
+        } else {
+            MINOR_COLOR
+        };
+        let (x_line_color, z_line_color) = if i == 0 {
+            (X_AXIS_COLOR, Z_AXIS_COLOR)
+        } else {
+            (color, color)
+        };
+
+        // Line parallel to X
+        lines.push(LineVertex::new([-extent, 0.0, offset], x_line_color));
+        lines.push(LineVertex::new([extent, 0.0, offset], x_line_color));
+        // Line parallel to Z
+        lines.push(LineVertex::new([offset, 0.0, -extent], z_line_color));
+        lines.push(LineVertex::new([offset, 0.0, extent], z_line_color));
+    }
+
+    lines
+}
+
+/// Line list for the axis gizmo: unit X, Y and Z axes from the origin.
+pub fn axis_gizmo() -> Vec<LineVertex> {
+    vec![
+        LineVertex::new([0.0, 0.0, 0.0], X_AXIS_COLOR),
+        LineVertex::new([1.0, 0.0, 0.0], X_AXIS_COLOR),
+        LineVertex::new([0.0, 0.0, 0.0], Y_AXIS_COLOR),
+        LineVertex::new([0.0, 1.0, 0.0], Y_AXIS_COLOR),
+        LineVertex::new([0.0, 0.0, 0.0], Z_AXIS_COLOR),
+        LineVertex::new([0.0, 0.0, 1.0], Z_AXIS_COLOR),
+    ]
+}
+
+/// Mesh component values of the visible entities of `world`, without
+/// duplicates.
+pub fn visible_meshes(world: &World) -> Vec<String> {
+    let mut meshes = Vec::new();
+    world.visit_visible(|entity, _| {
+        if let Some(mesh) = mesh_of(entity) {
+            if !meshes.contains(mesh) {
+                meshes.push(mesh.clone());
+            }
+        }
+    });
+    meshes
+}
+
+fn mesh_of(entity: &Entity) -> Option<&String> {
+    entity
+        .components
+        .iter()
+        .find_map(|component| match component {
+            Component::Mesh { mesh } => Some(mesh),
+            _ => None,
+        })
+}
+
+/// Line list marking the visible entities of `world`: local axes at every
+/// entity's origin and the bounds of entities with a mesh. `mesh_bounds`
+/// gives the bounds of a mesh component value; unit bounds are drawn where
+/// it returns `None`.
+///
+/// Stands in for real geometry until the viewport renders meshes.
+pub fn entity_markers(
+    world: &World,
+    mut mesh_bounds: impl FnMut(&str) -> Option<(Vec3, Vec3)>,
+) -> Vec<LineVertex> {
+    let mut lines = Vec::new();
+    world.visit_visible(|entity, matrix| {
+        let point = |p: Vec3| matrix.transform_point3(p).to_array();
+        let origin = point(Vec3::ZERO);
+
+        for (axis, color) in [
+            (Vec3::X, X_AXIS_COLOR),
+            (Vec3::Y, Y_AXIS_COLOR),
+            (Vec3::Z, Z_AXIS_COLOR),
+        ] {
+            lines.push(LineVertex::new(origin, color));
+            lines.push(LineVertex::new(point(axis * ENTITY_AXIS_LENGTH), color));
+        }
+
+        if let Some(mesh) = mesh_of(entity) {
+            let (min, max) = mesh_bounds(mesh).unwrap_or((Vec3::splat(-0.5), Vec3::splat(0.5)));
+            let corner = |i: u32| {
+                let bit = |n: usize| if i & (1 << n) == 0 { min[n] } else { max[n] };
+                point(Vec3::new(bit(0), bit(1), bit(2)))
+            };
+            // The 12 edges join corners differing in exactly one bit
+            for i in 0..8u32 {
+                for n in 0..3 {
+                    let j = i | (1 << n);
+                    if j != i {
+                        lines.push(LineVertex::new(corner(i), MESH_COLOR));
+                        lines.push(LineVertex::new(corner(j), MESH_COLOR));
+                    }
+                }
+            }
+        }
+    });
+    lines
+}
+
+/// Orientation-only projection for the axis gizmo, so it follows the camera
+/// rotation but keeps a constant size.
+pub fn gizmo_view_projection(camera: &OrbitCamera) -> Mat4 {
+    let view = Mat4::look_at_rh(camera.direction() * 3.0, Vec3::ZERO, Vec3::Y);
+    let projection = Mat4::orthographic_rh(-1.2, 1.2, -1.2, 1.2, 0.1, 10.0);
+    projection * view
+}
+
+/// Square region in the bottom-left corner the gizmo is drawn into, as
+/// `(x, y, size)` in pixels from the top-left of the target.
+pub fn gizmo_viewport(width: u32, height: u32) -> (u32, u32, u32) {
+    let size = (width.min(height) / 6)
+        .clamp(48, 128)
+        .min(width)
+        .min(height);
+    let margin = 8.min(width - size).min(height - size);
+    (margin, height - size - margin, size)
+}
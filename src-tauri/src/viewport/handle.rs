@@ -0,0 +1,135 @@
+use super::error::ViewportError;
+use raw_window_handle::{
+    AppKitDisplayHandle, AppKitWindowHandle, RawDisplayHandle, RawWindowHandle,
+    WaylandDisplayHandle, WaylandWindowHandle, Win32WindowHandle, WindowsDisplayHandle,
+    XcbDisplayHandle, XcbWindowHandle, XlibDisplayHandle, XlibWindowHandle,
+};
+use raw_window_handle_05 as rwh05;
+use std::{
+    num::{NonZeroIsize, NonZeroU32},
+    ptr::NonNull,
+};
+
+/// Native window and display handles the viewport surface is created on.
+///
+/// Tauri and tao still speak `raw-window-handle` 0.5 while wgpu expects 0.6,
+/// so the handles are translated once here. Every window system is handled
+/// at runtime rather than behind `cfg`s, so X11 and Wayland sessions on the
+/// same Linux build both work.
+#[derive(Debug, Clone, Copy)]
+pub struct WindowHandle {
+    pub(crate) window: RawWindowHandle,
+    pub(crate) display: RawDisplayHandle,
+}
+
+// SAFETY: the handles are plain identifiers owned by the Tauri window. They
+// are only dereferenced by wgpu while creating a surface, and the viewport
+// drops its surface before the window is destroyed.
+unsafe impl Send for WindowHandle {}
+unsafe impl Sync for WindowHandle {}
+
+impl WindowHandle {
+    pub fn from_window<W>(window: &W) -> Result<Self, ViewportError>
+    where
+        W: rwh05::HasRawWindowHandle + rwh05::HasRawDisplayHandle,
+    {
+        let raw_window = window.raw_window_handle();
+        let window_handle = convert_window_handle(raw_window).ok_or(
+            ViewportError::UnsupportedWindowSystem(window_system_name(&raw_window)),
+        )?;
+        let raw_display = window.raw_display_handle();
+        let display_handle = convert_display_handle(raw_display).ok_or(
+            ViewportError::UnsupportedWindowSystem(display_system_name(&raw_display)),
+        )?;
+
+        Ok(Self {
+            window: window_handle,
+            display: display_handle,
+        })
+    }
+
+    /// Short name of the window system, for logging.
+    pub fn window_system(&self) -> &'static str {
+        match self.window {
+            RawWindowHandle::Xlib(_) => "x11 (xlib)",
+            RawWindowHandle::Xcb(_) => "x11 (xcb)",
+            RawWindowHandle::Wayland(_) => "wayland",
+            RawWindowHandle::Win32(_) => "win32",
+            RawWindowHandle::AppKit(_) => "appkit",
+            _ => "unknown",
+        }
+    }
+}
+
+fn convert_window_handle(handle: rwh05::RawWindowHandle) -> Option<RawWindowHandle> {
+    Some(match handle {
+        rwh05::RawWindowHandle::Xlib(h) => {
+            let mut out = XlibWindowHandle::new(h.window);
+            out.visual_id = h.visual_id;
+            RawWindowHandle::Xlib(out)
+        }
+        rwh05::RawWindowHandle::Xcb(h) => {
+            let mut out = XcbWindowHandle::new(NonZeroU32::new(h.window)?);
+            out.visual_id = NonZeroU32::new(h.visual_id);
+            RawWindowHandle::Xcb(out)
+        }
+        rwh05::RawWindowHandle::Wayland(h) => {
+            RawWindowHandle::Wayland(WaylandWindowHandle::new(NonNull::new(h.surface)?))
+        }
+        rwh05::RawWindowHandle::Win32(h) => {
+            let mut out = Win32WindowHandle::new(NonZeroIsize::new(h.hwnd as isize)?);
+            out.hinstance = NonZeroIsize::new(h.hinstance as isize);
+            RawWindowHandle::Win32(out)
+        }
+        rwh05::RawWindowHandle::AppKit(h) => {
+            RawWindowHandle::AppKit(AppKitWindowHandle::new(NonNull::new(h.ns_view)?))
+        }
+        _ => return None,
+    })
+}
+
+fn convert_display_handle(handle: rwh05::RawDisplayHandle) -> Option<RawDisplayHandle> {
+    Some(match handle {
+        rwh05::RawDisplayHandle::Xlib(h) => {
+            RawDisplayHandle::Xlib(XlibDisplayHandle::new(NonNull::new(h.display), h.screen))
+        }
+        rwh05::RawDisplayHandle::Xcb(h) => {
+            RawDisplayHandle::Xcb(XcbDisplayHandle::new(NonNull::new(h.connection), h.screen))
+        }
+        rwh05::RawDisplayHandle::Wayland(h) => {
+            RawDisplayHandle::Wayland(WaylandDisplayHandle::new(NonNull::new(h.display)?))
+        }
+        rwh05::RawDisplayHandle::Windows(_) => {
+            RawDisplayHandle::Windows(WindowsDisplayHandle::new())
+        }
+        rwh05::RawDisplayHandle::AppKit(_) => RawDisplayHandle::AppKit(AppKitDisplayHandle::new()),
+        _ => return None,
+    })
+}
+
+fn window_system_name(handle: &rwh05::RawWindowHandle) -> &'static str {
+    match handle {
+        rwh05::RawWindowHandle::UiKit(_) => "uikit",
+        rwh05::RawWindowHandle::Orbital(_) => "orbital",
+        rwh05::RawWindowHandle::Drm(_) => "drm",
+        rwh05::RawWindowHandle::Gbm(_) => "gbm",
+        rwh05::RawWindowHandle::WinRt(_) => "winrt",
+        rwh05::RawWindowHandle::Web(_) => "web",
+        rwh05::RawWindowHandle::AndroidNdk(_) => "android",
+        rwh05::RawWindowHandle::Haiku(_) => "haiku",
+        _ => "unknown",
+    }
+}
+
+fn display_system_name(handle: &rwh05::RawDisplayHandle) -> &'static str {
+    match handle {
+        rwh05::RawDisplayHandle::UiKit(_) => "uikit",
+        rwh05::RawDisplayHandle::Orbital(_) => "orbital",
+        rwh05::RawDisplayHandle::Drm(_) => "drm",
+        rwh05::RawDisplayHandle::Gbm(_) => "gbm",
+        rwh05::RawDisplayHandle::Web(_) => "web",
+        rwh05::RawDisplayHandle::Android(_) => "android",
+        rwh05::RawDisplayHandle::Haiku(_) => "haiku",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,713 @@
+use super::{
+    backend::RenderBackend,
+    camera::OrbitCamera,
+    error::{ViewportError, ViewportResult},
+    fault::{Fault, FaultInjector},
+    grid::{self, LineVertex},
+};
+use glam::{Mat4, Vec4};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// RGBA8 image produced by the `HeadlessBackend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameBuffer {
+    pub width: u32,
+    pub height: u32,
+    /// Tightly packed RGBA8 pixels, row-major, top row first.
+    pub pixels: Vec<u8>,
+    /// Number of frames rendered into this buffer so far.
+    pub frame_index: u64,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            frame_index: 0,
+        }
+    }
+
+    /// Returns the RGBA value at `(x, y)`, or `None` if out of bounds.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        Some([
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ])
+    }
+
+    pub fn fill(&mut self, color: [u8; 4]) {
+        for px in self.pixels.chunks_exact_mut(4) {
+            px.copy_from_slice(&color);
+        }
+    }
+
+    /// Counts the pixels whose channels differ from `golden` by more than
+    /// `tolerance`. Returns `None` if the two images differ in size.
+    pub fn count_mismatched_pixels(&self, golden: &FrameBuffer, tolerance: u8) -> Option<usize> {
+        if self.width != golden.width || self.height != golden.height {
+            return None;
+        }
+
+        Some(
+            self.pixels
+                .chunks_exact(4)
+                .zip(golden.pixels.chunks_exact(4))
+                .filter(|(a, b)| {
+                    a.iter()
+                        .zip(b.iter())
+                        .any(|(a, b)| a.abs_diff(*b) > tolerance)
+                })
+                .count(),
+        )
+    }
+
+    /// Rasterizes a line list into the `(x, y, width, height)` region of the
+    /// image, mirroring what the GPU line pipeline draws.
+    pub fn draw_lines(
+        &mut self,
+        vertices: &[LineVertex],
+        view_proj: Mat4,
+        rect: (u32, u32, u32, u32),
+    ) {
+        for line in vertices.chunks_exact(2) {
+            let a = view_proj * Vec4::from((glam::Vec3::from(line[0].position), 1.0));
+            let b = view_proj * Vec4::from((glam::Vec3::from(line[1].position), 1.0));
+            let Some((a, b)) = clip_line(a, b) else {
+                continue;
+            };
+
+            let to_pixel = |clip: Vec4| {
+                let ndc = clip / clip.w;
+                (
+                    rect.0 as f32 + (ndc.x * 0.5 + 0.5) * rect.2 as f32,
+                    rect.1 as f32 + (0.5 - ndc.y * 0.5) * rect.3 as f32,
+                )
+            };
+            let color = linear_to_srgb8(line[0].color);
+            self.draw_segment(to_pixel(a), to_pixel(b), color, rect);
+        }
+    }
+
+    fn draw_segment(
+        &mut self,
+        from: (f32, f32),
+        to: (f32, f32),
+        color: [u8; 4],
+        rect: (u32, u32, u32, u32),
+    ) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as u32;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let x = (from.0 + dx * t).floor();
+            let y = (from.1 + dy * t).floor();
+            if x < rect.0 as f32
+                || y < rect.1 as f32
+                || x >= (rect.0 + rect.2) as f32
+                || y >= (rect.1 + rect.3) as f32
+            {
+                continue;
+            }
+            if let Some(i) = self.index(x as u32, y as u32) {
+                self.pixels[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height)
+            .then(|| (y as usize * self.width as usize + x as usize) * 4)
+    }
+}
+
+/// Clips a clip-space segment against the view volume (Liang-Barsky).
+fn clip_line(a: Vec4, b: Vec4) -> Option<(Vec4, Vec4)> {
+    // Signed distances to the six planes of the wgpu clip volume
+    let planes = |p: Vec4| [p.w + p.x, p.w - p.x, p.w + p.y, p.w - p.y, p.z, p.w - p.z];
+    let (da, db) = (planes(a), planes(b));
+
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for (da, db) in da.into_iter().zip(db) {
+        if da < 0.0 && db < 0.0 {
+            return None;
+        }
+        if da < 0.0 {
+            t0 = t0.max(da / (da - db));
+        } else if db < 0.0 {
+            t1 = t1.min(da / (da - db));
+        }
+    }
+
+    (t0 <= t1).then(|| (a.lerp(b, t0), a.lerp(b, t1)))
+}
+
+/// Encodes a linear RGBA color the way an `*Srgb` render target would.
+fn linear_to_srgb8(color: [f32; 4]) -> [u8; 4] {
+    let encode = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        let srgb = if c <= 0.003_130_8 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (srgb * 255.0).round() as u8
+    };
+    [
+        encode(color[0]),
+        encode(color[1]),
+        encode(color[2]),
+        (color[3].clamp(0.0, 1.0) * 255.0).round() as u8,
+    ]
+}
+
+/// Pure-CPU render backend that draws into an in-memory `FrameBuffer`.
+///
+/// Needs neither a GPU nor a window, so the render loop and the
+/// `ViewportCommand` handling can run on CI and build servers. Injected
+/// faults fail frames like the wgpu backend's, so the recovery paths can be
+/// exercised too.
+pub struct HeadlessBackend {
+    frame: Arc<RwLock<FrameBuffer>>,
+    clear_color: [u8; 4],
+    scene_lines: Vec<LineVertex>,
+    faults: FaultInjector,
+    device_lost: bool,
+}
+
+impl HeadlessBackend {
+    /// sRGB encoding of `WgpuBackend::DEFAULT_CLEAR_COLOR`.
+    pub const DEFAULT_CLEAR_COLOR: [u8; 4] = [25, 25, 25, 255];
+
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            frame: Arc::new(RwLock::new(FrameBuffer::new(width, height))),
+            clear_color: Self::DEFAULT_CLEAR_COLOR,
+            scene_lines: Vec::new(),
+            faults: FaultInjector::default(),
+            device_lost: false,
+        }
+    }
+
+    pub fn with_clear_color(mut self, color: [u8; 4]) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    /// Shares a fault queue with the backend, see `FaultInjector`.
+    pub fn with_fault_injector(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Handle to the output image. Stays valid after the backend has been
+    /// moved into the render loop, so callers can inspect rendered frames.
+    pub fn frame_buffer(&self) -> Arc<RwLock<FrameBuffer>> {
+        self.frame.clone()
+    }
+}
+
+impl RenderBackend for HeadlessBackend {
+    fn resize(&mut self, width: u32, height: u32) -> ViewportResult<()> {
+        let mut frame = self.frame.write();
+        if frame.width == width && frame.height == height {
+            return Ok(());
+        }
+
+        let frame_index = frame.frame_index;
+        *frame = FrameBuffer::new(width, height);
+        frame.frame_index = frame_index;
+        Ok(())
+    }
+
+    fn render_frame(&mut self, camera: &OrbitCamera) -> ViewportResult<()> {
+        if self.device_lost {
+            return Err(ViewportError::DeviceLost);
+        }
+        match self.faults.take() {
+            Some(Fault::SwapchainOutOfDate) => return Err(ViewportError::SwapchainOutOfDate),
+            Some(Fault::SurfaceLost) => return Err(ViewportError::SurfaceLost),
+            Some(Fault::DeviceLost) => {
+                self.device_lost = true;
+                return Err(ViewportError::DeviceLost);
+            }
+            None => {}
+        }
+
+        let mut frame = self.frame.write();
+        frame.fill(self.clear_color);
+
+        let (width, height) = (frame.width, frame.height);
+        if width > 0 && height > 0 {
+            let view_projection = camera.view_projection(width as f32 / height as f32);
+            frame.draw_lines(&grid::ground_grid(), view_projection, (0, 0, width, height));
+            frame.draw_lines(&self.scene_lines, view_projection, (0, 0, width, height));
+            let (x, y, size) = grid::gizmo_viewport(width, height);
+            frame.draw_lines(
+                &grid::axis_gizmo(),
+                grid::gizmo_view_projection(camera),
+                (x, y, size, size),
+            );
+        }
+
+        frame.frame_index += 1;
+        Ok(())
+    }
+
+    fn set_scene_lines(&mut self, lines: &[LineVertex]) {
+        self.scene_lines = lines.to_vec();
+    }
+
+    fn recover(&mut self, error: &ViewportError) -> ViewportResult<bool> {
+        // There is no swapchain to rebuild
+        Ok(matches!(
+            error,
+            ViewportError::SwapchainOutOfDate | ViewportError::SurfaceLost
+        ) && !self.device_lost)
+    }
+
+    /// Renders into the same `FrameBuffer` from now on. Fails if another
+    /// `Fault::DeviceLost` is queued, as if the new device were lost too.
+    fn reinitialize(self: Box<Self>) -> ViewportResult<Box<dyn RenderBackend>> {
+        if self.faults.take_if(Fault::DeviceLost) {
+            return Err(ViewportError::DeviceLost);
+        }
+        Ok(Box::new(Self {
+            device_lost: false,
+            ..*self
+        }))
+    }
+
+    fn is_device_lost(&self) -> bool {
+        self.device_lost
+    }
+
+    fn name(&self) -> &'static str {
+        "headless"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{SharedWorld, Transform, World};
+    use crate::viewport::{
+        events::{EventSink, ViewportEvent},
+        renderer::RenderLoop,
+        state::{Viewport, ViewportCommand, ViewportConfig},
+    };
+    use glam::Vec3;
+    use image::RgbaImage;
+    use parking_lot::Mutex;
+    use std::{
+        path::Path,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const WIDTH: u32 = 160;
+    const HEIGHT: u32 = 120;
+
+    /// A viewport whose render loop drives a `HeadlessBackend`, shut down on
+    /// drop.
+    struct Harness {
+        frame: Arc<RwLock<FrameBuffer>>,
+        viewport: Viewport,
+        events: Arc<Mutex<Vec<ViewportEvent>>>,
+    }
+
+    impl Harness {
+        fn start(backend: HeadlessBackend, world: World) -> Self {
+            let frame = backend.frame_buffer();
+            let viewport = Viewport::new("test".to_string(), "main");
+            let backend = backend.with_fault_injector(viewport.fault_injector.clone());
+            *viewport.backend.write() = Some(Box::new(backend));
+            let world: SharedWorld = Arc::new(RwLock::new(world));
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let sink = received.clone();
+            let events: EventSink = Arc::new(move |event| sink.lock().push(event));
+            let thread = RenderLoop::start(
+                viewport.running.clone(),
+                viewport.command_receiver.clone(),
+                viewport.pending_resize.clone(),
+                viewport.backend.clone(),
+                viewport.frame_stats.clone(),
+                world,
+                events,
+            );
+            viewport.set_render_thread(thread);
+            Self {
+                frame,
+                viewport,
+                events: received,
+            }
+        }
+
+        fn send(&self, cmd: ViewportCommand) {
+            self.viewport.send_command(cmd).unwrap();
+        }
+
+        /// Waits until the loop reported `event`.
+        fn wait_for_event(&self, event: &ViewportEvent) {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !self.events.lock().contains(event) {
+                assert!(
+                    Instant::now() < deadline,
+                    "render loop did not report {:?}",
+                    event
+                );
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        /// Waits until the loop rendered a frame `done` accepts, and returns it.
+        fn wait_for(&self, done: impl Fn(&FrameBuffer) -> bool) -> FrameBuffer {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                {
+                    let frame = self.frame.read();
+                    if done(&frame) {
+                        return frame.clone();
+                    }
+                }
+                assert!(
+                    Instant::now() < deadline,
+                    "render loop did not produce the frame"
+                );
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+
+    impl Drop for Harness {
+        fn drop(&mut self) {
+            self.viewport.shutdown();
+        }
+    }
+
+    fn first_frame(backend: HeadlessBackend, world: World) -> FrameBuffer {
+        Harness::start(backend, world).wait_for(|frame| frame.frame_index >= 1)
+    }
+
+    /// Color the grid, gizmo and markers draw axis `index` (X, Y, Z) in.
+    fn axis_color(index: usize) -> [u8; 4] {
+        linear_to_srgb8(grid::axis_gizmo()[index * 2].color)
+    }
+
+    fn count(frame: &FrameBuffer, color: [u8; 4]) -> usize {
+        frame
+            .pixels
+            .chunks_exact(4)
+            .filter(|px| *px == color)
+            .count()
+    }
+
+    #[test]
+    fn renders_the_grid_over_the_clear_color() {
+        let frame = first_frame(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        assert_eq!((frame.width, frame.height), (WIDTH, HEIGHT));
+
+        let clear = count(&frame, HeadlessBackend::DEFAULT_CLEAR_COLOR);
+        let total = (WIDTH * HEIGHT) as usize;
+        assert!(
+            clear > total / 2,
+            "only {} of {} pixels are cleared",
+            clear,
+            total
+        );
+        assert!(clear < total, "nothing was drawn");
+
+        // The grid lines through the origin in the axis colors
+        assert!(count(&frame, axis_color(0)) > 0);
+        assert!(count(&frame, axis_color(2)) > 0);
+    }
+
+    #[test]
+    fn draws_the_axis_gizmo_in_its_corner() {
+        let frame = first_frame(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        let (x, y, size) = grid::gizmo_viewport(WIDTH, HEIGHT);
+        let y_axis = axis_color(1);
+
+        let mut inside = 0;
+        for py in 0..HEIGHT {
+            for px in 0..WIDTH {
+                if frame.pixel(px, py) == Some(y_axis) {
+                    assert!(
+                        (x..x + size).contains(&px) && (y..y + size).contains(&py),
+                        "Y axis color outside the gizmo at ({}, {})",
+                        px,
+                        py
+                    );
+                    inside += 1;
+                }
+            }
+        }
+        assert!(inside > 0, "the gizmo has no Y axis");
+    }
+
+    #[test]
+    fn clears_to_the_configured_color() {
+        let color = [200, 10, 30, 255];
+        let frame = first_frame(
+            HeadlessBackend::new(WIDTH, HEIGHT).with_clear_color(color),
+            World::new(),
+        );
+        assert_eq!(frame.pixel(0, 0), Some(color));
+        assert_eq!(count(&frame, HeadlessBackend::DEFAULT_CLEAR_COLOR), 0);
+    }
+
+    #[test]
+    fn draws_markers_for_scene_entities() {
+        let empty = first_frame(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+
+        let mut world = World::new();
+        let id = world.spawn("Marker", None).unwrap();
+        world
+            .set_transform(id, Transform::from_position(Vec3::new(2.0, 1.0, 2.0)))
+            .unwrap();
+        let marked = first_frame(HeadlessBackend::new(WIDTH, HEIGHT), world);
+
+        let changed = marked.count_mismatched_pixels(&empty, 0).unwrap();
+        assert!(changed > 0, "the entity left no marker");
+        assert!(count(&marked, axis_color(1)) > count(&empty, axis_color(1)));
+    }
+
+    #[test]
+    fn resizes_on_config_updates() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        let before = harness.wait_for(|frame| frame.frame_index >= 1);
+
+        harness
+            .viewport
+            .resize(ViewportConfig {
+                width: 64,
+                height: 32,
+                ..ViewportConfig::default()
+            })
+            .unwrap();
+        // A resized buffer is blank until the next frame is rendered into it
+        let after = harness.wait_for(|frame| frame.width == 64 && frame.pixels[3] == 255);
+
+        assert_eq!(after.height, 32);
+        assert_eq!(after.pixels.len(), 64 * 32 * 4);
+        assert!(after.frame_index > before.frame_index);
+    }
+
+    #[test]
+    fn coalesces_bursts_of_resizes() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        harness.wait_for(|frame| frame.frame_index >= 1);
+
+        // Far more resizes than the command queue holds, faster than frames
+        for width in 1..=200 {
+            let config = ViewportConfig {
+                width,
+                height: 50,
+                ..ViewportConfig::default()
+            };
+            harness.viewport.resize(config).unwrap();
+        }
+
+        let last = harness.wait_for(|frame| frame.width == 200 && frame.pixels[3] == 255);
+        assert_eq!(last.height, 50);
+        assert_eq!(harness.viewport.config.read().width, 200);
+    }
+
+    #[test]
+    fn keeps_rendering_after_injected_faults() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        let first = harness.wait_for(|frame| frame.frame_index >= 1);
+
+        for fault in [
+            Fault::SwapchainOutOfDate,
+            Fault::SurfaceLost,
+            Fault::DeviceLost,
+        ] {
+            let before = harness.frame.read().frame_index;
+            harness.viewport.fault_injector.inject(fault);
+            harness.send(ViewportCommand::RequestRedraw);
+            let after = harness.wait_for(|frame| frame.frame_index > before);
+            assert_eq!(after.count_mismatched_pixels(&first, 0), Some(0));
+        }
+
+        let events = harness.events.lock();
+        assert_eq!(
+            *events,
+            [
+                ViewportEvent::SwapchainRecreated {
+                    reason: "swapchainOutOfDate"
+                },
+                ViewportEvent::SwapchainRecreated {
+                    reason: "surfaceLost"
+                },
+                ViewportEvent::DeviceLost,
+                ViewportEvent::DeviceRecovered {
+                    adapter: "headless".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_a_device_it_cannot_recreate() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        harness.wait_for(|frame| frame.frame_index >= 1);
+
+        harness.viewport.fault_injector.inject(Fault::DeviceLost);
+        harness.viewport.fault_injector.inject(Fault::DeviceLost);
+        harness.send(ViewportCommand::RequestRedraw);
+        harness.wait_for_event(&ViewportEvent::DeviceUnrecoverable {
+            code: "deviceLost",
+            message: ViewportError::DeviceLost.to_string(),
+        });
+
+        assert!(harness.viewport.backend.read().is_none());
+        // Commands are still accepted, there is just nothing to render with
+        harness.send(ViewportCommand::RequestRedraw);
+    }
+
+    /// Orbits, pans and zooms the camera and waits for the frame showing
+    /// the moved view.
+    fn move_camera(harness: &Harness, first: &FrameBuffer) -> FrameBuffer {
+        let (delta_yaw, delta_pitch) = (90f32.to_radians(), 20f32.to_radians());
+        harness.send(ViewportCommand::OrbitCamera {
+            delta_yaw,
+            delta_pitch,
+        });
+        harness.send(ViewportCommand::PanCamera { dx: 0.25, dy: 0.1 });
+        harness.send(ViewportCommand::ZoomCamera { factor: 0.5 });
+
+        let mut camera = OrbitCamera::default();
+        camera.orbit(delta_yaw, delta_pitch);
+        camera.pan(0.25, 0.1);
+        camera.zoom(0.5);
+        let expected = render_with(&camera);
+        harness.wait_for(|frame| {
+            frame.frame_index > first.frame_index
+                && frame.count_mismatched_pixels(&expected, 0) == Some(0)
+        })
+    }
+
+    #[test]
+    fn rerenders_after_camera_commands() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        let first = harness.wait_for(|frame| frame.frame_index >= 1);
+
+        let moved = move_camera(&harness, &first);
+        assert!(moved.count_mismatched_pixels(&first, 0).unwrap() > 0);
+    }
+
+    #[test]
+    fn restores_the_first_frame_after_a_camera_reset() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        let first = harness.wait_for(|frame| frame.frame_index >= 1);
+        let moved = move_camera(&harness, &first);
+
+        harness.send(ViewportCommand::ResetCamera);
+        let reset = harness.wait_for(|frame| frame.frame_index > moved.frame_index);
+
+        // Default camera, same image as before it moved
+        assert_eq!(reset.count_mismatched_pixels(&first, 0), Some(0));
+    }
+
+    /// Per-channel difference tolerated against a golden image, and the
+    /// share of pixels allowed beyond it, for float differences between
+    /// platforms.
+    const GOLDEN_TOLERANCE: u8 = 2;
+    const GOLDEN_MAX_MISMATCHED: f64 = 0.005;
+
+    /// Compares `frame` to `tests/fixtures/viewport/<name>.png`. Run with
+    /// `UPDATE_GOLDEN=1` to write the golden image instead.
+    fn assert_matches_golden(frame: &FrameBuffer, name: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/viewport")
+            .join(name)
+            .with_extension("png");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            RgbaImage::from_raw(frame.width, frame.height, frame.pixels.clone())
+                .unwrap()
+                .save(&path)
+                .unwrap();
+            return;
+        }
+
+        let golden = image::open(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e))
+            .into_rgba8();
+        let golden = FrameBuffer {
+            width: golden.width(),
+            height: golden.height(),
+            pixels: golden.into_raw(),
+            frame_index: frame.frame_index,
+        };
+        let mismatched = frame
+            .count_mismatched_pixels(&golden, GOLDEN_TOLERANCE)
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} is {}x{}, the frame {}x{}",
+                    name, golden.width, golden.height, frame.width, frame.height
+                )
+            });
+        let allowed = (frame.pixels.len() / 4) as f64 * GOLDEN_MAX_MISMATCHED;
+        assert!(
+            mismatched as f64 <= allowed,
+            "{} pixels differ from {}",
+            mismatched,
+            name
+        );
+    }
+
+    fn render_with(camera: &OrbitCamera) -> FrameBuffer {
+        let mut backend = HeadlessBackend::new(WIDTH, HEIGHT);
+        backend.render_frame(camera).unwrap();
+        let frame = backend.frame_buffer().read().clone();
+        frame
+    }
+
+    #[test]
+    fn grid_matches_golden() {
+        assert_matches_golden(&render_with(&OrbitCamera::default()), "grid");
+    }
+
+    #[test]
+    fn orbited_camera_matches_golden() {
+        let mut camera = OrbitCamera::default();
+        camera.orbit(90f32.to_radians(), 20f32.to_radians());
+        camera.zoom(0.5);
+        assert_matches_golden(&render_with(&camera), "grid_orbited");
+    }
+
+    #[test]
+    fn panned_camera_matches_golden() {
+        let mut camera = OrbitCamera::default();
+        camera.pan(0.25, 0.1);
+        assert_matches_golden(&render_with(&camera), "grid_panned");
+    }
+
+    #[test]
+    fn top_down_camera_matches_golden() {
+        let mut camera = OrbitCamera::default();
+        camera.orbit(0.0, std::f32::consts::FRAC_PI_2);
+        assert_matches_golden(&render_with(&camera), "grid_top_down");
+    }
+
+    #[test]
+    fn reset_camera_matches_the_default_golden() {
+        let harness = Harness::start(HeadlessBackend::new(WIDTH, HEIGHT), World::new());
+        let first = harness.wait_for(|frame| frame.frame_index >= 1);
+        let moved = move_camera(&harness, &first);
+
+        harness.send(ViewportCommand::ResetCamera);
+        let reset = harness.wait_for(|frame| frame.frame_index > moved.frame_index);
+        assert_matches_golden(&reset, "grid");
+    }
+}
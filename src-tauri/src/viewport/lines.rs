@@ -0,0 +1,173 @@
+use super::grid::LineVertex;
+use glam::Mat4;
+use wgpu::util::DeviceExt;
+
+/// A static line list with its own view-projection uniform.
+struct LineBatch {
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// GPU resources for drawing the editor grid, the scene's entity markers
+/// and the axis gizmo.
+pub struct LineRenderer {
+    pipeline: wgpu::RenderPipeline,
+    format: wgpu::TextureFormat,
+    grid: LineBatch,
+    gizmo: LineBatch,
+    /// Entity markers, drawn with the grid's view-projection.
+    scene: Option<(wgpu::Buffer, u32)>,
+}
+
+impl LineRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        grid: &[LineVertex],
+        gizmo: &[LineVertex],
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Line Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/lines.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Line Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Line Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Line Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let batch = |label: &str, vertices: &[LineVertex]| {
+            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&Mat4::IDENTITY.to_cols_array()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            });
+            LineBatch {
+                vertex_buffer,
+                vertex_count: vertices.len() as u32,
+                uniform_buffer,
+                bind_group,
+            }
+        };
+
+        Self {
+            grid: batch("Grid Lines", grid),
+            gizmo: batch("Gizmo Lines", gizmo),
+            scene: None,
+            pipeline,
+            format,
+        }
+    }
+
+    /// Replaces the entity marker lines.
+    pub fn set_scene(&mut self, device: &wgpu::Device, vertices: &[LineVertex]) {
+        self.scene = (!vertices.is_empty()).then(|| {
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Scene Lines"),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            (buffer, vertices.len() as u32)
+        });
+    }
+
+    /// Format of the color target the pipeline was built for.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// Uploads the view-projection matrices for the next frame.
+    pub fn update(&self, queue: &wgpu::Queue, grid_view_proj: Mat4, gizmo_view_proj: Mat4) {
+        queue.write_buffer(
+            &self.grid.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&grid_view_proj.to_cols_array()),
+        );
+        queue.write_buffer(
+            &self.gizmo.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&gizmo_view_proj.to_cols_array()),
+        );
+    }
+
+    /// Draws the grid and entity markers over the full target, then the
+    /// gizmo inside `gizmo_rect` (`x, y, size` in pixels).
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, gizmo_rect: (u32, u32, u32)) {
+        pass.set_pipeline(&self.pipeline);
+
+        pass.set_bind_group(0, &self.grid.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.grid.vertex_buffer.slice(..));
+        pass.draw(0..self.grid.vertex_count, 0..1);
+
+        if let Some((buffer, vertex_count)) = &self.scene {
+            pass.set_vertex_buffer(0, buffer.slice(..));
+            pass.draw(0..*vertex_count, 0..1);
+        }
+
+        let (x, y, size) = gizmo_rect;
+        pass.set_viewport(x as f32, y as f32, size as f32, size as f32, 0.0, 1.0);
+        pass.set_bind_group(0, &self.gizmo.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.gizmo.vertex_buffer.slice(..));
+        pass.draw(0..self.gizmo.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::assets::{mesh::MESH_EXTENSION, MeshAsset};
+use glam::Vec3;
+use log::{debug, warn};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Bounds of the mesh assets drawn by a viewport, loaded before they are
+/// first drawn and dropped when mesh files change on disk.
+#[derive(Debug, Default)]
+pub struct MeshBoundsCache {
+    bounds: HashMap<String, Option<(Vec3, Vec3)>>,
+    /// Project root relative mesh paths are resolved against.
+    root: Option<PathBuf>,
+}
+
+impl MeshBoundsCache {
+    /// Resolves relative mesh paths against `root` from now on, forgetting
+    /// every mesh if it changed. Returns whether it did.
+    pub fn set_root(&mut self, root: Option<PathBuf>) -> bool {
+        if self.root == root {
+            return false;
+        }
+        self.root = root;
+        self.bounds.clear();
+        true
+    }
+
+    /// Loads the bounds of the mesh component values in `meshes` not loaded
+    /// yet. Reads the mesh files, so is best called without holding the
+    /// scene lock.
+    pub fn load<'a>(&mut self, meshes: impl IntoIterator<Item = &'a str>) {
+        for mesh in meshes {
+            // Built-in primitives have no extension
+            if Path::new(mesh).extension().is_none() || self.bounds.contains_key(mesh) {
+                continue;
+            }
+            let path = match &self.root {
+                Some(root) => root.join(mesh),
+                None => PathBuf::from(mesh),
+            };
+            let bounds = match MeshAsset::load(&path) {
+                Ok(asset) => asset.bounds(),
+                Err(e) => {
+                    warn!("Failed to load mesh {}: {}", mesh, e);
+                    None
+                }
+            };
+            self.bounds.insert(mesh.to_string(), bounds);
+        }
+    }
+
+    /// Bounds of the mesh component value `mesh`. `None` for built-in
+    /// primitives, meshes that fail to load and those not loaded yet.
+    pub fn get(&self, mesh: &str) -> Option<(Vec3, Vec3)> {
+        self.bounds.get(mesh).copied().flatten()
+    }
+
+    /// Forgets every mesh if any of `paths` is a mesh file, returning
+    /// whether it did.
+    pub fn invalidate(&mut self, paths: &[PathBuf]) -> bool {
+        let changed = paths.iter().any(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == MESH_EXTENSION)
+        });
+        if changed {
+            debug!("Mesh files changed, reloading {} meshes", self.bounds.len());
+            self.bounds.clear();
+        }
+        changed
+    }
+}
@@ -1,7 +1,28 @@
-// pub mod state;
-// pub mod vulkan;
-// pub mod renderer;
+pub mod adapter;
+pub mod backend;
+pub mod camera;
+pub mod error;
+pub mod events;
+pub mod fault;
+pub mod gpu;
+pub mod grid;
+pub mod handle;
+pub mod headless;
+pub mod lines;
+pub mod meshes;
+pub mod pacing;
+pub mod policy;
+pub mod state;
+pub mod stats;
+pub mod target;
+pub mod timer;
+pub mod wgpu_backend;
+pub mod renderer;
 
-// pub use state::ViewportState;
-// pub use vulkan::VulkanState;
-// pub use renderer::RenderLoop;
+pub use backend::RenderBackend;
+pub use camera::OrbitCamera;
+pub use error::ViewportError;
+pub use headless::HeadlessBackend;
+pub use state::ViewportState;
+pub use wgpu_backend::WgpuBackend;
+pub use renderer::RenderLoop;
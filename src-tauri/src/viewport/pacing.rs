@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use super::error::{ViewportError, ViewportResult};
+
+/// How the render loop paces frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum FramePacing {
+    /// Render as fast as possible.
+    Uncapped,
+    /// Render at most `fps` frames per second.
+    Fixed { fps: u32 },
+    /// Render once per display refresh. Presenting blocks on vblank when
+    /// vsync is on in the `DisplaySettings`, which own the present mode;
+    /// otherwise frames are capped at the configured refresh rate.
+    #[default]
+    #[serde(rename = "vsync")]
+    VSync,
+    /// Only render when something changed, e.g. a resize or camera move.
+    OnDemand,
+}
+
+impl FramePacing {
+    /// Rejects settings the limiter cannot pace by, such as a cap of zero
+    /// frames per second.
+    pub fn validate(&self) -> ViewportResult<()> {
+        match self {
+            FramePacing::Fixed { fps: 0 } => Err(ViewportError::InvalidFramePacing(
+                "a fixed frame rate must be at least 1 fps",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Decides when the render loop should produce the next frame.
+pub struct FrameLimiter {
+    pacing: FramePacing,
+    backend_vsync: bool,
+    refresh_rate: u32,
+    last_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// Refresh rate assumed when the display settings do not name one.
+    pub const VSYNC_FALLBACK_FPS: u32 = 60;
+
+    /// Upper bound on how long the loop blocks waiting for commands, so a
+    /// cleared `running` flag is noticed even when nothing is sent.
+    pub const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    pub fn new(pacing: FramePacing) -> Self {
+        Self {
+            pacing,
+            backend_vsync: false,
+            refresh_rate: Self::VSYNC_FALLBACK_FPS,
+            last_frame: None,
+        }
+    }
+
+    pub fn pacing(&self) -> FramePacing {
+        self.pacing
+    }
+
+    pub fn set_pacing(&mut self, pacing: FramePacing) {
+        self.pacing = pacing;
+    }
+
+    /// Tells whether the backend blocks on vblank itself, as reported after
+    /// applying the display settings.
+    pub fn set_backend_vsync(&mut self, backend_vsync: bool) {
+        self.backend_vsync = backend_vsync;
+    }
+
+    /// Sets the display refresh rate vsync'd pacing falls back to when the
+    /// backend does not block on vblank.
+    pub fn set_refresh_rate(&mut self, refresh_rate: Option<u32>) {
+        self.refresh_rate = refresh_rate
+            .filter(|&hz| hz > 0)
+            .unwrap_or(Self::VSYNC_FALLBACK_FPS);
+    }
+
+    /// Minimum interval between two frames, if the mode imposes one.
+    fn min_interval(&self) -> Option<Duration> {
+        match self.pacing {
+            FramePacing::Fixed { fps } if fps > 0 => Some(Duration::from_secs(1) / fps),
+            FramePacing::VSync if !self.backend_vsync => {
+                Some(Duration::from_secs(1) / self.refresh_rate)
+            }
+            _ => None,
+        }
+    }
+
+    /// How long the loop may wait for commands before the next frame is due.
+    ///
+    /// `dirty` tells whether the viewport changed since the last frame; in
+    /// on-demand mode nothing is due until it did.
+    pub fn time_until_next_frame(&self, now: Instant, dirty: bool) -> Duration {
+        if matches!(self.pacing, FramePacing::OnDemand) && !dirty {
+            return Self::IDLE_POLL_INTERVAL;
+        }
+
+        match (self.min_interval(), self.last_frame) {
+            (Some(interval), Some(last)) => (last + interval).saturating_duration_since(now),
+            _ => Duration::ZERO,
+        }
+    }
+
+    pub fn should_render(&self, now: Instant, dirty: bool) -> bool {
+        if matches!(self.pacing, FramePacing::OnDemand) {
+            return dirty;
+        }
+        self.time_until_next_frame(now, dirty).is_zero()
+    }
+
+    pub fn frame_started(&mut self, now: Instant) {
+        self.last_frame = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// A limiter that rendered its first frame at `start`.
+    fn started(pacing: FramePacing, start: Instant) -> FrameLimiter {
+        let mut limiter = FrameLimiter::new(pacing);
+        limiter.frame_started(start);
+        limiter
+    }
+
+    #[test]
+    fn rejects_a_fixed_rate_of_zero() {
+        let error = FramePacing::Fixed { fps: 0 }.validate().unwrap_err();
+        assert_eq!(error.code(), "invalidFramePacing");
+
+        assert!(FramePacing::Fixed { fps: 1 }.validate().is_ok());
+        assert!(FramePacing::Uncapped.validate().is_ok());
+        assert!(FramePacing::OnDemand.validate().is_ok());
+    }
+
+    #[test]
+    fn renders_the_first_frame_immediately() {
+        let now = Instant::now();
+        for pacing in [
+            FramePacing::Uncapped,
+            FramePacing::Fixed { fps: 30 },
+            FramePacing::VSync,
+        ] {
+            let limiter = FrameLimiter::new(pacing);
+            assert_eq!(limiter.time_until_next_frame(now, false), Duration::ZERO);
+            assert!(limiter.should_render(now, false));
+        }
+    }
+
+    #[test]
+    fn never_waits_when_uncapped() {
+        let start = Instant::now();
+        let limiter = started(FramePacing::Uncapped, start);
+        assert!(limiter.should_render(start, false));
+    }
+
+    #[test]
+    fn spaces_frames_at_a_fixed_rate() {
+        let start = Instant::now();
+        let limiter = started(FramePacing::Fixed { fps: 50 }, start);
+
+        assert_eq!(limiter.time_until_next_frame(start, false), ms(20));
+        assert_eq!(limiter.time_until_next_frame(start + ms(15), false), ms(5));
+        assert!(!limiter.should_render(start + ms(19), true));
+        assert!(limiter.should_render(start + ms(20), false));
+        // Late frames are due at once, not early the next time
+        assert_eq!(
+            limiter.time_until_next_frame(start + ms(35), false),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn paces_vsync_by_the_refresh_rate_without_backend_vsync() {
+        let start = Instant::now();
+        let mut limiter = started(FramePacing::VSync, start);
+        assert_eq!(
+            limiter.time_until_next_frame(start, false),
+            Duration::from_secs(1) / FrameLimiter::VSYNC_FALLBACK_FPS
+        );
+
+        limiter.set_refresh_rate(Some(100));
+        assert_eq!(limiter.time_until_next_frame(start, false), ms(10));
+
+        // An unknown rate falls back rather than dividing by zero
+        limiter.set_refresh_rate(Some(0));
+        assert_eq!(
+            limiter.time_until_next_frame(start, false),
+            Duration::from_secs(1) / FrameLimiter::VSYNC_FALLBACK_FPS
+        );
+    }
+
+    #[test]
+    fn leaves_vsync_to_a_backend_that_blocks_on_vblank() {
+        let start = Instant::now();
+        let mut limiter = started(FramePacing::VSync, start);
+        limiter.set_backend_vsync(true);
+        assert!(limiter.should_render(start, false));
+    }
+
+    #[test]
+    fn renders_on_demand_only_when_dirty() {
+        let start = Instant::now();
+        let limiter = started(FramePacing::OnDemand, start);
+
+        assert!(!limiter.should_render(start + ms(500), false));
+        assert_eq!(
+            limiter.time_until_next_frame(start + ms(500), false),
+            FrameLimiter::IDLE_POLL_INTERVAL
+        );
+        assert!(limiter.should_render(start, true));
+        assert_eq!(limiter.time_until_next_frame(start, true), Duration::ZERO);
+    }
+
+    #[test]
+    fn applies_a_new_pacing_to_the_next_frame() {
+        let start = Instant::now();
+        let mut limiter = started(FramePacing::Fixed { fps: 10 }, start);
+        assert!(!limiter.should_render(start + ms(50), false));
+
+        limiter.set_pacing(FramePacing::Fixed { fps: 100 });
+        assert_eq!(limiter.pacing(), FramePacing::Fixed { fps: 100 });
+        assert!(limiter.should_render(start + ms(50), false));
+    }
+}
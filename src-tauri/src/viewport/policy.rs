@@ -0,0 +1,414 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Display options from the editor settings (`src/config/settings.json`) the
+/// swapchain is configured from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DisplaySettings {
+    pub hdr_enabled: bool,
+    pub vsync: bool,
+    pub adaptive_sync: bool,
+    /// Display refresh rate in Hz, `None` for the `"auto"` setting.
+    #[serde(deserialize_with = "deserialize_refresh_rate")]
+    pub refresh_rate: Option<u32>,
+}
+
+impl Default for DisplaySettings {
+    /// Mirrors the default values in `settings.json`.
+    fn default() -> Self {
+        Self {
+            hdr_enabled: false,
+            vsync: false,
+            adaptive_sync: true,
+            refresh_rate: None,
+        }
+    }
+}
+
+/// Accepts the `"auto"`/`"144"` strings used by the settings page as well as
+/// plain numbers.
+fn deserialize_refresh_rate<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RefreshRate {
+        Hz(u32),
+        Text(String),
+    }
+
+    Ok(match Option::<RefreshRate>::deserialize(deserializer)? {
+        Some(RefreshRate::Hz(hz)) => Some(hz),
+        Some(RefreshRate::Text(text)) if text != "auto" => {
+            Some(text.parse().map_err(serde::de::Error::custom)?)
+        }
+        _ => None,
+    }
+    .filter(|&hz| hz > 0))
+}
+
+/// How the color values written to a swapchain format are interpreted.
+///
+/// wgpu does not expose the surface color space, so it is derived from the
+/// format the way the platforms default it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorSpace {
+    /// 8-bit sRGB, the SDR default.
+    Srgb,
+    /// 10-bit HDR10; output must be PQ encoded in the Rec. 2020 gamut.
+    /// Never chosen, since the shaders write linear color.
+    Hdr10,
+    /// Linear extended sRGB in half floats, where 1.0 is SDR white.
+    ScRgb,
+}
+
+impl ColorSpace {
+    pub fn of(format: wgpu::TextureFormat) -> Option<Self> {
+        use wgpu::TextureFormat::*;
+        match format {
+            Bgra8Unorm | Bgra8UnormSrgb | Rgba8Unorm | Rgba8UnormSrgb => Some(ColorSpace::Srgb),
+            Rgb10a2Unorm => Some(ColorSpace::Hdr10),
+            Rgba16Float => Some(ColorSpace::ScRgb),
+            _ => None,
+        }
+    }
+
+    pub fn is_hdr(self) -> bool {
+        !matches!(self, ColorSpace::Srgb)
+    }
+}
+
+/// Swapchain configuration picked by a `SwapchainPolicy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapchainChoice {
+    pub format: wgpu::TextureFormat,
+    /// Format render passes use. The sRGB view of an 8-bit unorm format, so
+    /// linear shader output is encoded on write.
+    pub view_format: wgpu::TextureFormat,
+    pub color_space: ColorSpace,
+    pub present_mode: wgpu::PresentMode,
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    /// Frames that may be queued ahead of the display, which determines the
+    /// number of swapchain images.
+    pub frame_latency: u32,
+}
+
+impl SwapchainChoice {
+    /// Whether presenting waits for the display refresh.
+    pub fn blocks_on_vblank(&self) -> bool {
+        matches!(
+            self.present_mode,
+            wgpu::PresentMode::Fifo | wgpu::PresentMode::FifoRelaxed
+        )
+    }
+}
+
+/// Ranks surface formats, present modes and image counts against the user's
+/// display settings.
+///
+/// The policy only looks at the capability lists it is given, so its
+/// decisions can be checked against synthetic capabilities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapchainPolicy {
+    pub hdr: bool,
+    pub vsync: bool,
+    pub adaptive_sync: bool,
+    pub refresh_rate: Option<u32>,
+}
+
+impl Default for SwapchainPolicy {
+    fn default() -> Self {
+        Self::from(&DisplaySettings::default())
+    }
+}
+
+impl From<&DisplaySettings> for SwapchainPolicy {
+    fn from(settings: &DisplaySettings) -> Self {
+        Self {
+            hdr: settings.hdr_enabled,
+            vsync: settings.vsync,
+            adaptive_sync: settings.adaptive_sync,
+            refresh_rate: settings.refresh_rate,
+        }
+    }
+}
+
+impl SwapchainPolicy {
+    /// Refresh rates at which a third swapchain image is kept, since a
+    /// missed vblank costs less than a stalled frame there.
+    pub const HIGH_REFRESH_RATE: u32 = 120;
+
+    /// Picks the swapchain configuration, or `None` if the surface reports
+    /// no formats at all.
+    pub fn choose(&self, caps: &wgpu::SurfaceCapabilities) -> Option<SwapchainChoice> {
+        let format = *self.rank_formats(&caps.formats).first()?;
+        let present_mode = self.present_mode(&caps.present_modes);
+
+        Some(SwapchainChoice {
+            format,
+            view_format: view_format(format),
+            color_space: ColorSpace::of(format).unwrap_or(ColorSpace::Srgb),
+            present_mode,
+            alpha_mode: alpha_mode(&caps.alpha_modes),
+            frame_latency: self.frame_latency(present_mode),
+        })
+    }
+
+    /// Formats ordered from most to least preferred. Formats of equal rank
+    /// keep the order the surface reported them in.
+    pub fn rank_formats(&self, formats: &[wgpu::TextureFormat]) -> Vec<wgpu::TextureFormat> {
+        let mut ranked = formats.to_vec();
+        ranked.sort_by_key(|&format| std::cmp::Reverse(self.format_score(format)));
+        ranked
+    }
+
+    /// HDR10 formats rank with unknown ones: the shaders write linear color
+    /// with no PQ encoding, and wgpu cannot set the surface color space, so
+    /// they would come out too dark. With HDR on, surfaces without scRGB
+    /// stay SDR.
+    fn format_score(&self, format: wgpu::TextureFormat) -> u32 {
+        match (ColorSpace::of(format), self.hdr) {
+            (Some(ColorSpace::ScRgb), true) => 3,
+            (Some(ColorSpace::Srgb), _) if format.is_srgb() => 2,
+            (Some(ColorSpace::Srgb), _) => 1,
+            _ => 0,
+        }
+    }
+
+    /// Present mode for the vsync and adaptive sync settings, falling back
+    /// to FIFO, which every surface supports.
+    ///
+    /// With vsync off, mailbox avoids tearing without blocking. Adaptive
+    /// sync displays follow immediate presentation instead, and with vsync
+    /// on they may tear a late frame rather than wait a full refresh.
+    pub fn present_mode(&self, available: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        use wgpu::PresentMode::*;
+        let preferred: &[wgpu::PresentMode] = match (self.vsync, self.adaptive_sync) {
+            (true, true) => &[FifoRelaxed, Fifo],
+            (true, false) => &[Fifo],
+            (false, true) => &[Immediate, Mailbox, Fifo],
+            (false, false) => &[Mailbox, Immediate, Fifo],
+        };
+
+        preferred
+            .iter()
+            .copied()
+            .find(|mode| available.contains(mode))
+            .unwrap_or(Fifo)
+    }
+
+    /// Maximum frame latency for `present_mode`: two frames by default, three
+    /// for mailbox, which needs a spare image, and at high refresh rates.
+    pub fn frame_latency(&self, present_mode: wgpu::PresentMode) -> u32 {
+        let high_refresh = self
+            .refresh_rate
+            .map_or(false, |hz| hz >= Self::HIGH_REFRESH_RATE);
+        if present_mode == wgpu::PresentMode::Mailbox || high_refresh {
+            3
+        } else {
+            2
+        }
+    }
+}
+
+fn view_format(format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+    match ColorSpace::of(format) {
+        Some(ColorSpace::Srgb) => format.add_srgb_suffix(),
+        _ => format,
+    }
+}
+
+fn alpha_mode(available: &[wgpu::CompositeAlphaMode]) -> wgpu::CompositeAlphaMode {
+    if available.contains(&wgpu::CompositeAlphaMode::Opaque) {
+        wgpu::CompositeAlphaMode::Opaque
+    } else {
+        available
+            .first()
+            .copied()
+            .unwrap_or(wgpu::CompositeAlphaMode::Auto)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::{CompositeAlphaMode, PresentMode, TextureFormat, TextureUsages};
+
+    fn caps(
+        formats: &[TextureFormat],
+        present_modes: &[PresentMode],
+        alpha_modes: &[CompositeAlphaMode],
+    ) -> wgpu::SurfaceCapabilities {
+        wgpu::SurfaceCapabilities {
+            formats: formats.to_vec(),
+            present_modes: present_modes.to_vec(),
+            alpha_modes: alpha_modes.to_vec(),
+            usages: TextureUsages::RENDER_ATTACHMENT,
+        }
+    }
+
+    fn policy(hdr: bool, vsync: bool, adaptive_sync: bool) -> SwapchainPolicy {
+        SwapchainPolicy {
+            hdr,
+            vsync,
+            adaptive_sync,
+            refresh_rate: None,
+        }
+    }
+
+    const ALL_FORMATS: [TextureFormat; 5] = [
+        TextureFormat::Bgra8Unorm,
+        TextureFormat::Rgb10a2Unorm,
+        TextureFormat::Rgba16Float,
+        TextureFormat::Bgra8UnormSrgb,
+        TextureFormat::Rgba32Float,
+    ];
+
+    #[test]
+    fn prefers_srgb_formats_without_hdr() {
+        let ranked = policy(false, true, false).rank_formats(&ALL_FORMATS);
+        assert_eq!(ranked[0], TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(ranked[1], TextureFormat::Bgra8Unorm);
+    }
+
+    #[test]
+    fn prefers_scrgb_with_hdr() {
+        let ranked = policy(true, true, false).rank_formats(&ALL_FORMATS);
+        assert_eq!(ranked[0], TextureFormat::Rgba16Float);
+        assert_eq!(ranked[1], TextureFormat::Bgra8UnormSrgb);
+    }
+
+    #[test]
+    fn never_picks_hdr10_over_srgb() {
+        let formats = [TextureFormat::Rgb10a2Unorm, TextureFormat::Bgra8Unorm];
+        for hdr in [false, true] {
+            let choice = policy(hdr, true, false)
+                .choose(&caps(&formats, &[PresentMode::Fifo], &[]))
+                .unwrap();
+            assert_eq!(choice.format, TextureFormat::Bgra8Unorm);
+            assert_eq!(choice.color_space, ColorSpace::Srgb);
+        }
+    }
+
+    #[test]
+    fn keeps_the_surface_order_for_equal_ranks() {
+        let formats = [TextureFormat::Rgba8UnormSrgb, TextureFormat::Bgra8UnormSrgb];
+        assert_eq!(policy(false, true, false).rank_formats(&formats), formats);
+    }
+
+    #[test]
+    fn renders_through_the_srgb_view_of_unorm_formats() {
+        let choice = policy(false, true, false)
+            .choose(&caps(
+                &[TextureFormat::Bgra8Unorm],
+                &[PresentMode::Fifo],
+                &[],
+            ))
+            .unwrap();
+        assert_eq!(choice.view_format, TextureFormat::Bgra8UnormSrgb);
+
+        let choice = policy(true, true, false)
+            .choose(&caps(
+                &[TextureFormat::Rgba16Float],
+                &[PresentMode::Fifo],
+                &[],
+            ))
+            .unwrap();
+        assert_eq!(choice.view_format, TextureFormat::Rgba16Float);
+        assert!(choice.color_space.is_hdr());
+    }
+
+    #[test]
+    fn chooses_nothing_without_formats() {
+        assert_eq!(
+            policy(false, true, false).choose(&caps(&[], &[PresentMode::Fifo], &[])),
+            None
+        );
+    }
+
+    #[test]
+    fn picks_present_modes_for_vsync_and_adaptive_sync() {
+        use PresentMode::*;
+        let all = [Immediate, Mailbox, Fifo, FifoRelaxed];
+        assert_eq!(policy(false, true, true).present_mode(&all), FifoRelaxed);
+        assert_eq!(policy(false, true, false).present_mode(&all), Fifo);
+        assert_eq!(policy(false, false, true).present_mode(&all), Immediate);
+        assert_eq!(policy(false, false, false).present_mode(&all), Mailbox);
+    }
+
+    #[test]
+    fn falls_back_to_fifo() {
+        use PresentMode::*;
+        assert_eq!(policy(false, true, true).present_mode(&[Fifo]), Fifo);
+        assert_eq!(
+            policy(false, false, true).present_mode(&[Mailbox, Fifo]),
+            Mailbox
+        );
+        assert_eq!(
+            policy(false, false, false).present_mode(&[Immediate]),
+            Immediate
+        );
+        assert_eq!(policy(false, false, false).present_mode(&[]), Fifo);
+    }
+
+    #[test]
+    fn blocks_on_vblank_only_with_fifo_modes() {
+        let formats = [TextureFormat::Bgra8UnormSrgb];
+        let all = [
+            PresentMode::Immediate,
+            PresentMode::Mailbox,
+            PresentMode::Fifo,
+        ];
+        let vsync = policy(false, true, false).choose(&caps(&formats, &all, &[]));
+        let tearing = policy(false, false, false).choose(&caps(&formats, &all, &[]));
+        assert!(vsync.unwrap().blocks_on_vblank());
+        assert!(!tearing.unwrap().blocks_on_vblank());
+    }
+
+    #[test]
+    fn keeps_a_spare_image_for_mailbox_and_high_refresh_rates() {
+        let mut policy = policy(false, false, false);
+        assert_eq!(policy.frame_latency(PresentMode::Fifo), 2);
+        assert_eq!(policy.frame_latency(PresentMode::Mailbox), 3);
+
+        policy.refresh_rate = Some(SwapchainPolicy::HIGH_REFRESH_RATE);
+        assert_eq!(policy.frame_latency(PresentMode::Fifo), 3);
+        policy.refresh_rate = Some(60);
+        assert_eq!(policy.frame_latency(PresentMode::Fifo), 2);
+    }
+
+    #[test]
+    fn prefers_opaque_alpha() {
+        use CompositeAlphaMode::*;
+        let formats = [TextureFormat::Bgra8UnormSrgb];
+        let choose = |alpha: &[CompositeAlphaMode]| {
+            policy(false, true, false)
+                .choose(&caps(&formats, &[PresentMode::Fifo], alpha))
+                .unwrap()
+                .alpha_mode
+        };
+        assert_eq!(choose(&[PreMultiplied, Opaque]), Opaque);
+        assert_eq!(choose(&[Inherit, PreMultiplied]), Inherit);
+        assert_eq!(choose(&[]), Auto);
+    }
+
+    #[test]
+    fn reads_display_settings() {
+        let settings: DisplaySettings = serde_json::from_str(
+            r#"{ "hdrEnabled": true, "vsync": true, "adaptiveSync": false, "refreshRate": "144" }"#,
+        )
+        .unwrap();
+        let expected = SwapchainPolicy {
+            refresh_rate: Some(144),
+            ..policy(true, true, false)
+        };
+        assert_eq!(SwapchainPolicy::from(&settings), expected);
+
+        for auto in [r#""auto""#, "0", "null"] {
+            let json = format!(r#"{{ "refreshRate": {} }}"#, auto);
+            let settings: DisplaySettings = serde_json::from_str(&json).unwrap();
+            assert_eq!(settings, DisplaySettings::default());
+        }
+    }
+}
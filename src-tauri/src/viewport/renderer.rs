@@ -1,13 +1,23 @@
 use std::{
     sync::{Arc, atomic::{AtomicBool, Ordering}},
     thread,
-    time::Duration,
+    time::Instant,
 };
-use crossbeam_channel::Receiver;
-use log::debug;
-use super::state::ViewportCommand;
-use crate::viewport::vulkan::VulkanState;
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use log::{debug, error, info};
 use parking_lot::RwLock;
+use crate::scene::SharedWorld;
+use super::{
+    backend::{RenderBackend, SharedBackend},
+    camera::OrbitCamera,
+    error::ViewportError,
+    events::{EventSink, ViewportEvent},
+    grid,
+    meshes::MeshBoundsCache,
+    pacing::{FrameLimiter, FramePacing},
+    state::{PendingResize, ViewportCommand},
+    stats::{FrameSample, FrameStats},
+};
 
 pub struct RenderLoop;
 
@@ -15,37 +25,311 @@ impl RenderLoop {
     pub fn start(
         running: Arc<AtomicBool>,
         receiver: Receiver<ViewportCommand>,
-        vulkan: Arc<RwLock<Option<VulkanState>>>,
+        pending_resize: PendingResize,
+        backend: SharedBackend,
+        stats: Arc<RwLock<FrameStats>>,
+        world: SharedWorld,
+        events: EventSink,
     ) -> thread::JoinHandle<()> {
         thread::spawn(move || {
+            let mut camera = OrbitCamera::default();
+            let mut limiter = FrameLimiter::new(FramePacing::default());
+            let mut dirty = true;
+            // Set when recovery failed, cleared by the next viewport command
+            let mut faulted = false;
+            let mut last_frame_start: Option<Instant> = None;
+            // World revision the backend's entity markers were built from
+            let mut scene_revision: Option<u64> = None;
+            let mut mesh_bounds = MeshBoundsCache::default();
+
             while running.load(Ordering::Relaxed) {
+                // Wait for viewport updates until the next frame is due
+                let timeout = limiter.time_until_next_frame(Instant::now(), dirty);
+                let mut next = match receiver.recv_timeout(timeout) {
+                    Ok(cmd) => Some(cmd),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        debug!("Viewport command channel closed, stopping render loop");
+                        return;
+                    }
+                };
+
                 // Handle viewport updates
-                while let Ok(cmd) = receiver.try_recv() {
+                while let Some(cmd) = next.take().or_else(|| receiver.try_recv().ok()) {
                     match cmd {
-                        ViewportCommand::UpdateConfig(config) => {
-                            if let Some(vulkan) = vulkan.write().as_mut() {
-                                if let Err(e) = vulkan.resize(config.width, config.height) {
-                                    debug!("Failed to resize viewport: {}", e);
-                                }
+                        ViewportCommand::ResetCamera => {
+                            camera.reset();
+                        }
+                        ViewportCommand::OrbitCamera { delta_yaw, delta_pitch } => {
+                            camera.orbit(delta_yaw, delta_pitch);
+                        }
+                        ViewportCommand::PanCamera { dx, dy } => {
+                            camera.pan(dx, dy);
+                        }
+                        ViewportCommand::ZoomCamera { factor } => {
+                            camera.zoom(factor);
+                        }
+                        ViewportCommand::SetFramePacing(pacing) => {
+                            debug!("Setting frame pacing to {:?}", pacing);
+                            limiter.set_pacing(pacing);
+                            stats.write().clear();
+                            last_frame_start = None;
+                        }
+                        ViewportCommand::SetDisplaySettings(settings) => {
+                            debug!("Applying display settings {:?}", settings);
+                            let vsync = backend
+                                .write()
+                                .as_mut()
+                                .map_or(false, |backend| backend.set_display_settings(&settings));
+                            limiter.set_refresh_rate(settings.refresh_rate);
+                            limiter.set_backend_vsync(vsync);
+                        }
+                        ViewportCommand::RequestRedraw => {}
+                        ViewportCommand::SetProjectRoot(root) => {
+                            if mesh_bounds.set_root(root) {
+                                scene_revision = None;
                             }
                         }
-                        ViewportCommand::ResetCamera => {
-                            // Handle camera reset
+                        ViewportCommand::ReloadAssets(paths) => {
+                            if mesh_bounds.invalidate(&paths) {
+                                // Rebuild the entity markers with the new bounds
+                                scene_revision = None;
+                            }
                         }
                         ViewportCommand::Shutdown => {
                             debug!("Shutting down render loop");
                             return;
                         }
                     }
+                    dirty = true;
+                    faulted = false;
                 }
 
-                // Render frame if vulkan state exists
-                if let Some(_vulkan) = vulkan.read().as_ref() {
-                    // Render frame
+                // Apply the latest size the viewport was resized to
+                let resize = pending_resize.lock().take();
+                if let Some(config) = resize {
+                    if let Some(backend) = backend.write().as_mut() {
+                        let (width, height) = config.physical_size();
+                        if let Err(e) = backend.resize(width, height) {
+                            debug!("Failed to resize viewport: {}", e);
+                        }
+                    }
+                    dirty = true;
+                    faulted = false;
+                }
+
+                // Rebuild the entity markers when the scene changed
+                let revision = world.read().revision();
+                if scene_revision != Some(revision) {
+                    // Read mesh files without holding the scene lock. Meshes
+                    // added in between are drawn with unit bounds until the
+                    // next rebuild, which their revision triggers.
+                    let meshes = grid::visible_meshes(&world.read());
+                    mesh_bounds.load(meshes.iter().map(String::as_str));
+                    let lines = grid::entity_markers(&world.read(), |mesh| mesh_bounds.get(mesh));
+                    if let Some(backend) = backend.write().as_mut() {
+                        backend.set_scene_lines(&lines);
+                    }
+                    scene_revision = Some(revision);
+                    dirty = true;
+                }
+
+                // Another viewport on the shared device may have lost it; recover
+                // even when idle so the lost device can be released
+                if backend.read().as_ref().map_or(false, |backend| backend.is_device_lost()) {
+                    dirty = true;
+                }
+
+                let now = Instant::now();
+                if !running.load(Ordering::Relaxed) || faulted || !limiter.should_render(now, dirty) {
+                    continue;
+                }
+
+                limiter.frame_started(now);
+                dirty = false;
+
+                // Render frame if a backend exists
+                let mut slot = backend.write();
+                if let Some(active) = slot.as_mut() {
+                    if let Err(e) = active.render_frame(&camera) {
+                        let name = active.name();
+                        match Self::recover(&mut slot, &e, &events) {
+                            Ok(true) => dirty = true,
+                            Ok(false) => {
+                                error!("Failed to render frame with {} backend: {}", name, e);
+                            }
+                            Err(e) => {
+                                error!("Failed to recover {} backend: {}", name, e);
+                                let (code, message) = (e.code(), e.to_string());
+                                events(if slot.is_none() {
+                                    // Nothing is left to render with
+                                    ViewportEvent::DeviceUnrecoverable { code, message }
+                                } else {
+                                    ViewportEvent::RecoveryFailed { code, message }
+                                });
+                                faulted = true;
+                            }
+                        }
+                    }
                 }
 
-                thread::sleep(Duration::from_millis(0)); // todo: If we want to limit framerate we will do that here
+                if let Some(active) = slot.as_ref() {
+                    if let Some(last) = last_frame_start {
+                        stats.write().record(FrameSample {
+                            frame_time: now - last,
+                            cpu_time: now.elapsed(),
+                            gpu_time: active.last_gpu_time(),
+                        });
+                    }
+                    last_frame_start = Some(now);
+                }
             }
         })
     }
-}
\ No newline at end of file
+
+    /// Lets the backend recover from a render error and reports the outcome
+    /// to the frontend. Returns whether the backend recovered.
+    ///
+    /// A backend whose device was lost is replaced in `slot`; if that fails
+    /// the slot is left empty, which the caller reports as
+    /// `ViewportEvent::DeviceUnrecoverable`.
+    fn recover(
+        slot: &mut Option<Box<dyn RenderBackend>>,
+        error: &ViewportError,
+        events: &EventSink,
+    ) -> Result<bool, ViewportError> {
+        let Some(active) = slot.take() else {
+            return Ok(false);
+        };
+
+        if !matches!(error, ViewportError::DeviceLost) {
+            let recovered = slot.insert(active).recover(error)?;
+            if recovered {
+                debug!("Recreated swapchain after: {}", error);
+                events(ViewportEvent::SwapchainRecreated { reason: error.code() });
+            }
+            return Ok(recovered);
+        }
+
+        events(ViewportEvent::DeviceLost);
+        let name = active.name();
+        let recovered = active.reinitialize()?;
+        let adapter = recovered.adapter_name().unwrap_or_else(|| name.to_string());
+        info!("Recovered viewport on {}", adapter);
+        events(ViewportEvent::DeviceRecovered { adapter });
+        *slot = Some(recovered);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::viewport::{
+        fault::{Fault, FaultInjector},
+        headless::HeadlessBackend,
+    };
+    use parking_lot::Mutex;
+
+    type Received = Arc<Mutex<Vec<ViewportEvent>>>;
+
+    /// A headless backend sharing `faults`, and a sink recording events.
+    fn setup(faults: &FaultInjector) -> (Option<Box<dyn RenderBackend>>, EventSink, Received) {
+        let backend = HeadlessBackend::new(32, 32).with_fault_injector(faults.clone());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let events: EventSink = Arc::new(move |event| sink.lock().push(event));
+        (Some(Box::new(backend)), events, received)
+    }
+
+    /// Renders a frame, recovering like the render loop if it fails.
+    fn render(
+        slot: &mut Option<Box<dyn RenderBackend>>,
+        events: &EventSink,
+    ) -> Result<bool, ViewportError> {
+        let camera = OrbitCamera::default();
+        match slot.as_mut().unwrap().render_frame(&camera) {
+            Ok(()) => Ok(true),
+            Err(e) => RenderLoop::recover(slot, &e, events),
+        }
+    }
+
+    #[test]
+    fn recreates_the_swapchain_in_place() {
+        for (fault, reason) in [
+            (Fault::SwapchainOutOfDate, "swapchainOutOfDate"),
+            (Fault::SurfaceLost, "surfaceLost"),
+        ] {
+            let faults = FaultInjector::new();
+            let (mut slot, events, received) = setup(&faults);
+            faults.inject(fault);
+
+            assert!(render(&mut slot, &events).unwrap());
+            assert_eq!(
+                *received.lock(),
+                [ViewportEvent::SwapchainRecreated { reason }]
+            );
+            assert!(slot
+                .as_mut()
+                .unwrap()
+                .render_frame(&OrbitCamera::default())
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn replaces_the_backend_after_device_loss() {
+        let faults = FaultInjector::new();
+        let (mut slot, events, received) = setup(&faults);
+        faults.inject(Fault::DeviceLost);
+
+        assert!(render(&mut slot, &events).unwrap());
+        assert_eq!(
+            *received.lock(),
+            [
+                ViewportEvent::DeviceLost,
+                ViewportEvent::DeviceRecovered {
+                    adapter: "headless".to_string()
+                },
+            ]
+        );
+
+        let backend = slot.as_mut().unwrap();
+        assert!(!backend.is_device_lost());
+        assert!(backend.render_frame(&OrbitCamera::default()).is_ok());
+    }
+
+    #[test]
+    fn reports_a_lost_device_that_cannot_be_recreated() {
+        let faults = FaultInjector::new();
+        let (mut slot, events, received) = setup(&faults);
+        faults.inject(Fault::DeviceLost);
+        faults.inject(Fault::DeviceLost);
+
+        let error = render(&mut slot, &events).unwrap_err();
+        assert!(matches!(error, ViewportError::DeviceLost));
+        assert!(slot.is_none());
+        assert_eq!(*received.lock(), [ViewportEvent::DeviceLost]);
+    }
+
+    #[test]
+    fn leaves_errors_it_cannot_handle_to_the_caller() {
+        let faults = FaultInjector::new();
+        let (mut slot, events, received) = setup(&faults);
+
+        let recovered = RenderLoop::recover(&mut slot, &ViewportError::QueueFull, &events);
+        assert!(!recovered.unwrap());
+        assert!(slot.is_some());
+        assert!(received.lock().is_empty());
+    }
+
+    #[test]
+    fn does_nothing_without_a_backend() {
+        let (_, events, received) = setup(&FaultInjector::new());
+        let mut slot = None;
+
+        let recovered = RenderLoop::recover(&mut slot, &ViewportError::DeviceLost, &events);
+        assert!(!recovered.unwrap());
+        assert!(received.lock().is_empty());
+    }
+}
@@ -1,14 +1,32 @@
 use serde::{Serialize, Deserialize};
-use tao::window::Window;
 use std::{
+    collections::HashMap,
+    path::PathBuf,
     sync::{Arc, atomic::{AtomicBool, Ordering}},
     thread::JoinHandle,
 };
-use parking_lot::RwLock;
-use crossbeam_channel::{bounded, Sender, Receiver};
-use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use parking_lot::{Mutex, RwLock};
+use crossbeam_channel::{bounded, Sender, Receiver, TrySendError};
+use log::{debug, error, warn};
+use crate::scene::SharedWorld;
+use super::{
+    adapter::AdapterPreference, backend::{RenderBackend, SharedBackend},
+    error::{ViewportError, ViewportResult}, events::EventSink, fault::FaultInjector,
+    gpu::SharedGpu, handle::WindowHandle, pacing::FramePacing, policy::DisplaySettings,
+    renderer::RenderLoop, stats::FrameStats,
+};
+
+/// Identifies a viewport, e.g. `"level"` or `"animation"`, chosen by the
+/// editor that hosts it.
+pub type ViewportId = String;
 
+/// Rect of a viewport in CSS pixels relative to the window, as laid out by
+/// the frontend, and the scale to physical pixels.
+///
+/// A viewport renders to a surface covering its whole window, which is why a
+/// window hosts at most one viewport; only the size of the rect is used.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ViewportConfig {
     pub x: i32,
     pub y: i32,
@@ -17,88 +35,439 @@ pub struct ViewportConfig {
     pub device_pixel_ratio: f64,
 }
 
-// Platform-agnostic window handle storage
-#[derive(Clone)]
-pub struct VulkanHandle {
-    #[cfg(target_os = "windows")]
-    pub(crate) hwnd: u64,
-    #[cfg(target_os = "linux")]
-    pub(crate) xlib_window: u64,
-    #[cfg(target_os = "macos")]
-    pub(crate) ns_view: u64,
-    pub(crate) is_active: Arc<AtomicBool>,
+impl Default for ViewportConfig {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+            device_pixel_ratio: 1.0,
+        }
+    }
 }
 
-impl VulkanHandle {
-    pub fn new(window: &Window) -> Result<Self, Box<dyn std::error::Error>> {
-        let raw_handle = window.raw_window_handle();
-        
-        Ok(match raw_handle {
-            #[cfg(target_os = "windows")]
-            RawWindowHandle::Win32(handle) => Self {
-                hwnd: handle.hwnd as u64,
-                is_active: Arc::new(AtomicBool::new(true)),
-            },
-            #[cfg(target_os = "linux")]
-            RawWindowHandle::Xlib(handle) => Self {
-                xlib_window: handle.window as u64,
-                is_active: Arc::new(AtomicBool::new(true)),
-            },
-            #[cfg(target_os = "macos")]
-            RawWindowHandle::AppKit(handle) => Self {
-                ns_view: handle.ns_view as u64,
-                is_active: Arc::new(AtomicBool::new(true)),
-            },
-            _ => return Err("Unsupported window system".into()),
-        })
+impl ViewportConfig {
+    /// Extent of the rect in physical pixels, which the render target is
+    /// sized to.
+    pub fn physical_size(&self) -> (u32, u32) {
+        let scale = if self.device_pixel_ratio.is_finite() && self.device_pixel_ratio > 0.0 {
+            self.device_pixel_ratio
+        } else {
+            1.0
+        };
+        let physical = |css: u32| (css as f64 * scale).round() as u32;
+        (physical(self.width), physical(self.height))
     }
 }
 
+/// Size a viewport was resized to that its render loop has not applied yet.
+///
+/// Resizes replace the pending size rather than each queueing a command, so
+/// a burst of them while the window is dragged cannot fill the queue.
+pub type PendingResize = Arc<Mutex<Option<ViewportConfig>>>;
+
 #[derive(Clone)]
 pub enum ViewportCommand {
-    UpdateConfig(ViewportConfig),
     ResetCamera,
+    /// Circles the camera around its target, in radians.
+    OrbitCamera { delta_yaw: f32, delta_pitch: f32 },
+    /// Moves the camera target in the view plane, in fractions of the orbit
+    /// distance.
+    PanCamera { dx: f32, dy: f32 },
+    /// Scales the orbit distance; below 1 moves towards the target.
+    ZoomCamera { factor: f32 },
+    SetFramePacing(FramePacing),
+    SetDisplaySettings(DisplaySettings),
+    /// Marks the viewport dirty so a frame is rendered in on-demand mode.
+    RequestRedraw,
+    /// Asset files changed on disk; data loaded from them is reloaded.
+    ReloadAssets(Vec<PathBuf>),
+    /// A project was opened; relative mesh paths are resolved against its
+    /// root.
+    SetProjectRoot(Option<PathBuf>),
     Shutdown,
 }
 
-pub struct ViewportState {
+/// A single viewport with its own render thread, camera, target and
+/// command channel.
+pub struct Viewport {
+    pub id: ViewportId,
+    /// Label of the window the viewport renders to.
+    pub window: String,
     pub config: RwLock<ViewportConfig>,
-    pub vulkan_handle: RwLock<Option<VulkanHandle>>,
+    pub window_handle: RwLock<Option<WindowHandle>>,
+    pub backend: SharedBackend,
     pub render_thread: RwLock<Option<JoinHandle<()>>>,
     pub running: Arc<AtomicBool>,
+    pub frame_stats: Arc<RwLock<FrameStats>>,
+    pub pending_resize: PendingResize,
+    pub fault_injector: FaultInjector,
     pub command_sender: Sender<ViewportCommand>,
     pub command_receiver: Receiver<ViewportCommand>,
 }
 
-impl ViewportState {
-    pub fn new() -> Self {
+impl Viewport {
+    pub fn new(id: ViewportId, window: impl Into<String>) -> Self {
         let (sender, receiver) = bounded(10);
         Self {
-            config: RwLock::new(ViewportConfig {
-                x: 0,
-                y: 0,
-                width: 800,
-                height: 600,
-                device_pixel_ratio: 1.0,
-            }),
-            vulkan_handle: RwLock::new(None),
+            id,
+            window: window.into(),
+            config: RwLock::new(ViewportConfig::default()),
+            window_handle: RwLock::new(None),
+            backend: Arc::new(RwLock::new(None)),
             render_thread: RwLock::new(None),
             running: Arc::new(AtomicBool::new(true)),
+            frame_stats: Arc::new(RwLock::new(FrameStats::default())),
+            pending_resize: PendingResize::default(),
+            fault_injector: FaultInjector::new(),
             command_sender: sender,
             command_receiver: receiver,
         }
     }
-    
-    pub fn set_vulkan_handle(&self, handle: VulkanHandle) {
-        *self.vulkan_handle.write() = Some(handle);
+
+    pub fn set_window_handle(&self, handle: WindowHandle) {
+        *self.window_handle.write() = Some(handle);
+    }
+
+    pub fn send_command(&self, cmd: ViewportCommand) -> ViewportResult<()> {
+        // Never block the calling command; the render thread drains the queue every frame
+        self.command_sender.try_send(cmd)?;
+        Ok(())
     }
-    
-    pub fn send_command(&self, cmd: ViewportCommand) -> Result<(), String> {
-        self.command_sender.send(cmd)
-            .map_err(|e| e.to_string())
+
+    /// Resizes the viewport. Only the latest size matters, so it replaces
+    /// any size the render loop has not applied yet.
+    pub fn resize(&self, config: ViewportConfig) -> ViewportResult<()> {
+        *self.config.write() = config.clone();
+        if self.pending_resize.lock().replace(config).is_some() {
+            // The loop has not picked up the previous size, nor will it miss this one
+            return Ok(());
+        }
+        // The loop also checks for a pending size on its own, so a full queue is not fatal
+        match self.command_sender.try_send(ViewportCommand::RequestRedraw) {
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
     }
-    
+
     pub fn set_render_thread(&self, thread: JoinHandle<()>) {
         *self.render_thread.write() = Some(thread);
     }
+
+    /// Stops the render loop, waits for the render thread to exit and drops
+    /// the render backend together with its GPU resources.
+    pub fn shutdown(&self) {
+        let Some(thread) = self.render_thread.write().take() else {
+            return;
+        };
+
+        debug!("Shutting down viewport '{}'", self.id);
+        self.running.store(false, Ordering::Relaxed);
+        // The loop also notices the cleared flag on its own, so a full queue is not fatal
+        let _ = self.command_sender.try_send(ViewportCommand::Shutdown);
+
+        if thread.join().is_err() {
+            error!("Render thread of viewport '{}' panicked", self.id);
+        }
+
+        // Discard commands the loop did not get to before the next start
+        while self.command_receiver.try_recv().is_ok() {}
+        *self.pending_resize.lock() = None;
+
+        *self.backend.write() = None;
+        *self.window_handle.write() = None;
+    }
+}
+
+/// Registry of the editor's viewports, keyed by `ViewportId`.
+///
+/// Display settings, the adapter preference and the project root apply to
+/// every viewport, and all viewports render with the device in `gpu`.
+pub struct ViewportState {
+    pub viewports: RwLock<HashMap<ViewportId, Arc<Viewport>>>,
+    pub gpu: SharedGpu,
+    pub display_settings: RwLock<DisplaySettings>,
+    /// Adapter the shared device is created on once no viewport holds it.
+    pub adapter_preference: RwLock<AdapterPreference>,
+    /// Root of the open project, which scene mesh paths are relative to.
+    pub project_root: RwLock<Option<PathBuf>>,
+}
+
+impl ViewportState {
+    pub fn new() -> Self {
+        Self {
+            viewports: RwLock::new(HashMap::new()),
+            gpu: SharedGpu::new(),
+            display_settings: RwLock::new(DisplaySettings::default()),
+            adapter_preference: RwLock::new(AdapterPreference::default()),
+            project_root: RwLock::new(None),
+        }
+    }
+
+    /// The viewport registered under `id`.
+    pub fn get(&self, id: &str) -> ViewportResult<Arc<Viewport>> {
+        self.viewports
+            .read()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ViewportError::UnknownViewport(id.to_string()))
+    }
+
+    /// Registers a fresh viewport under `id` on `window`, shutting down the
+    /// viewport it replaces.
+    ///
+    /// Fails if another viewport renders to `window`: its surface covers the
+    /// whole window, so a second one could not be placed beside it.
+    pub fn create(&self, id: &str, window: &str) -> ViewportResult<Arc<Viewport>> {
+        let viewport = Arc::new(Viewport::new(id.to_string(), window));
+        let previous = {
+            let mut viewports = self.viewports.write();
+            let occupied = viewports
+                .values()
+                .find(|other| other.window == window && other.id != id);
+            if let Some(other) = occupied {
+                return Err(ViewportError::WindowInUse {
+                    window: window.to_string(),
+                    viewport: other.id.clone(),
+                });
+            }
+            viewports.insert(id.to_string(), viewport.clone())
+        };
+
+        // Tear down outside the registry lock, joining may take a frame
+        if let Some(previous) = previous {
+            previous.shutdown();
+        }
+        Ok(viewport)
+    }
+
+    /// Starts the render loop of `viewport` on `backend`, with the display
+    /// settings and project root every viewport shares.
+    pub fn start(
+        &self,
+        viewport: &Viewport,
+        backend: Box<dyn RenderBackend>,
+        world: SharedWorld,
+        events: EventSink,
+    ) -> ViewportResult<()> {
+        *viewport.backend.write() = Some(backend);
+
+        viewport.running.store(true, Ordering::Relaxed);
+        let render_thread = RenderLoop::start(
+            viewport.running.clone(),
+            viewport.command_receiver.clone(),
+            viewport.pending_resize.clone(),
+            viewport.backend.clone(),
+            viewport.frame_stats.clone(),
+            world,
+            events,
+        );
+        viewport.set_render_thread(render_thread);
+
+        // Let the loop pick up the refresh rate, vsync preference and project
+        let display_settings = self.display_settings.read().clone();
+        viewport.send_command(ViewportCommand::SetDisplaySettings(display_settings))?;
+        viewport.send_command(ViewportCommand::SetProjectRoot(self.project_root.read().clone()))
+    }
+
+    /// Shuts down and unregisters the viewport under `id`, if any.
+    pub fn remove(&self, id: &str) {
+        let removed = self.viewports.write().remove(id);
+        if let Some(viewport) = removed {
+            viewport.shutdown();
+        }
+    }
+
+    /// Sends `cmd` to every viewport. A viewport whose queue is full misses
+    /// the command, which is logged rather than failing the others.
+    pub fn broadcast(&self, cmd: ViewportCommand) {
+        for viewport in self.viewports.read().values() {
+            if let Err(e) = viewport.send_command(cmd.clone()) {
+                warn!("Failed to send command to viewport '{}': {}", viewport.id, e);
+            }
+        }
+    }
+
+    /// Shuts down and unregisters every viewport.
+    pub fn shutdown(&self) {
+        let viewports: Vec<_> = self.viewports.write().drain().map(|(_, v)| v).collect();
+        for viewport in viewports {
+            viewport.shutdown();
+        }
+    }
+}
+
+impl Default for ViewportState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::World;
+    use crate::viewport::headless::{FrameBuffer, HeadlessBackend};
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// Starts viewport `id` on a headless backend the way
+    /// `initialize_viewport` starts it on a window.
+    fn initialize(state: &ViewportState, id: &str, window: &str) -> Arc<RwLock<FrameBuffer>> {
+        let viewport = state.create(id, window).unwrap();
+        let backend =
+            HeadlessBackend::new(32, 24).with_fault_injector(viewport.fault_injector.clone());
+        let frame = backend.frame_buffer();
+        let world = Arc::new(RwLock::new(World::new()));
+        state
+            .start(&viewport, Box::new(backend), world, Arc::new(|_| {}))
+            .unwrap();
+        frame
+    }
+
+    fn wait_for(frame: &RwLock<FrameBuffer>, done: impl Fn(&FrameBuffer) -> bool) -> FrameBuffer {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            {
+                let frame = frame.read();
+                if done(&frame) {
+                    return frame.clone();
+                }
+            }
+            assert!(
+                Instant::now() < deadline,
+                "render loop did not produce the frame"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn drives_a_viewport_from_initialize_to_shutdown() {
+        let state = ViewportState::new();
+        let frame = initialize(&state, "level", "main");
+        let first = wait_for(&frame, |frame| frame.frame_index >= 1);
+
+        // update_native_viewport with a rect in CSS pixels on a 2x display
+        let config = ViewportConfig {
+            x: 10,
+            y: 20,
+            width: 40,
+            height: 30,
+            device_pixel_ratio: 2.0,
+        };
+        state.get("level").unwrap().resize(config).unwrap();
+        let resized = wait_for(&frame, |frame| frame.width == 80 && frame.pixels[3] == 255);
+        assert_eq!(resized.height, 60);
+        assert!(resized.frame_index > first.frame_index);
+
+        // reset_viewport_camera
+        state
+            .get("level")
+            .unwrap()
+            .send_command(ViewportCommand::ResetCamera)
+            .unwrap();
+        wait_for(&frame, |frame| frame.frame_index > resized.frame_index);
+
+        // shutdown_viewport
+        let viewport = state.get("level").unwrap();
+        state.remove("level");
+        assert!(viewport.render_thread.read().is_none());
+        assert!(viewport.backend.read().is_none());
+        let error = state.get("level").err().unwrap();
+        assert_eq!(error.code(), "unknownViewport");
+    }
+
+    #[test]
+    fn reinitializing_replaces_the_running_viewport() {
+        let state = ViewportState::new();
+        initialize(&state, "level", "main");
+        let previous = state.get("level").unwrap();
+
+        let frame = initialize(&state, "level", "main");
+        wait_for(&frame, |frame| frame.frame_index >= 1);
+
+        assert!(previous.render_thread.read().is_none());
+        assert!(!Arc::ptr_eq(&previous, &state.get("level").unwrap()));
+        state.shutdown();
+        assert!(state.viewports.read().is_empty());
+    }
+
+    #[test]
+    fn viewports_on_separate_windows_render_to_their_own_targets() {
+        let state = ViewportState::new();
+        let level = initialize(&state, "level", "main");
+        let preview = initialize(&state, "preview", "preview");
+        wait_for(&level, |frame| frame.frame_index >= 1);
+        wait_for(&preview, |frame| frame.frame_index >= 1);
+        assert!(!Arc::ptr_eq(&level, &preview));
+
+        let config = ViewportConfig {
+            width: 48,
+            height: 16,
+            ..ViewportConfig::default()
+        };
+        state.get("preview").unwrap().resize(config).unwrap();
+        wait_for(&preview, |frame| {
+            frame.width == 48 && frame.pixels[3] == 255
+        });
+
+        // Only the resized viewport changed size
+        let (width, height) = (level.read().width, level.read().height);
+        assert_eq!((width, height), (32, 24));
+
+        // Shutting one down leaves the other rendering
+        state.remove("preview");
+        let before = level.read().frame_index;
+        state
+            .get("level")
+            .unwrap()
+            .send_command(ViewportCommand::RequestRedraw)
+            .unwrap();
+        wait_for(&level, |frame| frame.frame_index > before);
+    }
+
+    #[test]
+    fn rejects_a_second_viewport_on_a_window() {
+        let state = ViewportState::new();
+        initialize(&state, "level", "main");
+
+        let error = state.create("preview", "main").err().unwrap();
+        assert_eq!(error.code(), "windowInUse");
+        assert!(state.get("preview").is_err());
+        assert!(state.get("level").unwrap().render_thread.read().is_some());
+
+        // Once the window is free it can host another viewport
+        state.remove("level");
+        assert!(state.create("preview", "main").is_ok());
+    }
+
+    #[test]
+    fn reads_the_rect_the_frontend_sends() {
+        let config: ViewportConfig = serde_json::from_value(serde_json::json!({
+            "x": 0,
+            "y": 48,
+            "width": 640,
+            "height": 360,
+            "devicePixelRatio": 1.5,
+        }))
+        .unwrap();
+        assert_eq!(config.physical_size(), (960, 540));
+    }
+
+    #[test]
+    fn treats_an_invalid_pixel_ratio_as_one() {
+        for device_pixel_ratio in [0.0, -2.0, f64::NAN] {
+            let config = ViewportConfig {
+                width: 100,
+                height: 50,
+                device_pixel_ratio,
+                ..ViewportConfig::default()
+            };
+            assert_eq!(config.physical_size(), (100, 50));
+        }
+    }
 }
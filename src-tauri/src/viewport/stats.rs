@@ -0,0 +1,275 @@
+use serde::Serialize;
+use std::{collections::VecDeque, time::Duration};
+
+/// Timing of one rendered frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSample {
+    /// Time since the previous frame started.
+    pub frame_time: Duration,
+    /// Time spent on the render thread recording and submitting the frame.
+    pub cpu_time: Duration,
+    /// Time from submission until the GPU finished the work, if known.
+    pub gpu_time: Option<Duration>,
+}
+
+/// Number of frames in a `FrameHistogram` bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// Inclusive lower bound of the bucket in milliseconds.
+    pub min_ms: f64,
+    /// Exclusive upper bound in milliseconds, `None` for the last bucket.
+    pub max_ms: Option<f64>,
+    pub count: usize,
+}
+
+/// Aggregated frame timings, as returned to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameStatsSnapshot {
+    pub total_frames: u64,
+    pub sampled_frames: usize,
+    pub fps: f64,
+    pub frame_time_avg_ms: f64,
+    pub frame_time_min_ms: f64,
+    pub frame_time_max_ms: f64,
+    /// Frame time percentiles; the 99th is the stutter users notice.
+    pub frame_time_p50_ms: f64,
+    pub frame_time_p95_ms: f64,
+    pub frame_time_p99_ms: f64,
+    pub cpu_time_avg_ms: f64,
+    pub gpu_time_avg_ms: Option<f64>,
+    pub cpu_histogram: Vec<HistogramBucket>,
+    pub gpu_histogram: Vec<HistogramBucket>,
+}
+
+/// Rolling window of per-frame timings recorded by the render loop.
+pub struct FrameStats {
+    samples: VecDeque<FrameSample>,
+    capacity: usize,
+    total_frames: u64,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl FrameStats {
+    /// Number of frames kept, a few seconds worth at typical refresh rates.
+    pub const DEFAULT_CAPACITY: usize = 512;
+
+    /// Bucket edges of the CPU/GPU time histograms, in milliseconds.
+    pub const HISTOGRAM_EDGES_MS: [f64; 10] =
+        [0.0, 1.0, 2.0, 4.0, 8.0, 12.0, 16.7, 33.3, 50.0, 100.0];
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            total_frames: 0,
+        }
+    }
+
+    pub fn record(&mut self, sample: FrameSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.total_frames += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn snapshot(&self) -> FrameStatsSnapshot {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let frame_times: Vec<f64> = self.samples.iter().map(|s| ms(s.frame_time)).collect();
+        let cpu_times: Vec<f64> = self.samples.iter().map(|s| ms(s.cpu_time)).collect();
+        let gpu_times: Vec<f64> = self
+            .samples
+            .iter()
+            .filter_map(|s| s.gpu_time.map(ms))
+            .collect();
+
+        let frame_time_avg_ms = average(&frame_times).unwrap_or(0.0);
+        let mut sorted_frame_times = frame_times.clone();
+        sorted_frame_times.sort_by(f64::total_cmp);
+
+        FrameStatsSnapshot {
+            total_frames: self.total_frames,
+            sampled_frames: self.samples.len(),
+            fps: if frame_time_avg_ms > 0.0 {
+                1000.0 / frame_time_avg_ms
+            } else {
+                0.0
+            },
+            frame_time_avg_ms,
+            frame_time_min_ms: frame_times.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            frame_time_max_ms: frame_times.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            frame_time_p50_ms: percentile(&sorted_frame_times, 50.0),
+            frame_time_p95_ms: percentile(&sorted_frame_times, 95.0),
+            frame_time_p99_ms: percentile(&sorted_frame_times, 99.0),
+            cpu_time_avg_ms: average(&cpu_times).unwrap_or(0.0),
+            gpu_time_avg_ms: average(&gpu_times),
+            cpu_histogram: histogram(&cpu_times),
+            gpu_histogram: histogram(&gpu_times),
+        }
+    }
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Nearest-rank percentile of ascending `sorted` values, 0 without any.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn histogram(values: &[f64]) -> Vec<HistogramBucket> {
+    let edges = FrameStats::HISTOGRAM_EDGES_MS;
+    let mut buckets: Vec<HistogramBucket> = edges
+        .iter()
+        .enumerate()
+        .map(|(i, &min_ms)| HistogramBucket {
+            min_ms,
+            max_ms: edges.get(i + 1).copied(),
+            count: 0,
+        })
+        .collect();
+
+    for &value in values {
+        let index = edges.iter().rposition(|&edge| value >= edge).unwrap_or(0);
+        buckets[index].count += 1;
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(frame_ms: u64, cpu_ms: u64, gpu_ms: Option<u64>) -> FrameSample {
+        FrameSample {
+            frame_time: Duration::from_millis(frame_ms),
+            cpu_time: Duration::from_millis(cpu_ms),
+            gpu_time: gpu_ms.map(Duration::from_millis),
+        }
+    }
+
+    fn counts(histogram: &[HistogramBucket]) -> Vec<usize> {
+        histogram.iter().map(|bucket| bucket.count).collect()
+    }
+
+    #[test]
+    fn reports_zeros_without_samples() {
+        let snapshot = FrameStats::default().snapshot();
+        assert_eq!(snapshot.total_frames, 0);
+        assert_eq!(snapshot.sampled_frames, 0);
+        assert_eq!(snapshot.fps, 0.0);
+        assert_eq!(snapshot.frame_time_p99_ms, 0.0);
+        assert_eq!(snapshot.gpu_time_avg_ms, None);
+        assert!(snapshot
+            .cpu_histogram
+            .iter()
+            .all(|bucket| bucket.count == 0));
+    }
+
+    #[test]
+    fn averages_frame_times() {
+        let mut stats = FrameStats::default();
+        for frame_ms in [10, 20, 30] {
+            stats.record(sample(frame_ms, 5, Some(3)));
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.sampled_frames, 3);
+        assert_eq!(snapshot.frame_time_avg_ms, 20.0);
+        assert_eq!(snapshot.fps, 50.0);
+        assert_eq!(snapshot.frame_time_min_ms, 10.0);
+        assert_eq!(snapshot.frame_time_max_ms, 30.0);
+        assert_eq!(snapshot.cpu_time_avg_ms, 5.0);
+        assert_eq!(snapshot.gpu_time_avg_ms, Some(3.0));
+    }
+
+    #[test]
+    fn takes_nearest_rank_percentiles() {
+        let mut stats = FrameStats::default();
+        // 1..=100 ms in shuffled order
+        for i in 0..100 {
+            stats.record(sample((i * 37) % 100 + 1, 1, None));
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.frame_time_p50_ms, 50.0);
+        assert_eq!(snapshot.frame_time_p95_ms, 95.0);
+        assert_eq!(snapshot.frame_time_p99_ms, 99.0);
+    }
+
+    #[test]
+    fn one_stutter_in_a_hundred_frames_only_shows_in_the_max() {
+        let mut stats = FrameStats::default();
+        for _ in 0..99 {
+            stats.record(sample(16, 1, None));
+        }
+        stats.record(sample(250, 1, None));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.frame_time_p95_ms, 16.0);
+        assert_eq!(snapshot.frame_time_p99_ms, 16.0);
+        assert_eq!(snapshot.frame_time_max_ms, 250.0);
+    }
+
+    #[test]
+    fn evicts_the_oldest_samples_beyond_capacity() {
+        let mut stats = FrameStats::with_capacity(4);
+        for frame_ms in [100, 100, 10, 20, 30, 40] {
+            stats.record(sample(frame_ms, 1, None));
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_frames, 6);
+        assert_eq!(snapshot.sampled_frames, 4);
+        assert_eq!(snapshot.frame_time_max_ms, 40.0);
+        assert_eq!(snapshot.frame_time_avg_ms, 25.0);
+    }
+
+    #[test]
+    fn clearing_keeps_the_frame_count() {
+        let mut stats = FrameStats::default();
+        stats.record(sample(16, 1, None));
+        stats.clear();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_frames, 1);
+        assert_eq!(snapshot.sampled_frames, 0);
+    }
+
+    #[test]
+    fn buckets_cpu_and_gpu_times() {
+        let mut stats = FrameStats::default();
+        stats.record(sample(16, 0, Some(1)));
+        stats.record(sample(16, 3, None));
+        stats.record(sample(16, 17, Some(150)));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(
+            counts(&snapshot.cpu_histogram),
+            [1, 0, 1, 0, 0, 0, 1, 0, 0, 0]
+        );
+        // Frames without a GPU timing are left out
+        assert_eq!(
+            counts(&snapshot.gpu_histogram),
+            [0, 1, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(snapshot.cpu_histogram[9].max_ms, None);
+    }
+}
@@ -0,0 +1,353 @@
+use super::{
+    error::{ViewportError, ViewportResult},
+    gpu::GpuContext,
+    headless::FrameBuffer,
+    policy::{SwapchainChoice, SwapchainPolicy},
+};
+use log::debug;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Where the viewport renders to: a window swapchain or an offscreen texture.
+pub enum RenderTarget {
+    Surface(SurfaceTarget),
+    Offscreen(OffscreenTarget),
+}
+
+/// A texture acquired from a `RenderTarget` for the current frame.
+pub struct TargetFrame {
+    pub view: wgpu::TextureView,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+}
+
+impl TargetFrame {
+    /// Presents the frame. A no-op for offscreen targets.
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+impl RenderTarget {
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            RenderTarget::Surface(target) => (target.config.width, target.config.height),
+            RenderTarget::Offscreen(target) => (target.width, target.height),
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            RenderTarget::Surface(target) => target.choice.view_format,
+            RenderTarget::Offscreen(target) => target.format,
+        }
+    }
+
+    pub fn resize(&mut self, gpu: &GpuContext, width: u32, height: u32) {
+        match self {
+            RenderTarget::Surface(target) => target.resize(gpu, width, height),
+            RenderTarget::Offscreen(target) => target.resize(gpu, width, height),
+        }
+    }
+
+    /// Recreates the swapchain or texture with the current configuration,
+    /// e.g. after the surface reported it is out of date.
+    pub fn reconfigure(&mut self, gpu: &GpuContext) {
+        match self {
+            RenderTarget::Surface(target) => target.configure(gpu),
+            RenderTarget::Offscreen(target) => target.recreate(gpu),
+        }
+    }
+
+    /// Acquires the texture to render the next frame into.
+    ///
+    /// Returns `Ok(None)` while the target has a zero extent, e.g. when the
+    /// window is minimized. A suboptimal swapchain is reconfigured once
+    /// before giving up on it and rendering into it anyway.
+    pub fn acquire(&mut self, gpu: &GpuContext) -> Result<Option<TargetFrame>, wgpu::SurfaceError> {
+        let (width, height) = self.size();
+        if width == 0 || height == 0 {
+            return Ok(None);
+        }
+
+        match self {
+            RenderTarget::Surface(target) => {
+                let mut surface_texture = target.surface.get_current_texture()?;
+                if surface_texture.suboptimal {
+                    debug!("Surface is suboptimal, reconfiguring");
+                    drop(surface_texture);
+                    target.configure(gpu);
+                    surface_texture = target.surface.get_current_texture()?;
+                }
+                let view = surface_texture
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor {
+                        format: Some(target.choice.view_format),
+                        ..Default::default()
+                    });
+                Ok(Some(TargetFrame {
+                    view,
+                    surface_texture: Some(surface_texture),
+                }))
+            }
+            RenderTarget::Offscreen(target) => Ok(Some(TargetFrame {
+                view: target
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default()),
+                surface_texture: None,
+            })),
+        }
+    }
+}
+
+/// Swapchain-backed target for a native window.
+pub struct SurfaceTarget {
+    pub surface: wgpu::Surface<'static>,
+    pub config: wgpu::SurfaceConfiguration,
+    policy: SwapchainPolicy,
+    choice: SwapchainChoice,
+}
+
+impl SurfaceTarget {
+    pub fn new(
+        gpu: &GpuContext,
+        surface: wgpu::Surface<'static>,
+        width: u32,
+        height: u32,
+        policy: SwapchainPolicy,
+    ) -> ViewportResult<Self> {
+        let choice = policy
+            .choose(&surface.get_capabilities(&gpu.adapter))
+            .ok_or(ViewportError::IncompatibleSurface)?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: choice.format,
+            width,
+            height,
+            present_mode: choice.present_mode,
+            desired_maximum_frame_latency: choice.frame_latency,
+            alpha_mode: choice.alpha_mode,
+            view_formats: Self::view_formats(&choice),
+        };
+
+        let mut target = Self {
+            surface,
+            config,
+            policy,
+            choice,
+        };
+        target.configure(gpu);
+        Ok(target)
+    }
+
+    pub fn policy(&self) -> &SwapchainPolicy {
+        &self.policy
+    }
+
+    pub fn choice(&self) -> &SwapchainChoice {
+        &self.choice
+    }
+
+    /// Re-evaluates the swapchain configuration under `policy` and
+    /// reconfigures if anything changed. Returns whether the swapchain waits
+    /// for vblank.
+    pub fn set_policy(&mut self, gpu: &GpuContext, policy: SwapchainPolicy) -> bool {
+        let Some(choice) = policy.choose(&self.surface.get_capabilities(&gpu.adapter)) else {
+            // The surface went away, keep the old configuration until the
+            // swapchain is recreated.
+            self.policy = policy;
+            return self.choice.blocks_on_vblank();
+        };
+        self.policy = policy;
+
+        if choice != self.choice {
+            debug!(
+                "Swapchain policy changed: {:?} ({:?}), {:?}, latency {}",
+                choice.format, choice.color_space, choice.present_mode, choice.frame_latency
+            );
+            self.config.format = choice.format;
+            self.config.present_mode = choice.present_mode;
+            self.config.desired_maximum_frame_latency = choice.frame_latency;
+            self.config.alpha_mode = choice.alpha_mode;
+            self.config.view_formats = Self::view_formats(&choice);
+            self.choice = choice;
+            self.configure(gpu);
+        }
+
+        self.choice.blocks_on_vblank()
+    }
+
+    fn view_formats(choice: &SwapchainChoice) -> Vec<wgpu::TextureFormat> {
+        if choice.view_format != choice.format {
+            vec![choice.view_format]
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn resize(&mut self, gpu: &GpuContext, width: u32, height: u32) {
+        if self.config.width == width && self.config.height == height {
+            return;
+        }
+
+        self.config.width = width;
+        self.config.height = height;
+        self.configure(gpu);
+    }
+
+    /// (Re)configures the swapchain with the current configuration.
+    pub fn configure(&mut self, gpu: &GpuContext) {
+        // wgpu rejects zero-sized swapchains; the surface is reconfigured
+        // once the window has a real extent again.
+        if self.config.width == 0 || self.config.height == 0 {
+            return;
+        }
+
+        debug!(
+            "Configuring surface {}x{} ({:?}, {:?})",
+            self.config.width, self.config.height, self.config.format, self.config.present_mode
+        );
+        self.surface.configure(&gpu.device, &self.config);
+    }
+}
+
+/// Offscreen texture target whose contents are read back after every frame.
+pub struct OffscreenTarget {
+    pub texture: wgpu::Texture,
+    pub format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    output: Arc<RwLock<FrameBuffer>>,
+}
+
+impl OffscreenTarget {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    pub fn new(gpu: &GpuContext, width: u32, height: u32) -> Self {
+        let output = Arc::new(RwLock::new(FrameBuffer::new(width, height)));
+        Self::with_frame_buffer(gpu, width, height, output)
+    }
+
+    /// Creates a target that reads back into an existing frame buffer, so
+    /// holders of the handle keep receiving frames.
+    pub fn with_frame_buffer(
+        gpu: &GpuContext,
+        width: u32,
+        height: u32,
+        output: Arc<RwLock<FrameBuffer>>,
+    ) -> Self {
+        Self {
+            texture: Self::create_texture(gpu, width, height),
+            format: Self::FORMAT,
+            width,
+            height,
+            output,
+        }
+    }
+
+    /// Handle to the most recently read back frame.
+    pub fn frame_buffer(&self) -> Arc<RwLock<FrameBuffer>> {
+        self.output.clone()
+    }
+
+    pub fn resize(&mut self, gpu: &GpuContext, width: u32, height: u32) {
+        if self.width == width && self.height == height {
+            return;
+        }
+
+        self.texture = Self::create_texture(gpu, width, height);
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Recreates the texture with the current size.
+    pub fn recreate(&mut self, gpu: &GpuContext) {
+        self.texture = Self::create_texture(gpu, self.width, self.height);
+    }
+
+    /// Copies the texture contents into the shared `FrameBuffer`.
+    pub fn read_back(&self, gpu: &GpuContext) -> ViewportResult<()> {
+        if self.width == 0 || self.height == 0 {
+            return Ok(());
+        }
+
+        let unpadded_bytes_per_row = self.width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback Buffer"),
+            size: padded_bytes_per_row as u64 * self.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offscreen Readback Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        gpu.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        gpu.device.poll(wgpu::Maintain::Wait);
+        // A dropped callback means the mapping never completed
+        receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+        let mut output = self.output.write();
+        let frame_index = output.frame_index;
+        *output = FrameBuffer::new(self.width, self.height);
+        output.frame_index = frame_index + 1;
+        {
+            let data = slice.get_mapped_range();
+            for (row, dst) in data
+                .chunks(padded_bytes_per_row as usize)
+                .zip(output.pixels.chunks_mut(unpadded_bytes_per_row as usize))
+            {
+                dst.copy_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        Ok(())
+    }
+
+    fn create_texture(gpu: &GpuContext, width: u32, height: u32) -> wgpu::Texture {
+        gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Viewport Target"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+}
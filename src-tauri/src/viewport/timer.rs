@@ -0,0 +1,123 @@
+use parking_lot::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Measures how long the GPU spends on the viewport pass using timestamp
+/// queries.
+///
+/// Results are read back asynchronously, so `last_duration` lags the
+/// current frame by one or two frames. Only available on adapters with
+/// `Features::TIMESTAMP_QUERY`.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: Arc<wgpu::Buffer>,
+    readback_pending: Arc<AtomicBool>,
+    period_ns: f32,
+    last_duration: Arc<Mutex<Option<Duration>>>,
+}
+
+impl GpuTimer {
+    const QUERY_COUNT: u32 = 2;
+    const BUFFER_SIZE: u64 = Self::QUERY_COUNT as u64 * wgpu::QUERY_SIZE as u64;
+
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Viewport Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: Self::QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Viewport Timestamp Resolve"),
+            size: Self::BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Viewport Timestamp Readback"),
+            size: Self::BUFFER_SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer: Arc::new(readback_buffer),
+            readback_pending: Arc::new(AtomicBool::new(false)),
+            period_ns: queue.get_timestamp_period(),
+            last_duration: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Timestamp writes for the render pass, or `None` while the previous
+    /// measurement is still being read back.
+    pub fn pass_timestamp_writes(&self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        (!self.readback_pending.load(Ordering::Acquire)).then_some(
+            wgpu::RenderPassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(0),
+                end_of_pass_write_index: Some(1),
+            },
+        )
+    }
+
+    /// Resolves the timestamps written by the pass. Call after the pass has
+    /// ended if `pass_timestamp_writes` returned `Some`.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(
+            &self.query_set,
+            0..Self::QUERY_COUNT,
+            &self.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            Self::BUFFER_SIZE,
+        );
+    }
+
+    /// Starts reading back the resolved timestamps. Call after submitting.
+    pub fn read_back(&self) {
+        self.readback_pending.store(true, Ordering::Release);
+
+        let buffer = self.readback_buffer.clone();
+        let pending = self.readback_pending.clone();
+        let last_duration = self.last_duration.clone();
+        let period_ns = self.period_ns;
+
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                if result.is_ok() {
+                    {
+                        let data = buffer.slice(..).get_mapped_range();
+                        let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+                        *last_duration.lock() = Some(Duration::from_nanos(
+                            (ticks as f64 * period_ns as f64) as u64,
+                        ));
+                    }
+                    buffer.unmap();
+                }
+                pending.store(false, Ordering::Release);
+            });
+    }
+
+    /// Most recently measured GPU time of the viewport pass.
+    pub fn last_duration(&self) -> Option<Duration> {
+        *self.last_duration.lock()
+    }
+}
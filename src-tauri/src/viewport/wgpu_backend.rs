@@ -0,0 +1,319 @@
+use super::{
+    adapter::AdapterPreference,
+    backend::RenderBackend,
+    camera::OrbitCamera,
+    error::{ViewportError, ViewportResult},
+    fault::{Fault, FaultInjector},
+    gpu::{GpuContext, SharedGpu},
+    grid::{self, LineVertex},
+    handle::WindowHandle,
+    headless::FrameBuffer,
+    lines::LineRenderer,
+    policy::{DisplaySettings, SwapchainPolicy},
+    target::{OffscreenTarget, RenderTarget, SurfaceTarget},
+    timer::GpuTimer,
+};
+use log::{info, warn};
+use parking_lot::RwLock;
+use std::{sync::Arc, time::Duration};
+
+/// wgpu render backend for the native viewport.
+///
+/// Renders either into the swapchain of a native window or into an offscreen
+/// texture, which allows the GPU path to be exercised without a window. The
+/// device is shared with the other viewports through a `SharedGpu`.
+pub struct WgpuBackend {
+    pub gpu: Arc<GpuContext>,
+    pub target: RenderTarget,
+    shared: SharedGpu,
+    /// Window the surface target presents to, kept to recreate the surface
+    /// after a device loss.
+    window: Option<WindowHandle>,
+    /// Adapter choice the device was created with, reapplied on recovery.
+    preference: AdapterPreference,
+    lines: Option<LineRenderer>,
+    /// Entity markers, kept to rebuild the line renderer.
+    scene_lines: Vec<LineVertex>,
+    timer: Option<GpuTimer>,
+    clear_color: wgpu::Color,
+    faults: FaultInjector,
+}
+
+impl WgpuBackend {
+    pub const DEFAULT_CLEAR_COLOR: wgpu::Color = wgpu::Color {
+        r: 0.010,
+        g: 0.010,
+        b: 0.010,
+        a: 1.0,
+    };
+
+    /// Creates a backend presenting to the given native window, on the
+    /// adapter picked for `preference` and with the swapchain configured by
+    /// `policy`.
+    pub fn for_window(
+        shared: &SharedGpu,
+        handle: &WindowHandle,
+        width: u32,
+        height: u32,
+        policy: SwapchainPolicy,
+        preference: AdapterPreference,
+    ) -> ViewportResult<Self> {
+        info!("Creating wgpu viewport on {}", handle.window_system());
+
+        let (gpu, surface) = shared.for_window(handle, &preference)?;
+        let target = SurfaceTarget::new(&gpu, surface, width, height, policy)?;
+
+        Ok(Self::from_parts(
+            gpu,
+            shared.clone(),
+            RenderTarget::Surface(target),
+            Some(*handle),
+            preference,
+        ))
+    }
+
+    /// Creates a backend rendering into an offscreen texture.
+    pub fn offscreen(
+        shared: &SharedGpu,
+        width: u32,
+        height: u32,
+        preference: AdapterPreference,
+    ) -> ViewportResult<Self> {
+        let gpu = shared.offscreen(&preference)?;
+        let target = OffscreenTarget::new(&gpu, width, height);
+
+        Ok(Self::from_parts(
+            gpu,
+            shared.clone(),
+            RenderTarget::Offscreen(target),
+            None,
+            preference,
+        ))
+    }
+
+    fn from_parts(
+        gpu: Arc<GpuContext>,
+        shared: SharedGpu,
+        target: RenderTarget,
+        window: Option<WindowHandle>,
+        preference: AdapterPreference,
+    ) -> Self {
+        Self {
+            timer: GpuTimer::new(&gpu.device, &gpu.queue),
+            gpu,
+            target,
+            shared,
+            window,
+            preference,
+            lines: None,
+            scene_lines: Vec::new(),
+            clear_color: Self::DEFAULT_CLEAR_COLOR,
+            faults: FaultInjector::default(),
+        }
+    }
+
+    pub fn with_clear_color(mut self, color: wgpu::Color) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    /// Shares a fault queue with the backend, see `FaultInjector`.
+    pub fn with_fault_injector(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Handle to the read back image when rendering offscreen.
+    pub fn frame_buffer(&self) -> Option<Arc<RwLock<FrameBuffer>>> {
+        match &self.target {
+            RenderTarget::Offscreen(target) => Some(target.frame_buffer()),
+            RenderTarget::Surface(_) => None,
+        }
+    }
+}
+
+impl RenderBackend for WgpuBackend {
+    fn resize(&mut self, width: u32, height: u32) -> ViewportResult<()> {
+        self.target.resize(&self.gpu, width, height);
+        Ok(())
+    }
+
+    fn render_frame(&mut self, camera: &OrbitCamera) -> ViewportResult<()> {
+        if let Some(fault) = self.faults.take() {
+            warn!("Injecting {:?} fault", fault);
+            match fault {
+                Fault::SwapchainOutOfDate => return Err(ViewportError::SwapchainOutOfDate),
+                Fault::SurfaceLost => return Err(ViewportError::SurfaceLost),
+                Fault::DeviceLost => self.gpu.device.destroy(),
+            }
+        }
+
+        // Fire pending callbacks such as the GPU timer readback
+        self.gpu.device.poll(wgpu::Maintain::Poll);
+        if self.gpu.is_lost() {
+            return Err(ViewportError::DeviceLost);
+        }
+
+        let frame = match self.target.acquire(&self.gpu)? {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        // (Re)build the line pipeline whenever the target format changes
+        let format = self.target.format();
+        if self.lines.as_ref().map(LineRenderer::format) != Some(format) {
+            let mut lines = LineRenderer::new(
+                &self.gpu.device,
+                format,
+                &grid::ground_grid(),
+                &grid::axis_gizmo(),
+            );
+            lines.set_scene(&self.gpu.device, &self.scene_lines);
+            self.lines = Some(lines);
+        }
+        let lines = self.lines.as_ref().expect("line renderer was just created");
+
+        let (width, height) = self.target.size();
+        lines.update(
+            &self.gpu.queue,
+            camera.view_projection(width as f32 / height as f32),
+            grid::gizmo_view_projection(camera),
+        );
+
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Viewport Frame Encoder"),
+            });
+        let timestamp_writes = self
+            .timer
+            .as_ref()
+            .and_then(GpuTimer::pass_timestamp_writes);
+        let timer = self.timer.as_ref().filter(|_| timestamp_writes.is_some());
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Viewport Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes,
+                occlusion_query_set: None,
+            });
+            lines.draw(&mut pass, grid::gizmo_viewport(width, height));
+        }
+        if let Some(timer) = timer {
+            timer.resolve(&mut encoder);
+        }
+        self.gpu.queue.submit(Some(encoder.finish()));
+        if let Some(timer) = timer {
+            timer.read_back();
+        }
+        frame.present();
+
+        if let RenderTarget::Offscreen(target) = &self.target {
+            target.read_back(&self.gpu)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_scene_lines(&mut self, lines: &[LineVertex]) {
+        self.scene_lines = lines.to_vec();
+        if let Some(renderer) = self.lines.as_mut() {
+            renderer.set_scene(&self.gpu.device, lines);
+        }
+    }
+
+    fn recover(&mut self, error: &ViewportError) -> ViewportResult<bool> {
+        match error {
+            ViewportError::SwapchainOutOfDate | ViewportError::SurfaceLost
+                if !self.gpu.is_lost() =>
+            {
+                self.target.reconfigure(&self.gpu);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn reinitialize(self: Box<Self>) -> ViewportResult<Box<dyn RenderBackend>> {
+        info!("Reinitializing wgpu viewport after device loss");
+
+        let Self {
+            gpu,
+            target,
+            shared,
+            window,
+            preference,
+            lines,
+            scene_lines,
+            timer,
+            clear_color,
+            faults,
+        } = *self;
+        let (width, height) = target.size();
+
+        // wgpu cannot reliably create a new device while the lost one is
+        // alive, so this viewport's share of it is released first
+        let (policy, frame_buffer) = match &target {
+            RenderTarget::Surface(target) => (Some(target.policy().clone()), None),
+            RenderTarget::Offscreen(target) => (None, Some(target.frame_buffer())),
+        };
+        drop((lines, timer, target, gpu));
+
+        let backend = match (window, policy) {
+            (Some(handle), Some(policy)) => {
+                Self::for_window(&shared, &handle, width, height, policy, preference)?
+            }
+            _ => {
+                let gpu = shared.offscreen(&preference)?;
+                let output = frame_buffer
+                    .unwrap_or_else(|| Arc::new(RwLock::new(FrameBuffer::new(width, height))));
+                let target = OffscreenTarget::with_frame_buffer(&gpu, width, height, output);
+                Self::from_parts(
+                    gpu,
+                    shared,
+                    RenderTarget::Offscreen(target),
+                    None,
+                    preference,
+                )
+            }
+        };
+
+        let mut backend = backend
+            .with_clear_color(clear_color)
+            .with_fault_injector(faults);
+        backend.scene_lines = scene_lines;
+        Ok(Box::new(backend))
+    }
+
+    fn is_device_lost(&self) -> bool {
+        self.gpu.is_lost()
+    }
+
+    fn set_display_settings(&mut self, settings: &DisplaySettings) -> bool {
+        match &mut self.target {
+            RenderTarget::Surface(target) => target.set_policy(&self.gpu, settings.into()),
+            RenderTarget::Offscreen(_) => false,
+        }
+    }
+
+    fn last_gpu_time(&self) -> Option<Duration> {
+        self.timer.as_ref().and_then(GpuTimer::last_duration)
+    }
+
+    fn adapter_name(&self) -> Option<String> {
+        Some(self.gpu.adapter_name())
+    }
+
+    fn name(&self) -> &'static str {
+        "wgpu"
+    }
+}
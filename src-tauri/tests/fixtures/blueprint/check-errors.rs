@@ -0,0 +1,11 @@
+// Generated by Pulsar Blueprint Editor
+#![allow(unused)]
+
+fn main() {
+    let var_count: u8 = 7;
+    let var_half: f64 = var_count * 0.5;
+    while true {
+        println!("{:?}", var_half);
+    }
+    let var_text: String = undefined_value;
+}
@@ -0,0 +1,9 @@
+// Generated by Pulsar Blueprint Editor
+#![allow(unused)]
+
+fn main() {
+    let var_count: u8 = 7;
+    while true {
+        println!("{:?}", var_count);
+    }
+}
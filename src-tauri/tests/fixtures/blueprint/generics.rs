@@ -0,0 +1,14 @@
+// Generated by Pulsar Blueprint Editor
+#![allow(unused)]
+
+fn main() {
+    let var_a: u8 = 7;
+    let var_b: u8 = 9;
+    let var_list: Vec<u8> = vec![var_a, var_b];
+    let var_first: u8 = var_list[0];
+    let var_half: f64 = 0.5;
+    let (var_pair_left, var_pair_right): (u8, f64) = (var_first, var_half);
+    let var_sum: f64 = var_half + var_half;
+    println!("{:?}", var_pair_left);
+    println!("{:?}", var_sum);
+}
@@ -0,0 +1,9 @@
+// Generated by Pulsar Blueprint Editor
+#![allow(unused)]
+
+fn main() {
+    let var_count: i32 = 3;
+    let var_scale: f32 = 1.5;
+    let var_total: f64 = (var_count as f64) * (var_scale as f64) + (var_count as i64) as f64;
+    println!("{:?}", var_total);
+}
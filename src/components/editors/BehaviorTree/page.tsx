@@ -1,8 +1,26 @@
 import React, { useState, useRef, useEffect } from 'react';
+import { open, save } from '@tauri-apps/api/dialog';
 import { ScrollArea } from "@/components/shared/ScrollArea";
-import { X, Save, Plus, Link } from 'lucide-react';
+import { X, Save, Plus, Link, FolderOpen, Play, Square } from 'lucide-react';
+import {
+  BEHAVIOR_EXTENSION,
+  BEHAVIOR_FORMAT_VERSION,
+  listBehaviorHandlers,
+  loadBehaviorTree,
+  saveBehaviorTree,
+  useBehaviorDebugger,
+  type BehaviorAsset,
+  type BehaviorDocument,
+  type BehaviorHandlers,
+  type BehaviorLayout,
+  type BehaviorNodeType,
+  type BehaviorStatus,
+  type BlackboardValue,
+} from '@/hooks/tauri/behavior';
 
-type NodeType = 'sequence' | 'selector' | 'condition' | 'action';
+type NodeType = BehaviorNodeType;
+
+type Values = Record<string, BlackboardValue>;
 
 interface Node {
   id: string;
@@ -10,6 +28,9 @@ interface Node {
   name: string;
   position: { x: number; y: number };
   children: string[];
+  /** Registered action or condition a leaf runs. */
+  handler?: string;
+  params: Values;
 }
 
 interface Connection {
@@ -18,6 +39,103 @@ interface Connection {
   to: string;
 }
 
+const NODE_TYPES: NodeType[] = [
+  'sequence', 'selector', 'parallel', 'inverter', 'repeat', 'timeout', 'cooldown', 'condition', 'action',
+];
+
+// Parameters a new node starts with
+const DEFAULT_PARAMS: Partial<Record<NodeType, Values>> = {
+  parallel: { policy: 'requireAll' },
+  timeout: { seconds: 5 },
+  cooldown: { seconds: 1 },
+};
+
+const TREE_FILTERS = [{ name: 'Behavior Tree', extensions: [BEHAVIOR_EXTENSION] }];
+
+// Running nodes are the active branch of the debugged agent
+const STATUS_RINGS: Record<BehaviorStatus, string> = {
+  running: 'ring-2 ring-yellow-400',
+  success: 'ring-2 ring-green-500',
+  failure: 'ring-2 ring-red-500',
+};
+
+// Children are ticked top to bottom, as they are drawn
+const toAsset = (nodes: Record<string, Node>, blackboard: Values): BehaviorAsset => ({
+  version: BEHAVIOR_FORMAT_VERSION,
+  nodes: Object.fromEntries(Object.values(nodes).map(node => [node.id, {
+    type: node.type,
+    name: node.name,
+    ...(node.handler ? { handler: node.handler } : {}),
+    params: node.params,
+    children: node.children
+      .filter(child => nodes[child])
+      .sort((a, b) => nodes[a].position.y - nodes[b].position.y),
+  }])),
+  blackboard,
+});
+
+const toLayout = (nodes: Record<string, Node>): BehaviorLayout => ({
+  version: BEHAVIOR_FORMAT_VERSION,
+  nodes: Object.fromEntries(Object.values(nodes).map(node => [node.id, node.position])),
+});
+
+// Nodes missing from the layout are laid out on a grid
+const fromDocument = ({ asset, layout }: BehaviorDocument) => {
+  const nodes: Record<string, Node> = {};
+  const connections: Connection[] = [];
+  Object.entries(asset.nodes).forEach(([id, node], index) => {
+    nodes[id] = {
+      id,
+      type: node.type,
+      name: node.name,
+      position: layout.nodes[id] ?? { x: 40 + (index % 4) * 200, y: 40 + Math.floor(index / 4) * 80 },
+      children: node.children ?? [],
+      handler: node.handler,
+      params: node.params ?? {},
+    };
+    for (const child of node.children ?? []) {
+      connections.push({ id: `conn-${id}-${child}`, from: id, to: child });
+    }
+  });
+  return { nodes, connections, blackboard: asset.blackboard ?? {} };
+};
+
+// Edits an object of values as JSON, applied when the field loses focus
+const ValuesField = ({ value, onChange }: { value: Values; onChange: (value: Values) => void }) => {
+  const [text, setText] = useState(() => JSON.stringify(value, null, 2));
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setText(JSON.stringify(value, null, 2));
+    setError(null);
+  }, [value]);
+
+  const apply = () => {
+    try {
+      const parsed = JSON.parse(text.trim() || '{}');
+      if (typeof parsed !== 'object' || parsed === null || Array.isArray(parsed)) {
+        throw new Error('Expected an object of values');
+      }
+      onChange(parsed);
+    } catch (err: any) {
+      setError(err?.message ?? String(err));
+    }
+  };
+
+  return (
+    <>
+      <textarea
+        className="w-full bg-zinc-950 border border-zinc-900 rounded p-1 text-xs font-mono"
+        rows={4}
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        onBlur={apply}
+      />
+      {error && <div className="text-xs text-red-400">{error}</div>}
+    </>
+  );
+};
+
 const AIFlowEditor = () => {
   const [nodes, setNodes] = useState<Record<string, Node>>({});
   const [connections, setConnections] = useState<Connection[]>([]);
@@ -26,6 +144,66 @@ const AIFlowEditor = () => {
   const [mousePos, setMousePos] = useState({ x: 0, y: 0 });
   const canvasRef = useRef<HTMLDivElement>(null);
 
+  // File the tree was last saved to or opened from
+  const [path, setPath] = useState<string | null>(null);
+  // Values the blackboard of agents starts with
+  const [blackboard, setBlackboard] = useState<Values>({});
+  const [handlers, setHandlers] = useState<BehaviorHandlers>({ actions: [], conditions: [] });
+  const [notice, setNotice] = useState<string | null>(null);
+  const agentDebugger = useBehaviorDebugger();
+
+  useEffect(() => {
+    listBehaviorHandlers().then(setHandlers).catch(() => undefined);
+  }, []);
+
+  const updateNode = (nodeId: string, update: Partial<Node>) => {
+    setNodes(prev => ({ ...prev, [nodeId]: { ...prev[nodeId], ...update } }));
+  };
+
+  const setParam = (nodeId: string, key: string, value: BlackboardValue | undefined) => {
+    setNodes(prev => {
+      const params = { ...prev[nodeId].params };
+      if (value === undefined) delete params[key];
+      else params[key] = value;
+      return { ...prev, [nodeId]: { ...prev[nodeId], params } };
+    });
+  };
+
+  // Reports why a file or debugger action failed
+  const attempt = (action: () => Promise<unknown>) => () => {
+    action().catch((err: any) => setNotice(err?.message ?? String(err)));
+  };
+
+  const saveTree = async () => {
+    const target = path ?? await save({ filters: TREE_FILTERS });
+    if (!target) return null;
+    await saveBehaviorTree(target, toAsset(nodes, blackboard), toLayout(nodes));
+    setPath(target);
+    setNotice(`Saved to ${target}`);
+    return target;
+  };
+
+  const openTree = async () => {
+    const target = await open({ filters: TREE_FILTERS, multiple: false });
+    if (typeof target !== 'string') return;
+    const loaded = fromDocument(await loadBehaviorTree(target));
+    setNodes(loaded.nodes);
+    setConnections(loaded.connections);
+    setBlackboard(loaded.blackboard);
+    setSelectedNode(null);
+    setConnectingFrom(null);
+    setPath(target);
+    setNotice(`Opened ${target}`);
+  };
+
+  // Agents run the saved tree, so play saves it first
+  const playTree = async () => {
+    const target = await saveTree();
+    if (!target) return;
+    const agent = await agentDebugger.spawn(target);
+    setNotice(`Debugging ${agent.name}`);
+  };
+
   // Track mouse position for the temporary connection line
   const handleMouseMove = (e: React.MouseEvent) => {
     if (canvasRef.current) {
@@ -49,7 +227,8 @@ const AIFlowEditor = () => {
       type,
       name: `${type} ${Object.keys(nodes).length + 1}`,
       position: { x: defaultX, y: defaultY },
-      children: []
+      children: [],
+      params: { ...DEFAULT_PARAMS[type] }
     };
 
     setNodes(prev => ({ ...prev, [id]: newNode }));
@@ -138,8 +317,59 @@ const AIFlowEditor = () => {
       <div className="w-48 bg-black border-r border-zinc-950">
         <ScrollArea className="h-full">
           <div className="p-2 space-y-2">
+            <div className="text-sm font-semibold px-2">Tree</div>
+            <div className="flex gap-2">
+              <button
+                className="flex-1 p-2 bg-zinc-950 rounded flex items-center gap-2 hover:bg-zinc-900"
+                onClick={attempt(openTree)}
+              >
+                <FolderOpen className="w-4 h-4" />
+                Open
+              </button>
+              <button
+                className="flex-1 p-2 bg-zinc-950 rounded flex items-center gap-2 hover:bg-zinc-900"
+                onClick={attempt(saveTree)}
+              >
+                <Save className="w-4 h-4" />
+                Save
+              </button>
+            </div>
+            {path && <div className="text-xs text-gray-500 px-2 break-all">{path}</div>}
+
+            <div className="text-sm font-semibold px-2">Debug</div>
+            <div className="flex gap-2">
+              <button
+                className="flex-1 p-2 bg-zinc-950 rounded flex items-center gap-2 hover:bg-zinc-900"
+                onClick={attempt(playTree)}
+              >
+                <Play className="w-4 h-4" />
+                Play
+              </button>
+              <button
+                className="flex-1 p-2 bg-zinc-950 rounded flex items-center gap-2 hover:bg-zinc-900 disabled:opacity-50"
+                disabled={agentDebugger.debugged === null}
+                onClick={attempt(async () => {
+                  if (agentDebugger.debugged !== null) await agentDebugger.despawn(agentDebugger.debugged);
+                })}
+              >
+                <Square className="w-4 h-4" />
+                Stop
+              </button>
+            </div>
+            <select
+              className="w-full bg-zinc-950 border border-zinc-900 rounded p-1 text-sm"
+              value={agentDebugger.debugged ?? ''}
+              onChange={(e) => attempt(() => agentDebugger.debug(e.target.value ? Number(e.target.value) : null))()}
+            >
+              <option value="">No agent</option>
+              {agentDebugger.agents.map(agent => (
+                <option key={agent.id} value={agent.id}>{agent.name}</option>
+              ))}
+            </select>
+            {notice && <div className="text-xs text-gray-400 px-2 break-all">{notice}</div>}
+
             <div className="text-sm font-semibold px-2">Add Node</div>
-            {(['sequence', 'selector', 'condition', 'action'] as NodeType[]).map(type => (
+            {NODE_TYPES.map(type => (
               <button
                 key={type}
                 className="w-full p-2 bg-zinc-950 rounded flex items-center gap-2 hover:bg-zinc-900 capitalize"
@@ -219,6 +449,7 @@ const AIFlowEditor = () => {
               selectedNode === node.id ? 'ring-1 ring-zinc-700' : ''
             } ${connectingFrom === node.id ? 'ring-1 ring-blue-500' : ''}
               ${connectingFrom && connectingFrom !== node.id ? 'ring-1 ring-green-500 cursor-pointer' : ''}
+              ${agentDebugger.nodes[node.id] ? STATUS_RINGS[agentDebugger.nodes[node.id]] : ''}
             `}
             style={{
               left: node.position.x,
@@ -254,10 +485,17 @@ const AIFlowEditor = () => {
                     setConnections(prev => prev.filter(conn => 
                       conn.from !== node.id && conn.to !== node.id
                     ));
-                    // Remove the node
-                    const newNodes = { ...nodes };
-                    delete newNodes[node.id];
-                    setNodes(newNodes);
+                    // Remove the node, and it from its parent's children
+                    setNodes(prev => {
+                      const newNodes = { ...prev };
+                      delete newNodes[node.id];
+                      for (const [id, other] of Object.entries(newNodes)) {
+                        if (other.children.includes(node.id)) {
+                          newNodes[id] = { ...other, children: other.children.filter(child => child !== node.id) };
+                        }
+                      }
+                      return newNodes;
+                    });
                     if (selectedNode === node.id) setSelectedNode(null);
                     if (connectingFrom === node.id) setConnectingFrom(null);
                   }}
@@ -266,7 +504,9 @@ const AIFlowEditor = () => {
                 </button>
               </div>
             </div>
-            <div className="text-xs text-gray-400 mt-1">{node.type}</div>
+            <div className="text-xs text-gray-400 mt-1">
+              {node.type}{node.handler ? ` · ${node.handler}` : ''}
+            </div>
             {/* Draggable handle */}
             <div 
               className="absolute inset-0 cursor-move"
@@ -312,6 +552,75 @@ const AIFlowEditor = () => {
                     {nodes[selectedNode].type}
                   </div>
                 </div>
+                {(nodes[selectedNode].type === 'condition' || nodes[selectedNode].type === 'action') && (
+                  <>
+                    <div className="space-y-2">
+                      <label className="text-xs text-gray-500">Handler</label>
+                      <input
+                        type="text"
+                        list={`behavior-${nodes[selectedNode].type}s`}
+                        className="w-full bg-zinc-950 border border-zinc-900 rounded p-1 text-sm"
+                        value={nodes[selectedNode].handler ?? ''}
+                        onChange={(e) => updateNode(selectedNode, { handler: e.target.value || undefined })}
+                      />
+                      <datalist id="behavior-actions">
+                        {handlers.actions.map(name => <option key={name} value={name} />)}
+                      </datalist>
+                      <datalist id="behavior-conditions">
+                        {handlers.conditions.map(name => <option key={name} value={name} />)}
+                      </datalist>
+                    </div>
+                    <div className="space-y-2">
+                      <label className="text-xs text-gray-500">Parameters</label>
+                      <ValuesField
+                        key={selectedNode}
+                        value={nodes[selectedNode].params}
+                        onChange={(params) => updateNode(selectedNode, { params })}
+                      />
+                    </div>
+                  </>
+                )}
+                {nodes[selectedNode].type === 'repeat' && (
+                  <div className="space-y-2">
+                    <label className="text-xs text-gray-500">Count (empty repeats forever)</label>
+                    <input
+                      type="number"
+                      min={0}
+                      step={1}
+                      className="w-full bg-zinc-950 border border-zinc-900 rounded p-1 text-sm"
+                      value={(nodes[selectedNode].params.count as number | undefined) ?? ''}
+                      onChange={(e) => setParam(
+                        selectedNode, 'count', e.target.value === '' ? undefined : Math.max(0, Math.round(Number(e.target.value)))
+                      )}
+                    />
+                  </div>
+                )}
+                {(nodes[selectedNode].type === 'timeout' || nodes[selectedNode].type === 'cooldown') && (
+                  <div className="space-y-2">
+                    <label className="text-xs text-gray-500">Seconds</label>
+                    <input
+                      type="number"
+                      min={0}
+                      step={0.1}
+                      className="w-full bg-zinc-950 border border-zinc-900 rounded p-1 text-sm"
+                      value={(nodes[selectedNode].params.seconds as number | undefined) ?? 0}
+                      onChange={(e) => setParam(selectedNode, 'seconds', Math.max(0, Number(e.target.value)))}
+                    />
+                  </div>
+                )}
+                {nodes[selectedNode].type === 'parallel' && (
+                  <div className="space-y-2">
+                    <label className="text-xs text-gray-500">Policy</label>
+                    <select
+                      className="w-full bg-zinc-950 border border-zinc-900 rounded p-1 text-sm"
+                      value={(nodes[selectedNode].params.policy as string | undefined) ?? 'requireAll'}
+                      onChange={(e) => setParam(selectedNode, 'policy', e.target.value)}
+                    >
+                      <option value="requireAll">Succeed when all succeed</option>
+                      <option value="requireOne">Succeed when one succeeds</option>
+                    </select>
+                  </div>
+                )}
                 <div className="space-y-2">
                   <label className="text-xs text-gray-500">Connections</label>
                   <div className="text-sm">
@@ -326,8 +635,32 @@ const AIFlowEditor = () => {
                 </div>
               </>
             ) : (
-              <div className="text-sm text-gray-500">
-                Select a node to edit its properties
+              <>
+                <div className="text-sm text-gray-500">
+                  Select a node to edit its properties
+                </div>
+                <div className="space-y-2">
+                  <label className="text-xs text-gray-500">Initial Blackboard</label>
+                  <ValuesField value={blackboard} onChange={setBlackboard} />
+                </div>
+              </>
+            )}
+            {agentDebugger.debugged !== null && (
+              <div className="space-y-2">
+                <label className="text-xs text-gray-500">
+                  Tick {agentDebugger.tick} · {agentDebugger.time.toFixed(1)}s
+                  {agentDebugger.status ? ` · ${agentDebugger.status}` : ''}
+                </label>
+                <div className="text-xs font-mono bg-zinc-950 border border-zinc-900 rounded p-1">
+                  {Object.keys(agentDebugger.blackboard).length === 0 ? (
+                    <div className="text-gray-500">Blackboard is empty</div>
+                  ) : Object.entries(agentDebugger.blackboard).map(([key, value]) => (
+                    <div key={key} className="flex justify-between gap-2">
+                      <span className="text-gray-400">{key}</span>
+                      <span className="truncate">{JSON.stringify(value)}</span>
+                    </div>
+                  ))}
+                </div>
               </div>
             )}
           </div>
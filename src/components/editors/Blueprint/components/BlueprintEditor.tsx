@@ -9,10 +9,20 @@ import React, { useState, useCallback, useEffect, useMemo, memo } from 'react';
 import 'reactflow/dist/style.css';
 import Editor from '@monaco-editor/react';
 import { Input } from '@/components/shared/Input';
-import { Search, X, Trash2 } from 'lucide-react';
+import {
+  Search, X, Trash2, Play, Pause, Square, StepForward, FastForward, ListChecks, Loader2,
+} from 'lucide-react';
 import { useNodeStore } from '../store/nodeStore';
 import PulsarNode from './PulsarNode';
-import { generateRustCode } from '../lib/generateRust';
+import { BlueprintRunContext } from '../context/BlueprintRunContext';
+import {
+  CargoDiagnostic,
+  compilationText,
+  useBlueprintCheck,
+  useBlueprintCompilation,
+  useBlueprintRun,
+  useNodeLibrary,
+} from '@/hooks/tauri/blueprint';
 import { Label } from '@/components/shared/Label';
 import {
   Select,
@@ -190,6 +200,92 @@ const ContextMenuComponent = memo(({
   );
 });
 
+// Controls of the graph running in the editor, what it printed and what
+// cargo check found outside the nodes
+const RunToolbar = memo(({ run, onRun, onStep, check, onCheck }: {
+  run: ReturnType<typeof useBlueprintRun>;
+  onRun: () => void;
+  onStep: () => void;
+  check: ReturnType<typeof useBlueprintCheck>;
+  onCheck: () => void;
+}) => {
+  const active = run.status === 'starting' || run.status === 'running' || run.status === 'paused';
+  const paused = run.status === 'paused';
+  const buttonClass =
+    'p-1.5 rounded hover:bg-neutral-800 text-neutral-300 disabled:opacity-40 disabled:hover:bg-transparent';
+
+  return (
+    <div className="w-80 bg-neutral-900 border border-neutral-800 rounded-lg shadow-xl">
+      <div className="flex items-center gap-1 p-1">
+        {paused ? (
+          <button className={buttonClass} title="Continue" onClick={run.resume}>
+            <FastForward className="h-4 w-4" />
+          </button>
+        ) : (
+          <button className={buttonClass} title="Run" onClick={onRun} disabled={active}>
+            <Play className="h-4 w-4" />
+          </button>
+        )}
+        <button
+          className={buttonClass}
+          title="Step to the next node"
+          onClick={paused ? run.step : onStep}
+          disabled={active && !paused}
+        >
+          <StepForward className="h-4 w-4" />
+        </button>
+        <button className={buttonClass} title="Pause" onClick={run.pause} disabled={!active || paused}>
+          <Pause className="h-4 w-4" />
+        </button>
+        <button className={buttonClass} title="Stop" onClick={run.stop} disabled={!active}>
+          <Square className="h-4 w-4" />
+        </button>
+        <div className="w-px h-4 mx-1 bg-neutral-800" />
+        <button
+          className={buttonClass}
+          title="Check the code with cargo"
+          onClick={onCheck}
+          disabled={check.checking}
+        >
+          {check.checking ? <Loader2 className="h-4 w-4 animate-spin" /> : <ListChecks className="h-4 w-4" />}
+        </button>
+        <span className="ml-2 text-xs text-neutral-500 capitalize">
+          {run.status === 'idle' ? '' : run.status}
+        </span>
+      </div>
+      {(run.output || run.failure) && (
+        <pre className="max-h-40 overflow-auto border-t border-neutral-800 p-2 text-xs text-neutral-300 whitespace-pre-wrap">
+          {run.output}
+          {run.failure && <span className="text-red-400">{run.failure.message}</span>}
+        </pre>
+      )}
+      {(check.result || check.error) && (
+        <div className="max-h-40 overflow-auto border-t border-neutral-800 p-2 text-xs">
+          {check.error ? (
+            <div className="text-red-400">{check.error.message}</div>
+          ) : check.result?.success ? (
+            <div className="text-emerald-400">
+              cargo check passed
+              {check.result.diagnostics.length === 1 && ' with 1 warning'}
+              {check.result.diagnostics.length > 1 && ` with ${check.result.diagnostics.length} warnings`}
+            </div>
+          ) : (
+            <div className="text-red-400">cargo check failed</div>
+          )}
+          {/* Problems in the code around the nodes */}
+          {check.result?.diagnostics
+            .filter((diagnostic: CargoDiagnostic) => diagnostic.node === null)
+            .map((diagnostic: CargoDiagnostic, index: number) => (
+              <pre key={index} className="mt-1 text-neutral-400 whitespace-pre-wrap">
+                {diagnostic.rendered}
+              </pre>
+            ))}
+        </div>
+      )}
+    </div>
+  );
+});
+
 // Memoized property panel component
 const PropertyPanel = memo(({ 
   selectedNode, 
@@ -283,7 +379,10 @@ const BlueprintEditor = () => {
   const [selectedNode, setSelectedNode] = useState<Node | null>(null);
   const [searchQuery, setSearchQuery] = useState('');
   const [rfInstance, setRfInstance] = useState<ReactFlowInstance | null>(null);
-  const { definitions, loadDefinition } = useNodeStore();
+  const { definitions, setLibrary } = useNodeStore();
+  const [breakpoints, setBreakpoints] = useState<string[]>([]);
+  const run = useBlueprintRun(breakpoints);
+  const check = useBlueprintCheck();
 
   // Memoized filtered definitions
   const filteredDefinitions = useMemo(() => 
@@ -293,31 +392,11 @@ const BlueprintEditor = () => {
     [definitions, searchQuery]
   );
 
-  // Load node definitions
+  // Node definitions come from the backend, reloaded when their files change
+  const library = useNodeLibrary();
   useEffect(() => {
-    const loadNodeDefinitions = async () => {
-      try {
-        const nodeFiles = [
-          'arithmetic.yaml',
-          'control_flow.yaml',
-          'variables.yaml',
-          'functions.yaml',
-          'types.yaml'
-        ];
-
-        await Promise.all(nodeFiles.map(async (file) => {
-          const response = await fetch(`/nodes/${file}`);
-          if (!response.ok) throw new Error(`Failed to load ${file}`);
-          const content = await response.text();
-          loadDefinition(content);
-        }));
-      } catch (error) {
-        console.error('Error loading node definitions:', error);
-      }
-    };
-
-    loadNodeDefinitions();
-  }, [loadDefinition]);
+    if (library) setLibrary(library);
+  }, [library, setLibrary]);
 
   const onInit = useCallback((instance: ReactFlowInstance) => {
     setRfInstance(instance);
@@ -444,89 +523,135 @@ const BlueprintEditor = () => {
     setContextMenu({ show: false, x: 0, y: 0, type: 'pane' });
   }, [contextMenu.position, definitions]);
 
+  const toggleBreakpoint = useCallback((nodeId: string) => {
+    setBreakpoints((current) =>
+      current.includes(nodeId) ? current.filter((id) => id !== nodeId) : [...current, nodeId]
+    );
+  }, []);
+
+  const { start } = run;
+  const startRun = useCallback(() => start(nodes, edges), [start, nodes, edges]);
+  const stepRun = useCallback(() => start(nodes, edges, true), [start, nodes, edges]);
+  const { check: runCheck } = check;
+  const checkCode = useCallback(() => runCheck(nodes, edges), [runCheck, nodes, edges]);
+
+  // What cargo check found in the code of each node
+  const checkDiagnostics = useMemo(() => {
+    const byNode: Record<string, CargoDiagnostic[]> = {};
+    for (const diagnostic of check.result?.diagnostics ?? []) {
+      if (diagnostic.node !== null) {
+        (byNode[diagnostic.node] ??= []).push(diagnostic);
+      }
+    }
+    return byNode;
+  }, [check.result]);
+
+  const runContext = useMemo(() => ({
+    breakpoints,
+    toggleBreakpoint,
+    values: run.values,
+    pausedNode: run.pausedNode,
+    failure: run.failure,
+    checkDiagnostics,
+  }), [breakpoints, toggleBreakpoint, run.values, run.pausedNode, run.failure, checkDiagnostics]);
+
   const closeContextMenu = useCallback(() => {
     setContextMenu({ show: false, x: 0, y: 0, type: 'pane' });
   }, []);
 
-  // Memoized Rust code generation
-  const generatedRustCode = useMemo(() => generateRustCode(nodes, edges), [nodes, edges]);
+  // Rust code compiled by the backend as the graph changes
+  const compilation = useBlueprintCompilation(nodes, edges);
+  const generatedRustCode = compilation ? compilationText(compilation) : '// Compiling...\n';
 
   return (
     <div className="w-full h-screen bg-black" onClick={closeContextMenu}>
-      <ReactFlow
-        nodes={nodes}
-        edges={edges}
-        onNodesChange={onNodesChange}
-        onEdgesChange={onEdgesChange}
-        onConnect={onConnect}
-        onInit={onInit}
-        onNodeClick={(_, node) => setSelectedNode(node)}
-        nodeTypes={nodeTypes}
-        defaultEdgeOptions={defaultEdgeOptions}
-        onContextMenu={(e) => handleContextMenu(e)}
-        onNodeContextMenu={(e, node) => handleContextMenu(e, node)}
-        connectionMode={ConnectionMode.Loose}
-        minZoom={0.1}
-        maxZoom={4}
-        proOptions={proOptions}
-        fitView
-        className="bg-black"
-        deleteKeyCode="Delete"
-        selectionKeyCode="Shift"
-        multiSelectionKeyCode="Control"
-        zoomActivationKeyCode="Control"
-        panActivationKeyCode="Space"
-      >
-        <MemoizedBackground color="#333" gap={16} />
-        <MemoizedControls
-          className="bg-neutral-900 border border-neutral-800 fill-neutral-400"
-          showInteractive={true}
-          position="bottom-right"
-        />
-        <MemoizedMiniMap
-          className="bg-neutral-900 border border-neutral-800"
-          nodeColor="#2563eb"
-          position="bottom-left"
-        />
-
-        {/* Right Panel */}
-        <Panel position="top-right" className="w-96 h-full bg-black border-l border-neutral-800">
-          <div className="flex flex-col h-full">
-            {/* Code Preview */}
-            <div className="h-1/2 border-b border-neutral-800">
-              <MemoizedEditor
-                height="100%"
-                defaultLanguage="rust"
-                theme="vs-dark"
-                value={generatedRustCode}
-                options={editorOptions}
-              />
-            </div>
+      <BlueprintRunContext.Provider value={runContext}>
+        <ReactFlow
+          nodes={nodes}
+          edges={edges}
+          onNodesChange={onNodesChange}
+          onEdgesChange={onEdgesChange}
+          onConnect={onConnect}
+          onInit={onInit}
+          onNodeClick={(_, node) => setSelectedNode(node)}
+          nodeTypes={nodeTypes}
+          defaultEdgeOptions={defaultEdgeOptions}
+          onContextMenu={(e) => handleContextMenu(e)}
+          onNodeContextMenu={(e, node) => handleContextMenu(e, node)}
+          connectionMode={ConnectionMode.Loose}
+          minZoom={0.1}
+          maxZoom={4}
+          proOptions={proOptions}
+          fitView
+          className="bg-black"
+          deleteKeyCode="Delete"
+          selectionKeyCode="Shift"
+          multiSelectionKeyCode="Control"
+          zoomActivationKeyCode="Control"
+          panActivationKeyCode="Space"
+        >
+          <MemoizedBackground color="#333" gap={16} />
+          <MemoizedControls
+            className="bg-neutral-900 border border-neutral-800 fill-neutral-400"
+            showInteractive={true}
+            position="bottom-right"
+          />
+          <MemoizedMiniMap
+            className="bg-neutral-900 border border-neutral-800"
+            nodeColor="#2563eb"
+            position="bottom-left"
+          />
+
+          {/* Run Controls */}
+          <Panel position="top-left">
+            <RunToolbar
+            run={run}
+            onRun={startRun}
+            onStep={stepRun}
+            check={check}
+            onCheck={checkCode}
+          />
+          </Panel>
+
+          {/* Right Panel */}
+          <Panel position="top-right" className="w-96 h-full bg-black border-l border-neutral-800">
+            <div className="flex flex-col h-full">
+              {/* Code Preview */}
+              <div className="h-1/2 border-b border-neutral-800">
+                <MemoizedEditor
+                  height="100%"
+                  defaultLanguage="rust"
+                  theme="vs-dark"
+                  value={generatedRustCode}
+                  options={editorOptions}
+                />
+              </div>
 
-            {/* Properties Panel */}
-            <div className="h-1/2 overflow-y-auto">
-              <PropertyPanel
-                selectedNode={selectedNode}
-                updateNodeFields={updateNodeFields}
-              />
+              {/* Properties Panel */}
+              <div className="h-1/2 overflow-y-auto">
+                <PropertyPanel
+                  selectedNode={selectedNode}
+                  updateNodeFields={updateNodeFields}
+                />
+              </div>
             </div>
-          </div>
-        </Panel>
-
-        {/* Context Menu */}
-        <ContextMenuComponent
-          show={contextMenu.show}
-          x={contextMenu.x}
-          y={contextMenu.y}
-          type={contextMenu.type}
-          nodeId={contextMenu.nodeId}
-          onDelete={deleteNode}
-          searchQuery={searchQuery}
-          setSearchQuery={setSearchQuery}
-          filteredDefinitions={filteredDefinitions}
-          addNode={addNode}
-        />
-      </ReactFlow>
+          </Panel>
+
+          {/* Context Menu */}
+          <ContextMenuComponent
+            show={contextMenu.show}
+            x={contextMenu.x}
+            y={contextMenu.y}
+            type={contextMenu.type}
+            nodeId={contextMenu.nodeId}
+            onDelete={deleteNode}
+            searchQuery={searchQuery}
+            setSearchQuery={setSearchQuery}
+            filteredDefinitions={filteredDefinitions}
+            addNode={addNode}
+          />
+        </ReactFlow>
+      </BlueprintRunContext.Provider>
     </div>
   );
 };
@@ -4,43 +4,21 @@ import { Input } from '@/components/shared/Input';
 import { Search } from 'lucide-react';
 import { useNodeStore } from '../store/nodeStore';
 import { useNodeEditor } from '../context/NodeEditorContext';
+import { useNodeLibrary } from '@/hooks/tauri/blueprint';
 
 const NodeLibrary = () => {
   const [searchQuery, setSearchQuery] = React.useState('');
-  const [isLoading, setIsLoading] = React.useState(true);
   const definitions = useNodeStore(state => state.definitions);
-  const loadDefinition = useNodeStore(state => state.loadDefinition);
+  const problems = useNodeStore(state => state.libraryProblems);
+  const setLibrary = useNodeStore(state => state.setLibrary);
   const { addNode } = useNodeEditor();
 
-  // Load node definitions on mount
+  // Node definitions are loaded by the backend, and reloaded when their files change
+  const library = useNodeLibrary();
+  const isLoading = library === null;
   useEffect(() => {
-    const loadNodeDefinitions = async () => {
-      try {
-        const nodeFiles = [
-          'arithmetic.yaml',
-          'control_flow.yaml',
-          'variables.yaml',
-          'functions.yaml',
-          'types.yaml'
-        ];
-
-        for (const file of nodeFiles) {
-          const response = await fetch(`/nodes/${file}`);
-          if (!response.ok) {
-            throw new Error(`Failed to load ${file}`);
-          }
-          const content = await response.text();
-          loadDefinition(content);
-        }
-      } catch (error) {
-        console.error('Error loading node definitions:', error);
-      } finally {
-        setIsLoading(false);
-      }
-    };
-
-    loadNodeDefinitions();
-  }, [loadDefinition]);
+    if (library) setLibrary(library);
+  }, [library, setLibrary]);
 
   // Group nodes by category
   const nodesByCategory = React.useMemo(() => {
@@ -89,6 +67,20 @@ const NodeLibrary = () => {
       </div>
 
       <ScrollArea className="flex-1 p-4">
+        {problems.length > 0 && (
+          <div className="mb-6 space-y-1">
+            {problems.map((problem, i) => (
+              <div
+                key={i}
+                title={problem.path}
+                className={`text-xs ${problem.severity === 'error' ? 'text-red-400' : 'text-yellow-400'}`}
+              >
+                {problem.path.split(/[\\/]/).pop()}
+                {problem.line !== null && `:${problem.line}`}: {problem.message}
+              </div>
+            ))}
+          </div>
+        )}
         {Object.entries(nodesByCategory).map(([category, nodes]) => (
           <div key={category} className="mb-6">
             <h3 className="text-sm font-medium mb-2 text-gray-400">{category}</h3>
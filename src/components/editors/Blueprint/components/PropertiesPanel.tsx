@@ -1,4 +1,4 @@
-import React from 'react';
+import React, { useEffect, useState } from 'react';
 import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/shared/Tabs';
 import { Card, CardContent } from '@/components/shared/Card';
 import { Input } from '@/components/shared/Input';
@@ -13,6 +13,8 @@ import { Trash2, Copy, Info } from 'lucide-react';
 
 const PropertiesPanel = () => {
   const { 
+    nodes,
+    edges,
     selectedNode, 
     updateNode, 
     deleteNode, 
@@ -20,6 +22,20 @@ const PropertiesPanel = () => {
     generateCode,
     validationErrors 
   } = useNodeEditor();
+  const [code, setCode] = useState('');
+
+  // Code is compiled by the backend, so it arrives asynchronously
+  useEffect(() => {
+    let ignore = false;
+    generateCode()
+      .then(result => {
+        if (!ignore) setCode(result);
+      })
+      .catch((err: unknown) => console.error('Failed to generate code', err));
+    return () => {
+      ignore = true;
+    };
+  }, [generateCode, nodes, edges]);
 
   // Handle field updates
   const handleFieldUpdate = (fieldName: string, value: string) => {
@@ -175,7 +191,7 @@ const PropertiesPanel = () => {
                 height="100%"
                 defaultLanguage="rust"
                 theme="vs-dark"
-                value={generateCode()}
+                value={code}
                 options={{
                   readOnly: true,
                   minimap: { enabled: false },
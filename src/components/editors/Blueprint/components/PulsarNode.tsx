@@ -1,6 +1,7 @@
 // components/PulsarNode.tsx
 import React from 'react';
 import { Handle, Position } from 'reactflow';
+import { useBlueprintRunContext } from '../context/BlueprintRunContext';
 
 // Type color mapping
 const TYPE_COLORS = {
@@ -22,7 +23,14 @@ const getTypeColors = (type: keyof typeof TYPE_COLORS | string) => {
   return TYPE_COLORS[type as keyof typeof TYPE_COLORS] || TYPE_COLORS.default;
 };
 
+// Short text of a value a run gave an output
+const formatValue = (value: unknown) => {
+  const text = typeof value === 'string' ? value : JSON.stringify(value) ?? 'null';
+  return text.length > 24 ? `${text.slice(0, 23)}…` : text;
+};
+
 interface PulsarNodeProps {
+  id: string;
   data: {
     nodeDefinition: {
       name: string;
@@ -45,7 +53,22 @@ interface PulsarNodeProps {
   selected?: boolean;
 }
 
-const PulsarNode = ({ data, selected }: PulsarNodeProps) => {
+const PulsarNode = ({ id, data, selected }: PulsarNodeProps) => {
+  const {
+    breakpoints, toggleBreakpoint, values, pausedNode, failure, checkDiagnostics,
+  } = useBlueprintRunContext();
+  const hasBreakpoint = breakpoints.includes(id);
+  const outputValues = values[id] || {};
+  const failed = failure?.node === id;
+  // Why the run failed here, and what cargo check found in the node's code
+  const problems = [
+    ...(failed && failure ? [{ severity: 'error', text: failure.message }] : []),
+    ...(checkDiagnostics[id] || []).map((diagnostic) => ({
+      severity: diagnostic.severity,
+      text: diagnostic.label ? `${diagnostic.message}: ${diagnostic.label}` : diagnostic.message,
+    })),
+  ];
+  const hasErrors = problems.some((problem) => problem.severity === 'error');
   const inputPins = data.nodeDefinition.pins.inputs || [];
   const outputPins = data.nodeDefinition.pins.outputs || [];
   const maxPins = Math.max(inputPins.length, outputPins.length);
@@ -53,24 +76,43 @@ const PulsarNode = ({ data, selected }: PulsarNodeProps) => {
   const pinSpacing = 32;
   const fieldsHeight = Object.keys(data.fields).length > 0 ? 64 : 0;
   const paddingHeight = 32;
+  const problemsHeight = problems.length > 0 ? 16 + problems.length * 32 : 0;
   
   const nodeHeight = Math.max(
-    headerHeight + (maxPins * pinSpacing) + fieldsHeight + paddingHeight,
+    headerHeight + (maxPins * pinSpacing) + fieldsHeight + problemsHeight + paddingHeight,
     160
   );
 
-  const pinContainerHeight = nodeHeight - headerHeight - fieldsHeight - paddingHeight;
+  const pinContainerHeight = nodeHeight - headerHeight - fieldsHeight - problemsHeight - paddingHeight;
 
   return (
     <div 
       className={`bg-black border rounded-lg min-w-[200px] ${
-        selected ? 'border-blue-500' : 'border-gray-800'
+        pausedNode === id
+          ? 'border-yellow-400 ring-2 ring-yellow-400/40'
+          : hasErrors
+            ? 'border-red-500'
+            : selected
+              ? 'border-blue-500'
+              : 'border-gray-800'
       }`}
       style={{ height: `${nodeHeight}px` }}
     >
       {/* Node Header */}
       <div className="px-4 py-3 border-b border-gray-800">
-        <div className="text-blue-400 font-medium">{data.nodeDefinition.name}</div>
+        <div className="flex items-center gap-2">
+          <button
+            title={hasBreakpoint ? 'Remove breakpoint' : 'Add breakpoint'}
+            onClick={(e) => {
+              e.stopPropagation();
+              toggleBreakpoint(id);
+            }}
+            className={`nodrag w-3 h-3 rounded-full border transition-colors ${
+              hasBreakpoint ? 'bg-red-500 border-red-400' : 'border-gray-600 hover:border-red-400'
+            }`}
+          />
+          <div className="text-blue-400 font-medium">{data.nodeDefinition.name}</div>
+        </div>
         <div className="text-gray-400 text-xs mt-1">{data.nodeDefinition.description}</div>
       </div>
 
@@ -128,6 +170,11 @@ const PulsarNode = ({ data, selected }: PulsarNodeProps) => {
                 style={{ top: yPosition, transform: 'translateY(-50%)' }}
               >
                 <div className="mr-4 text-right">
+                  {pin.name in outputValues && (
+                    <span className="text-xs mr-2 text-emerald-400 font-mono">
+                      {formatValue(outputValues[pin.name])}
+                    </span>
+                  )}
                   <span className="text-xs text-gray-300">{pin.name}</span>
                   <span className="text-xs ml-1" style={{ color }}>{pin.type}</span>
                 </div>
@@ -164,6 +211,26 @@ const PulsarNode = ({ data, selected }: PulsarNodeProps) => {
           ))}
         </div>
       )}
+
+      {/* Run Failure and Check Problems */}
+      {problems.length > 0 && (
+        <div
+          className="px-4 py-2 border-t border-red-900 text-xs overflow-hidden"
+          style={{ height: `${problemsHeight}px` }}
+        >
+          {problems.map((problem, index) => (
+            <div
+              key={index}
+              title={problem.text}
+              className={`h-8 line-clamp-2 ${
+                problem.severity === 'error' ? 'text-red-400' : 'text-yellow-400'
+              }`}
+            >
+              {problem.text}
+            </div>
+          ))}
+        </div>
+      )}
     </div>
   );
 };
@@ -0,0 +1,29 @@
+import { createContext, useContext } from 'react';
+import type { CargoDiagnostic } from '@/hooks/tauri/blueprint';
+
+/** What nodes show of running and checking the graph in the editor. */
+export interface BlueprintRunContextType {
+  /** Nodes a run pauses before. */
+  breakpoints: string[];
+  toggleBreakpoint: (nodeId: string) => void;
+  /** Values of the outputs of every node that ran, by node and pin. */
+  values: Record<string, Record<string, unknown>>;
+  /** Node the run is paused before. */
+  pausedNode: string | null;
+  /** Why the run failed, and the node it failed on if any. */
+  failure: { node: string | null; message: string } | null;
+  /** Problems `cargo check` found in the code of each node. */
+  checkDiagnostics: Record<string, CargoDiagnostic[]>;
+}
+
+// Nodes outside the Blueprint editor have no run to show
+export const BlueprintRunContext = createContext<BlueprintRunContextType>({
+  breakpoints: [],
+  toggleBreakpoint: () => {},
+  values: {},
+  pausedNode: null,
+  failure: null,
+  checkDiagnostics: {},
+});
+
+export const useBlueprintRunContext = () => useContext(BlueprintRunContext);
@@ -28,7 +28,7 @@ interface NodeEditorContextType {
   // Graph operations
   clearGraph: () => void;
   validateGraph: () => void;
-  generateCode: () => string;
+  generateCode: () => Promise<string>;
   
   // UI state
   isValidating: boolean;
@@ -157,10 +157,10 @@ export const NodeEditorProvider: React.FC<{ children: React.ReactNode }> = ({ ch
     }
   }, [validateGraphFromStore]);
 
-  const generateCode = useCallback(() => {
+  const generateCode = useCallback(async () => {
     setIsGeneratingCode(true);
     try {
-      return generateCodeFromStore();
+      return await generateCodeFromStore();
     } finally {
       setIsGeneratingCode(false);
     }
@@ -75,14 +75,14 @@ pins:
       type: bool
       description: Boolean condition
     - name: then_value
-      type: any
+      type: T
       description: Value if condition is true
     - name: else_value
-      type: any
+      type: T
       description: Value if condition is false
   outputs:
     - name: result
-      type: any
+      type: T
       description: Selected value based on condition
 template: |
   if {{condition}} { {{then_value}} } else { {{else_value}} }
@@ -102,11 +102,11 @@ fields:
 pins:
   inputs:
     - name: body
-      type: any
+      type: T
       description: Code to execute in loop
   outputs:
     - name: result
-      type: array
+      type: Vec<T>
       description: Array of results
 template: |
   (0..{{count}}).map(|_| { {{body}} }).collect::<Vec<_>>()
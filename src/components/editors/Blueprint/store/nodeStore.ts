@@ -1,7 +1,7 @@
 import { create } from 'zustand';
 import { Node, Edge } from 'reactflow';
 import { parse } from 'yaml';
-import { generateRustCode } from '../lib/generateRust';
+import { compileBlueprint, compilationText, NodeLibrary, LibraryProblem } from '@/hooks/tauri/blueprint';
 
 // Types
 export interface PulsarPin {
@@ -48,6 +48,8 @@ interface NodeStoreState {
   // Node Definitions
   definitions: Record<string, PulsarNodeDefinition>;
   definitionsByCategory: Record<string, PulsarNodeDefinition[]>;
+  /** Problems loading the node library, per file and line. */
+  libraryProblems: LibraryProblem[];
   
   // Editor State
   nodes: Node[];
@@ -63,6 +65,7 @@ interface NodeStoreState {
 
 interface NodeStoreActions {
   // Definition Management
+  setLibrary: (library: NodeLibrary) => void;
   loadDefinition: (content: string) => void;
   loadDefinitionFromFile: (file: File) => Promise<void>;
   getNodeDefinition: (type: string) => PulsarNodeDefinition | undefined;
@@ -84,7 +87,7 @@ interface NodeStoreActions {
   
   // Validation & Code Generation
   validateGraph: () => ValidationError[];
-  generateCode: () => string;
+  generateCode: () => Promise<string>;
   
   // Graph Management
   clearGraph: () => void;
@@ -97,6 +100,7 @@ export const useNodeStore = create<NodeStoreState & NodeStoreActions>((set, get)
   // Initial State
   definitions: {},
   definitionsByCategory: {},
+  libraryProblems: [],
   nodes: [],
   edges: [],
   selectedNodeId: null,
@@ -106,6 +110,21 @@ export const useNodeStore = create<NodeStoreState & NodeStoreActions>((set, get)
   error: null,
 
   // Definition Management
+  setLibrary: (library: NodeLibrary) => {
+    const definitions: Record<string, PulsarNodeDefinition> = library.nodes;
+    const byCategory = Object.entries(library.categories).reduce((acc, [category, names]) => {
+      acc[category] = names.map(name => definitions[name]);
+      return acc;
+    }, {} as Record<string, PulsarNodeDefinition[]>);
+
+    set({
+      definitions,
+      definitionsByCategory: byCategory,
+      libraryProblems: library.problems,
+      error: null
+    });
+  },
+
   loadDefinition: (content: string) => {
     try {
       // Split content into documents and parse each one
@@ -279,7 +298,7 @@ export const useNodeStore = create<NodeStoreState & NodeStoreActions>((set, get)
     return errors;
   },
 
-  generateCode: () => {
+  generateCode: async () => {
     const errors = get().validateGraph();
     if (errors.length > 0) {
       return '// Fix validation errors before generating code\n\n' +
@@ -289,7 +308,7 @@ export const useNodeStore = create<NodeStoreState & NodeStoreActions>((set, get)
         ).join('\n\n');
     }
 
-    return generateRustCode(get().nodes, get().edges);
+    return compilationText(await compileBlueprint(get().nodes, get().edges));
   },
 
   // Graph Management
@@ -1,9 +1,12 @@
 import React, { useRef, useEffect, useState } from 'react';
-import { invoke } from '@tauri-apps/api/tauri';
 import {
   Camera, Hand, Move, ZoomIn, Play, Pause,
-  Grid3X3, Eye, EyeOff, Bug, RotateCcw, Monitor
+  Grid3X3, Eye, EyeOff, Bug, RotateCcw, Monitor, AlertTriangle, RefreshCw
 } from 'lucide-react';
+import {
+  orbitViewportCamera, panViewportCamera, resetViewportCamera, useNativeViewport,
+  zoomViewportCamera
+} from '@/hooks/tauri/viewport';
 
 const TOOLS = {
   SELECT: 'select',
@@ -19,6 +22,23 @@ const RENDER_MODES = {
 
 type ViewportMode = typeof TOOLS[keyof typeof TOOLS];
 type RenderMode = typeof RENDER_MODES[keyof typeof RENDER_MODES];
+type CameraDrag = typeof TOOLS.ORBIT | typeof TOOLS.PAN | typeof TOOLS.ZOOM;
+
+/** Radians the camera orbits per dragged CSS pixel. */
+const ORBIT_SPEED = 0.01;
+/** Zoom factor exponent per dragged pixel or wheel delta. */
+const ZOOM_DRAG_SPEED = 0.01;
+const ZOOM_WHEEL_SPEED = 0.001;
+
+interface CameraInput {
+  yaw: number;
+  pitch: number;
+  panX: number;
+  panY: number;
+  zoom: number;
+}
+
+const NO_CAMERA_INPUT: CameraInput = { yaw: 0, pitch: 0, panX: 0, panY: 0, zoom: 1 };
 
 declare global {
   interface Navigator {
@@ -26,7 +46,12 @@ declare global {
   }
 }
 
-const Viewport = () => {
+interface ViewportProps {
+  /** Identifies this view to the native renderer, one per editor viewport. */
+  viewportId?: string;
+}
+
+const Viewport = ({ viewportId = 'level' }: ViewportProps) => {
   const nativeCanvasRef = useRef<HTMLCanvasElement>(null);
   const webCanvasRef = useRef<HTMLCanvasElement>(null);
   const workerRef = useRef<Worker | null>(null);
@@ -37,40 +62,100 @@ const Viewport = () => {
   const [showGizmos, setShowGizmos] = useState(true);
   const [showDebug, setShowDebug] = useState(false);
   const [webCanvasKey, setWebCanvasKey] = useState(0);
+  const cameraInputRef = useRef<CameraInput>({ ...NO_CAMERA_INPUT });
+  const cameraDragRef = useRef<{ pointerId: number; mode: CameraDrag; x: number; y: number } | null>(null);
 
-  // Native renderer
-  useEffect(() => {
-    if (renderMode !== RENDER_MODES.NATIVE || !nativeCanvasRef.current) return;
-    
-    const canvas = nativeCanvasRef.current;
-    const dpr = window.devicePixelRatio || 1;
-    const rect = canvas.getBoundingClientRect();
-    
-    canvas.width = Math.floor(rect.width * dpr);
-    canvas.height = Math.floor(rect.height * dpr);
-    canvas.style.width = `${rect.width}px`;
-    canvas.style.height = `${rect.height}px`;
-
-    const ctx = canvas.getContext('2d', {
-      alpha: false,
-      desynchronized: true,
-    });
-    
-    if (!ctx) return;
+  // Native renderer, drawn by the backend over the canvas' rect
+  const nativeViewport = useNativeViewport(
+    viewportId, nativeCanvasRef, renderMode === RENDER_MODES.NATIVE
+  );
 
-    const imageData = ctx.createImageData(canvas.width, canvas.height);
-    const pixels = new Uint8Array(imageData.data.buffer);
-    pixels.fill(0);
+  // Send camera input once per animation frame, so fast pointer moves
+  // cannot fill the viewport's command queue
+  useEffect(() => {
+    if (renderMode !== RENDER_MODES.NATIVE) return;
+
+    const moveCamera = (): Promise<unknown>[] => {
+      const input = cameraInputRef.current;
+      cameraInputRef.current = { ...NO_CAMERA_INPUT };
+      const calls: Promise<unknown>[] = [];
+      if (input.yaw !== 0 || input.pitch !== 0) {
+        calls.push(orbitViewportCamera(viewportId, input.yaw, input.pitch));
+      }
+      if (input.panX !== 0 || input.panY !== 0) {
+        calls.push(panViewportCamera(viewportId, input.panX, input.panY));
+      }
+      if (input.zoom !== 1) {
+        calls.push(zoomViewportCamera(viewportId, input.zoom));
+      }
+      return calls;
+    };
 
-    const animate = () => {
-      if (!isPlaying || renderMode !== RENDER_MODES.NATIVE || canvas.style.display === 'none') return;
-      ctx.putImageData(imageData, 0, 0);
-      requestAnimationFrame(animate);
+    let frameId: number;
+    const flush = () => {
+      Promise.all(moveCamera()).catch((error) => {
+        console.error('Failed to move viewport camera:', error);
+      });
+      frameId = requestAnimationFrame(flush);
     };
 
-    const frameId = requestAnimationFrame(animate);
+    frameId = requestAnimationFrame(flush);
     return () => cancelAnimationFrame(frameId);
-  }, [isPlaying, renderMode]);
+  }, [renderMode, viewportId]);
+
+  /** Which camera movement a drag with `button` performs, if any. */
+  const cameraDragFor = (button: number, altKey: boolean): CameraDrag | null => {
+    if (button === 1) return TOOLS.PAN;
+    if (button === 2 || (button === 0 && altKey)) return TOOLS.ORBIT;
+    if (button === 0 && activeTool !== TOOLS.SELECT) return activeTool;
+    return null;
+  };
+
+  const handlePointerDown = (e: React.PointerEvent<HTMLCanvasElement>) => {
+    const mode = cameraDragFor(e.button, e.altKey);
+    if (!mode) return;
+    e.preventDefault();
+    e.currentTarget.setPointerCapture(e.pointerId);
+    cameraDragRef.current = { pointerId: e.pointerId, mode, x: e.clientX, y: e.clientY };
+  };
+
+  const handlePointerMove = (e: React.PointerEvent<HTMLCanvasElement>) => {
+    const drag = cameraDragRef.current;
+    if (!drag || drag.pointerId !== e.pointerId) return;
+
+    const dx = e.clientX - drag.x;
+    const dy = e.clientY - drag.y;
+    drag.x = e.clientX;
+    drag.y = e.clientY;
+
+    const input = cameraInputRef.current;
+    switch (drag.mode) {
+      case TOOLS.ORBIT:
+        input.yaw -= dx * ORBIT_SPEED;
+        input.pitch += dy * ORBIT_SPEED;
+        break;
+      case TOOLS.PAN: {
+        // Drag the grid along with the pointer
+        const height = e.currentTarget.clientHeight || 1;
+        input.panX -= dx / height;
+        input.panY += dy / height;
+        break;
+      }
+      case TOOLS.ZOOM:
+        input.zoom *= Math.exp(dy * ZOOM_DRAG_SPEED);
+        break;
+    }
+  };
+
+  const handlePointerUp = (e: React.PointerEvent<HTMLCanvasElement>) => {
+    if (cameraDragRef.current?.pointerId !== e.pointerId) return;
+    cameraDragRef.current = null;
+    e.currentTarget.releasePointerCapture(e.pointerId);
+  };
+
+  const handleWheel = (e: React.WheelEvent<HTMLCanvasElement>) => {
+    cameraInputRef.current.zoom *= Math.exp(e.deltaY * ZOOM_WHEEL_SPEED);
+  };
 
   // WebGPU renderer
   useEffect(() => {
@@ -141,6 +226,12 @@ const Viewport = () => {
         ref={nativeCanvasRef}
         className="w-full h-full"
         style={{ display: renderMode === RENDER_MODES.NATIVE ? 'block' : 'none' }}
+        onPointerDown={handlePointerDown}
+        onPointerMove={handlePointerMove}
+        onPointerUp={handlePointerUp}
+        onPointerCancel={handlePointerUp}
+        onWheel={handleWheel}
+        onContextMenu={(e) => e.preventDefault()}
       />
       {renderMode === RENDER_MODES.WEB && (
         <canvas 
@@ -238,13 +329,45 @@ const Viewport = () => {
 
         <button
           className="p-2 rounded hover:bg-blue-900/10 hover:text-blue-500 text-gray-400"
-          onClick={() => invoke('reset_viewport_camera')}
+          onClick={() => resetViewportCamera(viewportId)}
           title="Reset View (F)"
         >
           <RotateCcw size={20} />
         </button>
       </div>
 
+      {renderMode === RENDER_MODES.NATIVE && nativeViewport.status !== 'running' && (
+        <div className="absolute inset-0 flex items-center justify-center pointer-events-none">
+          <div className="pointer-events-auto max-w-sm bg-black/80 text-sm text-gray-300 p-4 rounded-lg border border-blue-900/20">
+            {nativeViewport.status === 'failed' ? (
+              <>
+                <div className="flex items-center gap-2 text-red-400 font-medium mb-2">
+                  <AlertTriangle size={16} />
+                  The viewport stopped rendering
+                </div>
+                {nativeViewport.error && (
+                  <div className="text-xs text-gray-400 mb-3">{nativeViewport.error.message}</div>
+                )}
+                <button
+                  className="flex items-center gap-2 px-3 py-1 rounded bg-blue-900/20 hover:bg-blue-900/40 text-blue-400"
+                  onClick={nativeViewport.reinitialize}
+                >
+                  <RefreshCw size={14} />
+                  Reinitialize
+                </button>
+              </>
+            ) : (
+              <div className="flex items-center gap-2">
+                <RefreshCw size={16} className="animate-spin" />
+                {nativeViewport.status === 'recovering'
+                  ? 'Graphics device lost, recovering...'
+                  : 'Starting viewport...'}
+              </div>
+            )}
+          </div>
+        </div>
+      )}
+
       {showDebug && (
         <div className="absolute top-3 right-3 bg-black/80 text-xs text-gray-300 p-3 rounded-lg border border-red-500/20">
           <h3 className="text-red-400 font-medium mb-2">Debug Info</h3>
@@ -256,6 +379,7 @@ const Viewport = () => {
               `${webCanvasRef.current?.width || 0} x ${webCanvasRef.current?.height || 0}`
             }</div>
             <div>WebCanvas Key: {webCanvasKey}</div>
+            <div>Native Viewport: {nativeViewport.status}</div>
           </div>
         </div>
       )}
@@ -1,8 +1,14 @@
-import React, { useState, useEffect, useCallback, useRef } from 'react';
+import React, { useState, useEffect, useCallback, useMemo, useRef } from 'react';
+import { open, save }        from '@tauri-apps/api/dialog';
+import { invoke }            from '@tauri-apps/api/tauri';
 
 import {DockablePanel}       from '@/components/DockablePanel/index';
-import {initialSceneObjects} from '@/components/types';
 import useCanvas             from '@/hooks/useCanvas';
+import {
+  useScene,
+  type Entity,
+  type EntityId,
+}                            from '@/hooks/tauri/scene';
 import PropertiesPanel       from './PropertiesPanel';
 import SceneHierarchy        from './SceneHierarchy';
 import Viewport              from './Viewport';
@@ -29,7 +35,7 @@ interface PanelPositions {
 }
 
 interface SceneObject {
-  id:            string;
+  id:            EntityId;
   name:          string;
   type:          string;
   position:      { x: number; y: number; z: number };
@@ -44,6 +50,30 @@ interface ConsoleMessage {
   timestamp: string;
 }
 
+const SCENE_FILTERS = [{ name: 'Scene', extensions: ['scene', 'json'] }];
+
+// Flattens a native scene entity into the shape the editor panels display.
+const toSceneObject = (entity: Entity): SceneObject => {
+  const component = entity.components[0];
+  let type = 'Empty';
+  if (component?.type === 'camera') type = 'Camera';
+  else if (component?.type === 'light') {
+    type = `${component.kind[0].toUpperCase()}${component.kind.slice(1)} Light`;
+  } else if (component?.type === 'mesh') {
+    type = `${component.mesh[0]?.toUpperCase() ?? ''}${component.mesh.slice(1)}`;
+  }
+
+  return {
+    id:       entity.id,
+    name:     entity.name,
+    type,
+    visible:  entity.visible,
+    position: entity.transform.position,
+    rotation: entity.transform.rotation,
+    scale:    entity.transform.scale,
+  };
+};
+
 const LevelEditor: React.FC = () => {
   // Refs
   const containerRef = useRef<HTMLDivElement>(null);
@@ -51,8 +81,7 @@ const LevelEditor: React.FC = () => {
   
   // Core state management
   const [consoleMessages, setConsoleMessages] = useState<ConsoleMessage[]>([]);
-  const [selectedObject, setSelectedObject] =   useState<SceneObject | null>(null);
-  const [sceneObjects, setSceneObjects] =       useState<SceneObject[]>(initialSceneObjects);
+  const [selectedId, setSelectedId] =           useState<EntityId | null>(null);
   const [isMaximized, setIsMaximized] =         useState<boolean>(false);
   const [activeTool, setActiveTool] =           useState<string>('select');
   const [activeMenu, setActiveMenu] =           useState<string | null>(null);
@@ -75,6 +104,11 @@ const LevelEditor: React.FC = () => {
     viewport:   { x: 250, y: 40 }
   });
   
+  // The scene itself lives in the native engine
+  const { entities } = useScene();
+  const sceneObjects = useMemo(() => entities.map(toSceneObject), [entities]);
+  const selectedObject = sceneObjects.find(obj => obj.id === selectedId) ?? null;
+
   const { lastFrameTimeRef, renderScene } = useCanvas(sceneObjects, selectedObject);
   const animationFrameRef = useRef<number | null>(null);
 
@@ -91,40 +125,40 @@ const LevelEditor: React.FC = () => {
     setActiveMenu(prev => prev === menuName ? null : menuName);
   }, []);
 
-  const handleMenuAction = useCallback((action: string) => {
-    switch (action) {
-      case 'new':
-        setSceneObjects(initialSceneObjects);
-        setSelectedObject(null);
-        logMessage('info', 'New project created');
-        break;
-      case 'save':
-        try {
-          localStorage.setItem('sceneObjects', JSON.stringify(sceneObjects));
-          logMessage('success', 'Project saved successfully');
-        } catch (error) {
-          logMessage('error', 'Failed to save project');
-        }
-        break;
-      case 'load':
-        try {
-          const savedScene = localStorage.getItem('sceneObjects');
-          if (savedScene) {
-            setSceneObjects(JSON.parse(savedScene));
-            logMessage('success', 'Project loaded successfully');
-          }
-        } catch (error) {
-          logMessage('error', 'Failed to load project');
+  const handleMenuAction = useCallback(async (action: string) => {
+    setActiveMenu(null);
+    try {
+      switch (action) {
+        case 'new':
+          await invoke('new_scene');
+          setSelectedId(null);
+          logMessage('info', 'New scene created');
+          break;
+        case 'save': {
+          const path = await save({ filters: SCENE_FILTERS });
+          if (!path) return;
+          await invoke('save_scene', { path });
+          logMessage('success', `Scene saved to ${path}`);
+          break;
         }
-        break;
-      default:
-        if (action.startsWith('toggle')) {
-          const panelName = action.replace('toggle', '').toLowerCase();
-          togglePanel(panelName as keyof PanelVisibility);
+        case 'load': {
+          const path = await open({ filters: SCENE_FILTERS, multiple: false });
+          if (typeof path !== 'string') return;
+          await invoke('load_scene', { path });
+          setSelectedId(null);
+          logMessage('success', `Scene loaded from ${path}`);
+          break;
         }
+        default:
+          if (action.startsWith('toggle')) {
+            const panelName = action.replace('toggle', '').toLowerCase();
+            togglePanel(panelName as keyof PanelVisibility);
+          }
+      }
+    } catch (error: any) {
+      logMessage('error', `Scene ${action} failed: ${error?.message ?? error}`);
     }
-    setActiveMenu(null);
-  }, [sceneObjects, logMessage]);
+  }, [logMessage]);
 
   // Tool handlers
   const handleToolChange = useCallback((tool: string) => {
@@ -139,21 +173,20 @@ const LevelEditor: React.FC = () => {
     axis: string,
     value: string
   ) => {
-    if (!obj?.id) return;
+    const entity = entities.find(e => e.id === obj?.id);
+    if (!entity) return;
 
     const numValue = parseFloat(value);
     if (isNaN(numValue)) return;
 
-    setSceneObjects(prev => prev.map(o => {
-      if (o.id === obj.id) {
-        return {
-          ...o,
-          [property]: { ...(o as any)[property], [axis]: numValue }
-        };
-      }
-      return o;
-    }));
-  }, []);
+    const transform = {
+      ...entity.transform,
+      [property]: { ...(entity.transform as any)[property], [axis]: numValue }
+    };
+    invoke('set_entity_transform', { id: entity.id, transform }).catch((error: any) => {
+      logMessage('error', `Failed to update ${entity.name}: ${error?.message ?? error}`);
+    });
+  }, [entities, logMessage]);
 
   // Panel handlers
   const handlePanelMove = useCallback((panelId: string, position: PanelPosition) => {
@@ -253,7 +286,7 @@ const LevelEditor: React.FC = () => {
             <SceneHierarchy
               sceneObjects={sceneObjects}
               selectedObject={selectedObject}
-              onSelectObject={setSelectedObject}
+              onSelectObject={(obj: SceneObject) => setSelectedId(obj.id)}
             />
           </DockablePanel>
 
@@ -1,5 +1,34 @@
 import { useState, useCallback, useRef } from 'react';
 import { isImageFile, is3DFile, getFileLanguage } from '@/utils/fileUtils';
+import { useAssetChanges } from '@/hooks/tauri/assets';
+import {
+  readProjectFile,
+  readProjectFileBinary,
+  writeProjectFile,
+} from '@/hooks/tauri/project';
+
+/**
+ * Media type of an image file, from its extension
+ * @param {string} name - File name
+ */
+const imageMediaType = (name) => {
+  const extension = name.split('.').pop().toLowerCase();
+  if (extension === 'jpg') return 'image/jpeg';
+  if (extension === 'svg') return 'image/svg+xml';
+  return `image/${extension}`;
+};
+
+/**
+ * Reads a file's content for a tab, images as data URLs
+ * @param {Object} file - File with `path` and `name`
+ */
+const readTabContent = async (file) => {
+  if (isImageFile(file.name)) {
+    const data = await readProjectFileBinary(file.path);
+    return `data:${imageMediaType(file.name)};base64,${data}`;
+  }
+  return readProjectFile(file.path);
+};
 
 /**
  * useEditor Hook
@@ -10,15 +39,20 @@ import { isImageFile, is3DFile, getFileLanguage } from '@/utils/fileUtils';
  * - Editor instance management
  * - Console output
  * 
- * @param {Map} fileHandles - Map of file handles from the file system
+ * Files are read and written through the native project commands, by
+ * path relative to the project root. Open assets changed on disk are
+ * reloaded unless they have unsaved changes.
+ * 
  * @returns {Object} Editor state and handlers
  */
-export const useEditor = (fileHandles) => {
+export const useEditor = () => {
   // Editor state
   const [openTabs, setOpenTabs] = useState([]);
   const [activeTab, setActiveTab] = useState(null);
   const [consoleOutput, setConsoleOutput] = useState([]);
   const editorRef = useRef(null);
+  const openTabsRef = useRef(openTabs);
+  openTabsRef.current = openTabs;
 
   /**
    * Handles opening a file in the editor
@@ -30,26 +64,7 @@ export const useEditor = (fileHandles) => {
     try {
       // Check if file is already open
       if (!openTabs.some(tab => tab.path === file.path)) {
-        // Validate file handle
-        if (!fileHandles || !fileHandles.get(file.path)) {
-          throw new Error('File handle not found');
-        }
-        
-        // Load file content
-        const handle = fileHandles.get(file.path);
-        const fileData = await handle.getFile();
-        let content;
-        
-        // Handle different file types
-        if (isImageFile(file.name)) {
-          content = await new Promise((resolve) => {
-            const reader = new FileReader();
-            reader.onloadend = () => resolve(reader.result);
-            reader.readAsDataURL(fileData);
-          });
-        } else {
-          content = await fileData.text();
-        }
+        const content = await readTabContent(file);
         
         // Create new tab with file info
         const newTab = {
@@ -58,8 +73,7 @@ export const useEditor = (fileHandles) => {
           language: getFileLanguage(file.name),
           fileType: isImageFile(file.name) ? 'image' : 
                    is3DFile(file.name) ? '3d' : 'text',
-          mediaType: isImageFile(file.name) ? 
-                    `image/${file.name.split('.').pop()}` : null,
+          mediaType: isImageFile(file.name) ? imageMediaType(file.name) : null,
           isDirty: false,
           originalContent: content
         };
@@ -71,11 +85,11 @@ export const useEditor = (fileHandles) => {
       console.error('Error opening file:', error);
       setConsoleOutput(prev => [...prev, {
         type: 'error',
-        message: `Error opening file: ${error.message}`,
+        message: `Error opening file: ${error.message ?? error}`,
         timestamp: new Date().toISOString()
       }]);
     }
-  }, [openTabs, fileHandles]);
+  }, [openTabs]);
 
   /**
    * Handles closing a tab
@@ -117,13 +131,8 @@ export const useEditor = (fileHandles) => {
     if (!fileToSave || !editorRef.current) return;
 
     try {
-      const handle = fileHandles.get(fileToSave.path);
-      if (!handle) throw new Error('File handle not found');
-      
-      const writable = await handle.createWritable();
       const content = editorRef.current.getValue();
-      await writable.write(content);
-      await writable.close();
+      await writeProjectFile(fileToSave.path, content);
 
       setOpenTabs(prev =>
         prev.map(tab =>
@@ -142,11 +151,43 @@ export const useEditor = (fileHandles) => {
       console.error('Error saving file:', error);
       setConsoleOutput(prev => [...prev, {
         type: 'error',
-        message: `Error saving file: ${error.message}`,
+        message: `Error saving file: ${error.message ?? error}`,
         timestamp: new Date().toISOString()
       }]);
     }
-  }, [activeTab, openTabs, fileHandles]);
+  }, [activeTab, openTabs]);
+
+  /**
+   * Reloads open tabs whose asset was modified outside the editor
+   */
+  const handleAssetChange = useCallback(async ({ path, change }) => {
+    const changedPath = path.replace(/\\/g, '/');
+    const tab = openTabsRef.current.find(t => t.path === changedPath);
+    if (!tab || change !== 'modified') return;
+
+    if (tab.isDirty) {
+      setConsoleOutput(prev => [...prev, {
+        type: 'info',
+        message: `${tab.name} changed on disk; saving will overwrite it`,
+        timestamp: new Date().toISOString()
+      }]);
+      return;
+    }
+
+    try {
+      const content = await readTabContent(tab);
+      setOpenTabs(prev =>
+        prev.map(t =>
+          t.path === changedPath && !t.isDirty
+            ? { ...t, content, originalContent: content }
+            : t
+        )
+      );
+    } catch (error) {
+      console.error('Error reloading file:', error);
+    }
+  }, []);
+  useAssetChanges(handleAssetChange);
 
   /**
    * Handles tab selection
@@ -188,6 +229,7 @@ export const useEditor = (fileHandles) => {
  *    - Saving files
  *    - Closing tabs
  *    - Different file type support
+ *    - Reloading assets changed on disk
  * 
  * 3. Error Handling:
  *    - File operation errors
@@ -196,7 +238,6 @@ export const useEditor = (fileHandles) => {
  * 
  * 4. Future Improvements:
  *    - Add auto-save
- *    - Add file change watching
  *    - Add undo/redo stack
  *    - Add tab reordering
  *    - Add split views
@@ -204,5 +245,5 @@ export const useEditor = (fileHandles) => {
  * 5. Performance:
  *    - Memoized callbacks
  *    - Efficient state updates
- *    - File handle caching
+ *    - Native file commands
  */
@@ -1,73 +1,131 @@
-import { useState, useCallback } from 'react';
+import { useState, useCallback, useEffect, useRef } from 'react';
+import { open } from '@tauri-apps/api/dialog';
+import { openProject, useAssetChanges } from '@/hooks/tauri/assets';
+import {
+  listProjectDir,
+  createProjectFile,
+  createProjectDir,
+  renameProjectEntry,
+  moveProjectEntry,
+  deleteProjectEntry,
+} from '@/hooks/tauri/project';
 
 /**
  * useFileSystem Hook
- * 
- * Manages file system operations using the File System Access API.
- * Handles file/folder operations and maintains file tree structure.
- * 
+ *
+ * Manages file system operations through the native project commands.
+ * Paths are relative to the project root, which the backend confines
+ * every operation to. Folders are listed lazily when first opened, and the
+ * tree is refreshed when assets are created or removed outside the editor.
+ *
  * @returns {Object} File system state and operations
  */
 export const useFileSystem = () => {
   // State management
   const [files, setFiles] = useState([]); // File tree structure
-  const [fileHandles, setFileHandles] = useState(new Map()); // File handle cache
   const [currentProjectPath, setCurrentProjectPath] = useState('');
-  const [rootDirectoryHandle, setRootDirectoryHandle] = useState(null);
+  const [isProjectOpen, setIsProjectOpen] = useState(false);
+  const filesRef = useRef(files);
+  filesRef.current = files;
 
   /**
-   * Toggles folder open/closed state
-   * @param {string} path - Path of folder to toggle
+   * Lists a directory as tree items, reloading the children of folders
+   * that are open so a refresh keeps the tree expanded
+   * @param {string} path - Directory path, '' for the root
+   * @param {Set<string>} openPaths - Paths of open folders
+   * @returns {Promise<Array>} Directory structure
    */
-  const toggleFolder = useCallback((path) => {
-    setFiles(prevFiles => {
-      const updateFiles = (items) => {
-        return items.map(item => {
-          if (item.path === path && item.type === 'directory') {
-            return { ...item, open: !item.open };
-          }
-          if (item.children) {
-            return { ...item, children: updateFiles(item.children) };
-          }
-          return item;
-        });
+  const loadDirectory = async (path, openPaths = new Set()) => {
+    const entries = await listProjectDir(path);
+    return Promise.all(entries.map(async (entry) => {
+      const item = {
+        name: entry.name,
+        path: entry.path,
+        type: entry.kind,
+      };
+      if (entry.kind !== 'directory') return item;
+
+      const isOpen = openPaths.has(entry.path);
+      return {
+        ...item,
+        hasChildren: entry.hasChildren,
+        children: isOpen ? await loadDirectory(entry.path, openPaths) : null,
+        open: isOpen,
       };
-      return updateFiles(prevFiles);
+    }));
+  };
+
+  /**
+   * Toggles folder open/closed state, listing it the first time it opens
+   * @param {string} path - Path of folder to toggle
+   */
+  const toggleFolder = useCallback(async (path) => {
+    const updateFiles = (items, children) => items.map(item => {
+      if (item.path === path && item.type === 'directory') {
+        return { ...item, open: !item.open, children: item.children || children };
+      }
+      if (item.children) {
+        return { ...item, children: updateFiles(item.children, children) };
+      }
+      return item;
     });
+
+    const findItem = (items) => {
+      for (const item of items) {
+        if (item.path === path) return item;
+        const found = item.children && findItem(item.children);
+        if (found) return found;
+      }
+      return null;
+    };
+
+    const folder = findItem(filesRef.current);
+    let children = null;
+    if (folder && !folder.open && !folder.children) {
+      try {
+        children = await loadDirectory(path);
+      } catch (error) {
+        console.error('Error listing folder:', error);
+        return;
+      }
+    }
+    setFiles(prevFiles => updateFiles(prevFiles, children));
   }, []);
 
   /**
-   * Loads a project folder and builds file tree
+   * Picks a project folder, opens it and lists its root
    * @returns {Promise<Object>} Project structure and path
    */
   const loadProjectFolder = async () => {
     try {
-      const dirHandle = await window.showDirectoryPicker();
-      setRootDirectoryHandle(dirHandle);
-      
-      // Initialize file handles
-      const newFileHandles = new Map();
-      newFileHandles.set(dirHandle.name, dirHandle);
-      
-      // Process directory structure
-      const projectStructure = await processDirectory(dirHandle, '', newFileHandles);
-      
-      // Update state
-      setFileHandles(newFileHandles);
-      setFiles(projectStructure);
-      setCurrentProjectPath(dirHandle.name);
-      
-      return { structure: projectStructure, projectPath: dirHandle.name };
-    } catch (error) {
-      if (error.name === 'AbortError') {
+      const root = await open({ directory: true, multiple: false });
+      if (!root || Array.isArray(root)) {
         console.log('Folder selection was cancelled');
         return null;
       }
+
+      await openProject(root);
+      const projectStructure = await loadDirectory('');
+
+      // Update state
+      setFiles(projectStructure);
+      setCurrentProjectPath(root);
+      setIsProjectOpen(true);
+
+      return { structure: projectStructure, projectPath: root };
+    } catch (error) {
       console.error('Error loading project:', error);
       return null;
     }
   };
 
+  /**
+   * Joins a parent directory path and a name
+   * @param {string} parentPath - Path of parent directory, '' for the root
+   * @param {string} name - Entry name
+   */
+  const joinPath = (parentPath, name) => (parentPath ? `${parentPath}/${name}` : name);
+
   /**
    * Creates a new file in the specified directory
    * @param {string} filename - Name of new file
@@ -75,23 +133,7 @@ export const useFileSystem = () => {
    */
   const createFile = async (filename, parentPath) => {
     try {
-      let parentHandle;
-      if (parentPath) {
-        parentHandle = fileHandles.get(parentPath);
-      } else {
-        parentHandle = rootDirectoryHandle;
-      }
-
-      if (!parentHandle) {
-        throw new Error('Parent directory not found');
-      }
-
-      // Create and initialize file
-      const fileHandle = await parentHandle.getFileHandle(filename, { create: true });
-      const writable = await fileHandle.createWritable();
-      await writable.write('');
-      await writable.close();
-
+      await createProjectFile(joinPath(parentPath, filename));
       await refreshFiles();
       return true;
     } catch (error) {
@@ -101,31 +143,12 @@ export const useFileSystem = () => {
   };
 
   /**
-   * Deletes a file or directory
+   * Moves a file or directory to the system trash
    * @param {string} path - Path of item to delete
    */
   const deleteItem = async (path) => {
     try {
-      if (!rootDirectoryHandle) throw new Error('No root directory');
-      
-      const handle = fileHandles.get(path);
-      if (!handle) throw new Error('Item not found');
-  
-      // Get parent directory
-      const pathParts = path.split('/');
-      const itemName = pathParts.pop();
-      const parentPath = pathParts.join('/');
-      const parentHandle = parentPath ? fileHandles.get(parentPath) : rootDirectoryHandle;
-  
-      if (!parentHandle) throw new Error('Parent directory not found');
-  
-      // Delete item
-      if (handle.kind === 'directory') {
-        await parentHandle.removeEntry(itemName, { recursive: true });
-      } else {
-        await parentHandle.removeEntry(itemName);
-      }
-  
+      await deleteProjectEntry(path);
       await refreshFiles();
       return true;
     } catch (error) {
@@ -134,27 +157,6 @@ export const useFileSystem = () => {
     }
   };
 
-  /**
-   * Copies directory contents recursively
-   * @param {FileSystemDirectoryHandle} sourceDir - Source directory
-   * @param {FileSystemDirectoryHandle} targetDir - Target directory
-   */
-  const copyDirectoryContents = async (sourceDir, targetDir) => {
-    for await (const entry of sourceDir.values()) {
-      if (entry.kind === 'directory') {
-        const newDir = await targetDir.getDirectoryHandle(entry.name, { create: true });
-        const sourceSubDir = await sourceDir.getDirectoryHandle(entry.name);
-        await copyDirectoryContents(sourceSubDir, newDir);
-      } else {
-        const file = await entry.getFile();
-        const newFileHandle = await targetDir.getFileHandle(entry.name, { create: true });
-        const writable = await newFileHandle.createWritable();
-        await writable.write(await file.arrayBuffer());
-        await writable.close();
-      }
-    }
-  };
-
   /**
    * Renames a file or directory
    * @param {string} oldPath - Current path
@@ -162,37 +164,7 @@ export const useFileSystem = () => {
    */
   const renameItem = async (oldPath, newName) => {
     try {
-      if (!rootDirectoryHandle) throw new Error('No root directory');
-      
-      const handle = fileHandles.get(oldPath);
-      if (!handle) throw new Error('Item not found');
-
-      // Get parent directory
-      const pathParts = oldPath.split('/');
-      const oldName = pathParts.pop();
-      const parentPath = pathParts.join('/');
-      const parentHandle = parentPath ? fileHandles.get(parentPath) : rootDirectoryHandle;
-
-      if (!parentHandle) throw new Error('Parent directory not found');
-
-      // Rename operation
-      try {
-        if (handle.kind === 'directory') {
-          const newDirHandle = await parentHandle.getDirectoryHandle(newName, { create: true });
-          await copyDirectoryContents(handle, newDirHandle);
-          await parentHandle.removeEntry(oldName, { recursive: true });
-        } else {
-          const file = await handle.getFile();
-          const newFileHandle = await parentHandle.getFileHandle(newName, { create: true });
-          const writable = await newFileHandle.createWritable();
-          await writable.write(await file.arrayBuffer());
-          await writable.close();
-          await parentHandle.removeEntry(oldName);
-        }
-      } catch (error) {
-        throw new Error(`Failed to rename: ${error.message}`);
-      }
-
+      await renameProjectEntry(oldPath, newName);
       await refreshFiles();
       return true;
     } catch (error) {
@@ -202,24 +174,38 @@ export const useFileSystem = () => {
   };
 
   /**
-   * Refreshes the file tree structure
+   * Moves a file or directory into another directory
+   * @param {string} path - Path of item to move
+   * @param {string} destination - Path of target directory, '' for the root
+   */
+  const moveItem = async (path, destination) => {
+    try {
+      await moveProjectEntry(path, destination);
+      await refreshFiles();
+      return true;
+    } catch (error) {
+      console.error('Error moving item:', error);
+      throw error;
+    }
+  };
+
+  /**
+   * Refreshes the file tree structure, keeping open folders open
    */
   const refreshFiles = async () => {
     try {
-      if (!rootDirectoryHandle) return;
-      
-      const newFileHandles = new Map();
-      newFileHandles.set(currentProjectPath, rootDirectoryHandle);
-      
-      const projectStructure = await processDirectory(
-        rootDirectoryHandle, 
-        '', 
-        newFileHandles
-      );
-      
-      setFileHandles(newFileHandles);
+      if (!isProjectOpen) return;
+
+      const openPaths = new Set();
+      const collectOpen = (items) => items.forEach(item => {
+        if (item.open) openPaths.add(item.path);
+        if (item.children) collectOpen(item.children);
+      });
+      collectOpen(filesRef.current);
+
+      const projectStructure = await loadDirectory('', openPaths);
       setFiles(projectStructure);
-      
+
       return { structure: projectStructure, projectPath: currentProjectPath };
     } catch (error) {
       console.error('Error refreshing files:', error);
@@ -227,6 +213,21 @@ export const useFileSystem = () => {
     }
   };
 
+  // Refresh once per burst of asset changes, e.g. a whole folder copied in
+  const refreshRef = useRef(refreshFiles);
+  refreshRef.current = refreshFiles;
+  const refreshTimerRef = useRef(null);
+
+  const handleAssetChange = useCallback(({ change }) => {
+    if (change === 'modified') return;
+    clearTimeout(refreshTimerRef.current);
+    refreshTimerRef.current = setTimeout(() => {
+      refreshRef.current().catch(() => {});
+    }, 100);
+  }, []);
+  useAssetChanges(handleAssetChange);
+  useEffect(() => () => clearTimeout(refreshTimerRef.current), []);
+
   /**
    * Creates a new folder
    * @param {string} folderName - Name of new folder
@@ -234,20 +235,8 @@ export const useFileSystem = () => {
    */
   const createFolder = async (folderName, parentPath) => {
     try {
-      let parentHandle;
-      if (parentPath) {
-        parentHandle = fileHandles.get(parentPath);
-      } else {
-        parentHandle = rootDirectoryHandle;
-      }
-
-      if (!parentHandle) {
-        throw new Error('Parent directory not found');
-      }
-
-      await parentHandle.getDirectoryHandle(folderName, { create: true });
+      await createProjectDir(joinPath(parentPath, folderName));
       await refreshFiles();
-      
       return true;
     } catch (error) {
       console.error('Error creating folder:', error);
@@ -255,54 +244,11 @@ export const useFileSystem = () => {
     }
   };
 
-  /**
-   * Processes directory structure recursively
-   * @param {FileSystemDirectoryHandle} handle - Directory handle
-   * @param {string} path - Current path
-   * @param {Map} handles - File handles map
-   * @returns {Array} Directory structure
-   */
-  const processDirectory = async (handle, path = '', handles = new Map()) => {
-    const entries = [];
-    for await (const entry of handle.values()) {
-      const entryPath = path ? `${path}/${entry.name}` : entry.name;
-      
-      if (entry.kind === 'directory') {
-        const dirHandle = await handle.getDirectoryHandle(entry.name);
-        handles.set(entryPath, dirHandle);
-        const children = await processDirectory(dirHandle, entryPath, handles);
-        entries.push({
-          name: entry.name,
-          path: entryPath,
-          type: 'directory',
-          children,
-          open: false,
-        });
-      } else {
-        const fileHandle = await handle.getFileHandle(entry.name);
-        handles.set(entryPath, fileHandle);
-        entries.push({
-          name: entry.name,
-          path: entryPath,
-          type: 'file',
-        });
-      }
-    }
-    
-    // Sort directories first, then alphabetically
-    return entries.sort((a, b) => {
-      if (a.type === 'directory' && b.type !== 'directory') return -1;
-      if (a.type !== 'directory' && b.type === 'directory') return 1;
-      return a.name.localeCompare(b.name);
-    });
-  };
-
   return {
     files,
     setFiles,
-    fileHandles,
+    isProjectOpen,
     currentProjectPath,
-    rootDirectoryHandle,
     loadProjectFolder,
     refreshFiles,
     toggleFolder,
@@ -310,91 +256,44 @@ export const useFileSystem = () => {
     deleteItem,
     createFolder,
     renameItem,
+    moveItem,
     getFiles: () => files,
   };
 };
 
-// In your main Quasar component:
-const ExampleUsage = () => {
-  const [files, setFiles] = useState([]);
-  const { loadProjectFolder, createFile, currentProjectPath } = useFileSystem();
-  const [showNewFileDialog, setShowNewFileDialog] = useState(false);
-  const [selectedPath, setSelectedPath] = useState(null);
-
-  const handleLoadProject = async () => {
-    try {
-      const { structure } = await loadProjectFolder();
-      setFiles(structure);
-    } catch (error) {
-      console.error('Failed to load project:', error);
-    }
-  };
-
-  const handleCreateFile = async (filename, path) => {
-    try {
-      await createFile(filename, path);
-      const { structure } = await loadProjectFolder();
-      setFiles(structure);
-    } catch (error) {
-      console.error('Failed to create file:', error);
-    }
-  };
-
-  return (
-    <>
-      <button onClick={handleLoadProject}>Open Folder</button>
-      
-      <NewFileDialog
-        isOpen={showNewFileDialog}
-        onClose={() => setShowNewFileDialog(false)}
-        onCreate={handleCreateFile}
-        selectedPath={selectedPath}
-        currentProjectPath={currentProjectPath}
-      />
-    </>
-  );
-};
-
 /**
  * Hook Maintenance Notes:
- * 
+ *
  * 1. File System Features:
  *    - Project folder loading
+ *    - Lazy folder listing
  *    - File/folder creation
- *    - File/folder deletion
- *    - File/folder renaming
- *    - Directory processing
- *    - File handle management
- * 
+ *    - File/folder deletion (to the system trash)
+ *    - File/folder renaming and moving
+ *
  * 2. State Management:
  *    - File tree structure
- *    - File handles cache
  *    - Project path tracking
- *    - Root directory reference
- * 
+ *    - Open folders kept across refreshes
+ *    - Refreshed on asset-changed events
+ *
  * 3. Error Handling:
- *    - Operation validation
+ *    - Backend errors are `{ code, message }` objects
  *    - Error propagation
  *    - User feedback
- * 
+ *
  * 4. Performance:
- *    - File handle caching
- *    - Optimized directory processing
+ *    - Folders listed only when opened
  *    - Efficient state updates
- * 
+ *
  * 5. Future Improvements:
- *    - Add file watching
- *    - Add batch operations
+ *    - Add drag and drop moves
  *    - Add undo/redo
  *    - Add file filters
  *    - Add search functionality
- *    - Add file metadata
- * 
+ *
  * 6. Dependencies:
- *    - File System Access API
+ *    - Native project commands (@/hooks/tauri/project)
+ *    - Tauri dialog API
  *    - React state management
- * 
- * 7. Browser Support:
- *    - Requires modern browsers
- *    - Requires HTTPS or localhost
- */
\ No newline at end of file
+ */
@@ -46,7 +46,7 @@ const Quasar = () => {
    */
   const {
     files,                // File tree structure
-    fileHandles,          // File system handles
+    isProjectOpen,        // Whether a project folder is open
     loadProjectFolder,    // Load project directory
     refreshFiles,         // Refresh file tree
     createFile,          // Create new file
@@ -70,7 +70,7 @@ const Quasar = () => {
     handleTabClose,     // Close editor tab
     handleTabClick,     // Switch active tab
     handleFileSave,     // Save file changes
-  } = useEditor(fileHandles);
+  } = useEditor();
 
   /**
    * UI State
@@ -138,7 +138,7 @@ const Quasar = () => {
    * Falls back to load project if no folder is open
    */
   const handleRefresh = async () => {
-    if (!fileHandles || fileHandles.size === 0) {
+    if (!isProjectOpen) {
       await handleLoadProject();
       return;
     }
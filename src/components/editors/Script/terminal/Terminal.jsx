@@ -1,11 +1,14 @@
-import React from 'react';
+import React, { useState } from 'react';
 import { X, AlertCircle, Check, Terminal as TerminalIcon } from 'lucide-react';
+import TerminalPanel from '@/components/panels/Terminal';
 
 /**
  * Terminal Component
  * 
- * Displays console output with timestamps and type-based styling.
- * Supports error, success, and info messages.
+ * Hosts the shell sessions of the editor and its console output, with
+ * timestamps and type-based styling for error, success, and info messages.
+ * The panel is hidden rather than unmounted when closed, so the shells
+ * keep running.
  * 
  * @component
  * @param {Object} props - Component props
@@ -26,13 +29,26 @@ import { X, AlertCircle, Check, Terminal as TerminalIcon } from 'lucide-react';
  * />
  */
 const Terminal = ({ isVisible, onClose, output }) => {
-  if (!isVisible) return null;
+  const [view, setView] = useState('terminal');
+  // Start the first shell once the panel is first shown
+  const [wasShown, setWasShown] = useState(isVisible);
+  if (isVisible && !wasShown) setWasShown(true);
 
   return (
-    <div className="h-48 border-t border-gray-800 bg-black">
+    <div className={`h-48 border-t border-gray-800 bg-black ${isVisible ? '' : 'hidden'}`}>
       {/* Terminal Header */}
       <div className="flex items-center justify-between p-2 text-sm font-medium border-b border-gray-800">
-        <span>TERMINAL</span>
+        <div className="flex gap-4">
+          {['terminal', 'output'].map(name => (
+            <button
+              key={name}
+              onClick={() => setView(name)}
+              className={view === name ? 'text-gray-200' : 'text-gray-500 hover:text-gray-300'}
+            >
+              {name.toUpperCase()}
+            </button>
+          ))}
+        </div>
         <button
           onClick={onClose}
           className="p-1 hover:bg-gray-900 rounded"
@@ -42,8 +58,17 @@ const Terminal = ({ isVisible, onClose, output }) => {
         </button>
       </div>
 
-      {/* Terminal Output */}
-      <div className="h-[calc(100%-2.5rem)] overflow-auto p-2 font-mono">
+      {/* Shell Sessions */}
+      <div className={`h-[calc(100%-2.5rem)] ${view === 'terminal' ? '' : 'hidden'}`}>
+        {wasShown && <TerminalPanel />}
+      </div>
+
+      {/* Console Output */}
+      <div
+        className={`h-[calc(100%-2.5rem)] overflow-auto p-2 font-mono ${
+          view === 'output' ? '' : 'hidden'
+        }`}
+      >
         {output.map((output, index) => (
           <div
             key={index}
@@ -78,6 +103,7 @@ export default Terminal;
  * Component Maintenance Notes:
  * 
  * 1. Features:
+ *    - Shell sessions (PTY backed, one tab each)
  *    - Message types (error, success, info)
  *    - Timestamps
  *    - Type-based icons
@@ -105,10 +131,10 @@ export default Terminal;
  *    - Add filter options
  *    - Add search functionality
  *    - Add copy functionality
- *    - Add command input
  *    - Add message grouping
  *    - Add message expansion
  * 
  * 6. Dependencies:
  *    - lucide-react icons
+ *    - Terminal panel (@/components/panels/Terminal)
  */
@@ -1,25 +1,118 @@
-import React from 'react';
-
-const Console = ({ isVisible, messages, onClear, onHide }) => {
-  if (!isVisible) return null;
-
-  return (
-    <div className="flex-1 overflow-y-auto p-2 space-y-1">
-      {messages.map((msg, i) => (
-        <div key={i} className="flex items-center gap-2 hover:bg-blue-900/5 p-1 rounded">
-          <span className={`text-xs ${msg.type === 'info' ? 'text-blue-500' :
-              msg.type === 'success' ? 'text-green-500' :
-                msg.type === 'warning' ? 'text-yellow-500' :
-                  'text-red-500'
-            }`}>●</span>
-          <span className="text-gray-300">{msg.message}</span>
-          <span className="text-gray-500 text-xs ml-auto">
-            {new Date(msg.timestamp).toLocaleTimeString()}
-          </span>
-        </div>
-      ))}
-    </div>
-  );
-};
-
-export default Console;
\ No newline at end of file
+import React, { useEffect, useState } from 'react';
+import { getLogFilters, setLogFilter, useLogs } from '@/hooks/tauri/logs';
+
+const LEVELS = ['error', 'warn', 'info', 'debug', 'trace'];
+
+const LEVEL_COLORS = {
+  error: 'text-red-500',
+  warn: 'text-yellow-500',
+  info: 'text-blue-500',
+  debug: 'text-gray-500',
+  trace: 'text-gray-600',
+};
+
+/**
+ * Console panel showing the backend log alongside editor `messages`.
+ * The level selector sets what the backend logs; the filter narrows the
+ * shown records by module or text.
+ */
+const Console = ({ isVisible, messages = [], onClear, onHide }) => {
+  const { records, clear } = useLogs();
+  const [level, setLevel] = useState('info');
+  const [filter, setFilter] = useState('');
+
+  useEffect(() => {
+    getLogFilters()
+      .then(filters => setLevel(filters.default))
+      .catch(err => console.error('Failed to get log filters', err));
+  }, []);
+
+  if (!isVisible) return null;
+
+  const handleLevelChange = async (e) => {
+    const filters = await setLogFilter(e.target.value);
+    setLevel(filters.default);
+  };
+
+  const handleClear = async () => {
+    await clear();
+    onClear?.();
+  };
+
+  // Editor messages use the success/info/warning/error types
+  const entries = [
+    ...records.map(record => ({
+      key: `log-${record.id}`,
+      level: record.level,
+      target: record.target,
+      location: record.file ? `${record.file}:${record.line ?? ''}` : '',
+      message: record.message,
+      timestamp: record.timestamp,
+    })),
+    ...messages.map((msg, i) => ({
+      key: `msg-${i}`,
+      level: msg.type === 'warning' ? 'warn' : msg.type === 'error' ? 'error' : 'info',
+      target: 'editor',
+      location: '',
+      message: msg.message,
+      timestamp: new Date(msg.timestamp).getTime(),
+    })),
+  ]
+    .filter(entry => {
+      const text = filter.toLowerCase();
+      return !text || entry.target.toLowerCase().includes(text) ||
+        entry.message.toLowerCase().includes(text);
+    })
+    .sort((a, b) => a.timestamp - b.timestamp);
+
+  return (
+    <div className="flex-1 flex flex-col min-h-0">
+      <div className="flex items-center gap-2 p-2 border-b border-gray-800 text-xs">
+        <select
+          value={level}
+          onChange={handleLevelChange}
+          className="bg-gray-900 border border-gray-800 rounded px-1 py-0.5"
+          title="Log level"
+        >
+          {['off', ...LEVELS].map(name => (
+            <option key={name} value={name}>{name}</option>
+          ))}
+        </select>
+        <input
+          value={filter}
+          onChange={e => setFilter(e.target.value)}
+          placeholder="Filter by module or text"
+          className="flex-1 bg-transparent border border-gray-800 rounded px-2 py-0.5 outline-none"
+          spellCheck="false"
+        />
+        <button onClick={handleClear} className="px-2 py-0.5 hover:bg-gray-900 rounded">
+          Clear
+        </button>
+        {onHide && (
+          <button onClick={onHide} className="px-2 py-0.5 hover:bg-gray-900 rounded">
+            Hide
+          </button>
+        )}
+      </div>
+
+      <div className="flex-1 overflow-y-auto p-2 space-y-1">
+        {entries.map(entry => (
+          <div
+            key={entry.key}
+            className="flex items-center gap-2 hover:bg-blue-900/5 p-1 rounded"
+            title={entry.location}
+          >
+            <span className={`text-xs ${LEVEL_COLORS[entry.level]}`}>●</span>
+            <span className="text-gray-500 text-xs shrink-0">{entry.target}</span>
+            <span className="text-gray-300 break-all">{entry.message}</span>
+            <span className="text-gray-500 text-xs ml-auto shrink-0">
+              {new Date(entry.timestamp).toLocaleTimeString()}
+            </span>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+};
+
+export default Console;
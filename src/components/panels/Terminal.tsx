@@ -1,67 +1,144 @@
-import { useState } from 'react';
-import { X } from 'lucide-react';
+import { useEffect, useRef, useState, type ClipboardEvent, type KeyboardEvent } from 'react';
+import { Plus, X } from 'lucide-react';
+import { keyToInput, useTerminal } from '@/hooks/tauri/terminal';
 
-interface OutputLine {
-  type: 'command' | 'error' | 'success';
-  message: string;
+interface TerminalViewProps {
+  /** Working directory, by default the open project. */
+  cwd?: string;
+  visible?: boolean;
 }
 
-const Terminal = () => {
-  const [input, setInput] = useState('');
-  const [output, setOutput] = useState<OutputLine[]>([]);
+/**
+ * One shell session, killed when the view unmounts. Focus it to type;
+ * the terminal is resized to fit the view.
+ */
+export const TerminalView = ({ cwd, visible = true }: TerminalViewProps) => {
+  const { lines, exitCode, write, resize } = useTerminal(cwd);
+  const screenRef = useRef<HTMLDivElement>(null);
+  const glyphRef = useRef<HTMLSpanElement>(null);
 
-  const runCommand = async (cmd: string) => {
-    if (!cmd.trim()) return;
-    
-    setOutput(prev => [...prev, { type: 'command', message: `$ ${cmd}` }]);
+  // Keep the latest output in view
+  useEffect(() => {
+    const screen = screenRef.current;
+    if (screen) screen.scrollTop = screen.scrollHeight;
+  }, [lines]);
 
-    try {
-      const res = await fetch(`/api/cmd?cmd=${encodeURIComponent(cmd)}`);
-      const data = await res.json();
-      
-      if (data.error) {
-        setOutput(prev => [...prev, { type: 'error', message: data.error }]);
-      } else {
-        setOutput(prev => [...prev, { type: 'success', message: data.output }]);
-      }
-    } catch (err) {
-      setOutput(prev => [...prev, { 
-        type: 'error', 
-        message: (err as Error).message 
-      }]);
-    }
+  // Fit the pty to the view in whole characters
+  useEffect(() => {
+    const screen = screenRef.current;
+    const glyph = glyphRef.current;
+    if (!screen || !glyph || !visible) return;
+
+    const fit = () => {
+      const { width, height } = glyph.getBoundingClientRect();
+      // Hidden views have no size, keep the last one
+      if (width === 0 || height === 0 || screen.clientWidth === 0) return;
+      const cols = Math.max(1, Math.floor(screen.clientWidth / width));
+      const rows = Math.max(1, Math.floor(screen.clientHeight / height));
+      resize(cols, rows);
+    };
+    const observer = new ResizeObserver(fit);
+    observer.observe(screen);
+    return () => observer.disconnect();
+  }, [resize, visible]);
 
-    setInput('');
+  const handleKeyDown = (e: KeyboardEvent) => {
+    // Leave copy to the browser while text is selected
+    if (e.ctrlKey && e.key === 'c' && window.getSelection()?.toString()) return;
+    const input = keyToInput(e);
+    if (input === null) return;
+    e.preventDefault();
+    write(input);
+  };
+
+  const handlePaste = (e: ClipboardEvent) => {
+    e.preventDefault();
+    write(e.clipboardData.getData('text'));
   };
 
   return (
-    <div className="h-48 bg-black border-t border-gray-800">
-      <div className="flex items-center justify-between p-2 bg-gray-900 border-b border-gray-800">
-        <span className="text-sm font-medium">Console</span>
-      </div>
-      <div className="p-2 h-32 overflow-auto font-mono text-sm">
-        {output.map((line, i) => (
-          <div key={i} className={
-            line.type === 'error' ? 'text-red-400' : 
-            line.type === 'command' ? 'text-blue-400' : 
-            'text-gray-300'
-          }>
-            {line.message}
+    <div
+      ref={screenRef}
+      tabIndex={0}
+      onKeyDown={handleKeyDown}
+      onPaste={handlePaste}
+      className={`relative h-full overflow-auto p-2 font-mono text-sm text-gray-300 outline-none
+        whitespace-pre-wrap break-all ${visible ? '' : 'hidden'}`}
+    >
+      <span ref={glyphRef} className="invisible absolute">M</span>
+      {lines.map((line, i) => (
+        <div key={i}>{line || ' '}</div>
+      ))}
+      {exitCode !== undefined && (
+        <div className="text-gray-500">
+          [Process exited{exitCode === null ? '' : ` with code ${exitCode}`}]
+        </div>
+      )}
+    </div>
+  );
+};
+
+interface TerminalProps {
+  cwd?: string;
+}
+
+/**
+ * Terminal panel with a tab per shell session.
+ */
+const Terminal = ({ cwd }: TerminalProps) => {
+  const nextId = useRef(1);
+  const [tabs, setTabs] = useState<number[]>([0]);
+  const [activeTab, setActiveTab] = useState(0);
+
+  const addTab = () => {
+    const id = nextId.current++;
+    setTabs(prev => [...prev, id]);
+    setActiveTab(id);
+  };
+
+  const closeTab = (id: number) => {
+    const remaining = tabs.filter(tab => tab !== id);
+    setTabs(remaining);
+    if (activeTab === id && remaining.length > 0) {
+      setActiveTab(remaining[remaining.length - 1]);
+    }
+  };
+
+  return (
+    <div className="h-full flex flex-col bg-black">
+      <div className="flex items-center gap-1 px-2 py-1 bg-gray-900 border-b border-gray-800 text-sm">
+        {tabs.map((id, index) => (
+          <div
+            key={id}
+            onClick={() => setActiveTab(id)}
+            className={`flex items-center gap-1 px-2 py-0.5 rounded cursor-pointer ${
+              activeTab === id ? 'bg-gray-800 text-gray-200' : 'text-gray-500 hover:bg-gray-950'
+            }`}
+          >
+            <span>Shell {index + 1}</span>
+            <button
+              onClick={e => {
+                e.stopPropagation();
+                closeTab(id);
+              }}
+              className="p-0.5 hover:bg-gray-700 rounded"
+              title="Kill Terminal"
+            >
+              <X size={12} />
+            </button>
           </div>
         ))}
+        <button onClick={addTab} className="p-1 hover:bg-gray-800 rounded" title="New Terminal">
+          <Plus size={14} />
+        </button>
       </div>
-      <div className="p-2 border-t border-gray-800">
-        <input
-          value={input}
-          onChange={e => setInput(e.target.value)}
-          onKeyDown={e => e.key === 'Enter' && runCommand(input)}
-          className="w-full bg-transparent border-none outline-none font-mono text-sm text-gray-300"
-          placeholder="Enter command..."
-          spellCheck="false"
-        />
+      <div className="flex-1 min-h-0">
+        {tabs.map(id => (
+          <TerminalView key={id} cwd={cwd} visible={activeTab === id} />
+        ))}
       </div>
     </div>
   );
 };
 
-export default Terminal;
\ No newline at end of file
+export default Terminal;
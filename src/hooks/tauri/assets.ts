@@ -0,0 +1,197 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { useEffect } from "react"
+
+import type { EntityId, Transform } from "./scene"
+
+export interface ModelNode {
+  name: string
+  parent?: number
+  children?: number[]
+  transform: Transform
+  mesh?: number
+  skin?: number
+}
+
+export interface Skin {
+  name: string
+  joints: number[]
+  inverseBindMatrices: number[][][]
+  skeleton?: number
+}
+
+/** An imported model; parts are file names inside the import directory. */
+export interface ModelAsset {
+  name: string
+  source: string
+  nodes: ModelNode[]
+  roots: number[]
+  meshes: string[]
+  materials: string[]
+  images: string[]
+  skins: Skin[]
+  animations: string[]
+}
+
+export interface ModelImport {
+  model: ModelAsset
+  directory: string
+  entity: EntityId | null
+}
+
+export interface ImportModelOptions {
+  /** Where to write the assets, by default next to the source. */
+  outputDir?: string
+  /** Add the model to the scene, true by default. */
+  instantiate?: boolean
+  parent?: EntityId
+}
+
+/** Imports a glTF or GLB file as engine meshes, materials and animations. */
+export const importModel = (source: string, options: ImportModelOptions = {}) =>
+  invoke<ModelImport>("import_model", { source, ...options })
+
+export type TextureKind = "color" | "linear" | "normalMap"
+export type TextureCompression = "none" | "bc1" | "bc3" | "bc5" | "bc7"
+export type TextureContainer = "native" | "ktx2"
+
+export interface TextureImportSettings {
+  kind?: TextureKind
+  /** Generate the full mip chain, true by default. */
+  mipmaps?: boolean
+  /** Ignored for HDR images, which stay half-float. */
+  compression?: TextureCompression
+  container?: TextureContainer
+  /** Invert the green channel of Direct3D normal maps. */
+  flipGreen?: boolean
+}
+
+export const ASSET_IMPORT_EVENT = "asset-import"
+
+export type ImportEvent = { job: number; source: string } & (
+  | { kind: "progress"; progress: number }
+  | { kind: "finished"; output: string; hash: string }
+  | { kind: "failed"; code: string; message: string }
+)
+
+export interface TextureImport {
+  output: string
+  /** SHA-256 of the written file, identical for identical imports. */
+  hash: string
+}
+
+export interface ImportTextureOptions {
+  /** Output file, by default next to the source. */
+  output?: string
+  settings?: TextureImportSettings
+  onProgress?: (progress: number) => void
+}
+
+/**
+ * Imports a PNG, JPEG, TGA or HDR image on the native import queue and
+ * resolves once the texture is written.
+ */
+export const importTexture = async (
+  source: string,
+  { output, settings, onProgress }: ImportTextureOptions = {}
+) => {
+  // Listen before queueing so no event of the job is missed
+  const pending: ImportEvent[] = []
+  let handle: ((event: ImportEvent) => void) | undefined
+  const unlisten = await listen<ImportEvent>(ASSET_IMPORT_EVENT, ({ payload }) => {
+    if (handle) handle(payload)
+    else pending.push(payload)
+  })
+
+  try {
+    const job = await invoke<number>("import_texture", { source, output, settings })
+    return await new Promise<TextureImport>((resolve, reject) => {
+      handle = (event) => {
+        if (event.job !== job) return
+        if (event.kind === "progress") {
+          onProgress?.(event.progress)
+        } else if (event.kind === "finished") {
+          resolve({ output: event.output, hash: event.hash })
+        } else {
+          reject({ code: event.code, message: event.message })
+        }
+      }
+      pending.forEach(handle)
+    })
+  } finally {
+    unlisten()
+  }
+}
+
+export type AssetKind =
+  | "image"
+  | "modelSource"
+  | "model"
+  | "mesh"
+  | "material"
+  | "animation"
+  | "texture"
+  | "scene"
+
+/** An asset of the open project, identified by the GUID in its `.meta` file. */
+export interface AssetRecord {
+  guid: string
+  /** Path relative to the project root. */
+  path: string
+  kind: AssetKind
+  /** Project relative paths this asset uses, whether they exist or not. */
+  references: string[]
+}
+
+export interface AssetDependencies {
+  dependencies: AssetRecord[]
+  missing: string[]
+}
+
+export interface DeleteImpact {
+  direct: AssetRecord[]
+  indirect: AssetRecord[]
+}
+
+/** Scans the project at `root` into the asset database. */
+export const openProject = (root: string) =>
+  invoke<AssetRecord[]>("open_project", { root })
+
+export const listAssets = () => invoke<AssetRecord[]>("list_assets")
+
+export const getAssetDependencies = (guid: string) =>
+  invoke<AssetDependencies>("get_asset_dependencies", { guid })
+
+export const findAssetReferences = (guid: string) =>
+  invoke<AssetRecord[]>("find_asset_references", { guid })
+
+/** Assets that would break if `guid` was deleted. */
+export const getAssetDeleteImpact = (guid: string) =>
+  invoke<DeleteImpact>("get_asset_delete_impact", { guid })
+
+export const ASSET_CHANGED_EVENT = "asset-changed"
+
+/** An asset of the open project changed on disk. */
+export interface AssetChanged {
+  guid: string
+  path: string
+  kind: AssetKind
+  change: "created" | "modified" | "removed"
+  /** Import job reimporting the asset, reported on `ASSET_IMPORT_EVENT`. */
+  reimport: number | null
+}
+
+/**
+ * A React hook calling `onChange` whenever an asset of the open project is
+ * created, modified or removed, e.g. to reload it in an open editor.
+ */
+export const useAssetChanges = (onChange: (change: AssetChanged) => void) => {
+  useEffect(() => {
+    const unlisten = listen<AssetChanged>(ASSET_CHANGED_EVENT, ({ payload }) => {
+      onChange(payload)
+    })
+    return () => {
+      void unlisten.then((stop) => stop())
+    }
+  }, [onChange])
+}
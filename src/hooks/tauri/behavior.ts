@@ -0,0 +1,203 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { useCallback, useEffect, useRef, useState } from "react"
+
+export type BehaviorNodeType =
+  | "sequence"
+  | "selector"
+  | "parallel"
+  | "inverter"
+  | "repeat"
+  | "timeout"
+  | "cooldown"
+  | "condition"
+  | "action"
+
+export type BlackboardValue = boolean | number | string
+
+/**
+ * A node of a saved behavior tree. Repeats take a `count` parameter, forever
+ * if unset, timeouts and cooldowns `seconds`, and parallel nodes a `policy`
+ * of `"requireAll"` or `"requireOne"`.
+ */
+export interface AssetNode {
+  type: BehaviorNodeType
+  name: string
+  /** Registered action or condition a leaf runs. */
+  handler?: string
+  params?: Record<string, BlackboardValue>
+  /** Ids of the children, in the order they are ticked. */
+  children?: string[]
+}
+
+export interface BehaviorAsset {
+  version: number
+  /** Id of the root node, by default the only node without a parent. */
+  root?: string
+  nodes: Record<string, AssetNode>
+  /** Values the blackboard of an agent starts with. */
+  blackboard?: Record<string, BlackboardValue>
+}
+
+/** Where the editor draws the nodes of a tree, saved apart from the tree. */
+export interface BehaviorLayout {
+  version: number
+  nodes: Record<string, { x: number; y: number }>
+}
+
+export interface BehaviorDocument {
+  asset: BehaviorAsset
+  layout: BehaviorLayout
+}
+
+export const BEHAVIOR_FORMAT_VERSION = 1
+
+export const BEHAVIOR_EXTENSION = "behavior"
+
+/** Why a tree could not be loaded, saved or run. */
+export interface BehaviorError {
+  /** Stable identifier of the kind of error, e.g. `invalidTree` or `unknownAction`. */
+  code: string
+  message: string
+}
+
+export interface BehaviorHandlers {
+  actions: string[]
+  conditions: string[]
+}
+
+export type AgentId = number
+
+export interface AgentInfo {
+  id: AgentId
+  name: string
+  /** Tree file the agent was spawned from. */
+  path: string
+}
+
+export type BehaviorStatus = "running" | "success" | "failure"
+
+export const BEHAVIOR_DEBUG_EVENT = "behavior-debug"
+
+/** What one tick of the debugged agent did. */
+export interface TickTrace {
+  agent: AgentId
+  tick: number
+  /** Time of the tree in seconds. */
+  time: number
+  status: BehaviorStatus
+  /** What the nodes ticked returned, by id. Running nodes form the active branch. */
+  nodes: Record<string, BehaviorStatus>
+  blackboard: { set: Record<string, BlackboardValue>; removed: string[] }
+}
+
+export const loadBehaviorTree = (path: string) =>
+  invoke<BehaviorDocument>("load_behavior_tree", { path })
+
+/** Writes a tree to `path` and its layout next to it. */
+export const saveBehaviorTree = (path: string, asset: BehaviorAsset, layout: BehaviorLayout) =>
+  invoke<void>("save_behavior_tree", { path, asset, layout })
+
+export const listBehaviorHandlers = () => invoke<BehaviorHandlers>("list_behavior_handlers")
+
+/** Spawns an agent ticking the tree saved at `path`. */
+export const spawnBehaviorAgent = (path: string) =>
+  invoke<AgentInfo>("spawn_behavior_agent", { path })
+
+export const despawnBehaviorAgent = (agent: AgentId) =>
+  invoke<void>("despawn_behavior_agent", { agent })
+
+export const listBehaviorAgents = () => invoke<AgentInfo[]>("list_behavior_agents")
+
+/** Streams the ticks of an agent on `BEHAVIOR_DEBUG_EVENT`, or stops streaming. */
+export const debugBehaviorAgent = (agent: AgentId | null) =>
+  invoke<void>("debug_behavior_agent", { agent })
+
+export interface BehaviorDebugState {
+  /** Last tick of the debugged agent, 0 before the first. */
+  tick: number
+  time: number
+  status: BehaviorStatus | null
+  /** What the nodes ticked on the last tick returned, by id. */
+  nodes: Record<string, BehaviorStatus>
+  /** The agent's blackboard, as of the last tick. */
+  blackboard: Record<string, BlackboardValue>
+}
+
+const IDLE_DEBUG: BehaviorDebugState = {
+  tick: 0,
+  time: 0,
+  status: null,
+  nodes: {},
+  blackboard: {},
+}
+
+const applyTrace = (state: BehaviorDebugState, trace: TickTrace): BehaviorDebugState => {
+  const blackboard = { ...state.blackboard, ...trace.blackboard.set }
+  for (const key of trace.blackboard.removed) delete blackboard[key]
+  return {
+    tick: trace.tick,
+    time: trace.time,
+    status: trace.status,
+    nodes: trace.nodes,
+    blackboard,
+  }
+}
+
+/**
+ * A React hook spawning agents from saved trees and following the one being
+ * debugged. Agents it spawned are despawned when it unmounts.
+ */
+export const useBehaviorDebugger = () => {
+  const [agents, setAgents] = useState<AgentInfo[]>([])
+  const [debugged, setDebugged] = useState<AgentId | null>(null)
+  const [state, setState] = useState<BehaviorDebugState>(IDLE_DEBUG)
+  const debuggedRef = useRef<AgentId | null>(null)
+  const spawned = useRef(new Set<AgentId>())
+
+  useEffect(() => {
+    const unlisten = listen<TickTrace>(BEHAVIOR_DEBUG_EVENT, ({ payload }) => {
+      if (payload.agent === debuggedRef.current) setState((current) => applyTrace(current, payload))
+    })
+    void listBehaviorAgents()
+      .then(setAgents)
+      .catch(() => undefined)
+
+    return () => {
+      if (debuggedRef.current !== null) void debugBehaviorAgent(null).catch(() => undefined)
+      for (const agent of spawned.current) void despawnBehaviorAgent(agent).catch(() => undefined)
+      void unlisten.then((stop) => stop())
+    }
+  }, [])
+
+  const debug = useCallback(async (agent: AgentId | null) => {
+    debuggedRef.current = agent
+    setDebugged(agent)
+    setState(IDLE_DEBUG)
+    await debugBehaviorAgent(agent)
+  }, [])
+
+  const spawn = useCallback(
+    async (path: string) => {
+      const agent = await spawnBehaviorAgent(path)
+      spawned.current.add(agent.id)
+      setAgents(await listBehaviorAgents())
+      await debug(agent.id)
+      return agent
+    },
+    [debug]
+  )
+
+  const despawn = useCallback(async (agent: AgentId) => {
+    spawned.current.delete(agent)
+    if (debuggedRef.current === agent) {
+      debuggedRef.current = null
+      setDebugged(null)
+      setState(IDLE_DEBUG)
+    }
+    await despawnBehaviorAgent(agent)
+    setAgents(await listBehaviorAgents())
+  }, [])
+
+  return { agents, debugged, ...state, spawn, despawn, debug }
+}
@@ -0,0 +1,416 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { useCallback, useEffect, useRef, useState } from "react"
+import type { Edge, Node } from "reactflow"
+
+/** A problem found compiling a Blueprint graph, on the nodes it is about. */
+export interface BlueprintDiagnostic {
+  /** Stable identifier of the kind of problem, e.g. `typeMismatch` or `cycle`. */
+  code: string
+  severity: "error" | "warning"
+  message: string
+  /** Nodes the problem is on, the one to show it on first. */
+  nodeIds: string[]
+  pin: string | null
+  field: string | null
+}
+
+/** Where the code of a node is in the generated source. */
+export interface NodeSpan {
+  node: string
+  /** First line of the node's code, from 1. */
+  firstLine: number
+  /** Last line of the node's code, included. */
+  lastLine: number
+  /** Variables holding the values of the node's outputs, by pin. */
+  outputs: Record<string, string>
+}
+
+export interface BlueprintCompilation {
+  /** The generated Rust, or `null` if the graph has errors. */
+  code: string | null
+  diagnostics: BlueprintDiagnostic[]
+  /** Code of every node that compiled to any, in the order it runs. */
+  spans: NodeSpan[]
+}
+
+/** The parts of the React Flow graph the backend reads. */
+const toGraph = (nodes: Node[], edges: Edge[]) => ({
+  nodes: nodes.map(({ id, data }) => ({ id, data })),
+  edges: edges.map(({ id, source, sourceHandle, target, targetHandle }) => ({
+    id,
+    source,
+    sourceHandle,
+    target,
+    targetHandle,
+  })),
+})
+
+/** Compiles a graph to Rust in the backend, checking and inferring pin types. */
+export const compileBlueprint = (nodes: Node[], edges: Edge[]) =>
+  invoke<BlueprintCompilation>("compile_blueprint", { graph: toGraph(nodes, edges) })
+
+/** A problem rustc found in the generated code. */
+export interface CargoDiagnostic {
+  severity: "error" | "warning"
+  message: string
+  /** rustc's code of the problem, like `E0308`. */
+  code: string | null
+  /** Node whose code the problem is in, `null` if it is in the code around the nodes. */
+  node: string | null
+  /** Line of the generated source, from 1. */
+  line: number | null
+  column: number | null
+  /** What rustc says at the problem, like "expected `f64`, found integer". */
+  label: string | null
+  /** The problem as rustc prints it. */
+  rendered: string
+}
+
+export interface CargoCheck {
+  /** Whether the code compiles. */
+  success: boolean
+  /** The code that was checked. */
+  code: string
+  diagnostics: CargoDiagnostic[]
+}
+
+/** Why the code of a graph could not be checked, e.g. `compile` or `cargoNotFound`. */
+export interface CheckError {
+  code: string
+  message: string
+}
+
+/** Compiles a graph and checks the code with `cargo check` in a scratch crate. */
+export const checkBlueprint = (nodes: Node[], edges: Edge[]) =>
+  invoke<CargoCheck>("check_blueprint", { graph: toGraph(nodes, edges) })
+
+/** A React hook checking the code of a graph with cargo on demand. */
+export const useBlueprintCheck = () => {
+  const [result, setResult] = useState<CargoCheck | null>(null)
+  const [error, setError] = useState<CheckError | null>(null)
+  const [checking, setChecking] = useState(false)
+  // Only the latest check counts
+  const latest = useRef(0)
+
+  const check = useCallback((nodes: Node[], edges: Edge[]) => {
+    const id = ++latest.current
+    setChecking(true)
+    checkBlueprint(nodes, edges)
+      .then((checked) => {
+        if (id !== latest.current) return
+        setResult(checked)
+        setError(null)
+      })
+      .catch((err: CheckError) => {
+        if (id !== latest.current) return
+        setResult(null)
+        setError(err)
+      })
+      .finally(() => {
+        if (id === latest.current) setChecking(false)
+      })
+  }, [])
+
+  return { result, error, checking, check }
+}
+
+/** The generated code, or the errors keeping the graph from compiling as comments. */
+export const compilationText = ({ code, diagnostics }: BlueprintCompilation) => {
+  if (code !== null) return code
+  const errors = diagnostics
+    .filter((diagnostic) => diagnostic.severity === "error")
+    .map((diagnostic) => `// - ${diagnostic.message}`)
+  return ["// Fix these errors to generate code:", ...errors, ""].join("\n")
+}
+
+/**
+ * A React hook compiling the graph whenever it changes, once it has not
+ * changed for `delay` milliseconds.
+ */
+export const useBlueprintCompilation = (nodes: Node[], edges: Edge[], delay = 200) => {
+  const [compilation, setCompilation] = useState<BlueprintCompilation | null>(null)
+
+  useEffect(() => {
+    let ignore = false
+    const timeout = setTimeout(() => {
+      compileBlueprint(nodes, edges)
+        .then((result) => {
+          if (!ignore) setCompilation(result)
+        })
+        .catch((err: unknown) => {
+          console.error("Failed to compile the Blueprint", err)
+        })
+    }, delay)
+
+    return () => {
+      ignore = true
+      clearTimeout(timeout)
+    }
+  }, [nodes, edges, delay])
+
+  return compilation
+}
+
+export const NODE_LIBRARY_EVENT = "node-library"
+
+export interface NodePinDefinition {
+  name: string
+  type: string
+  description?: string
+  optional?: boolean
+}
+
+export interface NodeFieldDefinition {
+  type: string
+  label: string
+  description?: string
+  options?: string[]
+  default?: unknown
+  required?: boolean
+  validation?: {
+    pattern?: string
+    min?: number
+    max?: number
+    minLength?: number
+    maxLength?: number
+  }
+}
+
+export type NodeSource = { kind: "builtin" } | { kind: "user" } | { kind: "plugin"; name: string }
+
+/** A node definition of the library, and the file it was loaded from. */
+export interface LibraryNode {
+  name: string
+  category: string
+  description: string
+  fields: Record<string, NodeFieldDefinition>
+  pins: { inputs: NodePinDefinition[]; outputs: NodePinDefinition[] }
+  template: string
+  source: NodeSource
+  path: string
+  /** Line the definition starts on, from 1. */
+  line: number
+}
+
+/** A definition that could not be loaded, or a file that could not be read. */
+export interface LibraryProblem {
+  severity: "error" | "warning"
+  path: string
+  line: number | null
+  column: number | null
+  message: string
+}
+
+export interface NodeLibrary {
+  /** Definitions by name. */
+  nodes: Record<string, LibraryNode>
+  /** Names of the definitions in each category. */
+  categories: Record<string, string[]>
+  problems: LibraryProblem[]
+}
+
+/**
+ * The node definitions of the editor, the user's config directory and the
+ * open project's plugins, as loaded by the backend.
+ */
+export const getNodeLibrary = () => invoke<NodeLibrary>("get_node_library")
+
+/** Loads the node library from disk again. */
+export const reloadNodeLibrary = () => invoke<NodeLibrary>("reload_node_library")
+
+/** A React hook with the node library, updated whenever its files change. */
+export const useNodeLibrary = () => {
+  const [library, setLibrary] = useState<NodeLibrary | null>(null)
+
+  useEffect(() => {
+    let ignore = false
+    let changed = false
+    const unlisten = listen<NodeLibrary>(NODE_LIBRARY_EVENT, ({ payload }) => {
+      changed = true
+      if (!ignore) setLibrary(payload)
+    })
+    void getNodeLibrary()
+      .then((result) => {
+        // A reload that raced the request is newer
+        if (!ignore && !changed) setLibrary(result)
+      })
+      .catch((err: unknown) => {
+        console.error("Failed to load the node library", err)
+      })
+
+    return () => {
+      ignore = true
+      void unlisten.then((stop) => stop())
+    }
+  }, [])
+
+  return library
+}
+
+export type RunId = number
+
+export const BLUEPRINT_RUN_EVENT = "blueprint-run"
+
+export interface RunOptions {
+  /** Nodes to pause before. */
+  breakpoints?: string[]
+  /** Pause before the first node, to step through the graph from there. */
+  paused?: boolean
+}
+
+export type PauseReason = "breakpoint" | "step" | "request"
+
+export type RunEvent = { run: RunId } & (
+  | { kind: "values"; node: string; values: Record<string, unknown> }
+  | { kind: "paused"; node: string; reason: PauseReason }
+  | { kind: "resumed" }
+  | { kind: "output"; node: string; text: string }
+  | { kind: "finished" }
+  | { kind: "failed"; node: string; message: string }
+  | { kind: "stopped" }
+)
+
+/** Why a run could not start or be controlled. */
+export interface RunError {
+  /** Stable identifier of the kind of error, e.g. `compile` or `unsupported`. */
+  code: string
+  message: string
+  /** Node the error is on, if any. */
+  node: string | null
+}
+
+/** Interprets a graph in the backend, reporting its progress on `BLUEPRINT_RUN_EVENT`. */
+export const startBlueprintRun = (nodes: Node[], edges: Edge[], options: RunOptions = {}) =>
+  invoke<RunId>("start_blueprint_run", { graph: toGraph(nodes, edges), options })
+
+/** Resumes a paused run until the next breakpoint. */
+export const continueBlueprintRun = (run: RunId) => invoke<void>("continue_blueprint_run", { run })
+
+/** Runs the node a run is paused before, and pauses before the next one. */
+export const stepBlueprintRun = (run: RunId) => invoke<void>("step_blueprint_run", { run })
+
+export const pauseBlueprintRun = (run: RunId) => invoke<void>("pause_blueprint_run", { run })
+
+export const stopBlueprintRun = (run: RunId) => invoke<void>("stop_blueprint_run", { run })
+
+export const setBlueprintBreakpoints = (run: RunId, breakpoints: string[]) =>
+  invoke<void>("set_blueprint_breakpoints", { run, breakpoints })
+
+export type RunStatus = "idle" | "starting" | "running" | "paused" | "finished" | "failed" | "stopped"
+
+export interface BlueprintRunState {
+  status: RunStatus
+  /** Node the run is paused before. */
+  pausedNode: string | null
+  /** Why the run failed, and the node it failed on if any. */
+  failure: { node: string | null; message: string } | null
+  /** Values of the outputs of every node that ran, by node and pin. */
+  values: Record<string, Record<string, unknown>>
+  /** Text the nodes printed. */
+  output: string
+}
+
+const IDLE_RUN: BlueprintRunState = {
+  status: "idle",
+  pausedNode: null,
+  failure: null,
+  values: {},
+  output: "",
+}
+
+/** Most characters of output a run keeps. */
+const MAX_OUTPUT = 100_000
+
+const applyRunEvent = (state: BlueprintRunState, event: RunEvent): BlueprintRunState => {
+  switch (event.kind) {
+    case "values":
+      return { ...state, values: { ...state.values, [event.node]: event.values } }
+    case "paused":
+      return { ...state, status: "paused", pausedNode: event.node }
+    case "resumed":
+      return { ...state, status: "running", pausedNode: null }
+    case "output":
+      return { ...state, output: (state.output + event.text).slice(-MAX_OUTPUT) }
+    case "failed":
+      return {
+        ...state,
+        status: "failed",
+        pausedNode: null,
+        failure: { node: event.node, message: event.message },
+      }
+    default:
+      return { ...state, status: event.kind, pausedNode: null }
+  }
+}
+
+/**
+ * A React hook running graphs in the editor, one at a time. `breakpoints`
+ * apply to the current run as they change.
+ */
+export const useBlueprintRun = (breakpoints: string[]) => {
+  const [state, setState] = useState<BlueprintRunState>(IDLE_RUN)
+  const run = useRef<RunId | null>(null)
+  // Events may arrive before `start_blueprint_run` returns the run id
+  const pending = useRef<RunEvent[] | null>(null)
+
+  useEffect(() => {
+    const unlisten = listen<RunEvent>(BLUEPRINT_RUN_EVENT, ({ payload }) => {
+      if (pending.current) pending.current.push(payload)
+      else if (payload.run === run.current) setState((current) => applyRunEvent(current, payload))
+    })
+
+    return () => {
+      // Fails if the run already ended
+      if (run.current !== null) void stopBlueprintRun(run.current).catch(() => undefined)
+      void unlisten.then((stop) => stop())
+    }
+  }, [])
+
+  const breakpointsRef = useRef(breakpoints)
+  useEffect(() => {
+    breakpointsRef.current = breakpoints
+    if (run.current !== null) {
+      void setBlueprintBreakpoints(run.current, breakpoints).catch(() => undefined)
+    }
+  }, [breakpoints])
+
+  const start = useCallback((nodes: Node[], edges: Edge[], paused = false) => {
+    if (run.current !== null) void stopBlueprintRun(run.current).catch(() => undefined)
+    run.current = null
+    pending.current = []
+    setState({ ...IDLE_RUN, status: "starting" })
+
+    startBlueprintRun(nodes, edges, { breakpoints: breakpointsRef.current, paused })
+      .then((id) => {
+        const events = pending.current ?? []
+        pending.current = null
+        run.current = id
+        setState((current) =>
+          events
+            .filter((event) => event.run === id)
+            .reduce(applyRunEvent, { ...current, status: "running" })
+        )
+      })
+      .catch((err: RunError) => {
+        pending.current = null
+        setState({ ...IDLE_RUN, status: "failed", failure: err })
+      })
+  }, [])
+
+  const control = useCallback(
+    (command: (run: RunId) => Promise<void>) => () => {
+      if (run.current !== null) void command(run.current).catch(() => undefined)
+    },
+    []
+  )
+
+  return {
+    ...state,
+    start,
+    resume: control(continueBlueprintRun),
+    step: control(stepBlueprintRun),
+    pause: control(pauseBlueprintRun),
+    stop: control(stopBlueprintRun),
+  }
+}
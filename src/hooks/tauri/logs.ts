@@ -0,0 +1,100 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { useCallback, useEffect, useState } from "react"
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace"
+export type LogFilterLevel = "off" | LogLevel
+
+/** A record logged by the Rust backend. */
+export interface LogRecord {
+  /** Increases with every record. */
+  id: number
+  level: LogLevel
+  /** Module the record comes from, e.g. `pulsar_engine::viewport::renderer`. */
+  target: string
+  message: string
+  file: string | null
+  line: number | null
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number
+}
+
+export interface LogQuery {
+  /** Only records with a greater id. */
+  after?: number
+  /** Least severe level to include. */
+  level?: LogLevel
+  /** Only records whose target starts with this. */
+  target?: string
+  /** Only the most recent records, at most this many. */
+  limit?: number
+}
+
+/** Levels by module path; the most specific module prefix of a target wins. */
+export interface LogFilters {
+  default: LogFilterLevel
+  modules: Record<string, LogFilterLevel>
+}
+
+export const LOG_EVENT = "log"
+
+/** Recent records of the backend's log history, oldest first. */
+export const getLogs = (query: LogQuery = {}) => invoke<LogRecord[]>("get_logs", { query })
+
+export const clearLogs = () => invoke<void>("clear_logs")
+
+export const getLogFilters = () => invoke<LogFilters>("get_log_filters")
+
+/** Sets the level `module` logs at, or the default level without one. */
+export const setLogFilter = (level: LogFilterLevel, module?: string) =>
+  invoke<LogFilters>("set_log_filter", { module, level })
+
+export const removeLogFilter = (module: string) =>
+  invoke<LogFilters>("remove_log_filter", { module })
+
+/**
+ * A React hook following the backend log: the history first, then records
+ * as they are logged. Keeps the most recent `limit` records.
+ */
+export const useLogs = (limit = 1000) => {
+  const [records, setRecords] = useState<LogRecord[]>([])
+
+  useEffect(() => {
+    let ignore = false
+    // Records logged while the history loads are merged by id
+    const append = (incoming: LogRecord[]) => {
+      setRecords((prev) => {
+        const last = prev.length > 0 ? prev[prev.length - 1].id : 0
+        const next = [...prev, ...incoming.filter((record) => record.id > last)]
+        return next.slice(-limit)
+      })
+    }
+
+    const unlisten = listen<LogRecord>(LOG_EVENT, ({ payload }) => {
+      if (!ignore) append([payload])
+    })
+    void getLogs({ limit })
+      .then((history) => {
+        if (ignore) return
+        setRecords((prev) => {
+          const first = prev.length > 0 ? prev[0].id : Infinity
+          return [...history.filter((record) => record.id < first), ...prev].slice(-limit)
+        })
+      })
+      .catch((err: unknown) => {
+        console.error("Failed to load the log history", err)
+      })
+
+    return () => {
+      ignore = true
+      void unlisten.then((stop) => stop())
+    }
+  }, [limit])
+
+  const clear = useCallback(async () => {
+    await clearLogs()
+    setRecords([])
+  }, [])
+
+  return { records, clear }
+}
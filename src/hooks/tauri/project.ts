@@ -0,0 +1,55 @@
+import { invoke } from "@tauri-apps/api/tauri"
+
+/**
+ * Native file operations on the open project. Paths are relative to the
+ * project root with `/` separators; paths leading out of it are rejected
+ * with an `outsideProject` error.
+ */
+
+export interface FileEntry {
+  name: string
+  path: string
+  kind: "file" | "directory"
+  /** Size in bytes, 0 for directories. */
+  size: number
+  /** Last modification in milliseconds since the Unix epoch. */
+  modified: number | null
+  hasChildren: boolean
+}
+
+/** Entries of a directory, the project root by default, directories first. */
+export const listProjectDir = (path?: string) =>
+  invoke<FileEntry[]>("list_project_dir", { path })
+
+export const readProjectFile = (path: string) =>
+  invoke<string>("read_project_file", { path })
+
+/** Reads a binary file, base64 encoded. */
+export const readProjectFileBinary = (path: string) =>
+  invoke<string>("read_project_file_binary", { path })
+
+/** Creates or atomically replaces a text file. */
+export const writeProjectFile = (path: string, contents: string) =>
+  invoke<FileEntry>("write_project_file", { path, contents })
+
+/** Creates or atomically replaces a binary file from base64 `data`. */
+export const writeProjectFileBinary = (path: string, data: string) =>
+  invoke<FileEntry>("write_project_file_binary", { path, data })
+
+export const createProjectFile = (path: string) =>
+  invoke<FileEntry>("create_project_file", { path })
+
+export const createProjectDir = (path: string) =>
+  invoke<FileEntry>("create_project_dir", { path })
+
+/** Renames an entry within its directory, along with its `.meta` sidecar. */
+export const renameProjectEntry = (path: string, newName: string) =>
+  invoke<FileEntry>("rename_project_entry", { path, newName })
+
+/** Moves an entry into the directory at `destination`. */
+export const moveProjectEntry = (path: string, destination: string) =>
+  invoke<FileEntry>("move_project_entry", { path, destination })
+
+/** Moves an entry to the system trash, or deletes it if `permanent`. */
+export const deleteProjectEntry = (path: string, permanent = false) =>
+  invoke<void>("delete_project_entry", { path, permanent })
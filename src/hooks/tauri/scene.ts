@@ -0,0 +1,72 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { useCallback, useEffect, useState } from "react"
+
+export type EntityId = number
+
+export interface Vector3 {
+  x: number
+  y: number
+  z: number
+}
+
+export interface Transform {
+  position: Vector3
+  /** XYZ Euler angles in degrees. */
+  rotation: Vector3
+  scale: Vector3
+}
+
+export type Component =
+  | { type: "camera"; fovY: number; near: number; far: number }
+  | {
+      type: "light"
+      kind: "directional" | "point" | "spot"
+      color: [number, number, number]
+      intensity: number
+    }
+  | { type: "mesh"; mesh: string }
+
+export interface Entity {
+  id: EntityId
+  /** Persistent identifier written to scene files. */
+  guid: string
+  name: string
+  parent: EntityId | null
+  children: EntityId[]
+  visible: boolean
+  transform: Transform
+  components: Component[]
+}
+
+export const SCENE_CHANGED_EVENT = "scene-changed"
+
+/**
+ * A React hook returning the entities of the native scene in hierarchy order,
+ * refreshed whenever any panel changes the scene.
+ */
+export const useScene = () => {
+  const [entities, setEntities] = useState<Entity[]>([])
+
+  const refresh = useCallback(async () => {
+    setEntities(await invoke<Entity[]>("query_scene"))
+  }, [])
+
+  useEffect(() => {
+    let ignore = false
+    const unlisten = listen<number>(SCENE_CHANGED_EVENT, () => {
+      if (!ignore) void refresh()
+    })
+
+    void refresh().catch((err: unknown) => {
+      console.error("Failed to query the scene", err)
+    })
+
+    return () => {
+      ignore = true
+      void unlisten.then((stop) => stop())
+    }
+  }, [refresh])
+
+  return { entities, refresh }
+}
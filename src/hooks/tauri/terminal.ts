@@ -0,0 +1,221 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { useCallback, useEffect, useState } from "react"
+
+export type SessionId = number
+
+export interface TerminalOptions {
+  /** Working directory, by default the open project or the home directory. */
+  cwd?: string
+  cols?: number
+  rows?: number
+}
+
+export const TERMINAL_EVENT = "terminal"
+
+export type TerminalEvent = { session: SessionId } & (
+  | { kind: "output"; data: string }
+  | { kind: "exited"; code: number | null }
+)
+
+/** Starts the user's shell in a pseudo-terminal. */
+export const openTerminal = (options: TerminalOptions = {}) =>
+  invoke<SessionId>("open_terminal", { options })
+
+export const writeTerminal = (session: SessionId, data: string) =>
+  invoke<void>("write_terminal", { session, data })
+
+export const resizeTerminal = (session: SessionId, cols: number, rows: number) =>
+  invoke<void>("resize_terminal", { session, cols, rows })
+
+/** Kills the shell of a session. */
+export const closeTerminal = (session: SessionId) =>
+  invoke<void>("close_terminal", { session })
+
+export const listTerminals = () => invoke<SessionId[]>("list_terminals")
+
+/** Most lines a `TerminalBuffer` keeps. */
+const MAX_LINES = 5000
+
+// CSI with its parameters and final byte, terminated OSC, charset selection
+// or a two byte sequence
+const ESCAPE =
+  // eslint-disable-next-line no-control-regex
+  /^\x1b(?:\[([0-9;?]*)[ -/]*([@-~])|\][^\x07\x1b]*(?:\x07|\x1b\\)|[()][\s\S]|[^[\]()])/
+
+/**
+ * The text of a terminal session as lines. Interprets the cursor movements
+ * shells use for line editing; colors and other escape sequences are
+ * dropped.
+ */
+export class TerminalBuffer {
+  private rows: string[] = [""]
+  private column = 0
+  /** Escape sequence split across writes. */
+  private escape = ""
+
+  write(data: string) {
+    const text = this.escape + data
+    this.escape = ""
+
+    let i = 0
+    while (i < text.length) {
+      const char = text[i]
+      if (char === "\x1b") {
+        const match = ESCAPE.exec(text.slice(i))
+        if (!match) {
+          // Wait for the rest, unless it never ends
+          if (text.length - i < 256) this.escape = text.slice(i)
+          return
+        }
+        if (match[2]) this.control(match[1], match[2])
+        i += match[0].length
+        continue
+      }
+
+      if (char === "\n") {
+        this.rows.push("")
+        if (this.rows.length > MAX_LINES) this.rows.shift()
+        this.column = 0
+      } else if (char === "\r") {
+        this.column = 0
+      } else if (char === "\b") {
+        this.column = Math.max(0, this.column - 1)
+      } else if (char === "\t") {
+        this.put(" ".repeat(8 - (this.column % 8)))
+      } else if (char >= " ") {
+        this.put(char)
+      }
+      i += 1
+    }
+  }
+
+  lines() {
+    return [...this.rows]
+  }
+
+  private put(text: string) {
+    const last = this.rows.length - 1
+    const row = this.rows[last].padEnd(this.column)
+    this.rows[last] =
+      row.slice(0, this.column) + text + row.slice(this.column + text.length)
+    this.column += text.length
+  }
+
+  private control(params: string, command: string) {
+    const count = parseInt(params, 10) || 1
+    const last = this.rows.length - 1
+    if (command === "K") {
+      // Erase to the end of the line, or all of it
+      this.rows[last] = params === "2" ? "" : this.rows[last].slice(0, this.column)
+    } else if (command === "C") {
+      this.column += count
+    } else if (command === "D") {
+      this.column = Math.max(0, this.column - count)
+    } else if (command === "G") {
+      this.column = count - 1
+    } else if (command === "J" && (params === "2" || params === "3")) {
+      this.rows = [""]
+      this.column = 0
+    }
+  }
+}
+
+const KEYS: Record<string, string> = {
+  Enter: "\r",
+  Backspace: "\x7f",
+  Tab: "\t",
+  Escape: "\x1b",
+  ArrowUp: "\x1b[A",
+  ArrowDown: "\x1b[B",
+  ArrowRight: "\x1b[C",
+  ArrowLeft: "\x1b[D",
+  Home: "\x1b[H",
+  End: "\x1b[F",
+  Delete: "\x1b[3~",
+}
+
+type Key = Pick<KeyboardEvent, "key" | "ctrlKey" | "altKey" | "metaKey">
+
+/** Input a key press sends to the shell, `null` for keys it ignores. */
+export const keyToInput = ({ key, ctrlKey, altKey, metaKey }: Key) => {
+  if (metaKey) return null
+  if (ctrlKey) {
+    // Ctrl+C is 0x03, Ctrl+D 0x04 and so on
+    const code = key.length === 1 ? key.toUpperCase().charCodeAt(0) : 0
+    return code >= 64 && code <= 95 ? String.fromCharCode(code - 64) : null
+  }
+  if (KEYS[key]) return KEYS[key]
+  if (key.length === 1) return altKey ? `\x1b${key}` : key
+  return null
+}
+
+/**
+ * A React hook running a terminal session while the component is mounted.
+ * `exitCode` is `undefined` while the shell runs.
+ */
+export const useTerminal = (cwd?: string) => {
+  const [session, setSession] = useState<SessionId | null>(null)
+  const [lines, setLines] = useState<string[]>([""])
+  const [exitCode, setExitCode] = useState<number | null | undefined>(undefined)
+
+  useEffect(() => {
+    let ignore = false
+    let id: SessionId | null = null
+    const buffer = new TerminalBuffer()
+
+    // Events may arrive before `open_terminal` returns the session id
+    const pending: TerminalEvent[] = []
+    const handle = (event: TerminalEvent) => {
+      if (event.session !== id) return
+      if (event.kind === "output") {
+        buffer.write(event.data)
+        setLines(buffer.lines())
+      } else {
+        setExitCode(event.code)
+      }
+    }
+    const unlisten = listen<TerminalEvent>(TERMINAL_EVENT, ({ payload }) => {
+      if (id === null) pending.push(payload)
+      else handle(payload)
+    })
+
+    void unlisten
+      .then(() => openTerminal({ cwd }))
+      .then((opened) => {
+        if (ignore) {
+          void closeTerminal(opened)
+          return
+        }
+        id = opened
+        setSession(opened)
+        pending.forEach(handle)
+      })
+      .catch((err: unknown) => {
+        console.error("Failed to open a terminal", err)
+      })
+
+    return () => {
+      ignore = true
+      // Fails if the shell already exited
+      if (id !== null) void closeTerminal(id).catch(() => undefined)
+      void unlisten.then((stop) => stop())
+    }
+  }, [cwd])
+
+  const write = useCallback(
+    (data: string) => {
+      if (session !== null) void writeTerminal(session, data)
+    },
+    [session]
+  )
+
+  const resize = useCallback(
+    (cols: number, rows: number) => {
+      if (session !== null) void resizeTerminal(session, cols, rows)
+    },
+    [session]
+  )
+
+  return { session, lines, exitCode, write, resize }
+}
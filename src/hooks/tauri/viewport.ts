@@ -0,0 +1,209 @@
+import { listen } from "@tauri-apps/api/event"
+import { invoke } from "@tauri-apps/api/tauri"
+import { RefObject, useCallback, useEffect, useRef, useState } from "react"
+
+/** Identifies a native viewport, one per editor view, e.g. `"level"`. */
+export type ViewportId = string
+
+/** Rect of a viewport in CSS pixels relative to the window. */
+export interface ViewportConfig {
+  x: number
+  y: number
+  width: number
+  height: number
+  devicePixelRatio: number
+}
+
+export type FramePacing =
+  | { mode: "uncapped" }
+  | { mode: "fixed"; fps: number }
+  | { mode: "vsync" }
+  | { mode: "onDemand" }
+
+/** A failed viewport command or render, as serialized by `ViewportError`. */
+export interface ViewportError {
+  code: string
+  message: string
+}
+
+export const VIEWPORT_EVENT = "viewport-event"
+
+/** Render state changes the native viewport reports, see `ViewportEvent`. */
+export type ViewportEvent = { viewportId: ViewportId } & (
+  | { kind: "swapchainRecreated"; reason: string }
+  | { kind: "deviceLost" }
+  | { kind: "deviceRecovered"; adapter: string }
+  | { kind: "recoveryFailed"; code: string; message: string }
+  | { kind: "deviceUnrecoverable"; code: string; message: string }
+)
+
+/** Creates the native renderer of a viewport and starts its render loop. */
+export const initializeViewport = (viewportId: ViewportId) =>
+  invoke<void>("initialize_viewport", { viewportId })
+
+/** Reports the rect the viewport occupies, which its render target follows. */
+export const updateNativeViewport = (viewportId: ViewportId, config: ViewportConfig) =>
+  invoke<void>("update_native_viewport", { viewportId, config })
+
+/** Stops the render loop and releases the viewport's GPU resources. */
+export const shutdownViewport = (viewportId: ViewportId) =>
+  invoke<void>("shutdown_viewport", { viewportId })
+
+export const setViewportFramePacing = (viewportId: ViewportId, pacing: FramePacing) =>
+  invoke<void>("set_viewport_frame_pacing", { viewportId, pacing })
+
+/** Restores the default editor camera. */
+export const resetViewportCamera = (viewportId: ViewportId) =>
+  invoke<void>("reset_viewport_camera", { viewportId })
+
+/** Circles the camera around its target; angles are in radians. */
+export const orbitViewportCamera = (viewportId: ViewportId, deltaYaw: number, deltaPitch: number) =>
+  invoke<void>("orbit_viewport_camera", { viewportId, deltaYaw, deltaPitch })
+
+/** Moves the camera target in the view plane, in fractions of the orbit distance. */
+export const panViewportCamera = (viewportId: ViewportId, dx: number, dy: number) =>
+  invoke<void>("pan_viewport_camera", { viewportId, dx, dy })
+
+/** Scales the orbit distance; factors below 1 move towards the target. */
+export const zoomViewportCamera = (viewportId: ViewportId, factor: number) =>
+  invoke<void>("zoom_viewport_camera", { viewportId, factor })
+
+// Initialize and shutdown calls per viewport, chained so a remount cannot
+// shut down the viewport it just initialized
+const lifecycles = new Map<ViewportId, Promise<unknown>>()
+
+const queueLifecycle = (viewportId: ViewportId, call: () => Promise<unknown>) => {
+  const previous = lifecycles.get(viewportId) ?? Promise.resolve()
+  const next = previous.catch(() => undefined).then(call)
+  lifecycles.set(viewportId, next)
+  return next
+}
+
+/** The rect `element` occupies in the window. */
+const viewportConfig = (element: Element): ViewportConfig => {
+  const rect = element.getBoundingClientRect()
+  return {
+    x: Math.round(rect.left),
+    y: Math.round(rect.top),
+    width: Math.round(rect.width),
+    height: Math.round(rect.height),
+    devicePixelRatio: window.devicePixelRatio || 1,
+  }
+}
+
+export type NativeViewportStatus = "starting" | "running" | "recovering" | "failed"
+
+export interface NativeViewport {
+  status: NativeViewportStatus
+  /** Why the viewport failed to start or stopped rendering. */
+  error: ViewportError | null
+  /** Shuts the viewport down and initializes it again. */
+  reinitialize: () => void
+}
+
+/** Delay before a viewport that stopped rendering is reinitialized. */
+const REINITIALIZE_DELAY_MS = 2000
+/** Automatic reinitializations before waiting for `reinitialize`. */
+const MAX_AUTO_REINITIALIZE = 3
+
+/**
+ * A React hook running the native viewport `viewportId` over the element in
+ * `ref` while `enabled`: initializes it on mount, reports the element's rect
+ * whenever it moves or resizes and shuts the viewport down on unmount.
+ *
+ * Follows the viewport's `VIEWPORT_EVENT`s. A viewport that failed to start
+ * or to recover from a render error is reinitialized after a delay, a few
+ * times at most.
+ */
+export const useNativeViewport = (
+  viewportId: ViewportId,
+  ref: RefObject<Element>,
+  enabled = true,
+): NativeViewport => {
+  const [status, setStatus] = useState<NativeViewportStatus>("starting")
+  const [error, setError] = useState<ViewportError | null>(null)
+  const [generation, setGeneration] = useState(0)
+  const autoReinitializeRef = useRef(0)
+
+  const reinitialize = useCallback(() => {
+    autoReinitializeRef.current = 0
+    setGeneration((generation) => generation + 1)
+  }, [])
+
+  useEffect(() => {
+    const element = ref.current
+    if (!enabled || !element) return
+
+    // Rects are only sent between initialize and shutdown
+    let active = true
+    let initialized = false
+    let timer: ReturnType<typeof setTimeout> | undefined
+
+    const fail = (reason: ViewportError) => {
+      setStatus("failed")
+      setError(reason)
+      if (autoReinitializeRef.current >= MAX_AUTO_REINITIALIZE) return
+      autoReinitializeRef.current += 1
+      timer = setTimeout(() => {
+        if (active) setGeneration((generation) => generation + 1)
+      }, REINITIALIZE_DELAY_MS)
+    }
+
+    const sendRect = () => {
+      if (!active || !initialized) return
+      updateNativeViewport(viewportId, viewportConfig(element)).catch((error) => {
+        console.error(`Failed to update viewport '${viewportId}':`, error)
+      })
+    }
+
+    const unlisten = listen<ViewportEvent>(VIEWPORT_EVENT, ({ payload }) => {
+      if (!active || payload.viewportId !== viewportId) return
+      switch (payload.kind) {
+        case "deviceLost":
+          setStatus("recovering")
+          break
+        case "deviceRecovered":
+        case "swapchainRecreated":
+          setStatus("running")
+          setError(null)
+          break
+        case "recoveryFailed":
+        case "deviceUnrecoverable":
+          fail({ code: payload.code, message: payload.message })
+          break
+      }
+    })
+
+    setStatus("starting")
+    queueLifecycle(viewportId, () => initializeViewport(viewportId))
+      .then(() => {
+        if (!active) return
+        initialized = true
+        setStatus("running")
+        setError(null)
+        sendRect()
+      })
+      .catch((error: ViewportError) => {
+        console.error(`Failed to initialize viewport '${viewportId}':`, error)
+        if (active) fail(error)
+      })
+
+    // Element resizes, and window resizes that move it or change the pixel ratio
+    const observer = new ResizeObserver(sendRect)
+    observer.observe(element)
+    window.addEventListener("resize", sendRect)
+
+    return () => {
+      active = false
+      clearTimeout(timer)
+      observer.disconnect()
+      window.removeEventListener("resize", sendRect)
+      void unlisten.then((stop) => stop())
+      queueLifecycle(viewportId, () => shutdownViewport(viewportId)).catch((error) => {
+        console.error(`Failed to shut down viewport '${viewportId}':`, error)
+      })
+    }
+  }, [viewportId, ref, enabled, generation])
+
+  return { status, error, reinitialize }
+}